@@ -0,0 +1,225 @@
+//! Render pipeline benchmarks: diff rendering of large frames, ANSI width
+//! computation, markdown rendering of a long document, and surface
+//! compositing.
+//!
+//! Run with `cargo bench --bench render_pipeline`. Byte-output budgets for the
+//! same kinds of scenarios are pinned by `tests/render_output_budget.rs`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use tape_tui::core::component::Component;
+use tape_tui::core::terminal_image::is_image_line;
+use tape_tui::render::renderer::DiffRenderer;
+use tape_tui::render::surface::{composite_surfaces, RenderedSurface};
+use tape_tui::render::Frame;
+use tape_tui::{visible_width, Markdown, MarkdownTheme};
+
+const WIDTH: usize = 120;
+const HEIGHT: usize = 40;
+const FRAME_LINES: usize = 2_000;
+const WARMUP_ITERS: usize = 20;
+const MEASURE_ITERS: usize = 200;
+
+#[derive(Debug, Clone, Copy)]
+struct Stats {
+    mean_ns: f64,
+    p50_ns: u128,
+    p95_ns: u128,
+}
+
+fn compute_stats(samples_ns: &[u128]) -> Stats {
+    let mut sorted = samples_ns.to_vec();
+    sorted.sort_unstable();
+
+    let mean_ns =
+        samples_ns.iter().map(|value| *value as f64).sum::<f64>() / samples_ns.len() as f64;
+    let p50_ns = sorted[sorted.len() / 2];
+    let p95_ns = sorted[((sorted.len() * 95) / 100).min(sorted.len() - 1)];
+
+    Stats {
+        mean_ns,
+        p50_ns,
+        p95_ns,
+    }
+}
+
+/// Times `iteration(i)` after a warm-up and prints one summary line.
+fn bench(name: &str, mut iteration: impl FnMut(usize)) {
+    for i in 0..WARMUP_ITERS {
+        iteration(i);
+    }
+    let mut samples_ns = Vec::with_capacity(MEASURE_ITERS);
+    for i in 0..MEASURE_ITERS {
+        let start = Instant::now();
+        iteration(WARMUP_ITERS + i);
+        samples_ns.push(start.elapsed().as_nanos());
+    }
+    let stats = compute_stats(&samples_ns);
+    println!(
+        "{name:<32} mean_us={:>10.1} p50_us={:>10.1} p95_us={:>10.1}",
+        stats.mean_ns / 1_000.0,
+        stats.p50_ns as f64 / 1_000.0,
+        stats.p95_ns as f64 / 1_000.0,
+    );
+}
+
+fn styled_line(row: usize) -> String {
+    format!(
+        "\x1b[38;5;{}m{row:>5}\x1b[0m │ \x1b[1mfn\x1b[0m item_{row}(width: usize) -> 宽字符 {} \x1b[2m// note\x1b[0m",
+        row % 256,
+        "x".repeat(row % 40),
+    )
+}
+
+fn large_frame(edit: usize) -> Vec<String> {
+    let mut lines: Vec<String> = (0..FRAME_LINES).map(styled_line).collect();
+    let last = lines.len() - 1;
+    lines[last - edit % HEIGHT] = format!("edited {edit}");
+    lines
+}
+
+fn bench_diff_renderer() {
+    let mut renderer = DiffRenderer::new();
+    renderer.render(Frame::from(large_frame(0)), WIDTH, HEIGHT, false, false);
+    bench("diff/tail_edit_2000_lines", |i| {
+        let cmds = renderer.render(Frame::from(large_frame(i + 1)), WIDTH, HEIGHT, false, false);
+        black_box(cmds);
+    });
+
+    bench("diff/first_render_2000_lines", |i| {
+        let mut renderer = DiffRenderer::new();
+        let cmds = renderer.render(Frame::from(large_frame(i)), WIDTH, HEIGHT, false, false);
+        black_box(cmds);
+    });
+
+    let mut renderer = DiffRenderer::new();
+    let mut lines = large_frame(0);
+    renderer.render(Frame::from(lines.clone()), WIDTH, HEIGHT, false, false);
+    bench("diff/append_line_2000_lines", |i| {
+        lines.push(styled_line(FRAME_LINES + i));
+        let cmds = renderer.render(Frame::from(lines.clone()), WIDTH, HEIGHT, false, false);
+        black_box(cmds);
+    });
+}
+
+fn bench_visible_width() {
+    let lines: Vec<String> = (0..FRAME_LINES).map(styled_line).collect();
+    let emoji = "status 🚀 done ✅ — 日本語 mixed text ".repeat(8);
+    bench("width/styled_2000_lines", |_| {
+        let total: usize = lines.iter().map(|line| visible_width(line)).sum();
+        black_box(total);
+    });
+    bench("width/emoji_and_cjk", |_| {
+        black_box(visible_width(black_box(&emoji)));
+    });
+}
+
+fn plain(text: &str) -> String {
+    text.to_string()
+}
+
+fn plain_theme() -> MarkdownTheme {
+    MarkdownTheme {
+        heading: Box::new(plain),
+        link: Box::new(plain),
+        link_url: Box::new(plain),
+        code: Box::new(plain),
+        code_block: Box::new(plain),
+        code_block_border: Box::new(plain),
+        quote: Box::new(plain),
+        quote_border: Box::new(plain),
+        hr: Box::new(plain),
+        list_bullet: Box::new(plain),
+        bold: Box::new(plain),
+        italic: Box::new(plain),
+        strikethrough: Box::new(plain),
+        underline: Box::new(plain),
+        highlight_code: None,
+        code_block_indent: None,
+    }
+}
+
+fn long_document() -> String {
+    let mut document = String::new();
+    for section in 0..60 {
+        document.push_str(&format!(
+            "## Section {section}\n\n\
+             Paragraph with **bold**, *italic*, `inline code`, and a \
+             [link](https://example.com/{section}) that wraps across several \
+             lines once the terminal is narrower than the sentence itself.\n\n\
+             - first item\n- second item with `code`\n  - nested item\n\n\
+             > quoted remark for section {section}\n\n\
+             ```rust\nfn section_{section}() -> usize {{\n    {section} * 2\n}}\n```\n\n"
+        ));
+    }
+    document
+}
+
+fn bench_markdown() {
+    let document = long_document();
+    bench("markdown/long_document_fresh", |_| {
+        let mut markdown = Markdown::new(document.as_str(), 0, 0, plain_theme(), None);
+        black_box(markdown.render(WIDTH));
+    });
+
+    let mut markdown = Markdown::new("", 0, 0, plain_theme(), None);
+    bench("markdown/long_document_stream", |i| {
+        let mut end = (document.len() * (i % MEASURE_ITERS + 1)) / MEASURE_ITERS;
+        while !document.is_char_boundary(end) {
+            end -= 1;
+        }
+        markdown.set_text(&document[..end]);
+        black_box(markdown.render(WIDTH));
+    });
+}
+
+fn bench_surface_compositing() {
+    let base: Vec<String> = (0..FRAME_LINES).map(styled_line).collect();
+    let surfaces = vec![
+        RenderedSurface {
+            lines: (0..12)
+                .map(|row| format!("\x1b[7m modal row {row} \x1b[0m"))
+                .collect(),
+            row: 10,
+            col: 20,
+            width: 60,
+        },
+        RenderedSurface {
+            lines: (0..3).map(|row| format!("toast {row} 🚀")).collect(),
+            row: 1,
+            col: 90,
+            width: 28,
+        },
+        RenderedSurface {
+            lines: (0..6).map(|row| format!("drawer {row}")).collect(),
+            row: HEIGHT - 6,
+            col: 0,
+            width: WIDTH,
+        },
+    ];
+    bench("surface/composite_3_over_2000", |_| {
+        let lines = composite_surfaces(
+            base.clone(),
+            &surfaces,
+            WIDTH,
+            HEIGHT,
+            FRAME_LINES,
+            is_image_line,
+        );
+        black_box(lines);
+    });
+}
+
+fn main() {
+    println!("render pipeline benchmark");
+    println!(
+        "width={WIDTH}, height={HEIGHT}, frame_lines={FRAME_LINES}, \
+         warmup_iters={WARMUP_ITERS}, measure_iters={MEASURE_ITERS}"
+    );
+    println!();
+    bench_diff_renderer();
+    bench_visible_width();
+    bench_markdown();
+    bench_surface_compositing();
+}
@@ -85,6 +85,48 @@ pub struct RunRequest {
     pub run_id: RunId,
     pub messages: Vec<RunMessage>,
     pub instructions: String,
+    /// JSON Schema the final assistant message must satisfy. Providers that
+    /// support structured output constrain the reply to one JSON document
+    /// matching it; `None` leaves the reply as free text.
+    pub output_schema: Option<Value>,
+    /// Host tools the model may call in this run. Providers only offer the
+    /// listed tools; `None` offers every tool the provider knows.
+    pub allowed_tools: Option<Vec<String>>,
+    /// Caching hint for the prompt prefix; `None` leaves caching to the
+    /// provider's defaults.
+    pub prompt_cache: Option<PromptCacheHint>,
+}
+
+/// Marks the start of a run's prompt as stable so providers can serve it
+/// from their prompt cache.
+///
+/// A prefix is stable when the instructions and the first
+/// `stable_prefix_len` messages are identical to those of an earlier run,
+/// typically the previous turn of the same conversation. Providers without
+/// prompt caching ignore the hint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PromptCacheHint {
+    /// Leading [`RunRequest::messages`] that are cacheable; must not exceed
+    /// the message count.
+    pub stable_prefix_len: usize,
+    /// Groups runs that share a prefix, e.g. a session id. `None` uses the
+    /// provider's own key.
+    pub key: Option<String>,
+    /// Asks the provider to keep the cached prefix beyond its default
+    /// lifetime, for sessions with long pauses between turns.
+    pub extended_retention: bool,
+}
+
+impl PromptCacheHint {
+    /// Length of the prefix `messages` shares with `previous`, the messages
+    /// of an earlier run with the same instructions.
+    pub fn common_prefix_len(previous: &[RunMessage], messages: &[RunMessage]) -> usize {
+        previous
+            .iter()
+            .zip(messages)
+            .take_while(|(previous, message)| previous == message)
+            .count()
+    }
 }
 
 /// Generic host-mediated tool definition exposed by a provider.
@@ -144,14 +186,82 @@ impl ToolResult {
     }
 }
 
+/// Token accounting reported by a provider for one model response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Returns input plus output tokens.
+    #[must_use]
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens.saturating_add(self.output_tokens)
+    }
+
+    /// Adds another usage report into this one, saturating on overflow.
+    pub fn accumulate(&mut self, other: &TokenUsage) {
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.cached_input_tokens = self
+            .cached_input_tokens
+            .saturating_add(other.cached_input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+    }
+}
+
 /// Provider-emitted lifecycle event for a run.
+///
+/// `Usage` is non-terminal and may be emitted zero or more times per run (for
+/// example once per model response in a tool loop); hosts accumulate reports.
+///
+/// `AttemptFailed` is also non-terminal: it reports one failed provider
+/// request attempt. `will_retry` is false for the last attempt, which is
+/// followed by `Failed` (or `Cancelled`).
+///
+/// `ToolCallStreaming` previews a tool call while the model is still writing
+/// it: `arguments` holds everything parsed so far and grows with each event.
+/// It is display-only; the call itself still arrives through the host's tool
+/// executor with the final arguments, and may never arrive if the run ends.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RunEvent {
-    Started { run_id: RunId },
-    Chunk { run_id: RunId, text: String },
-    Finished { run_id: RunId },
-    Failed { run_id: RunId, error: String },
-    Cancelled { run_id: RunId },
+    Started {
+        run_id: RunId,
+    },
+    Chunk {
+        run_id: RunId,
+        text: String,
+    },
+    Usage {
+        run_id: RunId,
+        usage: TokenUsage,
+    },
+    AttemptFailed {
+        run_id: RunId,
+        /// 1-based attempt number within the current request.
+        attempt: u32,
+        /// HTTP status, when the attempt got a response.
+        status: Option<u16>,
+        error: String,
+        will_retry: bool,
+    },
+    ToolCallStreaming {
+        run_id: RunId,
+        call_id: String,
+        tool_name: String,
+        arguments: Value,
+    },
+    Finished {
+        run_id: RunId,
+    },
+    Failed {
+        run_id: RunId,
+        error: String,
+    },
+    Cancelled {
+        run_id: RunId,
+    },
 }
 
 impl RunEvent {
@@ -161,6 +271,9 @@ impl RunEvent {
         match self {
             Self::Started { run_id }
             | Self::Chunk { run_id, .. }
+            | Self::Usage { run_id, .. }
+            | Self::AttemptFailed { run_id, .. }
+            | Self::ToolCallStreaming { run_id, .. }
             | Self::Finished { run_id }
             | Self::Failed { run_id, .. }
             | Self::Cancelled { run_id } => *run_id,
@@ -185,6 +298,17 @@ pub struct ProviderProfile {
     pub thinking_level: Option<String>,
 }
 
+/// One entry of a provider's model catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelInfo {
+    pub model_id: String,
+    pub display_name: Option<String>,
+    /// Context window in tokens, when known.
+    pub context_window: Option<u64>,
+    /// Whether the backend currently offers this model to the account.
+    pub available: bool,
+}
+
 /// Provider interface for executing one run request.
 pub trait RunProvider: Send + Sync + 'static {
     /// Returns provider/model identity metadata.
@@ -209,6 +333,33 @@ pub trait RunProvider: Send + Sync + 'static {
         Err("Thinking-level cycling is not supported by this provider".to_string())
     }
 
+    /// Selects `model_id` for future runs.
+    ///
+    /// Providers reject ids outside their available models with an error that
+    /// lists the valid choices. Unsupported by default.
+    fn set_model(&self, model_id: &str) -> Result<ProviderProfile, String> {
+        let _ = model_id;
+        Err("Model selection is not supported by this provider".to_string())
+    }
+
+    /// Selects thinking level `level` for future runs.
+    ///
+    /// Providers reject levels the selected model does not offer with an error
+    /// that lists the valid choices. Unsupported by default.
+    fn set_thinking_level(&self, level: &str) -> Result<ProviderProfile, String> {
+        let _ = level;
+        Err("Thinking-level selection is not supported by this provider".to_string())
+    }
+
+    /// Returns the provider's model catalog, refreshed from its backend where
+    /// supported.
+    ///
+    /// Providers may cache the listing and may block on network I/O, so hosts
+    /// should call this off the UI thread. Unsupported by default.
+    fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        Err("Model listing is not supported by this provider".to_string())
+    }
+
     /// Executes a run request and emits lifecycle events in provider order.
     ///
     /// Providers can synchronously request host tool execution through `execute_tool`.
@@ -227,8 +378,8 @@ mod tests {
     use serde_json::json;
 
     use super::{
-        CancelSignal, ProviderInitError, ProviderProfile, RunEvent, RunMessage, RunProvider,
-        RunRequest, ToolCallRequest, ToolDefinition, ToolResult,
+        CancelSignal, PromptCacheHint, ProviderInitError, ProviderProfile, RunEvent, RunMessage,
+        RunProvider, RunRequest, TokenUsage, ToolCallRequest, ToolDefinition, ToolResult,
     };
 
     struct MinimalProvider;
@@ -264,6 +415,10 @@ mod tests {
                 run_id,
                 text: "partial".to_string(),
             },
+            RunEvent::Usage {
+                run_id,
+                usage: TokenUsage::default(),
+            },
             RunEvent::Finished { run_id },
             RunEvent::Failed {
                 run_id,
@@ -285,6 +440,11 @@ mod tests {
             text: "hello".to_string(),
         }
         .is_terminal());
+        assert!(!RunEvent::Usage {
+            run_id: 1,
+            usage: TokenUsage::default(),
+        }
+        .is_terminal());
         assert!(RunEvent::Finished { run_id: 1 }.is_terminal());
         assert!(RunEvent::Failed {
             run_id: 1,
@@ -294,6 +454,25 @@ mod tests {
         assert!(RunEvent::Cancelled { run_id: 1 }.is_terminal());
     }
 
+    #[test]
+    fn token_usage_accumulates_and_saturates() {
+        let mut usage = TokenUsage {
+            input_tokens: 100,
+            cached_input_tokens: 40,
+            output_tokens: 20,
+        };
+        usage.accumulate(&TokenUsage {
+            input_tokens: 50,
+            cached_input_tokens: 10,
+            output_tokens: u64::MAX,
+        });
+
+        assert_eq!(usage.input_tokens, 150);
+        assert_eq!(usage.cached_input_tokens, 50);
+        assert_eq!(usage.output_tokens, u64::MAX);
+        assert_eq!(usage.total_tokens(), u64::MAX);
+    }
+
     #[test]
     fn provider_init_error_preserves_message() {
         let error = ProviderInitError::new("missing token");
@@ -301,6 +480,23 @@ mod tests {
         assert_eq!(error.to_string(), "missing token");
     }
 
+    #[test]
+    fn prompt_cache_prefix_stops_at_the_first_changed_message() {
+        let user = |text: &str| RunMessage::UserText {
+            text: text.to_string(),
+        };
+        let previous = vec![user("one"), user("two")];
+        assert_eq!(
+            PromptCacheHint::common_prefix_len(&previous, &[user("one"), user("two"), user("3")]),
+            2
+        );
+        assert_eq!(
+            PromptCacheHint::common_prefix_len(&previous, &[user("one"), user("2")]),
+            1
+        );
+        assert_eq!(PromptCacheHint::common_prefix_len(&[], &previous), 0);
+    }
+
     #[test]
     fn run_request_carries_message_history_and_instructions() {
         let request = RunRequest {
@@ -309,6 +505,9 @@ mod tests {
                 text: "implement tests".to_string(),
             }],
             instructions: "system instructions".to_string(),
+            output_schema: None,
+            allowed_tools: None,
+            prompt_cache: None,
         };
 
         assert_eq!(request.run_id, 7);
@@ -399,4 +598,14 @@ mod tests {
             "Thinking-level cycling is not supported by this provider"
         );
     }
+
+    #[test]
+    fn default_model_listing_hook_reports_unsupported() {
+        let provider = MinimalProvider;
+        let error = provider
+            .list_models()
+            .expect_err("minimal provider should not support model listing");
+
+        assert_eq!(error, "Model listing is not supported by this provider");
+    }
 }
@@ -7,34 +7,67 @@
 //! Host-mediated tool execution is serial and limited to the v1 tool pack
 //! (`bash`, `read`, `edit`, `write`, `apply_patch`), with explicit failure/cancel outcomes for
 //! malformed payloads or non-complete terminal statuses.
+//! `RunRequest.output_schema` maps to a strict `json_schema` `text.format`.
+//! `RunRequest.prompt_cache` maps its key to `prompt_cache_key` (otherwise
+//! the session id is used) and extended retention to
+//! `prompt_cache_retention: "24h"`. The Responses API caches matching
+//! prefixes on its own, so the stable prefix length is only validated.
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex, MutexGuard};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use agent_provider::{
-    CancelSignal, ProviderInitError, ProviderProfile, RunEvent, RunMessage, RunProvider,
-    RunRequest, ToolCallRequest, ToolDefinition, ToolResult,
+    CancelSignal, ModelInfo, PromptCacheHint, ProviderInitError, ProviderProfile, RunEvent,
+    RunMessage, RunProvider, RunRequest, TokenUsage, ToolCallRequest, ToolDefinition, ToolResult,
 };
-use codex_api::payload::CodexReasoning;
+use codex_api::partial_json::parse_partial_json;
+use codex_api::payload::{CodexReasoning, CodexTextFormat};
 use codex_api::{
-    normalize_codex_url, CodexApiClient, CodexApiConfig, CodexApiError, CodexRequest,
-    CodexResponseStatus, CodexStreamEvent, StreamResult,
+    normalize_codex_url, CodexApiClient, CodexApiConfig, CodexApiError, CodexModelInfo,
+    CodexRequest, CodexResponseStatus, CodexStreamEvent, StreamResult,
 };
 use serde_json::{json, Value};
 use url::Url;
 
+pub use codex_api::{ProxyConfig, TlsConfig};
+
 /// Stable provider identifier used by `coding_agent` startup selection.
 pub const CODEX_API_PROVIDER_ID: &str = "codex-api";
 
-const V1_TOOL_NAMES: [&str; 5] = ["bash", "read", "edit", "write", "apply_patch"];
+const V1_TOOL_NAMES: [&str; 15] = [
+    "bash",
+    "read",
+    "edit",
+    "write",
+    "apply_patch",
+    "update_plan",
+    "git_status",
+    "git_diff",
+    "git_commit",
+    "restart_shell",
+    "bash_background",
+    "process_status",
+    "process_kill",
+    "process_logs",
+    "spawn_agent",
+];
 const THINKING_LEVELS_BASELINE: [&str; 5] = ["off", "minimal", "low", "medium", "high"];
 const THINKING_LEVELS_WITH_XHIGH: [&str; 6] = ["off", "minimal", "low", "medium", "high", "xhigh"];
 const SYNTHETIC_ORPHAN_TOOL_RESULT_CONTENT: &str = "No result provided";
 const NORMALIZED_TOOL_CALL_ID_MAX_LEN: usize = 64;
 const NORMALIZED_TOOL_CALL_ID_FALLBACK: &str = "call_0";
 const NORMALIZED_TOOL_ITEM_ID_FALLBACK: &str = "fc_0";
+/// `text.format.name` sent with a run request's output schema.
+const OUTPUT_SCHEMA_FORMAT_NAME: &str = "output";
+/// How long a backend model listing is reused before `list_models` refetches.
+const MODEL_CATALOG_TTL: Duration = Duration::from_secs(5 * 60);
+const MODEL_LIST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Streaming tool arguments are parsed again once they grow by this
+/// fraction (1/N) of the length last parsed, so previewing a large payload
+/// costs linear rather than quadratic time.
+const STREAMING_ARGUMENTS_REPARSE_DIVISOR: usize = 8;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct NormalizedToolCallId {
@@ -53,6 +86,8 @@ struct UnresolvedToolCall {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct SelectionState {
+    /// Bootstrap models, followed by any the backend listing added.
+    model_ids: Vec<String>,
     model_index: usize,
     thinking_index: usize,
 }
@@ -71,6 +106,29 @@ enum ReplayStepItem {
     ToolCall(PendingToolCall),
 }
 
+/// Function-call arguments still streaming in, keyed by output item id.
+#[derive(Debug, Default)]
+struct StreamingToolCalls {
+    calls: HashMap<String, StreamingToolCall>,
+}
+
+#[derive(Debug)]
+struct StreamingToolCall {
+    call_id: String,
+    tool_name: String,
+    raw_arguments: String,
+    /// Length of `raw_arguments` when it was last parsed.
+    parsed_len: usize,
+    last_emitted: Option<Value>,
+}
+
+/// Backend model listing, reused until [`MODEL_CATALOG_TTL`] elapses.
+#[derive(Debug, Clone)]
+struct CachedModelCatalog {
+    fetched_at: Instant,
+    models: Vec<CodexModelInfo>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 struct StreamStepOutcome {
     replay_items: Vec<ReplayStepItem>,
@@ -83,6 +141,8 @@ struct ValidatedConfig {
     base_url: Option<String>,
     session_id: Option<String>,
     timeout: Option<Duration>,
+    proxy: ProxyConfig,
+    tls: TlsConfig,
 }
 
 impl ValidatedConfig {
@@ -101,6 +161,8 @@ impl ValidatedConfig {
             config = config.with_timeout(timeout);
         }
 
+        config.proxy = self.proxy;
+        config.tls = self.tls;
         config
     }
 }
@@ -113,6 +175,8 @@ pub struct CodexApiProviderConfig {
     pub base_url: Option<String>,
     pub session_id: Option<String>,
     pub timeout: Option<Duration>,
+    pub proxy: ProxyConfig,
+    pub tls: TlsConfig,
 }
 
 impl CodexApiProviderConfig {
@@ -124,6 +188,8 @@ impl CodexApiProviderConfig {
             base_url: None,
             session_id: None,
             timeout: None,
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
         }
     }
 
@@ -145,6 +211,18 @@ impl CodexApiProviderConfig {
         self
     }
 
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    #[must_use]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
     fn validate(self) -> Result<ValidatedConfig, ProviderInitError> {
         let access_token = sanitize_required_string(self.access_token, "access token")?;
         let model_ids = sanitize_model_ids(self.model_ids)?;
@@ -166,12 +244,21 @@ impl CodexApiProviderConfig {
             })?;
         }
 
+        let proxy = match self.proxy {
+            ProxyConfig::Url(url) => ProxyConfig::Url(
+                sanitize_optional_string(Some(url), "proxy URL")?.unwrap_or_default(),
+            ),
+            proxy => proxy,
+        };
+
         Ok(ValidatedConfig {
             access_token,
             model_ids,
             base_url,
             session_id,
             timeout: self.timeout,
+            proxy,
+            tls: self.tls,
         })
     }
 }
@@ -196,27 +283,47 @@ trait StreamClient: Send + Sync {
 
         Ok(stream_result.terminal)
     }
+
+    fn list_models(&self) -> Result<Vec<CodexModelInfo>, CodexApiError> {
+        Err(CodexApiError::Unknown(
+            "model listing is not supported by this transport".to_string(),
+        ))
+    }
 }
 
+/// Real transport. Owns one runtime for the provider's lifetime: the HTTP
+/// client's pooled connections are driven by the runtime that opened them, so
+/// a runtime per request would drop the pool and pay a fresh TCP/TLS (and
+/// HTTP/2) handshake on every turn. The single background worker also keeps
+/// idle connections answering keep-alive pings between turns.
 #[derive(Debug)]
 struct DefaultStreamClient {
     client: CodexApiClient,
+    runtime: tokio::runtime::Runtime,
 }
 
-impl StreamClient for DefaultStreamClient {
-    fn stream(
-        &self,
-        request: &CodexRequest,
-        cancel: &CancelSignal,
-    ) -> Result<StreamResult, CodexApiError> {
-        let runtime = tokio::runtime::Builder::new_current_thread()
+impl DefaultStreamClient {
+    fn new(client: CodexApiClient) -> Result<Self, CodexApiError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("codex-api-io")
             .enable_all()
             .build()
             .map_err(|error| {
                 CodexApiError::Unknown(format!("failed to initialize tokio runtime: {error}"))
             })?;
+        Ok(Self { client, runtime })
+    }
+}
 
-        runtime.block_on(self.client.stream(request, Some(cancel)))
+impl StreamClient for DefaultStreamClient {
+    fn stream(
+        &self,
+        request: &CodexRequest,
+        cancel: &CancelSignal,
+    ) -> Result<StreamResult, CodexApiError> {
+        self.runtime
+            .block_on(self.client.stream(request, Some(cancel)))
     }
 
     fn stream_with_handler(
@@ -225,26 +332,32 @@ impl StreamClient for DefaultStreamClient {
         cancel: &CancelSignal,
         on_event: &mut dyn FnMut(CodexStreamEvent),
     ) -> Result<Option<CodexResponseStatus>, CodexApiError> {
-        let runtime = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|error| {
-                CodexApiError::Unknown(format!("failed to initialize tokio runtime: {error}"))
-            })?;
-
-        runtime.block_on(
+        self.runtime.block_on(
             self.client
                 .stream_with_handler(request, Some(cancel), |event| {
                     on_event(event);
                 }),
         )
     }
+
+    fn list_models(&self) -> Result<Vec<CodexModelInfo>, CodexApiError> {
+        self.runtime.block_on(async {
+            tokio::time::timeout(MODEL_LIST_TIMEOUT, self.client.list_models(None))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(CodexApiError::Unknown(format!(
+                        "model listing timed out after {}s",
+                        MODEL_LIST_TIMEOUT.as_secs()
+                    )))
+                })
+        })
+    }
 }
 
 /// `RunProvider` adapter backed by `codex_api` transport primitives.
 pub struct CodexApiProvider {
-    model_ids: Vec<String>,
     selection: Mutex<SelectionState>,
+    model_catalog: Mutex<Option<CachedModelCatalog>>,
     stream_client: Arc<dyn StreamClient>,
 }
 
@@ -258,21 +371,22 @@ impl CodexApiProvider {
             CodexApiClient::new(validated.into_codex_api_config()).map_err(map_init_error)?;
         client.build_headers(None).map_err(map_init_error)?;
 
-        let stream_client = Arc::new(DefaultStreamClient { client });
+        let stream_client = Arc::new(DefaultStreamClient::new(client).map_err(map_init_error)?);
 
         Ok(Self {
-            model_ids,
             selection: Mutex::new(SelectionState {
+                model_ids,
                 model_index: 0,
                 thinking_index: 0,
             }),
+            model_catalog: Mutex::new(None),
             stream_client,
         })
     }
 
     fn selected_model_and_thinking(&self) -> (String, String) {
         let selection = lock_unpoisoned(&self.selection);
-        let model_id = self.model_ids[selection.model_index].clone();
+        let model_id = selection.model_ids[selection.model_index].clone();
         let thinking_levels = thinking_levels_for_model(model_id.as_str());
         let thinking_index = selection
             .thinking_index
@@ -281,8 +395,28 @@ impl CodexApiProvider {
         (model_id, thinking_levels[thinking_index].to_string())
     }
 
+    /// Returns the cached backend listing, refetching it once stale. The cache
+    /// lock is not held during the fetch, so concurrent callers may both fetch.
+    fn cached_or_fetched_models(&self) -> Result<Vec<CodexModelInfo>, String> {
+        if let Some(catalog) = lock_unpoisoned(&self.model_catalog).as_ref() {
+            if catalog.fetched_at.elapsed() < MODEL_CATALOG_TTL {
+                return Ok(catalog.models.clone());
+            }
+        }
+
+        let models = self
+            .stream_client
+            .list_models()
+            .map_err(|error| format!("Model listing failed: {error}"))?;
+        *lock_unpoisoned(&self.model_catalog) = Some(CachedModelCatalog {
+            fetched_at: Instant::now(),
+            models: models.clone(),
+        });
+        Ok(models)
+    }
+
     fn profile_for_selection(&self, selection: &SelectionState) -> ProviderProfile {
-        let model_id = self.model_ids[selection.model_index].clone();
+        let model_id = selection.model_ids[selection.model_index].clone();
         let thinking_levels = thinking_levels_for_model(model_id.as_str());
         let thinking_index = selection
             .thinking_index
@@ -301,6 +435,7 @@ impl CodexApiProvider {
         stream_event: CodexStreamEvent,
         replay_items: &mut Vec<ReplayStepItem>,
         text_buffer: &mut String,
+        streaming_calls: &mut StreamingToolCalls,
         emit: &mut dyn FnMut(RunEvent),
     ) -> Result<(), String> {
         match stream_event {
@@ -314,12 +449,83 @@ impl CodexApiProvider {
                 }
             }
             CodexStreamEvent::ReasoningSummaryTextDelta { .. } => {}
+            CodexStreamEvent::RequestAttemptFailed {
+                attempt,
+                status,
+                message,
+                will_retry,
+            } => emit(RunEvent::AttemptFailed {
+                run_id,
+                attempt,
+                status,
+                error: message,
+                will_retry,
+            }),
+            CodexStreamEvent::ResponseUsage {
+                input_tokens,
+                cached_input_tokens,
+                output_tokens,
+            } => emit(RunEvent::Usage {
+                run_id,
+                usage: TokenUsage {
+                    input_tokens,
+                    cached_input_tokens,
+                    output_tokens,
+                },
+            }),
+            CodexStreamEvent::ToolCallStarted {
+                id: Some(id),
+                call_id: Some(call_id),
+                tool_name: Some(tool_name),
+            } if !call_id.trim().is_empty() && V1_TOOL_NAMES.contains(&tool_name.as_str()) => {
+                streaming_calls.calls.insert(
+                    id,
+                    StreamingToolCall {
+                        call_id: call_id.trim().to_string(),
+                        tool_name,
+                        raw_arguments: String::new(),
+                        parsed_len: 0,
+                        last_emitted: None,
+                    },
+                );
+            }
+            CodexStreamEvent::ToolCallArgumentsDelta {
+                item_id: Some(item_id),
+                delta,
+            } => {
+                let Some(call) = streaming_calls.calls.get_mut(&item_id) else {
+                    return Ok(());
+                };
+                call.raw_arguments.push_str(&delta);
+                let grown = call.raw_arguments.len() - call.parsed_len;
+                if grown < call.parsed_len / STREAMING_ARGUMENTS_REPARSE_DIVISOR {
+                    return Ok(());
+                }
+                call.parsed_len = call.raw_arguments.len();
+                let Some(arguments) =
+                    parse_partial_json(&call.raw_arguments).filter(Value::is_object)
+                else {
+                    return Ok(());
+                };
+                if call.last_emitted.as_ref() != Some(&arguments) {
+                    call.last_emitted = Some(arguments.clone());
+                    emit(RunEvent::ToolCallStreaming {
+                        run_id,
+                        call_id: call.call_id.clone(),
+                        tool_name: call.tool_name.clone(),
+                        arguments,
+                    });
+                }
+            }
             CodexStreamEvent::ToolCallRequested {
                 id,
                 call_id,
                 tool_name,
                 arguments,
             } => {
+                if let Some(id) = id.as_ref() {
+                    streaming_calls.calls.remove(id);
+                }
                 self.flush_text_buffer(text_buffer, replay_items);
                 replay_items.push(ReplayStepItem::ToolCall(parse_pending_tool_call(
                     id, call_id, tool_name, arguments,
@@ -346,6 +552,7 @@ impl CodexApiProvider {
     ) -> Result<StreamStepOutcome, String> {
         let mut replay_items = Vec::new();
         let mut text_buffer = String::new();
+        let mut streaming_calls = StreamingToolCalls::default();
 
         for stream_event in stream_events {
             self.process_stream_event(
@@ -353,6 +560,7 @@ impl CodexApiProvider {
                 stream_event,
                 &mut replay_items,
                 &mut text_buffer,
+                &mut streaming_calls,
                 emit,
             )?;
         }
@@ -368,6 +576,8 @@ impl CodexApiProvider {
         thinking_level: &str,
         messages: &[RunMessage],
         instructions: &str,
+        output_format: Option<&CodexTextFormat>,
+        allowed_tools: Option<&[String]>,
     ) -> Result<CodexRequest, String> {
         let sanitized_messages = sanitize_run_messages(messages.to_vec())?;
         let normalized_messages = normalize_run_messages_for_codex(sanitized_messages)?;
@@ -377,7 +587,8 @@ impl CodexApiProvider {
             Some(instructions.to_string()),
         );
         request.reasoning = thinking_reasoning_payload(thinking_level);
-        request.tools = codex_tool_payloads();
+        request.tools = codex_tool_payloads(allowed_tools);
+        request.text.format = output_format.cloned();
         Ok(request)
     }
 
@@ -417,11 +628,12 @@ impl CodexApiProvider {
             .expect("tests must provide at least one non-empty model id");
 
         Self {
-            model_ids,
             selection: Mutex::new(SelectionState {
+                model_ids,
                 model_index: 0,
                 thinking_index: 0,
             }),
+            model_catalog: Mutex::new(None),
             stream_client,
         }
     }
@@ -439,9 +651,9 @@ impl RunProvider for CodexApiProvider {
 
     fn cycle_model(&self) -> Result<ProviderProfile, String> {
         let mut selection = lock_unpoisoned(&self.selection);
-        selection.model_index = (selection.model_index + 1) % self.model_ids.len();
+        selection.model_index = (selection.model_index + 1) % selection.model_ids.len();
         selection.thinking_index = normalize_thinking_index(
-            self.model_ids[selection.model_index].as_str(),
+            selection.model_ids[selection.model_index].as_str(),
             selection.thinking_index,
         );
 
@@ -451,12 +663,71 @@ impl RunProvider for CodexApiProvider {
     fn cycle_thinking_level(&self) -> Result<ProviderProfile, String> {
         let mut selection = lock_unpoisoned(&self.selection);
         let thinking_levels =
-            thinking_levels_for_model(self.model_ids[selection.model_index].as_str());
+            thinking_levels_for_model(selection.model_ids[selection.model_index].as_str());
         selection.thinking_index = (selection.thinking_index + 1) % thinking_levels.len();
 
         Ok(self.profile_for_selection(&selection))
     }
 
+    fn set_model(&self, model_id: &str) -> Result<ProviderProfile, String> {
+        let model_id = model_id.trim();
+        let mut selection = lock_unpoisoned(&self.selection);
+        let Some(model_index) = selection.model_ids.iter().position(|id| id == model_id) else {
+            return Err(format!(
+                "Unknown model '{model_id}'; available models: {}",
+                selection.model_ids.join(", ")
+            ));
+        };
+
+        selection.model_index = model_index;
+        selection.thinking_index = normalize_thinking_index(model_id, selection.thinking_index);
+
+        Ok(self.profile_for_selection(&selection))
+    }
+
+    fn set_thinking_level(&self, level: &str) -> Result<ProviderProfile, String> {
+        let level = level.trim();
+        let mut selection = lock_unpoisoned(&self.selection);
+        let model_id = selection.model_ids[selection.model_index].as_str();
+        let thinking_levels = thinking_levels_for_model(model_id);
+        let Some(thinking_index) = thinking_levels
+            .iter()
+            .position(|candidate| *candidate == level)
+        else {
+            return Err(format!(
+                "Unknown thinking level '{level}' for {model_id}; available levels: {}",
+                thinking_levels.join(", ")
+            ));
+        };
+
+        selection.thinking_index = thinking_index;
+        Ok(self.profile_for_selection(&selection))
+    }
+
+    fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let listed = self.cached_or_fetched_models()?;
+        let mut selection = lock_unpoisoned(&self.selection);
+        for model in &listed {
+            if !selection.model_ids.contains(&model.slug) {
+                selection.model_ids.push(model.slug.clone());
+            }
+        }
+
+        Ok(selection
+            .model_ids
+            .iter()
+            .map(|model_id| {
+                let listing = listed.iter().find(|model| &model.slug == model_id);
+                ModelInfo {
+                    model_id: model_id.clone(),
+                    display_name: listing.and_then(|model| model.display_name.clone()),
+                    context_window: listing.and_then(|model| model.context_window),
+                    available: listing.is_some(),
+                }
+            })
+            .collect())
+    }
+
     fn run(
         &self,
         req: RunRequest,
@@ -468,10 +739,16 @@ impl RunProvider for CodexApiProvider {
             run_id,
             messages,
             instructions,
+            output_schema,
+            allowed_tools,
+            prompt_cache,
         } = req;
         let (model_id, thinking_level) = self.selected_model_and_thinking();
         let messages = sanitize_run_messages(messages)?;
         let instructions = sanitize_run_instructions(instructions)?;
+        let output_format = output_schema.map(output_text_format).transpose()?;
+        validate_allowed_tools(allowed_tools.as_deref())?;
+        validate_prompt_cache(prompt_cache.as_ref(), messages.len())?;
 
         let mut replay_messages = messages;
         let mut request = self.build_initial_request(
@@ -479,7 +756,10 @@ impl RunProvider for CodexApiProvider {
             &thinking_level,
             &replay_messages,
             &instructions,
+            output_format.as_ref(),
+            allowed_tools.as_deref(),
         )?;
+        apply_prompt_cache(&mut request, prompt_cache.as_ref());
 
         emit(RunEvent::Started { run_id });
 
@@ -496,6 +776,7 @@ impl RunProvider for CodexApiProvider {
 
             let mut replay_items = Vec::new();
             let mut text_buffer = String::new();
+            let mut streaming_calls = StreamingToolCalls::default();
             let mut stream_parse_error = None;
             let terminal = match self.stream_client.stream_with_handler(
                 &request,
@@ -510,6 +791,7 @@ impl RunProvider for CodexApiProvider {
                         stream_event,
                         &mut replay_items,
                         &mut text_buffer,
+                        &mut streaming_calls,
                         emit,
                     ) {
                         stream_parse_error = Some(error);
@@ -629,6 +911,8 @@ impl RunProvider for CodexApiProvider {
                 &thinking_level,
                 &replay_messages,
                 &instructions,
+                output_format.as_ref(),
+                allowed_tools.as_deref(),
             ) {
                 Ok(request) => request,
                 Err(error) => {
@@ -636,6 +920,7 @@ impl RunProvider for CodexApiProvider {
                     return Ok(());
                 }
             };
+            apply_prompt_cache(&mut request, prompt_cache.as_ref());
         }
     }
 }
@@ -745,12 +1030,223 @@ fn v1_tool_definitions() -> Vec<ToolDefinition> {
                 "additionalProperties": false
             }),
         },
+        ToolDefinition {
+            name: "update_plan".to_string(),
+            description: Some(
+                "Replace the visible task plan; keep at most one step in_progress".to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "explanation": { "type": "string" },
+                    "plan": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "step": { "type": "string" },
+                                "status": {
+                                    "type": "string",
+                                    "enum": ["pending", "in_progress", "completed"]
+                                }
+                            },
+                            "required": ["step", "status"],
+                            "additionalProperties": false
+                        }
+                    }
+                },
+                "required": ["plan"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "git_status".to_string(),
+            description: Some(
+                "Report the workspace git branch and changed files as JSON".to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "git_diff".to_string(),
+            description: Some(
+                "Show unstaged (or staged) git changes as JSON with per-file counts and a unified diff"
+                    .to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "staged": { "type": "boolean" }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "git_commit".to_string(),
+            description: Some(
+                "Create a git commit from staged changes, optionally staging paths or all tracked changes first"
+                    .to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "message": { "type": "string" },
+                    "paths": { "type": "array", "items": { "type": "string" } },
+                    "all": { "type": "boolean" }
+                },
+                "required": ["message"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "restart_shell".to_string(),
+            description: Some(
+                "Stop the persistent bash shell so the next bash call starts fresh in the workspace root"
+                    .to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "bash_background".to_string(),
+            description: Some(
+                "Start a long-running shell command in the background and return its process id"
+                    .to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string" },
+                    "cwd": { "type": "string" }
+                },
+                "required": ["command"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "process_status".to_string(),
+            description: Some(
+                "Report one background process, or all of them when id is omitted, as JSON"
+                    .to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" }
+                },
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "process_kill".to_string(),
+            description: Some(
+                "Stop a background process (SIGTERM, then SIGKILL after a grace period)"
+                    .to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" }
+                },
+                "required": ["id"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "process_logs".to_string(),
+            description: Some(
+                "Return the latest combined stdout/stderr lines of a background process".to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "tail_lines": { "type": "integer", "minimum": 1 }
+                },
+                "required": ["id"],
+                "additionalProperties": false
+            }),
+        },
+        ToolDefinition {
+            name: "spawn_agent".to_string(),
+            description: Some(
+                "Delegate a scoped task to a sub-agent with its own context and tools; returns its final answer"
+                    .to_string(),
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "task": { "type": "string" },
+                    "context": { "type": "string" },
+                    "tools": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["task"],
+                "additionalProperties": false
+            }),
+        },
     ]
 }
 
-fn codex_tool_payloads() -> Vec<Value> {
+/// Checks that every allowed tool is one this provider can offer.
+fn validate_allowed_tools(allowed_tools: Option<&[String]>) -> Result<(), String> {
+    let Some(unknown) = allowed_tools
+        .unwrap_or_default()
+        .iter()
+        .find(|name| !V1_TOOL_NAMES.contains(&name.as_str()))
+    else {
+        return Ok(());
+    };
+
+    Err(format!(
+        "Run request allows unknown tool '{unknown}'; supported tools: {}",
+        V1_TOOL_NAMES.join(", ")
+    ))
+}
+
+/// Checks that the cacheable prefix lies within the request's messages.
+fn validate_prompt_cache(
+    prompt_cache: Option<&PromptCacheHint>,
+    message_count: usize,
+) -> Result<(), String> {
+    match prompt_cache {
+        Some(hint) if hint.stable_prefix_len > message_count => Err(format!(
+            "Run request marks {} messages as a stable prompt prefix but has only {message_count}",
+            hint.stable_prefix_len
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Maps a caching hint onto the Responses caching fields; applied to every
+/// step request of the run.
+fn apply_prompt_cache(request: &mut CodexRequest, prompt_cache: Option<&PromptCacheHint>) {
+    let Some(hint) = prompt_cache else {
+        return;
+    };
+    if let Some(key) = hint
+        .key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+    {
+        request.prompt_cache_key = Some(key.to_string());
+    }
+    if hint.extended_retention {
+        request.prompt_cache_retention = Some("24h".to_string());
+    }
+}
+
+fn codex_tool_payloads(allowed_tools: Option<&[String]>) -> Vec<Value> {
     v1_tool_definitions()
         .into_iter()
+        .filter(|definition| allowed_tools.is_none_or(|allowed| allowed.contains(&definition.name)))
         .map(|definition| {
             let mut tool = json!({
                 "type": "function",
@@ -1405,6 +1901,21 @@ fn sanitize_run_messages(messages: Vec<RunMessage>) -> Result<Vec<RunMessage>, S
     Ok(messages)
 }
 
+/// Maps a provider-neutral output schema onto a strict `text.format`.
+fn output_text_format(schema: Value) -> Result<CodexTextFormat, String> {
+    if !schema.is_object() {
+        return Err(format!(
+            "Run request output schema must be a JSON object, got {}",
+            value_type_name(&schema)
+        ));
+    }
+
+    Ok(CodexTextFormat::json_schema(
+        OUTPUT_SCHEMA_FORMAT_NAME,
+        schema,
+    ))
+}
+
 fn sanitize_run_instructions(instructions: String) -> Result<String, String> {
     let trimmed = instructions.trim();
     if trimmed.is_empty() {
@@ -1490,6 +2001,8 @@ mod tests {
     use std::collections::VecDeque;
     use std::sync::atomic::{AtomicBool, AtomicUsize};
 
+    use agent_provider_conformance::{run_conformance_suite, Fixture};
+
     use super::*;
 
     #[derive(Debug)]
@@ -1601,38 +2114,224 @@ mod tests {
         }
     }
 
-    fn run_events_with_executor(
-        provider: &CodexApiProvider,
-        mut execute_tool: impl FnMut(ToolCallRequest) -> ToolResult,
-    ) -> Vec<RunEvent> {
-        let cancel = Arc::new(AtomicBool::new(false));
-        let mut events = Vec::new();
-
-        provider
-            .run(
-                RunRequest {
-                    run_id: 9,
-                    messages: vec![RunMessage::UserText {
-                        text: "hello".to_string(),
-                    }],
-                    instructions: "system instructions".to_string(),
-                },
-                cancel,
-                &mut execute_tool,
-                &mut |event| events.push(event),
-            )
-            .expect("run should not return provider-level failure");
-
-        events
+    /// Plays a conformance fixture as Codex stream events, one response per
+    /// request, honouring cancellation while stalled.
+    struct ConformanceStreamClient {
+        fixture: Fixture,
+        requests: AtomicUsize,
     }
 
-    fn run_events(provider: &CodexApiProvider) -> Vec<RunEvent> {
-        run_events_with_executor(provider, |_call| {
-            ToolResult::error("unused", "unused", "not used in this test")
-        })
-    }
+    impl StreamClient for ConformanceStreamClient {
+        fn stream(
+            &self,
+            _request: &CodexRequest,
+            _cancel: &CancelSignal,
+        ) -> Result<StreamResult, CodexApiError> {
+            panic!("conformance stream client should use stream_with_handler")
+        }
 
-    fn init_error(config: CodexApiProviderConfig) -> ProviderInitError {
+        fn stream_with_handler(
+            &self,
+            _request: &CodexRequest,
+            cancel: &CancelSignal,
+            on_event: &mut dyn FnMut(CodexStreamEvent),
+        ) -> Result<Option<CodexResponseStatus>, CodexApiError> {
+            let request_index = self.requests.fetch_add(1, Ordering::AcqRel);
+            let text = |on_event: &mut dyn FnMut(CodexStreamEvent), chunks: &[String]| {
+                for chunk in chunks {
+                    on_event(CodexStreamEvent::OutputTextDelta {
+                        delta: chunk.clone(),
+                    });
+                }
+            };
+            let tool_call = |call_id: &str, tool_name: &str, arguments: String| {
+                CodexStreamEvent::ToolCallRequested {
+                    id: Some("fc_conformance".to_string()),
+                    call_id: Some(call_id.to_string()),
+                    tool_name: Some(tool_name.to_string()),
+                    arguments: Some(Value::String(arguments)),
+                }
+            };
+
+            match &self.fixture {
+                Fixture::Text { chunks } => text(on_event, chunks),
+                Fixture::ToolRoundtrip { call, .. } if request_index == 0 => {
+                    on_event(tool_call(
+                        &call.call_id,
+                        &call.tool_name,
+                        call.arguments.to_string(),
+                    ));
+                }
+                Fixture::ToolRoundtrip { reply, .. } => text(on_event, std::slice::from_ref(reply)),
+                Fixture::Stall { chunks } => {
+                    text(on_event, chunks);
+                    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+                    while std::time::Instant::now() < deadline {
+                        if cancel.load(Ordering::Acquire) {
+                            return Err(CodexApiError::Cancelled);
+                        }
+                        std::thread::sleep(Duration::from_millis(5));
+                    }
+                    return Err(CodexApiError::Unknown(
+                        "stall was never cancelled".to_string(),
+                    ));
+                }
+                Fixture::MalformedPayload => {
+                    on_event(tool_call("call_1", "read", "not-json".to_string()));
+                }
+                Fixture::UpstreamFailed { message } => {
+                    return Err(CodexApiError::StreamFailed {
+                        code: None,
+                        message: message.clone(),
+                    });
+                }
+                Fixture::UpstreamCancelled => return Ok(Some(CodexResponseStatus::Cancelled)),
+            }
+            Ok(Some(CodexResponseStatus::Completed))
+        }
+    }
+
+    #[test]
+    fn provider_passes_the_conformance_suite() {
+        run_conformance_suite(|fixture| {
+            let stream = Arc::new(ConformanceStreamClient {
+                fixture: fixture.clone(),
+                requests: AtomicUsize::new(0),
+            });
+            Some(Box::new(CodexApiProvider::with_stream_client_for_tests(
+                vec!["gpt-5.1-codex".to_string()],
+                stream,
+            )) as Box<dyn RunProvider>)
+        })
+        .assert_passed();
+    }
+
+    /// Access token whose claims carry `chatgpt_account_id = "acct_test"`.
+    const TEST_ACCESS_TOKEN: &str = "header.eyJodHRwczovL2FwaS5vcGVuYWkuY29tL2F1dGgiOnsiY2hhdGdwdF9hY2NvdW50X2lkIjoiYWNjdF90ZXN0In19.signature";
+
+    /// Serves every request with `body` as a keep-alive SSE response,
+    /// counting accepted TCP connections and served requests.
+    fn spawn_keep_alive_server(body: &'static str) -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind test listener");
+        let base_url = format!(
+            "http://{}",
+            listener.local_addr().expect("listener address")
+        );
+        let connections = Arc::new(AtomicUsize::new(0));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let (accepted, served) = (Arc::clone(&connections), Arc::clone(&requests));
+
+        std::thread::spawn(move || {
+            for mut socket in listener.incoming().flatten() {
+                accepted.fetch_add(1, Ordering::AcqRel);
+                let served = Arc::clone(&served);
+                std::thread::spawn(move || {
+                    let mut pending = Vec::new();
+                    let mut buffer = [0_u8; 4096];
+                    loop {
+                        let Some(header_end) = pending
+                            .windows(4)
+                            .position(|window| window == b"\r\n\r\n")
+                            .map(|position| position + 4)
+                        else {
+                            match socket.read(&mut buffer) {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => pending.extend_from_slice(&buffer[..n]),
+                            }
+                            continue;
+                        };
+                        let headers =
+                            String::from_utf8_lossy(&pending[..header_end]).to_ascii_lowercase();
+                        let body_len = headers
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|value| value.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        while pending.len() < header_end + body_len {
+                            match socket.read(&mut buffer) {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => pending.extend_from_slice(&buffer[..n]),
+                            }
+                        }
+                        pending.drain(..header_end + body_len);
+
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\n\r\n{body}",
+                            body.len()
+                        );
+                        if socket.write_all(response.as_bytes()).is_err() {
+                            return;
+                        }
+                        served.fetch_add(1, Ordering::AcqRel);
+                    }
+                });
+            }
+        });
+
+        (base_url, connections, requests)
+    }
+
+    #[test]
+    fn real_transport_reuses_pooled_connection_across_runs() {
+        let (base_url, connections, requests) = spawn_keep_alive_server(concat!(
+            "data: {\"type\":\"response.output_text.delta\",\"delta\":\"hi\"}\n\n",
+            "data: {\"type\":\"response.completed\",\"response\":{\"status\":\"completed\"}}\n\n",
+        ));
+        let provider = CodexApiProvider::new(
+            CodexApiProviderConfig::new(TEST_ACCESS_TOKEN, vec!["gpt-5.1-codex".to_string()])
+                .with_base_url(base_url),
+        )
+        .expect("provider should initialize");
+
+        for _ in 0..3 {
+            let events = run_events(&provider);
+            assert!(matches!(
+                events.last(),
+                Some(RunEvent::Finished { run_id: 9 })
+            ));
+        }
+
+        assert_eq!(requests.load(Ordering::Acquire), 3);
+        assert_eq!(connections.load(Ordering::Acquire), 1);
+    }
+
+    fn run_events_with_executor(
+        provider: &CodexApiProvider,
+        mut execute_tool: impl FnMut(ToolCallRequest) -> ToolResult,
+    ) -> Vec<RunEvent> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut events = Vec::new();
+
+        provider
+            .run(
+                RunRequest {
+                    run_id: 9,
+                    messages: vec![RunMessage::UserText {
+                        text: "hello".to_string(),
+                    }],
+                    instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
+                },
+                cancel,
+                &mut execute_tool,
+                &mut |event| events.push(event),
+            )
+            .expect("run should not return provider-level failure");
+
+        events
+    }
+
+    fn run_events(provider: &CodexApiProvider) -> Vec<RunEvent> {
+        run_events_with_executor(provider, |_call| {
+            ToolResult::error("unused", "unused", "not used in this test")
+        })
+    }
+
+    fn init_error(config: CodexApiProviderConfig) -> ProviderInitError {
         match CodexApiProvider::new(config) {
             Ok(_) => panic!("provider init should fail for this test case"),
             Err(error) => error,
@@ -2442,6 +3141,9 @@ mod tests {
                         },
                     ],
                     instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
                 },
                 cancel,
                 &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -2539,6 +3241,9 @@ mod tests {
                         },
                     ],
                     instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
                 },
                 cancel,
                 &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -2600,6 +3305,9 @@ mod tests {
                     },
                 ],
                 instructions: "system instructions".to_string(),
+                output_schema: None,
+                allowed_tools: None,
+                prompt_cache: None,
             },
             cancel,
             &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -2713,6 +3421,323 @@ mod tests {
         assert_eq!(switched.thinking_level.as_deref(), Some("high"));
     }
 
+    #[test]
+    fn explicit_selection_validates_models_and_model_family_thinking_levels() {
+        let stream = FakeStreamClient::success(StreamResult {
+            events: Vec::new(),
+            terminal: Some(CodexResponseStatus::Completed),
+        });
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string(), "gpt-5.3-codex".to_string()],
+            stream,
+        );
+
+        let error = provider
+            .set_thinking_level("xhigh")
+            .expect_err("xhigh is unavailable for gpt-5.1-codex");
+        assert!(error.contains("off, minimal, low, medium, high"), "{error}");
+
+        let switched = provider
+            .set_model(" gpt-5.3-codex ")
+            .expect("configured model should be selectable");
+        assert_eq!(switched.model_id, "gpt-5.3-codex");
+        let switched = provider
+            .set_thinking_level("xhigh")
+            .expect("xhigh is available for gpt-5.3-codex");
+        assert_eq!(switched.thinking_level.as_deref(), Some("xhigh"));
+
+        let error = provider
+            .set_model("gpt-4o")
+            .expect_err("unconfigured models should be rejected");
+        assert!(error.contains("gpt-5.1-codex, gpt-5.3-codex"), "{error}");
+        assert_eq!(provider.profile().model_id, "gpt-5.3-codex");
+
+        let switched = provider
+            .set_model("gpt-5.1-codex")
+            .expect("configured model should be selectable");
+        assert_eq!(switched.thinking_level.as_deref(), Some("high"));
+    }
+
+    #[test]
+    fn output_schema_maps_to_strict_text_format_on_every_step_request() {
+        let stream = FakeStreamClient::scripted(vec![
+            FakeStreamOutcome::Success(StreamResult {
+                events: vec![CodexStreamEvent::ToolCallRequested {
+                    id: Some("fc_1".to_string()),
+                    call_id: Some("call_1".to_string()),
+                    tool_name: Some("read".to_string()),
+                    arguments: Some(Value::String("{\"path\":\"README.md\"}".to_string())),
+                }],
+                terminal: Some(CodexResponseStatus::Completed),
+            }),
+            FakeStreamOutcome::Success(StreamResult {
+                events: vec![CodexStreamEvent::OutputTextDelta {
+                    delta: "{\"summary\":\"done\"}".to_string(),
+                }],
+                terminal: Some(CodexResponseStatus::Completed),
+            }),
+        ]);
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string()],
+            Arc::clone(&stream) as Arc<dyn StreamClient>,
+        );
+        let schema = json!({
+            "type": "object",
+            "properties": {"summary": {"type": "string"}},
+            "required": ["summary"],
+            "additionalProperties": false,
+        });
+
+        let run = |output_schema: Value| {
+            let mut events = Vec::new();
+            provider
+                .run(
+                    RunRequest {
+                        run_id: 9,
+                        messages: vec![RunMessage::UserText {
+                            text: "summarize".to_string(),
+                        }],
+                        instructions: "system instructions".to_string(),
+                        output_schema: Some(output_schema),
+                        allowed_tools: None,
+                        prompt_cache: None,
+                    },
+                    Arc::new(AtomicBool::new(false)),
+                    &mut |call| ToolResult::success(call.call_id, call.tool_name, "contents"),
+                    &mut |event| events.push(event),
+                )
+                .map(|()| events)
+        };
+
+        let events = run(schema.clone()).expect("run should succeed");
+        assert!(matches!(events.last(), Some(RunEvent::Finished { .. })));
+        let requests = stream.observed_requests();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!(
+                serde_json::to_value(&request.text).expect("serialize text")["format"],
+                json!({"type": "json_schema", "name": "output", "schema": schema, "strict": true})
+            );
+        }
+
+        let error = run(json!("not a schema")).expect_err("non-object schemas are rejected");
+        assert_eq!(
+            error,
+            "Run request output schema must be a JSON object, got string"
+        );
+        assert_eq!(stream.observed_requests().len(), 2);
+    }
+
+    #[test]
+    fn allowed_tools_limit_advertised_tools_on_every_step_request() {
+        let stream = FakeStreamClient::scripted(vec![
+            FakeStreamOutcome::Success(StreamResult {
+                events: vec![CodexStreamEvent::ToolCallRequested {
+                    id: Some("fc_1".to_string()),
+                    call_id: Some("call_1".to_string()),
+                    tool_name: Some("read".to_string()),
+                    arguments: Some(Value::String("{\"path\":\"README.md\"}".to_string())),
+                }],
+                terminal: Some(CodexResponseStatus::Completed),
+            }),
+            FakeStreamOutcome::Success(StreamResult {
+                events: vec![CodexStreamEvent::OutputTextDelta {
+                    delta: "done".to_string(),
+                }],
+                terminal: Some(CodexResponseStatus::Completed),
+            }),
+        ]);
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string()],
+            Arc::clone(&stream) as Arc<dyn StreamClient>,
+        );
+
+        let run = |allowed_tools: Vec<&str>| {
+            provider.run(
+                RunRequest {
+                    run_id: 4,
+                    messages: vec![RunMessage::UserText {
+                        text: "look around".to_string(),
+                    }],
+                    instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: Some(allowed_tools.into_iter().map(String::from).collect()),
+                    prompt_cache: None,
+                },
+                Arc::new(AtomicBool::new(false)),
+                &mut |call| ToolResult::success(call.call_id, call.tool_name, "contents"),
+                &mut |_| {},
+            )
+        };
+
+        run(vec!["git_diff", "read"]).expect("run should succeed");
+        let requests = stream.observed_requests();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!(request_tool_names(request), vec!["read", "git_diff"]);
+        }
+
+        let error = run(vec!["read", "teleport"]).expect_err("unknown tools are rejected");
+        assert!(error.starts_with("Run request allows unknown tool 'teleport'"));
+        assert_eq!(stream.observed_requests().len(), 2);
+    }
+
+    #[test]
+    fn prompt_cache_hint_sets_key_and_retention_on_every_step_request() {
+        let stream = FakeStreamClient::scripted(vec![
+            FakeStreamOutcome::Success(StreamResult {
+                events: vec![CodexStreamEvent::ToolCallRequested {
+                    id: Some("fc_1".to_string()),
+                    call_id: Some("call_1".to_string()),
+                    tool_name: Some("read".to_string()),
+                    arguments: Some(Value::String("{\"path\":\"README.md\"}".to_string())),
+                }],
+                terminal: Some(CodexResponseStatus::Completed),
+            }),
+            FakeStreamOutcome::Success(StreamResult {
+                events: vec![CodexStreamEvent::OutputTextDelta {
+                    delta: "done".to_string(),
+                }],
+                terminal: Some(CodexResponseStatus::Completed),
+            }),
+        ]);
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string()],
+            Arc::clone(&stream) as Arc<dyn StreamClient>,
+        );
+
+        let run = |stable_prefix_len: usize| {
+            provider.run(
+                RunRequest {
+                    run_id: 5,
+                    messages: vec![
+                        RunMessage::UserText {
+                            text: "earlier turn".to_string(),
+                        },
+                        RunMessage::UserText {
+                            text: "look around".to_string(),
+                        },
+                    ],
+                    instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: Some(PromptCacheHint {
+                        stable_prefix_len,
+                        key: Some(" session-42 ".to_string()),
+                        extended_retention: true,
+                    }),
+                },
+                Arc::new(AtomicBool::new(false)),
+                &mut |call| ToolResult::success(call.call_id, call.tool_name, "contents"),
+                &mut |_| {},
+            )
+        };
+
+        run(1).expect("run should succeed");
+        let requests = stream.observed_requests();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!(request.prompt_cache_key.as_deref(), Some("session-42"));
+            assert_eq!(request.prompt_cache_retention.as_deref(), Some("24h"));
+        }
+
+        let error = run(3).expect_err("prefixes longer than the history are rejected");
+        assert_eq!(
+            error,
+            "Run request marks 3 messages as a stable prompt prefix but has only 2"
+        );
+        assert_eq!(stream.observed_requests().len(), 2);
+    }
+
+    struct FakeModelListClient {
+        listings: Mutex<VecDeque<Result<Vec<CodexModelInfo>, CodexApiError>>>,
+        calls: AtomicUsize,
+    }
+
+    impl StreamClient for FakeModelListClient {
+        fn stream(
+            &self,
+            _request: &CodexRequest,
+            _cancel: &CancelSignal,
+        ) -> Result<StreamResult, CodexApiError> {
+            Err(CodexApiError::Unknown("listing-only fake".to_string()))
+        }
+
+        fn list_models(&self) -> Result<Vec<CodexModelInfo>, CodexApiError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            lock_unpoisoned(&self.listings)
+                .pop_front()
+                .expect("unexpected model listing request")
+        }
+    }
+
+    fn listed_model(slug: &str, context_window: Option<u64>) -> CodexModelInfo {
+        CodexModelInfo {
+            slug: slug.to_string(),
+            display_name: None,
+            context_window,
+        }
+    }
+
+    #[test]
+    fn model_listing_merges_backend_catalog_caches_it_and_extends_selection() {
+        let client = Arc::new(FakeModelListClient {
+            listings: Mutex::new(VecDeque::from([
+                Err(CodexApiError::Unknown("backend down".to_string())),
+                Ok(vec![
+                    listed_model("gpt-5.3-codex", Some(272_000)),
+                    listed_model("gpt-5.4", None),
+                ]),
+            ])),
+            calls: AtomicUsize::new(0),
+        });
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string(), "gpt-5.3-codex".to_string()],
+            Arc::clone(&client) as Arc<dyn StreamClient>,
+        );
+
+        let error = provider
+            .list_models()
+            .expect_err("failed listings should surface");
+        assert_eq!(error, "Model listing failed: backend down");
+        let error = provider
+            .set_model("gpt-5.4")
+            .expect_err("unlisted models stay unselectable");
+        assert!(error.contains("gpt-5.1-codex, gpt-5.3-codex"), "{error}");
+
+        let models = provider.list_models().expect("listing");
+        let summary: Vec<(&str, Option<u64>, bool)> = models
+            .iter()
+            .map(|model| {
+                (
+                    model.model_id.as_str(),
+                    model.context_window,
+                    model.available,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("gpt-5.1-codex", None, false),
+                ("gpt-5.3-codex", Some(272_000), true),
+                ("gpt-5.4", None, true),
+            ]
+        );
+
+        assert_eq!(provider.list_models().expect("cached listing"), models);
+        assert_eq!(client.calls.load(Ordering::SeqCst), 2);
+
+        let switched = provider
+            .set_model("gpt-5.4")
+            .expect("backend-listed models become selectable");
+        assert_eq!(switched.model_id, "gpt-5.4");
+        assert_eq!(
+            provider.cycle_model().expect("cycle").model_id,
+            "gpt-5.1-codex"
+        );
+    }
+
     #[test]
     fn tool_definitions_advertise_only_v1_tools() {
         let stream = FakeStreamClient::success(StreamResult {
@@ -2803,6 +3828,53 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn run_forwards_response_usage_before_terminal_event() {
+        let stream = FakeStreamClient::success(StreamResult {
+            events: vec![
+                CodexStreamEvent::OutputTextDelta {
+                    delta: "Hello".to_string(),
+                },
+                CodexStreamEvent::ResponseUsage {
+                    input_tokens: 120,
+                    cached_input_tokens: 64,
+                    output_tokens: 32,
+                },
+            ],
+            terminal: Some(CodexResponseStatus::Completed),
+        });
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string()],
+            Arc::clone(&stream) as Arc<dyn StreamClient>,
+        );
+
+        let events = run_events_with_executor(&provider, |_call| {
+            panic!("tool executor should not be invoked when no tool calls are streamed")
+        });
+
+        let usage_index = events
+            .iter()
+            .position(|event| {
+                matches!(
+                    event,
+                    RunEvent::Usage {
+                        run_id: 9,
+                        usage: TokenUsage {
+                            input_tokens: 120,
+                            cached_input_tokens: 64,
+                            output_tokens: 32,
+                        },
+                    }
+                )
+            })
+            .expect("usage event should be forwarded");
+        assert_eq!(usage_index + 1, events.len() - 1);
+        assert!(matches!(
+            events.last(),
+            Some(RunEvent::Finished { run_id: 9 })
+        ));
+    }
+
     #[test]
     fn run_uses_incremental_stream_handler_and_emits_chunk_before_terminal() {
         let chunk_processed = Arc::new(AtomicBool::new(false));
@@ -2823,6 +3895,9 @@ mod tests {
                         text: "hello".to_string(),
                     }],
                     instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
                 },
                 cancel,
                 &mut |_call| {
@@ -2936,6 +4011,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn process_stream_events_previews_streaming_tool_arguments() {
+        let stream = FakeStreamClient::success(StreamResult {
+            events: Vec::new(),
+            terminal: Some(CodexResponseStatus::Completed),
+        });
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string()],
+            Arc::clone(&stream) as Arc<dyn StreamClient>,
+        );
+        let delta = |item_id: &str, delta: &str| CodexStreamEvent::ToolCallArgumentsDelta {
+            item_id: Some(item_id.to_string()),
+            delta: delta.to_string(),
+        };
+
+        let mut emitted = Vec::new();
+        let outcome = provider
+            .process_stream_events(
+                42,
+                vec![
+                    CodexStreamEvent::ToolCallStarted {
+                        id: Some("fc_1".to_string()),
+                        call_id: Some(" call_1 ".to_string()),
+                        tool_name: Some("bash".to_string()),
+                    },
+                    delta("fc_1", "{\"command\": \"cargo"),
+                    delta("fc_1", " te"),
+                    delta("fc_1", "st\", \"time"),
+                    delta("fc_unknown", "{\"command\": \"ignored\"}"),
+                    delta("fc_1", "out_sec\": 5}"),
+                    CodexStreamEvent::ToolCallRequested {
+                        id: Some("fc_1".to_string()),
+                        call_id: Some("call_1".to_string()),
+                        tool_name: Some("bash".to_string()),
+                        arguments: Some(Value::String(
+                            "{\"command\": \"cargo test\", \"timeout_sec\": 5}".to_string(),
+                        )),
+                    },
+                    delta("fc_1", "late"),
+                ],
+                &mut |event| emitted.push(event),
+            )
+            .expect("stream events should normalize");
+
+        let streaming = |arguments: Value| RunEvent::ToolCallStreaming {
+            run_id: 42,
+            call_id: "call_1".to_string(),
+            tool_name: "bash".to_string(),
+            arguments,
+        };
+        assert_eq!(
+            emitted,
+            vec![
+                streaming(json!({ "command": "cargo" })),
+                streaming(json!({ "command": "cargo te" })),
+                streaming(json!({ "command": "cargo test" })),
+                streaming(json!({ "command": "cargo test", "timeout_sec": 5 })),
+            ]
+        );
+        assert_eq!(outcome.replay_items.len(), 1);
+    }
+
+    #[test]
+    fn process_stream_events_parses_large_streaming_arguments_a_bounded_number_of_times() {
+        let stream = FakeStreamClient::success(StreamResult {
+            events: Vec::new(),
+            terminal: Some(CodexResponseStatus::Completed),
+        });
+        let provider = CodexApiProvider::with_stream_client_for_tests(
+            vec!["gpt-5.1-codex".to_string()],
+            Arc::clone(&stream) as Arc<dyn StreamClient>,
+        );
+        let mut events = vec![
+            CodexStreamEvent::ToolCallStarted {
+                id: Some("fc_1".to_string()),
+                call_id: Some("call_1".to_string()),
+                tool_name: Some("write".to_string()),
+            },
+            CodexStreamEvent::ToolCallArgumentsDelta {
+                item_id: Some("fc_1".to_string()),
+                delta: "{\"path\": \"big.txt\", \"content\": \"".to_string(),
+            },
+        ];
+        events.extend(
+            (0..10_000).map(|_| CodexStreamEvent::ToolCallArgumentsDelta {
+                item_id: Some("fc_1".to_string()),
+                delta: "x".to_string(),
+            }),
+        );
+
+        let mut emitted = Vec::new();
+        provider
+            .process_stream_events(42, events, &mut |event| emitted.push(event))
+            .expect("stream events should normalize");
+
+        assert!(emitted.len() < 100, "{} previews", emitted.len());
+        let Some(RunEvent::ToolCallStreaming { arguments, .. }) = emitted.last() else {
+            panic!("expected a streaming preview, got {emitted:?}");
+        };
+        assert_eq!(arguments["path"], "big.txt");
+        let content = arguments["content"].as_str().expect("content is a string");
+        assert!(content.len() > 8_000, "{} bytes previewed", content.len());
+    }
+
     #[test]
     fn process_stream_events_preserves_stream_item_id_in_replay_call_id_when_present() {
         let stream = FakeStreamClient::success(StreamResult {
@@ -3081,6 +4260,9 @@ mod tests {
                         },
                     ],
                     instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
                 },
                 cancel,
                 &mut |_call| {
@@ -3158,6 +4340,9 @@ mod tests {
                     },
                 ],
                 instructions: "system instructions".to_string(),
+                output_schema: None,
+                allowed_tools: None,
+                prompt_cache: None,
             },
             cancel,
             &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -3198,6 +4383,9 @@ mod tests {
                     },
                 ],
                 instructions: "system instructions".to_string(),
+                output_schema: None,
+                allowed_tools: None,
+                prompt_cache: None,
             },
             cancel,
             &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -3359,6 +4547,9 @@ mod tests {
                         },
                     ],
                     instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
                 },
                 cancel,
                 &mut |call| ToolResult::success(call.call_id, call.tool_name, "tool output"),
@@ -3951,6 +5142,9 @@ mod tests {
                     text: "  \n\t ".to_string(),
                 }],
                 instructions: "system instructions".to_string(),
+                output_schema: None,
+                allowed_tools: None,
+                prompt_cache: None,
             },
             cancel,
             &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -3981,6 +5175,9 @@ mod tests {
                 run_id: 12,
                 messages: Vec::new(),
                 instructions: "system instructions".to_string(),
+                output_schema: None,
+                allowed_tools: None,
+                prompt_cache: None,
             },
             cancel,
             &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -4013,6 +5210,9 @@ mod tests {
                     text: "assistant only".to_string(),
                 }],
                 instructions: "system instructions".to_string(),
+                output_schema: None,
+                allowed_tools: None,
+                prompt_cache: None,
             },
             cancel,
             &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -4045,6 +5245,9 @@ mod tests {
                     text: "hello".to_string(),
                 }],
                 instructions: "   \n\t ".to_string(),
+                output_schema: None,
+                allowed_tools: None,
+                prompt_cache: None,
             },
             cancel,
             &mut |_call| ToolResult::error("unused", "unused", "unused"),
@@ -0,0 +1,710 @@
+//! Reusable contract checks for `agent_provider::RunProvider` implementations.
+//!
+//! [`run_conformance_suite`] drives a provider through the cases every adapter
+//! must handle the same way: lifecycle ordering, cancellation before the run
+//! starts, mid-stream and during a tool call, a tool roundtrip, malformed
+//! payloads, and the mapping of upstream terminal statuses. For each case the
+//! provider factory receives the [`Fixture`] the case needs and builds a
+//! provider whose backend (a fake transport, a local server, a scripted mock)
+//! behaves that way. Returning `None` skips the case, for fixtures a provider
+//! has no way to produce.
+//!
+//! ```ignore
+//! run_conformance_suite(|fixture| Some(Box::new(provider_for(fixture)) as Box<dyn RunProvider>))
+//!     .assert_passed();
+//! ```
+//!
+//! Besides each case's own expectations, every run is checked against the
+//! shared lifecycle rules:
+//!
+//! - a run that returns `Ok` emits `Started` first and exactly one terminal
+//!   event, last;
+//! - a run that returns `Err` emits no terminal event;
+//! - every event carries the request's run id;
+//! - the provider neither panics nor runs past [`CASE_TIMEOUT`].
+
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use agent_provider::{
+    RunEvent, RunId, RunMessage, RunProvider, RunRequest, ToolCallRequest, ToolResult,
+};
+use serde_json::json;
+
+/// Longest a single case may run before it fails and is cancelled.
+pub const CASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tool the roundtrip fixtures call; adapters that validate tool names must
+/// accept it.
+pub const CONFORMANCE_TOOL_NAME: &str = "read";
+
+/// Content of the successful result the suite returns for every tool call.
+pub const CONFORMANCE_TOOL_OUTPUT: &str = "conformance tool output";
+
+/// Backend behaviour a case needs from the provider under test.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fixture {
+    /// Streams `chunks` as assistant text, then completes.
+    Text { chunks: Vec<String> },
+    /// Requests `call`; once the host returns its result, streams `reply` and
+    /// completes.
+    ToolRoundtrip {
+        call: ToolCallRequest,
+        reply: String,
+    },
+    /// Streams `chunks`, then keeps the response open until cancelled.
+    Stall { chunks: Vec<String> },
+    /// Delivers a payload the adapter cannot decode, such as a tool call
+    /// whose arguments are not JSON.
+    MalformedPayload,
+    /// The upstream request fails with `message`.
+    UpstreamFailed { message: String },
+    /// The upstream response ends with a cancelled status.
+    UpstreamCancelled,
+}
+
+/// Result of one conformance case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaseStatus {
+    Passed,
+    /// The factory returned `None` for the case's fixture.
+    Skipped,
+    /// Every expectation the run broke.
+    Failed(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseOutcome {
+    pub case: &'static str,
+    pub status: CaseStatus,
+}
+
+/// Outcomes of [`run_conformance_suite`], in case order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConformanceReport {
+    pub outcomes: Vec<CaseOutcome>,
+}
+
+impl ConformanceReport {
+    /// Cases that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &CaseOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome.status, CaseStatus::Failed(_)))
+    }
+
+    /// Names of the cases the factory skipped.
+    #[must_use]
+    pub fn skipped(&self) -> Vec<&'static str> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.status == CaseStatus::Skipped)
+            .map(|outcome| outcome.case)
+            .collect()
+    }
+
+    /// Panics with the full report unless no case failed and at least one ran.
+    #[track_caller]
+    pub fn assert_passed(&self) {
+        let ran = self
+            .outcomes
+            .iter()
+            .any(|outcome| outcome.status == CaseStatus::Passed);
+        if !ran || self.failures().next().is_some() {
+            panic!("provider conformance failed:\n{self}");
+        }
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for outcome in &self.outcomes {
+            match &outcome.status {
+                CaseStatus::Passed => writeln!(f, "{}: passed", outcome.case)?,
+                CaseStatus::Skipped => writeln!(f, "{}: skipped", outcome.case)?,
+                CaseStatus::Failed(failures) => {
+                    writeln!(f, "{}: FAILED", outcome.case)?;
+                    for failure in failures {
+                        writeln!(f, "  - {failure}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs every conformance case against providers built by `provider_factory`.
+///
+/// The factory is called once per case with the fixture that case needs; each
+/// provider serves exactly one run.
+pub fn run_conformance_suite<F>(mut provider_factory: F) -> ConformanceReport
+where
+    F: FnMut(&Fixture) -> Option<Box<dyn RunProvider>>,
+{
+    let outcomes = cases()
+        .into_iter()
+        .enumerate()
+        .map(|(index, case)| {
+            let status = match provider_factory(&case.fixture) {
+                None => CaseStatus::Skipped,
+                Some(provider) => {
+                    let mut failures = Vec::new();
+                    match observe_run(provider, index as RunId + 1, case.cancel_at) {
+                        Ok(observation) => {
+                            check_lifecycle(&observation, &mut failures);
+                            (case.check)(&case.fixture, &observation, &mut failures);
+                        }
+                        Err(error) => failures.push(error),
+                    }
+                    if failures.is_empty() {
+                        CaseStatus::Passed
+                    } else {
+                        CaseStatus::Failed(failures)
+                    }
+                }
+            };
+            CaseOutcome {
+                case: case.name,
+                status,
+            }
+        })
+        .collect();
+    ConformanceReport { outcomes }
+}
+
+/// When a case sets the run's cancel signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CancelAt {
+    Never,
+    BeforeStart,
+    FirstChunk,
+    ToolCall,
+}
+
+type CaseCheck = fn(&Fixture, &Observation, &mut Vec<String>);
+
+struct Case {
+    name: &'static str,
+    fixture: Fixture,
+    cancel_at: CancelAt,
+    check: CaseCheck,
+}
+
+fn cases() -> Vec<Case> {
+    let text = Fixture::Text {
+        chunks: vec![
+            "Hello".to_string(),
+            ", conformance".to_string(),
+            "\n".to_string(),
+        ],
+    };
+    let roundtrip = Fixture::ToolRoundtrip {
+        call: ToolCallRequest {
+            call_id: "call_conformance".to_string(),
+            tool_name: CONFORMANCE_TOOL_NAME.to_string(),
+            arguments: json!({ "path": "CONFORMANCE.md" }),
+        },
+        reply: "Read it.".to_string(),
+    };
+
+    vec![
+        Case {
+            name: "lifecycle_ordering",
+            fixture: text.clone(),
+            cancel_at: CancelAt::Never,
+            check: check_lifecycle_ordering,
+        },
+        Case {
+            name: "cancel_before_start",
+            fixture: text,
+            cancel_at: CancelAt::BeforeStart,
+            check: check_cancel_before_start,
+        },
+        Case {
+            name: "cancel_mid_stream",
+            fixture: Fixture::Stall {
+                chunks: vec!["partial".to_string()],
+            },
+            cancel_at: CancelAt::FirstChunk,
+            check: check_cancel_mid_stream,
+        },
+        Case {
+            name: "cancel_during_tool_call",
+            fixture: roundtrip.clone(),
+            cancel_at: CancelAt::ToolCall,
+            check: check_cancel_during_tool_call,
+        },
+        Case {
+            name: "tool_roundtrip",
+            fixture: roundtrip,
+            cancel_at: CancelAt::Never,
+            check: check_tool_roundtrip,
+        },
+        Case {
+            name: "malformed_payload",
+            fixture: Fixture::MalformedPayload,
+            cancel_at: CancelAt::Never,
+            check: check_malformed_payload,
+        },
+        Case {
+            name: "upstream_failure",
+            fixture: Fixture::UpstreamFailed {
+                message: "conformance upstream failure".to_string(),
+            },
+            cancel_at: CancelAt::Never,
+            check: check_upstream_failure,
+        },
+        Case {
+            name: "upstream_cancelled",
+            fixture: Fixture::UpstreamCancelled,
+            cancel_at: CancelAt::Never,
+            check: check_upstream_cancelled,
+        },
+    ]
+}
+
+/// Events and tool calls of one run, interleaved in the order they happened.
+enum Observed {
+    Event(RunEvent),
+    ToolCall(ToolCallRequest),
+}
+
+struct Observation {
+    run_id: RunId,
+    timeline: Vec<Observed>,
+    result: Result<(), String>,
+}
+
+impl Observation {
+    fn events(&self) -> impl Iterator<Item = &RunEvent> {
+        self.timeline.iter().filter_map(|observed| match observed {
+            Observed::Event(event) => Some(event),
+            Observed::ToolCall(_) => None,
+        })
+    }
+
+    fn tool_calls(&self) -> Vec<&ToolCallRequest> {
+        self.timeline
+            .iter()
+            .filter_map(|observed| match observed {
+                Observed::ToolCall(call) => Some(call),
+                Observed::Event(_) => None,
+            })
+            .collect()
+    }
+
+    /// Chunk text streamed after the first `skip` timeline entries.
+    fn text_after(&self, skip: usize) -> String {
+        self.timeline
+            .iter()
+            .skip(skip)
+            .filter_map(|observed| match observed {
+                Observed::Event(RunEvent::Chunk { text, .. }) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn terminal(&self) -> Option<&RunEvent> {
+        self.events().filter(|event| event.is_terminal()).last()
+    }
+
+    /// Records a failure unless the run returned `Ok` and ended with a
+    /// terminal event matching `expected`.
+    fn expect_terminal(
+        &self,
+        name: &str,
+        expected: impl Fn(&RunEvent) -> bool,
+        failures: &mut Vec<String>,
+    ) {
+        match (&self.result, self.terminal()) {
+            (Err(error), _) => failures.push(format!(
+                "run returned Err({error:?}), expected a {name} terminal event"
+            )),
+            (Ok(()), Some(terminal)) if expected(terminal) => {}
+            (Ok(()), terminal) => failures.push(format!(
+                "expected a {name} terminal event, got {terminal:?}"
+            )),
+        }
+    }
+}
+
+fn request(run_id: RunId) -> RunRequest {
+    RunRequest {
+        run_id,
+        messages: vec![RunMessage::UserText {
+            text: "Run the provider conformance case.".to_string(),
+        }],
+        instructions: "You are under a provider conformance test.".to_string(),
+        output_schema: None,
+        allowed_tools: None,
+        prompt_cache: None,
+    }
+}
+
+/// Runs the provider on a worker thread so panics and hangs become failures.
+fn observe_run(
+    provider: Box<dyn RunProvider>,
+    run_id: RunId,
+    cancel_at: CancelAt,
+) -> Result<Observation, String> {
+    let cancel = Arc::new(AtomicBool::new(cancel_at == CancelAt::BeforeStart));
+    let worker_cancel = Arc::clone(&cancel);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            let timeline = RefCell::new(Vec::new());
+            let result = provider.run(
+                request(run_id),
+                Arc::clone(&worker_cancel),
+                &mut |call| {
+                    timeline.borrow_mut().push(Observed::ToolCall(call.clone()));
+                    if cancel_at == CancelAt::ToolCall {
+                        worker_cancel.store(true, Ordering::SeqCst);
+                    }
+                    ToolResult::success(call.call_id, call.tool_name, CONFORMANCE_TOOL_OUTPUT)
+                },
+                &mut |event| {
+                    if cancel_at == CancelAt::FirstChunk && matches!(event, RunEvent::Chunk { .. })
+                    {
+                        worker_cancel.store(true, Ordering::SeqCst);
+                    }
+                    timeline.borrow_mut().push(Observed::Event(event));
+                },
+            );
+            (timeline.into_inner(), result)
+        }));
+        let _ = sender.send(outcome.map_err(|payload| panic_message(payload.as_ref())));
+    });
+
+    match receiver.recv_timeout(CASE_TIMEOUT) {
+        Ok(Ok((timeline, result))) => Ok(Observation {
+            run_id,
+            timeline,
+            result,
+        }),
+        Ok(Err(message)) => Err(format!("provider panicked: {message}")),
+        Err(_) => {
+            cancel.store(true, Ordering::SeqCst);
+            Err(format!(
+                "run did not return within {}s",
+                CASE_TIMEOUT.as_secs()
+            ))
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+fn check_lifecycle(observation: &Observation, failures: &mut Vec<String>) {
+    if let Some(event) = observation
+        .events()
+        .find(|event| event.run_id() != observation.run_id)
+    {
+        failures.push(format!(
+            "event {event:?} does not carry the request's run id {}",
+            observation.run_id
+        ));
+    }
+
+    let terminal_positions: Vec<usize> = observation
+        .timeline
+        .iter()
+        .enumerate()
+        .filter(|(_, observed)| matches!(observed, Observed::Event(event) if event.is_terminal()))
+        .map(|(index, _)| index)
+        .collect();
+
+    if let Err(error) = &observation.result {
+        if !terminal_positions.is_empty() {
+            failures.push(format!(
+                "run returned Err({error:?}) after emitting a terminal event"
+            ));
+        }
+        return;
+    }
+
+    if !matches!(
+        observation.timeline.first(),
+        Some(Observed::Event(RunEvent::Started { .. }))
+    ) {
+        failures.push("run did not begin with Started".to_string());
+    }
+    let started = observation
+        .events()
+        .filter(|event| matches!(event, RunEvent::Started { .. }))
+        .count();
+    if started > 1 {
+        failures.push(format!("run emitted Started {started} times"));
+    }
+    match terminal_positions.as_slice() {
+        [position] if *position + 1 == observation.timeline.len() => {}
+        [_] => {
+            failures.push("run emitted events or tool calls after its terminal event".to_string())
+        }
+        positions => failures.push(format!(
+            "run emitted {} terminal events, expected exactly one",
+            positions.len()
+        )),
+    }
+}
+
+fn check_lifecycle_ordering(
+    fixture: &Fixture,
+    observation: &Observation,
+    failures: &mut Vec<String>,
+) {
+    observation.expect_terminal(
+        "Finished",
+        |event| matches!(event, RunEvent::Finished { .. }),
+        failures,
+    );
+    if let Fixture::Text { chunks } = fixture {
+        let expected = chunks.concat();
+        let streamed = observation.text_after(0);
+        if streamed != expected {
+            failures.push(format!(
+                "streamed text {streamed:?} does not match the fixture's {expected:?}"
+            ));
+        }
+    }
+    if !observation.tool_calls().is_empty() {
+        failures.push("text-only run requested a tool call".to_string());
+    }
+}
+
+fn check_cancel_before_start(_: &Fixture, observation: &Observation, failures: &mut Vec<String>) {
+    observation.expect_terminal(
+        "Cancelled",
+        |event| matches!(event, RunEvent::Cancelled { .. }),
+        failures,
+    );
+    if !observation.text_after(0).is_empty() {
+        failures.push("run streamed text although it was cancelled before starting".to_string());
+    }
+    if !observation.tool_calls().is_empty() {
+        failures.push(
+            "run requested a tool call although it was cancelled before starting".to_string(),
+        );
+    }
+}
+
+fn check_cancel_mid_stream(_: &Fixture, observation: &Observation, failures: &mut Vec<String>) {
+    if observation.text_after(0).is_empty() {
+        failures.push("run never streamed a chunk, so it was not cancelled mid-stream".to_string());
+    }
+    observation.expect_terminal(
+        "Cancelled",
+        |event| matches!(event, RunEvent::Cancelled { .. }),
+        failures,
+    );
+}
+
+fn check_cancel_during_tool_call(
+    _: &Fixture,
+    observation: &Observation,
+    failures: &mut Vec<String>,
+) {
+    let calls = observation.tool_calls();
+    if calls.len() != 1 {
+        failures.push(format!(
+            "expected exactly one tool call before cancellation, got {}",
+            calls.len()
+        ));
+    }
+    observation.expect_terminal(
+        "Cancelled",
+        |event| matches!(event, RunEvent::Cancelled { .. }),
+        failures,
+    );
+    if let Some(position) = observation
+        .timeline
+        .iter()
+        .position(|observed| matches!(observed, Observed::ToolCall(_)))
+    {
+        if !observation.text_after(position + 1).is_empty() {
+            failures
+                .push("run kept streaming after being cancelled during a tool call".to_string());
+        }
+    }
+}
+
+fn check_tool_roundtrip(fixture: &Fixture, observation: &Observation, failures: &mut Vec<String>) {
+    observation.expect_terminal(
+        "Finished",
+        |event| matches!(event, RunEvent::Finished { .. }),
+        failures,
+    );
+    let Fixture::ToolRoundtrip { call, reply } = fixture else {
+        return;
+    };
+    match observation.tool_calls().as_slice() {
+        [observed] if *observed == call => {}
+        [observed] => failures.push(format!(
+            "tool call {observed:?} does not match the requested {call:?}"
+        )),
+        calls => failures.push(format!(
+            "expected exactly one tool call, got {}",
+            calls.len()
+        )),
+    }
+    if let Some(position) = observation
+        .timeline
+        .iter()
+        .position(|observed| matches!(observed, Observed::ToolCall(_)))
+    {
+        let after = observation.text_after(position + 1);
+        if !after.contains(reply.as_str()) {
+            failures.push(format!(
+                "reply {reply:?} was not streamed after the tool result (got {after:?})"
+            ));
+        }
+    }
+}
+
+fn check_malformed_payload(_: &Fixture, observation: &Observation, failures: &mut Vec<String>) {
+    if !observation.tool_calls().is_empty() {
+        failures.push("malformed payload reached the host as a tool call".to_string());
+    }
+    if observation.result.is_ok() {
+        observation.expect_terminal(
+            "Failed",
+            |event| matches!(event, RunEvent::Failed { error, .. } if !error.trim().is_empty()),
+            failures,
+        );
+    }
+}
+
+fn check_upstream_failure(
+    fixture: &Fixture,
+    observation: &Observation,
+    failures: &mut Vec<String>,
+) {
+    let Fixture::UpstreamFailed { message } = fixture else {
+        return;
+    };
+    observation.expect_terminal(
+        "Failed",
+        |event| matches!(event, RunEvent::Failed { error, .. } if error.contains(message.as_str())),
+        failures,
+    );
+}
+
+fn check_upstream_cancelled(_: &Fixture, observation: &Observation, failures: &mut Vec<String>) {
+    observation.expect_terminal(
+        "Cancelled",
+        |event| matches!(event, RunEvent::Cancelled { .. }),
+        failures,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use agent_provider::{CancelSignal, ProviderProfile};
+
+    use super::*;
+
+    /// Emits a chunk and two terminal events without ever starting.
+    struct SloppyProvider;
+
+    impl RunProvider for SloppyProvider {
+        fn profile(&self) -> ProviderProfile {
+            ProviderProfile {
+                provider_id: "sloppy".to_string(),
+                model_id: "sloppy".to_string(),
+                thinking_level: None,
+            }
+        }
+
+        fn run(
+            &self,
+            req: RunRequest,
+            _cancel: CancelSignal,
+            _execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+            emit: &mut dyn FnMut(RunEvent),
+        ) -> Result<(), String> {
+            emit(RunEvent::Chunk {
+                run_id: req.run_id + 100,
+                text: "hi".to_string(),
+            });
+            emit(RunEvent::Finished { run_id: req.run_id });
+            emit(RunEvent::Finished { run_id: req.run_id });
+            Ok(())
+        }
+    }
+
+    struct PanickingProvider;
+
+    impl RunProvider for PanickingProvider {
+        fn profile(&self) -> ProviderProfile {
+            SloppyProvider.profile()
+        }
+
+        fn run(
+            &self,
+            _req: RunRequest,
+            _cancel: CancelSignal,
+            _execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+            _emit: &mut dyn FnMut(RunEvent),
+        ) -> Result<(), String> {
+            panic!("adapter bug")
+        }
+    }
+
+    #[test]
+    fn lifecycle_violations_are_reported_per_case() {
+        let report = run_conformance_suite(|fixture| match fixture {
+            Fixture::Text { .. } => Some(Box::new(SloppyProvider) as Box<dyn RunProvider>),
+            _ => None,
+        });
+
+        let failed: Vec<_> = report.failures().map(|outcome| outcome.case).collect();
+        assert_eq!(failed, vec!["lifecycle_ordering", "cancel_before_start"]);
+        let CaseStatus::Failed(failures) = &report.outcomes[0].status else {
+            panic!("lifecycle_ordering should fail");
+        };
+        assert!(failures.iter().any(|failure| failure.contains("run id 1")));
+        assert!(failures
+            .iter()
+            .any(|failure| failure == "run did not begin with Started"));
+        assert!(failures
+            .iter()
+            .any(|failure| failure == "run emitted 2 terminal events, expected exactly one"));
+        assert_eq!(report.skipped().len(), 6);
+        assert!(report.to_string().contains("lifecycle_ordering: FAILED"));
+    }
+
+    #[test]
+    fn panicking_providers_fail_instead_of_aborting_the_suite() {
+        let report = run_conformance_suite(|fixture| {
+            matches!(fixture, Fixture::UpstreamCancelled)
+                .then(|| Box::new(PanickingProvider) as Box<dyn RunProvider>)
+        });
+
+        assert_eq!(
+            report.outcomes.last().map(|outcome| &outcome.status),
+            Some(&CaseStatus::Failed(vec![
+                "provider panicked: adapter bug".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "provider conformance failed")]
+    fn a_fully_skipped_suite_does_not_pass() {
+        run_conformance_suite(|_| None).assert_passed();
+    }
+}
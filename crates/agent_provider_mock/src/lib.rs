@@ -2,16 +2,26 @@
 //!
 //! This crate contains no transport/protocol logic and is intended for local
 //! development and contract-level integration testing.
+//!
+//! By default every run streams the same chunks. A [`MockScenario`] instead
+//! scripts each run step by step (chunks, delays, usage, tool calls, failed
+//! attempts, failures, and cancellation points); see [`scenario`].
+
+pub mod scenario;
 
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use agent_provider::{
-    CancelSignal, ProviderProfile, RunEvent, RunProvider, RunRequest, ToolCallRequest, ToolResult,
+    CancelSignal, ModelInfo, ProviderProfile, RunEvent, RunProvider, RunRequest, TokenUsage,
+    ToolCallRequest, ToolResult,
 };
 
+use scenario::{tool_result_text, SeededRng};
+pub use scenario::{MockScenario, ScenarioStep, ToolResultExpectation};
+
 /// Stable provider identifier used for explicit startup selection.
 pub const MOCK_PROVIDER_ID: &str = "mock";
 
@@ -21,10 +31,23 @@ struct SelectionState {
     thinking_index: usize,
 }
 
+#[derive(Debug)]
+enum MockScript {
+    /// Every run streams these chunks token by token.
+    Chunks(Vec<String>),
+    /// Each run takes the next scripted run.
+    Scenario {
+        scenario: MockScenario,
+        next_run: AtomicUsize,
+    },
+}
+
 /// Deterministic mock provider used by `coding_agent` tests and local runs.
 #[derive(Debug)]
 pub struct MockProvider {
-    chunks: Vec<String>,
+    script: MockScript,
+    /// Results the host returned for scripted tool calls, in call order.
+    tool_results: Mutex<Vec<ToolResult>>,
     model_ids: Vec<String>,
     thinking_levels: Vec<Option<String>>,
     selection: Mutex<SelectionState>,
@@ -52,7 +75,8 @@ impl MockProvider {
         let thinking_levels = sanitize_thinking_levels(thinking_levels);
 
         Self {
-            chunks,
+            script: MockScript::Chunks(chunks),
+            tool_results: Mutex::new(Vec::new()),
             model_ids,
             thinking_levels,
             selection: Mutex::new(SelectionState {
@@ -62,6 +86,25 @@ impl MockProvider {
         }
     }
 
+    /// Creates a mock provider that serves the runs scripted in `scenario`,
+    /// in order, with default profile options. Runs past the end fail.
+    #[must_use]
+    pub fn with_scenario(scenario: MockScenario) -> Self {
+        let mut provider = Self::new(Vec::new());
+        provider.script = MockScript::Scenario {
+            scenario,
+            next_run: AtomicUsize::new(0),
+        };
+        provider
+    }
+
+    /// Results the host returned for scripted tool calls so far, across
+    /// runs and in call order.
+    #[must_use]
+    pub fn tool_results(&self) -> Vec<ToolResult> {
+        lock_unpoisoned(&self.tool_results).clone()
+    }
+
     fn profile_for_selection(&self, selection: &SelectionState) -> ProviderProfile {
         ProviderProfile {
             provider_id: MOCK_PROVIDER_ID.to_string(),
@@ -148,17 +191,98 @@ impl RunProvider for MockProvider {
         Ok(self.profile_for_selection(&selection))
     }
 
+    fn set_model(&self, model_id: &str) -> Result<ProviderProfile, String> {
+        let model_id = model_id.trim();
+        let Some(index) = self.model_ids.iter().position(|id| id == model_id) else {
+            return Err(format!(
+                "Unknown model '{model_id}'; available models: {}",
+                self.model_ids.join(", ")
+            ));
+        };
+
+        let mut selection = lock_unpoisoned(&self.selection);
+        selection.model_index = index;
+        Ok(self.profile_for_selection(&selection))
+    }
+
+    fn set_thinking_level(&self, level: &str) -> Result<ProviderProfile, String> {
+        let level = level.trim();
+        let labels: Vec<&str> = self
+            .thinking_levels
+            .iter()
+            .map(|level| level.as_deref().unwrap_or("none"))
+            .collect();
+        let Some(index) = labels.iter().position(|label| *label == level) else {
+            return Err(format!(
+                "Unknown thinking level '{level}'; available levels: {}",
+                labels.join(", ")
+            ));
+        };
+
+        let mut selection = lock_unpoisoned(&self.selection);
+        selection.thinking_index = index;
+        Ok(self.profile_for_selection(&selection))
+    }
+
+    fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        Ok(self
+            .model_ids
+            .iter()
+            .map(|model_id| ModelInfo {
+                model_id: model_id.clone(),
+                display_name: None,
+                context_window: None,
+                available: true,
+            })
+            .collect())
+    }
+
     fn run(
         &self,
         req: RunRequest,
         cancel: CancelSignal,
-        _execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
         emit: &mut dyn FnMut(RunEvent),
     ) -> Result<(), String> {
         let run_id = req.run_id;
         let _ = req.messages;
         let _ = req.instructions;
 
+        let chunks = match &self.script {
+            MockScript::Chunks(chunks) => chunks,
+            MockScript::Scenario { scenario, next_run } => {
+                let run_index = next_run.fetch_add(1, Ordering::SeqCst);
+                emit(RunEvent::Started { run_id });
+                let Some(steps) = scenario.runs.get(run_index) else {
+                    emit(RunEvent::Failed {
+                        run_id,
+                        error: format!(
+                            "Mock scenario has no run {} (it scripts {})",
+                            run_index + 1,
+                            scenario.runs.len()
+                        ),
+                    });
+                    return Ok(());
+                };
+                let mut rng = SeededRng::new(scenario.seed, run_index);
+                let mut execute_and_record = |call| {
+                    let result = execute_tool(call);
+                    lock_unpoisoned(&self.tool_results).push(result.clone());
+                    result
+                };
+                run_scenario_steps(
+                    run_id,
+                    run_index,
+                    steps,
+                    &mut rng,
+                    &cancel,
+                    &mut execute_and_record,
+                    emit,
+                );
+                return Ok(());
+            }
+        };
+
         emit(RunEvent::Started { run_id });
         thread::sleep(Duration::from_millis(Self::RUN_DELAY_MS));
 
@@ -167,7 +291,7 @@ impl RunProvider for MockProvider {
             return Ok(());
         }
 
-        for chunk in &self.chunks {
+        for chunk in chunks {
             if cancel.load(Ordering::SeqCst) {
                 emit(RunEvent::Cancelled { run_id });
                 return Ok(());
@@ -210,6 +334,136 @@ impl RunProvider for MockProvider {
     }
 }
 
+/// How often blocking steps poll the cancel signal.
+const CANCEL_POLL: Duration = Duration::from_millis(5);
+
+/// Plays one scripted run after `Started`, always ending in a terminal event.
+fn run_scenario_steps(
+    run_id: u64,
+    run_index: usize,
+    steps: &[ScenarioStep],
+    rng: &mut SeededRng,
+    cancel: &CancelSignal,
+    execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+    emit: &mut dyn FnMut(RunEvent),
+) {
+    let mut tool_calls = 0;
+    for step in steps {
+        if cancel.load(Ordering::SeqCst) {
+            emit(RunEvent::Cancelled { run_id });
+            return;
+        }
+
+        match step {
+            ScenarioStep::Chunk { text } => emit(RunEvent::Chunk {
+                run_id,
+                text: text.clone(),
+            }),
+            ScenarioStep::Delay { ms, jitter_ms } => {
+                let delay = Duration::from_millis(ms.saturating_add(rng.up_to(*jitter_ms)));
+                if wait_for_cancel(cancel, delay) {
+                    emit(RunEvent::Cancelled { run_id });
+                    return;
+                }
+            }
+            ScenarioStep::Usage {
+                input_tokens,
+                cached_input_tokens,
+                output_tokens,
+            } => emit(RunEvent::Usage {
+                run_id,
+                usage: TokenUsage {
+                    input_tokens: *input_tokens,
+                    cached_input_tokens: *cached_input_tokens,
+                    output_tokens: *output_tokens,
+                },
+            }),
+            ScenarioStep::ToolCall {
+                call_id,
+                tool_name,
+                arguments,
+                expect,
+                echo_result,
+            } => {
+                tool_calls += 1;
+                let call_id = call_id
+                    .clone()
+                    .unwrap_or_else(|| format!("mock-call-{}-{tool_calls}", run_index + 1));
+                let result = execute_tool(ToolCallRequest {
+                    call_id,
+                    tool_name: tool_name.clone(),
+                    arguments: arguments.clone(),
+                });
+                if let Some(error) = expect.as_ref().and_then(|expect| expect.mismatch(&result)) {
+                    emit(RunEvent::Failed { run_id, error });
+                    return;
+                }
+                if *echo_result {
+                    emit(RunEvent::Chunk {
+                        run_id,
+                        text: tool_result_text(&result),
+                    });
+                }
+            }
+            ScenarioStep::AttemptFailed {
+                attempt,
+                status,
+                error,
+                will_retry,
+            } => emit(RunEvent::AttemptFailed {
+                run_id,
+                attempt: *attempt,
+                status: *status,
+                error: error.clone(),
+                will_retry: *will_retry,
+            }),
+            ScenarioStep::Fail { error } => {
+                emit(RunEvent::Failed {
+                    run_id,
+                    error: error.clone(),
+                });
+                return;
+            }
+            ScenarioStep::WaitForCancel { timeout_ms } => {
+                if wait_for_cancel(cancel, Duration::from_millis(*timeout_ms)) {
+                    emit(RunEvent::Cancelled { run_id });
+                } else {
+                    emit(RunEvent::Failed {
+                        run_id,
+                        error: format!("Mock scenario waited {timeout_ms}ms for cancellation"),
+                    });
+                }
+                return;
+            }
+            ScenarioStep::Finish => {
+                emit(RunEvent::Finished { run_id });
+                return;
+            }
+        }
+    }
+
+    if cancel.load(Ordering::SeqCst) {
+        emit(RunEvent::Cancelled { run_id });
+    } else {
+        emit(RunEvent::Finished { run_id });
+    }
+}
+
+/// Sleeps up to `duration`; true as soon as `cancel` is set.
+fn wait_for_cancel(cancel: &CancelSignal, duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep(CANCEL_POLL.min(deadline - now));
+    }
+}
+
 fn sanitize_model_ids(model_ids: Vec<String>) -> Vec<String> {
     let mut sanitized: Vec<String> = model_ids
         .into_iter()
@@ -259,6 +513,7 @@ mod tests {
     use std::sync::Arc;
 
     use agent_provider::RunMessage;
+    use agent_provider_conformance::{run_conformance_suite, Fixture};
 
     use super::*;
 
@@ -272,6 +527,9 @@ mod tests {
                         text: "test".to_string(),
                     }],
                     instructions: "system instructions".to_string(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
                 },
                 cancel,
                 &mut |_call| ToolResult::error("unused", "unused", "not used in mock tests"),
@@ -306,6 +564,39 @@ mod tests {
         assert_ne!(thinking_switched.thinking_level, initial.thinking_level);
     }
 
+    #[test]
+    fn set_hooks_select_listed_options_and_reject_others() {
+        let provider = MockProvider::with_profile_options(
+            Vec::new(),
+            vec!["mock".to_string(), "mock-large".to_string()],
+            vec![Some("fast".to_string()), None],
+        );
+
+        let profile = provider.set_model("mock-large").expect("listed model");
+        assert_eq!(profile.model_id, "mock-large");
+        let profile = provider.set_thinking_level("none").expect("listed level");
+        assert_eq!(profile.thinking_level, None);
+
+        let error = provider.set_model("other").expect_err("unlisted model");
+        assert_eq!(
+            error,
+            "Unknown model 'other'; available models: mock, mock-large"
+        );
+        let error = provider
+            .set_thinking_level("slow")
+            .expect_err("unlisted level");
+        assert!(error.ends_with("available levels: fast, none"), "{error}");
+        assert_eq!(provider.profile().model_id, "mock-large");
+
+        let listed: Vec<String> = provider
+            .list_models()
+            .expect("mock models are listable")
+            .into_iter()
+            .map(|model| model.model_id)
+            .collect();
+        assert_eq!(listed, vec!["mock", "mock-large"]);
+    }
+
     #[test]
     fn run_emits_started_chunks_and_finished() {
         let provider = MockProvider::new(vec!["one two".to_string()]);
@@ -351,4 +642,270 @@ mod tests {
         assert_eq!(profile.model_id, "mock");
         assert_eq!(profile.thinking_level.as_deref(), Some("balanced"));
     }
+
+    #[test]
+    fn scenario_fixtures_parse_as_json_or_jsonl() {
+        let json = MockScenario::parse(
+            r#"{"seed": 3, "runs": [[{"type": "chunk", "text": "hi"}, {"type": "finish"}]]}"#,
+        )
+        .expect("json fixture should parse");
+        assert_eq!(json.seed, 3);
+        assert_eq!(
+            json.runs,
+            vec![vec![
+                ScenarioStep::Chunk {
+                    text: "hi".to_string()
+                },
+                ScenarioStep::Finish,
+            ]]
+        );
+
+        let jsonl = MockScenario::parse(
+            "[{\"type\": \"tool_call\", \"tool_name\": \"read\"}]\n\n[{\"type\": \"fail\", \"error\": \"boom\"}]\n",
+        )
+        .expect("jsonl fixture should parse");
+        assert_eq!(jsonl.runs.len(), 2);
+        assert!(matches!(
+            &jsonl.runs[0][0],
+            ScenarioStep::ToolCall { call_id: None, tool_name, arguments, expect: None, echo_result: false }
+                if tool_name == "read" && arguments.as_object().is_some_and(|map| map.is_empty())
+        ));
+
+        let error = MockScenario::parse("[{\"type\": \"explode\"}]").expect_err("unknown step");
+        assert!(error.contains("line 1"));
+    }
+
+    #[test]
+    fn scenario_runs_play_in_order_and_route_tool_calls() {
+        let provider = MockProvider::with_scenario(MockScenario::new(vec![
+            vec![
+                ScenarioStep::Chunk {
+                    text: "reading".to_string(),
+                },
+                ScenarioStep::ToolCall {
+                    call_id: None,
+                    tool_name: "read".to_string(),
+                    arguments: serde_json::json!({"path": "a.txt"}),
+                    expect: None,
+                    echo_result: false,
+                },
+                ScenarioStep::AttemptFailed {
+                    attempt: 1,
+                    status: Some(503),
+                    error: "overloaded".to_string(),
+                    will_retry: true,
+                },
+                ScenarioStep::Usage {
+                    input_tokens: 10,
+                    cached_input_tokens: 0,
+                    output_tokens: 2,
+                },
+            ],
+            vec![ScenarioStep::Fail {
+                error: "boom".to_string(),
+            }],
+        ]));
+        let mut calls = Vec::new();
+        let mut events = Vec::new();
+        let run = |events: &mut Vec<RunEvent>, calls: &mut Vec<ToolCallRequest>| {
+            provider
+                .run(
+                    RunRequest {
+                        run_id: 7,
+                        messages: Vec::new(),
+                        instructions: String::new(),
+                        output_schema: None,
+                        allowed_tools: None,
+                        prompt_cache: None,
+                    },
+                    Arc::new(AtomicBool::new(false)),
+                    &mut |call| {
+                        calls.push(call.clone());
+                        ToolResult::success(call.call_id, call.tool_name, "ok")
+                    },
+                    &mut |event| events.push(event),
+                )
+                .expect("scenario run should succeed");
+        };
+
+        run(&mut events, &mut calls);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].call_id, "mock-call-1-1");
+        assert_eq!(calls[0].arguments["path"], "a.txt");
+        assert!(matches!(events[0], RunEvent::Started { run_id: 7 }));
+        assert!(matches!(&events[1], RunEvent::Chunk { text, .. } if text == "reading"));
+        assert!(matches!(
+            events[2],
+            RunEvent::AttemptFailed {
+                status: Some(503),
+                will_retry: true,
+                ..
+            }
+        ));
+        assert!(matches!(events[3], RunEvent::Usage { .. }));
+        assert!(matches!(events[4], RunEvent::Finished { run_id: 7 }));
+
+        events.clear();
+        run(&mut events, &mut calls);
+        assert!(matches!(&events[1], RunEvent::Failed { error, .. } if error == "boom"));
+
+        events.clear();
+        run(&mut events, &mut calls);
+        assert!(matches!(
+            &events[1],
+            RunEvent::Failed { error, .. } if error.contains("no run 3")
+        ));
+    }
+
+    #[test]
+    fn scenario_cancellation_points_wait_for_the_host() {
+        let provider = MockProvider::with_scenario(MockScenario::new(vec![
+            vec![
+                ScenarioStep::Chunk {
+                    text: "partial".to_string(),
+                },
+                ScenarioStep::WaitForCancel { timeout_ms: 5_000 },
+            ],
+            vec![ScenarioStep::WaitForCancel { timeout_ms: 0 }],
+        ]));
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let canceller = {
+            let cancel = Arc::clone(&cancel);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                cancel.store(true, Ordering::SeqCst);
+            })
+        };
+        let events = collect_events(&provider, cancel);
+        canceller.join().expect("canceller thread");
+        assert!(matches!(
+            events.last(),
+            Some(RunEvent::Cancelled { run_id: 7 })
+        ));
+
+        let events = collect_events(&provider, Arc::new(AtomicBool::new(false)));
+        assert!(matches!(
+            events.last(),
+            Some(RunEvent::Failed { error, .. }) if error.contains("waited 0ms")
+        ));
+    }
+
+    #[test]
+    fn seeded_jitter_is_reproducible() {
+        let draws = |seed| {
+            let mut rng = SeededRng::new(seed, 2);
+            (0..4).map(|_| rng.up_to(100)).collect::<Vec<_>>()
+        };
+        assert_eq!(draws(42), draws(42));
+        assert_ne!(draws(42), draws(43));
+        assert!(draws(42).iter().all(|value| *value <= 100));
+    }
+
+    #[test]
+    fn scripted_tool_calls_check_expectations_and_echo_results() {
+        let provider = MockProvider::with_scenario(
+            MockScenario::parse(concat!(
+                r#"[{"type": "tool_call", "tool_name": "write", "expect": {"is_error": true, "contains": "rejected"}, "echo_result": true}, {"type": "chunk", "text": " ok"}]"#,
+                "\n",
+                r#"[{"type": "tool_call", "tool_name": "write", "expect": {}}, {"type": "chunk", "text": "unreachable"}]"#,
+            ))
+            .expect("fixture should parse"),
+        );
+        let run = |events: &mut Vec<RunEvent>| {
+            provider
+                .run(
+                    RunRequest {
+                        run_id: 7,
+                        messages: Vec::new(),
+                        instructions: String::new(),
+                        output_schema: None,
+                        allowed_tools: None,
+                        prompt_cache: None,
+                    },
+                    Arc::new(AtomicBool::new(false)),
+                    &mut |call| ToolResult::error(call.call_id, call.tool_name, "write rejected"),
+                    &mut |event| events.push(event),
+                )
+                .expect("scenario run should succeed");
+        };
+
+        let mut events = Vec::new();
+        run(&mut events);
+        let chunks: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                RunEvent::Chunk { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(chunks, vec!["write rejected", " ok"]);
+        assert!(matches!(events.last(), Some(RunEvent::Finished { .. })));
+
+        events.clear();
+        run(&mut events);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[1],
+            RunEvent::Failed { error, .. }
+                if error == "Mock tool call mock-call-2-1 (write) expected success but returned an error: write rejected"
+        ));
+
+        let results = provider.tool_results();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.is_error));
+        assert_eq!(results[1].call_id, "mock-call-2-1");
+    }
+
+    #[test]
+    fn scenario_provider_passes_the_conformance_suite() {
+        let report = run_conformance_suite(|fixture| {
+            let chunks = |chunks: &[String]| {
+                chunks
+                    .iter()
+                    .map(|text| ScenarioStep::Chunk { text: text.clone() })
+                    .collect::<Vec<_>>()
+            };
+            let steps = match fixture {
+                Fixture::Text { chunks: text } => {
+                    let mut steps = chunks(text);
+                    steps.push(ScenarioStep::Finish);
+                    steps
+                }
+                Fixture::ToolRoundtrip { call, reply } => vec![
+                    ScenarioStep::ToolCall {
+                        call_id: Some(call.call_id.clone()),
+                        tool_name: call.tool_name.clone(),
+                        arguments: call.arguments.clone(),
+                        expect: None,
+                        echo_result: false,
+                    },
+                    ScenarioStep::Chunk {
+                        text: reply.clone(),
+                    },
+                    ScenarioStep::Finish,
+                ],
+                Fixture::Stall { chunks: text } => {
+                    let mut steps = chunks(text);
+                    steps.push(ScenarioStep::WaitForCancel { timeout_ms: 5_000 });
+                    steps
+                }
+                Fixture::UpstreamFailed { message } => vec![ScenarioStep::Fail {
+                    error: message.clone(),
+                }],
+                // No wire format to corrupt and no upstream status to map.
+                Fixture::MalformedPayload | Fixture::UpstreamCancelled => return None,
+            };
+            Some(
+                Box::new(MockProvider::with_scenario(MockScenario::new(vec![steps])))
+                    as Box<dyn RunProvider>,
+            )
+        });
+
+        report.assert_passed();
+        assert_eq!(
+            report.skipped(),
+            vec!["malformed_payload", "upstream_cancelled"]
+        );
+    }
 }
@@ -0,0 +1,212 @@
+//! Scripted runs for [`MockProvider::with_scenario`](crate::MockProvider::with_scenario).
+//!
+//! A scenario lists the steps of each run the provider will serve, in order:
+//! the first run executes `runs[0]`, the next `runs[1]`, and so on. Fixtures
+//! are either one JSON object
+//!
+//! ```json
+//! {"seed": 7, "runs": [[{"type": "chunk", "text": "hi"}, {"type": "finish"}]]}
+//! ```
+//!
+//! or JSONL with one run (a JSON array of steps) per line. Every run starts
+//! with `Started`; the provider checks the cancel signal before each step and
+//! ends with `Finished` if the steps run out without a terminal step.
+//!
+//! Tool-call steps go through the host's real `execute_tool`, so approval and
+//! transcript paths run end to end. A step can `expect` the result
+//! (`{"is_error": true, "contains": "rejected"}`) and fail the run on a
+//! mismatch, and `echo_result` streams the result back as a chunk;
+//! [`MockProvider::tool_results`](crate::MockProvider::tool_results) keeps
+//! every result for later assertions.
+
+use std::fs;
+use std::path::Path;
+
+use agent_provider::ToolResult;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// One step of a scripted run.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ScenarioStep {
+    /// Emits one `Chunk` with exactly this text.
+    Chunk { text: String },
+    /// Sleeps `ms` plus a seeded pseudo-random `0..=jitter_ms`, ending the run
+    /// as cancelled if the host cancels meanwhile.
+    Delay {
+        ms: u64,
+        #[serde(default)]
+        jitter_ms: u64,
+    },
+    Usage {
+        input_tokens: u64,
+        #[serde(default)]
+        cached_input_tokens: u64,
+        output_tokens: u64,
+    },
+    /// Routes a tool call through the host's `execute_tool`. `call_id`
+    /// defaults to `mock-call-<run>-<n>`.
+    ToolCall {
+        #[serde(default)]
+        call_id: Option<String>,
+        tool_name: String,
+        #[serde(default = "empty_arguments")]
+        arguments: Value,
+        /// Fails the run when the host's result does not match.
+        #[serde(default)]
+        expect: Option<ToolResultExpectation>,
+        /// Streams the result content back as a chunk, standing in for the
+        /// model reading it.
+        #[serde(default)]
+        echo_result: bool,
+    },
+    /// Emits a non-terminal `AttemptFailed`.
+    AttemptFailed {
+        attempt: u32,
+        #[serde(default)]
+        status: Option<u16>,
+        error: String,
+        #[serde(default)]
+        will_retry: bool,
+    },
+    /// Ends the run with `Failed`.
+    Fail { error: String },
+    /// Blocks until the host cancels, then ends the run with `Cancelled`;
+    /// fails the run if no cancellation arrives within `timeout_ms`.
+    WaitForCancel {
+        #[serde(default = "default_cancel_timeout_ms")]
+        timeout_ms: u64,
+    },
+    /// Ends the run with `Finished`.
+    Finish,
+}
+
+/// What a scripted tool call expects the host to return.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolResultExpectation {
+    #[serde(default)]
+    pub is_error: bool,
+    /// Substring the result content must contain.
+    #[serde(default)]
+    pub contains: Option<String>,
+}
+
+impl ToolResultExpectation {
+    /// Describes how `result` falls short, or `None` when it matches.
+    pub fn mismatch(&self, result: &ToolResult) -> Option<String> {
+        let content = tool_result_text(result);
+        if result.is_error != self.is_error {
+            let (expected, actual) = if self.is_error {
+                ("an error", "success")
+            } else {
+                ("success", "an error")
+            };
+            return Some(format!(
+                "Mock tool call {} ({}) expected {expected} but returned {actual}: {content}",
+                result.call_id, result.tool_name
+            ));
+        }
+        match &self.contains {
+            Some(needle) if !content.contains(needle.as_str()) => Some(format!(
+                "Mock tool call {} ({}) result does not contain '{needle}': {content}",
+                result.call_id, result.tool_name
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Result content as text: strings verbatim, other JSON serialized.
+pub(crate) fn tool_result_text(result: &ToolResult) -> String {
+    match &result.content {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn empty_arguments() -> Value {
+    Value::Object(Default::default())
+}
+
+fn default_cancel_timeout_ms() -> u64 {
+    5_000
+}
+
+/// Runs served by a scripted [`MockProvider`](crate::MockProvider).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockScenario {
+    /// Seeds delay jitter; the same seed replays the same timings.
+    #[serde(default)]
+    pub seed: u64,
+    pub runs: Vec<Vec<ScenarioStep>>,
+}
+
+impl MockScenario {
+    #[must_use]
+    pub fn new(runs: Vec<Vec<ScenarioStep>>) -> Self {
+        Self { seed: 0, runs }
+    }
+
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Parses a JSON object fixture or a JSONL fixture with one run per line.
+    pub fn parse(fixture: &str) -> Result<Self, String> {
+        if fixture.trim_start().starts_with('{') {
+            return serde_json::from_str(fixture)
+                .map_err(|error| format!("Invalid mock scenario: {error}"));
+        }
+
+        let runs = fixture
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|error| {
+                    format!("Invalid mock scenario run on line {}: {error}", index + 1)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(runs))
+    }
+
+    /// Reads and parses a fixture file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let fixture = fs::read_to_string(path).map_err(|error| {
+            format!("Failed to read mock scenario '{}': {error}", path.display())
+        })?;
+        Self::parse(&fixture)
+    }
+}
+
+/// splitmix64; small, seedable, and stable across platforms.
+pub(crate) struct SeededRng(u64);
+
+impl SeededRng {
+    pub(crate) fn new(seed: u64, run_index: usize) -> Self {
+        Self(seed ^ (run_index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform-ish value in `0..=max`.
+    pub(crate) fn up_to(&mut self, max: u64) -> u64 {
+        match max.checked_add(1) {
+            Some(bound) => self.next_u64() % bound,
+            None => self.next_u64(),
+        }
+    }
+}
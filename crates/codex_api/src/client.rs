@@ -4,27 +4,38 @@ use std::time::Duration;
 
 use futures_util::StreamExt;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use reqwest::{Client, Response, StatusCode};
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy, Response, StatusCode};
 
-use crate::config::CodexApiConfig;
+use crate::config::{CodexApiConfig, ProxyConfig, TlsConfig};
 use crate::error::{parse_error_message, CodexApiError};
 use crate::events::{CodexResponseStatus, CodexStreamEvent};
 use crate::headers::build_headers;
+use crate::models::{CodexModelInfo, CodexModelsResponse};
 use crate::payload::CodexRequest;
 use crate::retry::is_retryable_http_error;
 use crate::retry::{retry_delay_ms, MAX_RETRIES};
 use crate::sse::SseStreamParser;
-use crate::url::normalize_codex_url;
+use crate::url::{normalize_codex_models_url, normalize_codex_url};
+use crate::wire_log::{WireLog, WireLogConfig};
 
 /// Optional cancellation signal shared across request and stream loops.
 pub type CancellationSignal = Arc<AtomicBool>;
 
 const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(25);
 
+/// How long an idle pooled connection is kept for the next turn's request.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// TCP and HTTP/2 keep-alive probing interval for pooled connections.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+/// How long an HTTP/2 keep-alive ping may go unanswered before the
+/// connection is dropped from the pool.
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug)]
 pub struct CodexApiClient {
     http: Client,
     config: CodexApiConfig,
+    wire_log: Option<WireLog>,
 }
 
 #[derive(Debug, Clone)]
@@ -34,13 +45,33 @@ pub struct StreamResult {
 }
 
 impl CodexApiClient {
+    /// Builds the client and its connection pool. Connections (HTTP/2 when
+    /// the server negotiates it over TLS, keep-alive HTTP/1.1 otherwise) are
+    /// reused across requests as long as they are driven by the same tokio
+    /// runtime, so callers should keep one runtime for the client's lifetime.
     pub fn new(config: CodexApiConfig) -> Result<Self, CodexApiError> {
-        let mut builder = Client::builder();
+        let mut builder = Client::builder()
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(KEEP_ALIVE_INTERVAL)
+            .http2_keep_alive_interval(KEEP_ALIVE_INTERVAL)
+            .http2_keep_alive_timeout(KEEP_ALIVE_TIMEOUT)
+            .http2_keep_alive_while_idle(true);
         if let Some(timeout) = config.timeout {
             builder = builder.timeout(timeout);
         }
+        builder = apply_proxy(builder, &config.proxy)?;
+        builder = apply_tls(builder, &config.tls)?;
         let http = builder.build().map_err(CodexApiError::from)?;
-        Ok(Self { http, config })
+        let wire_log = config
+            .wire_log
+            .clone()
+            .or_else(WireLogConfig::from_env)
+            .map(|wire_log| WireLog::new(wire_log, vec![config.access_token.clone()]));
+        Ok(Self {
+            http,
+            config,
+            wire_log,
+        })
     }
 
     pub fn config(&self) -> &CodexApiConfig {
@@ -82,6 +113,19 @@ impl CodexApiClient {
             .json(&payload))
     }
 
+    /// Traces the request exactly as `build_request` sends it.
+    fn log_request(&self, attempt: u32, request: &CodexRequest) {
+        let Some(wire_log) = &self.wire_log else {
+            return;
+        };
+        let headers = self
+            .build_headers(self.config.user_agent.as_deref())
+            .unwrap_or_default();
+        let body =
+            serde_json::to_value(self.request_with_transport_defaults(request)).unwrap_or_default();
+        wire_log.request(attempt, &self.normalized_endpoint(), &headers, &body);
+    }
+
     fn request_with_transport_defaults(&self, request: &CodexRequest) -> CodexRequest {
         let mut payload = request.clone();
         payload.store = false;
@@ -119,6 +163,21 @@ impl CodexApiClient {
         request: &CodexRequest,
         cancellation: Option<&CancellationSignal>,
     ) -> Result<Response, CodexApiError> {
+        self.send_with_retry_observed(request, cancellation, &mut |_| {})
+            .await
+    }
+
+    /// [`Self::send_with_retry`], reporting each failed attempt as a
+    /// [`CodexStreamEvent::RequestAttemptFailed`].
+    async fn send_with_retry_observed<F>(
+        &self,
+        request: &CodexRequest,
+        cancellation: Option<&CancellationSignal>,
+        on_event: &mut F,
+    ) -> Result<Response, CodexApiError>
+    where
+        F: FnMut(CodexStreamEvent),
+    {
         let mut last_status: Option<StatusCode> = None;
         let mut last_error = None;
 
@@ -128,12 +187,16 @@ impl CodexApiClient {
             }
 
             let response = self.build_request(request)?.send();
+            self.log_request(attempt + 1, request);
             let response = await_or_cancel(response, cancellation)
                 .await?
                 .map_err(CodexApiError::from);
 
             match response {
                 Ok(response) => {
+                    if let Some(wire_log) = &self.wire_log {
+                        wire_log.response(attempt + 1, response.status(), response.headers());
+                    }
                     if response.status().is_success() {
                         return Ok(response);
                     }
@@ -148,12 +211,23 @@ impl CodexApiClient {
                                 .unwrap_or("request failed")
                                 .to_string()
                         });
+                    if let Some(wire_log) = &self.wire_log {
+                        wire_log.error_body(attempt + 1, &body);
+                    }
                     let message = parse_error_message(status, &body);
                     last_error = Some(message.clone());
                     let should_retry_status = is_retryable_http_error(status.as_u16(), &body);
                     let should_retry_message = !has_usage_limit_message(&message);
 
-                    if attempt < MAX_RETRIES && (should_retry_status || should_retry_message) {
+                    let will_retry =
+                        attempt < MAX_RETRIES && (should_retry_status || should_retry_message);
+                    on_event(CodexStreamEvent::RequestAttemptFailed {
+                        attempt: attempt + 1,
+                        status: Some(status.as_u16()),
+                        message: message.clone(),
+                        will_retry,
+                    });
+                    if will_retry {
                         await_or_cancel(tokio::time::sleep(retry_delay_ms(attempt)), cancellation)
                             .await?;
                         continue;
@@ -163,8 +237,18 @@ impl CodexApiClient {
                 }
                 Err(error) => {
                     let message = error.to_string();
+                    if let Some(wire_log) = &self.wire_log {
+                        wire_log.transport_error(attempt + 1, &message);
+                    }
                     last_error = Some(message.clone());
-                    if attempt < MAX_RETRIES && !has_usage_limit_message(&message) {
+                    let will_retry = attempt < MAX_RETRIES && !has_usage_limit_message(&message);
+                    on_event(CodexStreamEvent::RequestAttemptFailed {
+                        attempt: attempt + 1,
+                        status: None,
+                        message,
+                        will_retry,
+                    });
+                    if will_retry {
                         await_or_cancel(tokio::time::sleep(retry_delay_ms(attempt)), cancellation)
                             .await?;
                         continue;
@@ -192,7 +276,9 @@ impl CodexApiClient {
     where
         F: FnMut(CodexStreamEvent),
     {
-        let response = self.send_with_retry(request, cancellation).await?;
+        let response = self
+            .send_with_retry_observed(request, cancellation, &mut on_event)
+            .await?;
         let mut bytes = response.bytes_stream();
         let mut parser = SseStreamParser::default();
         let mut terminal = None;
@@ -205,6 +291,9 @@ impl CodexApiClient {
                 return Err(CodexApiError::Cancelled);
             }
             let chunk = chunk.map_err(CodexApiError::from)?;
+            if let Some(wire_log) = &self.wire_log {
+                wire_log.sse_chunk(&chunk);
+            }
             for event in parser.feed(&chunk) {
                 process_stream_event(event, &mut terminal, &mut on_event)?;
             }
@@ -231,6 +320,56 @@ impl CodexApiClient {
 
         Ok(StreamResult { events, terminal })
     }
+
+    pub fn normalized_models_endpoint(&self) -> String {
+        normalize_codex_models_url(&self.config.base_url)
+    }
+
+    /// Fetches the account's model catalog in a single attempt. Listing is
+    /// advisory, so failures are returned for the caller to fall back on
+    /// rather than retried.
+    pub async fn list_models(
+        &self,
+        cancellation: Option<&CancellationSignal>,
+    ) -> Result<Vec<CodexModelInfo>, CodexApiError> {
+        let endpoint = self.normalized_models_endpoint();
+        let headers = self.build_headers(self.config.user_agent.as_deref())?;
+        if let Some(wire_log) = &self.wire_log {
+            wire_log.request(1, &endpoint, &headers, &serde_json::Value::Null);
+        }
+
+        let response = self.http.get(&endpoint).headers(headers).send();
+        let response = match await_or_cancel(response, cancellation).await? {
+            Ok(response) => response,
+            Err(error) => {
+                if let Some(wire_log) = &self.wire_log {
+                    wire_log.transport_error(1, &error.to_string());
+                }
+                return Err(CodexApiError::from(error));
+            }
+        };
+        let status = response.status();
+        if let Some(wire_log) = &self.wire_log {
+            wire_log.response(1, status, response.headers());
+        }
+
+        let body = await_or_cancel(response.text(), cancellation)
+            .await?
+            .map_err(CodexApiError::from)?;
+        if !status.is_success() {
+            if let Some(wire_log) = &self.wire_log {
+                wire_log.error_body(1, &body);
+            }
+            return Err(CodexApiError::Status(
+                status,
+                parse_error_message(status, &body),
+            ));
+        }
+
+        let listing: CodexModelsResponse =
+            serde_json::from_str(&body).map_err(CodexApiError::Serde)?;
+        Ok(listing.models)
+    }
 }
 
 fn validate_request_payload_shape(request: &CodexRequest) -> Result<(), CodexApiError> {
@@ -371,9 +510,61 @@ where
     }
 }
 
+fn apply_proxy(
+    builder: ClientBuilder,
+    proxy: &ProxyConfig,
+) -> Result<ClientBuilder, CodexApiError> {
+    match proxy {
+        // reqwest reads the proxy environment variables itself.
+        ProxyConfig::System => Ok(builder),
+        ProxyConfig::Disabled => Ok(builder.no_proxy()),
+        ProxyConfig::Url(url) => {
+            let proxy = Proxy::all(url.as_str()).map_err(|error| {
+                CodexApiError::InvalidTransportConfig(format!("invalid proxy URL: {error}"))
+            })?;
+            Ok(builder.proxy(proxy.no_proxy(NoProxy::from_env())))
+        }
+    }
+}
+
+fn apply_tls(builder: ClientBuilder, tls: &TlsConfig) -> Result<ClientBuilder, CodexApiError> {
+    let mut builder = builder.tls_built_in_root_certs(tls.use_built_in_roots);
+    for path in &tls.root_certificate_paths {
+        let pem = std::fs::read(path).map_err(|error| {
+            CodexApiError::InvalidTransportConfig(format!(
+                "failed to read root certificate bundle '{}': {error}",
+                path.display()
+            ))
+        })?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|error| {
+            CodexApiError::InvalidTransportConfig(format!(
+                "invalid root certificate bundle '{}': {error}",
+                path.display()
+            ))
+        })?;
+        if certificates.is_empty() {
+            return Err(CodexApiError::InvalidTransportConfig(format!(
+                "root certificate bundle '{}' contains no certificates",
+                path.display()
+            )));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if !tls.use_built_in_roots && tls.root_certificate_paths.is_empty() {
+        return Err(CodexApiError::InvalidTransportConfig(
+            "built-in roots are disabled but no root certificate bundle was provided".to_string(),
+        ));
+    }
+    Ok(builder)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{process_stream_event, terminal_status};
+    use super::{process_stream_event, terminal_status, CodexApiClient};
+    use crate::config::CodexApiConfig;
+    use crate::error::CodexApiError;
     use crate::events::{CodexResponseStatus, CodexStreamEvent};
     use crate::sse::SseStreamParser;
 
@@ -494,4 +685,72 @@ mod tests {
         assert_eq!(terminal.flatten(), terminal_status(&events));
         assert_eq!(observed, events);
     }
+
+    const TEST_ROOT_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIBkzCCATmgAwIBAgIUXRLBGXrxncuxCWTMRQAk8PamJLswCgYIKoZIzj0EAwIw
+HjEcMBoGA1UEAwwTY29kZXhfYXBpIHRlc3Qgcm9vdDAgFw0yNjEwMTUwOTQzMzla
+GA8yMTI2MDkyMTA5NDMzOVowHjEcMBoGA1UEAwwTY29kZXhfYXBpIHRlc3Qgcm9v
+dDBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABKJ2Ab2hIALNkLJ+XTNKicCP9/GI
+p8RotmNeulur4fSCznWDN9GkYfY/EoPII7UjsivheCMV4hnTxDh4p2J2wBmjUzBR
+MB0GA1UdDgQWBBQxOMX76uqeVbLDTdca7JHacWv2hDAfBgNVHSMEGDAWgBQxOMX7
+6uqeVbLDTdca7JHacWv2hDAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gA
+MEUCIQD44/VXLZcDaYruD8iwr0vT4GjBk5Di3eWQuyaNPASrhAIgd7gNUG6F3/mL
+3PsvXzX/Z9vkdN+Wg26qlDej4eRCU9s=
+-----END CERTIFICATE-----
+";
+
+    fn transport_config_error(config: CodexApiConfig) -> String {
+        match CodexApiClient::new(config) {
+            Err(CodexApiError::InvalidTransportConfig(message)) => message,
+            other => panic!("expected transport config error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn custom_root_bundle_can_replace_built_in_roots() {
+        let path =
+            std::env::temp_dir().join(format!("codex-api-root-{}-replace.pem", std::process::id()));
+        std::fs::write(&path, TEST_ROOT_PEM).expect("bundle should be written");
+
+        let config = CodexApiConfig::new("token")
+            .with_root_certificate(&path)
+            .with_built_in_roots(false);
+        let client = CodexApiClient::new(config);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(client.is_ok(), "{:?}", client.err());
+    }
+
+    #[test]
+    fn unusable_root_bundles_fail_client_construction() {
+        let missing =
+            std::env::temp_dir().join(format!("codex-api-root-{}-missing.pem", std::process::id()));
+        let message =
+            transport_config_error(CodexApiConfig::new("token").with_root_certificate(&missing));
+        assert!(
+            message.contains("failed to read root certificate bundle"),
+            "{message}"
+        );
+
+        let empty =
+            std::env::temp_dir().join(format!("codex-api-root-{}-empty.pem", std::process::id()));
+        std::fs::write(&empty, "not a certificate\n").expect("bundle should be written");
+        let message =
+            transport_config_error(CodexApiConfig::new("token").with_root_certificate(&empty));
+        let _ = std::fs::remove_file(&empty);
+        assert!(message.contains("contains no certificates"), "{message}");
+
+        let message =
+            transport_config_error(CodexApiConfig::new("token").with_built_in_roots(false));
+        assert!(message.contains("no root certificate bundle"), "{message}");
+    }
+
+    #[test]
+    fn malformed_proxy_url_fails_client_construction() {
+        let config = CodexApiConfig::new("token")
+            .with_proxy(crate::ProxyConfig::Url("::not a url".to_string()));
+
+        let message = transport_config_error(config);
+        assert!(message.contains("invalid proxy URL"), "{message}");
+    }
 }
@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use crate::url::DEFAULT_CODEX_BASE_URL;
+use crate::wire_log::WireLogConfig;
 
 /// Transport configuration for Codex API requests.
 #[derive(Debug, Clone)]
@@ -23,6 +25,49 @@ pub struct CodexApiConfig {
     pub extra_headers: BTreeMap<String, String>,
     /// Optional request timeout.
     pub timeout: Option<Duration>,
+    /// Redacted transport trace; when `None`, `CODEX_API_WIRE_LOG` may name
+    /// a file instead.
+    pub wire_log: Option<WireLogConfig>,
+    /// Outbound proxy selection.
+    pub proxy: ProxyConfig,
+    /// Trust roots used to verify the server certificate.
+    pub tls: TlsConfig,
+}
+
+/// How requests reach the Codex endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ProxyConfig {
+    /// Honor `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` and `NO_PROXY` (either
+    /// case) from the process environment.
+    #[default]
+    System,
+    /// Connect directly, ignoring proxy environment variables.
+    Disabled,
+    /// Route every request through this proxy URL. Credentials embedded in the
+    /// URL are sent as proxy basic auth, and `NO_PROXY` still applies.
+    Url(String),
+}
+
+/// Certificate trust for TLS connections.
+///
+/// Built-in roots are the bundled Mozilla roots plus the platform trust
+/// store. TLS-intercepting proxies usually need their CA added through
+/// `root_certificate_paths`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// PEM files, each holding one or more extra root certificates.
+    pub root_certificate_paths: Vec<PathBuf>,
+    /// Whether built-in roots are trusted alongside the extra ones.
+    pub use_built_in_roots: bool,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            root_certificate_paths: Vec::new(),
+            use_built_in_roots: true,
+        }
+    }
 }
 
 impl Default for CodexApiConfig {
@@ -36,6 +81,9 @@ impl Default for CodexApiConfig {
             user_agent: None,
             extra_headers: BTreeMap::new(),
             timeout: None,
+            wire_log: None,
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
         }
     }
 }
@@ -85,6 +133,26 @@ impl CodexApiConfig {
         self
     }
 
+    pub fn with_wire_log(mut self, wire_log: WireLogConfig) -> Self {
+        self.wire_log = Some(wire_log);
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    pub fn with_root_certificate(mut self, pem_path: impl Into<PathBuf>) -> Self {
+        self.tls.root_certificate_paths.push(pem_path.into());
+        self
+    }
+
+    pub fn with_built_in_roots(mut self, enabled: bool) -> Self {
+        self.tls.use_built_in_roots = enabled;
+        self
+    }
+
     pub fn insert_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.extra_headers.insert(key.into(), value.into());
         self
@@ -9,6 +9,7 @@ pub enum CodexApiError {
     MissingAccessToken,
     MissingAccountId,
     InvalidBaseUrl(String),
+    InvalidTransportConfig(String),
     InvalidRequestPayload(String),
     UrlNormalization(String),
     Request(reqwest::Error),
@@ -106,6 +107,9 @@ impl fmt::Display for CodexApiError {
             Self::MissingAccessToken => write!(f, "access token is required"),
             Self::MissingAccountId => write!(f, "account id is required"),
             Self::InvalidBaseUrl(value) => write!(f, "invalid base URL: {value}"),
+            Self::InvalidTransportConfig(message) => {
+                write!(f, "invalid transport config: {message}")
+            }
             Self::InvalidRequestPayload(message) => {
                 write!(f, "invalid request payload: {message}")
             }
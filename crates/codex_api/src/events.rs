@@ -51,6 +51,23 @@ pub enum CodexStreamEvent {
         id: Option<String>,
         status: Option<CodexResponseStatus>,
     },
+    /// A function-tool call output item was opened; its arguments follow as
+    /// `ToolCallArgumentsDelta` events keyed by the same item `id`.
+    #[serde(rename = "response.output_item.added")]
+    ToolCallStarted {
+        id: Option<String>,
+        call_id: Option<String>,
+        tool_name: Option<String>,
+    },
+    /// Raw JSON text appended to a streaming function call's arguments.
+    ///
+    /// Concatenated deltas form the same string later delivered whole by
+    /// `ToolCallRequested`; see [`crate::partial_json`] for early parsing.
+    #[serde(rename = "response.function_call_arguments.delta")]
+    ToolCallArgumentsDelta {
+        item_id: Option<String>,
+        delta: String,
+    },
     /// Normalized function-tool call event extracted from output item payloads.
     #[serde(rename = "response.output_item.function_call")]
     ToolCallRequested {
@@ -59,6 +76,15 @@ pub enum CodexStreamEvent {
         tool_name: Option<String>,
         arguments: Option<Value>,
     },
+    /// Normalized token usage extracted from terminal response payloads.
+    ///
+    /// Emitted immediately before `ResponseCompleted` when the payload carries usage.
+    #[serde(rename = "response.usage")]
+    ResponseUsage {
+        input_tokens: u64,
+        cached_input_tokens: u64,
+        output_tokens: u64,
+    },
     #[serde(rename = "response.completed")]
     ResponseCompleted {
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -71,6 +97,15 @@ pub enum CodexStreamEvent {
         code: Option<String>,
         message: Option<String>,
     },
+    /// A failed HTTP attempt, synthesized by the client before it retries
+    /// (or gives up, when `will_retry` is false); never parsed from the wire.
+    #[serde(rename = "client.request_attempt_failed")]
+    RequestAttemptFailed {
+        attempt: u32,
+        status: Option<u16>,
+        message: String,
+        will_retry: bool,
+    },
     /// Unknown event type retained for parity-safe passthrough behavior.
     #[serde(rename = "unknown")]
     Unknown { event_type: String, payload: Value },
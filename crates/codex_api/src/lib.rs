@@ -9,23 +9,36 @@
 //!
 //! SSE normalization includes host-mediated tool-call extraction via
 //! [`CodexStreamEvent::ToolCallRequested`], while preserving malformed tool
-//! payloads for explicit caller-side failure handling.
+//! payloads for explicit caller-side failure handling. Function-call arguments
+//! are also surfaced as they stream ([`CodexStreamEvent::ToolCallArgumentsDelta`])
+//! and can be previewed with [`partial_json::parse_partial_json`].
+//!
+//! [`CodexApiClient::list_models`] reads the account's model catalog from the
+//! `codex/models` endpoint next to the responses endpoint.
+//!
+//! An opt-in, redacted wire trace of requests and raw SSE responses is
+//! available for bug reports; see [`wire_log`].
 
 pub mod client;
 pub mod config;
 pub mod error;
 pub mod events;
 pub mod headers;
+pub mod models;
+pub mod partial_json;
 pub mod payload;
 pub mod retry;
 pub mod sse;
 pub mod url;
+pub mod wire_log;
 
 pub use client::CodexApiClient;
 pub use client::StreamResult;
-pub use config::CodexApiConfig;
+pub use config::{CodexApiConfig, ProxyConfig, TlsConfig};
 pub use error::CodexApiError;
 pub use events::{CodexResponseStatus, CodexStreamEvent};
+pub use models::{CodexModelInfo, CodexModelsResponse};
 pub use payload::CodexRequest;
 pub use sse::SseStreamParser;
-pub use url::normalize_codex_url;
+pub use url::{normalize_codex_models_url, normalize_codex_url};
+pub use wire_log::WireLogConfig;
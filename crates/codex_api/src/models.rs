@@ -0,0 +1,24 @@
+//! Model catalog returned by the Codex model listing endpoint.
+//!
+//! Only the fields the client surfaces are decoded; unknown fields are
+//! ignored so backend additions do not break the listing.
+
+use serde::Deserialize;
+
+/// One model the backend offers to the authenticated account.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CodexModelInfo {
+    /// Model id accepted in the request `model` field.
+    pub slug: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Context window in tokens, when the backend reports one.
+    #[serde(default)]
+    pub context_window: Option<u64>,
+}
+
+/// Body of a successful `GET <base>/codex/models` response.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CodexModelsResponse {
+    pub models: Vec<CodexModelInfo>,
+}
@@ -0,0 +1,226 @@
+//! Best-effort parsing of an incomplete JSON document.
+//!
+//! Tool-call arguments stream in as raw JSON text. [`parse_partial_json`]
+//! turns any prefix of that text into the most complete value it can: open
+//! strings, objects and arrays are closed, and a trailing key without a value,
+//! a dangling separator or a half-written literal is dropped.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    /// Object waiting for a key or `}`.
+    ObjectKey,
+    /// Object waiting for `:` after a key.
+    ObjectColon,
+    /// Object waiting for a member value.
+    ObjectValue,
+    /// Object or array waiting for `,` or its closing bracket.
+    AfterMember,
+    /// Array waiting for an element or `]`.
+    ArrayValue,
+}
+
+/// Parses `text` as the prefix of a JSON document.
+///
+/// Returns `None` until the prefix holds at least the opening of a value, or
+/// once it contains a malformed number or literal.
+pub fn parse_partial_json(text: &str) -> Option<Value> {
+    let mut stack: Vec<(Container, bool)> = Vec::new();
+    // Byte offset and closing suffix of the longest prefix known to parse.
+    let mut complete: Option<(usize, String)> = None;
+    let mut string_start: Option<usize> = None;
+    let mut string_is_key = false;
+    let mut escape_start: Option<usize> = None;
+    let mut unicode_digits = 0;
+    let mut scalar_start: Option<usize> = None;
+
+    let closers = |stack: &[(Container, bool)]| -> String {
+        stack
+            .iter()
+            .rev()
+            .map(|(_, is_object)| if *is_object { '}' } else { ']' })
+            .collect()
+    };
+    let value_done = |stack: &mut Vec<(Container, bool)>| {
+        if let Some((state, _)) = stack.last_mut() {
+            *state = Container::AfterMember;
+        }
+    };
+
+    for (index, character) in text.char_indices() {
+        if string_start.is_some() {
+            if let Some(start) = escape_start {
+                if unicode_digits > 0 {
+                    unicode_digits -= 1;
+                } else if character == 'u' {
+                    unicode_digits = 4;
+                }
+                if unicode_digits == 0 && index > start {
+                    escape_start = None;
+                }
+                continue;
+            }
+            match character {
+                '\\' => escape_start = Some(index),
+                '"' => {
+                    string_start = None;
+                    if string_is_key {
+                        if let Some((state, _)) = stack.last_mut() {
+                            *state = Container::ObjectColon;
+                        }
+                    } else {
+                        value_done(&mut stack);
+                        complete = Some((index + 1, closers(&stack)));
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if let Some(start) = scalar_start {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '+' | '.') {
+                continue;
+            }
+            scalar_start = None;
+            if serde_json::from_str::<Value>(&text[start..index]).is_err() {
+                return None;
+            }
+            value_done(&mut stack);
+            complete = Some((index, closers(&stack)));
+        }
+
+        match character {
+            '{' => {
+                stack.push((Container::ObjectKey, true));
+                complete = Some((index + 1, closers(&stack)));
+            }
+            '[' => {
+                stack.push((Container::ArrayValue, false));
+                complete = Some((index + 1, closers(&stack)));
+            }
+            '}' | ']' => {
+                stack.pop();
+                value_done(&mut stack);
+                complete = Some((index + 1, closers(&stack)));
+                if stack.is_empty() {
+                    break;
+                }
+            }
+            '"' => {
+                string_is_key = matches!(stack.last(), Some((Container::ObjectKey, _)));
+                string_start = Some(index);
+            }
+            ':' => {
+                if let Some((state, _)) = stack.last_mut() {
+                    *state = Container::ObjectValue;
+                }
+            }
+            ',' => {
+                if let Some((state, is_object)) = stack.last_mut() {
+                    *state = if *is_object {
+                        Container::ObjectKey
+                    } else {
+                        Container::ArrayValue
+                    };
+                }
+            }
+            character if character.is_whitespace() => {}
+            _ => scalar_start = Some(index),
+        }
+    }
+
+    if string_start.is_some() && !string_is_key {
+        // Close an open string value, dropping a half-written escape.
+        let end = escape_start.unwrap_or(text.len());
+        value_done(&mut stack);
+        let candidate = format!("{}\"{}", &text[..end], closers(&stack));
+        if let Ok(value) = serde_json::from_str(&candidate) {
+            return Some(value);
+        }
+    } else if let Some(start) = scalar_start {
+        // A trailing number or literal counts once it is valid on its own;
+        // `tr` or `1.` may still grow into one.
+        if serde_json::from_str::<Value>(&text[start..]).is_ok() {
+            value_done(&mut stack);
+            if let Ok(value) = serde_json::from_str(&format!("{text}{}", closers(&stack))) {
+                return Some(value);
+            }
+        }
+    }
+
+    complete.and_then(|(end, suffix)| parse_completed(text, end, &suffix))
+}
+
+fn parse_completed(text: &str, end: usize, suffix: &str) -> Option<Value> {
+    serde_json::from_str(&format!("{}{suffix}", &text[..end])).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::parse_partial_json;
+
+    #[test]
+    fn every_prefix_of_a_document_parses_to_a_growing_value() {
+        let document = r#"{"command": "cargo test -p \"codex_api\" é", "timeout_sec": 120, "args": [true, null, -1.5e3], "nested": {"k": []}}"#;
+
+        let mut last_command_len = 0;
+        for end in (1..=document.len()).filter(|end| document.is_char_boundary(*end)) {
+            let value = parse_partial_json(&document[..end])
+                .unwrap_or_else(|| panic!("prefix {:?} should parse", &document[..end]));
+            assert!(value.is_object(), "prefix {:?}", &document[..end]);
+            let command_len = value
+                .get("command")
+                .and_then(|command| command.as_str())
+                .map_or(0, str::len);
+            assert!(command_len >= last_command_len);
+            last_command_len = command_len;
+        }
+
+        assert_eq!(
+            parse_partial_json(document),
+            serde_json::from_str(document).ok()
+        );
+    }
+
+    #[test]
+    fn partial_strings_close_and_dangling_members_drop() {
+        assert_eq!(
+            parse_partial_json(r#"{"command": "cargo te"#),
+            Some(json!({"command": "cargo te"}))
+        );
+        assert_eq!(
+            parse_partial_json(r#"{"path": "a.rs", "con"#),
+            Some(json!({"path": "a.rs"}))
+        );
+        assert_eq!(
+            parse_partial_json(r#"{"path": "a.rs", "content":"#),
+            Some(json!({"path": "a.rs"}))
+        );
+        assert_eq!(
+            parse_partial_json(r#"{"text": "line\"#),
+            Some(json!({"text": "line"}))
+        );
+        assert_eq!(
+            parse_partial_json(r#"{"text": "x\u00"#),
+            Some(json!({"text": "x"}))
+        );
+        assert_eq!(parse_partial_json(r#"{"staged": tr"#), Some(json!({})));
+        assert_eq!(
+            parse_partial_json(r#"{"timeout_sec": 12"#),
+            Some(json!({"timeout_sec": 12}))
+        );
+        assert_eq!(parse_partial_json(r#"["a", "#), Some(json!(["a"])));
+    }
+
+    #[test]
+    fn empty_and_invalid_prefixes_do_not_parse() {
+        assert_eq!(parse_partial_json(""), None);
+        assert_eq!(parse_partial_json("   "), None);
+        assert_eq!(parse_partial_json("{\"a\": nope}"), None);
+        assert_eq!(parse_partial_json("nope"), None);
+    }
+}
@@ -25,6 +25,13 @@ pub struct CodexRequest {
     pub parallel_tool_calls: bool,
     #[serde(rename = "prompt_cache_key", skip_serializing_if = "Option::is_none")]
     pub prompt_cache_key: Option<String>,
+    /// `in_memory` (the server default) or `24h`.
+    #[serde(
+        rename = "prompt_cache_retention",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub prompt_cache_retention: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -54,6 +61,7 @@ impl CodexRequest {
             tool_choice: Some("auto".to_string()),
             parallel_tool_calls: true,
             prompt_cache_key: None,
+            prompt_cache_retention: None,
             temperature: None,
             reasoning: None,
             tools: Vec::new(),
@@ -64,12 +72,39 @@ impl CodexRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodexRequestText {
     pub verbosity: String,
+    /// Constrains the final message to a response format; free text when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<CodexTextFormat>,
 }
 
 impl Default for CodexRequestText {
     fn default() -> Self {
         Self {
             verbosity: "medium".to_string(),
+            format: None,
+        }
+    }
+}
+
+/// Responses API `text.format` value for JSON-schema constrained output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodexTextFormat {
+    /// Always `json_schema`.
+    #[serde(rename = "type")]
+    pub format_type: String,
+    pub name: String,
+    pub schema: Value,
+    pub strict: bool,
+}
+
+impl CodexTextFormat {
+    /// A strict `json_schema` format named `name`.
+    pub fn json_schema(name: impl Into<String>, schema: Value) -> Self {
+        Self {
+            format_type: "json_schema".to_string(),
+            name: name.into(),
+            schema,
+            strict: true,
         }
     }
 }
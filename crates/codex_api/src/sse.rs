@@ -1,36 +1,78 @@
+use std::time::Duration;
+
 use serde_json::Value;
 
 use crate::events::{CodexResponseStatus, CodexStreamEvent};
 
 /// Incremental parser for SSE text streams.
 ///
+/// Framing follows the WHATWG event-stream rules, since proxies and gateways
+/// routinely rewrite it: a leading UTF-8 BOM is dropped, lines may end in
+/// CRLF, LF or CR (including a CRLF split across chunks), `:` comment lines
+/// are ignored, and consecutive `data:` lines accumulate into one payload
+/// joined by newlines. A blank line dispatches the pending event; bytes after
+/// the last blank line stay buffered, so a truncated event is never emitted.
+/// `id:` and `retry:` are tracked for reconnecting callers.
+///
 /// Function-call output items are normalized into `OutputItemDone` plus
 /// `ToolCallRequested` events. The parser preserves malformed tool payload fields
 /// so adapters can fail explicitly instead of relying on fallback coercions.
 #[derive(Debug, Default)]
 pub struct SseStreamParser {
-    buffer: Vec<u8>,
+    /// Bytes of the current, unterminated line.
+    line: Vec<u8>,
+    /// `data:` values of the pending event, each followed by `\n`.
+    data: String,
+    /// Whether the BOM check at the start of the stream is done.
+    started: bool,
+    /// The previous chunk ended in CR, so a leading LF belongs to that line
+    /// ending.
+    after_cr: bool,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
 }
 
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 impl SseStreamParser {
     /// Feed arbitrary bytes into the parser and drain complete events.
     pub fn feed(&mut self, bytes: &[u8]) -> Vec<CodexStreamEvent> {
-        self.buffer.extend_from_slice(bytes);
-        let mut events = Vec::new();
-
-        while let Some((split, separator_len)) = find_frame_separator(&self.buffer) {
-            let frame = self.buffer[..split].to_vec();
-            self.buffer.drain(0..split + separator_len);
+        let mut bytes = bytes;
+        if !self.started {
+            // Hold back a possible BOM prefix until it is complete.
+            self.line.extend_from_slice(bytes);
+            if self.line.len() < UTF8_BOM.len() && UTF8_BOM.starts_with(&self.line) {
+                return Vec::new();
+            }
+            self.started = true;
+            let held = std::mem::take(&mut self.line);
+            let held = held.strip_prefix(UTF8_BOM).unwrap_or(&held).to_vec();
+            return self.feed(&held);
+        }
 
-            if let Some(payload) = extract_data_payload(&frame) {
-                if payload == "[DONE]" || payload.is_empty() {
-                    continue;
-                }
+        if self.after_cr && !bytes.is_empty() {
+            self.after_cr = false;
+            if let Some(rest) = bytes.strip_prefix(b"\n") {
+                bytes = rest;
+            }
+        }
 
-                if let Ok(value) = serde_json::from_str::<Value>(&payload) {
-                    events.extend(map_event(value));
+        let mut events = Vec::new();
+        let mut index = 0;
+        while index < bytes.len() {
+            match bytes[index] {
+                b'\n' => self.end_line(&mut events),
+                b'\r' => {
+                    self.end_line(&mut events);
+                    match bytes.get(index + 1) {
+                        Some(b'\n') => index += 1,
+                        Some(_) => {}
+                        None => self.after_cr = true,
+                    }
                 }
+                byte => self.line.push(byte),
             }
+            index += 1;
         }
 
         events
@@ -42,36 +84,62 @@ impl SseStreamParser {
         parser.feed(input.as_bytes())
     }
 
+    /// True when no partial event is pending.
     pub fn is_empty_buffer(&self) -> bool {
-        self.buffer.iter().all(|byte| byte.is_ascii_whitespace())
+        self.data.is_empty() && self.line.iter().all(|byte| byte.is_ascii_whitespace())
     }
-}
 
-fn find_frame_separator(buffer: &[u8]) -> Option<(usize, usize)> {
-    for index in 0..buffer.len() {
-        if index + 1 < buffer.len() && &buffer[index..index + 2] == b"\n\n" {
-            return Some((index, 2));
+    /// The most recent `id:` field, for a reconnect's `Last-Event-ID`.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// The most recent valid `retry:` field.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    fn end_line(&mut self, events: &mut Vec<CodexStreamEvent>) {
+        let line = std::mem::take(&mut self.line);
+        if line.is_empty() {
+            self.dispatch(events);
+            return;
+        }
+        if line[0] == b':' {
+            return;
         }
-        if index + 3 < buffer.len() && &buffer[index..index + 4] == b"\r\n\r\n" {
-            return Some((index, 4));
+
+        let line = String::from_utf8_lossy(&line);
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_ref(), ""),
+        };
+        match field {
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            // `event:` names are redundant with the JSON `type` field.
+            _ => {}
         }
     }
-    None
-}
 
-fn extract_data_payload(frame: &[u8]) -> Option<String> {
-    let frame = std::str::from_utf8(frame).ok()?;
-    let data_lines: Vec<&str> = frame
-        .lines()
-        .filter_map(|line| line.strip_prefix("data:"))
-        .map(|value| value.trim())
-        .filter(|value| !value.is_empty())
-        .collect();
-
-    if data_lines.is_empty() {
-        None
-    } else {
-        Some(data_lines.join("\n"))
+    fn dispatch(&mut self, events: &mut Vec<CodexStreamEvent>) {
+        let data = std::mem::take(&mut self.data);
+        let payload = data.trim();
+        if payload.is_empty() || payload == "[DONE]" {
+            return;
+        }
+
+        if let Ok(value) = serde_json::from_str::<Value>(payload) {
+            events.extend(map_event(value));
+        }
     }
 }
 
@@ -103,6 +171,44 @@ fn map_event(value: Value) -> Vec<CodexStreamEvent> {
                 delta: delta.to_owned(),
             }]
         }
+        "response.output_item.added" => {
+            let item = value.get("item");
+            if item
+                .and_then(|item| item.get("type"))
+                .and_then(|value| value.as_str())
+                != Some("function_call")
+            {
+                return vec![CodexStreamEvent::Unknown {
+                    event_type,
+                    payload: value,
+                }];
+            }
+
+            let field = |name: &str| {
+                item.and_then(|item| item.get(name))
+                    .and_then(|value| value.as_str())
+                    .map(ToString::to_string)
+            };
+            vec![CodexStreamEvent::ToolCallStarted {
+                id: field("id"),
+                call_id: field("call_id"),
+                tool_name: field("name"),
+            }]
+        }
+        "response.function_call_arguments.delta" => {
+            let item_id = value
+                .get("item_id")
+                .and_then(|value| value.as_str())
+                .map(ToString::to_string);
+            let delta = value
+                .get("delta")
+                .and_then(|value| value.as_str())
+                .unwrap_or("");
+            vec![CodexStreamEvent::ToolCallArgumentsDelta {
+                item_id,
+                delta: delta.to_owned(),
+            }]
+        }
         "response.output_item.done" => {
             let id = value
                 .get("item")
@@ -158,8 +264,18 @@ fn map_event(value: Value) -> Vec<CodexStreamEvent> {
                 .and_then(|status| status.as_str())
                 .and_then(CodexResponseStatus::parse);
 
+            let mut events = Vec::new();
+            if let Some(usage) = value
+                .get("response")
+                .and_then(|response| response.get("usage"))
+                .filter(|usage| usage.is_object())
+            {
+                events.push(map_usage(usage));
+            }
+
             // Keep alias handling explicit so callers receive normalized completion.
-            vec![CodexStreamEvent::ResponseCompleted { status }]
+            events.push(CodexStreamEvent::ResponseCompleted { status });
+            events
         }
         "response.failed" => {
             let message = value
@@ -198,6 +314,20 @@ fn map_event(value: Value) -> Vec<CodexStreamEvent> {
     }
 }
 
+fn map_usage(usage: &Value) -> CodexStreamEvent {
+    let count = |value: Option<&Value>| value.and_then(Value::as_u64).unwrap_or(0);
+
+    CodexStreamEvent::ResponseUsage {
+        input_tokens: count(usage.get("input_tokens")),
+        cached_input_tokens: count(
+            usage
+                .get("input_tokens_details")
+                .and_then(|details| details.get("cached_tokens")),
+        ),
+        output_tokens: count(usage.get("output_tokens")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::SseStreamParser;
@@ -247,6 +377,57 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_streaming_function_call_arguments() {
+        let payload = concat!(
+            "data: {\"type\":\"response.output_item.added\",\"output_index\":0,\"item\":{\"type\":\"function_call\",\"id\":\"fc_1\",\"call_id\":\"call_1\",\"name\":\"bash\",\"arguments\":\"\"}}\n\n",
+            "data: {\"type\":\"response.function_call_arguments.delta\",\"item_id\":\"fc_1\",\"output_index\":0,\"delta\":\"{\\\"command\\\":\"}\n\n",
+            "data: {\"type\":\"response.output_item.added\",\"output_index\":1,\"item\":{\"type\":\"message\",\"id\":\"msg_1\"}}\n\n",
+        );
+
+        let events = SseStreamParser::parse_frames(payload);
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            CodexStreamEvent::ToolCallStarted {
+                id: Some("fc_1".to_string()),
+                call_id: Some("call_1".to_string()),
+                tool_name: Some("bash".to_string()),
+            }
+        );
+        assert_eq!(
+            events[1],
+            CodexStreamEvent::ToolCallArgumentsDelta {
+                item_id: Some("fc_1".to_string()),
+                delta: "{\"command\":".to_string(),
+            }
+        );
+        assert!(matches!(
+            &events[2],
+            CodexStreamEvent::Unknown { event_type, .. } if event_type == "response.output_item.added"
+        ));
+    }
+
+    #[test]
+    fn parse_completed_usage_precedes_completion_event() {
+        let payload = "data: {\"type\":\"response.completed\",\"response\":{\"status\":\"completed\",\"usage\":{\"input_tokens\":120,\"input_tokens_details\":{\"cached_tokens\":64},\"output_tokens\":32,\"total_tokens\":152}}}\n\n";
+
+        let events = SseStreamParser::parse_frames(payload);
+        assert_eq!(
+            events,
+            vec![
+                CodexStreamEvent::ResponseUsage {
+                    input_tokens: 120,
+                    cached_input_tokens: 64,
+                    output_tokens: 32,
+                },
+                CodexStreamEvent::ResponseCompleted {
+                    status: Some(CodexResponseStatus::Completed),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn parse_function_call_output_item_preserves_non_object_arguments() {
         let payload = concat!(
@@ -23,3 +23,13 @@ pub fn normalize_codex_url(input: &str) -> String {
     }
     format!("{trimmed}/codex/responses")
 }
+
+/// Normalize a base URL (or a Codex responses endpoint) to the Codex model
+/// listing endpoint, `<base>/codex/models`.
+pub fn normalize_codex_models_url(input: &str) -> String {
+    let responses = normalize_codex_url(input);
+    let codex_base = responses
+        .strip_suffix("/responses")
+        .unwrap_or(responses.as_str());
+    format!("{codex_base}/models")
+}
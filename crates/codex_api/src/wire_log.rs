@@ -0,0 +1,393 @@
+//! Opt-in transport trace for bug reports.
+//!
+//! When enabled (through [`CodexApiConfig::with_wire_log`](crate::CodexApiConfig::with_wire_log)
+//! or the [`WIRE_LOG_ENV_VAR`] environment variable), the client appends one
+//! JSON object per line to the log file for every request attempt, response
+//! head, error body, raw SSE chunk and transport error. The file rotates to
+//! `<path>.1`, `<path>.2`, ... once it reaches its size limit.
+//!
+//! Credentials never reach the file: credential headers and secret-looking
+//! JSON keys are replaced with `[REDACTED]`, and every string is scrubbed of the
+//! configured access token, bearer values and JWT-shaped substrings before it
+//! is written. Logging failures are swallowed; tracing never fails a request.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::{json, Map, Value};
+
+/// Environment variable naming a wire log file when the config sets none.
+pub const WIRE_LOG_ENV_VAR: &str = "CODEX_API_WIRE_LOG";
+
+/// Replacement for redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+const DEFAULT_MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+const DEFAULT_MAX_ROTATED_FILES: usize = 3;
+
+fn bearer_regex() -> &'static Regex {
+    static CACHED: OnceLock<Regex> = OnceLock::new();
+    CACHED.get_or_init(|| Regex::new(r"(?i)\bbearer\s+[^\s\x22,]+").expect("valid bearer pattern"))
+}
+
+fn jwt_regex() -> &'static Regex {
+    static CACHED: OnceLock<Regex> = OnceLock::new();
+    CACHED.get_or_init(|| {
+        Regex::new(r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+")
+            .expect("valid JWT pattern")
+    })
+}
+
+/// Where and how much to trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireLogConfig {
+    pub path: PathBuf,
+    /// Size at which the file rotates (default 8 MiB).
+    pub max_file_bytes: u64,
+    /// Rotated files kept besides the live one (default 3).
+    pub max_rotated_files: usize,
+}
+
+impl WireLogConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_rotated_files: DEFAULT_MAX_ROTATED_FILES,
+        }
+    }
+
+    /// Reads [`WIRE_LOG_ENV_VAR`]; unset or blank disables tracing.
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(WIRE_LOG_ENV_VAR)
+            .filter(|path| !path.to_string_lossy().trim().is_empty())
+            .map(Self::new)
+    }
+
+    pub fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = max_file_bytes.max(1);
+        self
+    }
+
+    pub fn with_max_rotated_files(mut self, max_rotated_files: usize) -> Self {
+        self.max_rotated_files = max_rotated_files;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct WireLogState {
+    config: WireLogConfig,
+    file: Option<File>,
+    written: u64,
+}
+
+/// Shared handle to an open trace; clones append to the same file.
+#[derive(Debug, Clone)]
+pub(crate) struct WireLog {
+    state: Arc<Mutex<WireLogState>>,
+    /// Literal credentials scrubbed from every line.
+    secrets: Arc<Vec<String>>,
+}
+
+impl WireLog {
+    pub(crate) fn new(config: WireLogConfig, secrets: Vec<String>) -> Self {
+        let secrets = secrets
+            .into_iter()
+            .map(|secret| secret.trim().to_string())
+            .filter(|secret| !secret.is_empty())
+            .collect();
+        Self {
+            state: Arc::new(Mutex::new(WireLogState {
+                config,
+                file: None,
+                written: 0,
+            })),
+            secrets: Arc::new(secrets),
+        }
+    }
+
+    pub(crate) fn request(&self, attempt: u32, url: &str, headers: &HeaderMap, body: &Value) {
+        self.write(json!({
+            "kind": "request",
+            "attempt": attempt,
+            "url": url,
+            "headers": redact_headers(headers),
+            "body": redact_json(body),
+        }));
+    }
+
+    pub(crate) fn response(&self, attempt: u32, status: StatusCode, headers: &HeaderMap) {
+        self.write(json!({
+            "kind": "response",
+            "attempt": attempt,
+            "status": status.as_u16(),
+            "headers": redact_headers(headers),
+        }));
+    }
+
+    pub(crate) fn error_body(&self, attempt: u32, body: &str) {
+        let body = serde_json::from_str::<Value>(body)
+            .map(|value| redact_json(&value))
+            .unwrap_or_else(|_| Value::String(body.to_string()));
+        self.write(json!({ "kind": "error_body", "attempt": attempt, "body": body }));
+    }
+
+    pub(crate) fn transport_error(&self, attempt: u32, message: &str) {
+        self.write(json!({
+            "kind": "transport_error",
+            "attempt": attempt,
+            "message": message,
+        }));
+    }
+
+    /// Raw SSE bytes as received, before framing.
+    pub(crate) fn sse_chunk(&self, bytes: &[u8]) {
+        self.write(json!({
+            "kind": "sse",
+            "data": String::from_utf8_lossy(bytes),
+        }));
+    }
+
+    fn write(&self, mut record: Value) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        if let Value::Object(fields) = &mut record {
+            fields.insert("ts_ms".to_string(), json!(timestamp_ms));
+        }
+        scrub_strings(&mut record, &self.secrets);
+        let mut line = record.to_string();
+        line.push('\n');
+
+        let mut state = lock_unpoisoned(&self.state);
+        let _ = state.append(line.as_bytes());
+    }
+}
+
+impl WireLogState {
+    fn append(&mut self, line: &[u8]) -> std::io::Result<()> {
+        let len = line.len() as u64;
+        if self.file.is_none() {
+            self.open()?;
+        }
+        if self.written > 0 && self.written + len > self.config.max_file_bytes {
+            self.file = None;
+            rotate(&self.config.path, self.config.max_rotated_files)?;
+            self.open()?;
+        }
+
+        let file = self.file.as_mut().expect("wire log file is open");
+        file.write_all(line)?;
+        self.written += len;
+        Ok(())
+    }
+
+    fn open(&mut self) -> std::io::Result<()> {
+        if let Some(parent) = self
+            .config
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.path)?;
+        self.written = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+}
+
+/// Shifts `path.N-1` to `path.N`, ..., `path` to `path.1`, dropping the
+/// oldest. With no rotated files kept, the live file is just removed.
+fn rotate(path: &Path, max_rotated_files: usize) -> std::io::Result<()> {
+    if max_rotated_files == 0 {
+        return fs::remove_file(path);
+    }
+    let rotated = |index: usize| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{index}"));
+        PathBuf::from(name)
+    };
+    let _ = fs::remove_file(rotated(max_rotated_files));
+    for index in (1..max_rotated_files).rev() {
+        let from = rotated(index);
+        if from.exists() {
+            fs::rename(&from, rotated(index + 1))?;
+        }
+    }
+    fs::rename(path, rotated(1))
+}
+
+fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "authorization" | "proxy-authorization" | "cookie" | "set-cookie" | "chatgpt-account-id"
+    ) || [
+        "token", "secret", "password", "api-key", "api_key", "apikey",
+    ]
+    .iter()
+    .any(|needle| name.contains(needle))
+}
+
+fn redact_headers(headers: &HeaderMap) -> Value {
+    let mut out = Map::new();
+    for (name, value) in headers {
+        let value = if is_sensitive_name(name.as_str()) {
+            REDACTED.to_string()
+        } else {
+            String::from_utf8_lossy(value.as_bytes()).into_owned()
+        };
+        out.insert(name.as_str().to_string(), Value::String(value));
+    }
+    Value::Object(out)
+}
+
+fn redact_json(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| {
+                    let value = if is_sensitive_name(key) && !value.is_null() {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact_json(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_json).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Applies [`redact_text`] to every string in `value`.
+fn scrub_strings(value: &mut Value, secrets: &[String]) {
+    match value {
+        Value::String(text) => *text = redact_text(text, secrets),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| scrub_strings(item, secrets)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| scrub_strings(field, secrets)),
+        _ => {}
+    }
+}
+
+/// Last line of defence for credentials that slipped into free text.
+fn redact_text(text: &str, secrets: &[String]) -> String {
+    let mut text = text.to_string();
+    for secret in secrets {
+        text = text.replace(secret.as_str(), REDACTED);
+    }
+    let text = bearer_regex().replace_all(&text, format!("Bearer {REDACTED}").as_str());
+    jwt_regex().replace_all(&text, REDACTED).into_owned()
+}
+
+fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    use super::*;
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("codex-api-wire-log-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("wire.jsonl")
+    }
+
+    #[test]
+    fn records_redact_headers_json_keys_and_free_text_credentials() {
+        let path = temp_log_path("redact");
+        let log = WireLog::new(WireLogConfig::new(&path), vec!["sk-literal".to_string()]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Bearer eyJhbGc.eyJzdWI.c2ln"),
+        );
+        headers.insert(
+            HeaderName::from_static("x-api-key"),
+            HeaderValue::from_static("k"),
+        );
+        headers.insert(
+            HeaderName::from_static("accept"),
+            HeaderValue::from_static("text/event-stream"),
+        );
+
+        log.request(
+            1,
+            "https://example.test/codex/responses",
+            &headers,
+            &json!({"model": "m", "auth": {"access_token": "t"}, "input": "token is sk-literal"}),
+        );
+        log.sse_chunk(b"data: {\"note\":\"Bearer abc.def\"}\n\n");
+
+        let contents = fs::read_to_string(&path).expect("wire log should be written");
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("each line is JSON"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["headers"]["authorization"], REDACTED);
+        assert_eq!(lines[0]["headers"]["x-api-key"], REDACTED);
+        assert_eq!(lines[0]["headers"]["accept"], "text/event-stream");
+        assert_eq!(lines[0]["body"]["auth"]["access_token"], REDACTED);
+        assert_eq!(lines[0]["body"]["input"], "token is [REDACTED]");
+        assert_eq!(lines[1]["kind"], "sse");
+        assert!(!contents.contains("sk-literal"));
+        assert!(!contents.contains("eyJhbGc"));
+        assert!(!contents.contains("abc.def"));
+    }
+
+    #[test]
+    fn log_rotates_and_keeps_a_bounded_number_of_files() {
+        let path = temp_log_path("rotate");
+        let log = WireLog::new(
+            WireLogConfig::new(&path)
+                .with_max_file_bytes(200)
+                .with_max_rotated_files(2),
+            Vec::new(),
+        );
+        for index in 0..12 {
+            log.transport_error(index, "connection reset by peer, retrying soon");
+        }
+
+        let rotated = |index: usize| PathBuf::from(format!("{}.{index}", path.display()));
+        assert!(path.exists());
+        assert!(rotated(1).exists());
+        assert!(rotated(2).exists());
+        assert!(!rotated(3).exists());
+        for file in [path.clone(), rotated(1), rotated(2)] {
+            let len = fs::metadata(&file).expect("log file metadata").len();
+            assert!(len <= 200, "{} grew to {len} bytes", file.display());
+        }
+        let live = fs::read_to_string(&path).expect("live log");
+        assert!(live
+            .lines()
+            .last()
+            .is_some_and(|line| line.contains("\"attempt\":11")));
+    }
+}
@@ -6,7 +6,9 @@ use std::time::Instant;
 
 use base64::{engine::general_purpose, Engine as _};
 use codex_api::events::{CodexResponseStatus, CodexStreamEvent};
-use codex_api::{CodexApiClient, CodexApiConfig, CodexApiError, CodexRequest};
+use codex_api::{
+    CodexApiClient, CodexApiConfig, CodexApiError, CodexRequest, ProxyConfig, WireLogConfig,
+};
 use serde_json::json;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
@@ -172,6 +174,63 @@ async fn stream_integration_successful_completion() {
     server.shutdown();
 }
 
+#[tokio::test]
+async fn stream_integration_wire_log_traces_retries_and_sse_without_credentials() {
+    let server = ScriptedServer::new(vec![
+        response_json(503, r##"{"error":{"message":"overloaded"}}"##),
+        response_sse(
+            200,
+            &[
+                r##"{"type":"response.output_text.delta","delta":"hello"}"##,
+                r##"{"type":"response.completed","response":{"status":"completed"}}"##,
+            ],
+        ),
+    ])
+    .await;
+    let log_dir = std::env::temp_dir().join(format!("codex-api-wire-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&log_dir);
+    let log_path = log_dir.join("trace.jsonl");
+
+    let token = token_with_account_id("acct");
+    let request = CodexRequest::new("gpt-codex", user_input("hi"), None);
+    let config = CodexApiConfig::new(token.clone())
+        .with_base_url(&server.base_url)
+        .with_wire_log(WireLogConfig::new(&log_path));
+    let client = CodexApiClient::new(config).expect("client");
+    client
+        .stream(&request, None)
+        .await
+        .expect("stream should succeed after a retry");
+
+    let trace = std::fs::read_to_string(&log_path).expect("wire log should be written");
+    let kinds: Vec<String> = trace
+        .lines()
+        .map(|line| {
+            let record: serde_json::Value = serde_json::from_str(line).expect("JSON line");
+            record["kind"].as_str().expect("kind").to_string()
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "request",
+            "response",
+            "error_body",
+            "request",
+            "response",
+            "sse"
+        ]
+    );
+    assert!(trace.contains("\"status\":503"));
+    assert!(trace.contains("overloaded"));
+    assert!(trace.contains("response.output_text.delta"));
+    assert!(trace.contains("\"authorization\":\"[REDACTED]\""));
+    assert!(!trace.contains(&token));
+
+    server.shutdown();
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
 #[tokio::test]
 async fn stream_with_handler_integration_emits_events_incrementally_in_parser_order() {
     let server = ScriptedServer::new(vec![ScriptedResponse::Respond {
@@ -479,6 +538,15 @@ async fn stream_integration_retryable_then_success() {
 
     assert_eq!(result.terminal, Some(CodexResponseStatus::Completed));
     assert_eq!(server.request_count(), 2);
+    assert_eq!(
+        result.events.first(),
+        Some(&CodexStreamEvent::RequestAttemptFailed {
+            attempt: 1,
+            status: Some(503),
+            message: "overloaded".to_string(),
+            will_retry: true,
+        })
+    );
 
     server.shutdown();
 }
@@ -905,6 +973,61 @@ fn status_reason(status: u16) -> &'static str {
     }
 }
 
+#[tokio::test]
+async fn stream_integration_explicit_proxy_receives_absolute_form_requests() {
+    let proxy = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("proxy listener should bind");
+    let proxy_url = format!("http://{}", proxy.local_addr().expect("proxy address"));
+    let proxy_task = tokio::spawn(async move {
+        let (mut socket, _) = proxy.accept().await.expect("proxy should accept");
+        let mut head = Vec::new();
+        let mut buffer = [0_u8; 2048];
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            let n = socket.read(&mut buffer).await.expect("proxy read");
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buffer[..n]);
+        }
+        let body = sse_frames(&[
+            r##"{"type":"response.output_text.delta","delta":"via proxy"}"##,
+            r##"{"type":"response.completed","response":{"status":"completed"}}"##,
+        ]);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        socket
+            .write_all(response.as_bytes())
+            .await
+            .expect("proxy write");
+        socket.write_all(&body).await.expect("proxy write");
+        String::from_utf8_lossy(&head).into_owned()
+    });
+
+    let request = CodexRequest::new("gpt-codex", user_input("hi"), None);
+    let config = CodexApiConfig::new(token_with_account_id("acct"))
+        .with_base_url("http://codex.invalid/backend-api")
+        .with_proxy(ProxyConfig::Url(proxy_url));
+    let client = CodexApiClient::new(config).expect("client");
+
+    let result = client
+        .stream(&request, None)
+        .await
+        .expect("stream through proxy should succeed");
+    let head = proxy_task.await.expect("proxy task");
+
+    assert!(
+        head.starts_with("POST http://codex.invalid/backend-api/codex/responses HTTP/1.1"),
+        "{head}"
+    );
+    assert!(result.events.iter().any(|event| matches!(
+        event,
+        CodexStreamEvent::OutputTextDelta { delta } if delta == "via proxy"
+    )));
+}
+
 async fn serve_one(
     mut socket: TcpStream,
     scripts: Arc<Vec<ScriptedResponse>>,
@@ -978,3 +1101,50 @@ async fn read_request_headers(socket: &mut TcpStream) -> std::io::Result<()> {
         }
     }
 }
+
+#[tokio::test]
+async fn list_models_integration_decodes_catalog_and_ignores_unknown_fields() {
+    let server = ScriptedServer::new(vec![response_json(
+        200,
+        r#"{"models":[{"slug":"gpt-5.3-codex","display_name":"GPT-5.3 Codex","context_window":272000,"visibility":"list"},{"slug":"gpt-5.2"}]}"#,
+    )])
+    .await;
+
+    let config = CodexApiConfig::new(token_with_account_id("acct")).with_base_url(&server.base_url);
+    let client = CodexApiClient::new(config).expect("client");
+    assert_eq!(
+        client.normalized_models_endpoint(),
+        format!("{}/codex/models", server.base_url)
+    );
+
+    let models = client.list_models(None).await.expect("listing");
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].slug, "gpt-5.3-codex");
+    assert_eq!(models[0].display_name.as_deref(), Some("GPT-5.3 Codex"));
+    assert_eq!(models[0].context_window, Some(272_000));
+    assert_eq!(models[1].display_name, None);
+    assert_eq!(models[1].context_window, None);
+
+    server.shutdown();
+}
+
+#[tokio::test]
+async fn list_models_integration_reports_status_errors_without_retrying() {
+    let server = ScriptedServer::new(vec![
+        response_json(403, r#"{"error":{"message":"models unavailable"}}"#),
+        response_json(200, r#"{"models":[]}"#),
+    ])
+    .await;
+
+    let config = CodexApiConfig::new(token_with_account_id("acct")).with_base_url(&server.base_url);
+    let client = CodexApiClient::new(config).expect("client");
+
+    let error = client.list_models(None).await.expect_err("403 should fail");
+    assert!(
+        matches!(&error, CodexApiError::Status(status, message) if status.as_u16() == 403 && message.contains("models unavailable")),
+        "unexpected error: {error:?}"
+    );
+    assert_eq!(server.request_count(), 1);
+
+    server.shutdown();
+}
@@ -1,5 +1,5 @@
 use base64::{engine::general_purpose, Engine as _};
-use codex_api::payload::CodexReasoning;
+use codex_api::payload::{CodexReasoning, CodexTextFormat};
 use codex_api::{CodexApiClient, CodexApiConfig, CodexRequest};
 use serde_json::{json, Value};
 
@@ -23,15 +23,18 @@ fn payload_serialization_defaults_match_parity_shape() {
     assert_eq!(body["tool_choice"], Value::String("auto".to_string()));
     assert_eq!(body["parallel_tool_calls"], Value::Bool(true));
     assert!(body.get("prompt_cache_key").is_none());
+    assert!(body.get("prompt_cache_retention").is_none());
     assert!(body.get("temperature").is_none());
     assert!(body.get("reasoning").is_none());
     assert!(body.get("tools").is_none());
+    assert!(body["text"].get("format").is_none());
 }
 
 #[test]
 fn payload_serialization_includes_optional_fields_when_set() {
     let mut request = CodexRequest::new("gpt-codex", user_input("hi"), Some("sys".to_string()));
     request.prompt_cache_key = Some("session-1".to_string());
+    request.prompt_cache_retention = Some("24h".to_string());
     request.temperature = Some(0.2);
     request.reasoning = Some(CodexReasoning {
         effort: Some("low".to_string()),
@@ -47,6 +50,7 @@ fn payload_serialization_includes_optional_fields_when_set() {
         body["prompt_cache_key"],
         Value::String("session-1".to_string())
     );
+    assert_eq!(body["prompt_cache_retention"], json!("24h"));
     assert_eq!(body["temperature"], json!(0.2));
     assert_eq!(
         body["reasoning"]["effort"],
@@ -265,3 +269,19 @@ fn token_with_account_id(account_id: &str) -> String {
     let payload = general_purpose::URL_SAFE_NO_PAD.encode(payload);
     format!("header.{payload}.signature")
 }
+
+#[test]
+fn payload_serialization_includes_json_schema_text_format() {
+    let mut request = CodexRequest::new("gpt-codex", user_input("hi"), Some("sys".to_string()));
+    let schema = json!({"type": "object", "properties": {}});
+    request.text.format = Some(CodexTextFormat::json_schema("output", schema.clone()));
+
+    let body = serde_json::to_value(&request).expect("serialize payload");
+    assert_eq!(
+        body["text"],
+        json!({
+            "verbosity": "medium",
+            "format": {"type": "json_schema", "name": "output", "schema": schema, "strict": true},
+        })
+    );
+}
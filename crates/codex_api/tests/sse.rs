@@ -220,3 +220,151 @@ fn sse_parser_response_failed_with_empty_message_maps_to_none() {
         Some(CodexStreamEvent::ResponseFailed { message: None })
     ));
 }
+
+/// Frames for `corpus_events`, built with the given line ending, optional BOM
+/// and the framing noise proxies add: comments, `event:`/`id:`/`retry:`
+/// fields, `data:` without a space, and JSON split over several data lines.
+fn corpus_stream(eol: &str, bom: bool) -> String {
+    let lines: Vec<&str> = vec![
+        ": keep-alive",
+        "",
+        "event: response.output_text.delta",
+        "id: 1",
+        "data: {\"type\":\"response.output_text.delta\",",
+        "data:\"delta\":\"Hel\"}",
+        "",
+        ":comment between events",
+        "retry: 1500",
+        "data: {\"type\":\"response.output_text.delta\",\"delta\":\"lo 🙂\"}",
+        "",
+        "id: 2",
+        "data",
+        "",
+        "data: {\"type\":\"response.output_item.done\",",
+        ": comment inside an event",
+        "data:  \"item\":{\"type\":\"function_call\",\"id\":\"fc_1\",\"call_id\":\"call_1\",\"name\":\"read\",\"arguments\":\"{}\"}}",
+        "",
+        "data: [DONE]",
+        "",
+        "data: {\"type\":\"response.completed\",\"response\":{\"status\":\"completed\"}}",
+        "",
+    ];
+    let mut stream = if bom {
+        "\u{FEFF}".to_string()
+    } else {
+        String::new()
+    };
+    for line in lines {
+        stream.push_str(line);
+        stream.push_str(eol);
+    }
+    stream
+}
+
+fn corpus_events() -> Vec<CodexStreamEvent> {
+    vec![
+        CodexStreamEvent::OutputTextDelta {
+            delta: "Hel".to_string(),
+        },
+        CodexStreamEvent::OutputTextDelta {
+            delta: "lo 🙂".to_string(),
+        },
+        CodexStreamEvent::OutputItemDone {
+            id: Some("fc_1".to_string()),
+            status: None,
+        },
+        CodexStreamEvent::ToolCallRequested {
+            id: Some("fc_1".to_string()),
+            call_id: Some("call_1".to_string()),
+            tool_name: Some("read".to_string()),
+            arguments: Some(serde_json::Value::String("{}".to_string())),
+        },
+        CodexStreamEvent::ResponseCompleted {
+            status: Some(CodexResponseStatus::Completed),
+        },
+    ]
+}
+
+/// Feeds `stream` in chunks of pseudo-random size (1..=max_chunk) from a
+/// fixed-seed LCG.
+fn feed_in_chunks(
+    stream: &[u8],
+    seed: u64,
+    max_chunk: usize,
+) -> (Vec<CodexStreamEvent>, SseStreamParser) {
+    let mut parser = SseStreamParser::default();
+    let mut state = seed;
+    let mut events = Vec::new();
+    let mut rest = stream;
+    while !rest.is_empty() {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        let size = ((state >> 33) as usize % max_chunk + 1).min(rest.len());
+        let (chunk, tail) = rest.split_at(size);
+        events.extend(parser.feed(chunk));
+        // Empty feeds must not disturb a pending CR.
+        events.extend(parser.feed(&[]));
+        rest = tail;
+    }
+    (events, parser)
+}
+
+#[test]
+fn sse_parser_corpus_is_independent_of_line_endings_bom_and_chunking() {
+    let expected = corpus_events();
+    for eol in ["\n", "\r\n", "\r"] {
+        for bom in [false, true] {
+            let stream = corpus_stream(eol, bom);
+            let label = format!("eol={eol:?} bom={bom}");
+            assert_eq!(
+                SseStreamParser::parse_frames(&stream),
+                expected,
+                "{label} in one shot"
+            );
+
+            for max_chunk in [1, 2, 3, 7, 64] {
+                for seed in 0..16 {
+                    let (events, parser) = feed_in_chunks(stream.as_bytes(), seed, max_chunk);
+                    assert_eq!(
+                        events, expected,
+                        "{label} max_chunk={max_chunk} seed={seed}"
+                    );
+                    assert!(parser.is_empty_buffer(), "{label} left a partial event");
+                    assert_eq!(parser.last_event_id(), Some("2"), "{label}");
+                    assert_eq!(
+                        parser.retry(),
+                        Some(std::time::Duration::from_millis(1500)),
+                        "{label}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn sse_parser_ignores_invalid_retry_and_keeps_bom_only_at_stream_start() {
+    let mut parser = SseStreamParser::default();
+    assert!(parser.feed(b"\xEF\xBB").is_empty());
+    assert!(parser
+        .feed(b"\xBFretry: 10ms\r\nid: a\x00b\r\n\r\n")
+        .is_empty());
+    assert_eq!(parser.retry(), None);
+    assert_eq!(parser.last_event_id(), None);
+
+    // A BOM mid-stream is part of the field name, so the line is ignored.
+    let events = parser.feed(
+        "\u{FEFF}data: {\"type\":\"response.output_text.delta\",\"delta\":\"x\"}\n\n".as_bytes(),
+    );
+    assert!(events.is_empty());
+}
+
+#[test]
+fn sse_parser_drops_an_event_truncated_before_its_blank_line() {
+    let mut parser = SseStreamParser::default();
+    assert!(parser
+        .feed(b"data: {\"type\":\"response.output_text.delta\",\"delta\":\"x\"}\r\n")
+        .is_empty());
+    assert!(!parser.is_empty_buffer());
+}
@@ -1,4 +1,4 @@
-use codex_api::normalize_codex_url;
+use codex_api::{normalize_codex_models_url, normalize_codex_url};
 
 #[test]
 fn url_normalization_keeps_existing_responses_endpoint() {
@@ -23,3 +23,17 @@ fn url_normalization_appends_codex_responses_to_generic_base() {
         "https://chatgpt.com/backend-api/codex/responses"
     );
 }
+
+#[test]
+fn models_url_normalization_shares_the_codex_base() {
+    for input in [
+        "https://chatgpt.com/backend-api",
+        "https://chatgpt.com/backend-api/codex",
+        "https://chatgpt.com/backend-api/codex/responses/",
+    ] {
+        assert_eq!(
+            normalize_codex_models_url(input),
+            "https://chatgpt.com/backend-api/codex/models"
+        );
+    }
+}
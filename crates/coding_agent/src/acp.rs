@@ -0,0 +1,747 @@
+//! Agent Client Protocol front (`coding_agent --acp`).
+//!
+//! Editors that speak ACP (such as Zed) launch the agent as a subprocess and
+//! exchange JSON-RPC 2.0 lines over stdin/stdout. This module maps the
+//! protocol onto the same core the TUI and `headless` use: `App` driven by a
+//! `RuntimeController` on a tape_tui runtime thread over
+//! [`HeadlessTerminal`](crate::headless::HeadlessTerminal).
+//!
+//! Supported client requests: `initialize`, `authenticate`, `session/new`,
+//! `session/prompt` (answered with a `stopReason` once the turn ends), and
+//! the `session/cancel` notification. Assistant text, notes, and tool calls
+//! stream as `session/update` notifications. Mutating tool calls pause for a
+//! `session/request_permission` round trip.
+//!
+//! One process serves one session, the one recorded by the startup session
+//! store, in the agent's working directory; `session/load`, MCP servers, and
+//! client file system access are not offered.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use serde_json::{json, Value};
+use tape_tui::runtime::tui::RuntimeHandle;
+
+use crate::app::{App, Mode, Role, ToolConfirmation};
+use crate::app_events::AppEvent;
+use crate::headless::{
+    response_json, spawn_line_reader, string_param, write_json_line, LineServer, RpcError,
+    INVALID_PARAMS, INVALID_REQUEST, JSONRPC_VERSION, METHOD_NOT_FOUND, PARSE_ERROR,
+    REQUEST_FAILED,
+};
+use crate::runtime::RuntimeController;
+
+pub const ACP_PROTOCOL_VERSION: u64 = 1;
+const ALLOW_OPTION_ID: &str = "allow";
+const REJECT_OPTION_ID: &str = "reject";
+
+#[derive(Debug, Default)]
+struct AcpState {
+    session_created: bool,
+    /// `session/prompt` waiting for its turn to end.
+    prompt: Option<PendingPrompt>,
+    next_request_id: u64,
+    /// Tool call ids of our `session/request_permission` requests, by id.
+    permission_requests: HashMap<u64, String>,
+}
+
+#[derive(Debug)]
+struct PendingPrompt {
+    id: Value,
+    cancelled: bool,
+}
+
+/// Serves one ACP session over line-delimited JSON-RPC.
+///
+/// Like [`HeadlessServer`](crate::headless::HeadlessServer), lines are
+/// handled on the runtime thread, and [`AcpServer::flush_events`] should run
+/// after every runtime tick.
+pub struct AcpServer<W: Write + Send + 'static> {
+    app: Arc<Mutex<App>>,
+    host: Arc<RuntimeController>,
+    session_id: String,
+    workspace_root: PathBuf,
+    output: Mutex<W>,
+    events: Mutex<Receiver<AppEvent>>,
+    state: Mutex<AcpState>,
+}
+
+impl<W: Write + Send + 'static> AcpServer<W> {
+    /// Serves `session_id` for `workspace_root`, the agent's working
+    /// directory, and writes to `output`.
+    pub fn new(
+        app: Arc<Mutex<App>>,
+        host: Arc<RuntimeController>,
+        session_id: impl Into<String>,
+        workspace_root: impl Into<PathBuf>,
+        output: W,
+    ) -> Arc<Self> {
+        let events = lock_unpoisoned(&app).subscribe();
+        Arc::new(Self {
+            app,
+            host,
+            session_id: session_id.into(),
+            workspace_root: workspace_root.into(),
+            output: Mutex::new(output),
+            events: Mutex::new(events),
+            state: Mutex::new(AcpState::default()),
+        })
+    }
+
+    /// Reads messages from `input` on a background thread and hands each line
+    /// to the runtime thread. End of input stops the agent.
+    pub fn spawn_reader<R: BufRead + Send + 'static>(
+        self: &Arc<Self>,
+        input: R,
+        runtime_handle: RuntimeHandle,
+    ) -> io::Result<JoinHandle<()>> {
+        spawn_line_reader(Arc::clone(self), input, runtime_handle)
+    }
+
+    /// Sends updates for every event published since the last call, and
+    /// answers the pending prompt if its turn ended.
+    pub fn flush_events(&self) {
+        let events: Vec<AppEvent> = lock_unpoisoned(&self.events).try_iter().collect();
+        let mut turn_end = None;
+        for event in events {
+            match event {
+                AppEvent::MessageAdded { message, .. } => match message.role {
+                    Role::Assistant if !message.content.is_empty() => {
+                        self.send_message_chunk(&message.content);
+                    }
+                    Role::System => self.send_message_chunk(&format!("{}\n\n", message.content)),
+                    _ => {}
+                },
+                AppEvent::ChunkAppended { chunk, .. } => self.send_message_chunk(&chunk),
+                AppEvent::ToolStarted {
+                    call_id,
+                    tool_name,
+                    arguments,
+                    ..
+                } => self.send_update(json!({
+                    "sessionUpdate": "tool_call",
+                    "toolCallId": call_id,
+                    "title": tool_name,
+                    "kind": tool_kind(&tool_name),
+                    "status": "in_progress",
+                    "rawInput": arguments,
+                })),
+                AppEvent::ToolFinished {
+                    call_id,
+                    is_error,
+                    content,
+                    ..
+                } => {
+                    let text = match &content {
+                        Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    self.send_update(json!({
+                        "sessionUpdate": "tool_call_update",
+                        "toolCallId": call_id,
+                        "status": if is_error { "failed" } else { "completed" },
+                        "content": [text_tool_content(&text)],
+                        "rawOutput": content,
+                    }));
+                }
+                AppEvent::ToolConfirmationRequested { confirmation } => {
+                    self.request_permission(&confirmation);
+                }
+                AppEvent::RunStateChanged { mode } => match mode {
+                    Mode::Running { .. } => turn_end = None,
+                    Mode::Idle => turn_end = Some(Ok(())),
+                    Mode::Error(error) => turn_end = Some(Err(error)),
+                    Mode::Exiting => turn_end = Some(Ok(())),
+                },
+                AppEvent::MessageUpdated { .. } | AppEvent::TranscriptReplaced { .. } => {}
+            }
+        }
+
+        if let Some(outcome) = turn_end {
+            self.finish_prompt(outcome);
+        }
+    }
+
+    /// Handles one incoming line. Call on the runtime thread.
+    pub fn handle_line(&self, line: &str) {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(error) => {
+                self.write(&response_json(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, format!("Parse error: {error}"))),
+                ));
+                return;
+            }
+        };
+
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(Value::as_str);
+        if message.get("jsonrpc") != Some(&json!(JSONRPC_VERSION)) {
+            self.write(&response_json(
+                id.unwrap_or(Value::Null),
+                Err(RpcError::new(INVALID_REQUEST, "Invalid request")),
+            ));
+            return;
+        }
+
+        let Some(method) = method else {
+            // A response from the client to one of our requests.
+            if let Some(id) = id.as_ref().and_then(Value::as_u64) {
+                self.handle_permission_response(id, &message);
+            }
+            return;
+        };
+
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = self.handle_request(method, id.clone(), &params);
+        self.flush_events();
+        if let Some(id) = id {
+            // `None` defers the response; `session/prompt` answers when its
+            // turn ends.
+            if let Some(result) = result {
+                self.write(&response_json(id, result));
+            }
+        }
+    }
+
+    fn handle_request(
+        &self,
+        method: &str,
+        id: Option<Value>,
+        params: &Value,
+    ) -> Option<Result<Value, RpcError>> {
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": ACP_PROTOCOL_VERSION,
+                "agentCapabilities": {
+                    "loadSession": false,
+                    "promptCapabilities": {
+                        "image": false,
+                        "audio": false,
+                        "embeddedContext": true,
+                    },
+                },
+                "authMethods": [],
+            })),
+            "authenticate" => Ok(Value::Null),
+            "session/new" => self.new_session(params),
+            "session/prompt" => return self.prompt(id, params),
+            "session/cancel" => {
+                self.cancel(params);
+                Ok(Value::Null)
+            }
+            unknown => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {unknown}"),
+            )),
+        };
+        Some(result)
+    }
+
+    fn new_session(&self, params: &Value) -> Result<Value, RpcError> {
+        let cwd = string_param(params, "cwd")?;
+        let canonical_cwd = PathBuf::from(&cwd).canonicalize().unwrap_or(cwd.into());
+        if canonical_cwd != self.workspace_root {
+            return Err(RpcError::new(
+                REQUEST_FAILED,
+                format!(
+                    "Sessions must use the agent's working directory {}",
+                    self.workspace_root.display()
+                ),
+            ));
+        }
+
+        let mut state = lock_unpoisoned(&self.state);
+        if state.session_created {
+            return Err(RpcError::new(
+                REQUEST_FAILED,
+                "This agent process serves a single session",
+            ));
+        }
+        let mut host = Arc::clone(&self.host);
+        // The editor cannot answer the TUI's y/n prompt, so every mutating
+        // tool call goes through `session/request_permission`.
+        lock_unpoisoned(&self.app)
+            .set_tool_preview(true, &mut host)
+            .map_err(|error| RpcError::new(REQUEST_FAILED, error))?;
+        state.session_created = true;
+        Ok(json!({ "sessionId": self.session_id }))
+    }
+
+    fn prompt(&self, id: Option<Value>, params: &Value) -> Option<Result<Value, RpcError>> {
+        if let Err(error) = self.check_session(params) {
+            return Some(Err(error));
+        }
+        let Some(id) = id else {
+            return Some(Err(RpcError::new(
+                INVALID_REQUEST,
+                "session/prompt must be a request",
+            )));
+        };
+        let text = match prompt_text(params) {
+            Ok(text) => text,
+            Err(error) => return Some(Err(error)),
+        };
+
+        let mut state = lock_unpoisoned(&self.state);
+        if state.prompt.is_some() {
+            return Some(Err(RpcError::new(
+                REQUEST_FAILED,
+                "A prompt is already in progress",
+            )));
+        }
+
+        let mut host = Arc::clone(&self.host);
+        let mut app = lock_unpoisoned(&self.app);
+        if !app.can_start_queued_turn() {
+            // A cancelled run is still stopping; a prompt now would be queued
+            // behind it and end without this request being answered.
+            return Some(Err(RpcError::new(
+                REQUEST_FAILED,
+                "The previous turn is still stopping",
+            )));
+        }
+        let mode_before = app.mode.clone();
+        app.on_input_replace(text);
+        app.on_submit(&mut host);
+        match &app.mode {
+            Mode::Running { .. } => {
+                state.prompt = Some(PendingPrompt {
+                    id,
+                    cancelled: false,
+                });
+                None
+            }
+            Mode::Error(error) if app.mode != mode_before => {
+                Some(Err(RpcError::new(REQUEST_FAILED, error.clone())))
+            }
+            // Slash commands finish without a run.
+            _ => Some(Ok(json!({ "stopReason": "end_turn" }))),
+        }
+    }
+
+    fn cancel(&self, params: &Value) {
+        if self.check_session(params).is_err() {
+            return;
+        }
+        if let Some(prompt) = lock_unpoisoned(&self.state).prompt.as_mut() {
+            prompt.cancelled = true;
+        }
+        let mut host = Arc::clone(&self.host);
+        let mut app = lock_unpoisoned(&self.app);
+        if matches!(app.mode, Mode::Running { .. }) {
+            app.on_cancel(&mut host);
+        }
+    }
+
+    fn check_session(&self, params: &Value) -> Result<(), RpcError> {
+        let session_id = string_param(params, "sessionId")?;
+        if !lock_unpoisoned(&self.state).session_created || session_id != self.session_id {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                format!("Unknown session {session_id}"),
+            ));
+        }
+        Ok(())
+    }
+
+    fn finish_prompt(&self, outcome: Result<(), String>) {
+        let Some(prompt) = lock_unpoisoned(&self.state).prompt.take() else {
+            return;
+        };
+        let result = match outcome {
+            Ok(()) if prompt.cancelled => Ok(json!({ "stopReason": "cancelled" })),
+            Ok(()) => Ok(json!({ "stopReason": "end_turn" })),
+            Err(error) => Err(RpcError::new(REQUEST_FAILED, error)),
+        };
+        self.write(&response_json(prompt.id, result));
+    }
+
+    fn request_permission(&self, confirmation: &ToolConfirmation) {
+        let id = {
+            let mut state = lock_unpoisoned(&self.state);
+            state.next_request_id += 1;
+            let id = state.next_request_id;
+            state
+                .permission_requests
+                .insert(id, confirmation.call_id.clone());
+            id
+        };
+        self.write(&json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "id": id,
+            "method": "session/request_permission",
+            "params": {
+                "sessionId": self.session_id,
+                "toolCall": {
+                    "toolCallId": confirmation.call_id,
+                    "title": confirmation.tool_name,
+                    "kind": tool_kind(&confirmation.tool_name),
+                    "status": "pending",
+                    "content": [text_tool_content(&confirmation.diff)],
+                },
+                "options": [
+                    { "optionId": ALLOW_OPTION_ID, "name": "Allow", "kind": "allow_once" },
+                    { "optionId": REJECT_OPTION_ID, "name": "Reject", "kind": "reject_once" },
+                ],
+            },
+        }));
+    }
+
+    /// Applies the client's answer to a permission request. Anything but the
+    /// allow option, including errors and a cancelled outcome, rejects.
+    fn handle_permission_response(&self, id: u64, response: &Value) {
+        let Some(call_id) = lock_unpoisoned(&self.state).permission_requests.remove(&id) else {
+            return;
+        };
+        let outcome = &response["result"]["outcome"];
+        let approved =
+            outcome["outcome"] == "selected" && outcome["optionId"] == json!(ALLOW_OPTION_ID);
+
+        let mut host = Arc::clone(&self.host);
+        let mut app = lock_unpoisoned(&self.app);
+        if app
+            .pending_tool_confirmation()
+            .is_some_and(|confirmation| confirmation.call_id == call_id)
+        {
+            app.resolve_tool_confirmation(approved, &mut host);
+        }
+    }
+
+    fn send_message_chunk(&self, text: &str) {
+        self.send_update(json!({
+            "sessionUpdate": "agent_message_chunk",
+            "content": { "type": "text", "text": text },
+        }));
+    }
+
+    fn send_update(&self, update: Value) {
+        if !lock_unpoisoned(&self.state).session_created {
+            return;
+        }
+        self.write(&json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "method": "session/update",
+            "params": { "sessionId": self.session_id, "update": update },
+        }));
+    }
+
+    fn write(&self, message: &Value) {
+        write_json_line(&self.output, message);
+    }
+}
+
+impl<W: Write + Send + 'static> LineServer for AcpServer<W> {
+    fn handle_line(&self, line: &str) {
+        AcpServer::handle_line(self, line);
+    }
+
+    fn end_of_input(&self) {
+        let mut host = Arc::clone(&self.host);
+        lock_unpoisoned(&self.app).on_quit(&mut host);
+        self.flush_events();
+    }
+}
+
+/// Joins the prompt's text blocks and embedded text resources into one
+/// submission.
+fn prompt_text(params: &Value) -> Result<String, RpcError> {
+    let blocks = params
+        .get("prompt")
+        .and_then(Value::as_array)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing array param 'prompt'"))?;
+    let mut parts = Vec::new();
+    for block in blocks {
+        match block["type"].as_str() {
+            Some("text") => {
+                if let Some(text) = block["text"].as_str() {
+                    parts.push(text.to_string());
+                }
+            }
+            Some("resource") => {
+                let resource = &block["resource"];
+                if let (Some(uri), Some(text)) =
+                    (resource["uri"].as_str(), resource["text"].as_str())
+                {
+                    parts.push(format!("{uri}:\n```\n{text}\n```"));
+                }
+            }
+            Some("resource_link") => {
+                if let Some(uri) = block["uri"].as_str() {
+                    parts.push(uri.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let text = parts.join("\n\n");
+    if text.trim().is_empty() {
+        return Err(RpcError::new(INVALID_PARAMS, "Prompt has no text content"));
+    }
+    Ok(text)
+}
+
+fn tool_kind(tool_name: &str) -> &'static str {
+    match tool_name {
+        "read" | "git_status" | "git_diff" | "process_status" | "process_logs" => "read",
+        "write" | "edit" | "apply_patch" => "edit",
+        "bash" | "bash_background" | "git_commit" | "restart_shell" | "process_kill" => "execute",
+        "update_plan" => "think",
+        _ => "other",
+    }
+}
+
+fn text_tool_content(text: &str) -> Value {
+    json!({ "type": "content", "content": { "type": "text", "text": text } })
+}
+
+fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use serde_json::{json, Value};
+    use tape_tui::TUI;
+
+    use super::{lock_unpoisoned, AcpServer};
+    use crate::app::{App, Mode, ToolConfirmation};
+    use crate::headless::HeadlessTerminal;
+    use crate::providers::MockProvider;
+    use crate::runtime::RuntimeController;
+
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            lock_unpoisoned(&self.0).extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        fn messages(&self) -> Vec<Value> {
+            String::from_utf8(lock_unpoisoned(&self.0).clone())
+                .expect("utf-8 output")
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("one JSON object per line"))
+                .collect()
+        }
+    }
+
+    struct Harness {
+        runtime: TUI<HeadlessTerminal>,
+        app: Arc<Mutex<App>>,
+        server: Arc<AcpServer<SharedOutput>>,
+        output: SharedOutput,
+        cwd: PathBuf,
+        _workspace: tempfile::TempDir,
+    }
+
+    fn harness() -> Harness {
+        let workspace = tempfile::tempdir().expect("workspace");
+        let cwd = workspace
+            .path()
+            .canonicalize()
+            .expect("canonical workspace");
+        let app = Arc::new(Mutex::new(App::new()));
+        let mut runtime = TUI::new(HeadlessTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(MockProvider::new(vec![
+                "Hello ".to_string(),
+                "there".to_string(),
+            ])),
+        );
+        let output = SharedOutput::default();
+        let server = AcpServer::new(
+            Arc::clone(&app),
+            host,
+            "session-1",
+            cwd.clone(),
+            output.clone(),
+        );
+        runtime.start().expect("runtime start");
+        Harness {
+            runtime,
+            app,
+            server,
+            output,
+            cwd,
+            _workspace: workspace,
+        }
+    }
+
+    impl Harness {
+        fn send(&self, message: Value) {
+            self.server.handle_line(&message.to_string());
+        }
+
+        fn open_session(&self) {
+            self.send(json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize",
+                "params": { "protocolVersion": 1, "clientCapabilities": {} } }));
+            self.send(json!({ "jsonrpc": "2.0", "id": 1, "method": "session/new",
+                "params": { "cwd": self.cwd, "mcpServers": [] } }));
+        }
+
+        fn prompt(&self, id: i64, text: &str) {
+            self.send(
+                json!({ "jsonrpc": "2.0", "id": id, "method": "session/prompt",
+                "params": { "sessionId": "session-1",
+                    "prompt": [{ "type": "text", "text": text }] } }),
+            );
+        }
+
+        fn run_until(&mut self, done: impl Fn(&[Value]) -> bool) -> Vec<Value> {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                let messages = self.output.messages();
+                if done(&messages) {
+                    return messages;
+                }
+                assert!(Instant::now() < deadline, "timed out: {messages:#?}");
+                self.runtime.run_once();
+                self.server.flush_events();
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+
+    fn response(messages: &[Value], id: i64) -> Option<&Value> {
+        messages
+            .iter()
+            .find(|message| message["id"] == json!(id) && message.get("method").is_none())
+    }
+
+    #[test]
+    fn prompt_streams_updates_and_answers_when_the_turn_ends() {
+        let mut harness = harness();
+        harness.open_session();
+        harness.prompt(2, "hi");
+        let messages = harness.run_until(|messages| response(messages, 2).is_some());
+
+        assert_eq!(
+            response(&messages, 0).expect("initialize")["result"]["protocolVersion"],
+            1
+        );
+        assert_eq!(
+            response(&messages, 1).expect("session/new")["result"],
+            json!({ "sessionId": "session-1" })
+        );
+        assert!(lock_unpoisoned(&harness.app).tool_preview());
+
+        let streamed: String = messages
+            .iter()
+            .filter(|message| message["method"] == "session/update")
+            .map(|message| {
+                assert_eq!(message["params"]["sessionId"], "session-1");
+                let update = &message["params"]["update"];
+                assert_eq!(update["sessionUpdate"], "agent_message_chunk");
+                update["content"]["text"]
+                    .as_str()
+                    .expect("text")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(streamed, "Hello there");
+        assert_eq!(
+            response(&messages, 2).expect("prompt")["result"],
+            json!({ "stopReason": "end_turn" })
+        );
+        // The answer comes after every update of its turn.
+        assert_eq!(messages.last(), response(&messages, 2));
+    }
+
+    #[test]
+    fn cancel_ends_the_prompt_as_cancelled() {
+        let mut harness = harness();
+        harness.open_session();
+        harness.prompt(2, "hi");
+        harness.send(json!({ "jsonrpc": "2.0", "method": "session/cancel",
+            "params": { "sessionId": "session-1" } }));
+        let messages = harness.run_until(|messages| response(messages, 2).is_some());
+
+        assert_eq!(
+            response(&messages, 2).expect("prompt")["result"],
+            json!({ "stopReason": "cancelled" })
+        );
+    }
+
+    #[test]
+    fn permission_requests_resolve_the_paused_tool_call() {
+        let harness = harness();
+        harness.open_session();
+        {
+            let mut app = lock_unpoisoned(&harness.app);
+            app.set_mode(Mode::Running { run_id: 3 });
+            app.on_tool_confirmation_requested(ToolConfirmation {
+                run_id: 3,
+                call_id: "call-7".to_string(),
+                tool_name: "write".to_string(),
+                diff: "+new line".to_string(),
+            });
+        }
+        harness.server.flush_events();
+
+        let messages = harness.output.messages();
+        let request = messages
+            .iter()
+            .find(|message| message["method"] == "session/request_permission")
+            .expect("permission request");
+        assert_eq!(request["params"]["toolCall"]["toolCallId"], "call-7");
+        assert_eq!(request["params"]["toolCall"]["kind"], "edit");
+
+        harness.send(json!({ "jsonrpc": "2.0", "id": request["id"],
+            "result": { "outcome": { "outcome": "selected", "optionId": "allow" } } }));
+        assert!(lock_unpoisoned(&harness.app)
+            .pending_tool_confirmation()
+            .is_none());
+    }
+
+    #[test]
+    fn sessions_are_checked_before_prompts_run() {
+        let harness = harness();
+        harness.prompt(1, "too early");
+        harness.send(json!({ "jsonrpc": "2.0", "id": 2, "method": "session/new",
+            "params": { "cwd": "/somewhere/else", "mcpServers": [] } }));
+        harness.open_session();
+        harness.send(json!({ "jsonrpc": "2.0", "id": 3, "method": "session/new",
+            "params": { "cwd": harness.cwd, "mcpServers": [] } }));
+        harness.send(
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "session/prompt",
+            "params": { "sessionId": "session-1", "prompt": [{ "type": "image" }] } }),
+        );
+        harness.send(json!({ "jsonrpc": "2.0", "id": 5, "method": "session/load",
+            "params": {} }));
+
+        let messages = harness.output.messages();
+        let code = |id| response(&messages, id).expect("response")["error"]["code"].clone();
+        assert_eq!(code(1), json!(-32602));
+        assert_eq!(code(2), json!(-32000));
+        assert_eq!(code(3), json!(-32000));
+        assert_eq!(code(4), json!(-32602));
+        assert_eq!(code(5), json!(-32601));
+        assert!(lock_unpoisoned(&harness.app).transcript.is_empty());
+    }
+}
@@ -1,5 +1,18 @@
-use crate::commands::{parse_slash_command, SlashCommand};
-use crate::provider::RunMessage;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app_events::{AppEvent, AppSubscribers};
+use crate::changes::{FileChange, FileChangeSet, FileSnapshot};
+use crate::checkpoints::CheckpointStore;
+use crate::commands::{parse_slash_command, CopyTarget, SlashCommand};
+use crate::pricing::PricingTable;
+use crate::project_instructions::{discover_project_instructions, prepend_project_instructions};
+use crate::prompts::{load_prompt_template, parse_prompt_invocation, PromptForm};
+use crate::provider::{ModelInfo, ProviderProfile, RunMessage, TokenUsage};
+use crate::subagent::SubagentStep;
+use crate::tools::Plan;
 
 pub type RunId = u64;
 
@@ -27,6 +40,149 @@ pub struct Message {
     pub run_id: Option<RunId>,
 }
 
+/// One failed provider request attempt reported during a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttemptFailure {
+    /// 1-based attempt number within its request.
+    pub attempt: u32,
+    /// HTTP status, when the attempt got a response.
+    pub status: Option<u16>,
+    pub error: String,
+    pub will_retry: bool,
+}
+
+/// Transcript note for a failed attempt the provider is retrying.
+pub fn retry_note(attempt: u32, status: Option<u16>, error: &str) -> String {
+    match status {
+        Some(status) => format!("Attempt {attempt} failed (HTTP {status}), retrying: {error}"),
+        None => format!("Attempt {attempt} failed, retrying: {error}"),
+    }
+}
+
+/// Transcript note for a failed run; `status` and `attempts` describe its
+/// final request.
+pub fn run_failed_note(error: &str, status: Option<u16>, attempts: u32) -> String {
+    let mut details = Vec::new();
+    if let Some(status) = status {
+        details.push(format!("HTTP {status}"));
+    }
+    if attempts > 1 {
+        details.push(format!("after {attempts} attempts"));
+    }
+    if details.is_empty() {
+        format!("Run failed: {error}")
+    } else {
+        format!("Run failed ({}): {error}", details.join(", "))
+    }
+}
+
+/// Per-turn metadata tracked alongside transcript messages for one run.
+///
+/// Metadata is keyed by run id and kept outside `Message` so transcript rows stay
+/// plain display data; it is in-memory only and not replayed from sessions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnMetadata {
+    pub started_at: SystemTime,
+    pub finished_at: Option<SystemTime>,
+    pub provider_id: String,
+    pub model_id: String,
+    pub thinking_level: Option<String>,
+    pub usage: Option<TokenUsage>,
+    /// Run cost in micro-USD; `None` while the model is unpriced or no usage
+    /// has been reported.
+    pub cost_micros: Option<u64>,
+    /// Failed provider request attempts, oldest first.
+    pub failed_attempts: Vec<AttemptFailure>,
+}
+
+impl TurnMetadata {
+    pub fn new(profile: &ProviderProfile, started_at: SystemTime) -> Self {
+        Self {
+            started_at,
+            finished_at: None,
+            provider_id: profile.provider_id.clone(),
+            model_id: profile.model_id.clone(),
+            thinking_level: profile.thinking_level.clone(),
+            usage: None,
+            cost_micros: None,
+            failed_attempts: Vec::new(),
+        }
+    }
+
+    /// Returns wall-clock run duration once the run reached a terminal state.
+    pub fn duration(&self) -> Option<Duration> {
+        self.finished_at.map(|finished_at| {
+            finished_at
+                .duration_since(self.started_at)
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Live progress of the active run's stream, shown in the status line.
+///
+/// Uses monotonic time and only changes on run events; the status line reads
+/// it on each render, so it never invalidates the transcript render cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunMetrics {
+    pub run_id: RunId,
+    pub started_at: Instant,
+    pub first_chunk_at: Option<Instant>,
+    /// Characters of assistant text streamed so far.
+    pub streamed_chars: usize,
+}
+
+impl RunMetrics {
+    /// Streams shorter than this report no rate; one early burst would
+    /// overstate it.
+    pub const MIN_RATE_WINDOW: Duration = Duration::from_millis(500);
+
+    pub fn new(run_id: RunId, started_at: Instant) -> Self {
+        Self {
+            run_id,
+            started_at,
+            first_chunk_at: None,
+            streamed_chars: 0,
+        }
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started_at)
+    }
+
+    /// Time from the run starting to its first text chunk.
+    pub fn time_to_first_chunk(&self) -> Option<Duration> {
+        self.first_chunk_at
+            .map(|first_chunk_at| first_chunk_at.saturating_duration_since(self.started_at))
+    }
+
+    /// Seconds spent streaming since the first chunk, once that is at least
+    /// [`Self::MIN_RATE_WINDOW`].
+    pub fn streaming_secs(&self, now: Instant) -> Option<f64> {
+        let streaming = now.saturating_duration_since(self.first_chunk_at?);
+        (streaming >= Self::MIN_RATE_WINDOW).then_some(streaming.as_secs_f64())
+    }
+}
+
+/// Wall-clock bounds of one tool call, kept beside the transcript like
+/// [`TurnMetadata`] so tool cards can show how long a call took.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolCallTiming {
+    pub started_at: SystemTime,
+    pub finished_at: Option<SystemTime>,
+}
+
+impl ToolCallTiming {
+    /// Returns the call duration once the tool reported its result.
+    pub fn duration(&self) -> Option<Duration> {
+        self.finished_at.map(|finished_at| {
+            finished_at
+                .duration_since(self.started_at)
+                .unwrap_or_default()
+        })
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 struct InputHistory {
     entries: Vec<String>,
@@ -97,6 +253,45 @@ struct PendingRunMemory {
     entries: Vec<RunMessage>,
 }
 
+/// Workspace checkpoints recorded during one user turn, in call order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnCheckpoints {
+    pub prompt: String,
+    pub checkpoint_ids: Vec<String>,
+    /// `None` for turns restored from a session file.
+    pub run_id: Option<RunId>,
+}
+
+/// Mutating tool call paused in preview mode until the user approves it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolConfirmation {
+    pub run_id: RunId,
+    pub call_id: String,
+    pub tool_name: String,
+    /// Unified diff of the changes the call would make.
+    pub diff: String,
+}
+
+/// Tool call the provider is still writing, shown before it executes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamingToolCall {
+    pub run_id: RunId,
+    pub call_id: String,
+    pub tool_name: String,
+    /// Arguments parsed so far; fields appear as the model writes them.
+    pub arguments: serde_json::Value,
+}
+
+/// Identifier for a user turn waiting in the host run queue.
+pub type QueuedTurnId = u64;
+
+/// User prompt waiting in the host run queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedTurn {
+    pub id: QueuedTurnId,
+    pub prompt: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct App {
     pub mode: Mode,
@@ -109,6 +304,34 @@ pub struct App {
     pub should_exit: bool,
     cancelling_run: Option<RunId>,
     system_instructions: String,
+    project_instruction_files: Vec<String>,
+    turn_metadata: BTreeMap<RunId, TurnMetadata>,
+    run_metrics: Option<RunMetrics>,
+    tool_call_timings: HashMap<(RunId, String), ToolCallTiming>,
+    /// Full output of calls whose result was capped, keyed by call.
+    tool_output_files: HashMap<(RunId, String), PathBuf>,
+    /// Tool calls made by `spawn_agent` sub-agents, keyed by the parent call.
+    subagent_steps: HashMap<(RunId, String), Vec<SubagentStep>>,
+    steered_run: Option<RunId>,
+    pricing: PricingTable,
+    session_cost_micros: Option<u64>,
+    session_title: Option<String>,
+    plan: Option<Plan>,
+    workspace_root: PathBuf,
+    file_changes: FileChangeSet,
+    change_review: Option<Vec<FileChange>>,
+    /// Turns that mutated files, oldest first; `/undo` rewinds them.
+    turn_checkpoints: Vec<TurnCheckpoints>,
+    tool_preview: bool,
+    /// Whether the transcript strips non-styling escapes from model and
+    /// tool output.
+    sanitize_output: bool,
+    pending_tool_confirmation: Option<ToolConfirmation>,
+    streaming_tool_call: Option<StreamingToolCall>,
+    prompt_form: Option<PromptForm>,
+    /// Text for the host to place in the editor on its next render.
+    pending_editor_text: Option<String>,
+    subscribers: AppSubscribers,
 }
 
 pub trait HostOps {
@@ -118,12 +341,111 @@ pub trait HostOps {
         instructions: String,
     ) -> Result<RunId, String>;
     fn cancel_run(&mut self, run_id: RunId);
+    /// Stops a run so a steering prompt can follow it.
+    ///
+    /// Unlike a plain cancel, hosts should let an in-flight tool call finish
+    /// and report its real result. Defaults to [`HostOps::cancel_run`].
+    fn interrupt_run(&mut self, run_id: RunId) {
+        self.cancel_run(run_id);
+    }
     fn request_render(&mut self);
     fn request_stop(&mut self);
+    /// Queues a prompt to start after the active run terminates (FIFO).
+    fn queue_turn(&mut self, _prompt: String) -> Result<QueuedTurnId, String> {
+        Err(ERROR_RUN_QUEUE_UNSUPPORTED.to_string())
+    }
+    /// Queues a prompt ahead of every other queued prompt.
+    fn queue_turn_next(&mut self, prompt: String) -> Result<QueuedTurnId, String> {
+        self.queue_turn(prompt)
+    }
+    /// Removes a queued prompt before it starts; false when it is unknown.
+    fn cancel_queued_turn(&mut self, _id: QueuedTurnId) -> bool {
+        false
+    }
+    /// Returns queued prompts in dispatch order.
+    fn queued_turns(&self) -> Vec<QueuedTurn> {
+        Vec::new()
+    }
+    /// Opens the panel that displays [`App::change_review`].
+    fn show_change_review(&mut self) -> Result<(), String> {
+        Err(ERROR_CHANGE_REVIEW_UNSUPPORTED.to_string())
+    }
+    /// Closes the change-review panel if it is open.
+    fn hide_change_review(&mut self) {}
+    /// Enables or disables per-call confirmation of mutating tools.
+    fn set_tool_preview(&mut self, _enabled: bool) -> Result<(), String> {
+        Err(ERROR_TOOL_PREVIEW_UNSUPPORTED.to_string())
+    }
+    /// Delivers the user's decision for a paused tool call.
+    fn resolve_tool_confirmation(&mut self, _call_id: &str, _approved: bool) {}
+    /// Places text on the system clipboard.
+    fn copy_to_clipboard(&mut self, _text: &str) -> Result<(), String> {
+        Err(ERROR_CLIPBOARD_UNSUPPORTED.to_string())
+    }
+    /// Shows a short-lived confirmation next to the status line.
+    fn show_toast(&mut self, _message: String) {}
+    /// Selects the provider model used by future runs.
+    fn set_model(&mut self, _model_id: &str) -> Result<ProviderProfile, String> {
+        Err(ERROR_PROFILE_SELECTION_UNSUPPORTED.to_string())
+    }
+    /// Selects the provider thinking level used by future runs.
+    fn set_thinking_level(&mut self, _level: &str) -> Result<ProviderProfile, String> {
+        Err(ERROR_PROFILE_SELECTION_UNSUPPORTED.to_string())
+    }
+    /// Opens the panel that displays [`App::prompt_form`].
+    fn show_prompt_form(&mut self) -> Result<(), String> {
+        Err(ERROR_PROMPT_FORM_UNSUPPORTED.to_string())
+    }
+    /// Closes the prompt form panel if it is open.
+    fn hide_prompt_form(&mut self) {}
+    /// Starts fetching the provider's model catalog; the host later delivers
+    /// it through [`App::on_models_listed`].
+    fn request_model_list(&mut self) -> Result<(), String> {
+        Err(ERROR_MODEL_LISTING_UNSUPPORTED.to_string())
+    }
+    /// Persists a new session title and shows it in the terminal title.
+    fn set_session_title(&mut self, _title: &str) -> Result<(), String> {
+        Err(ERROR_SESSIONS_UNSUPPORTED.to_string())
+    }
+    /// Returns the workspace's saved sessions.
+    fn list_sessions(&mut self) -> Result<SessionListings, String> {
+        Err(ERROR_SESSIONS_UNSUPPORTED.to_string())
+    }
+}
+
+/// Saved sessions as listed by `/sessions`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionListings {
+    /// Newest first.
+    pub sessions: Vec<SessionListing>,
+    /// Session files that could not be read, as `<path>: <error>`.
+    pub unreadable: Vec<String>,
 }
 
-const HELP_TEXT: &str = "Commands: /help, /clear, /cancel, /quit";
+/// A saved session as listed by `/sessions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionListing {
+    pub path: PathBuf,
+    pub created_at: String,
+    pub title: Option<String>,
+    /// Whether this is the running session.
+    pub current: bool,
+}
+
+const HELP_TEXT: &str =
+    "Commands: /help, /clear, /cancel, /steer <message>, /dequeue <position>, /changes, /undo [turn], /preview, /copy last [n], /prompt <name> [var=value ...], /model <id>, /models, /thinking <level>, /title [name], /sessions, /quit";
 const ERROR_RUN_ALREADY_ACTIVE: &str = "Run already active";
+const ERROR_RUN_QUEUE_UNSUPPORTED: &str = "Run queue is not supported by this host";
+const ERROR_CHANGE_REVIEW_UNSUPPORTED: &str = "Change review is not supported by this host";
+const ERROR_TOOL_PREVIEW_UNSUPPORTED: &str = "Tool preview is not supported by this host";
+const ERROR_CLIPBOARD_UNSUPPORTED: &str = "Clipboard is not supported by this host";
+const ERROR_PROFILE_SELECTION_UNSUPPORTED: &str =
+    "Model and thinking selection is not supported by this host";
+const ERROR_PROMPT_FORM_UNSUPPORTED: &str = "Prompt forms are not supported by this host";
+const ERROR_MODEL_LISTING_UNSUPPORTED: &str = "Model listing is not supported by this host";
+const ERROR_SESSIONS_UNSUPPORTED: &str = "Sessions are not persisted by this host";
+/// Longest generated session title, in characters.
+const SESSION_TITLE_MAX_CHARS: usize = 48;
 const FATAL_SESSION_PERSISTENCE_ERROR_PREFIX: &str = "Session persistence failed:";
 pub const SYSTEM_INSTRUCTIONS_ENV_VAR: &str = "CODING_AGENT_SYSTEM_INSTRUCTIONS";
 pub const DEFAULT_SYSTEM_INSTRUCTIONS: &str =
@@ -135,9 +457,118 @@ impl Default for App {
     }
 }
 
-pub fn system_instructions_from_env() -> String {
+/// `title` with control characters (C0, DEL and C1) replaced and whitespace
+/// collapsed; `None` when nothing is left. Titles end up in an OSC 0
+/// terminal-title sequence, which an ESC or BEL would end early.
+pub fn sanitize_session_title(title: &str) -> Option<String> {
+    let title = title
+        .replace(char::is_control, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// A session title from the first line of a prompt: whitespace collapsed,
+/// cut at a word boundary to [`SESSION_TITLE_MAX_CHARS`] with an ellipsis.
+/// `None` when the prompt has no text or is a slash command.
+pub fn session_title_from_prompt(prompt: &str) -> Option<String> {
+    let line = prompt
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    if line.starts_with('/') {
+        return None;
+    }
+    let line = sanitize_session_title(line)?;
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut title = String::new();
+    for word in &words {
+        let needed = title.chars().count() + usize::from(!title.is_empty()) + word.chars().count();
+        if needed > SESSION_TITLE_MAX_CHARS {
+            if title.is_empty() {
+                title = word.chars().take(SESSION_TITLE_MAX_CHARS - 1).collect();
+            }
+            title.push('…');
+            return Some(title);
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    Some(title)
+}
+
+/// Returns the bodies of fenced (```` ``` ```` or `~~~`) code blocks in
+/// `markdown`, in order. An unterminated fence runs to the end of the text.
+pub fn fenced_code_blocks(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(char, usize, Vec<&str>)> = None;
+
+    for line in markdown.lines() {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let trimmed = line.trim_start_matches(' ');
+        let fence = (indent <= 3)
+            .then(|| trimmed.chars().next())
+            .flatten()
+            .filter(|marker| *marker == '`' || *marker == '~')
+            .map(|marker| {
+                (
+                    marker,
+                    trimmed.chars().take_while(|ch| *ch == marker).count(),
+                )
+            })
+            .filter(|(_, length)| *length >= 3);
+
+        match (open.as_mut(), fence) {
+            (None, Some((marker, length))) => open = Some((marker, length, Vec::new())),
+            (Some((open_marker, open_length, body)), Some((marker, length)))
+                if marker == *open_marker
+                    && length >= *open_length
+                    && trimmed[length..].trim().is_empty() =>
+            {
+                blocks.push(body.join("\n"));
+                open = None;
+            }
+            (Some((_, _, body)), _) => body.push(line),
+            (None, None) => {}
+        }
+    }
+
+    if let Some((_, _, body)) = open {
+        blocks.push(body.join("\n"));
+    }
+    blocks
+}
+
+/// Formats a token count compactly, rounding to thousands: `272000` is `272k`.
+fn format_token_count(tokens: u64) -> String {
+    if tokens >= 1_000 {
+        format!("{}k", (tokens + 500) / 1_000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Base system instructions plus the project instruction files loaded into them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemInstructions {
+    pub text: String,
+    /// Labels of the loaded project instruction files, outermost first.
+    pub project_files: Vec<String>,
+}
+
+/// Resolves the base instructions from the environment and prepends any
+/// project instruction files found for `workspace_root`.
+pub fn system_instructions_from_env(workspace_root: &Path) -> SystemInstructions {
     let from_env = std::env::var(SYSTEM_INSTRUCTIONS_ENV_VAR).ok();
-    sanitize_system_instructions(from_env)
+    let base = sanitize_system_instructions(from_env);
+    let files = discover_project_instructions(workspace_root);
+    SystemInstructions {
+        text: prepend_project_instructions(&files, &base),
+        project_files: files.into_iter().map(|file| file.label).collect(),
+    }
 }
 
 fn sanitize_system_instructions(raw: Option<String>) -> String {
@@ -170,13 +601,90 @@ impl App {
             should_exit: false,
             cancelling_run: None,
             system_instructions: sanitize_system_instructions(system_instructions),
+            project_instruction_files: Vec::new(),
+            turn_metadata: BTreeMap::new(),
+            run_metrics: None,
+            tool_call_timings: HashMap::new(),
+            tool_output_files: HashMap::new(),
+            subagent_steps: HashMap::new(),
+            steered_run: None,
+            pricing: PricingTable::default(),
+            session_cost_micros: None,
+            session_title: None,
+            plan: None,
+            workspace_root: PathBuf::new(),
+            file_changes: FileChangeSet::default(),
+            change_review: None,
+            turn_checkpoints: Vec::new(),
+            tool_preview: false,
+            sanitize_output: true,
+            pending_tool_confirmation: None,
+            streaming_tool_call: None,
+            prompt_form: None,
+            pending_editor_text: None,
+            subscribers: AppSubscribers::default(),
+        }
+    }
+
+    /// Returns a receiver of [`AppEvent`]s published after this call.
+    ///
+    /// Dropping the receiver unsubscribes it on the next published event.
+    pub fn subscribe(&mut self) -> Receiver<AppEvent> {
+        self.subscribers.subscribe()
+    }
+
+    /// Publishes the event built by `event`, which only runs when someone is
+    /// subscribed.
+    fn publish(&mut self, event: impl FnOnce(&Self) -> AppEvent) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        let event = event(self);
+        self.subscribers.publish(event);
+    }
+
+    /// Sets [`App::mode`], publishing [`AppEvent::RunStateChanged`] when it
+    /// changes.
+    pub fn set_mode(&mut self, mode: Mode) {
+        if self.mode == mode {
+            return;
         }
+        self.mode = mode;
+        self.publish(|app| AppEvent::RunStateChanged {
+            mode: app.mode.clone(),
+        });
+    }
+
+    fn push_message(&mut self, message: Message) {
+        self.transcript.push(message);
+        self.bump_transcript_revision();
+        self.publish(|app| AppEvent::MessageAdded {
+            index: app.transcript.len() - 1,
+            message: app.transcript[app.transcript.len() - 1].clone(),
+        });
+    }
+
+    /// Records a transcript change other than an append or a streamed chunk.
+    fn replace_transcript(&mut self) {
+        self.bump_transcript_revision();
+        self.publish(|app| AppEvent::TranscriptReplaced {
+            messages: app.transcript.clone(),
+        });
     }
 
     pub fn system_instructions(&self) -> &str {
         &self.system_instructions
     }
 
+    /// Records which project instruction files the system instructions include.
+    pub fn set_project_instruction_files(&mut self, labels: Vec<String>) {
+        self.project_instruction_files = labels;
+    }
+
+    pub fn project_instruction_files(&self) -> &[String] {
+        &self.project_instruction_files
+    }
+
     /// Monotonic revision counter for transcript rendering cache invalidation.
     pub fn transcript_revision(&self) -> u64 {
         self.transcript_revision
@@ -189,13 +697,20 @@ impl App {
     /// Restores model-facing conversation memory from persisted session replay,
     /// and hydrates transcript with a stable non-streaming view of prior turns.
     pub fn restore_conversation(&mut self, messages: Vec<RunMessage>) {
-        self.mode = Mode::Idle;
+        self.set_mode(Mode::Idle);
         self.input.clear();
         self.history = InputHistory::default();
         self.should_exit = false;
         self.conversation = messages.clone();
         self.pending_run_memory = None;
         self.cancelling_run = None;
+        self.turn_metadata.clear();
+        self.tool_call_timings.clear();
+        self.tool_output_files.clear();
+        self.subagent_steps.clear();
+        self.steered_run = None;
+        self.plan = None;
+        self.pending_tool_confirmation = None;
 
         self.transcript = messages
             .into_iter()
@@ -250,7 +765,7 @@ impl App {
                 }
             })
             .collect();
-        self.bump_transcript_revision();
+        self.replace_transcript();
     }
 
     /// Returns model-facing conversation messages retained across turns.
@@ -363,7 +878,7 @@ impl App {
                 && message.run_id.is_none()
         }) {
             self.transcript.pop();
-            self.bump_transcript_revision();
+            self.replace_transcript();
         }
     }
 
@@ -409,6 +924,25 @@ impl App {
         }
     }
 
+    /// Inserts replayed notes (such as run failures) into the transcript
+    /// restored by [`App::restore_conversation`]; each note goes before the
+    /// message at its index.
+    pub fn restore_system_notes(&mut self, notes: Vec<(usize, String)>) {
+        for (index, note) in notes.into_iter().rev() {
+            let index = index.min(self.transcript.len());
+            self.transcript.insert(
+                index,
+                Message {
+                    role: Role::System,
+                    content: note,
+                    streaming: false,
+                    run_id: None,
+                },
+            );
+        }
+        self.replace_transcript();
+    }
+
     /// Appends a system message to transcript without mutating control state.
     pub fn push_system_message(&mut self, content: impl Into<String>) {
         self.push_system(content.into());
@@ -433,15 +967,60 @@ impl App {
                     // Persistent-session reset markers are deferred in v1.
                     // `/clear` only mutates in-memory transcript/conversation state.
                     self.transcript.clear();
-                    self.bump_transcript_revision();
+                    self.replace_transcript();
                     self.conversation.clear();
                     self.pending_run_memory = None;
+                    self.turn_metadata.clear();
+                    self.tool_call_timings.clear();
+                    self.tool_output_files.clear();
+                    self.subagent_steps.clear();
+                    self.steered_run = None;
+                    self.plan = None;
                     self.push_system("Transcript cleared".to_string());
                     host.request_render();
                 }
                 SlashCommand::Cancel => {
                     self.on_cancel(host);
                 }
+                SlashCommand::Steer(message) => {
+                    self.on_steer_command(message, host);
+                }
+                SlashCommand::Dequeue(position) => {
+                    self.on_dequeue(position, host);
+                }
+                SlashCommand::Changes => {
+                    self.on_changes_command(host);
+                }
+                SlashCommand::Undo(turn) => {
+                    self.on_undo_command(turn, host);
+                }
+                SlashCommand::Preview => {
+                    self.on_preview_command(host);
+                }
+                SlashCommand::Copy(target) => {
+                    self.on_copy_command(target, host);
+                }
+                SlashCommand::Prompt(args) => {
+                    self.on_prompt_command(&args, host);
+                }
+                SlashCommand::Model(model_id) => {
+                    self.on_model_command(&model_id, host);
+                }
+                SlashCommand::Models => {
+                    if let Err(error) = host.request_model_list() {
+                        self.push_system(format!("Model listing failed: {error}"));
+                    }
+                    host.request_render();
+                }
+                SlashCommand::Thinking(level) => {
+                    self.on_thinking_command(&level, host);
+                }
+                SlashCommand::Title(title) => {
+                    self.on_title_command(&title, host);
+                }
+                SlashCommand::Sessions => {
+                    self.on_sessions_command(host);
+                }
                 SlashCommand::Quit => {
                     self.on_quit(host);
                 }
@@ -454,35 +1033,34 @@ impl App {
             return;
         }
 
-        if matches!(self.mode, Mode::Running { .. }) {
-            self.push_system("Run already in progress. Use /cancel to stop it.".to_string());
-            host.request_render();
+        // Queued turns go first, even when the run before them failed to
+        // start and left the app idle.
+        if self.is_busy() || !host.queued_turns().is_empty() {
+            self.queue_user_turn(prompt, host);
             return;
         }
 
-        if self.cancelling_run.is_some() {
-            self.push_system("Cancelling active run, please wait.".to_string());
-            host.request_render();
-            return;
-        }
+        self.push_history_entry(prompt.clone());
+        self.start_user_turn(prompt, host);
+        host.request_render();
+    }
 
+    fn start_user_turn(&mut self, prompt: String, host: &mut dyn HostOps) {
         let run_messages = self.run_messages_with_pending_user_prompt(&prompt);
 
-        self.push_history_entry(prompt.clone());
-        self.transcript.push(Message {
+        self.push_message(Message {
             role: Role::User,
             content: prompt.clone(),
             streaming: false,
             run_id: None,
         });
-        self.bump_transcript_revision();
         self.conversation.push(RunMessage::UserText {
             text: prompt.clone(),
         });
 
         match host.start_run(run_messages, self.system_instructions.clone()) {
             Ok(run_id) => {
-                self.mode = Mode::Running { run_id };
+                self.set_mode(Mode::Running { run_id });
             }
             Err(error) => {
                 if error == ERROR_RUN_ALREADY_ACTIVE {
@@ -491,7 +1069,7 @@ impl App {
                         "Run already in progress. Use /cancel to stop it.".to_string(),
                     );
                 } else {
-                    self.mode = Mode::Error(error.clone());
+                    self.set_mode(Mode::Error(error.clone()));
                     self.push_system(format!("Failed to start run: {error}"));
                     if error.starts_with(FATAL_SESSION_PERSISTENCE_ERROR_PREFIX) {
                         self.should_exit = true;
@@ -500,7 +1078,92 @@ impl App {
                 }
             }
         }
+    }
+
+    fn is_busy(&self) -> bool {
+        matches!(self.mode, Mode::Running { .. }) || self.cancelling_run.is_some()
+    }
+
+    fn queue_user_turn(&mut self, prompt: String, host: &mut dyn HostOps) {
+        self.push_history_entry(prompt.clone());
+        match host.queue_turn(prompt) {
+            Ok(_) => {
+                let pending = host.queued_turns().len();
+                self.push_system(format!(
+                    "Message queued ({pending} pending). Use /dequeue <n> to drop it."
+                ));
+            }
+            Err(error) => self.push_system(format!("Failed to queue message: {error}")),
+        }
+        host.request_render();
+    }
+
+    /// Queues `prompt` ahead of other queued turns and interrupts the active run.
+    ///
+    /// The host stops the run at its next safe boundary (an in-flight tool call
+    /// is allowed to complete). Partial output is kept in model history and the
+    /// host starts the steering prompt once the interrupted run terminates.
+    fn on_steer_command(&mut self, prompt: String, host: &mut dyn HostOps) {
+        if prompt.is_empty() {
+            self.push_system("Usage: /steer <message>".to_string());
+            host.request_render();
+            return;
+        }
+
+        if !self.is_busy() {
+            self.push_history_entry(prompt.clone());
+            self.start_user_turn(prompt, host);
+            host.request_render();
+            return;
+        }
+
+        self.push_history_entry(prompt.clone());
+        if let Err(error) = host.queue_turn_next(prompt) {
+            self.push_system(format!("Failed to queue message: {error}"));
+            host.request_render();
+            return;
+        }
+
+        if let Mode::Running { run_id } = self.mode {
+            self.steered_run = Some(run_id);
+            self.cancelling_run = Some(run_id);
+            self.finalize_stream(run_id);
+            self.set_mode(Mode::Idle);
+            self.push_system("Interrupting run to send your message".to_string());
+            host.interrupt_run(run_id);
+        } else {
+            self.push_system("Message queued to run next".to_string());
+        }
+
+        host.request_render();
+    }
+
+    fn on_dequeue(&mut self, position: Option<usize>, host: &mut dyn HostOps) {
+        let Some(position) = position else {
+            self.push_system("Usage: /dequeue <position>".to_string());
+            host.request_render();
+            return;
+        };
+
+        let queued = host.queued_turns().get(position - 1).map(|turn| turn.id);
+        let removed = queued.is_some_and(|id| host.cancel_queued_turn(id));
+
+        if removed {
+            self.push_system(format!("Dropped queued message {position}"));
+        } else {
+            self.push_system(format!("No queued message at position {position}"));
+        }
+        host.request_render();
+    }
+
+    /// Returns true when a host may start the next queued turn.
+    pub fn can_start_queued_turn(&self) -> bool {
+        !self.should_exit && !self.is_busy()
+    }
 
+    /// Starts a user turn that the host dequeued after the previous run terminated.
+    pub fn start_queued_turn(&mut self, prompt: String, host: &mut dyn HostOps) {
+        self.start_user_turn(prompt, host);
         host.request_render();
     }
 
@@ -513,89 +1176,945 @@ impl App {
         if let Mode::Running { run_id } = self.mode {
             self.cancelling_run = Some(run_id);
             self.finalize_stream(run_id);
-            self.mode = Mode::Idle;
+            self.set_mode(Mode::Idle);
             self.push_system("Run cancelled".to_string());
             host.cancel_run(run_id);
         } else {
             self.push_system("No active run".to_string());
         }
 
-        host.request_render();
+        host.request_render();
+    }
+
+    pub fn on_control_c(&mut self, host: &mut dyn HostOps) {
+        if !self.input.is_empty() {
+            self.on_input_replace(String::new());
+            host.request_render();
+            return;
+        }
+
+        if matches!(self.mode, Mode::Running { .. }) {
+            self.on_cancel(host);
+            return;
+        }
+
+        self.on_quit(host);
+    }
+
+    pub fn on_quit(&mut self, host: &mut dyn HostOps) {
+        self.set_mode(Mode::Exiting);
+        self.should_exit = true;
+        host.request_stop();
+        host.request_render();
+    }
+
+    pub fn on_run_started(&mut self, run_id: RunId) {
+        if !self.is_active_run(run_id) {
+            return;
+        }
+
+        if self
+            .run_metrics
+            .is_none_or(|metrics| metrics.run_id != run_id)
+        {
+            self.run_metrics = Some(RunMetrics::new(run_id, Instant::now()));
+        }
+
+        if self.is_cancelling(run_id) || self.has_assistant_for_run(run_id) {
+            return;
+        }
+
+        self.push_message(Message {
+            role: Role::Assistant,
+            content: String::new(),
+            streaming: true,
+            run_id: Some(run_id),
+        });
+    }
+
+    pub fn on_run_chunk(&mut self, run_id: RunId, chunk: &str) {
+        if !self.is_active_run(run_id) && !self.is_cancelling(run_id) {
+            return;
+        }
+
+        let stream_active = !self.is_cancelling(run_id);
+        if let Some(metrics) = self
+            .run_metrics
+            .as_mut()
+            .filter(|metrics| stream_active && metrics.run_id == run_id)
+        {
+            metrics.first_chunk_at.get_or_insert_with(Instant::now);
+            metrics.streamed_chars += chunk.chars().count();
+        }
+
+        let appends_to_last = self.transcript.last().is_some_and(|last_message| {
+            last_message.role == Role::Assistant && last_message.run_id == Some(run_id)
+        });
+        if appends_to_last {
+            let index = self.transcript.len() - 1;
+            let last_message = &mut self.transcript[index];
+            last_message.content.push_str(chunk);
+            let stopped_streaming = !stream_active && last_message.streaming;
+            if stopped_streaming {
+                last_message.streaming = false;
+            }
+            self.bump_transcript_revision();
+            self.publish(|_| AppEvent::ChunkAppended {
+                index,
+                run_id,
+                chunk: chunk.to_string(),
+            });
+            if stopped_streaming {
+                self.publish(|app| AppEvent::MessageUpdated {
+                    index,
+                    message: app.transcript[index].clone(),
+                });
+            }
+        } else {
+            self.push_message(Message {
+                role: Role::Assistant,
+                content: chunk.to_string(),
+                streaming: stream_active,
+                run_id: Some(run_id),
+            });
+        }
+
+        self.append_pending_assistant_chunk(run_id, chunk);
+    }
+
+    /// Sets per-model pricing used to cost subsequent usage reports.
+    pub fn set_pricing_table(&mut self, pricing: PricingTable) {
+        self.pricing = pricing;
+    }
+
+    pub fn pricing_table(&self) -> &PricingTable {
+        &self.pricing
+    }
+
+    /// Running spend for the session in micro-USD; `None` until a priced run
+    /// reports usage (or a resumed session restored a total).
+    ///
+    /// Unlike transcript state this survives `/clear`: the session file and its
+    /// spend continue.
+    pub fn session_cost_micros(&self) -> Option<u64> {
+        self.session_cost_micros
+    }
+
+    /// Seeds the running session total from a resumed session file.
+    pub fn restore_session_cost(&mut self, session_cost_micros: Option<u64>) {
+        self.session_cost_micros = session_cost_micros;
+    }
+
+    /// Name of the session, once the first assistant turn finished or the
+    /// user set one with `/title`.
+    pub fn session_title(&self) -> Option<&str> {
+        self.session_title.as_deref()
+    }
+
+    /// Seeds the title from a resumed session file, or records the one the
+    /// host generated and persisted. Goes through [`sanitize_session_title`]
+    /// since a session file's header may have been edited.
+    pub fn restore_session_title(&mut self, title: Option<String>) {
+        self.session_title = title.as_deref().and_then(sanitize_session_title);
+    }
+
+    /// The title to generate after the first assistant turn: the first
+    /// prompt's heading line. `None` once the session has a title or before
+    /// an assistant reply is in the conversation.
+    pub fn pending_session_title(&self) -> Option<String> {
+        if self.session_title.is_some() {
+            return None;
+        }
+        let has_reply = self
+            .conversation
+            .iter()
+            .any(|message| matches!(message, RunMessage::AssistantText { .. }));
+        if !has_reply {
+            return None;
+        }
+        self.conversation.iter().find_map(|message| match message {
+            RunMessage::UserText { text } => session_title_from_prompt(text),
+            _ => None,
+        })
+    }
+
+    /// Seeds `/undo` with the checkpointed turns of a resumed session file.
+    pub fn restore_turn_checkpoints(&mut self, turns: Vec<TurnCheckpoints>) {
+        self.turn_checkpoints = turns;
+    }
+
+    pub fn turn_checkpoints(&self) -> &[TurnCheckpoints] {
+        &self.turn_checkpoints
+    }
+
+    /// Files a tool call of `run_id` mutated were saved as a checkpoint.
+    ///
+    /// The checkpoint joins the turn whose prompt started the run; failures
+    /// are surfaced because `/undo` cannot rewind that call.
+    pub fn on_checkpoint_recorded(&mut self, run_id: RunId, checkpoint: Result<String, String>) {
+        let checkpoint_id = match checkpoint {
+            Ok(checkpoint_id) => checkpoint_id,
+            Err(error) => {
+                self.push_system(format!(
+                    "Checkpoint failed; /undo cannot restore this tool call: {error}"
+                ));
+                return;
+            }
+        };
+
+        match self.turn_checkpoints.last_mut() {
+            Some(turn) if turn.run_id == Some(run_id) => turn.checkpoint_ids.push(checkpoint_id),
+            _ => {
+                let prompt = self
+                    .conversation
+                    .iter()
+                    .rev()
+                    .find_map(|message| match message {
+                        RunMessage::UserText { text } => Some(text.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                self.turn_checkpoints.push(TurnCheckpoints {
+                    prompt,
+                    checkpoint_ids: vec![checkpoint_id],
+                    run_id: Some(run_id),
+                });
+            }
+        }
+    }
+
+    /// `/undo` lists the turns that changed files; `/undo <n>` restores the
+    /// workspace to its state before turn `n`, rewinding every later turn too.
+    ///
+    /// Only files are restored; the conversation is kept. Refused while a run
+    /// is active so the restore cannot race tool writes.
+    fn on_undo_command(&mut self, turn: Option<usize>, host: &mut dyn HostOps) {
+        if self.turn_checkpoints.is_empty() {
+            self.push_system("No file changes to undo".to_string());
+            host.request_render();
+            return;
+        }
+
+        let Some(turn) = turn else {
+            let mut lines = vec![
+                "Turns with file changes (/undo <n> restores the workspace to before turn n):"
+                    .to_string(),
+            ];
+            for (index, checkpoints) in self.turn_checkpoints.iter().enumerate() {
+                let prompt = checkpoints.prompt.lines().next().unwrap_or_default();
+                let calls = checkpoints.checkpoint_ids.len();
+                let noun = if calls == 1 {
+                    "tool call"
+                } else {
+                    "tool calls"
+                };
+                lines.push(format!("  {}. {prompt} ({calls} {noun})", index + 1));
+            }
+            self.push_system(lines.join("\n"));
+            host.request_render();
+            return;
+        };
+
+        if self.is_busy() {
+            self.push_system("Cannot undo while a run is active".to_string());
+            host.request_render();
+            return;
+        }
+
+        if turn > self.turn_checkpoints.len() {
+            self.push_system(format!(
+                "No turn {turn}; /undo lists {} turns with file changes",
+                self.turn_checkpoints.len()
+            ));
+            host.request_render();
+            return;
+        }
+
+        let checkpoint_ids: Vec<String> = self.turn_checkpoints[turn - 1..]
+            .iter()
+            .flat_map(|checkpoints| checkpoints.checkpoint_ids.iter().cloned())
+            .collect();
+        match CheckpointStore::new(&self.workspace_root).restore(&checkpoint_ids) {
+            Ok(paths) => {
+                self.turn_checkpoints.truncate(turn - 1);
+                let labels: Vec<String> = paths
+                    .iter()
+                    .map(|path| {
+                        path.strip_prefix(&self.workspace_root)
+                            .unwrap_or(path)
+                            .display()
+                            .to_string()
+                    })
+                    .collect();
+                self.push_system(format!(
+                    "Restored the workspace to before turn {turn}: {}",
+                    labels.join(", ")
+                ));
+                if self.change_review.is_some() {
+                    self.change_review = Some(self.file_changes.review(&self.workspace_root));
+                }
+            }
+            Err(error) => self.push_system(format!("Undo failed: {error}")),
+        }
+        host.request_render();
+    }
+
+    /// Returns the latest plan reported through `update_plan`, if any.
+    pub fn plan(&self) -> Option<&Plan> {
+        self.plan.as_ref()
+    }
+
+    /// Replaces the current plan with one reported by the active run.
+    ///
+    /// The plan outlives its run so users can review it between turns; it is
+    /// in-memory only and cleared by `/clear`.
+    pub fn on_plan_updated(&mut self, run_id: RunId, plan: Plan) {
+        if !self.should_apply_run_event(run_id) {
+            return;
+        }
+
+        self.plan = Some(plan);
+    }
+
+    /// Sets the root used to label paths in the change review.
+    pub fn set_workspace_root(&mut self, workspace_root: impl Into<PathBuf>) {
+        self.workspace_root = workspace_root.into();
+    }
+
+    pub fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    /// Returns every file mutated by host tools this session.
+    pub fn file_changes(&self) -> &FileChangeSet {
+        &self.file_changes
+    }
+
+    /// Records pre-mutation snapshots reported by the host tool executor.
+    ///
+    /// Unlike other run events this is not gated on the active run: the files
+    /// changed on disk regardless of whether the run is still current.
+    pub fn on_files_touched(&mut self, snapshots: Vec<FileSnapshot>) {
+        for snapshot in snapshots {
+            self.file_changes.record(snapshot);
+        }
+    }
+
+    /// Returns the per-file review shown by the change panel while it is open.
+    pub fn change_review(&self) -> Option<&[FileChange]> {
+        self.change_review.as_deref()
+    }
+
+    fn on_changes_command(&mut self, host: &mut dyn HostOps) {
+        if self.file_changes.is_empty() {
+            self.push_system("No files changed this session".to_string());
+            host.request_render();
+            return;
+        }
+
+        self.change_review = Some(self.file_changes.review(&self.workspace_root));
+        if let Err(error) = host.show_change_review() {
+            self.change_review = None;
+            self.push_system(format!("Failed to open change review: {error}"));
+        }
+        host.request_render();
+    }
+
+    /// Returns the variables being collected for a `/prompt` template.
+    pub fn prompt_form(&self) -> Option<&PromptForm> {
+        self.prompt_form.as_ref()
+    }
+
+    pub fn prompt_form_mut(&mut self) -> Option<&mut PromptForm> {
+        self.prompt_form.as_mut()
+    }
+
+    /// Takes text queued for the editor by `/prompt`.
+    pub fn take_pending_editor_text(&mut self) -> Option<String> {
+        self.pending_editor_text.take()
+    }
+
+    fn on_prompt_command(&mut self, args: &str, host: &mut dyn HostOps) {
+        let prepared = parse_prompt_invocation(args).and_then(|invocation| {
+            let template = load_prompt_template(&self.workspace_root, &invocation.name)?;
+            let variables = template.variables();
+            if let Some(unknown) = invocation
+                .values
+                .keys()
+                .find(|name| !variables.contains(name))
+            {
+                return Err(format!(
+                    "Prompt template '{}' has no variable '{unknown}'",
+                    template.name
+                ));
+            }
+            Ok((template, invocation.values))
+        });
+        let (template, values) = match prepared {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                self.push_system(error);
+                host.request_render();
+                return;
+            }
+        };
+
+        let expanded = template.expand(&values);
+        match PromptForm::new(template, values) {
+            None => self.queue_editor_text(expanded),
+            Some(form) => {
+                self.prompt_form = Some(form);
+                if let Err(error) = host.show_prompt_form() {
+                    self.prompt_form = None;
+                    self.push_system(format!("Failed to open prompt form: {error}"));
+                }
+            }
+        }
+        host.request_render();
+    }
+
+    /// Expands the template with the collected values into the editor.
+    pub fn submit_prompt_form(&mut self, host: &mut dyn HostOps) {
+        if let Some(form) = self.prompt_form.take() {
+            self.queue_editor_text(form.expand());
+            host.hide_prompt_form();
+            host.request_render();
+        }
+    }
+
+    pub fn cancel_prompt_form(&mut self, host: &mut dyn HostOps) {
+        if self.prompt_form.take().is_some() {
+            host.hide_prompt_form();
+            host.request_render();
+        }
+    }
+
+    fn queue_editor_text(&mut self, text: String) {
+        self.on_input_replace(text.clone());
+        self.pending_editor_text = Some(text);
+    }
+
+    pub fn close_change_review(&mut self, host: &mut dyn HostOps) {
+        if self.change_review.take().is_some() {
+            host.hide_change_review();
+            host.request_render();
+        }
+    }
+
+    /// Restores one reviewed file to its pre-session contents.
+    ///
+    /// Reverts are refused while a run is active so they cannot race tool
+    /// writes; the review closes once no tracked files remain.
+    pub fn revert_file_change(&mut self, path: &Path, host: &mut dyn HostOps) {
+        let label = path
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+
+        if self.is_busy() {
+            self.push_system(format!("Cannot revert {label} while a run is active"));
+            host.request_render();
+            return;
+        }
+
+        match self.file_changes.revert(path) {
+            Ok(()) => self.push_system(format!("Reverted {label}")),
+            Err(error) => self.push_system(format!("Failed to revert {label}: {error}")),
+        }
+
+        if self.change_review.is_some() {
+            if self.file_changes.is_empty() {
+                self.close_change_review(host);
+            } else {
+                self.change_review = Some(self.file_changes.review(&self.workspace_root));
+            }
+        }
+        host.request_render();
+    }
+
+    /// Whether mutating tools wait for per-call confirmation.
+    pub fn tool_preview(&self) -> bool {
+        self.tool_preview
+    }
+
+    /// Turns per-call confirmation of mutating tools on or off.
+    pub fn set_tool_preview(
+        &mut self,
+        enabled: bool,
+        host: &mut dyn HostOps,
+    ) -> Result<(), String> {
+        host.set_tool_preview(enabled)?;
+        self.tool_preview = enabled;
+        Ok(())
+    }
+
+    /// Whether transcript rendering sanitizes model and tool output; on by
+    /// default.
+    pub fn sanitize_output(&self) -> bool {
+        self.sanitize_output
+    }
+
+    /// Opts out of (or back into) output sanitizing. Turning it off passes
+    /// raw escape sequences from the model and tools to the terminal.
+    pub fn set_sanitize_output(&mut self, enabled: bool) {
+        if self.sanitize_output != enabled {
+            self.sanitize_output = enabled;
+            self.bump_transcript_revision();
+        }
+    }
+
+    fn on_preview_command(&mut self, host: &mut dyn HostOps) {
+        let enabled = !self.tool_preview;
+        match self.set_tool_preview(enabled, host) {
+            Ok(()) => {
+                self.push_system(if enabled {
+                    "Preview mode on: write, edit, and apply_patch wait for approval (y/n)"
+                        .to_string()
+                } else {
+                    "Preview mode off".to_string()
+                });
+            }
+            Err(error) => self.push_system(format!("Failed to toggle preview mode: {error}")),
+        }
+        host.request_render();
+    }
+
+    fn on_model_command(&mut self, model_id: &str, host: &mut dyn HostOps) {
+        let message = if model_id.is_empty() {
+            "Usage: /model <id>".to_string()
+        } else if self.is_busy() {
+            "Cannot switch model while a run is active".to_string()
+        } else {
+            match host.set_model(model_id) {
+                Ok(profile) => format!("Switched model to {}", profile.model_id),
+                Err(error) => format!("Model switch failed: {error}"),
+            }
+        };
+        self.push_system(message);
+        host.request_render();
+    }
+
+    fn on_title_command(&mut self, title: &str, host: &mut dyn HostOps) {
+        let message = if let Some(title) = sanitize_session_title(title) {
+            match host.set_session_title(&title) {
+                Ok(()) => {
+                    let message = format!("Renamed session to {title}");
+                    self.session_title = Some(title);
+                    message
+                }
+                Err(error) => format!("Rename failed: {error}"),
+            }
+        } else {
+            match self.session_title.as_deref() {
+                Some(current) => format!("Session title: {current}"),
+                None => "Usage: /title <name>".to_string(),
+            }
+        };
+        self.push_system(message);
+        host.request_render();
+    }
+
+    fn on_sessions_command(&mut self, host: &mut dyn HostOps) {
+        let message = match host.list_sessions() {
+            Ok(listings) if listings.sessions.is_empty() && listings.unreadable.is_empty() => {
+                "No saved sessions".to_string()
+            }
+            Ok(listings) => {
+                let mut lines =
+                    vec!["Sessions (* current; resume with --session <path>):".to_string()];
+                for session in listings.sessions {
+                    let marker = if session.current { '*' } else { ' ' };
+                    let title = session.title.as_deref().unwrap_or("(untitled)");
+                    lines.push(format!(
+                        "{marker} {title}  {}  {}",
+                        session.created_at,
+                        session.path.display()
+                    ));
+                }
+                for unreadable in listings.unreadable {
+                    lines.push(format!("! skipped {unreadable}"));
+                }
+                lines.join("\n")
+            }
+            Err(error) => format!("Session listing failed: {error}"),
+        };
+        self.push_system(message);
+        host.request_render();
+    }
+
+    /// Shows a catalog requested with `/models`, marking `selected_model_id`.
+    pub fn on_models_listed(
+        &mut self,
+        selected_model_id: &str,
+        models: Result<Vec<ModelInfo>, String>,
+    ) {
+        let models = match models {
+            Ok(models) => models,
+            Err(error) => {
+                self.push_system(format!("Model listing failed: {error}"));
+                return;
+            }
+        };
+
+        let mut lines = vec!["Models (* selected):".to_string()];
+        for model in models {
+            let marker = if model.model_id == selected_model_id {
+                '*'
+            } else {
+                ' '
+            };
+            let mut details = Vec::new();
+            if let Some(display_name) = model.display_name {
+                details.push(display_name);
+            }
+            if let Some(context_window) = model.context_window {
+                details.push(format!("{} context", format_token_count(context_window)));
+            }
+            if !model.available {
+                details.push("unavailable".to_string());
+            }
+            let mut line = format!("{marker} {}", model.model_id);
+            if !details.is_empty() {
+                line.push_str(&format!(" ({})", details.join(", ")));
+            }
+            lines.push(line);
+        }
+        self.push_system(lines.join("\n"));
+    }
+
+    fn on_thinking_command(&mut self, level: &str, host: &mut dyn HostOps) {
+        let message = if level.is_empty() {
+            "Usage: /thinking <level>".to_string()
+        } else if self.is_busy() {
+            "Cannot switch thinking mode while a run is active".to_string()
+        } else {
+            match host.set_thinking_level(level) {
+                Ok(profile) => format!(
+                    "Switched thinking mode to {}",
+                    profile.thinking_level.as_deref().unwrap_or("none")
+                ),
+                Err(error) => format!("Thinking mode switch failed: {error}"),
+            }
+        };
+        self.push_system(message);
+        host.request_render();
+    }
+
+    fn on_copy_command(&mut self, target: Option<CopyTarget>, host: &mut dyn HostOps) {
+        let Some(target) = target else {
+            self.push_system("Usage: /copy last [code block number]".to_string());
+            host.request_render();
+            return;
+        };
+
+        let Some(content) = self
+            .transcript
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::Assistant && !message.content.is_empty())
+            .map(|message| message.content.clone())
+        else {
+            self.push_system("No assistant message to copy".to_string());
+            host.request_render();
+            return;
+        };
+
+        match target {
+            CopyTarget::LastMessage => {
+                self.copy_to_clipboard(&content, "last assistant message", host);
+            }
+            CopyTarget::LastCodeBlock(block) => match fenced_code_blocks(&content).get(block - 1) {
+                Some(code) => self.copy_to_clipboard(
+                    code,
+                    &format!("code block {block} of the last assistant message"),
+                    host,
+                ),
+                None => {
+                    self.push_system(format!(
+                        "No code block {block} in the last assistant message"
+                    ));
+                    host.request_render();
+                }
+            },
+        }
+    }
+
+    /// Copies `text` through the host clipboard and confirms with a toast;
+    /// `what` names the copied item in the confirmation or error.
+    pub fn copy_to_clipboard(&mut self, text: &str, what: &str, host: &mut dyn HostOps) {
+        match host.copy_to_clipboard(text) {
+            Ok(()) => host.show_toast(format!("Copied {what} ({} chars)", text.chars().count())),
+            Err(error) => self.push_system(format!("Failed to copy {what}: {error}")),
+        }
+        host.request_render();
+    }
+
+    /// Returns the tool call awaiting approval, if its run is still current.
+    pub fn pending_tool_confirmation(&self) -> Option<&ToolConfirmation> {
+        self.pending_tool_confirmation
+            .as_ref()
+            .filter(|confirmation| self.should_apply_run_event(confirmation.run_id))
+    }
+
+    pub fn on_tool_confirmation_requested(&mut self, confirmation: ToolConfirmation) {
+        if !self.should_apply_run_event(confirmation.run_id) {
+            return;
+        }
+
+        self.publish(|_| AppEvent::ToolConfirmationRequested {
+            confirmation: confirmation.clone(),
+        });
+        self.pending_tool_confirmation = Some(confirmation);
+    }
+
+    /// Approves or rejects the paused tool call; a rejection reaches the model
+    /// as an error tool result and leaves disk untouched.
+    pub fn resolve_tool_confirmation(&mut self, approved: bool, host: &mut dyn HostOps) {
+        let Some(confirmation) = self.pending_tool_confirmation.take() else {
+            return;
+        };
+
+        host.resolve_tool_confirmation(&confirmation.call_id, approved);
+        host.request_render();
+    }
+
+    /// Returns recorded turn metadata for a run, if any.
+    pub fn turn_metadata(&self, run_id: RunId) -> Option<&TurnMetadata> {
+        self.turn_metadata.get(&run_id)
+    }
+
+    /// Stream progress of the most recently started run.
+    pub fn run_metrics(&self) -> Option<&RunMetrics> {
+        self.run_metrics.as_ref()
+    }
+
+    /// Records provider identity and wall-clock start time for the active run.
+    pub fn on_run_profile(
+        &mut self,
+        run_id: RunId,
+        profile: &ProviderProfile,
+        started_at: SystemTime,
+    ) {
+        if !self.should_apply_run_event(run_id) || self.turn_metadata.contains_key(&run_id) {
+            return;
+        }
+
+        self.turn_metadata
+            .insert(run_id, TurnMetadata::new(profile, started_at));
+        self.bump_transcript_revision();
+    }
+
+    /// Accumulates provider token usage reports for the active run.
+    pub fn on_run_usage(&mut self, run_id: RunId, usage: &TokenUsage) {
+        if !self.should_apply_run_event(run_id) {
+            return;
+        }
+
+        let Some(metadata) = self.turn_metadata.get_mut(&run_id) else {
+            return;
+        };
+
+        let total_usage = metadata.usage.get_or_insert_with(TokenUsage::default);
+        total_usage.accumulate(usage);
+
+        if let Some(cost_micros) = self.pricing.cost_micros(&metadata.model_id, total_usage) {
+            let previous_cost_micros = metadata.cost_micros.replace(cost_micros).unwrap_or(0);
+            let session_cost_micros = self.session_cost_micros.get_or_insert(0);
+            *session_cost_micros = session_cost_micros
+                .saturating_add(cost_micros.saturating_sub(previous_cost_micros));
+        }
+        self.bump_transcript_revision();
+    }
+
+    /// Records a failed provider request attempt, noting retries in the
+    /// transcript.
+    pub fn on_run_attempt_failed(&mut self, run_id: RunId, failure: AttemptFailure) {
+        if !self.should_apply_run_event(run_id) {
+            return;
+        }
+
+        if failure.will_retry {
+            self.push_system(retry_note(failure.attempt, failure.status, &failure.error));
+        }
+        if let Some(metadata) = self.turn_metadata.get_mut(&run_id) {
+            metadata.failed_attempts.push(failure);
+        }
+    }
+
+    /// Stamps the terminal wall-clock time for a run.
+    ///
+    /// Must be called before the terminal lifecycle handler so the run is still
+    /// considered active or cancelling.
+    pub fn on_run_ended_at(&mut self, run_id: RunId, finished_at: SystemTime) {
+        if !self.should_apply_run_event(run_id) {
+            return;
+        }
+
+        let Some(metadata) = self.turn_metadata.get_mut(&run_id) else {
+            return;
+        };
+
+        if metadata.finished_at.is_none() {
+            metadata.finished_at = Some(finished_at);
+            self.bump_transcript_revision();
+        }
+    }
+
+    /// Returns recorded start/finish times for a tool call, if any.
+    pub fn tool_call_timing(&self, run_id: RunId, call_id: &str) -> Option<&ToolCallTiming> {
+        self.tool_call_timings.get(&(run_id, call_id.to_string()))
+    }
+
+    /// Stamps the wall-clock start time of a tool call.
+    pub fn on_tool_call_started_at(
+        &mut self,
+        run_id: RunId,
+        call_id: &str,
+        started_at: SystemTime,
+    ) {
+        if !self.should_apply_run_event(run_id) {
+            return;
+        }
+
+        self.tool_call_timings.insert(
+            (run_id, call_id.to_string()),
+            ToolCallTiming {
+                started_at,
+                finished_at: None,
+            },
+        );
+        self.bump_transcript_revision();
     }
 
-    pub fn on_control_c(&mut self, host: &mut dyn HostOps) {
-        if !self.input.is_empty() {
-            self.on_input_replace(String::new());
-            host.request_render();
+    /// Stamps the wall-clock finish time of a tool call started earlier.
+    pub fn on_tool_call_finished_at(
+        &mut self,
+        run_id: RunId,
+        call_id: &str,
+        finished_at: SystemTime,
+    ) {
+        if !self.should_apply_run_event(run_id) {
             return;
         }
 
-        if matches!(self.mode, Mode::Running { .. }) {
-            self.on_cancel(host);
+        let Some(timing) = self
+            .tool_call_timings
+            .get_mut(&(run_id, call_id.to_string()))
+        else {
             return;
-        }
+        };
 
-        self.on_quit(host);
+        if timing.finished_at.is_none() {
+            timing.finished_at = Some(finished_at);
+            self.bump_transcript_revision();
+        }
     }
 
-    pub fn on_quit(&mut self, host: &mut dyn HostOps) {
-        self.mode = Mode::Exiting;
-        self.should_exit = true;
-        host.request_stop();
-        host.request_render();
+    /// Where the full output of a call whose result was capped was saved.
+    pub fn tool_output_file(&self, run_id: RunId, call_id: &str) -> Option<&Path> {
+        self.tool_output_files
+            .get(&(run_id, call_id.to_string()))
+            .map(PathBuf::as_path)
     }
 
-    pub fn on_run_started(&mut self, run_id: RunId) {
-        if !self.is_active_run(run_id) {
+    /// Records that a call's result was capped and its full output saved at
+    /// `path`.
+    pub fn on_tool_output_saved(&mut self, run_id: RunId, call_id: &str, path: PathBuf) {
+        if !self.should_apply_run_event(run_id) {
             return;
         }
 
-        if self.is_cancelling(run_id) || self.has_assistant_for_run(run_id) {
+        self.tool_output_files
+            .insert((run_id, call_id.to_string()), path);
+        self.bump_transcript_revision();
+    }
+
+    /// Returns the tool calls a `spawn_agent` call's sub-agent has made so far.
+    pub fn subagent_steps(&self, run_id: RunId, call_id: &str) -> &[SubagentStep] {
+        self.subagent_steps
+            .get(&(run_id, call_id.to_string()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Records a tool call started by the sub-agent of `spawn_agent` call
+    /// `call_id`.
+    pub fn on_subagent_tool_call_started(
+        &mut self,
+        run_id: RunId,
+        call_id: &str,
+        step_call_id: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) {
+        if !self.should_apply_run_event(run_id) {
             return;
         }
 
-        self.transcript.push(Message {
-            role: Role::Assistant,
-            content: String::new(),
-            streaming: true,
-            run_id: Some(run_id),
-        });
+        self.subagent_steps
+            .entry((run_id, call_id.to_string()))
+            .or_default()
+            .push(SubagentStep {
+                call_id: step_call_id.to_string(),
+                tool_name: tool_name.to_string(),
+                arguments,
+                outcome: None,
+            });
         self.bump_transcript_revision();
     }
 
-    pub fn on_run_chunk(&mut self, run_id: RunId, chunk: &str) {
-        if !self.is_active_run(run_id) && !self.is_cancelling(run_id) {
+    pub fn on_subagent_tool_call_finished(
+        &mut self,
+        run_id: RunId,
+        call_id: &str,
+        step_call_id: &str,
+        is_error: bool,
+    ) {
+        if !self.should_apply_run_event(run_id) {
             return;
         }
 
-        let stream_active = !self.is_cancelling(run_id);
-
-        if let Some(last_message) = self.transcript.last_mut() {
-            if last_message.role == Role::Assistant && last_message.run_id == Some(run_id) {
-                last_message.content.push_str(chunk);
-                if !stream_active {
-                    last_message.streaming = false;
-                }
-            } else {
-                self.transcript.push(Message {
-                    role: Role::Assistant,
-                    content: chunk.to_string(),
-                    streaming: stream_active,
-                    run_id: Some(run_id),
-                });
-            }
-        } else {
-            self.transcript.push(Message {
-                role: Role::Assistant,
-                content: chunk.to_string(),
-                streaming: stream_active,
-                run_id: Some(run_id),
-            });
-        }
+        let Some(step) = self
+            .subagent_steps
+            .get_mut(&(run_id, call_id.to_string()))
+            .and_then(|steps| {
+                steps
+                    .iter_mut()
+                    .rev()
+                    .find(|step| step.call_id == step_call_id)
+            })
+        else {
+            return;
+        };
 
+        step.outcome = Some(is_error);
         self.bump_transcript_revision();
+    }
 
-        self.append_pending_assistant_chunk(run_id, chunk);
+    /// Returns the active run's tool call whose arguments are still streaming.
+    pub fn streaming_tool_call(&self) -> Option<&StreamingToolCall> {
+        self.streaming_tool_call
+            .as_ref()
+            .filter(|call| self.is_active_run(call.run_id))
+    }
+
+    pub fn on_tool_call_streaming(
+        &mut self,
+        run_id: RunId,
+        call_id: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) {
+        if !self.is_active_run(run_id) {
+            return;
+        }
+
+        self.streaming_tool_call = Some(StreamingToolCall {
+            run_id,
+            call_id: call_id.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments,
+        });
     }
 
     pub fn on_tool_call_started(
@@ -605,12 +2124,26 @@ impl App {
         tool_name: &str,
         arguments: &serde_json::Value,
     ) {
+        if self
+            .streaming_tool_call
+            .as_ref()
+            .is_some_and(|call| call.call_id == call_id)
+        {
+            self.streaming_tool_call = None;
+        }
+
         if !self.should_apply_run_event(run_id) {
             return;
         }
 
         self.append_pending_tool_call(run_id, call_id, tool_name, arguments);
         self.push_tool(run_id, format!("Tool {tool_name} ({call_id}) started"));
+        self.publish(|_| AppEvent::ToolStarted {
+            run_id,
+            call_id: call_id.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments: arguments.clone(),
+        });
     }
 
     pub fn on_tool_call_finished(
@@ -622,6 +2155,14 @@ impl App {
         content: &serde_json::Value,
         content_text: &str,
     ) {
+        if self
+            .pending_tool_confirmation
+            .as_ref()
+            .is_some_and(|confirmation| confirmation.call_id == call_id)
+        {
+            self.pending_tool_confirmation = None;
+        }
+
         if !self.should_apply_run_event(run_id) {
             return;
         }
@@ -639,6 +2180,13 @@ impl App {
         }
 
         self.push_tool(run_id, message);
+        self.publish(|_| AppEvent::ToolFinished {
+            run_id,
+            call_id: call_id.to_string(),
+            tool_name: tool_name.to_string(),
+            is_error,
+            content: content.clone(),
+        });
     }
 
     pub fn on_run_finished(&mut self, run_id: RunId) {
@@ -648,7 +2196,7 @@ impl App {
 
         if self.is_cancelling(run_id) {
             self.finalize_stream(run_id);
-            self.discard_pending_run_memory(run_id);
+            self.settle_cancelled_run_memory(run_id);
             self.finalize_cancelled_run(run_id);
             return;
         }
@@ -659,7 +2207,7 @@ impl App {
 
         self.finalize_stream(run_id);
         self.commit_pending_run_memory(run_id);
-        self.mode = Mode::Idle;
+        self.set_mode(Mode::Idle);
     }
 
     pub fn on_run_failed(&mut self, run_id: RunId, error: &str) {
@@ -669,7 +2217,7 @@ impl App {
 
         if self.is_cancelling(run_id) {
             self.finalize_stream(run_id);
-            self.discard_pending_run_memory(run_id);
+            self.settle_cancelled_run_memory(run_id);
             self.finalize_cancelled_run(run_id);
             return;
         }
@@ -680,8 +2228,17 @@ impl App {
 
         self.finalize_stream(run_id);
         self.discard_pending_run_memory(run_id);
-        self.mode = Mode::Error(error.to_string());
-        self.push_system(format!("Run failed: {error}"));
+        self.set_mode(Mode::Error(error.to_string()));
+        let final_attempt = self
+            .turn_metadata
+            .get(&run_id)
+            .and_then(|metadata| metadata.failed_attempts.last())
+            .filter(|failure| !failure.will_retry);
+        let note = match final_attempt {
+            Some(failure) => run_failed_note(error, failure.status, failure.attempt),
+            None => run_failed_note(error, None, 1),
+        };
+        self.push_system(note);
     }
 
     pub fn on_run_cancelled(&mut self, run_id: RunId) {
@@ -690,7 +2247,7 @@ impl App {
         }
 
         self.finalize_stream(run_id);
-        self.discard_pending_run_memory(run_id);
+        self.settle_cancelled_run_memory(run_id);
         self.finalize_cancelled_run(run_id);
     }
 
@@ -789,6 +2346,37 @@ impl App {
         );
     }
 
+    /// Keeps partial output of a run interrupted by steering, discards otherwise.
+    ///
+    /// Tool calls without a recorded result are dropped so replayed history
+    /// never contains dangling calls.
+    fn settle_cancelled_run_memory(&mut self, run_id: RunId) {
+        if self.steered_run != Some(run_id) {
+            self.discard_pending_run_memory(run_id);
+            return;
+        }
+
+        let Some(pending) = self.pending_run_memory.as_mut() else {
+            return;
+        };
+
+        let completed_call_ids: Vec<String> = pending
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                RunMessage::ToolResult { call_id, .. } => Some(call_id.clone()),
+                _ => None,
+            })
+            .collect();
+        pending.entries.retain(|entry| match entry {
+            RunMessage::ToolCall { call_id, .. } => completed_call_ids.contains(call_id),
+            _ => true,
+        });
+
+        self.steered_run = None;
+        self.commit_pending_run_memory(run_id);
+    }
+
     fn should_apply_run_event(&self, run_id: RunId) -> bool {
         !self.should_exit && (self.is_active_run(run_id) || self.is_cancelling(run_id))
     }
@@ -799,6 +2387,13 @@ impl App {
 
     fn finalize_stream(&mut self, run_id: RunId) {
         let mut mutated = false;
+        if self
+            .streaming_tool_call
+            .as_ref()
+            .is_some_and(|call| call.run_id == run_id)
+        {
+            self.streaming_tool_call = None;
+        }
 
         for message in &mut self.transcript {
             if message.role == Role::Assistant && message.run_id == Some(run_id) {
@@ -848,7 +2443,7 @@ impl App {
         }
 
         if mutated {
-            self.bump_transcript_revision();
+            self.replace_transcript();
         }
     }
 
@@ -868,28 +2463,26 @@ impl App {
         }
 
         self.cancelling_run = None;
-        self.mode = Mode::Idle;
+        self.set_mode(Mode::Idle);
         self.finalize_stream(run_id);
     }
 
     fn push_tool(&mut self, run_id: RunId, content: String) {
-        self.transcript.push(Message {
+        self.push_message(Message {
             role: Role::Tool,
             content,
             streaming: false,
             run_id: Some(run_id),
         });
-        self.bump_transcript_revision();
     }
 
     fn push_system(&mut self, content: String) {
-        self.transcript.push(Message {
+        self.push_message(Message {
             role: Role::System,
             content,
             streaming: false,
             run_id: None,
         });
-        self.bump_transcript_revision();
     }
 }
 
@@ -949,14 +2542,21 @@ mod tests {
     fn system_instructions_env_falls_back_to_default_when_unset_or_blank() {
         let _env_serialization = lock_unpoisoned(env_lock());
 
+        let workspace = tempfile::tempdir().expect("temp workspace");
+
         {
             let _guard = EnvVarGuard::set(None);
-            assert_eq!(system_instructions_from_env(), DEFAULT_SYSTEM_INSTRUCTIONS);
+            let instructions = system_instructions_from_env(workspace.path());
+            assert_eq!(instructions.text, DEFAULT_SYSTEM_INSTRUCTIONS);
+            assert!(instructions.project_files.is_empty());
         }
 
         {
             let _guard = EnvVarGuard::set(Some("   \n\t"));
-            assert_eq!(system_instructions_from_env(), DEFAULT_SYSTEM_INSTRUCTIONS);
+            assert_eq!(
+                system_instructions_from_env(workspace.path()).text,
+                DEFAULT_SYSTEM_INSTRUCTIONS
+            );
         }
     }
 
@@ -964,8 +2564,28 @@ mod tests {
     fn system_instructions_env_uses_trimmed_override_when_set() {
         let _env_serialization = lock_unpoisoned(env_lock());
         let _guard = EnvVarGuard::set(Some("  custom system instruction  "));
+        let workspace = tempfile::tempdir().expect("temp workspace");
+
+        assert_eq!(
+            system_instructions_from_env(workspace.path()).text,
+            "custom system instruction"
+        );
+    }
+
+    #[test]
+    fn system_instructions_env_prepends_project_instruction_files() {
+        let _env_serialization = lock_unpoisoned(env_lock());
+        let _guard = EnvVarGuard::set(Some("custom system instruction"));
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        std::fs::write(workspace.path().join("AGENTS.md"), "Run cargo fmt.\n")
+            .expect("write AGENTS.md");
 
-        assert_eq!(system_instructions_from_env(), "custom system instruction");
+        let instructions = system_instructions_from_env(workspace.path());
+        assert_eq!(
+            instructions.text,
+            "# Project instructions (AGENTS.md)\n\nRun cargo fmt.\n\ncustom system instruction"
+        );
+        assert_eq!(instructions.project_files, vec!["AGENTS.md"]);
     }
 
     #[test]
@@ -1022,6 +2642,264 @@ mod tests {
         );
     }
 
+    fn profile() -> ProviderProfile {
+        ProviderProfile {
+            provider_id: "mock".to_string(),
+            model_id: "mock-model".to_string(),
+            thinking_level: Some("high".to_string()),
+        }
+    }
+
+    #[test]
+    fn turn_metadata_records_profile_usage_and_duration_for_active_run() {
+        let started_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 3 };
+
+        app.on_run_started(3);
+        app.on_run_profile(3, &profile(), started_at);
+        app.on_run_usage(
+            3,
+            &TokenUsage {
+                input_tokens: 100,
+                cached_input_tokens: 20,
+                output_tokens: 10,
+            },
+        );
+        app.on_run_usage(
+            3,
+            &TokenUsage {
+                input_tokens: 50,
+                cached_input_tokens: 0,
+                output_tokens: 5,
+            },
+        );
+        app.on_run_ended_at(3, started_at + Duration::from_millis(1_500));
+        app.on_run_finished(3);
+
+        let metadata = app.turn_metadata(3).expect("metadata should be recorded");
+        assert_eq!(metadata.model_id, "mock-model");
+        assert_eq!(metadata.thinking_level.as_deref(), Some("high"));
+        assert_eq!(metadata.duration(), Some(Duration::from_millis(1_500)));
+        assert_eq!(
+            metadata.usage,
+            Some(TokenUsage {
+                input_tokens: 150,
+                cached_input_tokens: 20,
+                output_tokens: 15,
+            })
+        );
+
+        let revision = app.transcript_revision();
+        app.on_run_usage(
+            3,
+            &TokenUsage {
+                input_tokens: 1,
+                cached_input_tokens: 0,
+                output_tokens: 1,
+            },
+        );
+        assert_eq!(app.transcript_revision(), revision);
+        assert_eq!(
+            app.turn_metadata(3)
+                .and_then(|m| m.usage)
+                .map(|u| u.input_tokens),
+            Some(150)
+        );
+    }
+
+    #[test]
+    fn run_metrics_time_the_first_chunk_and_count_streamed_chars() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 3 };
+
+        app.on_run_started(3);
+        let started = *app.run_metrics().expect("metrics for the started run");
+        assert_eq!(started.run_id, 3);
+        assert_eq!(started.time_to_first_chunk(), None);
+
+        app.on_run_chunk(3, "héllo");
+        app.on_run_chunk(3, " world");
+        app.on_run_chunk(2, "stale");
+        let metrics = app.run_metrics().expect("metrics");
+        assert_eq!(metrics.streamed_chars, 11);
+        assert!(metrics.first_chunk_at >= Some(started.started_at));
+
+        app.on_run_started(3);
+        assert_eq!(app.run_metrics().map(|m| m.streamed_chars), Some(11));
+    }
+
+    #[test]
+    fn turn_metadata_ignores_stale_runs_and_clears_with_transcript() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 5 };
+
+        app.on_run_profile(4, &profile(), SystemTime::UNIX_EPOCH);
+        assert!(app.turn_metadata(4).is_none());
+
+        app.on_run_profile(5, &profile(), SystemTime::UNIX_EPOCH);
+        assert!(app.turn_metadata(5).is_some());
+
+        app.mode = Mode::Idle;
+        app.input = "/clear".to_string();
+        let mut host = NoopHost;
+        app.on_submit(&mut host);
+        assert!(app.turn_metadata(5).is_none());
+    }
+
+    #[test]
+    fn priced_usage_accumulates_run_and_session_cost_across_clear() {
+        let mut app = App::new();
+        app.set_pricing_table(PricingTable::new(BTreeMap::from([(
+            "mock-model".to_string(),
+            crate::pricing::ModelPricing::from_usd_per_million(1.0, None, 2.0)
+                .expect("rates should be valid"),
+        )])));
+        app.restore_session_cost(Some(500));
+
+        app.mode = Mode::Running { run_id: 1 };
+        app.on_run_profile(1, &profile(), SystemTime::UNIX_EPOCH);
+        let usage = TokenUsage {
+            input_tokens: 1_000,
+            cached_input_tokens: 0,
+            output_tokens: 500,
+        };
+        app.on_run_usage(1, &usage);
+        app.on_run_usage(1, &usage);
+        app.on_run_finished(1);
+
+        assert_eq!(
+            app.turn_metadata(1).and_then(|m| m.cost_micros),
+            Some(4_000)
+        );
+        assert_eq!(app.session_cost_micros(), Some(4_500));
+
+        app.input = "/clear".to_string();
+        app.on_submit(&mut NoopHost);
+        assert_eq!(app.session_cost_micros(), Some(4_500));
+
+        let unpriced = ProviderProfile {
+            model_id: "other-model".to_string(),
+            ..profile()
+        };
+        app.mode = Mode::Running { run_id: 2 };
+        app.on_run_profile(2, &unpriced, SystemTime::UNIX_EPOCH);
+        app.on_run_usage(2, &usage);
+        assert_eq!(app.turn_metadata(2).and_then(|m| m.cost_micros), None);
+        assert_eq!(app.session_cost_micros(), Some(4_500));
+    }
+
+    #[test]
+    fn plan_updates_apply_only_for_active_run_and_clear_with_transcript() {
+        use crate::tools::{PlanStep, PlanStepStatus};
+
+        let plan = Plan {
+            explanation: None,
+            steps: vec![PlanStep {
+                step: "Write tests".to_string(),
+                status: PlanStepStatus::InProgress,
+            }],
+        };
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 9 };
+
+        app.on_plan_updated(8, plan.clone());
+        assert!(app.plan().is_none());
+
+        app.on_plan_updated(9, plan.clone());
+        app.on_run_finished(9);
+        assert_eq!(app.plan(), Some(&plan));
+
+        app.input = "/clear".to_string();
+        app.on_submit(&mut NoopHost);
+        assert!(app.plan().is_none());
+    }
+
+    struct NoopHost;
+
+    impl HostOps for NoopHost {
+        fn start_run(
+            &mut self,
+            _messages: Vec<RunMessage>,
+            _instructions: String,
+        ) -> Result<RunId, String> {
+            Err("unused".to_string())
+        }
+
+        fn cancel_run(&mut self, _run_id: RunId) {}
+
+        fn request_render(&mut self) {}
+
+        fn request_stop(&mut self) {}
+    }
+
+    #[test]
+    fn subscribers_can_mirror_the_transcript_and_run_state_from_events() {
+        let mut app = App::new();
+        let events = app.subscribe();
+        let dropped = app.subscribe();
+        drop(dropped);
+
+        let run_id = 5;
+        app.set_mode(Mode::Running { run_id });
+        app.on_run_started(run_id);
+        app.on_run_chunk(run_id, "Reading ");
+        app.on_run_chunk(run_id, "the file.");
+        let arguments = serde_json::json!({ "path": "README.md" });
+        app.on_tool_call_started(run_id, "call-1", "read", &arguments);
+        app.on_tool_call_finished(
+            run_id,
+            "read",
+            "call-1",
+            false,
+            &serde_json::json!("contents"),
+            "contents",
+        );
+        app.on_run_chunk(run_id, "Done.");
+        app.on_run_finished(run_id);
+        app.push_system_message("note");
+
+        let events: Vec<AppEvent> = events.try_iter().collect();
+        assert_eq!(app.subscribers.senders_len(), 1);
+        assert!(events.contains(&AppEvent::ChunkAppended {
+            index: 0,
+            run_id,
+            chunk: "the file.".to_string(),
+        }));
+        assert!(events.contains(&AppEvent::ToolStarted {
+            run_id,
+            call_id: "call-1".to_string(),
+            tool_name: "read".to_string(),
+            arguments,
+        }));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            AppEvent::ToolFinished { call_id, is_error: false, .. } if call_id == "call-1"
+        )));
+
+        let mut transcript: Vec<Message> = Vec::new();
+        let mut modes = Vec::new();
+        for event in events {
+            match event {
+                AppEvent::MessageAdded { index, message } => {
+                    assert_eq!(index, transcript.len());
+                    transcript.push(message);
+                }
+                AppEvent::ChunkAppended { index, chunk, .. } => {
+                    transcript[index].content.push_str(&chunk);
+                }
+                AppEvent::MessageUpdated { index, message } => transcript[index] = message,
+                AppEvent::TranscriptReplaced { messages } => transcript = messages,
+                AppEvent::RunStateChanged { mode } => modes.push(mode),
+                AppEvent::ToolStarted { .. }
+                | AppEvent::ToolFinished { .. }
+                | AppEvent::ToolConfirmationRequested { .. } => {}
+            }
+        }
+        assert_eq!(transcript, app.transcript);
+        assert_eq!(modes, vec![Mode::Running { run_id }, Mode::Idle]);
+    }
+
     #[test]
     fn transcript_preserves_assistant_segments_after_tool_boundaries() {
         let mut app = App::new();
@@ -1200,6 +3078,51 @@ mod tests {
         assert_eq!(app.tool_call_result(77, "missing"), None);
     }
 
+    #[test]
+    fn tool_call_timings_record_duration_for_the_active_run_only() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 5 };
+        let started_at = SystemTime::UNIX_EPOCH + Duration::from_secs(10);
+
+        app.on_tool_call_started_at(5, "call-1", started_at);
+        app.on_tool_call_started_at(4, "stale", started_at);
+        assert_eq!(
+            app.tool_call_timing(5, "call-1")
+                .and_then(ToolCallTiming::duration),
+            None
+        );
+        assert_eq!(app.tool_call_timing(4, "stale"), None);
+
+        app.on_tool_call_finished_at(5, "call-1", started_at + Duration::from_millis(1_200));
+        app.on_tool_call_finished_at(5, "call-1", started_at + Duration::from_secs(9));
+        assert_eq!(
+            app.tool_call_timing(5, "call-1")
+                .and_then(ToolCallTiming::duration),
+            Some(Duration::from_millis(1_200))
+        );
+
+        app.mode = Mode::Idle;
+        app.input = "/clear".to_string();
+        app.on_submit(&mut NoopHost);
+        assert_eq!(app.tool_call_timing(5, "call-1"), None);
+    }
+
+    #[test]
+    fn fenced_code_blocks_match_fence_markers_and_keep_unterminated_blocks() {
+        let markdown =
+            "intro\n```rust\nfn main() {}\n```\n  ~~~~\n```\nnested\n~~~~\n````\nopen\n```";
+
+        assert_eq!(
+            fenced_code_blocks(markdown),
+            vec![
+                "fn main() {}".to_string(),
+                "```\nnested".to_string(),
+                "open\n```".to_string(),
+            ]
+        );
+        assert!(fenced_code_blocks("no fences here").is_empty());
+    }
+
     #[test]
     fn input_history_up_down_recall() {
         let mut app = App::new();
@@ -0,0 +1,110 @@
+//! Structured notifications of `App` state changes.
+//!
+//! Frontends other than the inline TUI (a web view, a headless JSON mode)
+//! mirror a session by subscribing with [`App::subscribe`] instead of
+//! diffing `App` under its lock. Events are published while the change is
+//! applied, so replaying them in order over an empty transcript reproduces
+//! `App::transcript` and `App::mode`.
+//!
+//! Only changes made through `App` methods are published; writing the
+//! public `transcript` or `mode` fields directly bypasses subscribers.
+//!
+//! [`App::subscribe`]: crate::app::App::subscribe
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::app::{Message, Mode, RunId, ToolConfirmation};
+
+/// Notification delivered to [`App::subscribe`](crate::app::App::subscribe)
+/// receivers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppEvent {
+    /// A message was appended to the transcript at `index`.
+    MessageAdded { index: usize, message: Message },
+    /// Streamed text was appended to the message at `index`.
+    ChunkAppended {
+        index: usize,
+        run_id: RunId,
+        chunk: String,
+    },
+    /// The message at `index` changed in place (for example, it stopped
+    /// streaming).
+    MessageUpdated { index: usize, message: Message },
+    /// The transcript changed in a way other than the events above (clear,
+    /// session restore, a finished stream being merged); carries all of it.
+    TranscriptReplaced { messages: Vec<Message> },
+    /// `App::mode` changed.
+    RunStateChanged { mode: Mode },
+    /// A tool call of `run_id` started.
+    ToolStarted {
+        run_id: RunId,
+        call_id: String,
+        tool_name: String,
+        arguments: serde_json::Value,
+    },
+    /// A tool call of `run_id` finished.
+    ToolFinished {
+        run_id: RunId,
+        call_id: String,
+        tool_name: String,
+        is_error: bool,
+        content: serde_json::Value,
+    },
+    /// A mutating tool call is paused until
+    /// [`App::resolve_tool_confirmation`](crate::app::App::resolve_tool_confirmation).
+    ToolConfirmationRequested { confirmation: ToolConfirmation },
+}
+
+/// Live subscriber channels of one `App`.
+///
+/// Subscribers are not part of the app's state: clones start without any and
+/// equality ignores them.
+#[derive(Default)]
+pub(crate) struct AppSubscribers {
+    senders: Vec<Sender<AppEvent>>,
+}
+
+impl AppSubscribers {
+    pub(crate) fn subscribe(&mut self) -> Receiver<AppEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.senders.push(tx);
+        rx
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn senders_len(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Sends `event` to every live subscriber, dropping disconnected ones.
+    pub(crate) fn publish(&mut self, event: AppEvent) {
+        self.senders.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+impl Clone for AppSubscribers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for AppSubscribers {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for AppSubscribers {}
+
+impl fmt::Debug for AppSubscribers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppSubscribers")
+            .field("len", &self.senders.len())
+            .finish()
+    }
+}
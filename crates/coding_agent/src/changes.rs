@@ -0,0 +1,429 @@
+//! Session-wide record of files mutated by host tools.
+//!
+//! The change set keeps the pre-session contents of every file touched by
+//! `write`, `edit`, or `apply_patch`, so the review panel can diff each file
+//! against that baseline and revert it. Only the first snapshot per path is
+//! kept; later mutations never move the baseline.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DIFF_CONTEXT_LINES: usize = 3;
+/// Upper bound on LCS table cells; larger edits diff as a full replacement.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Contents of a file before a host tool first mutated it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSnapshot {
+    pub path: PathBuf,
+    /// `None` when the file did not exist yet.
+    pub original: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChangeStatus {
+    Added,
+    Modified,
+    Deleted,
+    /// Touched this session but currently identical to the baseline.
+    Unchanged,
+}
+
+impl FileChangeStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+            Self::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// One touched file compared against its pre-session baseline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    /// Path relative to the workspace root, for display.
+    pub label: String,
+    pub status: FileChangeStatus,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Unified diff text; empty when unchanged or for binary content.
+    pub diff: String,
+    pub binary: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileChangeSet {
+    baselines: BTreeMap<PathBuf, Option<Vec<u8>>>,
+}
+
+impl FileChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.baselines.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.baselines.len()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.baselines.keys().map(PathBuf::as_path)
+    }
+
+    /// Records a baseline unless the path already has one.
+    pub fn record(&mut self, snapshot: FileSnapshot) {
+        self.baselines
+            .entry(snapshot.path)
+            .or_insert(snapshot.original);
+    }
+
+    /// Compares every touched file on disk against its baseline.
+    pub fn review(&self, workspace_root: &Path) -> Vec<FileChange> {
+        self.baselines
+            .iter()
+            .map(|(path, original)| {
+                let current = fs::read(path).ok();
+                compare_file(
+                    path,
+                    workspace_root,
+                    original.as_deref(),
+                    current.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    /// Restores `path` to its baseline (deleting it if it did not exist) and
+    /// stops tracking it.
+    pub fn revert(&mut self, path: &Path) -> Result<(), String> {
+        let Some(original) = self.baselines.get(path) else {
+            return Err(format!("{} has no recorded changes", path.display()));
+        };
+
+        match original {
+            Some(bytes) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|error| {
+                        format!(
+                            "Failed to create parent directories {}: {error}",
+                            parent.display()
+                        )
+                    })?;
+                }
+                fs::write(path, bytes)
+                    .map_err(|error| format!("Failed to restore {}: {error}", path.display()))?;
+            }
+            None => {
+                if path.exists() {
+                    fs::remove_file(path)
+                        .map_err(|error| format!("Failed to remove {}: {error}", path.display()))?;
+                }
+            }
+        }
+
+        self.baselines.remove(path);
+        Ok(())
+    }
+}
+
+fn compare_file(
+    path: &Path,
+    workspace_root: &Path,
+    original: Option<&[u8]>,
+    current: Option<&[u8]>,
+) -> FileChange {
+    let status = match (original, current) {
+        (None, Some(_)) => FileChangeStatus::Added,
+        (Some(_), None) => FileChangeStatus::Deleted,
+        (Some(before), Some(after)) if before != after => FileChangeStatus::Modified,
+        _ => FileChangeStatus::Unchanged,
+    };
+
+    let label = path
+        .strip_prefix(workspace_root)
+        .unwrap_or(path)
+        .display()
+        .to_string();
+    let mut change = FileChange {
+        path: path.to_path_buf(),
+        label,
+        status,
+        additions: 0,
+        deletions: 0,
+        diff: String::new(),
+        binary: false,
+    };
+    if status == FileChangeStatus::Unchanged {
+        return change;
+    }
+
+    let (Ok(before), Ok(after)) = (
+        std::str::from_utf8(original.unwrap_or_default()),
+        std::str::from_utf8(current.unwrap_or_default()),
+    ) else {
+        change.binary = true;
+        return change;
+    };
+
+    let old_label = original.map(|_| format!("a/{}", change.label));
+    let new_label = current.map(|_| format!("b/{}", change.label));
+    let diff = unified_diff(before, after, old_label.as_deref(), new_label.as_deref());
+
+    change.additions = diff.additions;
+    change.deletions = diff.deletions;
+    change.diff = diff.text;
+    change
+}
+
+/// A rendered unified diff with its line counts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnifiedDiff {
+    pub text: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Builds a unified diff between two texts; `None` labels render as
+/// `/dev/null` for added or deleted files.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: Option<&str>,
+    new_label: Option<&str>,
+) -> UnifiedDiff {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+
+    let additions = ops.iter().filter(|op| **op == DiffOp::Insert).count();
+    let deletions = ops.iter().filter(|op| **op == DiffOp::Delete).count();
+    if additions == 0 && deletions == 0 {
+        return UnifiedDiff::default();
+    }
+
+    let mut text = format!(
+        "--- {}\n+++ {}\n",
+        old_label.unwrap_or("/dev/null"),
+        new_label.unwrap_or("/dev/null")
+    );
+
+    // Positions (in old/new line numbers) at the start of each op.
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut old_index, mut new_index) = (0, 0);
+    for op in &ops {
+        positions.push((old_index, new_index));
+        match op {
+            DiffOp::Equal => {
+                old_index += 1;
+                new_index += 1;
+            }
+            DiffOp::Delete => old_index += 1,
+            DiffOp::Insert => new_index += 1,
+        }
+    }
+    positions.push((old_index, new_index));
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| **op != DiffOp::Equal)
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut hunk_start = 0;
+    while hunk_start < changed.len() {
+        let mut hunk_end = hunk_start;
+        while hunk_end + 1 < changed.len()
+            && changed[hunk_end + 1] - changed[hunk_end] <= DIFF_CONTEXT_LINES * 2 + 1
+        {
+            hunk_end += 1;
+        }
+
+        let first = changed[hunk_start].saturating_sub(DIFF_CONTEXT_LINES);
+        let last = (changed[hunk_end] + DIFF_CONTEXT_LINES + 1).min(ops.len());
+        let (old_start, new_start) = positions[first];
+        let (old_end, new_end) = positions[last];
+
+        text.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_end - old_start),
+            hunk_range(new_start, new_end - new_start)
+        ));
+        for index in first..last {
+            let (old_index, new_index) = positions[index];
+            let (prefix, line) = match ops[index] {
+                DiffOp::Equal => (' ', old_lines[old_index]),
+                DiffOp::Delete => ('-', old_lines[old_index]),
+                DiffOp::Insert => ('+', new_lines[new_index]),
+            };
+            text.push(prefix);
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        hunk_start = hunk_end + 1;
+    }
+
+    UnifiedDiff {
+        text,
+        additions,
+        deletions,
+    }
+}
+
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Line-level edit script: common prefix/suffix are trimmed, then the middle
+/// is aligned by longest common subsequence.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![DiffOp::Equal; prefix];
+    ops.extend(lcs_ops(old_mid, new_mid));
+    ops.extend(std::iter::repeat_n(DiffOp::Equal, suffix));
+    ops
+}
+
+fn lcs_ops(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let (rows, cols) = (old.len(), new.len());
+    if rows == 0 || cols == 0 || (rows + 1).saturating_mul(cols + 1) > MAX_DIFF_CELLS {
+        let mut ops = vec![DiffOp::Delete; rows];
+        ops.extend(std::iter::repeat_n(DiffOp::Insert, cols));
+        return ops;
+    }
+
+    // lengths[i][j] = LCS length of old[i..] and new[j..].
+    let width = cols + 1;
+    let mut lengths = vec![0u32; (rows + 1) * width];
+    for i in (0..rows).rev() {
+        for j in (0..cols).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(rows + cols);
+    let (mut i, mut j) = (0, 0);
+    while i < rows && j < cols {
+        if old[i] == new[j] {
+            ops.push(DiffOp::Equal);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            ops.push(DiffOp::Delete);
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert);
+            j += 1;
+        }
+    }
+    ops.extend(std::iter::repeat_n(DiffOp::Delete, rows - i));
+    ops.extend(std::iter::repeat_n(DiffOp::Insert, cols - j));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_emits_hunks_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nC\nd\ne\nf\ng\nh\ni\nj\nk\n";
+
+        let diff = unified_diff(old, new, Some("a/f.txt"), Some("b/f.txt"));
+
+        assert_eq!(diff.additions, 2);
+        assert_eq!(diff.deletions, 1);
+        assert_eq!(
+            diff.text,
+            "--- a/f.txt\n+++ b/f.txt\n\
+             @@ -1,6 +1,6 @@\n a\n b\n-c\n+C\n d\n e\n f\n\
+             @@ -8,3 +8,4 @@\n h\n i\n j\n+k\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_of_new_file_uses_dev_null() {
+        let diff = unified_diff("", "one\ntwo\n", None, Some("b/new.txt"));
+
+        assert_eq!(
+            diff.text,
+            "--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+one\n+two\n"
+        );
+        assert_eq!(
+            unified_diff("same\n", "same\n", None, None),
+            UnifiedDiff::default()
+        );
+    }
+
+    #[test]
+    fn change_set_keeps_first_baseline_and_reverts_files() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let existing = workspace.path().join("existing.txt");
+        let created = workspace.path().join("created.txt");
+        fs::write(&existing, "before\n").expect("seed file");
+
+        let mut changes = FileChangeSet::default();
+        changes.record(FileSnapshot {
+            path: existing.clone(),
+            original: Some(b"before\n".to_vec()),
+        });
+        changes.record(FileSnapshot {
+            path: existing.clone(),
+            original: Some(b"intermediate\n".to_vec()),
+        });
+        changes.record(FileSnapshot {
+            path: created.clone(),
+            original: None,
+        });
+        fs::write(&existing, "after\n").expect("modify file");
+        fs::write(&created, "new\n").expect("create file");
+
+        let review = changes.review(workspace.path());
+        assert_eq!(review.len(), 2);
+        assert_eq!(review[0].status, FileChangeStatus::Added);
+        assert_eq!(review[1].status, FileChangeStatus::Modified);
+        assert!(review[1].diff.contains("-before\n+after\n"));
+        assert!(review[1].diff.starts_with("--- a/existing.txt\n"));
+
+        changes.revert(&existing).expect("revert modified file");
+        changes.revert(&created).expect("revert created file");
+        assert_eq!(fs::read_to_string(&existing).expect("read"), "before\n");
+        assert!(!created.exists());
+        assert!(changes.is_empty());
+        assert!(changes.revert(&existing).is_err());
+    }
+}
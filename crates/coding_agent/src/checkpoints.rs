@@ -0,0 +1,307 @@
+//! Content-addressed workspace checkpoints under `.agent/checkpoints/`.
+//!
+//! Every mutating tool call that touched files records one checkpoint: a
+//! manifest listing each affected path with the blob holding its contents
+//! from before the call (or no blob when the file did not exist). Blobs live
+//! in `objects/` keyed by a hash of their bytes, so unchanged contents are
+//! stored once across checkpoints. Restoring a run of checkpoints puts every
+//! path back to its state before the earliest of them.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::changes::FileSnapshot;
+
+/// Checkpoint directory, relative to the workspace root.
+pub const CHECKPOINTS_DIR: &str = ".agent/checkpoints";
+const OBJECTS_DIR: &str = "objects";
+const MANIFEST_EXTENSION: &str = "json";
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CheckpointManifest {
+    id: String,
+    files: Vec<CheckpointFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CheckpointFile {
+    path: PathBuf,
+    /// Blob name, or `None` when the file did not exist.
+    blob: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(workspace_root: &Path) -> Self {
+        Self {
+            dir: workspace_root.join(CHECKPOINTS_DIR),
+        }
+    }
+
+    /// Stores `snapshots` and returns the new checkpoint id. Only the first
+    /// snapshot of each path is kept.
+    pub fn create(&self, snapshots: &[FileSnapshot]) -> Result<String, String> {
+        let mut files: Vec<CheckpointFile> = Vec::with_capacity(snapshots.len());
+        for snapshot in snapshots {
+            if files.iter().any(|file| file.path == snapshot.path) {
+                continue;
+            }
+            let blob = match &snapshot.original {
+                Some(bytes) => Some(self.store_blob(bytes)?),
+                None => None,
+            };
+            files.push(CheckpointFile {
+                path: snapshot.path.clone(),
+                blob,
+            });
+        }
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default();
+        for suffix in 0u32.. {
+            let id = format!("{millis:013}-{suffix}");
+            let manifest = CheckpointManifest {
+                id: id.clone(),
+                files: files.clone(),
+            };
+            let json = serde_json::to_vec_pretty(&manifest)
+                .map_err(|error| format!("Failed to encode checkpoint {id}: {error}"))?;
+            match write_new_file(&self.manifest_path(&id), &json) {
+                Ok(()) => return Ok(id),
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(format!("Failed to write checkpoint {id}: {error}")),
+            }
+        }
+        unreachable!("checkpoint id suffixes are unbounded")
+    }
+
+    /// Paths recorded by checkpoint `id`.
+    pub fn paths(&self, id: &str) -> Result<Vec<PathBuf>, String> {
+        Ok(self
+            .load(id)?
+            .files
+            .into_iter()
+            .map(|file| file.path)
+            .collect())
+    }
+
+    /// Restores every path recorded by `ids` to its contents in the earliest
+    /// checkpoint that lists it; files that did not exist yet are removed.
+    /// Nothing is written unless every manifest and blob could be read.
+    /// Returns the restored paths, sorted.
+    pub fn restore(&self, ids: &[String]) -> Result<Vec<PathBuf>, String> {
+        let mut targets: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
+        for id in ids {
+            for file in self.load(id)?.files {
+                targets.entry(file.path).or_insert(file.blob);
+            }
+        }
+
+        let mut contents: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::with_capacity(targets.len());
+        for (path, blob) in targets {
+            let bytes = match blob {
+                Some(blob) => Some(self.read_blob(&blob)?),
+                None => None,
+            };
+            contents.push((path, bytes));
+        }
+
+        let mut restored = Vec::with_capacity(contents.len());
+        for (path, bytes) in contents {
+            match bytes {
+                Some(bytes) => {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent).map_err(|error| {
+                            format!("Failed to create {}: {error}", parent.display())
+                        })?;
+                    }
+                    fs::write(&path, bytes).map_err(|error| {
+                        format!("Failed to restore {}: {error}", path.display())
+                    })?;
+                }
+                None => match fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(error) if error.kind() == ErrorKind::NotFound => {}
+                    Err(error) => {
+                        return Err(format!("Failed to remove {}: {error}", path.display()))
+                    }
+                },
+            }
+            restored.push(path);
+        }
+        Ok(restored)
+    }
+
+    fn load(&self, id: &str) -> Result<CheckpointManifest, String> {
+        if !is_checkpoint_id(id) {
+            return Err(format!("Invalid checkpoint id '{id}'"));
+        }
+        let raw = fs::read(self.manifest_path(id))
+            .map_err(|error| format!("Failed to read checkpoint {id}: {error}"))?;
+        let manifest: CheckpointManifest = serde_json::from_slice(&raw)
+            .map_err(|error| format!("Invalid checkpoint {id}: {error}"))?;
+        if manifest.id != id {
+            return Err(format!("Checkpoint {id} records id '{}'", manifest.id));
+        }
+        Ok(manifest)
+    }
+
+    /// Writes `bytes` under their hash unless an identical blob exists. A
+    /// different blob with the same hash is an error rather than an alias.
+    fn store_blob(&self, bytes: &[u8]) -> Result<String, String> {
+        let name = blob_name(bytes);
+        let path = self.dir.join(OBJECTS_DIR).join(&name);
+        match fs::read(&path) {
+            Ok(existing) if existing == bytes => return Ok(name),
+            Ok(_) => {
+                return Err(format!(
+                    "Checkpoint blob {name} collides with other content"
+                ))
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => return Err(format!("Failed to read blob {name}: {error}")),
+        }
+
+        match write_new_file(&path, bytes) {
+            Ok(()) => Ok(name),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => Ok(name),
+            Err(error) => Err(format!("Failed to write blob {name}: {error}")),
+        }
+    }
+
+    fn read_blob(&self, name: &str) -> Result<Vec<u8>, String> {
+        if name.len() != 32 || !name.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(format!("Invalid checkpoint blob name '{name}'"));
+        }
+        let bytes = fs::read(self.dir.join(OBJECTS_DIR).join(name))
+            .map_err(|error| format!("Failed to read blob {name}: {error}"))?;
+        if blob_name(&bytes) != name {
+            return Err(format!("Checkpoint blob {name} is corrupted"));
+        }
+        Ok(bytes)
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.{MANIFEST_EXTENSION}"))
+    }
+}
+
+fn is_checkpoint_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit() || byte == b'-')
+}
+
+/// FNV-1a 128-bit hash of `bytes` as 32 hex digits.
+fn blob_name(bytes: &[u8]) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in bytes {
+        hash ^= u128::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    format!("{hash:032x}")
+}
+
+/// Creates `path` (and its parents) with `bytes`, failing if it exists.
+fn write_new_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(bytes)?;
+    file.sync_data()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::{blob_name, CheckpointStore, CHECKPOINTS_DIR, OBJECTS_DIR};
+    use crate::changes::FileSnapshot;
+
+    fn snapshot(path: &std::path::Path, original: Option<&str>) -> FileSnapshot {
+        FileSnapshot {
+            path: path.to_path_buf(),
+            original: original.map(|text| text.as_bytes().to_vec()),
+        }
+    }
+
+    #[test]
+    fn restore_returns_paths_to_their_state_before_the_earliest_checkpoint() {
+        let workspace = TempDir::new().expect("temp workspace");
+        let store = CheckpointStore::new(workspace.path());
+        let kept = workspace.path().join("src/lib.rs");
+        let added = workspace.path().join("notes.md");
+        fs::create_dir_all(kept.parent().expect("parent")).expect("src dir");
+
+        fs::write(&kept, "one").expect("initial");
+        let first = store
+            .create(&[
+                snapshot(&kept, Some("one")),
+                snapshot(&kept, Some("ignored")),
+            ])
+            .expect("first checkpoint");
+        fs::write(&kept, "two").expect("first edit");
+
+        let second = store
+            .create(&[snapshot(&kept, Some("two")), snapshot(&added, None)])
+            .expect("second checkpoint");
+        fs::write(&kept, "three").expect("second edit");
+        fs::write(&added, "new").expect("new file");
+        assert_ne!(first, second);
+        assert_eq!(
+            store.paths(&second).expect("paths"),
+            vec![kept.clone(), added.clone()]
+        );
+
+        let restored = store
+            .restore(std::slice::from_ref(&second))
+            .expect("restore second");
+        assert_eq!(restored, vec![added.clone(), kept.clone()]);
+        assert_eq!(fs::read_to_string(&kept).expect("kept"), "two");
+        assert!(!added.exists());
+
+        store.restore(&[first, second]).expect("restore both");
+        assert_eq!(fs::read_to_string(&kept).expect("kept"), "one");
+    }
+
+    #[test]
+    fn identical_contents_share_one_blob_and_corruption_blocks_restore() {
+        let workspace = TempDir::new().expect("temp workspace");
+        let store = CheckpointStore::new(workspace.path());
+        let path = workspace.path().join("a.txt");
+        fs::write(&path, "current").expect("file");
+
+        let first = store
+            .create(&[snapshot(&path, Some("same"))])
+            .expect("first");
+        store
+            .create(&[snapshot(&path, Some("same"))])
+            .expect("second");
+        let objects = workspace.path().join(CHECKPOINTS_DIR).join(OBJECTS_DIR);
+        assert_eq!(fs::read_dir(&objects).expect("objects").count(), 1);
+
+        fs::write(objects.join(blob_name(b"same")), "tampered").expect("tamper");
+        assert!(store.restore(&[first]).is_err());
+        assert_eq!(fs::read_to_string(&path).expect("file"), "current");
+
+        assert!(store.restore(&["missing".to_string()]).is_err());
+        assert!(store.restore(&["../../x".to_string()]).is_err());
+    }
+}
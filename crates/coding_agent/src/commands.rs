@@ -3,10 +3,33 @@ pub enum SlashCommand {
     Help,
     Clear,
     Cancel,
+    Steer(String),
+    Dequeue(Option<usize>),
+    Changes,
+    /// One-based turn to rewind to; `None` lists the turns.
+    Undo(Option<usize>),
+    Preview,
+    Copy(Option<CopyTarget>),
+    /// Raw `/prompt` arguments: a template name and `var=value` pairs.
+    Prompt(String),
+    Model(String),
+    Models,
+    Thinking(String),
+    /// New session title; empty shows the current one.
+    Title(String),
+    Sessions,
     Quit,
     Unknown(String),
 }
 
+/// What `/copy last [n]` copies from the newest assistant message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyTarget {
+    LastMessage,
+    /// One-based index of a fenced code block.
+    LastCodeBlock(usize),
+}
+
 pub fn parse_slash_command(input: &str) -> Option<SlashCommand> {
     let trimmed = input.trim();
     if !trimmed.starts_with('/') {
@@ -18,14 +41,44 @@ pub fn parse_slash_command(input: &str) -> Option<SlashCommand> {
         .next()
         .unwrap_or(trimmed)
         .to_string();
+    let args = trimmed[command.len()..].trim();
 
     let parsed = match command.as_str() {
         "/help" => SlashCommand::Help,
         "/clear" => SlashCommand::Clear,
         "/cancel" => SlashCommand::Cancel,
+        "/steer" => SlashCommand::Steer(args.to_string()),
+        "/dequeue" => {
+            SlashCommand::Dequeue(args.parse::<usize>().ok().filter(|position| *position > 0))
+        }
+        "/changes" => SlashCommand::Changes,
+        "/undo" => SlashCommand::Undo(args.parse::<usize>().ok().filter(|turn| *turn > 0)),
+        "/preview" => SlashCommand::Preview,
+        "/copy" => SlashCommand::Copy(parse_copy_target(args)),
+        "/prompt" => SlashCommand::Prompt(args.to_string()),
+        "/model" => SlashCommand::Model(args.to_string()),
+        "/models" => SlashCommand::Models,
+        "/thinking" => SlashCommand::Thinking(args.to_string()),
+        "/title" => SlashCommand::Title(args.to_string()),
+        "/sessions" => SlashCommand::Sessions,
         "/quit" => SlashCommand::Quit,
         _ => SlashCommand::Unknown(command),
     };
 
     Some(parsed)
 }
+
+fn parse_copy_target(args: &str) -> Option<CopyTarget> {
+    let mut parts = args.split_whitespace();
+    if parts.next()? != "last" {
+        return None;
+    }
+
+    let target = match parts.next() {
+        None => CopyTarget::LastMessage,
+        Some(block) => {
+            CopyTarget::LastCodeBlock(block.parse::<usize>().ok().filter(|block| *block > 0)?)
+        }
+    };
+    parts.next().is_none().then_some(target)
+}
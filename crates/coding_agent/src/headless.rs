@@ -0,0 +1,632 @@
+//! Headless JSON-RPC frontend (`coding_agent --headless`).
+//!
+//! The agent core runs exactly as under the TUI — `App` driven by a
+//! `RuntimeController` on a tape_tui runtime thread — but the runtime's
+//! terminal draws nothing and reads no keys. Requests arrive as JSON-RPC 2.0
+//! objects, one per line, on the input stream; responses and notifications
+//! leave one per line on the output stream.
+//!
+//! Methods:
+//! - `submit` `{ "text": string }`: the same as typing `text` in the editor
+//!   and pressing enter, slash commands included.
+//! - `cancel`: cancels the active run.
+//! - `set_tool_preview` `{ "enabled": bool }`: pauses mutating tool calls
+//!   until they are approved.
+//! - `approve_tool` `{ "call_id": string, "approved": bool }`: resolves the
+//!   paused tool call.
+//! - `state`: returns `{ "mode", "transcript" }`.
+//! - `shutdown`: stops the agent. End of input does the same.
+//!
+//! Every [`AppEvent`] is sent as an `event` notification. Events caused by a
+//! request are written before its response.
+
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+use serde_json::{json, Value};
+use tape_tui::runtime::tui::{
+    Command, CustomCommand, CustomCommandCtx, CustomCommandError, RuntimeHandle,
+};
+use tape_tui::Terminal;
+
+use crate::app::{App, Message, Mode, Role, ToolConfirmation};
+use crate::app_events::AppEvent;
+use crate::runtime::RuntimeController;
+
+pub(crate) const JSONRPC_VERSION: &str = "2.0";
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const INVALID_REQUEST: i64 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+/// Well-formed request the agent could not carry out.
+pub(crate) const REQUEST_FAILED: i64 = -32000;
+
+const HEADLESS_COLUMNS: u16 = 120;
+const HEADLESS_ROWS: u16 = 40;
+
+/// Terminal for the headless runtime: it never reads input and discards
+/// output.
+#[derive(Debug, Default)]
+pub struct HeadlessTerminal;
+
+impl Terminal for HeadlessTerminal {
+    fn start(
+        &mut self,
+        _on_input: Box<dyn FnMut(String) + Send>,
+        _on_resize: Box<dyn FnMut() + Send>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn drain_input(&mut self, _max_ms: u64, _idle_ms: u64) {}
+
+    fn write(&mut self, _data: &str) {}
+
+    fn columns(&self) -> u16 {
+        HEADLESS_COLUMNS
+    }
+
+    fn rows(&self) -> u16 {
+        HEADLESS_ROWS
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RpcError {
+    pub(crate) code: i64,
+    pub(crate) message: String,
+}
+
+impl RpcError {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// JSON-RPC response object for `id`.
+pub(crate) fn response_json(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+/// Writes `message` as one line. A closed output leaves nobody to report to;
+/// the next read of input ends the session.
+pub(crate) fn write_json_line(output: &Mutex<impl Write>, message: &Value) {
+    let mut output = lock_unpoisoned(output);
+    let _ = writeln!(output, "{message}").and_then(|()| output.flush());
+}
+
+/// Line-delimited JSON-RPC endpoint fed by [`spawn_line_reader`].
+pub(crate) trait LineServer: Send + Sync + 'static {
+    /// Handles one input line; runs on the runtime thread.
+    fn handle_line(&self, line: &str);
+    /// Runs on the runtime thread once input has ended.
+    fn end_of_input(&self);
+}
+
+/// Reads `input` on a background thread and hands each non-blank line to
+/// `server` on the runtime thread, then reports the end of input.
+pub(crate) fn spawn_line_reader<S: LineServer, R: BufRead + Send + 'static>(
+    server: Arc<S>,
+    input: R,
+    runtime_handle: RuntimeHandle,
+) -> io::Result<JoinHandle<()>> {
+    thread::Builder::new()
+        .name("coding-agent-rpc-input".to_string())
+        .spawn(move || {
+            for line in input.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                runtime_handle.dispatch(Command::Custom(Box::new(LineCommand {
+                    server: Arc::clone(&server),
+                    line: Some(line),
+                })));
+            }
+            runtime_handle.dispatch(Command::Custom(Box::new(LineCommand {
+                server,
+                line: None,
+            })));
+        })
+}
+
+struct LineCommand<S: LineServer> {
+    server: Arc<S>,
+    /// `None` once input has ended.
+    line: Option<String>,
+}
+
+impl<S: LineServer> CustomCommand for LineCommand<S> {
+    fn name(&self) -> &'static str {
+        "rpc_input_line"
+    }
+
+    fn apply(self: Box<Self>, _ctx: &mut CustomCommandCtx) -> Result<(), CustomCommandError> {
+        match &self.line {
+            Some(line) => self.server.handle_line(line),
+            None => self.server.end_of_input(),
+        }
+        Ok(())
+    }
+}
+
+/// Serves one session over JSON-RPC.
+///
+/// Requests are handled on the runtime thread (see
+/// [`HeadlessServer::spawn_reader`]), so they see and mutate `App` in the same
+/// order as run events.
+pub struct HeadlessServer<W: Write + Send + 'static> {
+    app: Arc<Mutex<App>>,
+    host: Arc<RuntimeController>,
+    output: Mutex<W>,
+    events: Mutex<Receiver<AppEvent>>,
+}
+
+impl<W: Write + Send + 'static> HeadlessServer<W> {
+    /// Subscribes to `app` and writes to `output`.
+    pub fn new(app: Arc<Mutex<App>>, host: Arc<RuntimeController>, output: W) -> Arc<Self> {
+        let events = lock_unpoisoned(&app).subscribe();
+        Arc::new(Self {
+            app,
+            host,
+            output: Mutex::new(output),
+            events: Mutex::new(events),
+        })
+    }
+
+    /// Reads requests from `input` on a background thread and hands each line
+    /// to the runtime thread. End of input requests a shutdown.
+    pub fn spawn_reader<R: BufRead + Send + 'static>(
+        self: &Arc<Self>,
+        input: R,
+        runtime_handle: RuntimeHandle,
+    ) -> io::Result<JoinHandle<()>> {
+        spawn_line_reader(Arc::clone(self), input, runtime_handle)
+    }
+
+    /// Writes every event published since the last call.
+    pub fn flush_events(&self) {
+        let events: Vec<AppEvent> = lock_unpoisoned(&self.events).try_iter().collect();
+        for event in events {
+            self.write_message(json!({
+                "jsonrpc": JSONRPC_VERSION,
+                "method": "event",
+                "params": event_json(&event),
+            }));
+        }
+    }
+
+    /// Handles one request line and writes its response, if it needs one.
+    /// Call on the runtime thread.
+    pub fn handle_line(&self, line: &str) {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(error) => {
+                self.write_response(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, format!("Parse error: {error}"))),
+                );
+                return;
+            }
+        };
+
+        let id = request.get("id").cloned();
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some(method) if request.get("jsonrpc") == Some(&json!(JSONRPC_VERSION)) => {
+                let params = request.get("params").cloned().unwrap_or(Value::Null);
+                self.handle_request(method, &params)
+            }
+            _ => Err(RpcError::new(INVALID_REQUEST, "Invalid request")),
+        };
+
+        self.flush_events();
+        // Requests without an id are notifications and get no response.
+        if let Some(id) = id {
+            self.write_response(id, result);
+        }
+    }
+
+    fn handle_request(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        let mut host = Arc::clone(&self.host);
+        let mut app = lock_unpoisoned(&self.app);
+        match method {
+            "submit" => {
+                let text = string_param(params, "text")?;
+                app.on_input_replace(text);
+                app.on_submit(&mut host);
+                Ok(Value::Null)
+            }
+            "cancel" => {
+                app.on_cancel(&mut host);
+                Ok(Value::Null)
+            }
+            "set_tool_preview" => {
+                let enabled = bool_param(params, "enabled")?;
+                app.set_tool_preview(enabled, &mut host)
+                    .map_err(|error| RpcError::new(REQUEST_FAILED, error))?;
+                Ok(Value::Null)
+            }
+            "approve_tool" => {
+                let call_id = string_param(params, "call_id")?;
+                let approved = bool_param(params, "approved")?;
+                match app.pending_tool_confirmation() {
+                    Some(confirmation) if confirmation.call_id == call_id => {}
+                    _ => {
+                        return Err(RpcError::new(
+                            REQUEST_FAILED,
+                            format!("Tool call {call_id} is not waiting for approval"),
+                        ))
+                    }
+                }
+                app.resolve_tool_confirmation(approved, &mut host);
+                Ok(Value::Null)
+            }
+            "state" => Ok(json!({
+                "mode": mode_json(&app.mode),
+                "transcript": app.transcript.iter().map(message_json).collect::<Vec<_>>(),
+            })),
+            "shutdown" => {
+                app.on_quit(&mut host);
+                Ok(Value::Null)
+            }
+            unknown => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {unknown}"),
+            )),
+        }
+    }
+
+    fn shutdown(&self) {
+        let mut host = Arc::clone(&self.host);
+        lock_unpoisoned(&self.app).on_quit(&mut host);
+        self.flush_events();
+    }
+
+    fn write_response(&self, id: Value, result: Result<Value, RpcError>) {
+        self.write_message(response_json(id, result));
+    }
+
+    fn write_message(&self, message: Value) {
+        write_json_line(&self.output, &message);
+    }
+}
+
+impl<W: Write + Send + 'static> LineServer for HeadlessServer<W> {
+    fn handle_line(&self, line: &str) {
+        HeadlessServer::handle_line(self, line);
+    }
+
+    fn end_of_input(&self) {
+        self.shutdown();
+    }
+}
+
+pub(crate) fn string_param(params: &Value, name: &str) -> Result<String, RpcError> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing string param '{name}'")))
+}
+
+pub(crate) fn bool_param(params: &Value, name: &str) -> Result<bool, RpcError> {
+    params
+        .get(name)
+        .and_then(Value::as_bool)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing boolean param '{name}'")))
+}
+
+fn event_json(event: &AppEvent) -> Value {
+    match event {
+        AppEvent::MessageAdded { index, message } => json!({
+            "type": "message_added",
+            "index": index,
+            "message": message_json(message),
+        }),
+        AppEvent::ChunkAppended {
+            index,
+            run_id,
+            chunk,
+        } => json!({
+            "type": "chunk_appended",
+            "index": index,
+            "run_id": run_id,
+            "chunk": chunk,
+        }),
+        AppEvent::MessageUpdated { index, message } => json!({
+            "type": "message_updated",
+            "index": index,
+            "message": message_json(message),
+        }),
+        AppEvent::TranscriptReplaced { messages } => json!({
+            "type": "transcript_replaced",
+            "messages": messages.iter().map(message_json).collect::<Vec<_>>(),
+        }),
+        AppEvent::RunStateChanged { mode } => json!({
+            "type": "run_state_changed",
+            "mode": mode_json(mode),
+        }),
+        AppEvent::ToolStarted {
+            run_id,
+            call_id,
+            tool_name,
+            arguments,
+        } => json!({
+            "type": "tool_started",
+            "run_id": run_id,
+            "call_id": call_id,
+            "tool_name": tool_name,
+            "arguments": arguments,
+        }),
+        AppEvent::ToolFinished {
+            run_id,
+            call_id,
+            tool_name,
+            is_error,
+            content,
+        } => json!({
+            "type": "tool_finished",
+            "run_id": run_id,
+            "call_id": call_id,
+            "tool_name": tool_name,
+            "is_error": is_error,
+            "content": content,
+        }),
+        AppEvent::ToolConfirmationRequested { confirmation } => json!({
+            "type": "tool_confirmation_requested",
+            "confirmation": confirmation_json(confirmation),
+        }),
+    }
+}
+
+fn message_json(message: &Message) -> Value {
+    let role = match message.role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::System => "system",
+        Role::Tool => "tool",
+    };
+    json!({
+        "role": role,
+        "content": message.content,
+        "streaming": message.streaming,
+        "run_id": message.run_id,
+    })
+}
+
+fn mode_json(mode: &Mode) -> Value {
+    match mode {
+        Mode::Idle => json!({ "state": "idle" }),
+        Mode::Running { run_id } => json!({ "state": "running", "run_id": run_id }),
+        Mode::Error(error) => json!({ "state": "error", "error": error }),
+        Mode::Exiting => json!({ "state": "exiting" }),
+    }
+}
+
+fn confirmation_json(confirmation: &ToolConfirmation) -> Value {
+    json!({
+        "run_id": confirmation.run_id,
+        "call_id": confirmation.call_id,
+        "tool_name": confirmation.tool_name,
+        "diff": confirmation.diff,
+    })
+}
+
+fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use serde_json::{json, Value};
+    use tape_tui::TUI;
+
+    use super::{lock_unpoisoned, HeadlessServer, HeadlessTerminal};
+    use crate::app::{App, Mode};
+    use crate::providers::MockProvider;
+    use crate::runtime::RuntimeController;
+
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            lock_unpoisoned(&self.0).extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        fn messages(&self) -> Vec<Value> {
+            String::from_utf8(lock_unpoisoned(&self.0).clone())
+                .expect("utf-8 output")
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("one JSON object per line"))
+                .collect()
+        }
+    }
+
+    struct Harness {
+        runtime: TUI<HeadlessTerminal>,
+        app: Arc<Mutex<App>>,
+        server: Arc<HeadlessServer<SharedOutput>>,
+        output: SharedOutput,
+    }
+
+    fn harness() -> Harness {
+        let app = Arc::new(Mutex::new(App::new()));
+        let mut runtime = TUI::new(HeadlessTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(MockProvider::new(vec![
+                "Hello ".to_string(),
+                "there".to_string(),
+            ])),
+        );
+        let output = SharedOutput::default();
+        let server = HeadlessServer::new(Arc::clone(&app), host, output.clone());
+        runtime.start().expect("runtime start");
+        Harness {
+            runtime,
+            app,
+            server,
+            output,
+        }
+    }
+
+    impl Harness {
+        fn run_until(&mut self, done: impl Fn(&App) -> bool) {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while !done(&lock_unpoisoned(&self.app)) {
+                assert!(Instant::now() < deadline, "timed out waiting for the app");
+                self.runtime.run_once();
+                self.server.flush_events();
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+
+    fn response(messages: &[Value], id: i64) -> &Value {
+        messages
+            .iter()
+            .find(|message| message["id"] == json!(id))
+            .unwrap_or_else(|| panic!("no response to request {id}"))
+    }
+
+    #[test]
+    fn submitted_turns_stream_events_and_respond() {
+        let mut harness = harness();
+        harness
+            .server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"submit","params":{"text":"hi"}}"#);
+        harness.run_until(|app| app.mode == Mode::Idle && app.transcript.len() == 2);
+        harness
+            .server
+            .handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"state"}"#);
+
+        let messages = harness.output.messages();
+        let events: Vec<&Value> = messages
+            .iter()
+            .filter(|message| message["method"] == "event")
+            .map(|message| &message["params"])
+            .collect();
+        assert_eq!(events[0]["type"], "message_added");
+        assert_eq!(events[0]["message"]["content"], "hi");
+        assert_eq!(
+            events[1],
+            &json!({ "type": "run_state_changed", "mode": { "state": "running", "run_id": 1 } })
+        );
+        let streamed: String = events
+            .iter()
+            .filter(|event| event["type"] == "chunk_appended")
+            .map(|event| event["chunk"].as_str().expect("chunk"))
+            .collect();
+        assert_eq!(streamed, "Hello there");
+        assert!(events.contains(&&json!({
+            "type": "run_state_changed",
+            "mode": { "state": "idle" },
+        })));
+
+        // The submit response follows the events it caused.
+        let submit_position = messages
+            .iter()
+            .position(|message| message["id"] == json!(1))
+            .expect("submit response");
+        assert_eq!(submit_position, 2);
+        assert_eq!(response(&messages, 1)["result"], Value::Null);
+        let state = &response(&messages, 2)["result"];
+        assert_eq!(state["mode"], json!({ "state": "idle" }));
+        assert_eq!(state["transcript"][1]["content"], "Hello there");
+        assert_eq!(state["transcript"][1]["streaming"], false);
+    }
+
+    #[test]
+    fn malformed_and_unknown_requests_get_errors() {
+        let harness = harness();
+        for line in [
+            "not json",
+            r#"{"jsonrpc":"2.0","id":1,"method":"bogus"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"submit","params":{}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"approve_tool","params":{"call_id":"c","approved":true}}"#,
+            r#"{"id":4,"method":"state"}"#,
+            r#"{"jsonrpc":"2.0","method":"cancel"}"#,
+        ] {
+            harness.server.handle_line(line);
+        }
+
+        let codes: Vec<(Value, Value)> = harness
+            .output
+            .messages()
+            .into_iter()
+            .filter(|message| message.get("id").is_some())
+            .map(|message| (message["id"].clone(), message["error"]["code"].clone()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                (Value::Null, json!(-32700)),
+                (json!(1), json!(-32601)),
+                (json!(2), json!(-32602)),
+                (json!(3), json!(-32000)),
+                (json!(4), json!(-32600)),
+            ]
+        );
+        // The cancel notification still ran; it only has no response.
+        assert_eq!(
+            lock_unpoisoned(&harness.app).transcript[0].content,
+            "No active run"
+        );
+    }
+
+    #[test]
+    fn end_of_input_shuts_the_agent_down() {
+        let mut harness = harness();
+        let input =
+            Cursor::new(b"\n{\"jsonrpc\":\"2.0\",\"id\":7,\"method\":\"state\"}\n".to_vec());
+        harness
+            .server
+            .spawn_reader(input, harness.runtime.runtime_handle())
+            .expect("spawn reader")
+            .join()
+            .expect("reader thread");
+        harness.run_until(|app| app.should_exit);
+
+        let messages = harness.output.messages();
+        assert_eq!(response(&messages, 7)["result"]["mode"]["state"], "idle");
+        assert_eq!(
+            messages.last().expect("exit event")["params"],
+            json!({ "type": "run_state_changed", "mode": { "state": "exiting" } })
+        );
+    }
+}
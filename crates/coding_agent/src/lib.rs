@@ -7,6 +7,10 @@
 //! - `CODING_AGENT_PROVIDER=mock` for deterministic local tests
 //! - `CODING_AGENT_PROVIDER=codex-api` for Codex API transport
 //!
+//! With `mock`, `CODING_AGENT_MOCK_SCENARIO` may name a scenario fixture (see
+//! `agent_provider_mock::scenario`) that scripts each run's chunks, delays,
+//! tool calls and failures; without it every run streams a fixed reply.
+//!
 //! When `CODING_AGENT_PROVIDER=codex-api`, set `CODING_AGENT_CODEX_CONFIG_PATH`
 //! to a readable UTF-8 JSON file with this shape:
 //!
@@ -14,7 +18,14 @@
 //! {
 //!   "access_token": "<jwt-with-https://api.openai.com/auth.chatgpt_account_id>",
 //!   "models": ["gpt-5.3-codex"],
-//!   "timeout_sec": 120
+//!   "timeout_sec": 120,
+//!   "pricing": {
+//!     "gpt-5.3-codex": {
+//!       "input_per_million": 1.25,
+//!       "cached_input_per_million": 0.125,
+//!       "output_per_million": 10.0
+//!     }
+//!   }
 //! }
 //! ```
 //!
@@ -23,8 +34,19 @@
 //!   `https://api.openai.com/auth.chatgpt_account_id`.
 //! - `models` is required and must include at least one non-empty model ID.
 //! - `timeout_sec` is optional and must be > 0 when provided.
+//! - `proxy_url` is optional and routes every request through that proxy;
+//!   without it `HTTPS_PROXY`/`NO_PROXY` from the environment apply.
+//! - `root_certificate_paths` optionally lists PEM bundles trusted in addition
+//!   to the bundled and platform roots, e.g. a TLS-intercepting proxy's CA.
+//!   `use_built_in_roots: false` trusts only those bundles.
+//! - `pricing` is optional; rates are USD per million tokens, must be finite and
+//!   non-negative, and `cached_input_per_million` defaults to the input rate.
+//!   Models without pricing report token usage but no cost.
 //! - Unknown JSON fields are rejected.
 //!
+//! Set `CODEX_API_WIRE_LOG` to a file path to record a redacted transport trace
+//! (requests, response heads and raw SSE) for bug reports.
+//!
 //! ## System instructions
 //!
 //! Runtime run requests always include required system instructions.
@@ -32,12 +54,166 @@
 //! block; runtime appends a concise tool-use policy and tool inventory before
 //! dispatching each provider run.
 //!
+//! `AGENTS.md` and `.agent/instructions.md` files in the workspace root and
+//! its parents (up to the git repository root) are prepended to that base,
+//! outermost first, each capped at 32 KiB and 64 KiB in total; the header
+//! lists the files that were loaded.
+//!
+//! Tool call arguments are checked against the `input_schema` of the
+//! provider's tool definition before the tool runs (`tools::schema`); a
+//! violation comes back to the model as an error result naming the field,
+//! e.g. `field 'timeout_sec' must be at least 1, got 0`.
+//!
+//! File tools (`read`, `edit`, `write`, `apply_patch`, the git tools, and the
+//! `bash` working directory) resolve every path through `tools::sandbox`: the
+//! canonical target, after following symlinks, must lie under the workspace
+//! root or a root listed in `CODING_AGENT_ALLOWED_PATHS` (`PATH` syntax). An
+//! unresolvable allowlist entry makes host tools unavailable.
+//!
+//! Set `CODING_AGENT_PERSISTENT_SHELL=1` to run `bash` calls in one long-lived
+//! shell whose output is a pseudo-terminal: `cd`, exports, and sourced
+//! environments persist across calls, and each result reports the shell's
+//! `cwd`. A timed-out call, an `exit`, the `restart_shell` tool, or 30 minutes
+//! of idleness stops the shell; the next call starts fresh in the workspace.
+//! Persistent mode is Unix-only; elsewhere the variable is ignored and every
+//! call stays one-shot.
+//!
+//! Tool output over 32 KiB is cut to its head and tail around a
+//! `[N bytes omitted]` marker before it reaches the model; the full output is
+//! saved to `.agent/tool-output/<run_id>-<call_id>`, and expanding the tool
+//! card shows it. Set `CODING_AGENT_TOOL_OUTPUT_LIMITS` to change the caps per
+//! tool, e.g. `bash=16384,git_diff=65536,*=32768` (bytes; `*` covers the other
+//! tools).
+//!
+//! `bash_background` starts a detached command in its own process group and
+//! returns an id for `process_status`, `process_logs` (bounded combined
+//! output), and `process_kill`. Running processes are listed above the status
+//! line and are killed when the runtime controller is dropped.
+//!
+//! `spawn_agent` delegates a scoped task to a child run on the same provider.
+//! The child sees only the task and optional context, may call only the
+//! listed `tools` (default `read`, `git_status`, `git_diff`; never
+//! `spawn_agent` or `update_plan`), shares the parent's cancellation, and
+//! its final reply becomes the tool result. Its tool calls show up nested
+//! inside the `spawn_agent` card rather than as cards of their own.
+//!
+//! Streaming replies are revealed at word boundaries by `stream_smoothing`:
+//! the release rate follows the backlog, a trailing partial word waits for
+//! its completion (or 200 ms of stream silence), and the full text shows as
+//! soon as the run ends. Model-facing history always holds the raw chunks.
+//!
+//! Once the rendered transcript exceeds `CODING_AGENT_TRANSCRIPT_MEMORY_BYTES`
+//! (default 8 MiB, `0` for unbounded), the header and the oldest whole
+//! messages that have scrolled into the terminal's scrollback stop being
+//! rendered and leave the runtime's frame. They stay in the terminal's
+//! scrollback, and a width change renders everything again from the
+//! transcript.
+//!
+//! Set `CODING_AGENT_TRANSCRIPT_MAX_WIDTH` (columns) to keep the header and
+//! transcript from spanning very wide terminals, and
+//! `CODING_AGENT_TRANSCRIPT_CENTER=1` to center the narrowed column. The
+//! prompt, status line and footer keep the full width.
+//!
+//! Model replies, tool arguments and tool output are sanitized before they
+//! reach the terminal: colors and other SGR styling are kept, but cursor
+//! movement, screen clears, title changes and device queries are removed.
+//! Set `CODING_AGENT_RAW_OUTPUT=1` to pass them through unchanged.
+//!
+//! On a width change the runtime clears the screen and scrollback and
+//! redraws in full, so history reflows rather than keeping its old wrapping.
+//! Settled messages keep their rendered rows with the source they were
+//! wrapped from. A resize wraps each one again once, and later updates only
+//! re-render messages whose source changed.
+//!
+//! Live tool calls render as transcript cards: key arguments, status, and
+//! duration on one header line, with output collapsed to a few lines.
+//! `bash` calls render as code cells instead: the highlighted command above
+//! its exit code, duration and output, collapsed to the newest lines.
+//! Restored sessions keep plain tool timeline rows.
+//!
+//! `shift+tab` moves key focus from the editor into the transcript (and
+//! back). There `up`/`down` select prompts, replies, and tool cards,
+//! `enter`/`space` expand the selected card, `r` re-submits the selected
+//! prompt, and `escape` or any typing returns to the editor. The plan/build
+//! view label cycles with `alt+m`.
+//!
+//! Copy contract: `c` on a selected item (tool cards copy their output), `1`-`9`
+//! for one of its fenced code blocks, and `/copy last [n]` for the newest
+//! assistant message or its nth code block all write to the system clipboard
+//! through OSC 52 and confirm with a two-second toast on the status line.
+//! Terminals without OSC 52 support silently ignore the write.
+//!
+//! While a turn runs, the status line shows its elapsed time, the time to
+//! the first streamed token and the streaming rate: output tokens per second
+//! once the provider reports usage, characters per second before that. The
+//! spinner ticker refreshes it; the transcript is not re-rendered for it.
+//!
+//! `/model <id>` and `/thinking <level>` jump straight to a provider-listed
+//! option instead of cycling; unknown values are rejected by the provider and
+//! both commands are refused while a run is active. `/models` lists the
+//! provider's catalog on a background thread, marking the selected model,
+//! context windows, and configured models the backend no longer offers. The
+//! Codex provider caches its `codex/models` listing for five minutes, and
+//! models it adds beyond the bootstrap `models` array become selectable.
+//!
+//! Prompt templates live in `<cwd>/.agent/prompts/<name>.md` with `{{variable}}`
+//! placeholders. `/prompt <name> [var=value ...]` expands one into the editor
+//! for review; variables not given inline are collected in a modal form
+//! (`tab`/`enter` next field, `escape` cancels) before expansion.
+//!
+//! `workspace_index` keeps an in-memory list of workspace files and their
+//! modification times, rescanned every two seconds on a background thread
+//! (`git ls-files` inside a repository, so ignore rules apply). Typing `@` in
+//! the prompt completes file paths from it.
+//!
+//! Frontends other than the inline TUI can mirror a session through
+//! `App::subscribe`, which returns a channel of `app_events::AppEvent`s:
+//! messages added, streamed chunks, in-place message updates, whole-transcript
+//! replacements, run state changes, and tool calls starting and finishing.
+//!
 //! Conversation memory contract: `coding_agent` owns model-facing run history and
 //! replays it on every turn through provider-neutral `RunMessage` items.
 //!
+//! Run queue contract: submitting a prompt while a run is active (or still
+//! cancelling) queues it; queued turns start in FIFO order after the active run
+//! reaches any terminal event, and `/dequeue <n>` drops one before it starts.
+//! `/steer <message>` instead queues at the front and interrupts the active run
+//! at its next safe boundary (an in-flight tool call completes and reports its
+//! real result), keeping the partial assistant/tool output in model-facing
+//! history. Explicit `/cancel` still discards partial output.
+//!
+//! Change review contract: files mutated by `write`, `edit`, and `apply_patch`
+//! are tracked in memory with their pre-session contents. `/changes` opens a
+//! modal surface listing each touched file with a diff against that baseline;
+//! `r` reverts the selected file (refused while a run is active). Baselines
+//! are not persisted, so a resumed session starts with an empty change set.
+//!
+//! Checkpoint contract: every tool call that mutates files stores their prior
+//! contents as a checkpoint under `<cwd>/.agent/checkpoints/` (blobs in
+//! `objects/` named by content hash, one JSON manifest per checkpoint) and
+//! records its id on the persisted `tool_result` entry. `/undo` lists the
+//! turns that changed files and `/undo <n>` restores the workspace to its
+//! state before turn `n`, rewinding later turns too; the conversation is kept.
+//! Undo is refused while a run is active and survives `--continue`.
+//!
+//! Preview contract: `/preview` toggles a session-wide mode in which `write`,
+//! `edit`, and `apply_patch` are validated and rendered as a would-be diff
+//! before execution. The run worker blocks until the user presses `y` or `n`;
+//! a rejection (or cancellation while waiting) returns an error tool result to
+//! the model and leaves disk untouched.
+//!
 //! Codex transport contract: Responses API `input` must be list-shaped JSON.
 //! Plain string `input` payloads are rejected during codex_api request preflight.
 //!
+//! Hosts embedding the runtime can call `RuntimeController::set_output_schema`
+//! to request final answers as JSON matching a schema; the Codex adapter sends
+//! it as a strict `json_schema` `text.format` on every request of the run.
+//!
+//! Each run request carries a `PromptCacheHint` marking the messages it shares
+//! with the previous run (same instructions, unchanged history) as a stable
+//! prefix. The Codex adapter keys its prompt cache by session id, so later turns
+//! of a long session reuse the cached history.
+//!
 //! ## Persistent sessions (v1 fail-closed contract)
 //!
 //! In default mode (`coding_agent` with no resume flags), startup preallocates
@@ -63,6 +239,25 @@
 //! - runtime append/sync failures are fatal (error mode + stop request + exit);
 //! - no degraded persistence fallback mode is used by the binary startup path.
 //!
+//! Sessions are titled after the first assistant turn from the first prompt's
+//! opening line (whitespace collapsed, cut to 48 characters); `/title <name>`
+//! renames the session. Control characters are stripped from titles when they
+//! are made and when they are loaded from a header, since the title is sent
+//! in a terminal escape sequence. The title is stored in the header's optional
+//! `title` field, shown as the terminal title (also on resume), and listed by
+//! `/sessions` next to each saved session's start time and file path. Session
+//! files with an unreadable header are listed as skipped.
+//!
+//! Every terminal run that reported token usage appends a `cost_summary` entry
+//! (tokens, run cost, running session cost in micro-USD). Replay skips these
+//! entries for model memory; resume seeds the status-line session total from
+//! the newest one on the replayed branch.
+//!
+//! Runs that fail, are cancelled, or need provider retries also append a
+//! `run_outcome` entry (terminal error, final HTTP status, and each failed
+//! request attempt). Replay skips it for model memory; resume shows it as
+//! transcript notes where the run ended.
+//!
 //! Persistence is event-driven (user submit / committed run events) only.
 //! There is no additional save-on-exit flush step.
 //!
@@ -73,11 +268,39 @@
 //! Deferred scope note for v1: no persistence reset markers are defined yet.
 //! `/clear` and `memory_reset` persistence semantics are intentionally deferred;
 //! `/clear` only affects in-memory state for the running process.
+//!
+//! `--headless` runs the same core without the TUI: `headless` serves
+//! line-delimited JSON-RPC 2.0 on stdin/stdout (`submit`, `cancel`,
+//! `set_tool_preview`, `approve_tool`, `state`, `shutdown`) and streams every
+//! `AppEvent` as an `event` notification. Session flags work as usual, and
+//! end of input stops the agent.
+//!
+//! `--acp` serves the Agent Client Protocol on the same transport so editors
+//! can drive the agent: one session per process, rooted at the workspace,
+//! with mutating tool calls routed through `session/request_permission`.
+//! Loading sessions, MCP servers and client-side file access are not
+//! supported.
+//!
+//! Terminal capabilities learned at startup (kitty keyboard support, cell
+//! size) are cached in `$XDG_CONFIG_HOME/tape_tui/capabilities` so later runs
+//! skip those probes. `--no-cap-cache` drops the current terminal's entry and
+//! probes again.
 
+pub mod acp;
 pub mod app;
+pub mod app_events;
+pub mod changes;
+pub mod checkpoints;
 pub mod commands;
+pub mod headless;
+pub mod pricing;
+pub mod project_instructions;
+pub mod prompts;
 pub mod provider;
 pub mod providers;
 pub mod runtime;
+pub mod stream_smoothing;
+pub mod subagent;
 pub mod tools;
 pub mod tui;
+pub mod workspace_index;
@@ -1,17 +1,29 @@
-use std::io;
+use std::io::{self, BufReader};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
+use coding_agent::acp::AcpServer;
+use coding_agent::app::TurnCheckpoints;
 use coding_agent::app::{system_instructions_from_env, App};
-use coding_agent::provider::RunMessage;
+use coding_agent::headless::{HeadlessServer, HeadlessTerminal};
+use coding_agent::provider::{RunMessage, RunProvider};
 use coding_agent::providers;
-use coding_agent::runtime::RuntimeController;
-use coding_agent::tui::AppComponent;
+use coding_agent::runtime::{replayed_run_notes, replayed_turn_checkpoints, RuntimeController};
+use coding_agent::tui::{
+    sanitize_output_from_env, transcript_memory_budget_from_env, transcript_width_from_env,
+    AppComponent, ChangeReviewPanel, PromptFormPanel,
+};
+use coding_agent::workspace_index::WorkspaceIndex;
 use session_store::{SessionSeed, SessionStore};
-use tape_tui::{prewarm_markdown_highlighting, ProcessTerminal, TUI};
+use tape_tui::runtime::tui::RuntimeHandle;
+use tape_tui::{prewarm_markdown_highlighting, CapabilityCache, ProcessTerminal, TUI};
 
-const USAGE: &str =
-    "Usage:\n  coding_agent\n  coding_agent --continue\n  coding_agent --session <session-filepath>";
+/// Quiet period before a window drag's resize reaches the transcript layout.
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(50);
+const WORKSPACE_RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+const USAGE: &str = "Usage:\n  coding_agent\n  coding_agent --continue\n  coding_agent --session <session-filepath>\n\nOptions:\n  --no-cap-cache  Probe terminal capabilities again instead of using the cache\n  --headless      Serve JSON-RPC on stdin/stdout instead of running the TUI\n  --acp           Serve the Agent Client Protocol on stdin/stdout";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum StartupMode {
@@ -24,6 +36,10 @@ struct StartupSession {
     persistence: StartupSessionPersistence,
     startup_session_id: String,
     replayed_messages: Vec<RunMessage>,
+    replayed_run_notes: Vec<(usize, String)>,
+    turn_checkpoints: Vec<TurnCheckpoints>,
+    session_cost_micros: Option<u64>,
+    session_title: Option<String>,
 }
 
 enum StartupSessionPersistence {
@@ -48,53 +64,119 @@ fn run() -> io::Result<()> {
         .name("markdown-highlight-prewarm".to_string())
         .spawn(prewarm_markdown_highlighting);
 
-    let startup_mode = parse_startup_mode(std::env::args().skip(1))?;
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let no_capability_cache = take_flag(&mut args, "--no-cap-cache");
+    let headless = take_flag(&mut args, "--headless");
+    let acp = take_flag(&mut args, "--acp");
+    if headless && acp {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot combine --headless with --acp\n{USAGE}"),
+        ));
+    }
+    let startup_mode = parse_startup_mode(args)?;
     let cwd = std::env::current_dir().map_err(io::Error::other)?;
     let startup = load_startup_session(&cwd, startup_mode).map_err(io::Error::other)?;
 
-    let system_instructions = system_instructions_from_env();
-    let mut app_state = App::with_system_instructions(Some(system_instructions));
+    let workspace_root = cwd.canonicalize().unwrap_or_else(|_| cwd.clone());
+    let system_instructions = system_instructions_from_env(&workspace_root);
+    let mut app_state = App::with_system_instructions(Some(system_instructions.text));
+    app_state.set_project_instruction_files(system_instructions.project_files);
     if !startup.replayed_messages.is_empty() {
         app_state.restore_conversation(startup.replayed_messages);
     }
-    let app = Arc::new(Mutex::new(app_state));
-
-    let terminal = ProcessTerminal::new();
-    let mut tui = TUI::new(terminal);
-    let runtime_handle = tui.runtime_handle();
+    if !startup.replayed_run_notes.is_empty() {
+        app_state.restore_system_notes(startup.replayed_run_notes);
+    }
+    app_state.restore_session_cost(startup.session_cost_micros);
+    app_state.restore_session_title(startup.session_title);
+    app_state.restore_turn_checkpoints(startup.turn_checkpoints);
+    app_state.set_workspace_root(workspace_root.clone());
 
     let provider = providers::provider_from_env_with_session_id(Some(&startup.startup_session_id))
         .map_err(io::Error::other)?;
     let provider_profile = provider.profile();
+    app_state.set_pricing_table(providers::pricing_table_from_env().map_err(io::Error::other)?);
+    app_state.set_sanitize_output(sanitize_output_from_env());
+    let app = Arc::new(Mutex::new(app_state));
 
-    let host = match startup.persistence {
-        StartupSessionPersistence::Deferred(seed) => {
-            RuntimeController::new_with_deferred_session_seed(
-                Arc::clone(&app),
-                runtime_handle,
-                provider,
-                seed,
-            )
+    if acp {
+        let mut runtime = TUI::new(HeadlessTerminal);
+        let host = controller_for_startup(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            provider,
+            startup.persistence,
+        );
+        let server = AcpServer::new(
+            Arc::clone(&app),
+            host,
+            startup.startup_session_id,
+            workspace_root,
+            io::stdout(),
+        );
+        runtime.start()?;
+        server.spawn_reader(BufReader::new(io::stdin()), runtime.runtime_handle())?;
+        while !lock_unpoisoned(&app).should_exit {
+            runtime.run_blocking_once();
+            server.flush_events();
         }
-        StartupSessionPersistence::Active(session_store) => {
-            RuntimeController::new_with_session_store(
-                Arc::clone(&app),
-                runtime_handle,
-                provider,
-                session_store,
-            )
+        return runtime.stop();
+    }
+
+    if headless {
+        let mut runtime = TUI::new(HeadlessTerminal);
+        let host = controller_for_startup(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            provider,
+            startup.persistence,
+        );
+        let server = HeadlessServer::new(Arc::clone(&app), host, io::stdout());
+        // Starting the runtime drops queued commands, so read only after it.
+        runtime.start()?;
+        server.spawn_reader(BufReader::new(io::stdin()), runtime.runtime_handle())?;
+        while !lock_unpoisoned(&app).should_exit {
+            runtime.run_blocking_once();
+            server.flush_events();
         }
-    };
-    let root_component = tui.register_component(AppComponent::new(
+        return runtime.stop();
+    }
+
+    let terminal = ProcessTerminal::new();
+    let mut tui = TUI::new(terminal);
+    let host = controller_for_startup(
         Arc::clone(&app),
-        Arc::clone(&host),
-        provider_profile,
-    ));
+        tui.runtime_handle(),
+        provider,
+        startup.persistence,
+    );
+    let workspace_index = WorkspaceIndex::spawn(workspace_root, WORKSPACE_RESCAN_INTERVAL);
+    let mut app_component =
+        AppComponent::new(Arc::clone(&app), Arc::clone(&host), provider_profile);
+    app_component.set_workspace_index(workspace_index);
+    app_component.set_transcript_memory_budget(transcript_memory_budget_from_env());
+    app_component.set_transcript_width(transcript_width_from_env());
+    let root_component = tui.register_component(app_component);
+    let change_review_component =
+        tui.register_component(ChangeReviewPanel::new(Arc::clone(&app), Arc::clone(&host)));
+    host.set_change_review_component(change_review_component);
+    let prompt_form_component =
+        tui.register_component(PromptFormPanel::new(Arc::clone(&app), Arc::clone(&host)));
+    host.set_prompt_form_component(prompt_form_component);
     tui.set_root(vec![root_component]);
     tui.set_focus(root_component);
     tui.set_low_latency_coalescing(false);
+    tui.set_resize_debounce(Some(RESIZE_DEBOUNCE));
+    tui.set_capability_cache(CapabilityCache::in_config_dir());
+    if no_capability_cache {
+        tui.reset_capability_cache();
+    }
 
     tui.start()?;
+    if let Some(title) = lock_unpoisoned(&app).session_title() {
+        tui.set_title(title);
+    }
 
     while !lock_unpoisoned(&app).should_exit {
         tui.run_blocking_once();
@@ -103,6 +185,22 @@ fn run() -> io::Result<()> {
     tui.stop()
 }
 
+fn controller_for_startup(
+    app: Arc<Mutex<App>>,
+    runtime_handle: RuntimeHandle,
+    provider: Arc<dyn RunProvider>,
+    persistence: StartupSessionPersistence,
+) -> Arc<RuntimeController> {
+    match persistence {
+        StartupSessionPersistence::Deferred(seed) => {
+            RuntimeController::new_with_deferred_session_seed(app, runtime_handle, provider, seed)
+        }
+        StartupSessionPersistence::Active(session_store) => {
+            RuntimeController::new_with_session_store(app, runtime_handle, provider, session_store)
+        }
+    }
+}
+
 fn format_cli_parse_error(error: &str) -> String {
     let (summary, usage) = match error.split_once("\nUsage:\n") {
         Some((summary, usage_tail)) => (summary.trim(), format!("Usage:\n{usage_tail}")),
@@ -116,6 +214,13 @@ fn format_cli_parse_error(error: &str) -> String {
     )
 }
 
+/// Removes every `flag` from `args`, returning whether it was present.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != before
+}
+
 fn parse_startup_mode(args: impl IntoIterator<Item = String>) -> io::Result<StartupMode> {
     let mut mode: Option<StartupMode> = None;
     let mut args = args.into_iter();
@@ -170,6 +275,10 @@ fn load_startup_session(cwd: &Path, startup_mode: StartupMode) -> Result<Startup
                 persistence: StartupSessionPersistence::Deferred(seed),
                 startup_session_id,
                 replayed_messages: Vec::new(),
+                replayed_run_notes: Vec::new(),
+                turn_checkpoints: Vec::new(),
+                session_cost_micros: None,
+                session_title: None,
             })
         }
         StartupMode::ContinueLatest => {
@@ -180,12 +289,30 @@ fn load_startup_session(cwd: &Path, startup_mode: StartupMode) -> Result<Startup
             let replayed_messages = session_store
                 .replay_leaf(None)
                 .map_err(|error| error.to_string())?;
+            let session_cost_micros = session_store
+                .replay_session_cost_micros(None)
+                .map_err(|error| error.to_string())?;
+            let replayed_run_notes = replayed_run_notes(
+                &session_store
+                    .replay_run_outcomes(None)
+                    .map_err(|error| error.to_string())?,
+            );
+            let turn_checkpoints = replayed_turn_checkpoints(
+                session_store
+                    .replay_turn_checkpoints(None)
+                    .map_err(|error| error.to_string())?,
+            );
             let startup_session_id = session_store.session_id().to_string();
+            let session_title = session_store.title().map(ToOwned::to_owned);
 
             Ok(StartupSession {
                 persistence: StartupSessionPersistence::Active(session_store),
                 startup_session_id,
                 replayed_messages,
+                replayed_run_notes,
+                turn_checkpoints,
+                session_cost_micros,
+                session_title,
             })
         }
         StartupMode::ContinuePath(path) => {
@@ -198,12 +325,30 @@ fn load_startup_session(cwd: &Path, startup_mode: StartupMode) -> Result<Startup
             let replayed_messages = session_store
                 .replay_leaf(None)
                 .map_err(|error| error.to_string())?;
+            let session_cost_micros = session_store
+                .replay_session_cost_micros(None)
+                .map_err(|error| error.to_string())?;
+            let replayed_run_notes = replayed_run_notes(
+                &session_store
+                    .replay_run_outcomes(None)
+                    .map_err(|error| error.to_string())?,
+            );
+            let turn_checkpoints = replayed_turn_checkpoints(
+                session_store
+                    .replay_turn_checkpoints(None)
+                    .map_err(|error| error.to_string())?,
+            );
             let startup_session_id = session_store.session_id().to_string();
+            let session_title = session_store.title().map(ToOwned::to_owned);
 
             Ok(StartupSession {
                 persistence: StartupSessionPersistence::Active(session_store),
                 startup_session_id,
                 replayed_messages,
+                replayed_run_notes,
+                turn_checkpoints,
+                session_cost_micros,
+                session_title,
             })
         }
     }
@@ -224,6 +369,21 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn take_flag_removes_flag_before_session_parsing() {
+        let mut args = vec![
+            "--no-cap-cache".to_string(),
+            "--continue".to_string(),
+            "--no-cap-cache".to_string(),
+        ];
+        assert!(take_flag(&mut args, "--no-cap-cache"));
+        assert_eq!(
+            parse_startup_mode(args.clone()).expect("continue mode"),
+            StartupMode::ContinueLatest
+        );
+        assert!(!take_flag(&mut args, "--no-cap-cache"));
+    }
+
     #[test]
     fn parse_startup_mode_rejects_unknown_flags() {
         let error = parse_startup_mode(["--bogus".to_string()])
@@ -296,11 +456,31 @@ mod tests {
                 },
             ))
             .expect("seed entry should append");
+        store
+            .append(SessionEntry::new(
+                "entry-00000000000000000002",
+                Some("entry-00000000000000000001"),
+                "2026-02-14T00:00:02Z",
+                SessionEntryKind::CostSummary {
+                    model_id: "gpt-test".to_string(),
+                    input_tokens: 10,
+                    cached_input_tokens: 0,
+                    output_tokens: 2,
+                    run_cost_micros: Some(30),
+                    session_cost_micros: Some(1_230),
+                },
+            ))
+            .expect("cost summary should append");
+        store
+            .set_title(Some("Say hello".to_string()))
+            .expect("title should persist");
 
         let startup = load_startup_session(cwd.path(), StartupMode::ContinueLatest)
             .expect("continue startup should load latest session");
 
         assert_eq!(startup.startup_session_id, expected_session_id);
+        assert_eq!(startup.session_cost_micros, Some(1_230));
+        assert_eq!(startup.session_title.as_deref(), Some("Say hello"));
         assert_eq!(
             startup.replayed_messages,
             vec![RunMessage::UserText {
@@ -318,6 +498,10 @@ mod tests {
             persistence,
             startup_session_id,
             replayed_messages,
+            replayed_run_notes,
+            turn_checkpoints,
+            session_cost_micros,
+            session_title,
         } = startup;
 
         let sessions_root = session_root(cwd.path());
@@ -326,6 +510,10 @@ mod tests {
             "default startup must not eagerly materialize session root"
         );
         assert!(replayed_messages.is_empty());
+        assert!(replayed_run_notes.is_empty());
+        assert!(turn_checkpoints.is_empty());
+        assert_eq!(session_cost_micros, None);
+        assert_eq!(session_title, None);
 
         match persistence {
             StartupSessionPersistence::Deferred(seed) => {
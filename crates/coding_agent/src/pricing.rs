@@ -0,0 +1,173 @@
+//! Per-model token pricing and spend accounting.
+//!
+//! Rates are configured in USD per million tokens and stored as integer
+//! nano-USD per token; computed costs are integer micro-USD so running totals
+//! never accumulate floating-point drift.
+
+use std::collections::BTreeMap;
+
+use crate::provider::TokenUsage;
+
+const NANOS_PER_MICRO: u128 = 1_000;
+
+/// Token rates for one model, in nano-USD per token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelPricing {
+    pub input_nanos_per_token: u64,
+    pub cached_input_nanos_per_token: u64,
+    pub output_nanos_per_token: u64,
+}
+
+impl ModelPricing {
+    /// Builds pricing from USD-per-million-token rates.
+    ///
+    /// Cached input falls back to the regular input rate when not provided.
+    pub fn from_usd_per_million(
+        input: f64,
+        cached_input: Option<f64>,
+        output: f64,
+    ) -> Result<Self, String> {
+        let input_nanos_per_token = usd_per_million_to_nanos_per_token(input, "input")?;
+        let cached_input_nanos_per_token = match cached_input {
+            Some(rate) => usd_per_million_to_nanos_per_token(rate, "cached_input")?,
+            None => input_nanos_per_token,
+        };
+        let output_nanos_per_token = usd_per_million_to_nanos_per_token(output, "output")?;
+
+        Ok(Self {
+            input_nanos_per_token,
+            cached_input_nanos_per_token,
+            output_nanos_per_token,
+        })
+    }
+
+    /// Returns the cost of `usage` in micro-USD, rounded to the nearest micro.
+    ///
+    /// `usage.input_tokens` includes cached tokens, which are billed at the
+    /// cached rate instead of the regular input rate.
+    pub fn cost_micros(&self, usage: &TokenUsage) -> u64 {
+        let cached_input = usage.cached_input_tokens.min(usage.input_tokens);
+        let uncached_input = usage.input_tokens - cached_input;
+
+        let nanos = u128::from(uncached_input) * u128::from(self.input_nanos_per_token)
+            + u128::from(cached_input) * u128::from(self.cached_input_nanos_per_token)
+            + u128::from(usage.output_tokens) * u128::from(self.output_nanos_per_token);
+
+        let micros = (nanos + NANOS_PER_MICRO / 2) / NANOS_PER_MICRO;
+        u64::try_from(micros).unwrap_or(u64::MAX)
+    }
+}
+
+/// Pricing keyed by model id; models without an entry are unpriced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PricingTable {
+    models: BTreeMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    pub fn new(models: BTreeMap<String, ModelPricing>) -> Self {
+        Self { models }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    pub fn model(&self, model_id: &str) -> Option<&ModelPricing> {
+        self.models.get(model_id)
+    }
+
+    /// Returns the cost of `usage` for `model_id`, or `None` when unpriced.
+    pub fn cost_micros(&self, model_id: &str, usage: &TokenUsage) -> Option<u64> {
+        self.model(model_id)
+            .map(|pricing| pricing.cost_micros(usage))
+    }
+}
+
+/// Formats a micro-USD amount for display, e.g. `$0.0042` or `$12.50`.
+pub fn format_cost_usd(micros: u64) -> String {
+    let dollars = micros as f64 / 1_000_000.0;
+    if micros < 1_000_000 {
+        format!("${dollars:.4}")
+    } else {
+        format!("${dollars:.2}")
+    }
+}
+
+fn usd_per_million_to_nanos_per_token(rate: f64, label: &str) -> Result<u64, String> {
+    if !rate.is_finite() || rate < 0.0 {
+        return Err(format!(
+            "{label} rate must be a finite, non-negative USD amount per million tokens"
+        ));
+    }
+
+    // USD per million tokens equals micro-USD per token; scale once more to nanos.
+    let nanos = (rate * 1_000.0).round();
+    if nanos > u64::MAX as f64 {
+        return Err(format!("{label} rate is too large"));
+    }
+
+    Ok(nanos as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input_tokens: u64, cached_input_tokens: u64, output_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens,
+            output_tokens,
+        }
+    }
+
+    #[test]
+    fn cost_bills_cached_input_at_cached_rate() {
+        let pricing = ModelPricing::from_usd_per_million(1.25, Some(0.125), 10.0)
+            .expect("rates should be valid");
+
+        // 800 uncached * 1250 + 200 cached * 125 + 100 out * 10000 nanos.
+        assert_eq!(pricing.cost_micros(&usage(1_000, 200, 100)), 2_025);
+    }
+
+    #[test]
+    fn cached_rate_defaults_to_input_rate() {
+        let pricing =
+            ModelPricing::from_usd_per_million(2.0, None, 0.0).expect("rates should be valid");
+
+        assert_eq!(pricing.cached_input_nanos_per_token, 2_000);
+        assert_eq!(
+            pricing.cost_micros(&usage(1_000_000, 500_000, 0)),
+            2_000_000
+        );
+    }
+
+    #[test]
+    fn rejects_negative_and_non_finite_rates() {
+        assert!(ModelPricing::from_usd_per_million(-1.0, None, 1.0).is_err());
+        assert!(ModelPricing::from_usd_per_million(1.0, Some(f64::NAN), 1.0).is_err());
+        assert!(ModelPricing::from_usd_per_million(1.0, None, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn table_returns_none_for_unpriced_models() {
+        let table = PricingTable::new(BTreeMap::from([(
+            "priced".to_string(),
+            ModelPricing::from_usd_per_million(1.0, None, 1.0).expect("rates should be valid"),
+        )]));
+
+        assert_eq!(
+            table.cost_micros("priced", &usage(1_000, 0, 1_000)),
+            Some(2_000)
+        );
+        assert_eq!(table.cost_micros("other", &usage(1_000, 0, 1_000)), None);
+    }
+
+    #[test]
+    fn format_cost_uses_four_decimals_below_one_dollar() {
+        assert_eq!(format_cost_usd(0), "$0.0000");
+        assert_eq!(format_cost_usd(4_200), "$0.0042");
+        assert_eq!(format_cost_usd(12_500_000), "$12.50");
+    }
+}
@@ -0,0 +1,176 @@
+//! Project instruction files prepended to the system instructions.
+//!
+//! `AGENTS.md` and `.agent/instructions.md` are read from the workspace root
+//! and each parent directory up to the enclosing git repository root (only the
+//! workspace root outside a repository). Files further out come first, so the
+//! most specific instructions are read last. Oversized files are truncated and
+//! files that no longer fit the overall budget are skipped, nearest first.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File names looked up in every searched directory, in load order.
+pub const PROJECT_INSTRUCTION_FILES: &[&str] = &["AGENTS.md", ".agent/instructions.md"];
+/// Largest number of bytes kept from a single file.
+pub const MAX_INSTRUCTION_FILE_BYTES: usize = 32 * 1024;
+/// Largest number of bytes kept across all files.
+pub const MAX_PROJECT_INSTRUCTION_BYTES: usize = 64 * 1024;
+
+/// One loaded instruction file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectInstructionFile {
+    pub path: PathBuf,
+    /// Path relative to the workspace root, e.g. `AGENTS.md` or `../AGENTS.md`.
+    pub label: String,
+    pub content: String,
+    pub truncated: bool,
+}
+
+/// Loads instruction files for `workspace_root`, outermost directory first.
+pub fn discover_project_instructions(workspace_root: &Path) -> Vec<ProjectInstructionFile> {
+    let mut remaining = MAX_PROJECT_INSTRUCTION_BYTES;
+    let mut files = Vec::new();
+    for (depth, directory) in search_directories(workspace_root).iter().enumerate() {
+        let mut directory_files = Vec::new();
+        for name in PROJECT_INSTRUCTION_FILES {
+            if remaining == 0 {
+                break;
+            }
+            let path = directory.join(name);
+            let Ok(raw) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let trimmed = raw.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let limit = MAX_INSTRUCTION_FILE_BYTES.min(remaining);
+            let content = truncate_at_char_boundary(trimmed, limit);
+            remaining -= content.len();
+            directory_files.push(ProjectInstructionFile {
+                label: format!("{}{name}", "../".repeat(depth)),
+                truncated: content.len() < trimmed.len(),
+                content: content.to_string(),
+                path,
+            });
+        }
+        files.push(directory_files);
+    }
+
+    files.into_iter().rev().flatten().collect()
+}
+
+/// Places `files` ahead of `base`, each under a heading naming its path.
+pub fn prepend_project_instructions(files: &[ProjectInstructionFile], base: &str) -> String {
+    let mut sections: Vec<String> = files
+        .iter()
+        .map(|file| {
+            let mut section = format!(
+                "# Project instructions ({})\n\n{}",
+                file.label, file.content
+            );
+            if file.truncated {
+                section.push_str("\n\n[truncated]");
+            }
+            section
+        })
+        .collect();
+    sections.push(base.to_string());
+    sections.join("\n\n")
+}
+
+/// The workspace root followed by its parents up to the git repository root.
+fn search_directories(workspace_root: &Path) -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    for directory in workspace_root.ancestors() {
+        directories.push(directory.to_path_buf());
+        if directory.join(".git").exists() {
+            return directories;
+        }
+    }
+    vec![workspace_root.to_path_buf()]
+}
+
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::{
+        discover_project_instructions, prepend_project_instructions, MAX_INSTRUCTION_FILE_BYTES,
+        MAX_PROJECT_INSTRUCTION_BYTES,
+    };
+
+    #[test]
+    fn files_load_from_repository_root_down_to_workspace() {
+        let repository = TempDir::new().expect("temp repository");
+        fs::create_dir(repository.path().join(".git")).expect("git dir");
+        fs::write(repository.path().join("AGENTS.md"), "root rules\n").expect("root file");
+        let workspace = repository.path().join("crates/app");
+        fs::create_dir_all(workspace.join(".agent")).expect("workspace dirs");
+        fs::write(workspace.join("AGENTS.md"), "crate rules").expect("crate file");
+        fs::write(workspace.join(".agent/instructions.md"), "  \n").expect("blank file");
+
+        let files = discover_project_instructions(&workspace);
+        let labels: Vec<&str> = files.iter().map(|file| file.label.as_str()).collect();
+        assert_eq!(labels, vec!["../../AGENTS.md", "AGENTS.md"]);
+        assert_eq!(files[0].content, "root rules");
+
+        assert_eq!(
+            prepend_project_instructions(&files, "base"),
+            "# Project instructions (../../AGENTS.md)\n\nroot rules\n\n\
+             # Project instructions (AGENTS.md)\n\ncrate rules\n\nbase"
+        );
+    }
+
+    #[test]
+    fn parents_are_not_searched_outside_a_repository() {
+        let parent = TempDir::new().expect("temp parent");
+        fs::write(parent.path().join("AGENTS.md"), "unrelated").expect("parent file");
+        let workspace = parent.path().join("workspace");
+        fs::create_dir_all(workspace.join(".agent")).expect("workspace dirs");
+        fs::write(workspace.join(".agent/instructions.md"), "local").expect("local file");
+
+        let files = discover_project_instructions(&workspace);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].label, ".agent/instructions.md");
+    }
+
+    #[test]
+    fn oversized_files_are_truncated_and_the_budget_favours_nearer_files() {
+        let repository = TempDir::new().expect("temp repository");
+        fs::create_dir(repository.path().join(".git")).expect("git dir");
+        let workspace = repository.path().join("a/b");
+        fs::create_dir_all(&workspace).expect("workspace dirs");
+        let large = "é".repeat(MAX_INSTRUCTION_FILE_BYTES);
+        for directory in [
+            workspace.clone(),
+            repository.path().join("a"),
+            repository.path().to_path_buf(),
+        ] {
+            fs::write(directory.join("AGENTS.md"), &large).expect("large file");
+        }
+
+        let files = discover_project_instructions(&workspace);
+        let labels: Vec<&str> = files.iter().map(|file| file.label.as_str()).collect();
+        assert_eq!(labels, vec!["../AGENTS.md", "AGENTS.md"]);
+        assert!(files.iter().all(|file| file.truncated));
+        assert_eq!(files[1].content.len(), MAX_INSTRUCTION_FILE_BYTES);
+        let total: usize = files.iter().map(|file| file.content.len()).sum();
+        assert!(total <= MAX_PROJECT_INSTRUCTION_BYTES);
+        assert!(prepend_project_instructions(&files, "base").contains("[truncated]\n\nbase"));
+    }
+}
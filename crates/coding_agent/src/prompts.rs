@@ -0,0 +1,368 @@
+//! Named prompt templates stored under `.agent/prompts/`.
+//!
+//! A template is a Markdown file whose `{{variable}}` placeholders are filled
+//! in before the text lands in the editor. `/prompt <name> [var=value ...]`
+//! fills variables given inline and collects the rest through a
+//! [`PromptForm`].
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Template directory, relative to the workspace root.
+pub const PROMPTS_DIR: &str = ".agent/prompts";
+const TEMPLATE_EXTENSION: &str = "md";
+
+/// A loaded template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub body: String,
+}
+
+impl PromptTemplate {
+    /// Placeholder names in order of first appearance, without duplicates.
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables: Vec<String> = Vec::new();
+        for (_, name) in placeholders(&self.body) {
+            if !variables.iter().any(|known| known == name) {
+                variables.push(name.to_string());
+            }
+        }
+        variables
+    }
+
+    /// Replaces every placeholder that has a value; others are kept verbatim.
+    pub fn expand(&self, values: &HashMap<String, String>) -> String {
+        let mut expanded = String::with_capacity(self.body.len());
+        let mut copied = 0;
+        for (range, name) in placeholders(&self.body) {
+            if let Some(value) = values.get(name) {
+                expanded.push_str(&self.body[copied..range.start]);
+                expanded.push_str(value);
+                copied = range.end;
+            }
+        }
+        expanded.push_str(&self.body[copied..]);
+        expanded.trim_end().to_string()
+    }
+}
+
+/// One variable the form still needs a value for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptField {
+    pub name: String,
+    pub value: String,
+}
+
+/// Values being collected for a template's missing variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptForm {
+    template: PromptTemplate,
+    provided: HashMap<String, String>,
+    fields: Vec<PromptField>,
+    selected: usize,
+}
+
+impl PromptForm {
+    /// Creates a form for the variables of `template` missing from `provided`;
+    /// `None` when nothing is missing.
+    pub fn new(template: PromptTemplate, provided: HashMap<String, String>) -> Option<Self> {
+        let fields: Vec<PromptField> = template
+            .variables()
+            .into_iter()
+            .filter(|name| !provided.contains_key(name))
+            .map(|name| PromptField {
+                name,
+                value: String::new(),
+            })
+            .collect();
+        if fields.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            template,
+            provided,
+            fields,
+            selected: 0,
+        })
+    }
+
+    pub fn template_name(&self) -> &str {
+        &self.template.name
+    }
+
+    pub fn fields(&self) -> &[PromptField] {
+        &self.fields
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects the field at `index`, clamped to the last field.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.fields.len() - 1);
+    }
+
+    pub fn is_last_selected(&self) -> bool {
+        self.selected + 1 == self.fields.len()
+    }
+
+    pub fn set_selected_value(&mut self, value: impl Into<String>) {
+        self.fields[self.selected].value = value.into();
+    }
+
+    /// Expands the template with inline and collected values.
+    pub fn expand(&self) -> String {
+        let mut values = self.provided.clone();
+        for field in &self.fields {
+            values.insert(field.name.clone(), field.value.clone());
+        }
+        self.template.expand(&values)
+    }
+}
+
+/// `/prompt` arguments: the template name and inline `var=value` pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptInvocation {
+    pub name: String,
+    pub values: HashMap<String, String>,
+}
+
+/// Parses `name [var=value ...]`; values may be wrapped in double quotes to
+/// include whitespace.
+pub fn parse_prompt_invocation(args: &str) -> Result<PromptInvocation, String> {
+    let mut tokens = split_arguments(args)?.into_iter();
+    let Some(name) = tokens.next() else {
+        return Err("Usage: /prompt <name> [variable=value ...]".to_string());
+    };
+
+    let mut values = HashMap::new();
+    for token in tokens {
+        let Some((variable, value)) = token.split_once('=') else {
+            return Err(format!("Expected variable=value, got '{token}'"));
+        };
+        if !is_variable_name(variable) {
+            return Err(format!("Invalid variable name '{variable}'"));
+        }
+        values.insert(variable.to_string(), value.to_string());
+    }
+
+    Ok(PromptInvocation { name, values })
+}
+
+pub fn prompts_dir(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(PROMPTS_DIR)
+}
+
+/// Template names available in `workspace_root`, sorted.
+pub fn list_prompt_templates(workspace_root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(prompts_dir(workspace_root)) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.extension().and_then(|extension| extension.to_str())
+                    == Some(TEMPLATE_EXTENSION)
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .filter(|name| is_template_name(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Reads `<workspace_root>/.agent/prompts/<name>.md`.
+pub fn load_prompt_template(workspace_root: &Path, name: &str) -> Result<PromptTemplate, String> {
+    if !is_template_name(name) {
+        return Err(format!(
+            "Invalid prompt template name '{name}'; use letters, digits, '-' and '_'"
+        ));
+    }
+
+    let path = prompts_dir(workspace_root).join(format!("{name}.{TEMPLATE_EXTENSION}"));
+    match fs::read_to_string(&path) {
+        Ok(body) => Ok(PromptTemplate {
+            name: name.to_string(),
+            body,
+        }),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            let available = list_prompt_templates(workspace_root);
+            Err(if available.is_empty() {
+                format!("Unknown prompt template '{name}'; no templates in {PROMPTS_DIR}")
+            } else {
+                format!(
+                    "Unknown prompt template '{name}'; available templates: {}",
+                    available.join(", ")
+                )
+            })
+        }
+        Err(error) => Err(format!("Failed to read {}: {error}", path.display())),
+    }
+}
+
+fn is_template_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || matches!(character, '-' | '_'))
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut characters = name.chars();
+    characters
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && characters.all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+/// Byte ranges and names of `{{ name }}` placeholders in `body`.
+fn placeholders(body: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut search_from = 0;
+    while let Some(open) = body[search_from..].find("{{") {
+        let start = search_from + open;
+        let Some(close) = body[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + close + 2;
+        let name = body[start + 2..end - 2].trim();
+        if is_variable_name(name) {
+            found.push((start..end, name));
+            search_from = end;
+        } else {
+            search_from = start + 1;
+        }
+    }
+    found
+}
+
+fn split_arguments(args: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut in_quotes = false;
+    for character in args.chars() {
+        match character {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_token = true;
+            }
+            character if character.is_whitespace() && !in_quotes => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            character => {
+                current.push(character);
+                in_token = true;
+            }
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quote in /prompt arguments".to_string());
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::{
+        list_prompt_templates, load_prompt_template, parse_prompt_invocation, PromptForm,
+        PromptTemplate, PROMPTS_DIR,
+    };
+
+    fn template(body: &str) -> PromptTemplate {
+        PromptTemplate {
+            name: "review".to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn variables_are_unique_in_order_and_expansion_keeps_unknown_placeholders() {
+        let template =
+            template("Review {{ path }} for {{focus}}.\n{{path}} again, {{not valid}} {{x\n");
+        assert_eq!(template.variables(), vec!["path", "focus"]);
+
+        let values = HashMap::from([("path".to_string(), "src/lib.rs".to_string())]);
+        assert_eq!(
+            template.expand(&values),
+            "Review src/lib.rs for {{focus}}.\nsrc/lib.rs again, {{not valid}} {{x"
+        );
+    }
+
+    #[test]
+    fn form_collects_only_missing_variables_and_expands_all_values() {
+        let provided = HashMap::from([("path".to_string(), "a.rs".to_string())]);
+        let mut form = PromptForm::new(
+            template("{{path}}: {{focus}} / {{depth}}"),
+            provided.clone(),
+        )
+        .expect("focus and depth are missing");
+        let names: Vec<&str> = form
+            .fields()
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["focus", "depth"]);
+
+        form.set_selected_value("errors");
+        form.select(7);
+        assert!(form.is_last_selected());
+        form.set_selected_value("shallow");
+        assert_eq!(form.expand(), "a.rs: errors / shallow");
+
+        assert_eq!(PromptForm::new(template("{{path}}"), provided), None);
+    }
+
+    #[test]
+    fn invocations_parse_quoted_values_and_reject_malformed_pairs() {
+        let invocation =
+            parse_prompt_invocation(r#"review path=src/lib.rs focus="error handling""#)
+                .expect("valid invocation");
+        assert_eq!(invocation.name, "review");
+        assert_eq!(invocation.values["path"], "src/lib.rs");
+        assert_eq!(invocation.values["focus"], "error handling");
+
+        assert!(parse_prompt_invocation("").is_err());
+        assert!(parse_prompt_invocation("review path").is_err());
+        assert!(parse_prompt_invocation("review 9x=1").is_err());
+        assert!(parse_prompt_invocation(r#"review focus="open"#).is_err());
+    }
+
+    #[test]
+    fn templates_load_from_the_workspace_prompts_directory() {
+        let workspace = TempDir::new().expect("temp workspace");
+        let dir = workspace.path().join(PROMPTS_DIR);
+        fs::create_dir_all(&dir).expect("prompts dir");
+        fs::write(dir.join("review.md"), "Review {{path}}\n").expect("template");
+        fs::write(dir.join("notes.txt"), "ignored").expect("other file");
+
+        assert_eq!(list_prompt_templates(workspace.path()), vec!["review"]);
+        let loaded = load_prompt_template(workspace.path(), "review").expect("template loads");
+        assert_eq!(loaded.body, "Review {{path}}\n");
+
+        let error = load_prompt_template(workspace.path(), "missing").expect_err("unknown");
+        assert_eq!(
+            error,
+            "Unknown prompt template 'missing'; available templates: review"
+        );
+        assert!(load_prompt_template(workspace.path(), "../secrets").is_err());
+    }
+}
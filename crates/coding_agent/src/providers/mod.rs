@@ -1,23 +1,31 @@
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use agent_provider_codex_api::{CodexApiProvider, CodexApiProviderConfig, CODEX_API_PROVIDER_ID};
+use agent_provider_codex_api::{
+    CodexApiProvider, CodexApiProviderConfig, ProxyConfig, TlsConfig, CODEX_API_PROVIDER_ID,
+};
 use agent_provider_mock::MOCK_PROVIDER_ID;
 use serde::Deserialize;
 
+use crate::pricing::{ModelPricing, PricingTable};
 use crate::provider::{ProviderInitError, RunProvider};
 
 /// Environment variable used to select a run provider implementation.
 pub const PROVIDER_ENV_VAR: &str = "CODING_AGENT_PROVIDER";
 /// Environment variable containing a path to codex-api JSON bootstrap configuration.
 pub const CODEX_CONFIG_PATH_ENV_VAR: &str = "CODING_AGENT_CODEX_CONFIG_PATH";
+/// Environment variable containing a path to a `MockProvider` scenario
+/// fixture (JSON or JSONL); unset keeps the built-in mock stream.
+pub const MOCK_SCENARIO_PATH_ENV_VAR: &str = "CODING_AGENT_MOCK_SCENARIO";
 /// Provider IDs currently supported by this binary.
 pub const SUPPORTED_PROVIDER_IDS: [&str; 2] = [MOCK_PROVIDER_ID, CODEX_API_PROVIDER_ID];
 
 const ACCOUNT_ID_CLAIM_PATH: &str = "https://api.openai.com/auth.chatgpt_account_id";
 
-pub use agent_provider_mock::MockProvider;
+pub use agent_provider_mock::{MockProvider, MockScenario, ScenarioStep, ToolResultExpectation};
 
 /// Resolves the configured run provider from explicit environment selection.
 pub fn provider_from_env() -> Result<Arc<dyn RunProvider>, ProviderInitError> {
@@ -59,7 +67,7 @@ pub fn provider_for_id_with_session_id(
     }
 
     match provider_id {
-        MOCK_PROVIDER_ID => Ok(Arc::new(MockProvider::default())),
+        MOCK_PROVIDER_ID => mock_provider_from_scenario_env(),
         CODEX_API_PROVIDER_ID => codex_api_provider_from_config_path_env(startup_session_id),
         unknown => Err(ProviderInitError::new(format!(
             "Unsupported provider '{unknown}'. Available providers: {}",
@@ -68,6 +76,29 @@ pub fn provider_for_id_with_session_id(
     }
 }
 
+fn mock_provider_from_scenario_env() -> Result<Arc<dyn RunProvider>, ProviderInitError> {
+    let Some(path) = std::env::var_os(MOCK_SCENARIO_PATH_ENV_VAR) else {
+        return Ok(Arc::new(MockProvider::default()));
+    };
+
+    let scenario = MockScenario::load(path).map_err(ProviderInitError::new)?;
+    Ok(Arc::new(MockProvider::with_scenario(scenario)))
+}
+
+/// Resolves the pricing table for the provider selected in the environment.
+///
+/// Only codex-api bootstrap config carries pricing; other providers and configs
+/// without a `pricing` object yield an empty (unpriced) table.
+pub fn pricing_table_from_env() -> Result<PricingTable, ProviderInitError> {
+    let provider_id = std::env::var(PROVIDER_ENV_VAR).unwrap_or_default();
+    if provider_id.trim() != CODEX_API_PROVIDER_ID {
+        return Ok(PricingTable::default());
+    }
+
+    let (_, config) = read_codex_bootstrap_config()?;
+    pricing_table_from_bootstrap(config.pricing)
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct CodexBootstrapConfig {
@@ -75,11 +106,49 @@ struct CodexBootstrapConfig {
     models: Vec<String>,
     #[serde(default)]
     timeout_sec: Option<u64>,
+    #[serde(default)]
+    proxy_url: Option<String>,
+    #[serde(default)]
+    root_certificate_paths: Vec<PathBuf>,
+    #[serde(default)]
+    use_built_in_roots: Option<bool>,
+    #[serde(default)]
+    pricing: BTreeMap<String, BootstrapModelPricing>,
+}
+
+/// Per-model rates in USD per million tokens.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BootstrapModelPricing {
+    input_per_million: f64,
+    #[serde(default)]
+    cached_input_per_million: Option<f64>,
+    output_per_million: f64,
 }
 
 fn codex_api_provider_from_config_path_env(
     startup_session_id: Option<&str>,
 ) -> Result<Arc<dyn RunProvider>, ProviderInitError> {
+    let (config_path, config) = read_codex_bootstrap_config()?;
+    let provider_config = codex_provider_config_from_bootstrap(config, startup_session_id)?;
+
+    CodexApiProvider::new(provider_config)
+        .map(|provider| Arc::new(provider) as Arc<dyn RunProvider>)
+        .map_err(|error| {
+            if error.message().contains("account id is required") {
+                ProviderInitError::new(format!(
+                    "Invalid codex-api bootstrap token: access_token must be a JWT containing claim '{ACCOUNT_ID_CLAIM_PATH}'"
+                ))
+            } else {
+                ProviderInitError::new(format!(
+                    "Failed to initialize codex-api provider from '{config_path}': {}",
+                    error.message()
+                ))
+            }
+        })
+}
+
+fn read_codex_bootstrap_config() -> Result<(String, CodexBootstrapConfig), ProviderInitError> {
     let config_path = std::env::var(CODEX_CONFIG_PATH_ENV_VAR).map_err(|_| {
         ProviderInitError::new(format!(
             "Missing codex-api bootstrap config path. Set {CODEX_CONFIG_PATH_ENV_VAR} to a readable JSON file"
@@ -105,22 +174,7 @@ fn codex_api_provider_from_config_path_env(
         ))
     })?;
 
-    let provider_config = codex_provider_config_from_bootstrap(config, startup_session_id)?;
-
-    CodexApiProvider::new(provider_config)
-        .map(|provider| Arc::new(provider) as Arc<dyn RunProvider>)
-        .map_err(|error| {
-            if error.message().contains("account id is required") {
-                ProviderInitError::new(format!(
-                    "Invalid codex-api bootstrap token: access_token must be a JWT containing claim '{ACCOUNT_ID_CLAIM_PATH}'"
-                ))
-            } else {
-                ProviderInitError::new(format!(
-                    "Failed to initialize codex-api provider from '{config_path}': {}",
-                    error.message()
-                ))
-            }
-        })
+    Ok((config_path.to_string(), config))
 }
 
 fn codex_provider_config_from_bootstrap(
@@ -140,6 +194,22 @@ fn codex_provider_config_from_bootstrap(
         provider_config = provider_config.with_timeout(Duration::from_secs(timeout_sec));
     }
 
+    if let Some(proxy_url) = config.proxy_url {
+        provider_config = provider_config
+            .with_proxy(ProxyConfig::Url(sanitize_nonempty(proxy_url, "proxy_url")?));
+    }
+
+    let tls = TlsConfig {
+        root_certificate_paths: config.root_certificate_paths,
+        use_built_in_roots: config.use_built_in_roots.unwrap_or(true),
+    };
+    if !tls.use_built_in_roots && tls.root_certificate_paths.is_empty() {
+        return Err(ProviderInitError::new(
+            "codex-api bootstrap field 'use_built_in_roots' can only be false when 'root_certificate_paths' is provided",
+        ));
+    }
+    provider_config = provider_config.with_tls(tls);
+
     if let Some(startup_session_id) = startup_session_id {
         provider_config = provider_config.with_session_id(startup_session_id);
     }
@@ -147,6 +217,34 @@ fn codex_provider_config_from_bootstrap(
     Ok(provider_config)
 }
 
+fn pricing_table_from_bootstrap(
+    pricing: BTreeMap<String, BootstrapModelPricing>,
+) -> Result<PricingTable, ProviderInitError> {
+    let mut models = BTreeMap::new();
+    for (model_id, rates) in pricing {
+        let model_id = model_id.trim();
+        if model_id.is_empty() {
+            return Err(ProviderInitError::new(
+                "codex-api bootstrap field 'pricing' must not contain empty model ids",
+            ));
+        }
+
+        let model_pricing = ModelPricing::from_usd_per_million(
+            rates.input_per_million,
+            rates.cached_input_per_million,
+            rates.output_per_million,
+        )
+        .map_err(|error| {
+            ProviderInitError::new(format!(
+                "codex-api bootstrap field 'pricing.{model_id}' is invalid: {error}"
+            ))
+        })?;
+        models.insert(model_id.to_string(), model_pricing);
+    }
+
+    Ok(PricingTable::new(models))
+}
+
 fn sanitize_nonempty(value: String, field_name: &str) -> Result<String, ProviderInitError> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -310,6 +408,56 @@ mod tests {
         assert_eq!(provider.profile().provider_id, MOCK_PROVIDER_ID);
     }
 
+    #[test]
+    fn mock_provider_plays_the_scenario_fixture_from_env() {
+        let _env_serialization = lock_unpoisoned(env_lock());
+        let fixture_dir = tempfile::tempdir().expect("tempdir");
+        let fixture_path = fixture_dir.path().join("scenario.jsonl");
+        fs::write(
+            &fixture_path,
+            r#"[{"type": "chunk", "text": "scripted"}, {"type": "fail", "error": "boom"}]"#,
+        )
+        .expect("fixture should be written");
+        let _scenario = EnvVarGuard::set(
+            MOCK_SCENARIO_PATH_ENV_VAR,
+            Some(fixture_path.to_str().expect("utf-8 path")),
+        );
+
+        let provider = provider_for_id(MOCK_PROVIDER_ID).expect("scenario should load");
+        let mut events = Vec::new();
+        provider
+            .run(
+                crate::provider::RunRequest {
+                    run_id: 1,
+                    messages: Vec::new(),
+                    instructions: String::new(),
+                    output_schema: None,
+                    allowed_tools: None,
+                    prompt_cache: None,
+                },
+                std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                &mut |call| {
+                    crate::provider::ToolResult::error(call.call_id, call.tool_name, "unused")
+                },
+                &mut |event| events.push(event),
+            )
+            .expect("scenario run should succeed");
+        assert_eq!(
+            events.last(),
+            Some(&crate::provider::RunEvent::Failed {
+                run_id: 1,
+                error: "boom".to_string(),
+            })
+        );
+
+        let _missing = EnvVarGuard::set(MOCK_SCENARIO_PATH_ENV_VAR, Some("/nonexistent/fixture"));
+        let error = provider_init_error(
+            provider_for_id(MOCK_PROVIDER_ID),
+            "missing fixture should fail",
+        );
+        assert!(error.message().contains("Failed to read mock scenario"));
+    }
+
     #[test]
     fn codex_bootstrap_requires_config_path_env() {
         let _env_serialization = lock_unpoisoned(env_lock());
@@ -508,6 +656,10 @@ mod tests {
             access_token: VALID_ACCOUNT_TOKEN.to_string(),
             models: vec!["gpt-5.3-codex".to_string()],
             timeout_sec: Some(120),
+            proxy_url: None,
+            root_certificate_paths: Vec::new(),
+            use_built_in_roots: None,
+            pricing: BTreeMap::new(),
         };
 
         let provider_config = codex_provider_config_from_bootstrap(config, Some("session-123"))
@@ -534,4 +686,90 @@ mod tests {
         );
         assert!(error.message().contains("unknown field `session_id`"));
     }
+
+    #[test]
+    fn pricing_table_is_empty_for_non_codex_providers() {
+        let _env_serialization = lock_unpoisoned(env_lock());
+        let _provider = EnvVarGuard::set(PROVIDER_ENV_VAR, Some(MOCK_PROVIDER_ID));
+
+        let pricing = pricing_table_from_env().expect("mock provider has no pricing");
+        assert!(pricing.is_empty());
+    }
+
+    #[test]
+    fn pricing_table_reads_codex_bootstrap_rates() {
+        let _env_serialization = lock_unpoisoned(env_lock());
+        let file = write_bootstrap_config(&format!(
+            "{{\n  \"access_token\": \"{VALID_ACCOUNT_TOKEN}\",\n  \"models\": [\"gpt-5.3-codex\"],\n  \"pricing\": {{\"gpt-5.3-codex\": {{\"input_per_million\": 1.25, \"cached_input_per_million\": 0.125, \"output_per_million\": 10}}}}\n}}"
+        ));
+        let _provider = EnvVarGuard::set(PROVIDER_ENV_VAR, Some(CODEX_API_PROVIDER_ID));
+        let _config = EnvVarGuard::set(
+            CODEX_CONFIG_PATH_ENV_VAR,
+            Some(file.path().to_str().expect("temp path must be utf-8")),
+        );
+
+        let pricing = pricing_table_from_env().expect("valid pricing should parse");
+        assert_eq!(
+            pricing.model("gpt-5.3-codex"),
+            Some(&ModelPricing {
+                input_nanos_per_token: 1_250,
+                cached_input_nanos_per_token: 125,
+                output_nanos_per_token: 10_000,
+            })
+        );
+        provider_from_env().expect("pricing must not affect provider bootstrap");
+    }
+
+    #[test]
+    fn pricing_table_rejects_negative_rates() {
+        let _env_serialization = lock_unpoisoned(env_lock());
+        let file = write_bootstrap_config(&format!(
+            "{{\n  \"access_token\": \"{VALID_ACCOUNT_TOKEN}\",\n  \"models\": [\"m\"],\n  \"pricing\": {{\"m\": {{\"input_per_million\": -1, \"output_per_million\": 1}}}}\n}}"
+        ));
+        let _provider = EnvVarGuard::set(PROVIDER_ENV_VAR, Some(CODEX_API_PROVIDER_ID));
+        let _config = EnvVarGuard::set(
+            CODEX_CONFIG_PATH_ENV_VAR,
+            Some(file.path().to_str().expect("temp path must be utf-8")),
+        );
+
+        let error = pricing_table_from_env().expect_err("negative rates should fail");
+        assert!(error.message().contains("'pricing.m' is invalid"));
+    }
+
+    #[test]
+    fn codex_bootstrap_maps_proxy_and_root_certificates() {
+        let config: CodexBootstrapConfig = serde_json::from_str(&format!(
+            "{{\n  \"access_token\": \"{VALID_ACCOUNT_TOKEN}\",\n  \"models\": [\"gpt-5.3-codex\"],\n  \"proxy_url\": \" http://proxy.corp:3128 \",\n  \"root_certificate_paths\": [\"/etc/corp/root.pem\"],\n  \"use_built_in_roots\": false\n}}"
+        ))
+        .expect("bootstrap JSON should parse");
+
+        let provider_config = codex_provider_config_from_bootstrap(config, None)
+            .expect("provider config should build");
+
+        assert_eq!(
+            provider_config.proxy,
+            ProxyConfig::Url("http://proxy.corp:3128".to_string())
+        );
+        assert_eq!(
+            provider_config.tls,
+            TlsConfig {
+                root_certificate_paths: vec![PathBuf::from("/etc/corp/root.pem")],
+                use_built_in_roots: false,
+            }
+        );
+    }
+
+    #[test]
+    fn codex_bootstrap_rejects_disabled_roots_without_bundles() {
+        let config: CodexBootstrapConfig = serde_json::from_str(&format!(
+            "{{\n  \"access_token\": \"{VALID_ACCOUNT_TOKEN}\",\n  \"models\": [\"gpt-5.3-codex\"],\n  \"use_built_in_roots\": false\n}}"
+        ))
+        .expect("bootstrap JSON should parse");
+
+        let error = match codex_provider_config_from_bootstrap(config, None) {
+            Ok(_) => panic!("disabling built-in roots without bundles should fail"),
+            Err(error) => error,
+        };
+        assert!(error.message().contains("'use_built_in_roots'"));
+    }
 }
@@ -1,24 +1,51 @@
+use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 
 use serde_json::Value;
-use session_store::{SessionEntry, SessionEntryKind, SessionSeed, SessionStore};
+use session_store::{
+    FailedAttempt, ReplayedRunOutcome, ReplayedTurnCheckpoints, RunOutcomeKind, SessionEntry,
+    SessionEntryKind, SessionSeed, SessionStore,
+};
 use tape_tui::runtime::tui::{
     Command, CustomCommand, CustomCommandCtx, CustomCommandError, RuntimeHandle,
 };
+use tape_tui::{
+    ComponentId, SurfaceAnchor, SurfaceHandle, SurfaceInputPolicy, SurfaceKind,
+    SurfaceLayoutOptions, SurfaceMargin, SurfaceOptions, SurfaceSizeValue,
+};
 
-use crate::app::{App, HostOps, Mode, RunId};
+use crate::app::{
+    retry_note, run_failed_note, sanitize_session_title, App, AttemptFailure, HostOps, Mode,
+    QueuedTurn, QueuedTurnId, RunId, SessionListing, SessionListings, ToolConfirmation,
+    TurnCheckpoints,
+};
+use crate::changes::FileSnapshot;
+use crate::checkpoints::CheckpointStore;
 use crate::provider::{
-    ProviderProfile, RunEvent, RunMessage, RunProvider, RunRequest, ToolCallRequest, ToolResult,
+    ModelInfo, PromptCacheHint, ProviderProfile, RunEvent, RunMessage, RunProvider, RunRequest,
+    ToolCallRequest, ToolResult,
+};
+use crate::subagent::{SubagentTask, SPAWN_AGENT_TOOL};
+use crate::tools::output::{CappedOutput, OutputLimits, TOOL_OUTPUT_LIMITS_ENV_VAR};
+use crate::tools::process::{BackgroundProcessInfo, ProcessTable};
+use crate::tools::sandbox::ALLOWED_PATHS_ENV_VAR;
+use crate::tools::schema::validate_arguments;
+use crate::tools::shell::PERSISTENT_SHELL_ENV_VAR;
+use crate::tools::{
+    BuiltinToolExecutor, Plan, PlanStep, PlanStepStatus, ToolCall, ToolExecutor, ToolOutput,
 };
-use crate::tools::{BuiltinToolExecutor, ToolCall, ToolExecutor, ToolOutput};
 
 struct ActiveRun {
     run_id: RunId,
     cancel: Arc<AtomicBool>,
+    interrupted: Arc<AtomicBool>,
     join_handle: Option<JoinHandle<()>>,
 }
 
@@ -55,7 +82,13 @@ impl SessionRecorder {
         )
     }
 
-    fn persist_committed_entries(&mut self, entries: &[RunMessage]) -> Result<(), String> {
+    /// Appends committed run messages; tool results carry their checkpoint
+    /// id from `checkpoint_ids` (keyed by call id), when one was recorded.
+    fn persist_committed_entries(
+        &mut self,
+        entries: &[RunMessage],
+        checkpoint_ids: &HashMap<String, String>,
+    ) -> Result<(), String> {
         for entry in entries {
             match entry {
                 RunMessage::AssistantText { text } => self.append_kind(
@@ -85,6 +118,7 @@ impl SessionRecorder {
                         tool_name: tool_name.clone(),
                         content: content.clone(),
                         is_error: *is_error,
+                        checkpoint_id: checkpoint_ids.get(call_id).cloned(),
                     },
                     "tool result",
                 )?,
@@ -95,6 +129,20 @@ impl SessionRecorder {
         Ok(())
     }
 
+    fn persist_cost_summary(&mut self, summary: SessionEntryKind) -> Result<(), String> {
+        self.append_kind(summary, "cost summary")
+    }
+
+    fn persist_run_outcome(&mut self, outcome: SessionEntryKind) -> Result<(), String> {
+        self.append_kind(outcome, "run outcome")
+    }
+
+    fn persist_title(&mut self, title: &str) -> Result<(), String> {
+        self.store
+            .set_title(Some(title.to_string()))
+            .map_err(|error| format!("{SESSION_PERSISTENCE_FATAL_ERROR_PREFIX} title: {error}"))
+    }
+
     fn append_kind(&mut self, kind: SessionEntryKind, description: &str) -> Result<(), String> {
         let entry_id = format!("entry-{:020}", self.next_entry_index);
         self.next_entry_index = self.next_entry_index.saturating_add(1);
@@ -111,6 +159,40 @@ impl SessionRecorder {
     }
 }
 
+/// FIFO of user turns submitted while a run was active.
+#[derive(Debug, Default)]
+struct RunQueue {
+    next_id: QueuedTurnId,
+    turns: VecDeque<QueuedTurn>,
+}
+
+impl RunQueue {
+    fn push(&mut self, prompt: String, front: bool) -> QueuedTurnId {
+        self.next_id = self.next_id.saturating_add(1);
+        let turn = QueuedTurn {
+            id: self.next_id,
+            prompt,
+        };
+
+        if front {
+            self.turns.push_front(turn);
+        } else {
+            self.turns.push_back(turn);
+        }
+
+        self.next_id
+    }
+
+    fn remove(&mut self, id: QueuedTurnId) -> bool {
+        let Some(index) = self.turns.iter().position(|turn| turn.id == id) else {
+            return false;
+        };
+
+        self.turns.remove(index);
+        true
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProfileSwitchResult {
     Updated(ProviderProfile),
@@ -131,6 +213,37 @@ enum RuntimeEvent {
         run_id: RunId,
         result: ToolResult,
     },
+    PlanUpdated {
+        run_id: RunId,
+        plan: Plan,
+    },
+    FilesTouched {
+        snapshots: Vec<FileSnapshot>,
+    },
+    CheckpointRecorded {
+        run_id: RunId,
+        checkpoint: Result<String, String>,
+    },
+    ToolOutputSaved {
+        run_id: RunId,
+        call_id: String,
+        path: PathBuf,
+    },
+    ToolConfirmationRequested(ToolConfirmation),
+    ModelsListed(Result<Vec<ModelInfo>, String>),
+    SubagentToolCallStarted {
+        run_id: RunId,
+        call_id: String,
+        step_call_id: String,
+        tool_name: String,
+        arguments: Value,
+    },
+    SubagentToolCallCompleted {
+        run_id: RunId,
+        call_id: String,
+        step_call_id: String,
+        is_error: bool,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -140,6 +253,16 @@ enum BuiltinDispatchTool {
     Edit,
     Write,
     ApplyPatch,
+    UpdatePlan,
+    GitStatus,
+    GitDiff,
+    GitCommit,
+    RestartShell,
+    BashBackground,
+    ProcessStatus,
+    ProcessKill,
+    ProcessLogs,
+    SpawnAgent,
 }
 
 #[derive(Debug)]
@@ -157,12 +280,68 @@ impl HostToolExecutor {
             }
         }
     }
+
+    fn take_file_snapshots(&mut self) -> Vec<FileSnapshot> {
+        match self {
+            Self::Ready(executor) => executor.take_file_snapshots(),
+            Self::Unavailable(_) => Vec::new(),
+        }
+    }
+
+    fn checkpoint_store(&self) -> Option<CheckpointStore> {
+        match self {
+            Self::Ready(executor) => Some(CheckpointStore::new(executor.workspace_root())),
+            Self::Unavailable(_) => None,
+        }
+    }
+
+    fn cap_output(
+        &self,
+        tool_name: &str,
+        run_id: RunId,
+        call_id: &str,
+        content: String,
+    ) -> CappedOutput {
+        match self {
+            Self::Ready(executor) => executor.cap_output(tool_name, run_id, call_id, content),
+            Self::Unavailable(_) => CappedOutput {
+                content,
+                full_output: None,
+            },
+        }
+    }
+
+    fn preview(&self, call: &ToolCall) -> Option<Result<String, String>> {
+        match self {
+            Self::Ready(executor) => executor.preview(call),
+            Self::Unavailable(_) => None,
+        }
+    }
+
+    fn process_table(&self) -> ProcessTable {
+        match self {
+            Self::Ready(executor) => executor.process_table(),
+            Self::Unavailable(_) => ProcessTable::default(),
+        }
+    }
+}
+
+/// Modal panel registered by the host, plus its surface while shown.
+#[derive(Default)]
+struct PanelSurface {
+    component_id: Option<ComponentId>,
+    handle: Option<SurfaceHandle>,
 }
 
 pub const POST_TERMINAL_TOOL_REJECTION_ERROR: &str =
     "Provider requested tool call after terminal run event";
 pub const SESSION_PERSISTENCE_FATAL_ERROR_PREFIX: &str = "Session persistence failed:";
 const RUN_EVENT_DRAIN_BATCH_SIZE: usize = 4;
+const CHANGE_REVIEW_WIDTH_PERCENT: f32 = 90.0;
+const CHANGE_REVIEW_HEIGHT_PERCENT: f32 = 80.0;
+const PROMPT_FORM_WIDTH_PERCENT: f32 = 70.0;
+const TOOL_CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const TOAST_DURATION: Duration = Duration::from_secs(2);
 
 pub struct RuntimeController {
     app: Arc<Mutex<App>>,
@@ -173,8 +352,27 @@ pub struct RuntimeController {
     provider: Arc<dyn RunProvider>,
     provider_id: String,
     tool_dispatch: HashMap<(String, String), BuiltinDispatchTool>,
+    /// `input_schema` of each tool the provider defines, by tool name; calls
+    /// are validated against it before they run.
+    tool_schemas: HashMap<String, Value>,
     host_tool_executor: Mutex<HostToolExecutor>,
     session_persistence: Mutex<SessionPersistenceState>,
+    run_queue: Mutex<RunQueue>,
+    change_review_surface: Mutex<PanelSurface>,
+    prompt_form_surface: Mutex<PanelSurface>,
+    tool_preview_enabled: AtomicBool,
+    tool_confirmations: Mutex<HashMap<String, mpsc::Sender<bool>>>,
+    /// Shared with the tool executor so listing never waits on a tool call.
+    background_processes: ProcessTable,
+    toast: Mutex<Option<(String, Instant)>>,
+    /// Attached to every run request; see [`RuntimeController::set_output_schema`].
+    output_schema: Mutex<Option<Value>>,
+    /// Instructions and messages of the last top-level run; the part of the
+    /// next run's prompt that matches them is marked cacheable.
+    last_run_prompt: Mutex<Option<(String, Vec<RunMessage>)>>,
+    /// Checkpoint ids by run and tool call id, until the run's entries are
+    /// persisted.
+    tool_checkpoints: Mutex<HashMap<(RunId, String), String>>,
 }
 
 impl RuntimeController {
@@ -233,6 +431,8 @@ impl RuntimeController {
         session_persistence: SessionPersistenceState,
     ) -> Arc<Self> {
         let provider_id = provider.profile().provider_id;
+        let host_tool_executor = build_default_host_tool_executor();
+        let background_processes = host_tool_executor.process_table();
 
         Arc::new(Self {
             app,
@@ -241,8 +441,23 @@ impl RuntimeController {
             next_run_id: AtomicU64::new(1),
             active_run: Mutex::new(None),
             tool_dispatch: build_tool_dispatch_table(&provider_id),
-            host_tool_executor: Mutex::new(build_default_host_tool_executor()),
+            tool_schemas: provider
+                .tool_definitions()
+                .into_iter()
+                .map(|definition| (definition.name, definition.input_schema))
+                .collect(),
+            host_tool_executor: Mutex::new(host_tool_executor),
             session_persistence: Mutex::new(session_persistence),
+            run_queue: Mutex::new(RunQueue::default()),
+            change_review_surface: Mutex::new(PanelSurface::default()),
+            prompt_form_surface: Mutex::new(PanelSurface::default()),
+            tool_preview_enabled: AtomicBool::new(false),
+            tool_confirmations: Mutex::new(HashMap::new()),
+            background_processes,
+            toast: Mutex::new(None),
+            output_schema: Mutex::new(None),
+            last_run_prompt: Mutex::new(None),
+            tool_checkpoints: Mutex::new(HashMap::new()),
             provider,
             provider_id,
         })
@@ -269,22 +484,46 @@ impl RuntimeController {
             &base_system_instructions,
             tool_prompting_instruction_appendix(),
         )?;
+        let prompt_cache = self.prompt_cache_hint(&instructions, &messages);
         let request = RunRequest {
             run_id,
             messages,
             instructions,
+            output_schema: lock_unpoisoned(&self.output_schema).clone(),
+            allowed_tools: None,
+            prompt_cache: Some(prompt_cache),
         };
         let join_handle = self.spawn_worker(request, Arc::clone(&cancel))?;
 
         *active_run = Some(ActiveRun {
             run_id,
             cancel,
+            interrupted: Arc::new(AtomicBool::new(false)),
             join_handle: Some(join_handle),
         });
 
         Ok(run_id)
     }
 
+    /// Marks the prompt prefix shared with the previous run as stable and
+    /// remembers this prompt for the next one. Changed instructions, a
+    /// cleared transcript or compaction shorten the prefix to what still
+    /// matches.
+    fn prompt_cache_hint(&self, instructions: &str, messages: &[RunMessage]) -> PromptCacheHint {
+        let mut last_run_prompt = lock_unpoisoned(&self.last_run_prompt);
+        let stable_prefix_len = match last_run_prompt.as_ref() {
+            Some((last_instructions, last_messages)) if last_instructions == instructions => {
+                PromptCacheHint::common_prefix_len(last_messages, messages)
+            }
+            _ => 0,
+        };
+        *last_run_prompt = Some((instructions.to_string(), messages.to_vec()));
+        PromptCacheHint {
+            stable_prefix_len,
+            ..PromptCacheHint::default()
+        }
+    }
+
     fn spawn_worker(
         self: &Arc<Self>,
         request: RunRequest,
@@ -369,14 +608,49 @@ impl RuntimeController {
             arguments: call.arguments.clone(),
         });
 
+        let result = self.execute_host_tool_call(run_id, cancel, call);
+        self.record_checkpoint(run_id, &call_id);
+        self.finish_tool_call(run_id, result)
+    }
+
+    /// Stores the files the finished call (including any sub-agent calls it
+    /// made) mutated, as they were before it ran, as one checkpoint.
+    fn record_checkpoint(self: &Arc<Self>, run_id: RunId, call_id: &str) {
+        let (snapshots, store) = {
+            let mut executor = lock_unpoisoned(&self.host_tool_executor);
+            (executor.take_file_snapshots(), executor.checkpoint_store())
+        };
+        if snapshots.is_empty() {
+            return;
+        }
+
+        if let Some(store) = store {
+            let checkpoint = store.create(&snapshots);
+            if let Ok(checkpoint_id) = &checkpoint {
+                lock_unpoisoned(&self.tool_checkpoints)
+                    .insert((run_id, call_id.to_string()), checkpoint_id.clone());
+            }
+            self.enqueue_runtime_event(RuntimeEvent::CheckpointRecorded { run_id, checkpoint });
+        }
+        self.enqueue_runtime_event(RuntimeEvent::FilesTouched { snapshots });
+    }
+
+    /// Runs one tool call for `run_id` without reporting it to the app; the
+    /// caller decides how the call shows up in the transcript.
+    fn execute_host_tool_call(
+        self: &Arc<Self>,
+        run_id: RunId,
+        cancel: &Arc<AtomicBool>,
+        call: ToolCallRequest,
+    ) -> ToolResult {
+        let call_id = call.call_id.clone();
+        let tool_name = call.tool_name.clone();
+
         if cancel.load(Ordering::SeqCst) {
-            return self.finish_tool_call(
-                run_id,
-                ToolResult::error(
-                    call_id,
-                    tool_name,
-                    "Run cancellation requested before host tool execution",
-                ),
+            return ToolResult::error(
+                call_id,
+                tool_name,
+                "Run cancellation requested before host tool execution",
             );
         }
 
@@ -387,14 +661,52 @@ impl RuntimeController {
                 self.provider_id
             );
 
-            return self.finish_tool_call(run_id, ToolResult::error(call_id, tool_name, error));
+            return ToolResult::error(call_id, tool_name, error);
         };
 
+        if let Some(schema) = self.tool_schemas.get(&tool_name) {
+            if let Err(error) = validate_arguments(schema, &call.arguments) {
+                let error = format!("Invalid arguments for tool '{tool_name}': {error}");
+                return ToolResult::error(call_id, tool_name, error);
+            }
+        }
+
+        if dispatch_tool == BuiltinDispatchTool::SpawnAgent {
+            let outcome = self
+                .parse_spawn_agent_call(&call)
+                .and_then(|task| self.run_subagent(run_id, cancel, &call_id, &task));
+            return match outcome {
+                Ok(reply) => {
+                    let reply = self.cap_tool_output(run_id, &call_id, &tool_name, reply);
+                    ToolResult::success(call_id, tool_name, reply)
+                }
+                Err(error) => ToolResult::error(call_id, tool_name, error),
+            };
+        }
+
         let tool_call = match parse_tool_call(&call, dispatch_tool) {
             Ok(tool_call) => tool_call,
-            Err(error) => {
-                return self.finish_tool_call(run_id, ToolResult::error(call_id, tool_name, error));
+            Err(error) => return ToolResult::error(call_id, tool_name, error),
+        };
+
+        if self.tool_preview_enabled.load(Ordering::SeqCst) {
+            let preview = lock_unpoisoned(&self.host_tool_executor).preview(&tool_call);
+            match preview {
+                None => {}
+                Some(Err(error)) => return ToolResult::error(call_id, tool_name, error),
+                Some(Ok(diff)) => {
+                    if let Err(error) =
+                        self.await_tool_confirmation(run_id, cancel, &call_id, &tool_name, diff)
+                    {
+                        return ToolResult::error(call_id, tool_name, error);
+                    }
+                }
             }
+        }
+
+        let updated_plan = match &tool_call {
+            ToolCall::UpdatePlan { plan } => Some(plan.clone()),
+            _ => None,
         };
 
         let tool_output = match catch_unwind(AssertUnwindSafe(|| {
@@ -405,13 +717,18 @@ impl RuntimeController {
             Err(_) => ToolOutput::fail("Host tool executor panicked".to_string()),
         };
 
+        if let Some(plan) = updated_plan.filter(|_| tool_output.ok) {
+            self.enqueue_runtime_event(RuntimeEvent::PlanUpdated { run_id, plan });
+        }
+
+        let content = self.cap_tool_output(run_id, &call_id, &tool_name, tool_output.content);
         let mut result = if tool_output.ok {
-            ToolResult::success(call_id.clone(), tool_name.clone(), tool_output.content)
+            ToolResult::success(call_id.clone(), tool_name.clone(), content)
         } else {
-            ToolResult::error(call_id.clone(), tool_name.clone(), tool_output.content)
+            ToolResult::error(call_id.clone(), tool_name.clone(), content)
         };
 
-        if cancel.load(Ordering::SeqCst) && !result.is_error {
+        if cancel.load(Ordering::SeqCst) && !result.is_error && !self.is_interrupted_run(run_id) {
             result = ToolResult::error(
                 call_id,
                 tool_name,
@@ -419,7 +736,193 @@ impl RuntimeController {
             );
         }
 
-        self.finish_tool_call(run_id, result)
+        result
+    }
+
+    /// Applies `tool_name`'s output cap to `content`, telling the app where
+    /// the full output was saved when it had to be cut.
+    fn cap_tool_output(
+        self: &Arc<Self>,
+        run_id: RunId,
+        call_id: &str,
+        tool_name: &str,
+        content: String,
+    ) -> String {
+        let capped = lock_unpoisoned(&self.host_tool_executor)
+            .cap_output(tool_name, run_id, call_id, content);
+        if let Some(path) = capped.full_output {
+            self.enqueue_runtime_event(RuntimeEvent::ToolOutputSaved {
+                run_id,
+                call_id: call_id.to_string(),
+                path,
+            });
+        }
+        capped.content
+    }
+
+    fn parse_spawn_agent_call(&self, call: &ToolCallRequest) -> Result<SubagentTask, String> {
+        let args = args_object(&call.tool_name, &call.arguments)?;
+        SubagentTask::new(
+            required_string_arg(args, &call.tool_name, "task")?,
+            optional_string_arg(args, &call.tool_name, "context")?,
+            optional_string_array_arg(args, &call.tool_name, "tools")?,
+            |tool| {
+                self.tool_dispatch
+                    .contains_key(&(self.provider_id.clone(), tool.to_string()))
+            },
+        )
+    }
+
+    /// Runs `task` as a child run on the same provider and returns its final
+    /// reply. The child shares the parent's cancel signal, and its token
+    /// usage is reported as the parent run's.
+    fn run_subagent(
+        self: &Arc<Self>,
+        run_id: RunId,
+        cancel: &Arc<AtomicBool>,
+        call_id: &str,
+        task: &SubagentTask,
+    ) -> Result<String, String> {
+        let request = RunRequest {
+            run_id: self.next_run_id.fetch_add(1, Ordering::SeqCst),
+            messages: task.messages(),
+            instructions: task.instructions(),
+            output_schema: None,
+            allowed_tools: Some(task.tools.clone()),
+            prompt_cache: None,
+        };
+        let reply = RefCell::new(String::new());
+        let mut terminal = None;
+
+        let outcome = {
+            let mut emit = |event: RunEvent| match event {
+                RunEvent::Chunk { text, .. } => reply.borrow_mut().push_str(&text),
+                RunEvent::Usage { usage, .. } => {
+                    self.enqueue_runtime_event(RuntimeEvent::Provider(RunEvent::Usage {
+                        run_id,
+                        usage,
+                    }))
+                }
+                event if event.is_terminal() => {
+                    terminal.get_or_insert(event);
+                }
+                _ => {}
+            };
+            let mut execute_tool = |step: ToolCallRequest| {
+                // Text streamed before a tool call is narration; the answer is
+                // what the child says after its last call.
+                reply.borrow_mut().clear();
+                self.dispatch_subagent_tool_call(run_id, cancel, call_id, task, step)
+            };
+            catch_unwind(AssertUnwindSafe(|| {
+                self.provider
+                    .run(request, Arc::clone(cancel), &mut execute_tool, &mut emit)
+            }))
+        };
+
+        match (outcome, terminal) {
+            (Err(_), _) => Err("Sub-agent provider panicked".to_string()),
+            (Ok(Err(error)), _) | (Ok(Ok(())), Some(RunEvent::Failed { error, .. })) => {
+                Err(format!("Sub-agent failed: {error}"))
+            }
+            (Ok(Ok(())), Some(RunEvent::Cancelled { .. })) => {
+                Err("Sub-agent run was cancelled".to_string())
+            }
+            (Ok(Ok(())), Some(_)) => {
+                let reply = reply.into_inner().trim().to_string();
+                if reply.is_empty() {
+                    Err("Sub-agent finished without a final answer".to_string())
+                } else {
+                    Ok(reply)
+                }
+            }
+            (Ok(Ok(())), None) => Err("Sub-agent exited without terminal event".to_string()),
+        }
+    }
+
+    /// Executes a sub-agent's tool call, reporting it under the parent
+    /// `spawn_agent` call instead of as a transcript card of its own.
+    fn dispatch_subagent_tool_call(
+        self: &Arc<Self>,
+        run_id: RunId,
+        cancel: &Arc<AtomicBool>,
+        call_id: &str,
+        task: &SubagentTask,
+        step: ToolCallRequest,
+    ) -> ToolResult {
+        let step_call_id = step.call_id.clone();
+        self.enqueue_runtime_event(RuntimeEvent::SubagentToolCallStarted {
+            run_id,
+            call_id: call_id.to_string(),
+            step_call_id: step_call_id.clone(),
+            tool_name: step.tool_name.clone(),
+            arguments: step.arguments.clone(),
+        });
+
+        let result = if task.allows(&step.tool_name) {
+            self.execute_host_tool_call(run_id, cancel, step)
+        } else {
+            let error = format!(
+                "Tool '{}' is not available to this sub-agent",
+                step.tool_name
+            );
+            ToolResult::error(step.call_id, step.tool_name, error)
+        };
+
+        self.enqueue_runtime_event(RuntimeEvent::SubagentToolCallCompleted {
+            run_id,
+            call_id: call_id.to_string(),
+            step_call_id,
+            is_error: result.is_error,
+        });
+        result
+    }
+
+    /// Blocks the run worker until the user approves or rejects a previewed
+    /// tool call. Cancellation wins over a pending decision.
+    fn await_tool_confirmation(
+        self: &Arc<Self>,
+        run_id: RunId,
+        cancel: &Arc<AtomicBool>,
+        call_id: &str,
+        tool_name: &str,
+        diff: String,
+    ) -> Result<(), String> {
+        let (sender, receiver) = mpsc::channel();
+        lock_unpoisoned(&self.tool_confirmations).insert(call_id.to_string(), sender);
+        self.enqueue_runtime_event(RuntimeEvent::ToolConfirmationRequested(ToolConfirmation {
+            run_id,
+            call_id: call_id.to_string(),
+            tool_name: tool_name.to_string(),
+            diff,
+        }));
+
+        let approved = loop {
+            if cancel.load(Ordering::SeqCst) {
+                break None;
+            }
+
+            match receiver.recv_timeout(TOOL_CONFIRMATION_POLL_INTERVAL) {
+                Ok(approved) => break Some(approved),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break Some(false),
+            }
+        };
+        lock_unpoisoned(&self.tool_confirmations).remove(call_id);
+
+        match approved {
+            Some(true) => Ok(()),
+            Some(false) => Err(format!(
+                "User rejected {tool_name} in preview mode; no files were changed"
+            )),
+            None if self.is_interrupted_run(run_id) => Err(
+                "Run interrupted while awaiting preview confirmation; no files were changed"
+                    .to_string(),
+            ),
+            None => {
+                Err("Run cancellation requested while awaiting preview confirmation".to_string())
+            }
+        }
     }
 
     fn finish_tool_call(self: &Arc<Self>, run_id: RunId, result: ToolResult) -> ToolResult {
@@ -456,7 +959,7 @@ impl RuntimeController {
         !pending_events.is_empty()
     }
 
-    fn drain_pending_run_events(&self, max_events: usize) -> usize {
+    fn drain_pending_run_events(self: &Arc<Self>, max_events: usize) -> usize {
         if max_events == 0 {
             return 0;
         }
@@ -486,7 +989,7 @@ impl RuntimeController {
     /// Use this in non-ticking environments (for example headless test
     /// harnesses or external callers that never call `RuntimeHandle::run_once`) to
     /// guarantee queued run and tool state is applied.
-    pub fn flush_pending_run_events(&self) -> usize {
+    pub fn flush_pending_run_events(self: &Arc<Self>) -> usize {
         let mut total_drained = 0usize;
 
         loop {
@@ -520,7 +1023,7 @@ impl RuntimeController {
         }
     }
 
-    fn apply_runtime_event(&self, event: RuntimeEvent) {
+    fn apply_runtime_event(self: &Arc<Self>, event: RuntimeEvent) {
         match event {
             RuntimeEvent::Provider(event) => self.apply_provider_run_event(event),
             RuntimeEvent::ToolCallStarted {
@@ -530,11 +1033,13 @@ impl RuntimeController {
                 arguments,
             } => {
                 let mut app = lock_unpoisoned(&self.app);
+                app.on_tool_call_started_at(run_id, &call_id, SystemTime::now());
                 app.on_tool_call_started(run_id, &call_id, &tool_name, &arguments);
             }
             RuntimeEvent::ToolCallCompleted { run_id, result } => {
                 let content = tool_result_content_as_text(&result.content);
                 let mut app = lock_unpoisoned(&self.app);
+                app.on_tool_call_finished_at(run_id, &result.call_id, SystemTime::now());
                 app.on_tool_call_finished(
                     run_id,
                     &result.tool_name,
@@ -544,50 +1049,227 @@ impl RuntimeController {
                     &content,
                 );
             }
+            RuntimeEvent::PlanUpdated { run_id, plan } => {
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_plan_updated(run_id, plan);
+            }
+            RuntimeEvent::FilesTouched { snapshots } => {
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_files_touched(snapshots);
+            }
+            RuntimeEvent::CheckpointRecorded { run_id, checkpoint } => {
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_checkpoint_recorded(run_id, checkpoint);
+            }
+            RuntimeEvent::ToolOutputSaved {
+                run_id,
+                call_id,
+                path,
+            } => {
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_tool_output_saved(run_id, &call_id, path);
+            }
+            RuntimeEvent::ToolConfirmationRequested(confirmation) => {
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_tool_confirmation_requested(confirmation);
+            }
+            RuntimeEvent::ModelsListed(models) => {
+                let selected_model_id = self.provider.profile().model_id;
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_models_listed(&selected_model_id, models);
+            }
+            RuntimeEvent::SubagentToolCallStarted {
+                run_id,
+                call_id,
+                step_call_id,
+                tool_name,
+                arguments,
+            } => {
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_subagent_tool_call_started(
+                    run_id,
+                    &call_id,
+                    &step_call_id,
+                    &tool_name,
+                    arguments,
+                );
+            }
+            RuntimeEvent::SubagentToolCallCompleted {
+                run_id,
+                call_id,
+                step_call_id,
+                is_error,
+            } => {
+                let mut app = lock_unpoisoned(&self.app);
+                app.on_subagent_tool_call_finished(run_id, &call_id, &step_call_id, is_error);
+            }
         }
     }
 
-    fn apply_provider_run_event(&self, event: RunEvent) {
+    fn apply_provider_run_event(self: &Arc<Self>, event: RunEvent) {
         let run_id = event.run_id();
         let terminal = event.is_terminal();
-        let persist_finished_entries = matches!(event, RunEvent::Finished { .. });
+        // Steering keeps partial output of interrupted runs, so any terminal
+        // event may commit entries.
+        let persist_terminal_entries = terminal;
         let mut committed_entries = Vec::new();
+        let mut run_outcome = None;
+        let mut cost_summary = None;
+        let terminal_outcome = match &event {
+            RunEvent::Finished { .. } => Some((RunOutcomeKind::Finished, None)),
+            RunEvent::Failed { error, .. } => Some((RunOutcomeKind::Failed, Some(error.clone()))),
+            RunEvent::Cancelled { .. } => Some((RunOutcomeKind::Cancelled, None)),
+            _ => None,
+        };
 
         {
             let mut app = lock_unpoisoned(&self.app);
-            let conversation_len_before = if persist_finished_entries {
+            let conversation_len_before = if persist_terminal_entries {
                 app.conversation_messages().len()
             } else {
                 0
             };
 
+            if terminal {
+                app.on_run_ended_at(run_id, SystemTime::now());
+            }
+
             match event {
-                RunEvent::Started { run_id } => app.on_run_started(run_id),
+                RunEvent::Started { run_id } => {
+                    app.on_run_started(run_id);
+                    app.on_run_profile(run_id, &self.provider.profile(), SystemTime::now());
+                }
                 RunEvent::Chunk { run_id, text } => app.on_run_chunk(run_id, &text),
+                RunEvent::Usage { run_id, usage } => app.on_run_usage(run_id, &usage),
+                RunEvent::AttemptFailed {
+                    run_id,
+                    attempt,
+                    status,
+                    error,
+                    will_retry,
+                } => app.on_run_attempt_failed(
+                    run_id,
+                    AttemptFailure {
+                        attempt,
+                        status,
+                        error,
+                        will_retry,
+                    },
+                ),
+                RunEvent::ToolCallStreaming {
+                    run_id,
+                    call_id,
+                    tool_name,
+                    arguments,
+                } => app.on_tool_call_streaming(run_id, &call_id, &tool_name, arguments),
                 RunEvent::Finished { run_id } => app.on_run_finished(run_id),
                 RunEvent::Failed { run_id, error } => app.on_run_failed(run_id, &error),
                 RunEvent::Cancelled { run_id } => app.on_run_cancelled(run_id),
             }
 
-            if persist_finished_entries {
+            if persist_terminal_entries {
                 committed_entries = committed_assistant_and_tool_entries(
                     app.conversation_messages(),
                     conversation_len_before,
                 );
             }
+
+            if let Some((outcome, error)) = terminal_outcome {
+                run_outcome = run_outcome_entry(&app, run_id, outcome, error);
+                cost_summary = cost_summary_entry(&app, run_id);
+            }
+        }
+
+        if persist_terminal_entries && !committed_entries.is_empty() {
+            if let Err(error) = self.persist_committed_entries(run_id, &committed_entries) {
+                self.handle_persistence_failure(error);
+            }
+        }
+
+        if persist_terminal_entries {
+            self.title_session_after_first_turn();
+        }
+
+        if let Some(outcome) = run_outcome {
+            if let Err(error) = self.persist_run_outcome(outcome) {
+                self.handle_persistence_failure(error);
+            }
         }
 
-        if persist_finished_entries && !committed_entries.is_empty() {
-            if let Err(error) = self.persist_committed_entries(&committed_entries) {
+        if let Some(summary) = cost_summary {
+            if let Err(error) = self.persist_cost_summary(summary) {
                 self.handle_persistence_failure(error);
             }
         }
 
         if terminal {
+            lock_unpoisoned(&self.tool_checkpoints)
+                .retain(|(checkpoint_run_id, _), _| *checkpoint_run_id != run_id);
             self.clear_active_run_if_matching(run_id);
+            self.dispatch_next_queued_turn();
+        }
+    }
+
+    /// Starts the oldest queued turn when no run is active. A turn that fails
+    /// to start has no terminal event to wait for, so the next one is tried
+    /// until a run starts or the queue is empty.
+    ///
+    /// Lock order is `app` then `active_run` and `run_queue`, matching
+    /// submissions that start or queue through `HostOps` while the app is
+    /// locked.
+    fn dispatch_next_queued_turn(self: &Arc<Self>) {
+        let mut app = lock_unpoisoned(&self.app);
+        loop {
+            if self.lock_active_run().is_some() || !app.can_start_queued_turn() {
+                return;
+            }
+
+            let Some(turn) = lock_unpoisoned(&self.run_queue).turns.pop_front() else {
+                return;
+            };
+
+            let mut host = Arc::clone(self);
+            app.start_queued_turn(turn.prompt, &mut host);
         }
     }
 
+    /// Returns user turns waiting to run, in dispatch order.
+    pub fn queued_turns(&self) -> Vec<QueuedTurn> {
+        lock_unpoisoned(&self.run_queue)
+            .turns
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns background processes started by `bash_background` that are
+    /// still running, oldest first.
+    pub fn background_processes(&self) -> Vec<BackgroundProcessInfo> {
+        self.background_processes.running()
+    }
+
+    /// Returns the current toast message until it expires.
+    pub fn toast(&self) -> Option<String> {
+        lock_unpoisoned(&self.toast)
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() < TOAST_DURATION)
+            .map(|(message, _)| message.clone())
+    }
+
+    /// Registers the component rendered by the `/changes` review surface.
+    pub fn set_change_review_component(&self, component_id: ComponentId) {
+        lock_unpoisoned(&self.change_review_surface).component_id = Some(component_id);
+    }
+
+    pub fn set_prompt_form_component(&self, component_id: ComponentId) {
+        lock_unpoisoned(&self.prompt_form_surface).component_id = Some(component_id);
+    }
+
+    /// Removes a queued turn before it starts.
+    pub fn cancel_queued_turn(&self, id: QueuedTurnId) -> bool {
+        lock_unpoisoned(&self.run_queue).remove(id)
+    }
+
     fn persist_submitted_user_turn(&self, messages: &[RunMessage]) -> Result<(), String> {
         let Some(RunMessage::UserText { text }) = messages.last() else {
             return Ok(());
@@ -605,19 +1287,72 @@ impl RuntimeController {
         session_recorder.persist_user_turn(text)
     }
 
-    fn persist_committed_entries(&self, entries: &[RunMessage]) -> Result<(), String> {
+    fn persist_committed_entries(
+        &self,
+        run_id: RunId,
+        entries: &[RunMessage],
+    ) -> Result<(), String> {
+        let checkpoint_ids: HashMap<String, String> = lock_unpoisoned(&self.tool_checkpoints)
+            .iter()
+            .filter(|((checkpoint_run_id, _), _)| *checkpoint_run_id == run_id)
+            .map(|((_, call_id), checkpoint_id)| (call_id.clone(), checkpoint_id.clone()))
+            .collect();
+
         let mut session_persistence = lock_unpoisoned(&self.session_persistence);
         let Some(session_recorder) = Self::ensure_active_recorder(&mut session_persistence)? else {
             return Ok(());
         };
 
-        session_recorder.persist_committed_entries(entries)
+        session_recorder.persist_committed_entries(entries, &checkpoint_ids)
     }
 
-    fn ensure_active_recorder(
-        session_persistence: &mut SessionPersistenceState,
-    ) -> Result<Option<&mut SessionRecorder>, String> {
-        if matches!(session_persistence, SessionPersistenceState::Deferred(_)) {
+    fn persist_cost_summary(&self, summary: SessionEntryKind) -> Result<(), String> {
+        let mut session_persistence = lock_unpoisoned(&self.session_persistence);
+        let Some(session_recorder) = Self::ensure_active_recorder(&mut session_persistence)? else {
+            return Ok(());
+        };
+
+        session_recorder.persist_cost_summary(summary)
+    }
+
+    fn persist_run_outcome(&self, outcome: SessionEntryKind) -> Result<(), String> {
+        let mut session_persistence = lock_unpoisoned(&self.session_persistence);
+        let Some(session_recorder) = Self::ensure_active_recorder(&mut session_persistence)? else {
+            return Ok(());
+        };
+
+        session_recorder.persist_run_outcome(outcome)
+    }
+
+    /// Names an untitled session from its first prompt once a reply is in,
+    /// persisting the title and showing it in the terminal title.
+    fn title_session_after_first_turn(&self) {
+        let Some(title) = lock_unpoisoned(&self.app).pending_session_title() else {
+            return;
+        };
+        if let Err(error) = self.persist_session_title(&title) {
+            self.handle_persistence_failure(error);
+            return;
+        }
+        lock_unpoisoned(&self.app).restore_session_title(Some(title));
+    }
+
+    fn persist_session_title(&self, title: &str) -> Result<(), String> {
+        let mut session_persistence = lock_unpoisoned(&self.session_persistence);
+        if let Some(session_recorder) = Self::ensure_active_recorder(&mut session_persistence)? {
+            session_recorder.persist_title(title)?;
+        }
+        drop(session_persistence);
+
+        self.runtime_handle
+            .dispatch(Command::SetTitle(title.to_string()));
+        Ok(())
+    }
+
+    fn ensure_active_recorder(
+        session_persistence: &mut SessionPersistenceState,
+    ) -> Result<Option<&mut SessionRecorder>, String> {
+        if matches!(session_persistence, SessionPersistenceState::Deferred(_)) {
             let seed = match session_persistence {
                 SessionPersistenceState::Deferred(seed) => seed.clone(),
                 _ => unreachable!("checked deferred state before materialization"),
@@ -638,7 +1373,7 @@ impl RuntimeController {
     fn handle_persistence_failure(&self, error: String) {
         {
             let mut app = lock_unpoisoned(&self.app);
-            app.mode = Mode::Error(error.clone());
+            app.set_mode(Mode::Error(error.clone()));
             app.push_system_message(format!("Session persistence failed: {error}"));
             app.should_exit = true;
         }
@@ -671,6 +1406,22 @@ impl RuntimeController {
         self.lock_active_run().as_ref().map(|active| active.run_id) == Some(run_id)
     }
 
+    fn is_interrupted_run(&self, run_id: RunId) -> bool {
+        self.lock_active_run().as_ref().is_some_and(|active| {
+            active.run_id == run_id && active.interrupted.load(Ordering::SeqCst)
+        })
+    }
+
+    fn interrupt_run_internal(&self, run_id: RunId) {
+        let active_run = self.lock_active_run();
+        if let Some(active_run) = active_run.as_ref() {
+            if active_run.run_id == run_id {
+                active_run.interrupted.store(true, Ordering::SeqCst);
+                active_run.cancel.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
     fn cancel_run_internal(&self, run_id: RunId) {
         let active_run = self.lock_active_run();
         if let Some(active_run) = active_run.as_ref() {
@@ -704,6 +1455,49 @@ impl RuntimeController {
         }
     }
 
+    pub fn set_model_profile(&self, model_id: &str) -> ProfileSwitchResult {
+        let active_run = self.lock_active_run();
+        if active_run.is_some() {
+            return ProfileSwitchResult::RejectedWhileRunning;
+        }
+
+        match self.provider.set_model(model_id) {
+            Ok(profile) => ProfileSwitchResult::Updated(profile),
+            Err(error) => ProfileSwitchResult::Failed(error),
+        }
+    }
+
+    pub fn set_thinking_profile(&self, level: &str) -> ProfileSwitchResult {
+        let active_run = self.lock_active_run();
+        if active_run.is_some() {
+            return ProfileSwitchResult::RejectedWhileRunning;
+        }
+
+        match self.provider.set_thinking_level(level) {
+            Ok(profile) => ProfileSwitchResult::Updated(profile),
+            Err(error) => ProfileSwitchResult::Failed(error),
+        }
+    }
+
+    /// Asks the provider to constrain final answers of runs started after this
+    /// call to JSON matching `schema`; `None` restores free-text replies.
+    ///
+    /// Intended for hosts embedding the runtime that parse the final assistant
+    /// message. Providers without structured output support ignore it.
+    pub fn set_output_schema(&self, schema: Option<Value>) {
+        *lock_unpoisoned(&self.output_schema) = schema;
+    }
+
+    /// Returns the provider's current model and thinking selection.
+    pub fn provider_profile(&self) -> ProviderProfile {
+        self.provider.profile()
+    }
+
+    /// Hands the terminal back to the shell and stops the process until `fg`.
+    pub fn suspend(&self) {
+        self.runtime_handle.dispatch(Command::Suspend);
+    }
+
     pub fn render_telemetry_snapshot(&self) -> tape_tui::runtime::RuntimeRenderTelemetrySnapshot {
         self.runtime_handle.render_telemetry_snapshot()
     }
@@ -738,6 +1532,14 @@ impl CustomCommand for DrainRunEventsCommand {
     }
 }
 
+impl Drop for RuntimeController {
+    fn drop(&mut self) {
+        // The executor is not the only table handle; do not leave processes
+        // running past the host.
+        self.background_processes.kill_all();
+    }
+}
+
 impl HostOps for Arc<RuntimeController> {
     fn start_run(
         &mut self,
@@ -751,6 +1553,187 @@ impl HostOps for Arc<RuntimeController> {
         self.cancel_run_internal(run_id);
     }
 
+    fn interrupt_run(&mut self, run_id: RunId) {
+        self.interrupt_run_internal(run_id);
+    }
+
+    fn queue_turn(&mut self, prompt: String) -> Result<QueuedTurnId, String> {
+        Ok(lock_unpoisoned(&self.run_queue).push(prompt, false))
+    }
+
+    fn queue_turn_next(&mut self, prompt: String) -> Result<QueuedTurnId, String> {
+        Ok(lock_unpoisoned(&self.run_queue).push(prompt, true))
+    }
+
+    fn cancel_queued_turn(&mut self, id: QueuedTurnId) -> bool {
+        RuntimeController::cancel_queued_turn(self, id)
+    }
+
+    fn queued_turns(&self) -> Vec<QueuedTurn> {
+        RuntimeController::queued_turns(self)
+    }
+
+    fn show_change_review(&mut self) -> Result<(), String> {
+        let mut surface = lock_unpoisoned(&self.change_review_surface);
+        let Some(component_id) = surface.component_id else {
+            return Err("No change review panel is registered".to_string());
+        };
+
+        if surface.handle.is_none() {
+            surface.handle = Some(self.runtime_handle.show_surface(
+                component_id,
+                Some(change_review_surface_options()),
+                false,
+            ));
+        }
+        Ok(())
+    }
+
+    fn hide_change_review(&mut self) {
+        if let Some(handle) = lock_unpoisoned(&self.change_review_surface).handle.take() {
+            handle.hide();
+        }
+    }
+
+    fn show_prompt_form(&mut self) -> Result<(), String> {
+        let mut surface = lock_unpoisoned(&self.prompt_form_surface);
+        let Some(component_id) = surface.component_id else {
+            return Err("No prompt form panel is registered".to_string());
+        };
+
+        if surface.handle.is_none() {
+            surface.handle = Some(self.runtime_handle.show_surface(
+                component_id,
+                Some(prompt_form_surface_options()),
+                false,
+            ));
+        }
+        Ok(())
+    }
+
+    fn hide_prompt_form(&mut self) {
+        if let Some(handle) = lock_unpoisoned(&self.prompt_form_surface).handle.take() {
+            handle.hide();
+        }
+    }
+
+    fn set_tool_preview(&mut self, enabled: bool) -> Result<(), String> {
+        self.tool_preview_enabled.store(enabled, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn resolve_tool_confirmation(&mut self, call_id: &str, approved: bool) {
+        if let Some(sender) = lock_unpoisoned(&self.tool_confirmations).remove(call_id) {
+            let _ = sender.send(approved);
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: &str) -> Result<(), String> {
+        self.runtime_handle
+            .dispatch(Command::SetClipboard(text.to_string()));
+        Ok(())
+    }
+
+    fn set_model(&mut self, model_id: &str) -> Result<ProviderProfile, String> {
+        match self.set_model_profile(model_id) {
+            ProfileSwitchResult::Updated(profile) => Ok(profile),
+            ProfileSwitchResult::RejectedWhileRunning => {
+                Err("Cannot switch model while a run is active".to_string())
+            }
+            ProfileSwitchResult::Failed(error) => Err(error),
+        }
+    }
+
+    fn set_thinking_level(&mut self, level: &str) -> Result<ProviderProfile, String> {
+        match self.set_thinking_profile(level) {
+            ProfileSwitchResult::Updated(profile) => Ok(profile),
+            ProfileSwitchResult::RejectedWhileRunning => {
+                Err("Cannot switch thinking mode while a run is active".to_string())
+            }
+            ProfileSwitchResult::Failed(error) => Err(error),
+        }
+    }
+
+    fn set_session_title(&mut self, title: &str) -> Result<(), String> {
+        if matches!(
+            *lock_unpoisoned(&self.session_persistence),
+            SessionPersistenceState::Disabled
+        ) {
+            return Err("Sessions are not persisted".to_string());
+        }
+        self.persist_session_title(title)
+    }
+
+    fn list_sessions(&mut self) -> Result<SessionListings, String> {
+        let (cwd, session_id) = match &*lock_unpoisoned(&self.session_persistence) {
+            SessionPersistenceState::Disabled => {
+                return Err("Sessions are not persisted".to_string())
+            }
+            SessionPersistenceState::Deferred(seed) => (seed.cwd.clone(), seed.session_id.clone()),
+            SessionPersistenceState::Active(recorder) => {
+                let header = recorder.store.header();
+                (
+                    std::path::PathBuf::from(&header.cwd),
+                    header.session_id.clone(),
+                )
+            }
+        };
+        let list = SessionStore::list_sessions(&cwd).map_err(|error| error.to_string())?;
+        let relative = |path: PathBuf| {
+            path.strip_prefix(&cwd)
+                .map(ToOwned::to_owned)
+                .unwrap_or(path)
+        };
+        Ok(SessionListings {
+            sessions: list
+                .sessions
+                .into_iter()
+                .map(|session| SessionListing {
+                    path: relative(session.path),
+                    created_at: session.created_at,
+                    title: session.title.as_deref().and_then(sanitize_session_title),
+                    current: session.session_id == session_id,
+                })
+                .collect(),
+            unreadable: list
+                .unreadable
+                .into_iter()
+                .map(|unreadable| {
+                    format!(
+                        "{}: {}",
+                        relative(unreadable.path).display(),
+                        unreadable.error
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    fn request_model_list(&mut self) -> Result<(), String> {
+        // The listing may hit the network; keep it off the UI thread.
+        let controller = Arc::clone(self);
+        thread::Builder::new()
+            .name("coding-agent-models".to_string())
+            .spawn(move || {
+                let models = controller.provider.list_models();
+                controller.enqueue_runtime_event(RuntimeEvent::ModelsListed(models));
+            })
+            .map(|_| ())
+            .map_err(|error| format!("Failed to spawn model listing: {error}"))
+    }
+
+    fn show_toast(&mut self, message: String) {
+        *lock_unpoisoned(&self.toast) = Some((message, Instant::now()));
+        self.runtime_handle.dispatch(Command::RequestRender);
+
+        // Re-render once the toast expires so it disappears even when idle.
+        let runtime_handle = self.runtime_handle.clone();
+        thread::spawn(move || {
+            thread::sleep(TOAST_DURATION);
+            runtime_handle.dispatch(Command::RequestRender);
+        });
+    }
+
     fn request_render(&mut self) {
         self.runtime_handle.dispatch(Command::RequestRender);
     }
@@ -775,7 +1758,37 @@ fn compose_system_instructions(base: &str, tool_appendix: &str) -> Result<String
 }
 
 fn tool_prompting_instruction_appendix() -> &'static str {
-    "Tool use policy:\n- Use tools for workspace actions: read, bash, edit, write, apply_patch.\n- Prefer the smallest safe tool for the step you are performing.\n- Never fabricate tool success; report explicit tool errors as-is.\n- Keep mutating changes minimal and verifiable.\n- Do not substitute fallback providers or hidden behavior when provider/tool errors occur."
+    "Tool use policy:\n- Use tools for workspace actions: read, bash, edit, write, apply_patch.\n- Use git_status, git_diff, and git_commit for version control instead of running git through bash.\n- Use bash_background for dev servers, watchers, and other long-running commands; check them with process_status and process_logs, and stop them with process_kill.\n- When bash output includes a cwd line, the shell is persistent: cd and exports carry over between calls, and restart_shell resets it.\n- For multi-step work, keep an update_plan checklist current as steps start and finish.\n- Use spawn_agent to delegate a self-contained subtask; the sub-agent sees only the task and context you pass, gets read-only tools unless you list others, and returns its final answer.\n- Prefer the smallest safe tool for the step you are performing.\n- Never fabricate tool success; report explicit tool errors as-is.\n- Keep mutating changes minimal and verifiable.\n- Do not substitute fallback providers or hidden behavior when provider/tool errors occur."
+}
+
+fn change_review_surface_options() -> SurfaceOptions {
+    SurfaceOptions {
+        kind: SurfaceKind::Modal,
+        input_policy: SurfaceInputPolicy::Capture,
+        layout: SurfaceLayoutOptions {
+            anchor: Some(SurfaceAnchor::Center),
+            margin: Some(SurfaceMargin::uniform(1)),
+            width: Some(SurfaceSizeValue::percent(CHANGE_REVIEW_WIDTH_PERCENT)),
+            max_height: Some(SurfaceSizeValue::percent(CHANGE_REVIEW_HEIGHT_PERCENT)),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn prompt_form_surface_options() -> SurfaceOptions {
+    SurfaceOptions {
+        kind: SurfaceKind::Modal,
+        input_policy: SurfaceInputPolicy::Capture,
+        layout: SurfaceLayoutOptions {
+            anchor: Some(SurfaceAnchor::Center),
+            margin: Some(SurfaceMargin::uniform(1)),
+            width: Some(SurfaceSizeValue::percent(PROMPT_FORM_WIDTH_PERCENT)),
+            max_height: Some(SurfaceSizeValue::percent(CHANGE_REVIEW_HEIGHT_PERCENT)),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
 }
 
 fn build_default_host_tool_executor() -> HostToolExecutor {
@@ -788,10 +1801,44 @@ fn build_default_host_tool_executor() -> HostToolExecutor {
         }
     };
 
-    match BuiltinToolExecutor::new(workspace_root) {
-        Ok(executor) => HostToolExecutor::Ready(executor),
-        Err(error) => HostToolExecutor::Unavailable(error),
+    let mut executor = match BuiltinToolExecutor::new(workspace_root) {
+        Ok(executor) => executor,
+        Err(error) => return HostToolExecutor::Unavailable(error),
+    };
+
+    if let Some(allowed_paths) = std::env::var_os(ALLOWED_PATHS_ENV_VAR) {
+        for path in std::env::split_paths(&allowed_paths) {
+            if path.as_os_str().is_empty() {
+                continue;
+            }
+
+            if let Err(error) = executor.allow_path(&path) {
+                return HostToolExecutor::Unavailable(format!(
+                    "Invalid {ALLOWED_PATHS_ENV_VAR}: {error}"
+                ));
+            }
+        }
+    }
+
+    if std::env::var(PERSISTENT_SHELL_ENV_VAR)
+        .is_ok_and(|value| matches!(value.trim(), "1" | "true"))
+    {
+        // Unsupported off Unix; `bash` then stays one-shot.
+        let _ = executor.set_persistent_shell(true);
+    }
+
+    if let Ok(spec) = std::env::var(TOOL_OUTPUT_LIMITS_ENV_VAR) {
+        match OutputLimits::parse(&spec) {
+            Ok(limits) => executor.set_output_limits(limits),
+            Err(error) => {
+                return HostToolExecutor::Unavailable(format!(
+                    "Invalid {TOOL_OUTPUT_LIMITS_ENV_VAR}: {error}"
+                ));
+            }
+        }
     }
+
+    HostToolExecutor::Ready(executor)
 }
 
 fn build_tool_dispatch_table(provider_id: &str) -> HashMap<(String, String), BuiltinDispatchTool> {
@@ -815,9 +1862,49 @@ fn build_tool_dispatch_table(provider_id: &str) -> HashMap<(String, String), Bui
             BuiltinDispatchTool::Write,
         ),
         (
-            (provider_id, "apply_patch".to_string()),
+            (provider_id.clone(), "apply_patch".to_string()),
             BuiltinDispatchTool::ApplyPatch,
         ),
+        (
+            (provider_id.clone(), "update_plan".to_string()),
+            BuiltinDispatchTool::UpdatePlan,
+        ),
+        (
+            (provider_id.clone(), "git_status".to_string()),
+            BuiltinDispatchTool::GitStatus,
+        ),
+        (
+            (provider_id.clone(), "git_diff".to_string()),
+            BuiltinDispatchTool::GitDiff,
+        ),
+        (
+            (provider_id.clone(), "git_commit".to_string()),
+            BuiltinDispatchTool::GitCommit,
+        ),
+        (
+            (provider_id.clone(), "restart_shell".to_string()),
+            BuiltinDispatchTool::RestartShell,
+        ),
+        (
+            (provider_id.clone(), "bash_background".to_string()),
+            BuiltinDispatchTool::BashBackground,
+        ),
+        (
+            (provider_id.clone(), "process_status".to_string()),
+            BuiltinDispatchTool::ProcessStatus,
+        ),
+        (
+            (provider_id.clone(), "process_kill".to_string()),
+            BuiltinDispatchTool::ProcessKill,
+        ),
+        (
+            (provider_id.clone(), "process_logs".to_string()),
+            BuiltinDispatchTool::ProcessLogs,
+        ),
+        (
+            (provider_id, SPAWN_AGENT_TOOL.to_string()),
+            BuiltinDispatchTool::SpawnAgent,
+        ),
     ])
 }
 
@@ -848,9 +1935,97 @@ fn parse_tool_call(
         BuiltinDispatchTool::ApplyPatch => Ok(ToolCall::ApplyPatch {
             input: required_string_arg(args, &call.tool_name, "input")?,
         }),
+        BuiltinDispatchTool::UpdatePlan => Ok(ToolCall::UpdatePlan {
+            plan: Plan {
+                explanation: optional_string_arg(args, &call.tool_name, "explanation")?,
+                steps: plan_steps_arg(args, &call.tool_name)?,
+            },
+        }),
+        BuiltinDispatchTool::GitStatus => Ok(ToolCall::GitStatus),
+        BuiltinDispatchTool::GitDiff => Ok(ToolCall::GitDiff {
+            path: optional_string_arg(args, &call.tool_name, "path")?,
+            staged: optional_bool_arg(args, &call.tool_name, "staged")?.unwrap_or(false),
+        }),
+        BuiltinDispatchTool::GitCommit => Ok(ToolCall::GitCommit {
+            message: required_string_arg(args, &call.tool_name, "message")?,
+            paths: optional_string_array_arg(args, &call.tool_name, "paths")?,
+            all: optional_bool_arg(args, &call.tool_name, "all")?.unwrap_or(false),
+        }),
+        BuiltinDispatchTool::RestartShell => Ok(ToolCall::RestartShell),
+        BuiltinDispatchTool::BashBackground => Ok(ToolCall::BashBackground {
+            command: required_string_arg(args, &call.tool_name, "command")?,
+            cwd: optional_string_arg(args, &call.tool_name, "cwd")?,
+        }),
+        BuiltinDispatchTool::ProcessStatus => Ok(ToolCall::ProcessStatus {
+            id: optional_string_arg(args, &call.tool_name, "id")?,
+        }),
+        BuiltinDispatchTool::ProcessKill => Ok(ToolCall::ProcessKill {
+            id: required_string_arg(args, &call.tool_name, "id")?,
+        }),
+        BuiltinDispatchTool::ProcessLogs => Ok(ToolCall::ProcessLogs {
+            id: required_string_arg(args, &call.tool_name, "id")?,
+            tail_lines: optional_u64_arg(args, &call.tool_name, "tail_lines")?,
+        }),
+        BuiltinDispatchTool::SpawnAgent => Err(format!(
+            "Tool '{}' runs a sub-agent, not a host tool executor call",
+            call.tool_name
+        )),
     }
 }
 
+fn plan_steps_arg(
+    args: &serde_json::Map<String, Value>,
+    tool_name: &str,
+) -> Result<Vec<PlanStep>, String> {
+    let steps = match args.get("plan") {
+        Some(Value::Array(steps)) => steps,
+        Some(_) => {
+            return Err(format!(
+                "Invalid arguments for tool '{tool_name}': field 'plan' must be an array"
+            ))
+        }
+        None => {
+            return Err(format!(
+                "Invalid arguments for tool '{tool_name}': missing required field 'plan'"
+            ))
+        }
+    };
+
+    steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| {
+            let position = index + 1;
+            let Some(step) = step.as_object() else {
+                return Err(format!(
+                    "Invalid arguments for tool '{tool_name}': plan item {position} must be a JSON object"
+                ));
+            };
+
+            let Some(text) = step.get("step").and_then(Value::as_str) else {
+                return Err(format!(
+                    "Invalid arguments for tool '{tool_name}': plan item {position} requires string field 'step'"
+                ));
+            };
+
+            let status = step
+                .get("status")
+                .and_then(Value::as_str)
+                .and_then(PlanStepStatus::parse)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid arguments for tool '{tool_name}': plan item {position} field 'status' must be one of pending, in_progress, completed"
+                    )
+                })?;
+
+            Ok(PlanStep {
+                step: text.to_string(),
+                status,
+            })
+        })
+        .collect()
+}
+
 fn args_object<'a>(
     tool_name: &str,
     args: &'a Value,
@@ -907,6 +2082,42 @@ fn optional_u64_arg(
     }
 }
 
+fn optional_bool_arg(
+    args: &serde_json::Map<String, Value>,
+    tool_name: &str,
+    field: &str,
+) -> Result<Option<bool>, String> {
+    match args.get(field) {
+        Some(Value::Bool(value)) => Ok(Some(*value)),
+        Some(_) => Err(format!(
+            "Invalid arguments for tool '{tool_name}': optional field '{field}' must be a boolean"
+        )),
+        None => Ok(None),
+    }
+}
+
+fn optional_string_array_arg(
+    args: &serde_json::Map<String, Value>,
+    tool_name: &str,
+    field: &str,
+) -> Result<Option<Vec<String>>, String> {
+    let invalid = || {
+        format!(
+            "Invalid arguments for tool '{tool_name}': optional field '{field}' must be an array of strings"
+        )
+    };
+
+    match args.get(field) {
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|value| value.as_str().map(ToString::to_string).ok_or_else(invalid))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some),
+        Some(_) => Err(invalid()),
+        None => Ok(None),
+    }
+}
+
 fn tool_result_content_as_text(value: &Value) -> String {
     match value {
         Value::String(content) => content.clone(),
@@ -934,6 +2145,109 @@ fn committed_assistant_and_tool_entries(
         .collect()
 }
 
+/// Builds the session cost summary for a terminal run that reported usage.
+fn cost_summary_entry(app: &App, run_id: RunId) -> Option<SessionEntryKind> {
+    let metadata = app.turn_metadata(run_id)?;
+    let usage = metadata.usage?;
+
+    Some(SessionEntryKind::CostSummary {
+        model_id: metadata.model_id.clone(),
+        input_tokens: usage.input_tokens,
+        cached_input_tokens: usage.cached_input_tokens,
+        output_tokens: usage.output_tokens,
+        run_cost_micros: metadata.cost_micros,
+        session_cost_micros: app.session_cost_micros(),
+    })
+}
+
+/// Builds the run outcome entry for a terminal run that failed, was
+/// cancelled, or needed retries.
+fn run_outcome_entry(
+    app: &App,
+    run_id: RunId,
+    outcome: RunOutcomeKind,
+    error: Option<String>,
+) -> Option<SessionEntryKind> {
+    let failed_attempts = app
+        .turn_metadata(run_id)
+        .map(|metadata| metadata.failed_attempts.as_slice())
+        .unwrap_or_default();
+    if outcome == RunOutcomeKind::Finished && failed_attempts.is_empty() {
+        return None;
+    }
+
+    let status = failed_attempts
+        .last()
+        .filter(|failure| outcome == RunOutcomeKind::Failed && !failure.will_retry)
+        .and_then(|failure| failure.status);
+    Some(SessionEntryKind::RunOutcome {
+        outcome,
+        error,
+        status,
+        failed_attempts: failed_attempts
+            .iter()
+            .map(|failure| FailedAttempt {
+                attempt: failure.attempt,
+                status: failure.status,
+                error: failure.error.clone(),
+                will_retry: failure.will_retry,
+            })
+            .collect(),
+    })
+}
+
+/// `/undo` turns for the checkpoints on a resumed branch, for
+/// [`App::restore_turn_checkpoints`].
+pub fn replayed_turn_checkpoints(turns: Vec<ReplayedTurnCheckpoints>) -> Vec<TurnCheckpoints> {
+    turns
+        .into_iter()
+        .map(|turn| TurnCheckpoints {
+            prompt: turn.prompt,
+            checkpoint_ids: turn.checkpoint_ids,
+            run_id: None,
+        })
+        .collect()
+}
+
+/// Transcript notes for the run outcomes on a resumed branch, positioned by
+/// replayed message index for [`App::restore_system_notes`].
+pub fn replayed_run_notes(outcomes: &[ReplayedRunOutcome]) -> Vec<(usize, String)> {
+    let mut notes = Vec::new();
+    for outcome in outcomes {
+        let at = outcome.message_index;
+        for failure in outcome
+            .failed_attempts
+            .iter()
+            .filter(|failure| failure.will_retry)
+        {
+            notes.push((
+                at,
+                retry_note(failure.attempt, failure.status, &failure.error),
+            ));
+        }
+        match outcome.outcome {
+            RunOutcomeKind::Finished => {}
+            RunOutcomeKind::Failed => {
+                let final_attempt = outcome
+                    .failed_attempts
+                    .last()
+                    .filter(|failure| !failure.will_retry)
+                    .map_or(1, |failure| failure.attempt);
+                notes.push((
+                    at,
+                    run_failed_note(
+                        outcome.error.as_deref().unwrap_or("unknown error"),
+                        outcome.status,
+                        final_attempt,
+                    ),
+                ));
+            }
+            RunOutcomeKind::Cancelled => notes.push((at, "Run cancelled".to_string())),
+        }
+    }
+    notes
+}
+
 fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
     match mutex.lock() {
         Ok(guard) => guard,
@@ -949,9 +2263,10 @@ mod tests {
     use tape_tui::{Terminal, TUI};
 
     use super::{
-        compose_system_instructions, lock_unpoisoned, tool_prompting_instruction_appendix, App,
-        Mode, ProviderProfile, RunEvent, RunMessage, RunProvider, RunRequest, RuntimeController,
-        RuntimeEvent, ToolCallRequest, ToolResult,
+        compose_system_instructions, lock_unpoisoned, parse_tool_call,
+        tool_prompting_instruction_appendix, App, BuiltinDispatchTool, Mode, Plan, PlanStep,
+        PlanStepStatus, ProviderProfile, RunEvent, RunMessage, RunProvider, RunRequest,
+        RuntimeController, RuntimeEvent, ToolCall, ToolCallRequest, ToolResult,
     };
 
     #[derive(Default)]
@@ -1023,6 +2338,88 @@ mod tests {
         assert!(!composed.trim().is_empty());
     }
 
+    #[test]
+    fn update_plan_arguments_parse_into_plan_steps() {
+        let call = ToolCallRequest {
+            call_id: "call-plan".to_string(),
+            tool_name: "update_plan".to_string(),
+            arguments: serde_json::json!({
+                "explanation": "Split the work",
+                "plan": [
+                    { "step": "Read code", "status": "completed" },
+                    { "step": "Write fix", "status": "in_progress" }
+                ]
+            }),
+        };
+
+        assert_eq!(
+            parse_tool_call(&call, BuiltinDispatchTool::UpdatePlan),
+            Ok(ToolCall::UpdatePlan {
+                plan: Plan {
+                    explanation: Some("Split the work".to_string()),
+                    steps: vec![
+                        PlanStep {
+                            step: "Read code".to_string(),
+                            status: PlanStepStatus::Completed,
+                        },
+                        PlanStep {
+                            step: "Write fix".to_string(),
+                            status: PlanStepStatus::InProgress,
+                        },
+                    ],
+                },
+            })
+        );
+
+        let bad_status = ToolCallRequest {
+            arguments: serde_json::json!({
+                "plan": [{ "step": "Ship", "status": "done" }]
+            }),
+            ..call
+        };
+        let error = parse_tool_call(&bad_status, BuiltinDispatchTool::UpdatePlan)
+            .expect_err("unknown status should be rejected");
+        assert!(error.contains("plan item 1 field 'status' must be one of"));
+    }
+
+    #[test]
+    fn git_tool_arguments_default_flags_and_validate_paths() {
+        let diff = ToolCallRequest {
+            call_id: "call-diff".to_string(),
+            tool_name: "git_diff".to_string(),
+            arguments: serde_json::json!({ "path": "src/lib.rs" }),
+        };
+        assert_eq!(
+            parse_tool_call(&diff, BuiltinDispatchTool::GitDiff),
+            Ok(ToolCall::GitDiff {
+                path: Some("src/lib.rs".to_string()),
+                staged: false,
+            })
+        );
+
+        let commit = ToolCallRequest {
+            call_id: "call-commit".to_string(),
+            tool_name: "git_commit".to_string(),
+            arguments: serde_json::json!({ "message": "Fix", "paths": ["a.rs"], "all": true }),
+        };
+        assert_eq!(
+            parse_tool_call(&commit, BuiltinDispatchTool::GitCommit),
+            Ok(ToolCall::GitCommit {
+                message: "Fix".to_string(),
+                paths: Some(vec!["a.rs".to_string()]),
+                all: true,
+            })
+        );
+
+        let bad_paths = ToolCallRequest {
+            arguments: serde_json::json!({ "message": "Fix", "paths": ["a.rs", 3] }),
+            ..commit
+        };
+        let error = parse_tool_call(&bad_paths, BuiltinDispatchTool::GitCommit)
+            .expect_err("non-string paths should be rejected");
+        assert!(error.contains("field 'paths' must be an array of strings"));
+    }
+
     #[test]
     fn composed_system_instructions_reject_empty_base() {
         let error = compose_system_instructions("   ", tool_prompting_instruction_appendix())
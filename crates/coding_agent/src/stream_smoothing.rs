@@ -0,0 +1,197 @@
+//! Word-granular release of streaming assistant text.
+//!
+//! Provider chunks split text at arbitrary points, so rendering each chunk
+//! as it lands shows half-written words that flicker into place. The
+//! transcript instead shows a prefix of the streamed text that grows at word
+//! boundaries: the release rate adapts to the backlog so output never lags
+//! far behind the stream, and a trailing partial word is held until it is
+//! completed or the stream stalls. Finished messages bypass the smoother and
+//! render in full.
+
+use std::time::{Duration, Instant};
+
+/// The release rate is the backlog divided by this window, so display lag
+/// decays with this time constant however fast text arrives.
+pub const SMOOTHING_CATCH_UP_WINDOW: Duration = Duration::from_millis(250);
+/// Release rate floor, in bytes per second, so small backlogs finish.
+pub const SMOOTHING_MIN_BYTES_PER_SECOND: f64 = 200.0;
+/// A trailing partial word is shown anyway once the stream has not grown
+/// for this long.
+pub const SMOOTHING_MAX_PARTIAL_WORD_HOLD: Duration = Duration::from_millis(200);
+
+/// Tracks how much of one streaming message is visible.
+#[derive(Debug, Clone, Default)]
+pub struct StreamSmoother {
+    state: Option<SmootherState>,
+}
+
+#[derive(Debug, Clone)]
+struct SmootherState {
+    key: u64,
+    released: usize,
+    received: usize,
+    last_release: Instant,
+    last_growth: Instant,
+}
+
+impl StreamSmoother {
+    /// Returns the byte length of `text` to show at `now` for the stream
+    /// identified by `key` (always a char boundary). A new key, or text that
+    /// was replaced so the released prefix no longer ends on a char boundary
+    /// (e.g. it shrank), starts over.
+    pub fn visible_len(&mut self, key: u64, text: &str, now: Instant) -> usize {
+        let state = match &mut self.state {
+            Some(state) if state.key == key && text.is_char_boundary(state.released) => state,
+            _ => self.state.insert(SmootherState {
+                key,
+                released: 0,
+                received: 0,
+                last_release: now,
+                last_growth: now,
+            }),
+        };
+
+        if text.len() != state.received {
+            state.received = text.len();
+            state.last_growth = now;
+        }
+
+        let backlog = text.len() - state.released;
+        if backlog == 0 {
+            state.last_release = now;
+            return state.released;
+        }
+
+        let elapsed = now
+            .saturating_duration_since(state.last_release)
+            .as_secs_f64();
+        let rate = (backlog as f64 / SMOOTHING_CATCH_UP_WINDOW.as_secs_f64())
+            .max(SMOOTHING_MIN_BYTES_PER_SECOND);
+        let budget = (rate * elapsed) as usize;
+
+        let target = if now.saturating_duration_since(state.last_growth)
+            >= SMOOTHING_MAX_PARTIAL_WORD_HOLD
+        {
+            text.len()
+        } else if budget == 0 {
+            state.released
+        } else {
+            word_boundary_at_or_after(text, state.released + budget)
+                .or_else(|| last_word_boundary(text))
+                .unwrap_or(0)
+                .max(state.released)
+        };
+
+        if target > state.released {
+            state.released = target;
+            state.last_release = now;
+        }
+        state.released
+    }
+
+    /// Forgets the tracked stream, e.g. once nothing is streaming.
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// First position from the char containing byte `from` onwards that follows
+/// a whitespace character.
+fn word_boundary_at_or_after(text: &str, from: usize) -> Option<usize> {
+    if from >= text.len() {
+        return None;
+    }
+    let mut start = from;
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    if text[..start].ends_with(char::is_whitespace) {
+        return Some(start);
+    }
+    text[start..]
+        .char_indices()
+        .find(|(_, character)| character.is_whitespace())
+        .map(|(offset, character)| start + offset + character.len_utf8())
+}
+
+/// Position after the last whitespace character of `text`.
+fn last_word_boundary(text: &str) -> Option<usize> {
+    text.char_indices()
+        .rev()
+        .find(|(_, character)| character.is_whitespace())
+        .map(|(offset, character)| offset + character.len_utf8())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{StreamSmoother, SMOOTHING_MAX_PARTIAL_WORD_HOLD};
+
+    fn at(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn text_is_released_at_word_boundaries_and_partial_words_are_held() {
+        let start = Instant::now();
+        let mut smoother = StreamSmoother::default();
+
+        assert_eq!(smoother.visible_len(1, "Hello wor", start), 0);
+        let visible = smoother.visible_len(1, "Hello wor", at(start, 30));
+        assert_eq!(visible, "Hello ".len());
+
+        // More time alone does not reveal the unfinished word...
+        assert_eq!(
+            smoother.visible_len(1, "Hello wor", at(start, 100)),
+            visible
+        );
+        // ...until it completes.
+        let text = "Hello world, and more";
+        let visible = smoother.visible_len(1, text, at(start, 120));
+        assert_eq!(&text[..visible], "Hello world, and ");
+    }
+
+    #[test]
+    fn release_rate_scales_with_the_backlog() {
+        let start = Instant::now();
+        let mut smoother = StreamSmoother::default();
+        let text = "word ".repeat(400);
+
+        smoother.visible_len(1, &text, start);
+        let early = smoother.visible_len(1, &text, at(start, 50));
+        assert!(text[..early].ends_with(' '));
+        // A 2000-byte backlog releases far more per tick than the rate floor.
+        assert_eq!(early, 400);
+
+        let later = smoother.visible_len(1, &text, at(start, 150));
+        assert!(later > early && later < text.len(), "released {later}");
+    }
+
+    #[test]
+    fn stalled_streams_show_the_partial_word_and_new_streams_start_over() {
+        let start = Instant::now();
+        let mut smoother = StreamSmoother::default();
+
+        smoother.visible_len(1, "Thinking abo", start);
+        let stalled = at(start, SMOOTHING_MAX_PARTIAL_WORD_HOLD.as_millis() as u64);
+        assert_eq!(smoother.visible_len(1, "Thinking abo", stalled), 12);
+
+        assert_eq!(smoother.visible_len(2, "Next reply", stalled), 0);
+        smoother.reset();
+        assert_eq!(smoother.visible_len(2, "Next reply", stalled), 0);
+        assert_eq!(
+            smoother.visible_len(2, "Naïve é", at(start, 260)),
+            "Naïve ".len()
+        );
+
+        // A replaced chunk that splits the released prefix's last char.
+        let stalled = at(
+            start,
+            260 + SMOOTHING_MAX_PARTIAL_WORD_HOLD.as_millis() as u64,
+        );
+        assert_eq!(smoother.visible_len(2, "Naïve é", stalled), "Naïve é".len());
+        let replaced = smoother.visible_len(2, "Naïve  é", stalled);
+        assert!("Naïve  é".is_char_boundary(replaced), "{replaced}");
+    }
+}
@@ -0,0 +1,183 @@
+//! `spawn_agent` delegation.
+//!
+//! The parent run hands a scoped task to a child run on the same provider.
+//! The child starts from a fresh context (the task plus optional context
+//! text), may only call its allowed tools, and its final reply becomes the
+//! `spawn_agent` tool result. Its tool calls are tracked as [`SubagentStep`]s
+//! so the parent's tool card can show them nested.
+
+use serde_json::Value;
+
+use crate::provider::RunMessage;
+
+pub const SPAWN_AGENT_TOOL: &str = "spawn_agent";
+/// Tools a sub-agent gets when the call does not list any.
+pub const DEFAULT_SUBAGENT_TOOLS: &[&str] = &["read", "git_status", "git_diff"];
+/// Tools a sub-agent never gets: delegation does not nest, and the plan
+/// belongs to the parent run.
+const SUBAGENT_EXCLUDED_TOOLS: &[&str] = &[SPAWN_AGENT_TOOL, "update_plan"];
+const SUBAGENT_INSTRUCTIONS: &str = "You are a sub-agent working on one task delegated by another coding agent. Stay within the task, use only the tools you are given, and never fabricate tool results. Finish with a concise final answer containing everything the delegating agent needs; it sees only that answer.";
+
+/// A validated `spawn_agent` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubagentTask {
+    pub task: String,
+    pub context: Option<String>,
+    pub tools: Vec<String>,
+}
+
+impl SubagentTask {
+    /// Builds a task, defaulting to [`DEFAULT_SUBAGENT_TOOLS`]. Requested tools
+    /// must satisfy `is_host_tool` and may not include `spawn_agent` or
+    /// `update_plan`.
+    pub fn new(
+        task: String,
+        context: Option<String>,
+        tools: Option<Vec<String>>,
+        is_host_tool: impl Fn(&str) -> bool,
+    ) -> Result<Self, String> {
+        let task = task.trim().to_string();
+        if task.is_empty() {
+            return Err("spawn_agent task cannot be empty".to_string());
+        }
+
+        let tools = match tools {
+            None => DEFAULT_SUBAGENT_TOOLS
+                .iter()
+                .map(|tool| (*tool).to_string())
+                .collect(),
+            Some(tools) => {
+                let mut unique: Vec<String> = Vec::with_capacity(tools.len());
+                for tool in tools {
+                    if SUBAGENT_EXCLUDED_TOOLS.contains(&tool.as_str()) {
+                        return Err(format!("spawn_agent cannot grant '{tool}' to a sub-agent"));
+                    }
+                    if !is_host_tool(&tool) {
+                        return Err(format!("spawn_agent tools lists unknown tool '{tool}'"));
+                    }
+                    if !unique.contains(&tool) {
+                        unique.push(tool);
+                    }
+                }
+                unique
+            }
+        };
+
+        Ok(Self {
+            task,
+            context: context
+                .map(|context| context.trim().to_string())
+                .filter(|context| !context.is_empty()),
+            tools,
+        })
+    }
+
+    /// The child's whole conversation: one user message with the context
+    /// (when given) ahead of the task.
+    pub fn messages(&self) -> Vec<RunMessage> {
+        let text = match &self.context {
+            Some(context) => format!("Context:\n{context}\n\nTask:\n{}", self.task),
+            None => self.task.clone(),
+        };
+        vec![RunMessage::UserText { text }]
+    }
+
+    pub fn instructions(&self) -> String {
+        let tools = if self.tools.is_empty() {
+            "none".to_string()
+        } else {
+            self.tools.join(", ")
+        };
+        format!("{SUBAGENT_INSTRUCTIONS}\n\nTools available to you: {tools}.")
+    }
+
+    pub fn allows(&self, tool_name: &str) -> bool {
+        self.tools.iter().any(|tool| tool == tool_name)
+    }
+}
+
+/// One tool call made by a sub-agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubagentStep {
+    pub call_id: String,
+    pub tool_name: String,
+    pub arguments: Value,
+    /// `Some(is_error)` once the call returned.
+    pub outcome: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SubagentTask, DEFAULT_SUBAGENT_TOOLS};
+    use crate::provider::RunMessage;
+
+    fn is_host_tool(name: &str) -> bool {
+        matches!(
+            name,
+            "read" | "bash" | "git_diff" | "spawn_agent" | "update_plan"
+        )
+    }
+
+    #[test]
+    fn tasks_default_to_read_only_tools_and_reject_nested_delegation() {
+        let task = SubagentTask::new(" find the bug ".to_string(), None, None, is_host_tool)
+            .expect("valid task");
+        assert_eq!(task.task, "find the bug");
+        assert_eq!(task.tools, DEFAULT_SUBAGENT_TOOLS);
+        assert!(task.allows("read"));
+        assert!(!task.allows("bash"));
+
+        let tools = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect());
+        let task = SubagentTask::new(
+            "run tests".to_string(),
+            None,
+            tools(&["bash", "read", "bash"]),
+            is_host_tool,
+        )
+        .expect("valid tools");
+        assert_eq!(task.tools, vec!["bash", "read"]);
+
+        for (requested, error) in [
+            (
+                &["spawn_agent"][..],
+                "spawn_agent cannot grant 'spawn_agent' to a sub-agent",
+            ),
+            (
+                &["update_plan"][..],
+                "spawn_agent cannot grant 'update_plan' to a sub-agent",
+            ),
+            (
+                &["teleport"][..],
+                "spawn_agent tools lists unknown tool 'teleport'",
+            ),
+        ] {
+            assert_eq!(
+                SubagentTask::new("x".to_string(), None, tools(requested), is_host_tool),
+                Err(error.to_string())
+            );
+        }
+        assert!(SubagentTask::new("  ".to_string(), None, None, is_host_tool).is_err());
+    }
+
+    #[test]
+    fn child_context_holds_only_the_delegated_task() {
+        let task = SubagentTask::new(
+            "Summarize src/lib.rs".to_string(),
+            Some("We are fixing the parser.".to_string()),
+            Some(Vec::new()),
+            is_host_tool,
+        )
+        .expect("valid task");
+
+        assert_eq!(
+            task.messages(),
+            vec![RunMessage::UserText {
+                text: "Context:\nWe are fixing the parser.\n\nTask:\nSummarize src/lib.rs"
+                    .to_string()
+            }]
+        );
+        assert!(task
+            .instructions()
+            .ends_with("Tools available to you: none."));
+    }
+}
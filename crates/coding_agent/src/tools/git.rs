@@ -0,0 +1,371 @@
+//! Git host tools: `git_status`, `git_diff`, and `git_commit`.
+//!
+//! Each tool runs the `git` CLI inside the workspace root and returns a JSON
+//! document as its output, so providers receive structured results instead of
+//! porcelain text. Paths in results are relative to the repository root, as
+//! reported by git.
+
+use std::path::Path;
+use std::process::Command;
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    format_exit_status, to_json_text, truncate_to_byte_limit, BuiltinToolExecutor, ToolOutput,
+};
+
+impl BuiltinToolExecutor {
+    pub(super) fn execute_git_status(&self) -> ToolOutput {
+        let stdout = match self.run_git(&["status", "--porcelain=v1", "--branch", "-z"]) {
+            Ok(stdout) => stdout,
+            Err(error) => return ToolOutput::fail(error),
+        };
+
+        ToolOutput::ok(to_json_text(parse_status_porcelain(&stdout)))
+    }
+
+    pub(super) fn execute_git_diff(&self, path: Option<String>, staged: bool) -> ToolOutput {
+        let pathspec = match path.as_deref().map(|path| self.git_pathspec(path)) {
+            Some(Ok(pathspec)) => Some(pathspec),
+            Some(Err(error)) => return ToolOutput::fail(error),
+            None => None,
+        };
+
+        let mut base_args = vec!["diff", "--no-color", "--no-ext-diff", "--no-renames"];
+        if staged {
+            base_args.push("--cached");
+        }
+
+        let mut numstat_args = base_args.clone();
+        numstat_args.extend(["--numstat", "-z"]);
+        let mut patch_args = base_args;
+        if let Some(pathspec) = pathspec.as_deref() {
+            numstat_args.extend(["--", pathspec]);
+            patch_args.extend(["--", pathspec]);
+        }
+
+        let numstat = match self.run_git(&numstat_args) {
+            Ok(stdout) => stdout,
+            Err(error) => return ToolOutput::fail(error),
+        };
+        let patch = match self.run_git(&patch_args) {
+            Ok(stdout) => stdout,
+            Err(error) => return ToolOutput::fail(error),
+        };
+
+        let truncated = patch.len() > self.bash_max_output_bytes;
+        let patch = truncate_to_byte_limit(patch, self.bash_max_output_bytes);
+
+        ToolOutput::ok(to_json_text(json!({
+            "staged": staged,
+            "path": path,
+            "files": parse_numstat(&numstat),
+            "diff": patch,
+            "truncated": truncated,
+        })))
+    }
+
+    pub(super) fn execute_git_commit(
+        &self,
+        message: String,
+        paths: Option<Vec<String>>,
+        all: bool,
+    ) -> ToolOutput {
+        if message.trim().is_empty() {
+            return ToolOutput::fail("git_commit requires a non-empty message".to_string());
+        }
+
+        if let Some(paths) = paths.as_ref().filter(|paths| !paths.is_empty()) {
+            let pathspecs = match paths
+                .iter()
+                .map(|path| self.git_pathspec(path))
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(pathspecs) => pathspecs,
+                Err(error) => return ToolOutput::fail(error),
+            };
+
+            let mut add_args = vec!["add", "--all", "--"];
+            add_args.extend(pathspecs.iter().map(String::as_str));
+            if let Err(error) = self.run_git(&add_args) {
+                return ToolOutput::fail(error);
+            }
+        }
+
+        let mut commit_args = vec!["commit", "--quiet"];
+        if all {
+            commit_args.push("--all");
+        }
+        commit_args.extend(["--message", message.as_str()]);
+        if let Err(error) = self.run_git(&commit_args) {
+            return ToolOutput::fail(error);
+        }
+
+        let commit = match self.run_git(&["rev-parse", "HEAD"]) {
+            Ok(stdout) => stdout.trim().to_string(),
+            Err(error) => return ToolOutput::fail(error),
+        };
+        let files = match self.run_git(&[
+            "diff-tree",
+            "--root",
+            "--no-commit-id",
+            "--no-renames",
+            "--name-status",
+            "-r",
+            "-z",
+            "HEAD",
+        ]) {
+            Ok(stdout) => parse_name_status(&stdout),
+            Err(error) => return ToolOutput::fail(error),
+        };
+
+        ToolOutput::ok(to_json_text(json!({
+            "commit": commit,
+            "summary": message.lines().next().unwrap_or_default().trim(),
+            "files": files,
+        })))
+    }
+
+    /// Runs `git` in the workspace root and returns stdout, or a failure
+    /// message carrying git's own diagnostics.
+    fn run_git(&self, args: &[&str]) -> Result<String, String> {
+        let subcommand = args.first().copied().unwrap_or("git");
+        let output = Command::new("git")
+            .arg("--no-pager")
+            .args(args)
+            .current_dir(self.sandbox.root())
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .env("GIT_OPTIONAL_LOCKS", "0")
+            .output()
+            .map_err(|error| format!("Failed to launch git {subcommand}: {error}"))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        if output.status.success() {
+            return Ok(stdout);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = [stderr.trim(), stdout.trim()]
+            .into_iter()
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Err(truncate_to_byte_limit(
+            format!(
+                "git {subcommand} failed ({}): {detail}",
+                format_exit_status(output.status)
+            ),
+            self.bash_max_output_bytes,
+        ))
+    }
+
+    /// Validates `path` against the workspace and returns it as a pathspec
+    /// relative to the workspace root when possible.
+    fn git_pathspec(&self, path: &str) -> Result<String, String> {
+        let resolved = self.sandbox.resolve_write(path)?;
+        let relative = resolved
+            .strip_prefix(self.sandbox.root())
+            .map_err(|_| format!("Path is outside the git workspace: {}", resolved.display()))?;
+
+        if relative == Path::new("") {
+            Ok(".".to_string())
+        } else {
+            Ok(relative.to_string_lossy().into_owned())
+        }
+    }
+}
+
+/// Parses `git status --porcelain=v1 --branch -z` output.
+fn parse_status_porcelain(output: &str) -> Value {
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+    let mut status = Map::new();
+    let mut entries = Vec::new();
+
+    let mut header = None;
+    let mut pending = records.next();
+    if let Some(line) = pending.and_then(|record| record.strip_prefix("## ")) {
+        header = Some(line);
+        pending = records.next();
+    }
+
+    let branch = header.map(parse_branch_header).unwrap_or_default();
+    status.insert("branch".to_string(), json!(branch.branch));
+    status.insert("upstream".to_string(), json!(branch.upstream));
+    status.insert("ahead".to_string(), json!(branch.ahead));
+    status.insert("behind".to_string(), json!(branch.behind));
+
+    while let Some(record) = pending {
+        pending = records.next();
+        if record.len() < 4 || !record.is_char_boundary(3) {
+            continue;
+        }
+
+        let mut codes = record[..2].chars();
+        let index = codes.next().unwrap_or(' ');
+        let worktree = codes.next().unwrap_or(' ');
+        let path = &record[3..];
+
+        let orig_path = if matches!(index, 'R' | 'C') {
+            let orig_path = pending;
+            pending = records.next();
+            orig_path
+        } else {
+            None
+        };
+
+        entries.push(json!({
+            "path": path,
+            "orig_path": orig_path,
+            "index": status_code_label(index),
+            "worktree": status_code_label(worktree),
+        }));
+    }
+
+    status.insert("clean".to_string(), json!(entries.is_empty()));
+    status.insert("entries".to_string(), Value::Array(entries));
+    Value::Object(status)
+}
+
+#[derive(Debug, Default)]
+struct BranchHeader {
+    branch: Option<String>,
+    upstream: Option<String>,
+    ahead: u64,
+    behind: u64,
+}
+
+/// Parses the `## ...` header, e.g. `main...origin/main [ahead 1, behind 2]`.
+fn parse_branch_header(header: &str) -> BranchHeader {
+    if let Some(branch) = header.strip_prefix("No commits yet on ") {
+        return BranchHeader {
+            branch: Some(branch.to_string()),
+            ..BranchHeader::default()
+        };
+    }
+
+    if header.starts_with("HEAD (no branch)") {
+        return BranchHeader::default();
+    }
+
+    let (refs, tracking) = match header.split_once(" [") {
+        Some((refs, tracking)) => (refs, tracking.trim_end_matches(']')),
+        None => (header, ""),
+    };
+    let (branch, upstream) = match refs.split_once("...") {
+        Some((branch, upstream)) => (branch, Some(upstream.to_string())),
+        None => (refs, None),
+    };
+
+    let mut parsed = BranchHeader {
+        branch: Some(branch.to_string()),
+        upstream,
+        ..BranchHeader::default()
+    };
+    for part in tracking.split(", ") {
+        if let Some(count) = part.strip_prefix("ahead ") {
+            parsed.ahead = count.parse().unwrap_or_default();
+        } else if let Some(count) = part.strip_prefix("behind ") {
+            parsed.behind = count.parse().unwrap_or_default();
+        }
+    }
+
+    parsed
+}
+
+fn status_code_label(code: char) -> &'static str {
+    match code {
+        'M' => "modified",
+        'T' => "type_changed",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'U' => "unmerged",
+        '?' => "untracked",
+        '!' => "ignored",
+        _ => "unmodified",
+    }
+}
+
+/// Parses `git diff --numstat -z` output; binary files report `null` counts.
+fn parse_numstat(output: &str) -> Vec<Value> {
+    output
+        .split('\0')
+        .filter_map(|record| {
+            let mut fields = record.splitn(3, '\t');
+            let additions = fields.next()?;
+            let deletions = fields.next()?;
+            let path = fields.next()?;
+
+            Some(json!({
+                "path": path,
+                "additions": additions.parse::<u64>().ok(),
+                "deletions": deletions.parse::<u64>().ok(),
+            }))
+        })
+        .collect()
+}
+
+/// Parses `git diff-tree --name-status -z` output into `{status, path}` pairs.
+fn parse_name_status(output: &str) -> Vec<Value> {
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+    let mut files = Vec::new();
+
+    while let (Some(code), Some(path)) = (records.next(), records.next()) {
+        let status = code.chars().next().map_or("unmodified", status_code_label);
+        files.push(json!({ "path": path, "status": status }));
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_porcelain_parses_branch_tracking_and_renames() {
+        let output =
+            "## main...origin/main [ahead 2, behind 1]\0R  new.rs\0old.rs\0 M src/lib.rs\0?? notes.txt\0";
+
+        let status = parse_status_porcelain(output);
+
+        assert_eq!(status["branch"], "main");
+        assert_eq!(status["upstream"], "origin/main");
+        assert_eq!(status["ahead"], 2);
+        assert_eq!(status["behind"], 1);
+        assert_eq!(status["clean"], false);
+        assert_eq!(
+            status["entries"],
+            json!([
+                { "path": "new.rs", "orig_path": "old.rs", "index": "renamed", "worktree": "unmodified" },
+                { "path": "src/lib.rs", "orig_path": null, "index": "unmodified", "worktree": "modified" },
+                { "path": "notes.txt", "orig_path": null, "index": "untracked", "worktree": "untracked" },
+            ])
+        );
+    }
+
+    #[test]
+    fn branch_header_handles_unborn_and_detached_heads() {
+        let unborn = parse_branch_header("No commits yet on trunk");
+        assert_eq!(unborn.branch.as_deref(), Some("trunk"));
+        assert_eq!(unborn.upstream, None);
+
+        let detached = parse_branch_header("HEAD (no branch)");
+        assert_eq!(detached.branch, None);
+    }
+
+    #[test]
+    fn numstat_reports_binary_files_without_counts() {
+        let files = parse_numstat("3\t1\tsrc/main.rs\0-\t-\tlogo.png\0");
+
+        assert_eq!(
+            files,
+            vec![
+                json!({ "path": "src/main.rs", "additions": 3, "deletions": 1 }),
+                json!({ "path": "logo.png", "additions": null, "deletions": null }),
+            ]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use std::fs;
-use std::io::Read;
+use std::io::{ErrorKind, Read};
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
 use std::time::Duration;
@@ -11,9 +11,32 @@ use apply_patch_engine::{
 };
 use wait_timeout::ChildExt;
 
+use crate::changes::FileSnapshot;
+
+mod git;
+pub mod output;
+mod preview;
+pub mod process;
+pub mod sandbox;
+pub mod schema;
+#[cfg(unix)]
+pub mod shell;
+#[cfg(not(unix))]
+#[path = "shell_unsupported.rs"]
+pub mod shell;
+
+use output::OutputLimits;
+use process::ProcessTable;
+use sandbox::PathSandbox;
+use shell::{PersistentShell, DEFAULT_SHELL_IDLE_TIMEOUT};
+
 const DEFAULT_BASH_TIMEOUT_SEC: u64 = 30;
-const DEFAULT_BASH_MAX_OUTPUT_BYTES: usize = 100 * 1024;
+/// Capture cap for `bash` output; what reaches the model is capped further by
+/// [`OutputLimits`].
+const DEFAULT_BASH_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
 const DEFAULT_READ_MAX_BYTES: usize = 200 * 1024;
+const ERROR_PERSISTENT_SHELL_UNSUPPORTED: &str =
+    "Persistent shell mode is only supported on Unix platforms";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ToolCall {
@@ -37,6 +60,90 @@ pub enum ToolCall {
     ApplyPatch {
         input: String,
     },
+    UpdatePlan {
+        plan: Plan,
+    },
+    GitStatus,
+    GitDiff {
+        path: Option<String>,
+        staged: bool,
+    },
+    GitCommit {
+        message: String,
+        paths: Option<Vec<String>>,
+        all: bool,
+    },
+    RestartShell,
+    BashBackground {
+        command: String,
+        cwd: Option<String>,
+    },
+    ProcessStatus {
+        id: Option<String>,
+    },
+    ProcessKill {
+        id: String,
+    },
+    ProcessLogs {
+        id: String,
+        tail_lines: Option<u64>,
+    },
+}
+
+/// Progress state of one step in an agent-maintained plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanStepStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+impl PlanStepStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "in_progress" => Some(Self::InProgress),
+            "completed" => Some(Self::Completed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStep {
+    pub step: String,
+    pub status: PlanStepStatus,
+}
+
+/// Latest plan reported through the `update_plan` tool; each call replaces it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub explanation: Option<String>,
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    pub fn completed_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| step.status == PlanStepStatus::Completed)
+            .count()
+    }
+
+    /// Returns the step currently in progress, if any.
+    pub fn current_step(&self) -> Option<&PlanStep> {
+        self.steps
+            .iter()
+            .find(|step| step.status == PlanStepStatus::InProgress)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -81,44 +188,122 @@ pub trait ToolExecutor {
     fn execute(&mut self, call: ToolCall) -> ToolOutput;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct BuiltinToolExecutor {
-    workspace_root: PathBuf,
+    sandbox: PathSandbox,
     default_bash_timeout_sec: u64,
     bash_max_output_bytes: usize,
     read_max_bytes: usize,
+    output_limits: OutputLimits,
+    file_snapshots: Vec<FileSnapshot>,
+    persistent_shell: bool,
+    shell_idle_timeout: Duration,
+    shell: Option<PersistentShell>,
+    processes: ProcessTable,
 }
 
 impl BuiltinToolExecutor {
     pub fn new(workspace_root: impl Into<PathBuf>) -> Result<Self, String> {
-        let workspace_root = workspace_root.into();
-        let canonical_root = workspace_root
-            .canonicalize()
-            .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
-
-        if !canonical_root.is_dir() {
-            return Err("Workspace root must be a directory".to_string());
-        }
-
         Ok(Self {
-            workspace_root: canonical_root,
+            sandbox: PathSandbox::new(workspace_root)?,
             default_bash_timeout_sec: DEFAULT_BASH_TIMEOUT_SEC,
             bash_max_output_bytes: DEFAULT_BASH_MAX_OUTPUT_BYTES,
             read_max_bytes: DEFAULT_READ_MAX_BYTES,
+            output_limits: OutputLimits::default(),
+            file_snapshots: Vec::new(),
+            persistent_shell: false,
+            shell_idle_timeout: DEFAULT_SHELL_IDLE_TIMEOUT,
+            shell: None,
+            processes: ProcessTable::default(),
         })
     }
 
     pub fn workspace_root(&self) -> &Path {
-        &self.workspace_root
+        self.sandbox.root()
+    }
+
+    /// Lets file tools access `path` (and everything under it) in addition to
+    /// the workspace root.
+    pub fn allow_path(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        self.sandbox.allow(path)
+    }
+
+    /// Runs `bash` calls in one long-lived shell so `cd` and exported
+    /// variables persist; disabling stops the current shell. Enabling fails
+    /// on non-Unix platforms, where every call stays one-shot.
+    pub fn set_persistent_shell(&mut self, enabled: bool) -> Result<(), String> {
+        if enabled && !cfg!(unix) {
+            return Err(ERROR_PERSISTENT_SHELL_UNSUPPORTED.to_string());
+        }
+        self.persistent_shell = enabled;
+        if !enabled {
+            self.shell = None;
+        }
+        Ok(())
+    }
+
+    pub fn persistent_shell(&self) -> bool {
+        self.persistent_shell
+    }
+
+    /// How long a persistent shell may sit unused before it is killed.
+    pub fn set_shell_idle_timeout(&mut self, timeout: Duration) {
+        self.shell_idle_timeout = timeout;
+    }
+
+    /// Shared handle to the processes started by `bash_background`.
+    pub fn process_table(&self) -> ProcessTable {
+        self.processes.clone()
+    }
+
+    /// Drains the pre-mutation snapshots recorded by `write`, `edit`, and
+    /// `apply_patch` since the last call, in mutation order.
+    pub fn take_file_snapshots(&mut self) -> Vec<FileSnapshot> {
+        std::mem::take(&mut self.file_snapshots)
+    }
+
+    /// Records `path`'s current contents before it is mutated; unreadable
+    /// files are skipped so a revert can never clobber them.
+    fn record_file_snapshot(&mut self, path: &Path) {
+        let original = match fs::read(path) {
+            Ok(bytes) => Some(bytes),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(_) => return,
+        };
+
+        self.file_snapshots.push(FileSnapshot {
+            path: path.to_path_buf(),
+            original,
+        });
     }
 
     fn execute_bash(
-        &self,
+        &mut self,
         command: String,
         timeout_sec: Option<u64>,
         cwd: Option<String>,
     ) -> ToolOutput {
         let timeout = timeout_sec.unwrap_or(self.default_bash_timeout_sec);
+        let cwd_path = match cwd {
+            Some(cwd) => {
+                let cwd_path = match self.sandbox.resolve_existing(&cwd) {
+                    Ok(path) => path,
+                    Err(error) => return ToolOutput::fail(format!("Invalid bash cwd: {error}")),
+                };
+
+                if !cwd_path.is_dir() {
+                    return ToolOutput::fail("Invalid bash cwd: expected a directory".to_string());
+                }
+
+                Some(cwd_path)
+            }
+            None => None,
+        };
+
+        if self.persistent_shell {
+            return self.execute_persistent_bash(command, Duration::from_secs(timeout), cwd_path);
+        }
+
         let mut command_builder = Command::new("bash");
         command_builder
             .arg("-lc")
@@ -126,16 +311,7 @@ impl BuiltinToolExecutor {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        if let Some(cwd) = cwd {
-            let cwd_path = match self.resolve_existing_path(&cwd) {
-                Ok(path) => path,
-                Err(error) => return ToolOutput::fail(format!("Invalid bash cwd: {error}")),
-            };
-
-            if !cwd_path.is_dir() {
-                return ToolOutput::fail("Invalid bash cwd: expected a directory".to_string());
-            }
-
+        if let Some(cwd_path) = cwd_path {
             command_builder.current_dir(cwd_path);
         }
 
@@ -192,7 +368,7 @@ impl BuiltinToolExecutor {
     }
 
     fn execute_read_file(&self, path: String) -> ToolOutput {
-        let resolved = match self.resolve_existing_path(&path) {
+        let resolved = match self.sandbox.resolve_existing(&path) {
             Ok(path) => path,
             Err(error) => return ToolOutput::fail(error),
         };
@@ -223,12 +399,17 @@ impl BuiltinToolExecutor {
         ToolOutput::ok(content)
     }
 
-    fn execute_edit_file(&self, path: String, old_text: String, new_text: String) -> ToolOutput {
+    fn execute_edit_file(
+        &mut self,
+        path: String,
+        old_text: String,
+        new_text: String,
+    ) -> ToolOutput {
         if old_text.is_empty() {
             return ToolOutput::fail("old_text must not be empty".to_string());
         }
 
-        let resolved = match self.resolve_existing_path(&path) {
+        let resolved = match self.sandbox.resolve_existing(&path) {
             Ok(path) => path,
             Err(error) => return ToolOutput::fail(error),
         };
@@ -251,6 +432,7 @@ impl BuiltinToolExecutor {
         }
 
         let updated_content = current_content.replacen(&old_text, &new_text, 1);
+        self.record_file_snapshot(&resolved);
         if let Err(error) = fs::write(&resolved, updated_content) {
             return ToolOutput::fail(format!(
                 "Failed to write file {}: {error}",
@@ -261,8 +443,8 @@ impl BuiltinToolExecutor {
         ToolOutput::ok(format!("Updated {}", resolved.display()))
     }
 
-    fn execute_write_file(&self, path: String, content: String) -> ToolOutput {
-        let resolved = match self.resolve_write_path(&path) {
+    fn execute_write_file(&mut self, path: String, content: String) -> ToolOutput {
+        let mut resolved = match self.sandbox.resolve_write(&path) {
             Ok(path) => path,
             Err(error) => return ToolOutput::fail(error),
         };
@@ -285,11 +467,16 @@ impl BuiltinToolExecutor {
                 }
             };
 
-            if let Err(error) = self.ensure_inside_workspace(&canonical_parent) {
+            if let Err(error) = self.sandbox.ensure_allowed(&canonical_parent) {
                 return ToolOutput::fail(error);
             }
+
+            if let Some(file_name) = resolved.file_name() {
+                resolved = canonical_parent.join(file_name);
+            }
         }
 
+        self.record_file_snapshot(&resolved);
         if let Err(error) = fs::write(&resolved, content) {
             return ToolOutput::fail(format!(
                 "Failed to write file {}: {error}",
@@ -300,13 +487,13 @@ impl BuiltinToolExecutor {
         ToolOutput::ok(format!("Wrote {}", resolved.display()))
     }
 
-    fn execute_apply_patch(&self, input: String) -> ToolOutput {
+    fn execute_apply_patch(&mut self, input: String) -> ToolOutput {
         if input.trim().is_empty() {
             return ToolOutput::fail("apply_patch requires non-empty input".to_string());
         }
 
         let argv = vec!["apply_patch".to_string(), input];
-        let action = match maybe_parse_apply_patch_verified(&argv, &self.workspace_root) {
+        let action = match maybe_parse_apply_patch_verified(&argv, self.sandbox.root()) {
             MaybeApplyPatchVerified::Body(action) => action,
             MaybeApplyPatchVerified::CorrectnessError(error) => {
                 return ToolOutput::fail(self.map_apply_patch_error(error));
@@ -333,7 +520,7 @@ impl BuiltinToolExecutor {
         for (path, change) in action.changes() {
             let mutation = match change {
                 ApplyPatchFileChange::Add { content } => {
-                    let resolved = match self.resolve_patch_write_target(path) {
+                    let resolved = match self.sandbox.resolve_write(path) {
                         Ok(path) => path,
                         Err(error) => {
                             return ToolOutput::fail(self.map_apply_patch_path_error(error));
@@ -374,7 +561,7 @@ impl BuiltinToolExecutor {
                     };
 
                     let destination_path = match move_path {
-                        Some(move_path) => match self.resolve_patch_write_target(move_path) {
+                        Some(move_path) => match self.sandbox.resolve_write(move_path) {
                             Ok(path) => path,
                             Err(error) => {
                                 return ToolOutput::fail(self.map_apply_patch_path_error(error));
@@ -424,6 +611,7 @@ impl BuiltinToolExecutor {
                         writes_started = true;
                     }
 
+                    self.record_file_snapshot(&path);
                     if let Err(error) = fs::write(&path, content) {
                         return ToolOutput::fail(self.format_apply_patch_io_failure(
                             format!(
@@ -438,6 +626,7 @@ impl BuiltinToolExecutor {
                     added.push(path);
                 }
                 PatchMutation::Delete { path } => {
+                    self.record_file_snapshot(&path);
                     if let Err(error) = fs::remove_file(&path) {
                         return ToolOutput::fail(self.format_apply_patch_io_failure(
                             format!(
@@ -469,6 +658,7 @@ impl BuiltinToolExecutor {
                         writes_started = true;
                     }
 
+                    self.record_file_snapshot(&destination_path);
                     if let Err(error) = fs::write(&destination_path, content) {
                         return ToolOutput::fail(self.format_apply_patch_io_failure(
                             format!(
@@ -481,6 +671,7 @@ impl BuiltinToolExecutor {
                     writes_started = true;
 
                     if source_path != destination_path {
+                        self.record_file_snapshot(&source_path);
                         if let Err(error) = fs::remove_file(&source_path) {
                             return ToolOutput::fail(self.format_apply_patch_io_failure(
                                 format!(
@@ -501,84 +692,15 @@ impl BuiltinToolExecutor {
         ToolOutput::ok(self.format_apply_patch_summary(&added, &modified, &deleted))
     }
 
-    fn resolve_existing_path(&self, path: &str) -> Result<PathBuf, String> {
-        if path.trim().is_empty() {
-            return Err("Path must not be empty".to_string());
-        }
-
-        let candidate = self.absolute_candidate(path);
-        let canonical = candidate
-            .canonicalize()
-            .map_err(|error| format!("Failed to resolve path {}: {error}", candidate.display()))?;
-
-        self.ensure_inside_workspace(&canonical)?;
-        Ok(canonical)
-    }
-
-    fn resolve_write_path(&self, path: &str) -> Result<PathBuf, String> {
-        if path.trim().is_empty() {
-            return Err("Path must not be empty".to_string());
-        }
-
-        let candidate = self.absolute_candidate(path);
-        let parent = candidate.parent().ok_or_else(|| {
-            format!(
-                "Path {} has no parent directory and cannot be written safely",
-                candidate.display()
-            )
-        })?;
-
-        let anchor = canonicalize_existing_ancestor(parent)?;
-        self.ensure_inside_workspace(&anchor)?;
-
-        Ok(candidate)
-    }
-
-    fn absolute_candidate(&self, path: &str) -> PathBuf {
-        let path = Path::new(path);
-        if path.is_absolute() {
-            path.to_path_buf()
-        } else {
-            self.workspace_root.join(path)
-        }
-    }
-
-    fn ensure_inside_workspace(&self, canonical_path: &Path) -> Result<(), String> {
-        if canonical_path.starts_with(&self.workspace_root) {
-            Ok(())
-        } else {
-            Err(format!(
-                "Path escapes workspace root: {}",
-                canonical_path.display()
-            ))
-        }
-    }
-
-    fn resolve_patch_existing_path(&self, path: &Path) -> Result<PathBuf, String> {
-        self.resolve_existing_path(path.to_string_lossy().as_ref())
-    }
-
     fn resolve_patch_existing_or_planned_path(
         &self,
         path: &Path,
         planned_existing_paths: &HashSet<PathBuf>,
     ) -> Result<PathBuf, String> {
         if planned_existing_paths.contains(path) && !path.exists() {
-            self.resolve_write_path(path.to_string_lossy().as_ref())
+            self.sandbox.resolve_write(path)
         } else {
-            self.resolve_patch_existing_path(path)
-        }
-    }
-
-    fn resolve_patch_write_target(&self, path: &Path) -> Result<PathBuf, String> {
-        if path.exists() {
-            let canonical = path
-                .canonicalize()
-                .map_err(|error| format!("Failed to resolve path {}: {error}", path.display()))?;
-            self.ensure_inside_workspace(&canonical)?;
-            Ok(path.to_path_buf())
-        } else {
-            self.resolve_write_path(path.to_string_lossy().as_ref())
+            self.sandbox.resolve_existing(path)
         }
     }
 
@@ -646,9 +768,7 @@ impl BuiltinToolExecutor {
     }
 
     fn workspace_relative_display(&self, path: &Path) -> String {
-        path.strip_prefix(&self.workspace_root)
-            .map(|relative| relative.display().to_string())
-            .unwrap_or_else(|_| path.display().to_string())
+        self.sandbox.relative_display(path)
     }
 }
 
@@ -668,10 +788,62 @@ impl ToolExecutor for BuiltinToolExecutor {
             } => self.execute_edit_file(path, old_text, new_text),
             ToolCall::WriteFile { path, content } => self.execute_write_file(path, content),
             ToolCall::ApplyPatch { input } => self.execute_apply_patch(input),
+            ToolCall::UpdatePlan { plan } => execute_update_plan(&plan),
+            ToolCall::GitStatus => self.execute_git_status(),
+            ToolCall::GitDiff { path, staged } => self.execute_git_diff(path, staged),
+            ToolCall::GitCommit {
+                message,
+                paths,
+                all,
+            } => self.execute_git_commit(message, paths, all),
+            ToolCall::RestartShell => self.execute_restart_shell(),
+            ToolCall::BashBackground { command, cwd } => self.execute_bash_background(command, cwd),
+            ToolCall::ProcessStatus { id } => self.execute_process_status(id),
+            ToolCall::ProcessKill { id } => self.execute_process_kill(id),
+            ToolCall::ProcessLogs { id, tail_lines } => self.execute_process_logs(id, tail_lines),
         }
     }
 }
 
+/// Validates a plan update; the host applies the plan to app state on success.
+fn execute_update_plan(plan: &Plan) -> ToolOutput {
+    if plan.steps.is_empty() {
+        return ToolOutput::fail("update_plan requires at least one step".to_string());
+    }
+
+    if let Some(index) = plan
+        .steps
+        .iter()
+        .position(|step| step.step.trim().is_empty())
+    {
+        return ToolOutput::fail(format!(
+            "update_plan step {} must have non-empty text",
+            index + 1
+        ));
+    }
+
+    let in_progress = plan
+        .steps
+        .iter()
+        .filter(|step| step.status == PlanStepStatus::InProgress)
+        .count();
+    if in_progress > 1 {
+        return ToolOutput::fail(format!(
+            "update_plan allows at most one in_progress step; found {in_progress}"
+        ));
+    }
+
+    ToolOutput::ok(format!(
+        "Plan updated: {}/{} steps completed",
+        plan.completed_count(),
+        plan.steps.len()
+    ))
+}
+
+fn to_json_text(value: serde_json::Value) -> String {
+    serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string())
+}
+
 fn read_pipe_bytes(pipe: Option<impl Read>) -> Vec<u8> {
     let Some(mut pipe) = pipe else {
         return Vec::new();
@@ -697,21 +869,6 @@ fn truncate_to_byte_limit(content: String, max_bytes: usize) -> String {
     truncated
 }
 
-fn canonicalize_existing_ancestor(path: &Path) -> Result<PathBuf, String> {
-    for ancestor in path.ancestors() {
-        if ancestor.exists() {
-            return ancestor.canonicalize().map_err(|error| {
-                format!("Failed to resolve path {}: {error}", ancestor.display())
-            });
-        }
-    }
-
-    Err(format!(
-        "No existing ancestor found for path {}",
-        path.display()
-    ))
-}
-
 fn format_exit_status(status: ExitStatus) -> String {
     match status.code() {
         Some(code) => format!("exit_code={code}"),
@@ -0,0 +1,310 @@
+//! Per-tool output caps.
+//!
+//! Tool output larger than its tool's cap is cut down before it goes back to
+//! the model: the head and tail are kept around a `[N bytes omitted]` marker,
+//! snapped to line boundaries where that loses little. The full output is
+//! written to `.agent/tool-output/<run_id>-<call_id>` so the TUI can still
+//! show all of it and the model can `read` it if the elided middle matters.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::BuiltinToolExecutor;
+use crate::app::RunId;
+
+/// Per-tool caps, e.g. `bash=16384,git_diff=65536,*=32768` (bytes; `*` sets
+/// the cap for every other tool).
+pub const TOOL_OUTPUT_LIMITS_ENV_VAR: &str = "CODING_AGENT_TOOL_OUTPUT_LIMITS";
+/// Full-output directory, relative to the workspace root.
+pub const TOOL_OUTPUT_DIR: &str = ".agent/tool-output";
+pub const DEFAULT_TOOL_OUTPUT_MAX_BYTES: usize = 32 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLimits {
+    default_max_bytes: usize,
+    per_tool: BTreeMap<String, usize>,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        Self {
+            default_max_bytes: DEFAULT_TOOL_OUTPUT_MAX_BYTES,
+            per_tool: BTreeMap::new(),
+        }
+    }
+}
+
+impl OutputLimits {
+    /// Parses a comma-separated list of `tool=bytes` entries; `*=bytes`
+    /// replaces the default cap.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut limits = Self::default();
+        for entry in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let Some((tool, bytes)) = entry.split_once('=') else {
+                return Err(format!("expected `tool=bytes`, got `{entry}`"));
+            };
+            let (tool, bytes) = (tool.trim(), bytes.trim());
+            let max_bytes = match bytes.parse::<usize>() {
+                Ok(max_bytes) if max_bytes > 0 => max_bytes,
+                _ => {
+                    return Err(format!(
+                        "limit for `{tool}` must be a positive number of bytes, got `{bytes}`"
+                    ))
+                }
+            };
+            if tool == "*" {
+                limits.default_max_bytes = max_bytes;
+            } else if tool.is_empty() {
+                return Err(format!("missing tool name in `{entry}`"));
+            } else {
+                limits.per_tool.insert(tool.to_string(), max_bytes);
+            }
+        }
+        Ok(limits)
+    }
+
+    pub fn max_bytes(&self, tool_name: &str) -> usize {
+        self.per_tool
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.default_max_bytes)
+    }
+}
+
+/// Tool output after its cap was applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CappedOutput {
+    pub content: String,
+    /// Where the full output was written, when `content` had to be cut.
+    pub full_output: Option<PathBuf>,
+}
+
+impl BuiltinToolExecutor {
+    pub fn set_output_limits(&mut self, limits: OutputLimits) {
+        self.output_limits = limits;
+    }
+
+    pub fn output_limits(&self) -> &OutputLimits {
+        &self.output_limits
+    }
+
+    /// Applies `tool_name`'s cap to `content`. Oversized output is saved
+    /// under [`TOOL_OUTPUT_DIR`] first and the returned content ends with a
+    /// note pointing at it (or saying why it could not be saved).
+    pub fn cap_output(
+        &self,
+        tool_name: &str,
+        run_id: RunId,
+        call_id: &str,
+        content: String,
+    ) -> CappedOutput {
+        let Some(truncated) = truncate_head_tail(&content, self.output_limits.max_bytes(tool_name))
+        else {
+            return CappedOutput {
+                content,
+                full_output: None,
+            };
+        };
+
+        let relative = format!("{TOOL_OUTPUT_DIR}/{}", output_file_name(run_id, call_id));
+        let path = self.workspace_root().join(&relative);
+        let saved = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, &content));
+        match saved {
+            Ok(()) => CappedOutput {
+                content: format!("{truncated}\n[full output saved to {relative}]"),
+                full_output: Some(path),
+            },
+            Err(error) => CappedOutput {
+                content: format!("{truncated}\n[full output could not be saved: {error}]"),
+                full_output: None,
+            },
+        }
+    }
+}
+
+/// `content` cut to at most `max_bytes` bytes of head and tail around a
+/// `[N bytes omitted]` marker, or `None` when it already fits. Each cut moves
+/// to a line boundary when that keeps at least half of its side.
+pub fn truncate_head_tail(content: &str, max_bytes: usize) -> Option<String> {
+    if content.len() <= max_bytes {
+        return None;
+    }
+
+    let head_budget = max_bytes / 2;
+    let mut head_end = floor_char_boundary(content, head_budget);
+    if let Some(newline) = content[..head_end].rfind('\n') {
+        if newline >= head_budget / 2 {
+            head_end = newline;
+        }
+    }
+
+    let tail_budget = max_bytes - head_budget;
+    let mut tail_start = ceil_char_boundary(content, content.len() - tail_budget);
+    if let Some(newline) = content[tail_start..].find('\n') {
+        if newline < tail_budget / 2 {
+            tail_start += newline + 1;
+        }
+    }
+
+    Some(format!(
+        "{}\n[{} bytes omitted]\n{}",
+        &content[..head_end],
+        tail_start - head_end,
+        &content[tail_start..]
+    ))
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// `<run_id>-<call_id>`. Providers reuse call ids across runs, hence the run
+/// id. Call ids come from the provider; anything but ASCII alphanumerics,
+/// `-` and `_` is replaced so the id cannot name a path outside the
+/// directory, and a hash of the raw id is appended so ids that differ only
+/// in replaced characters keep separate files.
+fn output_file_name(run_id: RunId, call_id: &str) -> String {
+    let sanitized = call_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if !call_id.is_empty() && sanitized == call_id {
+        format!("{run_id}-{call_id}")
+    } else {
+        format!("{run_id}-{sanitized}-{:016x}", fnv1a(call_id.as_bytes()))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{
+        output_file_name, truncate_head_tail, OutputLimits, DEFAULT_TOOL_OUTPUT_MAX_BYTES,
+    };
+    use crate::tools::BuiltinToolExecutor;
+
+    #[test]
+    fn head_and_tail_are_kept_around_an_omitted_marker() {
+        assert_eq!(truncate_head_tail("short", 16), None);
+
+        let lines = (1..=40)
+            .map(|line| format!("line {line:02}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(
+            truncate_head_tail(&lines, 40).as_deref(),
+            Some("line 01\nline 02\n[289 bytes omitted]\nline 39\nline 40")
+        );
+
+        let unbroken = "é".repeat(20);
+        let truncated = truncate_head_tail(&unbroken, 9).expect("over the cap");
+        assert_eq!(truncated, "éé\n[32 bytes omitted]\néé");
+    }
+
+    #[test]
+    fn limits_parse_per_tool_overrides_and_a_default() {
+        let limits = OutputLimits::parse("bash=100, *=2000,git_diff=50").expect("valid limits");
+        assert_eq!(limits.max_bytes("bash"), 100);
+        assert_eq!(limits.max_bytes("git_diff"), 50);
+        assert_eq!(limits.max_bytes("read"), 2000);
+        assert_eq!(
+            OutputLimits::parse("").expect("empty").max_bytes("read"),
+            DEFAULT_TOOL_OUTPUT_MAX_BYTES
+        );
+        assert_eq!(
+            OutputLimits::parse("bash=0"),
+            Err("limit for `bash` must be a positive number of bytes, got `0`".to_string())
+        );
+        assert_eq!(
+            OutputLimits::parse("bash"),
+            Err("expected `tool=bytes`, got `bash`".to_string())
+        );
+    }
+
+    #[test]
+    fn oversized_output_is_saved_in_full_under_the_workspace() {
+        let workspace = tempdir().expect("workspace");
+        let mut executor = BuiltinToolExecutor::new(workspace.path()).expect("executor");
+        executor.set_output_limits(OutputLimits::parse("bash=20").expect("limits"));
+
+        let small = executor.cap_output("read", 1, "call-1", "x".repeat(100));
+        assert_eq!(small.content, "x".repeat(100));
+        assert_eq!(small.full_output, None);
+
+        let full = "y".repeat(100);
+        let capped = executor.cap_output("bash", 3, "call-2", full.clone());
+        assert_eq!(
+            capped.content,
+            format!(
+                "{}\n[80 bytes omitted]\n{}\n[full output saved to .agent/tool-output/3-call-2]",
+                "y".repeat(10),
+                "y".repeat(10)
+            )
+        );
+        let path = capped.full_output.expect("saved output");
+        assert_eq!(path, workspace.path().join(".agent/tool-output/3-call-2"));
+        assert_eq!(fs::read_to_string(path).expect("full output"), full);
+    }
+
+    #[test]
+    fn reused_and_sanitized_call_ids_keep_separate_files() {
+        let escaping = output_file_name(1, "../call/2");
+        assert!(escaping.starts_with("1-___call_2-"), "{escaping}");
+        assert!(!escaping.contains('/'));
+
+        let workspace = tempdir().expect("workspace");
+        let mut executor = BuiltinToolExecutor::new(workspace.path()).expect("executor");
+        executor.set_output_limits(OutputLimits::parse("*=4").expect("limits"));
+        let outputs = [
+            (1, "call-1", "first run output"),
+            (2, "call-1", "second run output"),
+            (2, "a/b", "slash id output"),
+            (2, "a_b", "underscore id output"),
+        ];
+        let paths = outputs
+            .iter()
+            .map(|(run_id, call_id, output)| {
+                executor
+                    .cap_output("bash", *run_id, call_id, output.to_string())
+                    .full_output
+                    .expect("saved output")
+            })
+            .collect::<Vec<_>>();
+        for (path, (_, _, output)) in paths.iter().zip(outputs) {
+            assert_eq!(fs::read_to_string(path).expect("full output"), output);
+        }
+    }
+}
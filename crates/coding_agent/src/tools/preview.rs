@@ -0,0 +1,166 @@
+//! Dry-run previews for mutating tools.
+//!
+//! A preview validates a `write`, `edit`, or `apply_patch` call the same way
+//! execution would and renders the would-be change as a unified diff, without
+//! touching disk.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use apply_patch_engine::{
+    maybe_parse_apply_patch_verified, ApplyPatchFileChange, MaybeApplyPatchVerified,
+};
+
+use super::{BuiltinToolExecutor, ToolCall};
+use crate::changes::unified_diff;
+
+impl BuiltinToolExecutor {
+    /// Returns the would-be diff for a mutating call, or `None` for calls that
+    /// never write to disk. Errors match what execution would report.
+    pub fn preview(&self, call: &ToolCall) -> Option<Result<String, String>> {
+        match call {
+            ToolCall::WriteFile { path, content } => Some(self.preview_write_file(path, content)),
+            ToolCall::EditFile {
+                path,
+                old_text,
+                new_text,
+            } => Some(self.preview_edit_file(path, old_text, new_text)),
+            ToolCall::ApplyPatch { input } => Some(self.preview_apply_patch(input)),
+            _ => None,
+        }
+    }
+
+    fn preview_write_file(&self, path: &str, content: &str) -> Result<String, String> {
+        let resolved = self.sandbox.resolve_write(path)?;
+        let current = fs::read_to_string(&resolved).ok();
+
+        Ok(self.preview_diff(&resolved, current.as_deref(), Some(content)))
+    }
+
+    fn preview_edit_file(
+        &self,
+        path: &str,
+        old_text: &str,
+        new_text: &str,
+    ) -> Result<String, String> {
+        if old_text.is_empty() {
+            return Err("old_text must not be empty".to_string());
+        }
+
+        let resolved = self.sandbox.resolve_existing(path)?;
+        let current = fs::read_to_string(&resolved)
+            .map_err(|error| format!("Failed to read file {}: {error}", resolved.display()))?;
+
+        let occurrence_count = current.match_indices(old_text).count();
+        if occurrence_count != 1 {
+            return Err(format!(
+                "edit_file requires exactly one match; found {occurrence_count}"
+            ));
+        }
+
+        let updated = current.replacen(old_text, new_text, 1);
+        Ok(self.preview_diff(&resolved, Some(&current), Some(&updated)))
+    }
+
+    fn preview_apply_patch(&self, input: &str) -> Result<String, String> {
+        if input.trim().is_empty() {
+            return Err("apply_patch requires non-empty input".to_string());
+        }
+
+        let argv = vec!["apply_patch".to_string(), input.to_string()];
+        let action = match maybe_parse_apply_patch_verified(&argv, self.sandbox.root()) {
+            MaybeApplyPatchVerified::Body(action) => action,
+            MaybeApplyPatchVerified::CorrectnessError(error) => {
+                return Err(self.map_apply_patch_error(error));
+            }
+            MaybeApplyPatchVerified::ShellParseError(error) => {
+                return Err(format!(
+                    "apply_patch parse error: failed to parse invocation shell: {error:?}"
+                ));
+            }
+            MaybeApplyPatchVerified::NotApplyPatch => {
+                return Err(
+                    "apply_patch parse error: input did not contain a valid apply_patch command"
+                        .to_string(),
+                );
+            }
+        };
+
+        if action.is_empty() {
+            return Err("apply_patch produced no file changes".to_string());
+        }
+
+        let mut sections = Vec::new();
+        let mut planned_existing_paths: HashSet<PathBuf> = HashSet::new();
+        for (path, change) in action.changes() {
+            let section = match change {
+                ApplyPatchFileChange::Add { content } => {
+                    let resolved = self
+                        .sandbox
+                        .resolve_write(path)
+                        .map_err(|error| self.map_apply_patch_path_error(error))?;
+                    planned_existing_paths.insert(path.clone());
+                    self.preview_diff(&resolved, None, Some(content))
+                }
+                ApplyPatchFileChange::Delete { content } => {
+                    self.resolve_patch_existing_or_planned_path(path, &planned_existing_paths)
+                        .map_err(|error| self.map_apply_patch_path_error(error))?;
+                    planned_existing_paths.remove(path);
+                    self.preview_diff(path, Some(content), None)
+                }
+                ApplyPatchFileChange::Update {
+                    move_path,
+                    new_content,
+                    ..
+                } => {
+                    self.resolve_patch_existing_or_planned_path(path, &planned_existing_paths)
+                        .map_err(|error| self.map_apply_patch_path_error(error))?;
+                    if let Some(move_path) = move_path {
+                        planned_existing_paths.remove(path);
+                        planned_existing_paths.insert(move_path.clone());
+                    } else {
+                        planned_existing_paths.insert(path.clone());
+                    }
+
+                    let current = fs::read_to_string(path).unwrap_or_default();
+                    match move_path {
+                        Some(move_path) => {
+                            let destination = self
+                                .sandbox
+                                .resolve_write(move_path)
+                                .map_err(|error| self.map_apply_patch_path_error(error))?;
+                            let old_label = format!("a/{}", self.workspace_relative_display(path));
+                            let new_label =
+                                format!("b/{}", self.workspace_relative_display(&destination));
+                            unified_diff(&current, new_content, Some(&old_label), Some(&new_label))
+                                .text
+                        }
+                        None => self.preview_diff(path, Some(&current), Some(new_content)),
+                    }
+                }
+            };
+            sections.push(section);
+        }
+
+        Ok(sections.concat())
+    }
+
+    fn preview_diff(&self, path: &Path, before: Option<&str>, after: Option<&str>) -> String {
+        let label = self.workspace_relative_display(path);
+        let old_label = before.map(|_| format!("a/{label}"));
+        let new_label = after.map(|_| format!("b/{label}"));
+        let diff = unified_diff(
+            before.unwrap_or_default(),
+            after.unwrap_or_default(),
+            old_label.as_deref(),
+            new_label.as_deref(),
+        );
+
+        if diff.text.is_empty() {
+            format!("{label}: no content changes\n")
+        } else {
+            diff.text
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! Background processes: `bash_background`, `process_status`, `process_kill`,
+//! and `process_logs`.
+//!
+//! A background command runs detached from the tool call in its own process
+//! group (on Unix), with stdout and stderr captured into a bounded in-memory
+//! log. The table is shared with the host through [`ProcessTable`] so the TUI
+//! can list running processes without waiting on the tool executor. Processes
+//! still running when the last table handle drops, or when
+//! [`ProcessTable::kill_all`] is called, are killed.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Read;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::{to_json_text, BuiltinToolExecutor, ToolOutput};
+
+const MAX_RUNNING_PROCESSES: usize = 16;
+const PROCESS_LOG_CAPACITY_BYTES: usize = 256 * 1024;
+const DEFAULT_LOG_TAIL_LINES: usize = 200;
+#[cfg(unix)]
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+#[cfg(unix)]
+const KILL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl BuiltinToolExecutor {
+    pub(super) fn execute_bash_background(
+        &self,
+        command: String,
+        cwd: Option<String>,
+    ) -> ToolOutput {
+        if command.trim().is_empty() {
+            return ToolOutput::fail("bash_background requires a non-empty command".to_string());
+        }
+
+        let cwd = match cwd {
+            Some(cwd) => match self.sandbox.resolve_existing(&cwd) {
+                Ok(path) if path.is_dir() => path,
+                Ok(_) => {
+                    return ToolOutput::fail("Invalid bash cwd: expected a directory".to_string())
+                }
+                Err(error) => return ToolOutput::fail(format!("Invalid bash cwd: {error}")),
+            },
+            None => self.sandbox.root().to_path_buf(),
+        };
+
+        match self.processes.spawn(&command, &cwd) {
+            Ok(info) => ToolOutput::ok(to_json_text(info.to_json())),
+            Err(error) => ToolOutput::fail(error),
+        }
+    }
+
+    pub(super) fn execute_process_status(&self, id: Option<String>) -> ToolOutput {
+        match id {
+            Some(id) => match self.processes.get(&id) {
+                Some(info) => ToolOutput::ok(to_json_text(info.to_json())),
+                None => ToolOutput::fail(unknown_process_error(&id)),
+            },
+            None => {
+                let processes: Vec<Value> = self
+                    .processes
+                    .list()
+                    .iter()
+                    .map(BackgroundProcessInfo::to_json)
+                    .collect();
+                ToolOutput::ok(to_json_text(json!({ "processes": processes })))
+            }
+        }
+    }
+
+    pub(super) fn execute_process_kill(&self, id: String) -> ToolOutput {
+        match self.processes.kill(&id) {
+            Ok(info) => ToolOutput::ok(to_json_text(info.to_json())),
+            Err(error) => ToolOutput::fail(error),
+        }
+    }
+
+    pub(super) fn execute_process_logs(&self, id: String, tail_lines: Option<u64>) -> ToolOutput {
+        let tail_lines = tail_lines
+            .map(|lines| usize::try_from(lines).unwrap_or(usize::MAX))
+            .unwrap_or(DEFAULT_LOG_TAIL_LINES);
+
+        match self.processes.logs(&id, tail_lines) {
+            Some((info, logs)) => {
+                let mut value = info.to_json();
+                value["total_bytes"] = json!(logs.total_bytes);
+                value["truncated"] = json!(logs.truncated);
+                value["logs"] = json!(logs.text);
+                ToolOutput::ok(to_json_text(value))
+            }
+            None => ToolOutput::fail(unknown_process_error(&id)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundProcessStatus {
+    Running,
+    /// Exit code, or `None` when the process was ended by a signal.
+    Exited(Option<i32>),
+}
+
+/// Point-in-time view of one background process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackgroundProcessInfo {
+    pub id: String,
+    pub pid: u32,
+    pub command: String,
+    pub cwd: PathBuf,
+    pub status: BackgroundProcessStatus,
+    pub elapsed: Duration,
+}
+
+impl BackgroundProcessInfo {
+    pub fn is_running(&self) -> bool {
+        self.status == BackgroundProcessStatus::Running
+    }
+
+    fn to_json(&self) -> Value {
+        let (status, exit_code) = match self.status {
+            BackgroundProcessStatus::Running => ("running", None),
+            BackgroundProcessStatus::Exited(code) => ("exited", code),
+        };
+
+        json!({
+            "id": self.id,
+            "pid": self.pid,
+            "command": self.command,
+            "cwd": self.cwd.display().to_string(),
+            "status": status,
+            "exit_code": exit_code,
+            "elapsed_sec": self.elapsed.as_secs(),
+        })
+    }
+}
+
+struct LogTail {
+    text: String,
+    total_bytes: u64,
+    truncated: bool,
+}
+
+/// Shared handle to the background processes started by one executor.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessTable {
+    state: Arc<Mutex<ProcessTableState>>,
+}
+
+#[derive(Debug, Default)]
+struct ProcessTableState {
+    next_id: u64,
+    processes: BTreeMap<u64, BackgroundProcess>,
+}
+
+#[derive(Debug)]
+struct BackgroundProcess {
+    id: String,
+    command: String,
+    cwd: PathBuf,
+    child: Child,
+    started_at: Instant,
+    finished_after: Option<Duration>,
+    status: BackgroundProcessStatus,
+    log: Arc<Mutex<ProcessLog>>,
+}
+
+#[derive(Debug, Default)]
+struct ProcessLog {
+    bytes: VecDeque<u8>,
+    total_bytes: u64,
+}
+
+impl ProcessTable {
+    /// Lists every process started this session, oldest first.
+    pub fn list(&self) -> Vec<BackgroundProcessInfo> {
+        let mut state = self.lock();
+        state
+            .processes
+            .values_mut()
+            .map(BackgroundProcess::info)
+            .collect()
+    }
+
+    /// Lists only processes that are still running.
+    pub fn running(&self) -> Vec<BackgroundProcessInfo> {
+        self.list()
+            .into_iter()
+            .filter(BackgroundProcessInfo::is_running)
+            .collect()
+    }
+
+    fn get(&self, id: &str) -> Option<BackgroundProcessInfo> {
+        let mut state = self.lock();
+        state.find_mut(id).map(BackgroundProcess::info)
+    }
+
+    fn spawn(&self, command: &str, cwd: &Path) -> Result<BackgroundProcessInfo, String> {
+        let mut state = self.lock();
+        let running = state
+            .processes
+            .values_mut()
+            .map(BackgroundProcess::info)
+            .filter(BackgroundProcessInfo::is_running)
+            .count();
+        if running >= MAX_RUNNING_PROCESSES {
+            return Err(format!(
+                "Too many background processes running ({running}); kill one with process_kill first"
+            ));
+        }
+
+        let mut command_builder = Command::new("bash");
+        command_builder
+            .arg("-lc")
+            .arg(command)
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        #[cfg(unix)]
+        command_builder.process_group(0);
+        let mut child = command_builder
+            .spawn()
+            .map_err(|error| format!("Failed to launch background command: {error}"))?;
+
+        let log = Arc::new(Mutex::new(ProcessLog::default()));
+        if let Some(stdout) = child.stdout.take() {
+            spawn_log_reader(stdout, Arc::clone(&log));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            spawn_log_reader(stderr, Arc::clone(&log));
+        }
+
+        state.next_id += 1;
+        let key = state.next_id;
+        let mut process = BackgroundProcess {
+            id: format!("proc-{key}"),
+            command: command.to_string(),
+            cwd: cwd.to_path_buf(),
+            child,
+            started_at: Instant::now(),
+            finished_after: None,
+            status: BackgroundProcessStatus::Running,
+            log,
+        };
+        let info = process.info();
+        state.processes.insert(key, process);
+        Ok(info)
+    }
+
+    /// Kills every running process. The host calls this on shutdown, when
+    /// other handles to the table may still be alive.
+    pub fn kill_all(&self) {
+        self.lock().kill_running();
+    }
+
+    /// Sends SIGTERM to the process group, then SIGKILL after a grace period.
+    fn kill(&self, id: &str) -> Result<BackgroundProcessInfo, String> {
+        let pid = {
+            let mut state = self.lock();
+            let process = state
+                .find_mut(id)
+                .ok_or_else(|| unknown_process_error(id))?;
+            let info = process.info();
+            if !info.is_running() {
+                return Ok(info);
+            }
+            info.pid
+        };
+
+        if let Some(info) = self.terminate(id, pid) {
+            return Ok(info);
+        }
+
+        let mut state = self.lock();
+        let process = state
+            .find_mut(id)
+            .ok_or_else(|| unknown_process_error(id))?;
+        kill_process_group(&mut process.child);
+        let _ = process.child.wait();
+        Ok(process.info())
+    }
+
+    /// SIGTERM to the process group, then polls for the grace period; `None`
+    /// while the process is still running.
+    #[cfg(unix)]
+    fn terminate(&self, id: &str, pid: u32) -> Option<BackgroundProcessInfo> {
+        signal_process_group(pid, libc::SIGTERM);
+        let deadline = Instant::now() + KILL_GRACE_PERIOD;
+        loop {
+            // The lock is released between polls so renders listing processes
+            // are never blocked for the whole grace period.
+            if let Some(info) = self.get(id).filter(|info| !info.is_running()) {
+                return Some(info);
+            }
+
+            if Instant::now() >= deadline {
+                return None;
+            }
+            thread::sleep(KILL_POLL_INTERVAL);
+        }
+    }
+
+    /// Without signals there is no graceful stop; the caller kills outright.
+    #[cfg(not(unix))]
+    fn terminate(&self, _id: &str, _pid: u32) -> Option<BackgroundProcessInfo> {
+        None
+    }
+
+    fn logs(&self, id: &str, tail_lines: usize) -> Option<(BackgroundProcessInfo, LogTail)> {
+        let (info, log) = {
+            let mut state = self.lock();
+            let process = state.find_mut(id)?;
+            (process.info(), Arc::clone(&process.log))
+        };
+
+        let log = log.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let bytes: Vec<u8> = log.bytes.iter().copied().collect();
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        let skipped = lines.len().saturating_sub(tail_lines);
+        let tail = LogTail {
+            text: lines[skipped..].concat(),
+            total_bytes: log.total_bytes,
+            truncated: skipped > 0 || log.total_bytes > bytes.len() as u64,
+        };
+        Some((info, tail))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ProcessTableState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ProcessTableState {
+    fn find_mut(&mut self, id: &str) -> Option<&mut BackgroundProcess> {
+        self.processes.values_mut().find(|process| process.id == id)
+    }
+
+    fn kill_running(&mut self) {
+        for process in self.processes.values_mut() {
+            if process.info().is_running() {
+                kill_process_group(&mut process.child);
+                let _ = process.child.wait();
+            }
+        }
+    }
+}
+
+impl Drop for ProcessTableState {
+    fn drop(&mut self) {
+        self.kill_running();
+    }
+}
+
+impl BackgroundProcess {
+    /// Refreshes the exit status without blocking and returns a snapshot.
+    fn info(&mut self) -> BackgroundProcessInfo {
+        if self.status == BackgroundProcessStatus::Running {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                self.status = BackgroundProcessStatus::Exited(status.code());
+                self.finished_after = Some(self.started_at.elapsed());
+            }
+        }
+
+        BackgroundProcessInfo {
+            id: self.id.clone(),
+            pid: self.child.id(),
+            command: self.command.clone(),
+            cwd: self.cwd.clone(),
+            status: self.status,
+            elapsed: self
+                .finished_after
+                .unwrap_or_else(|| self.started_at.elapsed()),
+        }
+    }
+}
+
+impl ProcessLog {
+    fn append(&mut self, chunk: &[u8]) {
+        self.total_bytes += chunk.len() as u64;
+        self.bytes.extend(chunk);
+        let excess = self.bytes.len().saturating_sub(PROCESS_LOG_CAPACITY_BYTES);
+        self.bytes.drain(..excess);
+    }
+}
+
+fn spawn_log_reader(mut pipe: impl Read + Send + 'static, log: Arc<Mutex<ProcessLog>>) {
+    thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+        loop {
+            match pipe.read(&mut buffer) {
+                Ok(0) | Err(_) => return,
+                Ok(read) => log
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .append(&buffer[..read]),
+            }
+        }
+    });
+}
+
+/// SIGKILL to the process group `child` leads. Off Unix commands get no
+/// group of their own, so only `child` itself is killed.
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) {
+    signal_process_group(child.id(), libc::SIGKILL);
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+
+    // SAFETY: kill(2) has no memory-safety preconditions; background commands
+    // are spawned as the leaders of their own process groups.
+    unsafe {
+        libc::kill(-pid, signal);
+    }
+}
+
+fn unknown_process_error(id: &str) -> String {
+    format!("Unknown background process '{id}'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_log_keeps_the_newest_bytes_within_capacity() {
+        let mut log = ProcessLog::default();
+        log.append(&vec![b'a'; PROCESS_LOG_CAPACITY_BYTES]);
+        log.append(b"tail\n");
+
+        assert_eq!(log.bytes.len(), PROCESS_LOG_CAPACITY_BYTES);
+        assert_eq!(log.total_bytes, PROCESS_LOG_CAPACITY_BYTES as u64 + 5);
+        assert!(log.bytes.iter().rev().take(5).eq(b"\nliat".iter()));
+    }
+}
@@ -0,0 +1,280 @@
+//! Path sandbox shared by every file-touching tool.
+//!
+//! All path arguments, including the targets named inside `apply_patch`
+//! payloads, are resolved here. A path is accepted only when its canonical
+//! form lies under the workspace root or one of the explicitly allowed roots.
+//! Symlinks are followed before the check, so a link inside the workspace that
+//! points elsewhere is rejected rather than written through.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Environment variable listing extra roots (in `PATH` syntax) that tools may
+/// read and write in addition to the workspace root.
+pub const ALLOWED_PATHS_ENV_VAR: &str = "CODING_AGENT_ALLOWED_PATHS";
+
+#[derive(Debug, Clone)]
+pub struct PathSandbox {
+    root: PathBuf,
+    allowed_roots: Vec<PathBuf>,
+}
+
+impl PathSandbox {
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, String> {
+        let root = root.into();
+        let canonical_root = root
+            .canonicalize()
+            .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+
+        if !canonical_root.is_dir() {
+            return Err("Workspace root must be a directory".to_string());
+        }
+
+        Ok(Self {
+            root: canonical_root,
+            allowed_roots: Vec::new(),
+        })
+    }
+
+    /// Canonical workspace root; relative path arguments resolve against it.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn allowed_roots(&self) -> &[PathBuf] {
+        &self.allowed_roots
+    }
+
+    /// Adds an extra root outside the workspace that tools may access.
+    pub fn allow(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let canonical = path.canonicalize().map_err(|error| {
+            format!("Failed to resolve allowed path {}: {error}", path.display())
+        })?;
+
+        if !self.allowed_roots.contains(&canonical) {
+            self.allowed_roots.push(canonical);
+        }
+        Ok(())
+    }
+
+    /// Resolves a path that must already exist, following symlinks.
+    pub fn resolve_existing(&self, path: impl AsRef<Path>) -> Result<PathBuf, String> {
+        let candidate = self.candidate(path.as_ref())?;
+        let canonical = candidate
+            .canonicalize()
+            .map_err(|error| format!("Failed to resolve path {}: {error}", candidate.display()))?;
+
+        self.ensure_allowed_from(&candidate, &canonical)?;
+        Ok(canonical)
+    }
+
+    /// Resolves a path that is about to be created or overwritten.
+    ///
+    /// Existing targets are canonicalized like [`Self::resolve_existing`]; a
+    /// dangling symlink is refused because writing through it would create a
+    /// file at an unchecked location. For new paths the deepest existing
+    /// ancestor is canonicalized and the missing components are appended, so
+    /// `..` may not climb back out of that ancestor.
+    pub fn resolve_write(&self, path: impl AsRef<Path>) -> Result<PathBuf, String> {
+        let candidate = self.candidate(path.as_ref())?;
+
+        if let Ok(metadata) = fs::symlink_metadata(&candidate) {
+            if metadata.file_type().is_symlink() && !candidate.exists() {
+                return Err(format!(
+                    "Refusing to write through dangling symlink: {}",
+                    candidate.display()
+                ));
+            }
+
+            let canonical = candidate.canonicalize().map_err(|error| {
+                format!("Failed to resolve path {}: {error}", candidate.display())
+            })?;
+            self.ensure_allowed_from(&candidate, &canonical)?;
+            return Ok(canonical);
+        }
+
+        let anchor = candidate
+            .ancestors()
+            .skip(1)
+            .find(|ancestor| ancestor.exists())
+            .ok_or_else(|| {
+                format!(
+                    "No existing ancestor found for path {}",
+                    candidate.display()
+                )
+            })?;
+        let canonical_anchor = anchor
+            .canonicalize()
+            .map_err(|error| format!("Failed to resolve path {}: {error}", anchor.display()))?;
+        self.ensure_allowed_from(anchor, &canonical_anchor)?;
+
+        let mut resolved = canonical_anchor;
+        let mut depth = 0usize;
+        for component in candidate
+            .strip_prefix(anchor)
+            .unwrap_or(&candidate)
+            .components()
+        {
+            match component {
+                Component::Normal(part) => {
+                    resolved.push(part);
+                    depth += 1;
+                }
+                Component::CurDir => {}
+                Component::ParentDir if depth > 0 => {
+                    resolved.pop();
+                    depth -= 1;
+                }
+                _ => {
+                    return Err(format!(
+                        "Path escapes workspace root: {}",
+                        candidate.display()
+                    ));
+                }
+            }
+        }
+
+        self.ensure_allowed(&resolved)?;
+        Ok(resolved)
+    }
+
+    /// Checks an already-canonical path against the workspace and allowlist.
+    pub fn ensure_allowed(&self, canonical_path: &Path) -> Result<(), String> {
+        if self.is_allowed(canonical_path) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Path escapes workspace root: {}",
+                canonical_path.display()
+            ))
+        }
+    }
+
+    pub fn is_allowed(&self, canonical_path: &Path) -> bool {
+        canonical_path.starts_with(&self.root)
+            || self
+                .allowed_roots
+                .iter()
+                .any(|allowed| canonical_path.starts_with(allowed))
+    }
+
+    /// Renders `path` relative to the workspace root when it lies inside it.
+    pub fn relative_display(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .map(|relative| relative.display().to_string())
+            .unwrap_or_else(|_| path.display().to_string())
+    }
+
+    fn candidate(&self, path: &Path) -> Result<PathBuf, String> {
+        if path.as_os_str().to_string_lossy().trim().is_empty() {
+            return Err("Path must not be empty".to_string());
+        }
+
+        Ok(if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        })
+    }
+
+    /// Like [`Self::ensure_allowed`], naming the symlink when one caused the
+    /// escape so the model can tell a link apart from a `..` path.
+    fn ensure_allowed_from(&self, candidate: &Path, canonical: &Path) -> Result<(), String> {
+        if self.is_allowed(canonical) {
+            return Ok(());
+        }
+
+        let via_symlink = candidate.ancestors().any(|ancestor| {
+            fs::symlink_metadata(ancestor)
+                .map(|metadata| metadata.file_type().is_symlink())
+                .unwrap_or(false)
+        });
+        if via_symlink {
+            Err(format!(
+                "Path escapes workspace root via symlink: {} -> {}",
+                candidate.display(),
+                canonical.display()
+            ))
+        } else {
+            self.ensure_allowed(canonical)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn write_paths_normalize_missing_components_without_climbing_out() {
+        let outer = tempdir().expect("temp dir");
+        let root = outer.path().join("workspace");
+        fs::create_dir_all(&root).expect("create workspace");
+        let sandbox = PathSandbox::new(&root).expect("sandbox");
+
+        assert_eq!(
+            sandbox.resolve_write("new/../x.txt").expect("inside"),
+            sandbox.root().join("x.txt")
+        );
+        let error = sandbox
+            .resolve_write("new/../../x.txt")
+            .expect_err("climbs out");
+        assert!(error.contains("Path escapes workspace root"), "{error}");
+        assert!(sandbox.resolve_write("  ").is_err());
+    }
+
+    #[test]
+    fn allowlisted_roots_are_accepted_outside_the_workspace() {
+        let outer = tempdir().expect("temp dir");
+        let root = outer.path().join("workspace");
+        let shared = outer.path().join("shared");
+        fs::create_dir_all(&root).expect("create workspace");
+        fs::create_dir_all(&shared).expect("create shared");
+        let mut sandbox = PathSandbox::new(&root).expect("sandbox");
+        let target = shared.join("notes.txt");
+
+        assert!(sandbox.resolve_write(&target).is_err());
+        sandbox.allow(&shared).expect("allow shared");
+        assert_eq!(
+            sandbox.resolve_write(&target).expect("allowed"),
+            shared.canonicalize().expect("canonical").join("notes.txt")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_pointing_outside_the_workspace_are_rejected() {
+        use std::os::unix::fs::symlink;
+
+        let outer = tempdir().expect("temp dir");
+        let root = outer.path().join("workspace");
+        fs::create_dir_all(&root).expect("create workspace");
+        fs::write(outer.path().join("secret.txt"), "secret").expect("write secret");
+        symlink(outer.path().join("secret.txt"), root.join("link.txt")).expect("file link");
+        symlink(outer.path(), root.join("outer")).expect("dir link");
+        symlink(outer.path().join("missing.txt"), root.join("dangling.txt"))
+            .expect("dangling link");
+        let sandbox = PathSandbox::new(&root).expect("sandbox");
+
+        for error in [
+            sandbox.resolve_existing("link.txt").expect_err("file link"),
+            sandbox
+                .resolve_write("link.txt")
+                .expect_err("file link write"),
+            sandbox
+                .resolve_write("outer/new.txt")
+                .expect_err("dir link"),
+        ] {
+            assert!(
+                error.contains("Path escapes workspace root via symlink"),
+                "{error}"
+            );
+        }
+        let error = sandbox
+            .resolve_write("dangling.txt")
+            .expect_err("dangling link");
+        assert!(error.contains("dangling symlink"), "{error}");
+    }
+}
@@ -0,0 +1,321 @@
+//! Tool argument validation against a tool definition's `input_schema`.
+//!
+//! Covers the JSON Schema subset provider tool definitions use: `type`
+//! (a name or a list of names), `properties`, `required`,
+//! `additionalProperties`, `items`, `enum`, `minimum`/`maximum`,
+//! `minLength`/`maxLength` and `minItems`/`maxItems`. Other keywords are
+//! ignored, so a schema this module does not fully understand never rejects
+//! valid arguments. The first violation is reported with the path of the
+//! offending field, e.g. `plan[2].status`.
+
+use serde_json::{Map, Value};
+
+/// Checks `arguments` against `schema`; the error names the offending field
+/// and what was expected of it.
+pub fn validate_arguments(schema: &Value, arguments: &Value) -> Result<(), String> {
+    validate_value(schema, arguments, "")
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let names = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.is_empty() && !names.iter().any(|name| has_type(value, name)) {
+            let expected = names
+                .iter()
+                .map(|name| type_description(name))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            return Err(format!(
+                "{} must be {expected}, got {}",
+                subject(path),
+                value_kind(value)
+            ));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed = allowed.iter().map(display_value).collect::<Vec<_>>();
+            return Err(format!(
+                "{} must be one of {}",
+                subject(path),
+                allowed.join(", ")
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => validate_object(schema, fields, path),
+        Value::Array(items) => {
+            check_count(schema, "minItems", "maxItems", items.len(), "item", path)?;
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_value(item_schema, item, &format!("{path}[{index}]"))?;
+                }
+            }
+            Ok(())
+        }
+        Value::String(text) => check_count(
+            schema,
+            "minLength",
+            "maxLength",
+            text.chars().count(),
+            "character",
+            path,
+        ),
+        Value::Number(number) => {
+            let Some(number) = number.as_f64() else {
+                return Ok(());
+            };
+            if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < minimum {
+                    return Err(format!(
+                        "{} must be at least {}, got {}",
+                        subject(path),
+                        display_value(&schema["minimum"]),
+                        display_value(value)
+                    ));
+                }
+            }
+            if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > maximum {
+                    return Err(format!(
+                        "{} must be at most {}, got {}",
+                        subject(path),
+                        display_value(&schema["maximum"]),
+                        display_value(value)
+                    ));
+                }
+            }
+            Ok(())
+        }
+        Value::Bool(_) | Value::Null => Ok(()),
+    }
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    fields: &Map<String, Value>,
+    path: &str,
+) -> Result<(), String> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    for name in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !fields.contains_key(name) {
+            return Err(format!(
+                "missing required field '{}'",
+                field_path(path, name)
+            ));
+        }
+    }
+
+    for (name, value) in fields {
+        let field = field_path(path, name);
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property_schema) => validate_value(property_schema, value, &field)?,
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    let known = properties
+                        .map(|properties| properties.keys().cloned().collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    return Err(if known.is_empty() {
+                        format!("unknown field '{field}'; no fields are accepted")
+                    } else {
+                        format!(
+                            "unknown field '{field}'; expected one of {}",
+                            known.join(", ")
+                        )
+                    });
+                }
+                Some(additional @ Value::Object(_)) => validate_value(additional, value, &field)?,
+                _ => {}
+            },
+        }
+    }
+    Ok(())
+}
+
+fn check_count(
+    schema: &Map<String, Value>,
+    min_keyword: &str,
+    max_keyword: &str,
+    count: usize,
+    unit: &str,
+    path: &str,
+) -> Result<(), String> {
+    let plural = |n: u64| if n == 1 { "" } else { "s" };
+    if let Some(min) = schema.get(min_keyword).and_then(Value::as_u64) {
+        if (count as u64) < min {
+            return Err(format!(
+                "{} must have at least {min} {unit}{}, got {count}",
+                subject(path),
+                plural(min)
+            ));
+        }
+    }
+    if let Some(max) = schema.get(max_keyword).and_then(Value::as_u64) {
+        if count as u64 > max {
+            return Err(format!(
+                "{} must have at most {max} {unit}{}, got {count}",
+                subject(path),
+                plural(max)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        // Unknown type names are not ours to enforce.
+        _ => true,
+    }
+}
+
+fn type_description(name: &str) -> String {
+    match name {
+        "object" => "a JSON object".to_string(),
+        "array" => "an array".to_string(),
+        "string" => "a string".to_string(),
+        "boolean" => "a boolean".to_string(),
+        "null" => "null".to_string(),
+        "number" => "a number".to_string(),
+        "integer" => "an integer".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "an object",
+        Value::Array(_) => "an array",
+        Value::String(_) => "a string",
+        Value::Bool(_) => "a boolean",
+        Value::Null => "null",
+        Value::Number(number) if number.is_f64() => "a fractional number",
+        Value::Number(_) => "an integer",
+    }
+}
+
+/// Strings unquoted, everything else as JSON.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn field_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{parent}.{name}")
+    }
+}
+
+fn subject(path: &str) -> String {
+    if path.is_empty() {
+        "arguments".to_string()
+    } else {
+        format!("field '{path}'")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::validate_arguments;
+
+    fn plan_schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "timeout_sec": { "type": "integer", "minimum": 1 },
+                "plan": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "step": { "type": "string", "minLength": 1 },
+                            "status": { "type": "string", "enum": ["pending", "completed"] }
+                        },
+                        "required": ["step", "status"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["plan"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn valid_arguments_pass() {
+        let arguments = json!({
+            "timeout_sec": 5,
+            "plan": [{ "step": "write tests", "status": "pending" }]
+        });
+        assert_eq!(validate_arguments(&plan_schema(), &arguments), Ok(()));
+    }
+
+    #[test]
+    fn violations_name_the_field_and_the_expectation() {
+        let error = |arguments: serde_json::Value| {
+            validate_arguments(&plan_schema(), &arguments).expect_err("invalid arguments")
+        };
+        assert_eq!(
+            error(json!("plan")),
+            "arguments must be a JSON object, got a string"
+        );
+        assert_eq!(error(json!({})), "missing required field 'plan'");
+        assert_eq!(
+            error(json!({ "plan": [], "timeout_sec": 1.5 })),
+            "field 'timeout_sec' must be an integer, got a fractional number"
+        );
+        assert_eq!(
+            error(json!({ "plan": [], "timeout_sec": 0 })),
+            "field 'timeout_sec' must be at least 1, got 0"
+        );
+        assert_eq!(
+            error(json!({ "plan": [], "verbose": true })),
+            "unknown field 'verbose'; expected one of plan, timeout_sec"
+        );
+        assert_eq!(
+            error(json!({ "plan": [
+                { "step": "a", "status": "pending" },
+                { "step": "b", "status": "done" }
+            ] })),
+            "field 'plan[1].status' must be one of pending, completed"
+        );
+        assert_eq!(
+            error(json!({ "plan": [{ "step": "", "status": "pending" }] })),
+            "field 'plan[0].step' must have at least 1 character, got 0"
+        );
+        assert_eq!(
+            error(json!({ "plan": [{ "status": "pending" }] })),
+            "missing required field 'plan[0].step'"
+        );
+    }
+}
@@ -0,0 +1,557 @@
+//! Persistent `bash` sessions.
+//!
+//! In persistent mode every `bash` call runs inside one long-lived shell, so
+//! `cd`, exported variables, and sourced environments (virtualenvs) carry over
+//! between calls. The shell reads control lines from a pipe while its stdout
+//! and stderr are a pseudo-terminal, so commands see a TTY; each command's own
+//! stdin is `/dev/null`.
+//!
+//! A call that times out kills the shell's process group, as does the idle
+//! reaper once the shell has gone unused for the idle timeout. Either way the
+//! next call starts a fresh shell in the workspace root.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{format_exit_status, truncate_to_byte_limit, BuiltinToolExecutor, ToolOutput};
+
+/// Set to `1` or `true` to run `bash` calls in one persistent shell.
+pub const PERSISTENT_SHELL_ENV_VAR: &str = "CODING_AGENT_PERSISTENT_SHELL";
+pub const DEFAULT_SHELL_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+const PTY_COLUMNS: u16 = 200;
+const PTY_ROWS: u16 = 50;
+/// Bytes kept past the output cap so the completion marker is still found.
+const MARKER_SCAN_WINDOW: usize = 8 * 1024;
+
+static NEXT_SHELL_RUN_ID: AtomicU64 = AtomicU64::new(1);
+
+impl BuiltinToolExecutor {
+    /// Runs one `bash` call in the persistent shell, starting it if needed.
+    pub(super) fn execute_persistent_bash(
+        &mut self,
+        command: String,
+        timeout: Duration,
+        cwd: Option<PathBuf>,
+    ) -> ToolOutput {
+        let mut notes = Vec::new();
+        if self.shell.as_ref().is_some_and(PersistentShell::is_reaped) {
+            self.shell = None;
+            notes.push(format!(
+                "note: previous shell was reaped after {}s idle; cwd and environment were reset",
+                self.shell_idle_timeout.as_secs()
+            ));
+        }
+
+        let mut shell = match self.shell.take() {
+            Some(shell) => shell,
+            None => match PersistentShell::spawn(self.sandbox.root(), self.shell_idle_timeout) {
+                Ok(shell) => shell,
+                Err(error) => {
+                    return ToolOutput::fail(format!("Failed to start persistent shell: {error}"))
+                }
+            },
+        };
+
+        let run = match shell.run(
+            &command,
+            cwd.as_deref(),
+            timeout,
+            self.bash_max_output_bytes,
+        ) {
+            Ok(run) => run,
+            Err(error) => {
+                self.shell = Some(shell);
+                return ToolOutput::fail(format!("Persistent shell I/O failed: {error}"));
+            }
+        };
+
+        let (status_label, ok) = match &run.outcome {
+            ShellOutcome::Completed { exit_code, .. } => {
+                (format!("exit_code={exit_code}"), *exit_code == 0)
+            }
+            ShellOutcome::TimedOut => (format!("timeout after {}s", timeout.as_secs()), false),
+            ShellOutcome::Exited(status) => (format!("shell exited ({status})"), false),
+        };
+
+        let mut content = format!("status: {status_label}\n");
+        match &run.outcome {
+            ShellOutcome::Completed { cwd, .. } => {
+                content.push_str(&format!("cwd: {cwd}\n"));
+                self.shell = Some(shell);
+            }
+            ShellOutcome::TimedOut | ShellOutcome::Exited(_) => {
+                drop(shell);
+                notes.push(
+                    "note: persistent shell was stopped; the next call starts a fresh shell"
+                        .to_string(),
+                );
+            }
+        }
+        for note in notes {
+            content.push_str(&note);
+            content.push('\n');
+        }
+        content.push_str("output:\n");
+        content.push_str(&run.output);
+
+        ToolOutput {
+            ok,
+            content: truncate_to_byte_limit(content, self.bash_max_output_bytes),
+        }
+    }
+
+    pub(super) fn execute_restart_shell(&mut self) -> ToolOutput {
+        if !self.persistent_shell {
+            return ToolOutput::ok(
+                "Persistent shell mode is off; every bash call already starts a fresh shell",
+            );
+        }
+
+        let was_running = self.shell.take().is_some();
+        ToolOutput::ok(format!(
+            "{}; the next bash call starts a fresh shell in {}",
+            if was_running {
+                "Persistent shell stopped"
+            } else {
+                "No persistent shell was running"
+            },
+            self.sandbox.root().display()
+        ))
+    }
+}
+
+#[derive(Debug)]
+enum ShellOutcome {
+    Completed { exit_code: i32, cwd: String },
+    TimedOut,
+    Exited(String),
+}
+
+#[derive(Debug)]
+struct ShellRun {
+    output: String,
+    outcome: ShellOutcome,
+}
+
+/// Long-lived `bash` process attached to a pseudo-terminal.
+#[derive(Debug)]
+pub(super) struct PersistentShell {
+    child: Child,
+    control: ChildStdin,
+    output: Receiver<Vec<u8>>,
+    reaper: Arc<ShellReaper>,
+}
+
+impl PersistentShell {
+    fn spawn(workspace_root: &Path, idle_timeout: Duration) -> io::Result<Self> {
+        let (master, slave) = open_pty()?;
+        let stderr = slave.try_clone()?;
+
+        let mut command = Command::new("bash");
+        command
+            .arg("--noprofile")
+            .arg("--norc")
+            .current_dir(workspace_root)
+            .env("TERM", "dumb")
+            .env("PAGER", "cat")
+            .env("GIT_PAGER", "cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::from(slave))
+            .stderr(Stdio::from(stderr))
+            .process_group(0);
+        let mut child = command.spawn()?;
+        // The slave copies held by `command` must close so reads on the master
+        // end once the shell (and anything it started) exits.
+        drop(command);
+
+        let control = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("shell stdin was not captured"))?;
+        let output = spawn_pty_reader(File::from(master));
+        let reaper = ShellReaper::spawn(child.id(), idle_timeout);
+
+        Ok(Self {
+            child,
+            control,
+            output,
+            reaper,
+        })
+    }
+
+    fn is_reaped(&self) -> bool {
+        lock_state(&self.reaper).reaped
+    }
+
+    fn run(
+        &mut self,
+        command: &str,
+        cwd: Option<&Path>,
+        timeout: Duration,
+        max_output_bytes: usize,
+    ) -> io::Result<ShellRun> {
+        self.reaper.set_busy(true);
+        let result = self.run_inner(command, cwd, timeout, max_output_bytes);
+        self.reaper.set_busy(false);
+        result
+    }
+
+    fn run_inner(
+        &mut self,
+        command: &str,
+        cwd: Option<&Path>,
+        timeout: Duration,
+        max_output_bytes: usize,
+    ) -> io::Result<ShellRun> {
+        let run_id = format!(
+            "{}_{}",
+            std::process::id(),
+            NEXT_SHELL_RUN_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let marker = format!("__TAPE_SHELL_DONE_{run_id}__");
+        let script_path = std::env::temp_dir().join(format!("tape-shell-{run_id}.sh"));
+
+        let mut script = String::new();
+        if let Some(cwd) = cwd {
+            script.push_str(&format!("cd -- {} || return\n", shell_quote(cwd)));
+        }
+        script.push_str(command);
+        script.push('\n');
+        fs::write(&script_path, script)?;
+
+        // Drain anything a previous command left behind (e.g. background jobs).
+        while self.output.try_recv().is_ok() {}
+
+        let script = shell_quote(&script_path);
+        let control_line = format!(
+            ". {script} < /dev/null; __tape_status=$?; rm -f {script}; printf '\\n%s:%s:%s\\n' '{marker}' \"$__tape_status\" \"$PWD\"\n"
+        );
+        if let Err(error) = self
+            .control
+            .write_all(control_line.as_bytes())
+            .and_then(|()| self.control.flush())
+        {
+            let _ = fs::remove_file(&script_path);
+            return Ok(ShellRun {
+                output: String::new(),
+                outcome: ShellOutcome::Exited(format!("control pipe closed: {error}")),
+            });
+        }
+
+        let deadline = Instant::now() + timeout;
+        let needle = format!("\n{marker}:");
+        let mut captured = CapturedOutput::new(max_output_bytes);
+        let outcome = loop {
+            if let Some((output_end, exit_code, cwd)) = captured.find_marker(&needle) {
+                captured.truncate_at(output_end);
+                break ShellOutcome::Completed { exit_code, cwd };
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break ShellOutcome::TimedOut;
+            }
+
+            match self.output.recv_timeout(remaining) {
+                Ok(chunk) => captured.push(&chunk),
+                Err(RecvTimeoutError::Timeout) => break ShellOutcome::TimedOut,
+                Err(RecvTimeoutError::Disconnected) => {
+                    let status = self
+                        .child
+                        .wait()
+                        .map(format_exit_status)
+                        .unwrap_or_else(|error| format!("wait failed: {error}"));
+                    break ShellOutcome::Exited(status);
+                }
+            }
+        };
+
+        if !matches!(outcome, ShellOutcome::Completed { .. }) {
+            let _ = fs::remove_file(&script_path);
+        }
+
+        Ok(ShellRun {
+            output: captured.into_string(),
+            outcome,
+        })
+    }
+}
+
+impl Drop for PersistentShell {
+    fn drop(&mut self) {
+        self.reaper.close();
+        kill_process_group(self.child.id());
+        let _ = self.child.wait();
+    }
+}
+
+/// Output of one command: the first `limit` bytes plus a trailing window that
+/// is scanned for the completion marker.
+struct CapturedOutput {
+    bytes: Vec<u8>,
+    limit: usize,
+    truncated: bool,
+}
+
+impl CapturedOutput {
+    fn new(limit: usize) -> Self {
+        Self {
+            bytes: Vec::new(),
+            limit,
+            truncated: false,
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.bytes.extend_from_slice(chunk);
+        let keep = self.limit + MARKER_SCAN_WINDOW;
+        if self.bytes.len() > keep + MARKER_SCAN_WINDOW {
+            let excess = self.bytes.len() - keep;
+            self.bytes.drain(self.limit..self.limit + excess);
+            self.truncated = true;
+        }
+    }
+
+    fn find_marker(&self, needle: &str) -> Option<(usize, i32, String)> {
+        let start = find_bytes(&self.bytes, needle.as_bytes())?;
+        let rest = &self.bytes[start + needle.len()..];
+        let line_end = rest.iter().position(|byte| *byte == b'\n')?;
+        let line = String::from_utf8_lossy(&rest[..line_end]);
+        let (exit_code, cwd) = line.split_once(':')?;
+        Some((start, exit_code.trim().parse().ok()?, cwd.to_string()))
+    }
+
+    fn truncate_at(&mut self, end: usize) {
+        self.bytes.truncate(end);
+    }
+
+    fn into_string(mut self) -> String {
+        if self.bytes.len() > self.limit {
+            self.bytes.truncate(self.limit);
+            self.truncated = true;
+        }
+
+        let mut output = String::from_utf8_lossy(&self.bytes).into_owned();
+        if self.truncated {
+            output.push_str("\n[truncated]");
+        }
+        output
+    }
+}
+
+/// Kills an idle shell once it has gone unused for the idle timeout.
+#[derive(Debug)]
+struct ShellReaper {
+    state: Mutex<ReaperState>,
+    wake: Condvar,
+}
+
+#[derive(Debug)]
+struct ReaperState {
+    last_used: Instant,
+    busy: bool,
+    closed: bool,
+    reaped: bool,
+}
+
+impl ShellReaper {
+    fn spawn(pid: u32, idle_timeout: Duration) -> Arc<Self> {
+        let reaper = Arc::new(Self {
+            state: Mutex::new(ReaperState {
+                last_used: Instant::now(),
+                busy: false,
+                closed: false,
+                reaped: false,
+            }),
+            wake: Condvar::new(),
+        });
+
+        let watched = Arc::clone(&reaper);
+        thread::spawn(move || {
+            let mut state = lock_state(&watched);
+            loop {
+                if state.closed {
+                    return;
+                }
+
+                let idle = state.last_used.elapsed();
+                if !state.busy && idle >= idle_timeout {
+                    kill_process_group(pid);
+                    state.reaped = true;
+                    return;
+                }
+
+                let wait = if state.busy {
+                    idle_timeout
+                } else {
+                    idle_timeout - idle
+                };
+                state = watched
+                    .wake
+                    .wait_timeout(state, wait)
+                    .map(|(state, _)| state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+            }
+        });
+
+        reaper
+    }
+
+    fn set_busy(&self, busy: bool) {
+        let mut state = lock_state(self);
+        state.busy = busy;
+        state.last_used = Instant::now();
+        self.wake.notify_all();
+    }
+
+    fn close(&self) {
+        lock_state(self).closed = true;
+        self.wake.notify_all();
+    }
+}
+
+fn lock_state(reaper: &ShellReaper) -> std::sync::MutexGuard<'_, ReaperState> {
+    reaper
+        .state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn open_pty() -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut master = -1;
+    let mut slave = -1;
+    let size = libc::winsize {
+        ws_row: PTY_ROWS,
+        ws_col: PTY_COLUMNS,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    // SAFETY: the out-pointers are valid for writes; null name and termios
+    // pointers are permitted by openpty(3).
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            &size,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: openpty succeeded, so both descriptors are open and owned here.
+    let (master, slave) = unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+    set_cloexec(&master)?;
+    set_cloexec(&slave)?;
+    disable_output_processing(&slave)?;
+    Ok((master, slave))
+}
+
+fn set_cloexec(fd: &OwnedFd) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: `fd` is an open descriptor for the duration of both calls.
+    let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) };
+    if flags < 0
+        || unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, flags | libc::FD_CLOEXEC) } < 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Stops the terminal from rewriting `\n` as `\r\n` and from echoing.
+fn disable_output_processing(fd: &OwnedFd) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: termios is plain data; tcgetattr fully initializes it on success.
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd.as_raw_fd(), &mut termios) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    termios.c_oflag &= !libc::OPOST;
+    termios.c_lflag &= !libc::ECHO;
+    // SAFETY: `termios` was populated by tcgetattr above.
+    if unsafe { libc::tcsetattr(fd.as_raw_fd(), libc::TCSANOW, &termios) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn spawn_pty_reader(mut master: File) -> Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+        loop {
+            match master.read(&mut buffer) {
+                Ok(0) => return,
+                Ok(read) => {
+                    if sender.send(buffer[..read].to_vec()).is_err() {
+                        return;
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                // Linux reports EIO once every slave descriptor is closed.
+                Err(_) => return,
+            }
+        }
+    });
+    receiver
+}
+
+fn kill_process_group(pid: u32) {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+
+    // SAFETY: kill(2) has no memory-safety preconditions; the shell was
+    // spawned as the leader of its own process group.
+    unsafe {
+        libc::kill(-pid, libc::SIGKILL);
+    }
+}
+
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captured_output_keeps_head_and_finds_marker_past_the_cap() {
+        let mut captured = CapturedOutput::new(8);
+        captured.push(&vec![b'x'; 64 * 1024]);
+        captured.push(b"\n__DONE__:3:/tmp/work\n");
+
+        let (end, exit_code, cwd) = captured.find_marker("\n__DONE__:").expect("marker");
+        assert_eq!((exit_code, cwd.as_str()), (3, "/tmp/work"));
+        captured.truncate_at(end);
+        assert_eq!(captured.into_string(), "xxxxxxxx\n[truncated]");
+    }
+
+    #[test]
+    fn shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote(Path::new("/tmp/it's")), "'/tmp/it'\\''s'");
+    }
+}
@@ -0,0 +1,35 @@
+//! Persistent `bash` sessions on platforms without them.
+//!
+//! The persistent shell needs a pseudo-terminal and process groups, so it is
+//! Unix-only. Here [`BuiltinToolExecutor::set_persistent_shell`] refuses to
+//! enable it and every `bash` call starts a fresh shell.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{BuiltinToolExecutor, ToolOutput, ERROR_PERSISTENT_SHELL_UNSUPPORTED};
+
+/// Set to `1` or `true` to run `bash` calls in one persistent shell.
+pub const PERSISTENT_SHELL_ENV_VAR: &str = "CODING_AGENT_PERSISTENT_SHELL";
+pub const DEFAULT_SHELL_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Never constructed on this platform.
+#[derive(Debug)]
+pub(super) enum PersistentShell {}
+
+impl BuiltinToolExecutor {
+    pub(super) fn execute_persistent_bash(
+        &mut self,
+        _command: String,
+        _timeout: Duration,
+        _cwd: Option<PathBuf>,
+    ) -> ToolOutput {
+        ToolOutput::fail(ERROR_PERSISTENT_SHELL_UNSUPPORTED)
+    }
+
+    pub(super) fn execute_restart_shell(&mut self) -> ToolOutput {
+        ToolOutput::ok(
+            "Persistent shell mode is unsupported on this platform; every bash call already starts a fresh shell",
+        )
+    }
+}
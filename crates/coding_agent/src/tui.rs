@@ -1,4 +1,7 @@
-use std::collections::VecDeque;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
 use std::process::Command;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
@@ -10,13 +13,24 @@ use tape_tui::core::component::Focusable;
 use tape_tui::core::cursor::CursorPos;
 use tape_tui::core::input::KeyEventType;
 use tape_tui::{
-    default_editor_keybindings_handle, Component, Editor, EditorOptions, EditorTheme, InputEvent,
-    Markdown, MarkdownTheme, SelectListTheme,
+    default_editor_keybindings_handle, sanitize_ansi, CodeCell, CodeCellStatus, CodeCellTheme,
+    Component, ContentWidth, Editor, EditorOptions, EditorTheme, FocusRing, Input, InputEvent,
+    Markdown, MarkdownTheme, PathDisplay, SelectListTheme,
 };
 
-use crate::app::{App, HostOps, Message, Mode, Role};
+use crate::app::{
+    fenced_code_blocks, App, HostOps, Message, Mode, QueuedTurn, Role, RunId, RunMetrics,
+    StreamingToolCall, ToolConfirmation, TurnMetadata,
+};
+use crate::changes::{FileChange, FileChangeStatus};
+use crate::pricing::format_cost_usd;
 use crate::provider::ProviderProfile;
 use crate::runtime::{ProfileSwitchResult, RuntimeController};
+use crate::stream_smoothing::StreamSmoother;
+use crate::subagent::SubagentStep;
+use crate::tools::process::BackgroundProcessInfo;
+use crate::tools::{Plan, PlanStepStatus};
+use crate::workspace_index::{WorkspaceIndex, WorkspaceMentionProvider};
 
 struct HistoryUpdateGuard(Arc<AtomicBool>);
 
@@ -48,6 +62,96 @@ impl ViewMode {
     }
 }
 
+/// Mode-line label shown while the transcript has key focus.
+const TRANSCRIPT_FOCUS_HINT: &str =
+    "transcript · ↑/↓ select · enter expand · c copy · 1-9 copy code · r re-run · esc back";
+
+/// Bytes of rendered transcript lines kept for redraws; `0` keeps all of
+/// them. Older lines stay in the terminal's scrollback once released.
+pub const TRANSCRIPT_MEMORY_BUDGET_ENV_VAR: &str = "CODING_AGENT_TRANSCRIPT_MEMORY_BYTES";
+pub const DEFAULT_TRANSCRIPT_MEMORY_BUDGET: usize = 8 * 1024 * 1024;
+
+/// Reads [`TRANSCRIPT_MEMORY_BUDGET_ENV_VAR`]: unset or invalid values use
+/// [`DEFAULT_TRANSCRIPT_MEMORY_BUDGET`], and `0` means unbounded (`None`).
+pub fn transcript_memory_budget_from_env() -> Option<usize> {
+    match std::env::var(TRANSCRIPT_MEMORY_BUDGET_ENV_VAR)
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+    {
+        Some(0) => None,
+        Some(budget) => Some(budget),
+        None => Some(DEFAULT_TRANSCRIPT_MEMORY_BUDGET),
+    }
+}
+
+/// Widest the transcript is rendered, in columns; unset or `0` uses the
+/// full terminal width.
+pub const TRANSCRIPT_MAX_WIDTH_ENV_VAR: &str = "CODING_AGENT_TRANSCRIPT_MAX_WIDTH";
+/// `1` centers a narrowed transcript on wider terminals.
+pub const TRANSCRIPT_CENTER_ENV_VAR: &str = "CODING_AGENT_TRANSCRIPT_CENTER";
+
+/// Reads [`TRANSCRIPT_MAX_WIDTH_ENV_VAR`] and [`TRANSCRIPT_CENTER_ENV_VAR`].
+pub fn transcript_width_from_env() -> ContentWidth {
+    ContentWidth {
+        max_width: std::env::var(TRANSCRIPT_MAX_WIDTH_ENV_VAR)
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|max_width| *max_width > 0),
+        centered: std::env::var(TRANSCRIPT_CENTER_ENV_VAR).is_ok_and(|value| value.trim() == "1"),
+    }
+}
+
+/// Set to `1` to render raw escape sequences from model and tool output
+/// instead of sanitizing them.
+pub const RAW_OUTPUT_ENV_VAR: &str = "CODING_AGENT_RAW_OUTPUT";
+
+/// Whether [`RAW_OUTPUT_ENV_VAR`] leaves output sanitizing on.
+pub fn sanitize_output_from_env() -> bool {
+    !std::env::var(RAW_OUTPUT_ENV_VAR).is_ok_and(|value| value.trim() == "1")
+}
+
+/// `text` from the model or a tool as the transcript shows it: sanitized
+/// unless the app opted out.
+fn display_text<'a>(app: &App, text: &'a str) -> Cow<'a, str> {
+    if app.sanitize_output() {
+        Cow::Owned(sanitize_ansi(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// [`App::tool_call_result`] with string content passed through
+/// [`display_text`].
+fn display_tool_result<'a>(
+    app: &'a App,
+    run_id: RunId,
+    call_id: &str,
+) -> Option<(Cow<'a, Value>, bool)> {
+    let (content, is_error) = app.tool_call_result(run_id, call_id)?;
+    let content = match content {
+        Value::String(text) if app.sanitize_output() => {
+            Cow::Owned(Value::String(sanitize_ansi(text)))
+        }
+        content => Cow::Borrowed(content),
+    };
+    Some((content, is_error))
+}
+
+/// A card's result, with `full_output` (the saved output of a capped call)
+/// in place of its content.
+fn card_tool_result<'a>(
+    app: &'a App,
+    card: &ToolCard<'_>,
+    full_output: Option<&str>,
+) -> Option<(Cow<'a, Value>, bool)> {
+    let (content, is_error) = display_tool_result(app, card.run_id, card.call_id)?;
+    let content = match full_output {
+        Some(full_output) => Cow::Owned(Value::String(display_text(app, full_output).into_owned())),
+        None => content,
+    };
+    Some((content, is_error))
+}
+
 fn ansi_wrap(text: &str, prefix: &str, suffix: &str) -> String {
     format!("{prefix}{text}{suffix}")
 }
@@ -119,10 +223,25 @@ pub struct AppComponent {
     provider_profile: ProviderProfile,
     working_directory_label: String,
     transcript_render_cache: Option<TranscriptRenderCache>,
+    /// Rendered rows of settled plain messages, by transcript index.
+    transcript_items: Vec<Option<TranscriptItemRows>>,
     editor: Editor,
     is_applying_history: Arc<AtomicBool>,
     cursor_pos: Option<CursorPos>,
     view_mode: ViewMode,
+    show_turn_metadata: bool,
+    plan_panel_expanded: bool,
+    focus: FocusRing<FocusRegion>,
+    transcript_focus: TranscriptFocus,
+    stream_smoother: StreamSmoother,
+    transcript_memory_budget: Option<usize>,
+    transcript_width: ContentWidth,
+    released: ReleasedTranscript,
+    /// Code cells of the rendered `bash` cards, so a command is highlighted
+    /// once rather than on every render.
+    bash_cells: HashMap<ToolCardId, BashCell>,
+    /// Rows the header took in the last render that showed it.
+    header_rows: usize,
     debug_stats: DebugStats,
 }
 
@@ -130,7 +249,138 @@ pub struct AppComponent {
 struct TranscriptRenderCache {
     width: usize,
     transcript_revision: u64,
+    show_turn_metadata: bool,
+    transcript_focus_revision: u64,
+    /// Bytes of the streaming assistant message shown by the smoother.
+    streaming_visible_len: Option<usize>,
+    released_messages: usize,
     lines: Arc<Vec<String>>,
+    /// Rows and bytes of each rendered message, oldest first, starting at
+    /// `released_messages`.
+    message_rows: Vec<(usize, usize)>,
+}
+
+/// Rows of one settled message with the source they were wrapped from.
+///
+/// A transcript change re-renders the transcript but reuses these rows for
+/// messages whose source and width are unchanged. After a width change
+/// (a resize reflow) each message is wrapped again from its cached source
+/// once, so streaming updates afterwards only re-render the live message.
+#[derive(Debug, Clone)]
+struct TranscriptItemRows {
+    role: Role,
+    content: String,
+    width: usize,
+    lines: Vec<String>,
+}
+
+impl TranscriptItemRows {
+    fn matches(&self, message: &Message) -> bool {
+        self.role == message.role && self.content == message.content
+    }
+}
+
+/// Leading content left out of renders after it scrolled into terminal
+/// scrollback (see `Component::release_scrolled_rows`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ReleasedTranscript {
+    /// Width the released rows were rendered at. A width change clears the
+    /// terminal, so everything is rendered again, reflowed from the
+    /// transcript.
+    width: usize,
+    header: bool,
+    messages: usize,
+}
+
+/// Identifies a tool-call card by the run and call id that produced it.
+type ToolCardId = (RunId, String);
+
+/// A `bash` card's cell with the command it was built from. The cell keeps
+/// the command's highlighted lines; status, output and expansion are set
+/// again on each render.
+struct BashCell {
+    command: String,
+    cell: CodeCell,
+}
+
+/// Region of the app that receives keys; `shift+tab` cycles between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FocusRegion {
+    Editor,
+    Transcript,
+}
+
+/// Selection and tool-card expansion state for transcript navigation.
+///
+/// Items are transcript message indices derived on every render, so only the
+/// selected index and expanded card ids live here; `revision` invalidates the
+/// transcript render cache.
+#[derive(Debug, Default)]
+struct TranscriptFocus {
+    selected: Option<usize>,
+    expanded: HashSet<ToolCardId>,
+    /// Saved full output of expanded cards whose result was capped, read
+    /// when the card is expanded.
+    full_outputs: HashMap<ToolCardId, String>,
+    revision: u64,
+}
+
+impl TranscriptFocus {
+    fn is_selected(&self, index: usize) -> bool {
+        self.selected == Some(index)
+    }
+
+    fn is_expanded(&self, id: &ToolCardId) -> bool {
+        self.expanded.contains(id)
+    }
+
+    fn select(&mut self, index: Option<usize>) {
+        if self.selected != index {
+            self.selected = index;
+            self.bump();
+        }
+    }
+
+    /// Moves the selection through `items` (transcript indices, oldest
+    /// first). Moving up with nothing selected picks the newest item; moving
+    /// down past the newest item returns false so focus can go back to the
+    /// editor.
+    fn move_selection(&mut self, items: &[usize], backwards: bool) -> bool {
+        let current = self
+            .selected
+            .and_then(|selected| items.iter().position(|item| *item == selected));
+
+        let next = match (current, backwards) {
+            (Some(position), true) => Some(position.saturating_sub(1)),
+            (None, true) => items.len().checked_sub(1),
+            (Some(position), false) => Some(position + 1).filter(|next| *next < items.len()),
+            (None, false) => None,
+        };
+        self.select(next.map(|position| items[position]));
+        self.selected.is_some()
+    }
+
+    fn toggle_expanded(&mut self, id: ToolCardId) {
+        if self.expanded.remove(&id) {
+            self.full_outputs.remove(&id);
+        } else {
+            self.expanded.insert(id);
+        }
+        self.bump();
+    }
+
+    fn full_output(&self, id: &ToolCardId) -> Option<&str> {
+        self.full_outputs.get(id).map(String::as_str)
+    }
+
+    fn set_full_output(&mut self, id: ToolCardId, output: String) {
+        self.full_outputs.insert(id, output);
+        self.bump();
+    }
+
+    fn bump(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -140,6 +390,8 @@ struct DebugStats {
     render_count_total: u64,
     cache_hits: u64,
     cache_misses: u64,
+    /// Messages wrapped again because their source or the width changed.
+    item_rewraps: u64,
     last_frame_lines: usize,
     last_transcript_lines: usize,
     last_revision: u64,
@@ -157,6 +409,7 @@ impl DebugStats {
             render_count_total: 0,
             cache_hits: 0,
             cache_misses: 0,
+            item_rewraps: 0,
             last_frame_lines: 0,
             last_transcript_lines: 0,
             last_revision: 0,
@@ -193,30 +446,7 @@ impl AppComponent {
             lock_unpoisoned(&app_for_change).on_input_replace(value);
         })));
         editor.set_on_submit(Some(Box::new(move |value| {
-            let mut app = lock_unpoisoned(&app_for_submit);
-            app.on_input_replace(value);
-
-            let mut host = Arc::clone(&host_for_submit);
-            app.on_submit(&mut host);
-
-            if matches!(app.mode, Mode::Running { .. }) {
-                let app_for_spinner = Arc::clone(&app_for_submit);
-                let host_for_spinner = Arc::clone(&host_for_submit);
-                thread::spawn(move || loop {
-                    thread::sleep(Duration::from_millis(120));
-
-                    let running = {
-                        let app = lock_unpoisoned(&app_for_spinner);
-                        matches!(app.mode, Mode::Running { .. })
-                    };
-                    if !running {
-                        break;
-                    }
-
-                    let mut host = host_for_spinner.clone();
-                    host.request_render();
-                });
-            }
+            submit_prompt(&app_for_submit, &host_for_submit, value);
         })));
 
         Self {
@@ -225,14 +455,229 @@ impl AppComponent {
             provider_profile,
             working_directory_label: render_working_directory(),
             transcript_render_cache: None,
+            transcript_items: Vec::new(),
+            bash_cells: HashMap::new(),
             editor,
             is_applying_history,
             cursor_pos: None,
             view_mode: ViewMode::Plan,
+            show_turn_metadata: false,
+            plan_panel_expanded: true,
+            focus: FocusRing::new(vec![FocusRegion::Editor, FocusRegion::Transcript])
+                .expect("focus ring has regions"),
+            transcript_focus: TranscriptFocus::default(),
+            stream_smoother: StreamSmoother::default(),
+            transcript_memory_budget: Some(DEFAULT_TRANSCRIPT_MEMORY_BUDGET),
+            transcript_width: ContentWidth::default(),
+            released: ReleasedTranscript::default(),
+            header_rows: 0,
             debug_stats: DebugStats::new(),
         }
     }
 
+    /// Bytes of rendered transcript to keep once lines have scrolled into
+    /// terminal scrollback; `None` keeps the whole transcript.
+    pub fn set_transcript_memory_budget(&mut self, budget: Option<usize>) {
+        self.transcript_memory_budget = budget;
+    }
+
+    /// Caps the width the header and transcript render at, optionally
+    /// centering them; the prompt, status and footer keep the full width.
+    pub fn set_transcript_width(&mut self, transcript_width: ContentWidth) {
+        if self.transcript_width != transcript_width {
+            self.transcript_width = transcript_width;
+            self.transcript_render_cache = None;
+        }
+    }
+
+    /// Completes `@path` mentions in the prompt from `index`.
+    pub fn set_workspace_index(&mut self, index: WorkspaceIndex) {
+        self.editor
+            .set_autocomplete_provider(Box::new(WorkspaceMentionProvider::new(index)));
+    }
+
+    /// Moves key focus into the transcript, selecting the newest item;
+    /// false (focus unchanged) when there is nothing to select.
+    fn focus_transcript(&mut self) -> bool {
+        let items = transcript_item_indices(&lock_unpoisoned(&self.app));
+        if !self.transcript_focus.move_selection(&items, true) {
+            return false;
+        }
+
+        self.focus.focus(&FocusRegion::Transcript);
+        true
+    }
+
+    fn focus_editor(&mut self) {
+        self.focus.focus(&FocusRegion::Editor);
+        self.transcript_focus.select(None);
+    }
+
+    /// Handles keys while the transcript has focus: `up`/`down` move the
+    /// selection, `enter`/`space` toggle a selected tool card, `r` re-runs a
+    /// selected prompt, `c` copies the selected item and `1`-`9` one of its
+    /// fenced code blocks, and `escape`/`shift+tab` return to the editor. Any
+    /// other key returns focus to the editor and falls through to it.
+    fn handle_transcript_key(&mut self, event: &InputEvent) -> bool {
+        let InputEvent::Key {
+            key_id,
+            event_type: KeyEventType::Press,
+            ..
+        } = event
+        else {
+            return false;
+        };
+
+        let (items, selected) = {
+            let app = lock_unpoisoned(&self.app);
+            let items = transcript_item_indices(&app);
+            let selected = self
+                .transcript_focus
+                .selected
+                .filter(|selected| items.contains(selected))
+                .map(|selected| (selected, app.transcript[selected].clone()));
+            (items, selected)
+        };
+
+        let handled = match key_id.as_str() {
+            "up" | "\u{1b}[A" | "\u{1b}OA" | "down" | "\u{1b}[B" | "\u{1b}OB" => {
+                let backwards = matches!(key_id.as_str(), "up" | "\u{1b}[A" | "\u{1b}OA");
+                if !self.transcript_focus.move_selection(&items, backwards) {
+                    self.focus_editor();
+                }
+                true
+            }
+            "enter" | "space" => {
+                let card_id = selected.as_ref().and_then(|(_, message)| {
+                    tool_card_for_message(&lock_unpoisoned(&self.app), message)
+                        .map(|card| card.id())
+                });
+                if let Some(card_id) = card_id {
+                    self.transcript_focus.toggle_expanded(card_id.clone());
+                    if self.transcript_focus.is_expanded(&card_id) {
+                        let path = lock_unpoisoned(&self.app)
+                            .tool_output_file(card_id.0, &card_id.1)
+                            .map(Path::to_path_buf);
+                        if let Some(bytes) = path.and_then(|path| fs::read(path).ok()) {
+                            self.transcript_focus.set_full_output(
+                                card_id,
+                                String::from_utf8_lossy(&bytes).into_owned(),
+                            );
+                        }
+                    }
+                }
+                true
+            }
+            "r" => {
+                if let Some((_, message)) =
+                    selected.filter(|(_, message)| message.role == Role::User)
+                {
+                    self.focus_editor();
+                    submit_prompt(&self.app, &self.host, message.content);
+                    lock_unpoisoned(&self.app).on_input_replace(self.editor.get_text());
+                }
+                true
+            }
+            "c" => {
+                if let Some((_, message)) = selected {
+                    self.copy_transcript_item(&message, None);
+                }
+                true
+            }
+            "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => {
+                if let (Some((_, message)), Ok(block)) = (selected, key_id.parse::<usize>()) {
+                    self.copy_transcript_item(&message, Some(block));
+                }
+                true
+            }
+            "escape" | "shift+tab" => {
+                self.focus_editor();
+                true
+            }
+            _ => {
+                self.focus_editor();
+                false
+            }
+        };
+
+        let mut host = Arc::clone(&self.host);
+        host.request_render();
+        handled
+    }
+
+    /// Copies a selected transcript item, or its `code_block`th fenced code
+    /// block; tool cards copy the call's output.
+    fn copy_transcript_item(&self, message: &Message, code_block: Option<usize>) {
+        let mut app = lock_unpoisoned(&self.app);
+        let mut host = Arc::clone(&self.host);
+
+        let (text, what) = match tool_card_for_message(&app, message) {
+            Some(card) => {
+                let Some((content, _)) = app.tool_call_result(card.run_id, card.call_id) else {
+                    return;
+                };
+                let text = match content {
+                    Value::String(text) => text.clone(),
+                    other => {
+                        serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string())
+                    }
+                };
+                (text, format!("{} output", card.tool_name))
+            }
+            None => {
+                let what = match message.role {
+                    Role::User => "prompt",
+                    _ => "message",
+                };
+                (message.content.clone(), what.to_string())
+            }
+        };
+
+        match code_block {
+            None => app.copy_to_clipboard(&text, &what, &mut host),
+            Some(block) => match fenced_code_blocks(&text).get(block - 1) {
+                Some(code) => {
+                    app.copy_to_clipboard(code, &format!("code block {block}"), &mut host)
+                }
+                None => {
+                    app.push_system_message(format!(
+                        "No code block {block} in the selected {what}"
+                    ));
+                    host.request_render();
+                }
+            },
+        }
+    }
+
+    /// Maps `y`/`n` to a preview decision while a tool call awaits approval
+    /// and the editor is empty, so typing a prompt is never hijacked.
+    fn tool_confirmation_decision(&self, event: &InputEvent) -> Option<bool> {
+        let InputEvent::Key {
+            key_id,
+            event_type: KeyEventType::Press,
+            ..
+        } = event
+        else {
+            return None;
+        };
+
+        let approved = match key_id.as_str() {
+            "y" => true,
+            "n" => false,
+            _ => return None,
+        };
+
+        if !self.editor.get_text().is_empty()
+            || lock_unpoisoned(&self.app)
+                .pending_tool_confirmation()
+                .is_none()
+        {
+            return None;
+        }
+
+        Some(approved)
+    }
+
     fn with_app_mut(&self, mut f: impl FnMut(&mut App, &mut dyn HostOps)) {
         let mut app = lock_unpoisoned(&self.app);
         let mut host = Arc::clone(&self.host);
@@ -240,13 +685,49 @@ impl AppComponent {
     }
 
     fn render_transcript_lines_cached(&mut self, width: usize) -> (Arc<Vec<String>>, Mode) {
-        let (mode, transcript_revision) = {
-            let app = lock_unpoisoned(&self.app);
-            (app.mode.clone(), app.transcript_revision())
+        // One lock for the whole render: the streaming prefix length is only
+        // valid for the transcript it was measured on.
+        let app_handle = Arc::clone(&self.app);
+        let app = lock_unpoisoned(&app_handle);
+        let (mode, transcript_revision, streaming_visible_len) = {
+            if app.transcript.len() < self.released.messages {
+                // The transcript was replaced; nothing of it is released.
+                self.released = ReleasedTranscript {
+                    width,
+                    ..ReleasedTranscript::default()
+                };
+            }
+            self.transcript_items.truncate(app.transcript.len());
+            let streaming_visible_len = match app.transcript.last() {
+                Some(Message {
+                    role: Role::Assistant,
+                    streaming: true,
+                    run_id: Some(run_id),
+                    content,
+                }) => Some(
+                    self.stream_smoother
+                        .visible_len(*run_id, content, Instant::now()),
+                ),
+                _ => {
+                    self.stream_smoother.reset();
+                    None
+                }
+            };
+            (
+                app.mode.clone(),
+                app.transcript_revision(),
+                streaming_visible_len,
+            )
         };
 
         if let Some(cache) = self.transcript_render_cache.as_ref() {
-            if cache.width == width && cache.transcript_revision == transcript_revision {
+            if cache.width == width
+                && cache.transcript_revision == transcript_revision
+                && cache.show_turn_metadata == self.show_turn_metadata
+                && cache.transcript_focus_revision == self.transcript_focus.revision
+                && cache.streaming_visible_len == streaming_visible_len
+                && cache.released_messages == self.released.messages
+            {
                 self.debug_stats.cache_hits = self.debug_stats.cache_hits.saturating_add(1);
                 self.debug_stats.last_transcript_lines = cache.lines.len();
                 self.debug_stats.last_revision = transcript_revision;
@@ -256,22 +737,121 @@ impl AppComponent {
 
         self.debug_stats.cache_misses = self.debug_stats.cache_misses.saturating_add(1);
 
-        let rendered_lines = {
-            let app = lock_unpoisoned(&self.app);
+        let released_messages = self.released.messages;
+        let (content_width, margin) = self.transcript_width.layout(width);
+        let (rendered_lines, message_rows) = {
             let mut lines = Vec::new();
+            let mut message_rows = Vec::new();
+            let mut annotated_runs = HashSet::new();
+            let mut carded_calls = HashSet::new();
+            let mut rendered_cards = HashSet::new();
+
+            let last_index = app.transcript.len().saturating_sub(1);
+            for (index, message) in app.transcript.iter().enumerate() {
+                if index < released_messages {
+                    // Keep the per-run bookkeeping so later messages render
+                    // exactly as they did before the release.
+                    if let Some(run_id) = message.run_id {
+                        annotated_runs.insert(run_id);
+                    }
+                    if let Some(card) = tool_card_for_message(&app, message) {
+                        carded_calls.insert(card.id());
+                    }
+                    continue;
+                }
+                let start = lines.len();
+                let smoothed;
+                let message = match streaming_visible_len {
+                    Some(visible_len) if index == last_index => {
+                        smoothed = Message {
+                            content: message.content[..visible_len].to_string(),
+                            ..message.clone()
+                        };
+                        &smoothed
+                    }
+                    _ => message,
+                };
+                if let Some((run_id, call_id)) = tool_finished_message_id(message) {
+                    if carded_calls.contains(&(run_id, call_id.to_string())) {
+                        message_rows.push((0, 0));
+                        continue;
+                    }
+                }
 
-            for message in &app.transcript {
-                render_message_lines(&app, message, width, &mut lines);
-                lines.push(separator_line(width));
+                if self.show_turn_metadata {
+                    if let Some(metadata) = message
+                        .run_id
+                        .filter(|run_id| annotated_runs.insert(*run_id))
+                        .and_then(|run_id| app.turn_metadata(run_id))
+                    {
+                        lines.push(truncate_ansi_to_width(
+                            &format_turn_metadata_line(metadata),
+                            content_width,
+                        ));
+                    }
+                }
+                if let Some(card) = tool_card_for_message(&app, message) {
+                    let id = card.id();
+                    render_tool_card_lines(
+                        &app,
+                        &card,
+                        content_width,
+                        self.transcript_focus.is_expanded(&id),
+                        self.transcript_focus.full_output(&id),
+                        self.transcript_focus.is_selected(index),
+                        &mut self.bash_cells,
+                        &mut lines,
+                    );
+                    rendered_cards.insert(id.clone());
+                    carded_calls.insert(id);
+                } else if self.transcript_focus.is_selected(index) {
+                    render_selected_message_lines(&app, message, content_width, &mut lines);
+                } else if message.role == Role::Tool || message.streaming {
+                    render_message_lines(&app, message, content_width, &mut lines);
+                } else {
+                    if self.transcript_items.len() <= index {
+                        self.transcript_items.resize(index + 1, None);
+                    }
+                    let slot = &mut self.transcript_items[index];
+                    let cached = slot
+                        .as_ref()
+                        .is_some_and(|item| item.width == content_width && item.matches(message));
+                    if !cached {
+                        let mut item_lines = Vec::new();
+                        render_message_lines(&app, message, content_width, &mut item_lines);
+                        *slot = Some(TranscriptItemRows {
+                            role: message.role.clone(),
+                            content: message.content.clone(),
+                            width: content_width,
+                            lines: item_lines,
+                        });
+                        self.debug_stats.item_rewraps =
+                            self.debug_stats.item_rewraps.saturating_add(1);
+                    }
+                    if let Some(item) = slot.as_ref() {
+                        lines.extend(item.lines.iter().cloned());
+                    }
+                }
+                lines.push(separator_line(content_width));
+                ContentWidth::indent(&mut lines[start..], margin);
+                let bytes = lines[start..].iter().map(String::len).sum();
+                message_rows.push((lines.len() - start, bytes));
             }
 
-            Arc::new(lines)
+            self.bash_cells.retain(|id, _| rendered_cards.contains(id));
+            (Arc::new(lines), message_rows)
         };
+        drop(app);
 
         self.transcript_render_cache = Some(TranscriptRenderCache {
             width,
             transcript_revision,
+            show_turn_metadata: self.show_turn_metadata,
+            transcript_focus_revision: self.transcript_focus.revision,
+            streaming_visible_len,
+            released_messages,
             lines: Arc::clone(&rendered_lines),
+            message_rows,
         });
 
         self.debug_stats.last_transcript_lines = rendered_lines.len();
@@ -343,19 +923,101 @@ impl Component for AppComponent {
         record_render_timestamp_ms(&mut self.debug_stats, now_ms);
         self.debug_stats.render_count_total = self.debug_stats.render_count_total.saturating_add(1);
 
+        let pending_editor_text = lock_unpoisoned(&self.app).take_pending_editor_text();
+        if let Some(text) = pending_editor_text {
+            self.set_editor_text_with_history_bypass(&text);
+        }
+
+        if self.released.width != width {
+            self.released = ReleasedTranscript {
+                width,
+                ..ReleasedTranscript::default()
+            };
+        }
         let (transcript_lines, mode) = self.render_transcript_lines_cached(width);
         let mut lines = Vec::with_capacity(transcript_lines.len().saturating_add(10));
 
-        append_wrapped_text(&mut lines, width, &render_header(), "", "");
+        if !self.released.header {
+            let (content_width, margin) = self.transcript_width.layout(width);
+            append_wrapped_text(&mut lines, content_width, &render_header(), "", "");
+            let instructions_line = render_project_instructions_line(
+                lock_unpoisoned(&self.app).project_instruction_files(),
+            );
+            if let Some(instructions_line) = instructions_line {
+                append_wrapped_text(&mut lines, content_width, &instructions_line, "", "");
+            }
+            ContentWidth::indent(&mut lines, margin);
+            self.header_rows = lines.len();
+        }
         lines.extend(transcript_lines.iter().cloned());
 
-        append_wrapped_text(&mut lines, width, &render_status_line(&mode), "", "");
+        let (
+            confirmation_lines,
+            plan_lines,
+            metrics_label,
+            cost_label,
+            tool_preview,
+            streaming_tool_label,
+        ) = {
+            let app = lock_unpoisoned(&self.app);
+            let confirmation_lines = app
+                .pending_tool_confirmation()
+                .map(|confirmation| render_tool_confirmation_lines(width, confirmation))
+                .unwrap_or_default();
+            let plan_lines = app
+                .plan()
+                .map(|plan| render_plan_lines(width, plan, self.plan_panel_expanded))
+                .unwrap_or_default();
+            let metrics_label = match (&mode, app.run_metrics()) {
+                (Mode::Running { run_id }, Some(metrics)) if metrics.run_id == *run_id => Some(
+                    render_run_metrics_label(metrics, app.turn_metadata(*run_id), Instant::now()),
+                ),
+                _ => None,
+            };
+            (
+                confirmation_lines,
+                plan_lines,
+                metrics_label,
+                render_cost_label(&app, &mode),
+                app.tool_preview(),
+                app.streaming_tool_call().map(format_streaming_tool_call),
+            )
+        };
+        lines.extend(confirmation_lines);
+        lines.extend(plan_lines);
+        lines.extend(render_queued_turn_lines(width, &self.host.queued_turns()));
+        lines.extend(render_background_process_lines(
+            width,
+            &self.host.background_processes(),
+        ));
+        let mut status_line = render_status_line(&mode, streaming_tool_label.as_deref());
+        if let Some(metrics_label) = metrics_label {
+            status_line.push_str(&format!(" {} {}", dim("·"), dim(&metrics_label)));
+        }
+        if let Some(cost_label) = cost_label {
+            status_line.push_str(&format!(" {} {}", dim("·"), dim(&cost_label)));
+        }
+        if tool_preview {
+            status_line.push_str(&format!(" {} {}", dim("·"), yellow("preview")));
+        }
+        if let Some(toast) = self.host.toast() {
+            status_line.push_str(&format!(" {} {}", dim("·"), green(&format!("✓ {toast}"))));
+        }
+        append_wrapped_text(&mut lines, width, &status_line, "", "");
         let editor_start_row = lines.len();
         let mut editor_lines = self.editor.render(width);
         if let Some(editor_border) = editor_lines.get_mut(0) {
-            *editor_border = render_mode_line(width, self.view_mode);
+            let label = if self.focus.is_focused(&FocusRegion::Transcript) {
+                TRANSCRIPT_FOCUS_HINT
+            } else {
+                self.view_mode.label()
+            };
+            *editor_border = render_mode_line(width, label);
         }
         lines.extend(editor_lines);
+        // `/model` and `/thinking` switch the profile through the app, so the
+        // footer follows the provider rather than only the shortcuts.
+        self.provider_profile = self.host.provider_profile();
         append_wrapped_text(
             &mut lines,
             width,
@@ -376,10 +1038,15 @@ impl Component for AppComponent {
         self.debug_stats.last_frame_lines = lines.len().saturating_add(1);
         lines.push(render_debug_line(width, &self.debug_stats));
 
-        self.cursor_pos = self.editor.cursor_pos().map(|position| CursorPos {
-            row: position.row + editor_start_row,
-            col: position.col,
-        });
+        self.cursor_pos = self
+            .focus
+            .is_focused(&FocusRegion::Editor)
+            .then(|| self.editor.cursor_pos())
+            .flatten()
+            .map(|position| CursorPos {
+                row: position.row + editor_start_row,
+                col: position.col,
+            });
 
         lines
     }
@@ -388,6 +1055,56 @@ impl Component for AppComponent {
         self.cursor_pos
     }
 
+    /// Releases the header and the oldest whole messages that have scrolled
+    /// off while the rendered transcript is over its memory budget. The
+    /// newest message is always kept.
+    fn release_scrolled_rows(&mut self, scrolled_rows: usize) -> usize {
+        let Some(budget) = self.transcript_memory_budget else {
+            return 0;
+        };
+        let Some(cache) = self.transcript_render_cache.as_ref() else {
+            return 0;
+        };
+        if cache.width != self.released.width || cache.released_messages != self.released.messages {
+            return 0;
+        }
+        let mut retained_bytes: usize = cache.message_rows.iter().map(|(_, bytes)| bytes).sum();
+        if retained_bytes <= budget {
+            return 0;
+        }
+
+        let mut rows = if self.released.header {
+            0
+        } else {
+            self.header_rows
+        };
+        let mut messages = 0;
+        let releasable = cache.message_rows.len().saturating_sub(1);
+        for &(message_rows, bytes) in &cache.message_rows[..releasable] {
+            if retained_bytes <= budget || rows + message_rows > scrolled_rows {
+                break;
+            }
+            rows += message_rows;
+            retained_bytes -= bytes;
+            messages += 1;
+        }
+        if messages == 0 {
+            return 0;
+        }
+        self.released.header = true;
+        self.released.messages += messages;
+        // Released messages only come back after a width change, which
+        // wraps them again anyway.
+        for item in self
+            .transcript_items
+            .iter_mut()
+            .take(self.released.messages)
+        {
+            *item = None;
+        }
+        rows
+    }
+
     fn set_terminal_rows(&mut self, rows: usize) {
         self.editor.set_terminal_rows(rows);
     }
@@ -397,6 +1114,27 @@ impl Component for AppComponent {
     }
 
     fn handle_event(&mut self, event: &InputEvent) {
+        if let Some(approved) = self.tool_confirmation_decision(event) {
+            self.with_app_mut(|app, host| app.resolve_tool_confirmation(approved, host));
+            return;
+        }
+
+        if self.focus.is_focused(&FocusRegion::Transcript) && self.handle_transcript_key(event) {
+            return;
+        }
+
+        // An open suggestion list owns navigation and dismissal keys.
+        if self.editor.is_showing_autocomplete()
+            && matches!(
+                event,
+                InputEvent::Key { key_id, .. }
+                    if matches!(key_id.as_str(), "up" | "down" | "escape")
+            )
+        {
+            self.editor.handle_event(event);
+            return;
+        }
+
         match event {
             InputEvent::Key {
                 key_id,
@@ -417,6 +1155,9 @@ impl Component for AppComponent {
                         self.set_editor_text_with_history_bypass(&next_input);
                     }
                 }
+                "ctrl+z" => {
+                    self.host.suspend();
+                }
                 "ctrl+p" => {
                     self.cycle_model_shortcut();
                 }
@@ -424,10 +1165,26 @@ impl Component for AppComponent {
                     self.cycle_thinking_shortcut();
                 }
                 "shift+tab" => {
+                    if self.focus_transcript() {
+                        let mut host = Arc::clone(&self.host);
+                        host.request_render();
+                    }
+                }
+                "alt+m" => {
                     self.view_mode = self.view_mode.next();
                     let mut host = Arc::clone(&self.host);
                     host.request_render();
                 }
+                "ctrl+g" => {
+                    self.show_turn_metadata = !self.show_turn_metadata;
+                    let mut host = Arc::clone(&self.host);
+                    host.request_render();
+                }
+                "ctrl+l" => {
+                    self.plan_panel_expanded = !self.plan_panel_expanded;
+                    let mut host = Arc::clone(&self.host);
+                    host.request_render();
+                }
                 "up" | "\u{1b}[A" | "\u{1b}OA" => {
                     let mut next_input = None;
                     self.with_app_mut(|app, host| {
@@ -463,48 +1220,559 @@ impl Component for AppComponent {
     }
 }
 
-fn render_status_line(mode: &Mode) -> String {
-    match mode {
-        Mode::Idle => {
-            format!("{} {}", cyan("*"), dim("Ready - awaiting your input"))
-        }
-        Mode::Running { run_id } => {
-            format!(
-                "{} {} {}",
-                spinner_glyph(),
-                yellow_dim("Working"),
-                green(&format!("run_id={run_id}"))
-            )
+/// Modal surface listing files touched this session with per-file diffs.
+///
+/// The panel renders [`App::change_review`]; `/changes` opens it through
+/// [`HostOps::show_change_review`] and escape closes it.
+pub struct ChangeReviewPanel {
+    app: Arc<Mutex<App>>,
+    host: Arc<RuntimeController>,
+    selected: usize,
+    diff_scroll: usize,
+    viewport_rows: usize,
+    focused: bool,
+}
+
+impl ChangeReviewPanel {
+    pub fn new(app: Arc<Mutex<App>>, host: Arc<RuntimeController>) -> Self {
+        Self {
+            app,
+            host,
+            selected: 0,
+            diff_scroll: 0,
+            viewport_rows: 24,
+            focused: false,
         }
-        Mode::Error(error) => format!("{} {} {}", red("!"), red("Error:"), dim(error)),
-        Mode::Exiting => {
-            format!("{} {}", yellow_dim("Shutting down"), yellow("..."))
+    }
+
+    fn select(&mut self, index: usize) {
+        if index != self.selected {
+            self.selected = index;
+            self.diff_scroll = 0;
         }
     }
-}
 
-fn now_millis() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .ok()
-        .and_then(|since_epoch| u64::try_from(since_epoch.as_millis()).ok())
-        .unwrap_or(0)
+    fn diff_page_rows(&self) -> usize {
+        (self.viewport_rows / 2).max(1)
+    }
 }
 
-fn record_render_timestamp_ms(stats: &mut DebugStats, now_ms: u64) {
-    stats.render_timestamps_ms.push_back(now_ms);
-    while stats
-        .render_timestamps_ms
-        .front()
-        .is_some_and(|timestamp| now_ms.saturating_sub(*timestamp) > 1000)
-    {
-        let _ = stats.render_timestamps_ms.pop_front();
+impl Component for ChangeReviewPanel {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        let app = lock_unpoisoned(&self.app);
+        let Some(review) = app.change_review() else {
+            return Vec::new();
+        };
+
+        self.selected = self.selected.min(review.len().saturating_sub(1));
+        let lines = render_change_review_lines(
+            width,
+            self.viewport_rows,
+            review,
+            self.selected,
+            self.diff_scroll,
+        );
+        self.diff_scroll = lines.diff_scroll;
+        lines.lines
     }
-}
 
-const RENDER_DURATION_WINDOW_SIZE: usize = 64;
+    fn set_viewport_size(&mut self, _cols: usize, rows: usize) {
+        self.viewport_rows = rows.max(1);
+    }
 
-fn record_render_duration_ms(stats: &mut DebugStats, duration_ms: u64) {
+    fn handle_event(&mut self, event: &InputEvent) {
+        let InputEvent::Key {
+            key_id,
+            event_type: KeyEventType::Press,
+            ..
+        } = event
+        else {
+            return;
+        };
+
+        let app = Arc::clone(&self.app);
+        let mut app = lock_unpoisoned(&app);
+        let mut host = Arc::clone(&self.host);
+        let file_count = app.change_review().map_or(0, <[FileChange]>::len);
+
+        match key_id.as_str() {
+            "escape" | "q" => app.close_change_review(&mut host),
+            "up" | "k" => self.select(self.selected.saturating_sub(1)),
+            "down" | "j" => self.select((self.selected + 1).min(file_count.saturating_sub(1))),
+            "pageUp" => {
+                self.diff_scroll = self.diff_scroll.saturating_sub(self.diff_page_rows());
+            }
+            "pageDown" => {
+                self.diff_scroll = self.diff_scroll.saturating_add(self.diff_page_rows());
+            }
+            "r" => {
+                let path = app
+                    .change_review()
+                    .and_then(|review| review.get(self.selected))
+                    .map(|change| change.path.clone());
+                if let Some(path) = path {
+                    app.revert_file_change(&path, &mut host);
+                    self.diff_scroll = 0;
+                }
+                return;
+            }
+            _ => return,
+        }
+
+        host.request_render();
+    }
+
+    fn as_focusable(&mut self) -> Option<&mut dyn Focusable> {
+        Some(self)
+    }
+}
+
+impl Focusable for ChangeReviewPanel {
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+}
+
+/// Modal surface collecting the variables a `/prompt` template still needs.
+///
+/// The panel edits [`App::prompt_form`] one field at a time; enter on the
+/// last field expands the template into the editor and escape abandons it.
+pub struct PromptFormPanel {
+    app: Arc<Mutex<App>>,
+    host: Arc<RuntimeController>,
+    input: Input,
+    /// Field whose value `input` currently holds.
+    loaded_field: Option<usize>,
+    cursor_pos: Option<CursorPos>,
+    focused: bool,
+}
+
+impl PromptFormPanel {
+    pub fn new(app: Arc<Mutex<App>>, host: Arc<RuntimeController>) -> Self {
+        let mut input = Input::new(default_editor_keybindings_handle());
+        input.set_focused(true);
+        Self {
+            app,
+            host,
+            input,
+            loaded_field: None,
+            cursor_pos: None,
+            focused: false,
+        }
+    }
+}
+
+impl Component for PromptFormPanel {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        self.cursor_pos = None;
+        let app = lock_unpoisoned(&self.app);
+        let Some(form) = app.prompt_form() else {
+            self.loaded_field = None;
+            return Vec::new();
+        };
+
+        if self.loaded_field != Some(form.selected()) {
+            self.input
+                .set_value(form.fields()[form.selected()].value.clone());
+            self.input.move_cursor_to_end();
+            self.loaded_field = Some(form.selected());
+        }
+
+        let mut lines = vec![
+            format!(
+                "{} {}",
+                bold(&format!("Prompt {}", form.template_name())),
+                dim("· tab next field · enter insert · esc cancel")
+            ),
+            separator_line(width),
+        ];
+        for (index, field) in form.fields().iter().enumerate() {
+            if index == form.selected() {
+                self.input.set_prompt(format!("› {}: ", field.name));
+                let input_lines = self.input.render(width);
+                self.cursor_pos = self.input.cursor_pos().map(|pos| CursorPos {
+                    row: lines.len() + pos.row,
+                    col: pos.col,
+                });
+                lines.extend(input_lines);
+            } else {
+                lines.push(truncate_ansi_to_width(
+                    &format!("  {}: {}", dim(&field.name), field.value),
+                    width,
+                ));
+            }
+        }
+        lines
+    }
+
+    fn cursor_pos(&self) -> Option<CursorPos> {
+        self.cursor_pos
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        let app = Arc::clone(&self.app);
+        let mut app = lock_unpoisoned(&app);
+        let mut host = Arc::clone(&self.host);
+        let Some(form) = app.prompt_form_mut() else {
+            return;
+        };
+
+        let key_id = match event {
+            InputEvent::Key {
+                key_id,
+                event_type: KeyEventType::Press,
+                ..
+            } => key_id.as_str(),
+            _ => "",
+        };
+        match key_id {
+            "escape" => {
+                self.loaded_field = None;
+                app.cancel_prompt_form(&mut host);
+                return;
+            }
+            "enter" if form.is_last_selected() => {
+                self.loaded_field = None;
+                app.submit_prompt_form(&mut host);
+                return;
+            }
+            "enter" | "tab" | "down" => form.select(form.selected() + 1),
+            "shift+tab" | "up" => form.select(form.selected().saturating_sub(1)),
+            _ => {
+                self.input.handle_event(event);
+                form.set_selected_value(self.input.get_value());
+            }
+        }
+
+        host.request_render();
+    }
+
+    fn as_focusable(&mut self) -> Option<&mut dyn Focusable> {
+        Some(self)
+    }
+}
+
+impl Focusable for PromptFormPanel {
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+}
+
+struct ChangeReviewLines {
+    lines: Vec<String>,
+    /// Diff scroll offset after clamping to the selected file's diff.
+    diff_scroll: usize,
+}
+
+/// Renders the file list and the selected file's diff within `max_rows`.
+fn render_change_review_lines(
+    width: usize,
+    max_rows: usize,
+    review: &[FileChange],
+    selected: usize,
+    diff_scroll: usize,
+) -> ChangeReviewLines {
+    let mut lines = vec![
+        format!(
+            "{} {}",
+            bold("Changes"),
+            dim(&format!(
+                "· {} files · ↑/↓ select · pgup/pgdn scroll · r revert · esc close",
+                review.len()
+            ))
+        ),
+        separator_line(width),
+    ];
+
+    for (index, change) in review.iter().enumerate() {
+        let marker = if index == selected { "›" } else { " " };
+        let status = format!("{:<9}", change.status.label());
+        let status = match change.status {
+            FileChangeStatus::Added => green(&status),
+            FileChangeStatus::Deleted => red(&status),
+            FileChangeStatus::Modified => yellow(&status),
+            FileChangeStatus::Unchanged => dim(&status),
+        };
+        let counts = if change.binary {
+            dim("binary")
+        } else {
+            format!(
+                "{} {}",
+                green(&format!("+{}", change.additions)),
+                red(&format!("-{}", change.deletions))
+            )
+        };
+        lines.push(format!("{marker} {status} {} {counts}", change.label));
+    }
+    lines.push(separator_line(width));
+
+    let diff_lines: Vec<String> = match review.get(selected) {
+        Some(change) if change.binary => vec![dim("<binary file>")],
+        Some(change) if change.diff.is_empty() => vec![dim("<no changes against session start>")],
+        Some(change) => change.diff.lines().map(colorize_diff_line).collect(),
+        None => Vec::new(),
+    };
+    let diff_rows = max_rows.saturating_sub(lines.len()).max(1);
+    let diff_scroll = diff_scroll.min(diff_lines.len().saturating_sub(diff_rows));
+    lines.extend(diff_lines.into_iter().skip(diff_scroll).take(diff_rows));
+
+    ChangeReviewLines {
+        lines: lines
+            .iter()
+            .map(|line| truncate_ansi_to_width(line, width))
+            .collect(),
+        diff_scroll,
+    }
+}
+
+/// Renders the `update_plan` panel pinned above the status line.
+///
+/// Collapsed, the panel is a single progress row naming the current step.
+fn render_plan_lines(width: usize, plan: &Plan, expanded: bool) -> Vec<String> {
+    let progress = format!("{}/{} done", plan.completed_count(), plan.steps.len());
+
+    if !expanded {
+        let mut line = format!("{} {}", bold("Plan"), dim(&format!("· {progress}")));
+        if let Some(current) = plan.current_step() {
+            line.push_str(&format!(" {} {}", yellow("▸"), current.step));
+        }
+        line.push_str(&format!(" {}", dim("(ctrl+l to expand)")));
+        return vec![truncate_ansi_to_width(&line, width)];
+    }
+
+    let mut lines = vec![truncate_ansi_to_width(
+        &format!(
+            "{} {}",
+            bold("Plan"),
+            dim(&format!("· {progress} (ctrl+l to collapse)"))
+        ),
+        width,
+    )];
+
+    if let Some(explanation) = plan
+        .explanation
+        .as_deref()
+        .map(str::trim)
+        .filter(|explanation| !explanation.is_empty())
+    {
+        append_wrapped_text(&mut lines, width, &dim(explanation), "  ", "  ");
+    }
+
+    for step in &plan.steps {
+        let (marker, text) = match step.status {
+            PlanStepStatus::Completed => (green("✓"), dim(&step.step)),
+            PlanStepStatus::InProgress => (yellow("▸"), bold(&step.step)),
+            PlanStepStatus::Pending => (dim("○"), step.step.clone()),
+        };
+        append_wrapped_text(&mut lines, width, &text, &format!("  {marker} "), "    ");
+    }
+
+    lines
+}
+
+/// Renders the preview of a tool call paused for approval: a header naming
+/// the tool and keys, then the colourised would-be diff, capped in height.
+fn render_tool_confirmation_lines(width: usize, confirmation: &ToolConfirmation) -> Vec<String> {
+    let mut lines = vec![truncate_ansi_to_width(
+        &format!(
+            "{} {} {}",
+            yellow("Preview:"),
+            bold(&confirmation.tool_name),
+            dim("— y approve · n reject")
+        ),
+        width,
+    )];
+
+    let diff_lines: Vec<&str> = confirmation.diff.lines().collect();
+    for line in diff_lines.iter().take(TOOL_CONFIRMATION_MAX_DIFF_LINES) {
+        lines.push(truncate_ansi_to_width(
+            &format!("  {}", colorize_diff_line(line)),
+            width,
+        ));
+    }
+
+    let hidden = diff_lines
+        .len()
+        .saturating_sub(TOOL_CONFIRMATION_MAX_DIFF_LINES);
+    if hidden > 0 {
+        lines.push(truncate_ansi_to_width(
+            &dim(&format!("  … {hidden} more lines")),
+            width,
+        ));
+    }
+
+    lines
+}
+
+/// Renders one pending badge row per queued turn, numbered for `/dequeue`.
+fn render_queued_turn_lines(width: usize, turns: &[QueuedTurn]) -> Vec<String> {
+    turns
+        .iter()
+        .enumerate()
+        .map(|(index, turn)| {
+            let preview = turn.prompt.lines().next().unwrap_or_default();
+            let line = format!(
+                "{} {} {}",
+                yellow("[queued]"),
+                dim(&format!("{}.", index + 1)),
+                preview
+            );
+            truncate_ansi_to_width(&line, width)
+        })
+        .collect()
+}
+
+/// Renders one row per running `bash_background` process.
+fn render_background_process_lines(
+    width: usize,
+    processes: &[BackgroundProcessInfo],
+) -> Vec<String> {
+    processes
+        .iter()
+        .map(|process| {
+            let command = process.command.lines().next().unwrap_or_default();
+            let line = format!(
+                "{} {} {} {}",
+                cyan("[bg]"),
+                process.id,
+                dim(&format!("· {}s ·", process.elapsed.as_secs())),
+                command
+            );
+            truncate_ansi_to_width(&line, width)
+        })
+        .collect()
+}
+
+fn render_status_line(mode: &Mode, streaming_tool_label: Option<&str>) -> String {
+    match mode {
+        Mode::Idle => {
+            format!("{} {}", cyan("*"), dim("Ready - awaiting your input"))
+        }
+        Mode::Running { run_id } => match streaming_tool_label {
+            Some(label) => format!("{} {label}", spinner_glyph()),
+            None => format!(
+                "{} {} {}",
+                spinner_glyph(),
+                yellow_dim("Working"),
+                green(&format!("run_id={run_id}"))
+            ),
+        },
+        Mode::Error(error) => format!("{} {} {}", red("!"), red("Error:"), dim(error)),
+        Mode::Exiting => {
+            format!("{} {}", yellow_dim("Shutting down"), yellow("..."))
+        }
+    }
+}
+
+const STREAMING_TOOL_PREVIEW_CHARS: usize = 48;
+
+/// Summarizes a tool call the model is still writing, e.g.
+/// ``bash: running `cargo te…` ``.
+fn format_streaming_tool_call(call: &StreamingToolCall) -> String {
+    let (verb, key) = match call.tool_name.as_str() {
+        "bash" | "bash_background" => ("running", "command"),
+        "read" => ("reading", "path"),
+        "write" => ("writing", "path"),
+        "edit" => ("editing", "path"),
+        "git_commit" => ("committing", "message"),
+        _ => ("preparing", ""),
+    };
+    let preview = argument_string(&call.arguments, key).map(|value| {
+        value
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .take(STREAMING_TOOL_PREVIEW_CHARS)
+            .collect::<String>()
+    });
+
+    let label = yellow_dim(&format!("{}:", call.tool_name));
+    match preview {
+        Some(preview) => format!("{label} {verb} {}", dim(&format!("`{preview}…`"))),
+        None => format!("{label} {}", dim(&format!("{verb}…"))),
+    }
+}
+
+/// Elapsed time, time to first token and streaming rate of a running turn,
+/// e.g. `12.4s · first token 0.8s · 41 tok/s`. The rate is in output tokens
+/// once the provider has reported usage and in characters until then.
+fn render_run_metrics_label(
+    metrics: &RunMetrics,
+    metadata: Option<&TurnMetadata>,
+    now: Instant,
+) -> String {
+    let mut parts = vec![format_turn_duration(metrics.elapsed(now))];
+    if let Some(first_chunk) = metrics.time_to_first_chunk() {
+        parts.push(format!("first token {}", format_turn_duration(first_chunk)));
+    }
+    let output_tokens = metadata
+        .and_then(|metadata| metadata.usage.as_ref())
+        .map_or(0, |usage| usage.output_tokens);
+    if let Some(secs) = metrics.streaming_secs(now) {
+        parts.push(if output_tokens > 0 {
+            format!("{:.0} tok/s", output_tokens as f64 / secs)
+        } else {
+            format!("{:.0} chars/s", metrics.streamed_chars as f64 / secs)
+        });
+    }
+    parts.join(" · ")
+}
+
+/// Formats running spend for the status line, or `None` while unpriced.
+///
+/// Shows the active run's cost next to the session total while a priced run
+/// is streaming.
+fn render_cost_label(app: &App, mode: &Mode) -> Option<String> {
+    if app.pricing_table().is_empty() && app.session_cost_micros().is_none() {
+        return None;
+    }
+
+    let session_cost = format_cost_usd(app.session_cost_micros().unwrap_or(0));
+    let run_cost = match mode {
+        Mode::Running { run_id } => app
+            .turn_metadata(*run_id)
+            .and_then(|metadata| metadata.cost_micros),
+        _ => None,
+    };
+
+    Some(match run_cost {
+        Some(run_cost) => format!("{} run / {session_cost} session", format_cost_usd(run_cost)),
+        None => format!("{session_cost} session"),
+    })
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|since_epoch| u64::try_from(since_epoch.as_millis()).ok())
+        .unwrap_or(0)
+}
+
+fn record_render_timestamp_ms(stats: &mut DebugStats, now_ms: u64) {
+    stats.render_timestamps_ms.push_back(now_ms);
+    while stats
+        .render_timestamps_ms
+        .front()
+        .is_some_and(|timestamp| now_ms.saturating_sub(*timestamp) > 1000)
+    {
+        let _ = stats.render_timestamps_ms.pop_front();
+    }
+}
+
+const RENDER_DURATION_WINDOW_SIZE: usize = 64;
+const TOOL_CONFIRMATION_MAX_DIFF_LINES: usize = 40;
+
+fn record_render_duration_ms(stats: &mut DebugStats, duration_ms: u64) {
     stats.render_durations_ms.push_back(duration_ms);
     while stats.render_durations_ms.len() > RENDER_DURATION_WINDOW_SIZE {
         let _ = stats.render_durations_ms.pop_front();
@@ -770,6 +2038,13 @@ fn render_header() -> String {
     )
 }
 
+fn render_project_instructions_line(labels: &[String]) -> Option<String> {
+    if labels.is_empty() {
+        return None;
+    }
+    Some(dim(&format!("instructions: {}", labels.join(", "))))
+}
+
 fn render_working_directory() -> String {
     match std::env::current_dir() {
         Ok(path) => {
@@ -823,17 +2098,16 @@ fn render_provider_metadata(profile: &ProviderProfile) -> String {
     metadata
 }
 
+/// Widest the working directory is shown in the status footer before it is
+/// truncated in the middle.
+const WORKING_DIRECTORY_MAX_WIDTH: usize = 48;
+
 fn format_working_directory_with_home(cwd: &str, branch: &str, home: Option<&str>) -> String {
-    let display_path = home
-        .map(|home| {
-            if cwd == home {
-                "~".to_string()
-            } else {
-                cwd.strip_prefix(&format!("{home}/"))
-                    .map_or(cwd.to_string(), |rest| format!("~/{rest}"))
-            }
-        })
-        .unwrap_or_else(|| cwd.to_string());
+    let paths = match home {
+        Some(home) => PathDisplay::new().with_home(home),
+        None => PathDisplay::new(),
+    };
+    let display_path = paths.display(cwd, WORKING_DIRECTORY_MAX_WIDTH);
 
     format!("{} {}", dim(&display_path), dim(&format!("({branch})")))
 }
@@ -884,8 +2158,8 @@ fn current_git_branch() -> Option<String> {
     }
 }
 
-fn render_mode_line(width: usize, view_mode: ViewMode) -> String {
-    let label = format!(" {} ", view_mode.label());
+fn render_mode_line(width: usize, label: &str) -> String {
+    let label = format!(" {label} ");
     let label_width = visible_text_width(&label);
 
     if width == 0 {
@@ -909,6 +2183,75 @@ fn render_mode_line(width: usize, view_mode: ViewMode) -> String {
     )
 }
 
+/// Formats the dim metadata gutter row rendered above the first message of a turn.
+///
+/// Clock times are UTC (`HH:MM:SS`); std exposes no local timezone offset.
+fn format_turn_metadata_line(metadata: &TurnMetadata) -> String {
+    let model_id = metadata.model_id.trim();
+    let model_id = if model_id.is_empty() {
+        "unknown"
+    } else {
+        model_id
+    };
+
+    let mut parts = vec![format_clock_utc(metadata.started_at), model_id.to_string()];
+
+    if let Some(thinking_level) = metadata
+        .thinking_level
+        .as_deref()
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+    {
+        parts.push(format!("thinking {thinking_level}"));
+    }
+
+    parts.push(
+        metadata
+            .duration()
+            .map(format_turn_duration)
+            .unwrap_or_else(|| "running".to_string()),
+    );
+
+    if let Some(usage) = metadata.usage.as_ref() {
+        let mut usage_label = format!("{} in", usage.input_tokens);
+        if usage.cached_input_tokens > 0 {
+            usage_label.push_str(&format!(" ({} cached)", usage.cached_input_tokens));
+        }
+        usage_label.push_str(&format!(" / {} out", usage.output_tokens));
+        parts.push(usage_label);
+    }
+
+    if let Some(cost_micros) = metadata.cost_micros {
+        parts.push(format_cost_usd(cost_micros));
+    }
+
+    dim(&format!("│ {}", parts.join(" · ")))
+}
+
+fn format_clock_utc(time: SystemTime) -> String {
+    let seconds_of_day = time
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs() % 86_400)
+        .unwrap_or(0);
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds_of_day / 3_600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+fn format_turn_duration(duration: Duration) -> String {
+    let total_ms = duration.as_millis();
+    if total_ms < 60_000 {
+        format!("{}.{}s", total_ms / 1_000, total_ms % 1_000 / 100)
+    } else {
+        let total_secs = duration.as_secs();
+        format!("{}m{:02}s", total_secs / 60, total_secs % 60)
+    }
+}
+
 fn separator_line(width: usize) -> String {
     let max = width.max(10);
     dim(&"─".repeat(max))
@@ -924,6 +2267,35 @@ fn spinner_glyph() -> String {
     FRAMES[(index / 120 % 4) as usize].to_string()
 }
 
+/// Submits `prompt` as if typed into the editor, keeping the render ticker
+/// alive while the run (or any queued turn) is in flight.
+fn submit_prompt(app: &Arc<Mutex<App>>, host: &Arc<RuntimeController>, prompt: String) {
+    let mut app_guard = lock_unpoisoned(app);
+    app_guard.on_input_replace(prompt);
+
+    let mut submit_host = Arc::clone(host);
+    app_guard.on_submit(&mut submit_host);
+
+    if matches!(app_guard.mode, Mode::Running { .. }) || !host.queued_turns().is_empty() {
+        let app_for_spinner = Arc::clone(app);
+        let host_for_spinner = Arc::clone(host);
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_millis(120));
+
+            let running = {
+                let app = lock_unpoisoned(&app_for_spinner);
+                matches!(app.mode, Mode::Running { .. })
+            } || !host_for_spinner.queued_turns().is_empty();
+            if !running {
+                break;
+            }
+
+            let mut host = host_for_spinner.clone();
+            host.request_render();
+        });
+    }
+}
+
 fn render_message_lines(app: &App, message: &Message, width: usize, lines: &mut Vec<String>) {
     let role_prefix = message_role_prefix(message);
 
@@ -935,7 +2307,10 @@ fn render_message_lines(app: &App, message: &Message, width: usize, lines: &mut
     match message.role {
         Role::Assistant => {
             append_wrapped_text(lines, width, &format!("{role_prefix}:"), "", "");
-            let markdown_lines = render_markdown_lines(width.saturating_sub(2), &message.content);
+            let markdown_lines = render_markdown_lines(
+                width.saturating_sub(2),
+                &display_text(app, &message.content),
+            );
             for line in markdown_lines {
                 lines.push(format!("  {line}"));
             }
@@ -963,8 +2338,7 @@ fn render_message_lines(app: &App, message: &Message, width: usize, lines: &mut
 fn message_display_lines(app: &App, message: &Message) -> Vec<String> {
     match message.role {
         Role::Tool => tool_message_display_lines(app, message),
-        _ => message
-            .content
+        _ => display_text(app, &message.content)
             .split('\n')
             .map(ToString::to_string)
             .collect(),
@@ -972,8 +2346,7 @@ fn message_display_lines(app: &App, message: &Message) -> Vec<String> {
 }
 
 fn tool_message_display_lines(app: &App, message: &Message) -> Vec<String> {
-    let fallback_lines: Vec<String> = message
-        .content
+    let fallback_lines: Vec<String> = display_text(app, &message.content)
         .split('\n')
         .map(ToString::to_string)
         .collect();
@@ -993,37 +2366,369 @@ fn tool_message_display_lines(app: &App, message: &Message) -> Vec<String> {
                 return fallback_lines;
             };
 
-            vec![format_tool_started_line(tool_name, arguments)]
+            let line = format_tool_started_line(tool_name, arguments, &tool_path_display(app));
+            vec![display_text(app, &line).into_owned()]
         }
         ToolMessageKind::Completed | ToolMessageKind::Failed => {
-            let Some((content, is_error)) = app.tool_call_result(run_id, call_id) else {
+            let Some((content, is_error)) = display_tool_result(app, run_id, call_id) else {
                 return fallback_lines;
             };
 
             let mut lines = Vec::new();
             let status = if is_error { "failed" } else { "completed" };
             lines.push(dim(&format!("{tool_name} {status}")));
-            lines.extend(render_value_content(content));
+            match (tool_name, is_error) {
+                ("git_diff", false) => lines.extend(render_git_diff_content(&content)),
+                _ => lines.extend(render_value_content(&content)),
+            }
             lines
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ToolMessageKind {
-    Started,
-    Completed,
-    Failed,
+/// Output lines shown by a collapsed tool card before the "more lines" note.
+const TOOL_CARD_COLLAPSED_OUTPUT_LINES: usize = 6;
+
+/// A tool call rendered as one transcript card, anchored at its `started`
+/// message; the matching `completed`/`failed` message is folded into it.
+struct ToolCard<'a> {
+    run_id: RunId,
+    call_id: &'a str,
+    tool_name: &'a str,
+    arguments: &'a Value,
 }
 
-fn parse_tool_timeline_message(content: &str) -> Option<(&str, &str, ToolMessageKind)> {
-    let body = content.strip_prefix("Tool ")?;
-    let (tool_name, call_part) = body.split_once(" (")?;
-    let (call_id, suffix) = call_part.split_once(") ")?;
+impl ToolCard<'_> {
+    fn id(&self) -> ToolCardId {
+        (self.run_id, self.call_id.to_string())
+    }
+}
 
-    let kind = if suffix.starts_with("started") {
-        ToolMessageKind::Started
-    } else if suffix.starts_with("completed") {
+/// Returns the card for a live `started` tool message. Restored sessions and
+/// calls without recorded arguments keep the plain timeline rows.
+fn tool_card_for_message<'a>(app: &'a App, message: &'a Message) -> Option<ToolCard<'a>> {
+    if message.role != Role::Tool {
+        return None;
+    }
+
+    let run_id = message.run_id?;
+    let (tool_name, call_id, kind) = parse_tool_timeline_message(message.content.as_str())?;
+    if kind != ToolMessageKind::Started {
+        return None;
+    }
+
+    Some(ToolCard {
+        run_id,
+        call_id,
+        tool_name,
+        arguments: app.tool_call_arguments(run_id, call_id)?,
+    })
+}
+
+fn tool_finished_message_id(message: &Message) -> Option<(RunId, &str)> {
+    if message.role != Role::Tool {
+        return None;
+    }
+
+    let run_id = message.run_id?;
+    match parse_tool_timeline_message(message.content.as_str())? {
+        (_, call_id, ToolMessageKind::Completed | ToolMessageKind::Failed) => {
+            Some((run_id, call_id))
+        }
+        (_, _, ToolMessageKind::Started) => None,
+    }
+}
+
+/// Lists the transcript indices that transcript focus can select: user
+/// prompts, assistant replies, and tool cards.
+fn transcript_item_indices(app: &App) -> Vec<usize> {
+    app.transcript
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| match message.role {
+            Role::User | Role::Assistant => true,
+            Role::Tool => tool_card_for_message(app, message).is_some(),
+            Role::System => false,
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Renders a selected message two columns in, behind the selection marker.
+fn render_selected_message_lines(
+    app: &App,
+    message: &Message,
+    width: usize,
+    lines: &mut Vec<String>,
+) {
+    let mut message_lines = Vec::new();
+    render_message_lines(app, message, width.saturating_sub(2), &mut message_lines);
+    for (index, line) in message_lines.into_iter().enumerate() {
+        let marker = if index == 0 {
+            format!("{} ", cyan("›"))
+        } else {
+            "  ".to_string()
+        };
+        lines.push(format!("{marker}{line}"));
+    }
+}
+
+/// Renders a card header (focus marker, key arguments, status, duration)
+/// followed by the call's output, truncated unless the card is expanded.
+/// `full_output` replaces a capped result's content once it has been read.
+#[allow(clippy::too_many_arguments)]
+fn render_tool_card_lines(
+    app: &App,
+    card: &ToolCard<'_>,
+    width: usize,
+    expanded: bool,
+    full_output: Option<&str>,
+    selected: bool,
+    bash_cells: &mut HashMap<ToolCardId, BashCell>,
+    lines: &mut Vec<String>,
+) {
+    if card.tool_name == "bash" {
+        render_bash_cell_lines(
+            app,
+            card,
+            width,
+            expanded,
+            full_output,
+            selected,
+            bash_cells,
+            lines,
+        );
+        return;
+    }
+
+    let result = card_tool_result(app, card, full_output);
+    let status = match &result {
+        Some((_, false)) => green("done"),
+        Some((_, true)) => red("failed"),
+        None if app.mode
+            == (Mode::Running {
+                run_id: card.run_id,
+            }) =>
+        {
+            yellow("running")
+        }
+        None => dim("no result"),
+    };
+
+    let mut header = format!(
+        "{} {} {} ",
+        if expanded { "▾" } else { "▸" },
+        display_text(
+            app,
+            &format_tool_started_line(card.tool_name, card.arguments, &tool_path_display(app))
+        ),
+        dim("·"),
+    );
+    header.push_str(&status);
+    if let Some(duration) = app
+        .tool_call_timing(card.run_id, card.call_id)
+        .and_then(|timing| timing.duration())
+    {
+        header.push_str(&dim(&format!(" · {}", format_turn_duration(duration))));
+    }
+    let focus_prefix = if selected {
+        format!("{} ", cyan("›"))
+    } else {
+        "  ".to_string()
+    };
+    append_wrapped_text(lines, width, &header, &focus_prefix, "    ");
+
+    let mut output = render_subagent_step_lines(
+        app.subagent_steps(card.run_id, card.call_id),
+        &tool_path_display(app),
+    )
+    .into_iter()
+    .map(|line| display_text(app, &line).into_owned())
+    .collect::<Vec<_>>();
+    match (card.tool_name, result) {
+        (_, None) if output.is_empty() => return,
+        (_, None) => {}
+        ("git_diff", Some((content, false))) => output.extend(render_git_diff_content(&content)),
+        (_, Some((content, _))) => output.extend(render_value_content(&content)),
+    }
+    let shown = if expanded {
+        output.len()
+    } else {
+        output.len().min(TOOL_CARD_COLLAPSED_OUTPUT_LINES)
+    };
+
+    let body_prefix = format!("  {} ", dim("│"));
+    for line in &output[..shown] {
+        append_wrapped_text(lines, width, line, &body_prefix, &body_prefix);
+    }
+
+    let hidden = output.len() - shown;
+    if hidden > 0 {
+        let hint = if selected { " · enter to expand" } else { "" };
+        lines.push(format!(
+            "{body_prefix}{}",
+            dim(&format!(
+                "… {hidden} more line{}{hint}",
+                if hidden == 1 { "" } else { "s" }
+            ))
+        ));
+    }
+}
+
+/// Renders a `bash` call as a code cell: the highlighted command above its
+/// exit status, duration and output. Collapsed cells keep the newest
+/// output lines. The card's cell is reused from `bash_cells` while its
+/// command is unchanged.
+#[allow(clippy::too_many_arguments)]
+fn render_bash_cell_lines(
+    app: &App,
+    card: &ToolCard<'_>,
+    width: usize,
+    expanded: bool,
+    full_output: Option<&str>,
+    selected: bool,
+    bash_cells: &mut HashMap<ToolCardId, BashCell>,
+    lines: &mut Vec<String>,
+) {
+    let command = argument_string(card.arguments, "command").unwrap_or("<missing command>");
+    let command = display_text(app, command);
+    let id = card.id();
+    if bash_cells
+        .get(&id)
+        .is_none_or(|cached| cached.command != *command)
+    {
+        let mut cell = CodeCell::new(command.as_ref(), Some("bash"));
+        cell.set_theme(Some(code_cell_theme()));
+        cell.set_collapsed_output_lines(TOOL_CARD_COLLAPSED_OUTPUT_LINES);
+        bash_cells.insert(
+            id.clone(),
+            BashCell {
+                command: command.into_owned(),
+                cell,
+            },
+        );
+    }
+    let cell = &mut bash_cells
+        .get_mut(&id)
+        .expect("bash cell inserted above")
+        .cell;
+    cell.set_label(Some(format!("{} bash", if expanded { "▾" } else { "▸" })));
+    cell.set_collapsed(!expanded);
+    let (status, output) = match card_tool_result(app, card, full_output) {
+        Some((content, is_error)) => parse_bash_result(&content, is_error),
+        None if app.mode
+            == (Mode::Running {
+                run_id: card.run_id,
+            }) =>
+        {
+            (CodeCellStatus::Running, String::new())
+        }
+        None => (
+            CodeCellStatus::Failed("no result".to_string()),
+            String::new(),
+        ),
+    };
+    cell.set_status(status);
+    cell.set_output(output);
+    cell.set_duration(
+        app.tool_call_timing(card.run_id, card.call_id)
+            .and_then(|timing| timing.duration()),
+    );
+
+    for (index, line) in cell.render(width.saturating_sub(2)).into_iter().enumerate() {
+        let prefix = if index == 0 && selected {
+            format!("{} ", cyan("›"))
+        } else {
+            "  ".to_string()
+        };
+        lines.push(format!("{prefix}{line}"));
+    }
+}
+
+/// Splits a `bash` result into its status and the command output. Results
+/// start with `status: exit_code=N` (or a timeout/shell note) followed by
+/// `stdout:`/`stderr:` or `output:` sections; other content (a failure to
+/// launch) is shown as output.
+fn parse_bash_result(content: &Value, is_error: bool) -> (CodeCellStatus, String) {
+    let content = match content {
+        Value::String(content) => content.clone(),
+        other => other.to_string(),
+    };
+    let fallback = if is_error {
+        CodeCellStatus::Failed("failed".to_string())
+    } else {
+        CodeCellStatus::Exited(0)
+    };
+    let Some((status_line, rest)) = content
+        .strip_prefix("status: ")
+        .map(|rest| rest.split_once('\n').unwrap_or((rest, "")))
+    else {
+        return (fallback, content);
+    };
+
+    let status = match status_line.strip_prefix("exit_code=") {
+        Some(code) => code.parse().map_or(fallback, CodeCellStatus::Exited),
+        None => CodeCellStatus::Failed(status_line.to_string()),
+    };
+    let mut output = Vec::new();
+    let mut lines = rest.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        match line {
+            "stdout:" | "output:" => {}
+            // An empty trailing stderr section.
+            "stderr:" if lines.peek().is_none_or(|next| next.is_empty()) => {}
+            _ => output.push(line),
+        }
+    }
+    (status, output.join("\n").trim_end().to_string())
+}
+
+fn code_cell_theme() -> CodeCellTheme {
+    CodeCellTheme {
+        border: Box::new(dim),
+        label: Box::new(cyan),
+        success: Box::new(green),
+        failure: Box::new(red),
+        note: Box::new(dim),
+    }
+}
+
+/// One indented line per sub-agent tool call, shown above the
+/// `spawn_agent` result.
+fn render_subagent_step_lines(steps: &[SubagentStep], paths: &PathDisplay) -> Vec<String> {
+    steps
+        .iter()
+        .map(|step| {
+            let status = match step.outcome {
+                Some(false) => green("done"),
+                Some(true) => red("failed"),
+                None => yellow("running"),
+            };
+            format!(
+                "  {} {} {} {status}",
+                dim("↳"),
+                format_tool_started_line(&step.tool_name, &step.arguments, paths),
+                dim("·"),
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToolMessageKind {
+    Started,
+    Completed,
+    Failed,
+}
+
+fn parse_tool_timeline_message(content: &str) -> Option<(&str, &str, ToolMessageKind)> {
+    let body = content.strip_prefix("Tool ")?;
+    let (tool_name, call_part) = body.split_once(" (")?;
+    let (call_id, suffix) = call_part.split_once(") ")?;
+
+    let kind = if suffix.starts_with("started") {
+        ToolMessageKind::Started
+    } else if suffix.starts_with("completed") {
         ToolMessageKind::Completed
     } else if suffix.starts_with("failed") {
         ToolMessageKind::Failed
@@ -1034,7 +2739,25 @@ fn parse_tool_timeline_message(content: &str) -> Option<(&str, &str, ToolMessage
     Some((tool_name, call_id, kind))
 }
 
-fn format_tool_started_line(tool_name: &str, arguments: &Value) -> String {
+/// Widest a path is shown in a tool card header before it is truncated in
+/// the middle.
+const TOOL_CARD_PATH_MAX_WIDTH: usize = 60;
+
+/// Shortens tool paths relative to the workspace and the home directory.
+fn tool_path_display(app: &App) -> PathDisplay {
+    let paths = PathDisplay::new().with_workspace_root(app.workspace_root());
+    match std::env::var_os("HOME") {
+        Some(home) => paths.with_home(home),
+        None => paths,
+    }
+}
+
+fn format_tool_started_line(tool_name: &str, arguments: &Value, paths: &PathDisplay) -> String {
+    let path = |fallback: &'static str| {
+        argument_string(arguments, "path").map_or(fallback.to_string(), |path| {
+            paths.display(path, TOOL_CARD_PATH_MAX_WIDTH)
+        })
+    };
     match tool_name {
         "bash" => {
             let command = argument_string(arguments, "command").unwrap_or("<missing command>");
@@ -1045,17 +2768,48 @@ fn format_tool_started_line(tool_name: &str, arguments: &Value) -> String {
             }
             line
         }
-        "read" => {
-            let path = argument_string(arguments, "path").unwrap_or("<missing path>");
-            format!("read {path}")
+        "read" => format!("read {}", path("<missing path>")),
+        "write" => format!("write {}", path("<missing path>")),
+        "edit" => format!("edit {}", path("<missing path>")),
+        "update_plan" => {
+            let steps = arguments
+                .get("plan")
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+            format!("update_plan {}", dim(&format!("({steps} steps)")))
+        }
+        "git_diff" => {
+            let mut line = "git diff".to_string();
+            if arguments.get("staged").and_then(Value::as_bool) == Some(true) {
+                line.push_str(" --staged");
+            }
+            if let Some(path) = argument_string(arguments, "path") {
+                line.push(' ');
+                line.push_str(&paths.display(path, TOOL_CARD_PATH_MAX_WIDTH));
+            }
+            line
+        }
+        "git_commit" => {
+            let message = argument_string(arguments, "message").unwrap_or("<missing message>");
+            let summary = message.lines().next().unwrap_or_default();
+            format!("git commit {}", dim(&format!("({summary})")))
+        }
+        "restart_shell" => "restart shell".to_string(),
+        "bash_background" => {
+            let command = argument_string(arguments, "command").unwrap_or("<missing command>");
+            format!("$ {command} {}", dim("(background)"))
         }
-        "write" => {
-            let path = argument_string(arguments, "path").unwrap_or("<missing path>");
-            format!("write {path}")
+        "process_status" => match argument_string(arguments, "id") {
+            Some(id) => format!("process status {id}"),
+            None => "process status".to_string(),
+        },
+        "process_kill" => {
+            let id = argument_string(arguments, "id").unwrap_or("<missing id>");
+            format!("process kill {id}")
         }
-        "edit" => {
-            let path = argument_string(arguments, "path").unwrap_or("<missing path>");
-            format!("edit {path}")
+        "process_logs" => {
+            let id = argument_string(arguments, "id").unwrap_or("<missing id>");
+            format!("process logs {id}")
         }
         "apply_patch" => {
             let input = argument_string(arguments, "input").unwrap_or_default();
@@ -1064,6 +2818,10 @@ fn format_tool_started_line(tool_name: &str, arguments: &Value) -> String {
                 dim(&format!("({} chars)", input.chars().count()))
             )
         }
+        "spawn_agent" => {
+            let task = argument_string(arguments, "task").unwrap_or("<missing task>");
+            format!("agent {}", task.lines().next().unwrap_or_default())
+        }
         _ => format!("{tool_name} {arguments}"),
     }
 }
@@ -1085,6 +2843,71 @@ fn render_value_content(value: &Value) -> Vec<String> {
     }
 }
 
+/// Renders a `git_diff` result as per-file change counts followed by the
+/// colourised unified diff; falls back to plain content if it is not JSON.
+fn render_git_diff_content(value: &Value) -> Vec<String> {
+    let parsed = match value {
+        Value::String(content) => serde_json::from_str::<Value>(content).ok(),
+        other => Some(other.clone()),
+    };
+    let Some((parsed, diff)) = parsed
+        .as_ref()
+        .and_then(|parsed| Some((parsed, parsed.get("diff")?)))
+    else {
+        return render_value_content(value);
+    };
+
+    let files = parsed
+        .get("files")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if files.is_empty() {
+        return vec![dim("<no changes>")];
+    }
+
+    let mut lines = Vec::new();
+    for file in files {
+        let path = file.get("path").and_then(Value::as_str).unwrap_or("?");
+        let counts = match (
+            file.get("additions").and_then(Value::as_u64),
+            file.get("deletions").and_then(Value::as_u64),
+        ) {
+            (Some(additions), Some(deletions)) => format!(
+                "{} {}",
+                green(&format!("+{additions}")),
+                red(&format!("-{deletions}"))
+            ),
+            _ => dim("binary"),
+        };
+        lines.push(format!("{path} {counts}"));
+    }
+
+    lines.extend(
+        diff.as_str()
+            .unwrap_or_default()
+            .lines()
+            .map(colorize_diff_line),
+    );
+    lines
+}
+
+fn colorize_diff_line(line: &str) -> String {
+    if line.starts_with("+++") || line.starts_with("---") {
+        bold(line)
+    } else if line.starts_with('+') {
+        green(line)
+    } else if line.starts_with('-') {
+        red(line)
+    } else if line.starts_with("@@") {
+        cyan(line)
+    } else if line.starts_with("diff ") || line.starts_with("index ") {
+        dim(line)
+    } else {
+        line.to_string()
+    }
+}
+
 fn argument_string<'a>(arguments: &'a Value, key: &str) -> Option<&'a str> {
     arguments
         .get(key)
@@ -1252,6 +3075,7 @@ fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
     use std::sync::{Arc, Mutex};
 
     use tape_tui::{Terminal, TUI};
@@ -1524,7 +3348,7 @@ mod tests {
 
     #[test]
     fn render_mode_line_is_left_anchored() {
-        let line = strip_ansi(&render_mode_line(30, ViewMode::Plan));
+        let line = strip_ansi(&render_mode_line(30, ViewMode::Plan.label()));
         assert!(line.starts_with("──"));
         assert!(line.contains(" plan "));
         assert_eq!(line.chars().count(), 30);
@@ -1560,6 +3384,16 @@ mod tests {
         assert_eq!(line, "/tmp/other (main)");
     }
 
+    #[test]
+    fn project_instructions_line_lists_loaded_files() {
+        assert_eq!(render_project_instructions_line(&[]), None);
+        let labels = vec!["../AGENTS.md".to_string(), "AGENTS.md".to_string()];
+        assert_eq!(
+            render_project_instructions_line(&labels).map(|line| strip_ansi(&line)),
+            Some("instructions: ../AGENTS.md, AGENTS.md".to_string())
+        );
+    }
+
     #[test]
     fn provider_metadata_includes_provider_model_and_thinking() {
         let profile = ProviderProfile {
@@ -1599,12 +3433,278 @@ mod tests {
         assert_eq!(line, "provider mock • model gpt-5-codex");
     }
 
+    #[test]
+    fn turn_metadata_line_includes_clock_model_thinking_duration_and_usage() {
+        let started_at = UNIX_EPOCH + Duration::from_secs(86_400 * 3 + 13 * 3_600 + 5 * 60 + 9);
+        let mut metadata = TurnMetadata::new(
+            &ProviderProfile {
+                provider_id: "codex-api".to_string(),
+                model_id: "gpt-5.1-codex".to_string(),
+                thinking_level: Some("high".to_string()),
+            },
+            started_at,
+        );
+
+        assert_eq!(
+            strip_ansi(&format_turn_metadata_line(&metadata)),
+            "│ 13:05:09 · gpt-5.1-codex · thinking high · running"
+        );
+
+        metadata.finished_at = Some(started_at + Duration::from_millis(4_250));
+        metadata.usage = Some(crate::provider::TokenUsage {
+            input_tokens: 1200,
+            cached_input_tokens: 64,
+            output_tokens: 340,
+        });
+
+        assert_eq!(
+            strip_ansi(&format_turn_metadata_line(&metadata)),
+            "│ 13:05:09 · gpt-5.1-codex · thinking high · 4.2s · 1200 in (64 cached) / 340 out"
+        );
+
+        metadata.cost_micros = Some(4_870);
+        assert!(strip_ansi(&format_turn_metadata_line(&metadata)).ends_with(" · $0.0049"));
+    }
+
+    #[test]
+    fn cost_label_shows_run_and_session_spend_only_when_priced() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 2 };
+        assert_eq!(render_cost_label(&app, &app.mode.clone()), None);
+
+        app.set_pricing_table(crate::pricing::PricingTable::new(
+            [(
+                "gpt-5.1-codex".to_string(),
+                crate::pricing::ModelPricing::from_usd_per_million(1.0, None, 1.0)
+                    .expect("rates should be valid"),
+            )]
+            .into_iter()
+            .collect(),
+        ));
+        assert_eq!(
+            render_cost_label(&app, &app.mode.clone()).as_deref(),
+            Some("$0.0000 session")
+        );
+
+        app.on_run_profile(
+            2,
+            &ProviderProfile {
+                provider_id: "codex-api".to_string(),
+                model_id: "gpt-5.1-codex".to_string(),
+                thinking_level: None,
+            },
+            UNIX_EPOCH,
+        );
+        app.on_run_usage(
+            2,
+            &crate::provider::TokenUsage {
+                input_tokens: 1_500,
+                cached_input_tokens: 0,
+                output_tokens: 1_000,
+            },
+        );
+        assert_eq!(
+            render_cost_label(&app, &app.mode.clone()).as_deref(),
+            Some("$0.0025 run / $0.0025 session")
+        );
+        assert_eq!(
+            render_cost_label(&app, &Mode::Idle).as_deref(),
+            Some("$0.0025 session")
+        );
+    }
+
+    #[test]
+    fn plan_panel_renders_progress_markers_and_collapses_to_current_step() {
+        use crate::tools::PlanStep;
+
+        let plan = Plan {
+            explanation: Some("Fix the parser".to_string()),
+            steps: vec![
+                PlanStep {
+                    step: "Reproduce".to_string(),
+                    status: PlanStepStatus::Completed,
+                },
+                PlanStep {
+                    step: "Patch tokenizer".to_string(),
+                    status: PlanStepStatus::InProgress,
+                },
+                PlanStep {
+                    step: "Add tests".to_string(),
+                    status: PlanStepStatus::Pending,
+                },
+            ],
+        };
+
+        let expanded: Vec<String> = render_plan_lines(80, &plan, true)
+            .iter()
+            .map(|line| strip_ansi(line))
+            .collect();
+        assert_eq!(
+            expanded,
+            vec![
+                "Plan · 1/3 done (ctrl+l to collapse)".to_string(),
+                "  Fix the parser".to_string(),
+                "  ✓ Reproduce".to_string(),
+                "  ▸ Patch tokenizer".to_string(),
+                "  ○ Add tests".to_string(),
+            ]
+        );
+
+        let collapsed: Vec<String> = render_plan_lines(80, &plan, false)
+            .iter()
+            .map(|line| strip_ansi(line))
+            .collect();
+        assert_eq!(
+            collapsed,
+            vec!["Plan · 1/3 done ▸ Patch tokenizer (ctrl+l to expand)".to_string()]
+        );
+    }
+
+    #[test]
+    fn tool_confirmation_lines_show_keys_and_cap_long_diffs() {
+        let mut diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n".to_string();
+        for index in 0..TOOL_CONFIRMATION_MAX_DIFF_LINES {
+            diff.push_str(&format!("+line {index}\n"));
+        }
+        let confirmation = ToolConfirmation {
+            run_id: 1,
+            call_id: "call-1".to_string(),
+            tool_name: "edit".to_string(),
+            diff,
+        };
+
+        let lines: Vec<String> = render_tool_confirmation_lines(80, &confirmation)
+            .iter()
+            .map(|line| strip_ansi(line))
+            .collect();
+        assert_eq!(lines[0], "Preview: edit — y approve · n reject");
+        assert_eq!(lines[1], "  --- a/src/lib.rs");
+        assert_eq!(lines.len(), TOOL_CONFIRMATION_MAX_DIFF_LINES + 2);
+        assert_eq!(lines.last().map(String::as_str), Some("  … 2 more lines"));
+    }
+
+    #[test]
+    fn background_process_lines_show_id_elapsed_and_first_command_line() {
+        let processes = vec![BackgroundProcessInfo {
+            id: "proc-2".to_string(),
+            pid: 42,
+            command: "npm run dev\n# second line".to_string(),
+            cwd: std::path::PathBuf::from("/work"),
+            status: crate::tools::process::BackgroundProcessStatus::Running,
+            elapsed: Duration::from_secs(75),
+        }];
+
+        let lines: Vec<String> = render_background_process_lines(80, &processes)
+            .iter()
+            .map(|line| strip_ansi(line))
+            .collect();
+        assert_eq!(lines, vec!["[bg] proc-2 · 75s · npm run dev".to_string()]);
+    }
+
+    #[test]
+    fn queued_turn_lines_number_each_pending_prompt_and_show_first_line() {
+        let turns = vec![
+            QueuedTurn {
+                id: 4,
+                prompt: "first prompt\nwith details".to_string(),
+            },
+            QueuedTurn {
+                id: 7,
+                prompt: "second".to_string(),
+            },
+        ];
+
+        let lines: Vec<String> = render_queued_turn_lines(80, &turns)
+            .iter()
+            .map(|line| strip_ansi(line))
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "[queued] 1. first prompt".to_string(),
+                "[queued] 2. second".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn turn_duration_switches_to_minutes_after_one_minute() {
+        assert_eq!(format_turn_duration(Duration::from_millis(950)), "0.9s");
+        assert_eq!(format_turn_duration(Duration::from_secs(65)), "1m05s");
+    }
+
     #[test]
     fn view_mode_cycles_between_plan_and_build() {
         assert_eq!(ViewMode::Plan.next(), ViewMode::Build);
         assert_eq!(ViewMode::Build.next(), ViewMode::Plan);
     }
 
+    #[test]
+    fn run_metrics_label_shows_elapsed_first_token_and_rate() {
+        let started_at = Instant::now();
+        let mut metrics = RunMetrics::new(1, started_at);
+        let at = |millis| started_at + Duration::from_millis(millis);
+        assert_eq!(render_run_metrics_label(&metrics, None, at(700)), "0.7s");
+
+        metrics.first_chunk_at = Some(at(800));
+        metrics.streamed_chars = 300;
+        assert_eq!(
+            render_run_metrics_label(&metrics, None, at(1_000)),
+            "1.0s · first token 0.8s"
+        );
+        assert_eq!(
+            render_run_metrics_label(&metrics, None, at(2_800)),
+            "2.8s · first token 0.8s · 150 chars/s"
+        );
+
+        let mut metadata = TurnMetadata::new(
+            &ProviderProfile {
+                provider_id: "mock".to_string(),
+                model_id: "mock-model".to_string(),
+                thinking_level: None,
+            },
+            SystemTime::now(),
+        );
+        metadata.usage = Some(crate::provider::TokenUsage {
+            input_tokens: 10,
+            cached_input_tokens: 0,
+            output_tokens: 80,
+        });
+        assert_eq!(
+            render_run_metrics_label(&metrics, Some(&metadata), at(2_800)),
+            "2.8s · first token 0.8s · 40 tok/s"
+        );
+    }
+
+    #[test]
+    fn status_line_previews_streaming_tool_call_until_it_starts() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 7 };
+        app.on_tool_call_streaming(
+            7,
+            "call-1",
+            "bash",
+            serde_json::json!({ "command": "cargo te" }),
+        );
+
+        let label = app.streaming_tool_call().map(format_streaming_tool_call);
+        let status = strip_ansi(&render_status_line(&app.mode, label.as_deref()));
+        assert!(status.ends_with("bash: running `cargo te…`"), "{status}");
+
+        app.on_tool_call_streaming(7, "call-2", "apply_patch", serde_json::json!({}));
+        let label = app.streaming_tool_call().map(format_streaming_tool_call);
+        assert_eq!(
+            label.as_deref().map(strip_ansi).as_deref(),
+            Some("apply_patch: preparing…")
+        );
+
+        app.on_tool_call_started(7, "call-2", "apply_patch", &serde_json::json!({}));
+        assert!(app.streaming_tool_call().is_none());
+        let status = strip_ansi(&render_status_line(&app.mode, None));
+        assert!(status.contains("Working"), "{status}");
+    }
+
     #[test]
     fn tool_message_display_lines_include_clean_bash_started_command() {
         let mut app = App::new();
@@ -1662,14 +3762,584 @@ mod tests {
     }
 
     #[test]
-    fn tool_message_display_lines_leave_non_started_entries_unchanged() {
-        let app = App::new();
-        let message = Message {
-            role: Role::Tool,
-            content: "Tool bash (call-1) completed".to_string(),
-            streaming: false,
-            run_id: Some(7),
-        };
+    fn tool_paths_are_shown_relative_to_the_workspace() {
+        let mut app = App::new();
+        app.set_workspace_root("/work/project");
+        app.mode = Mode::Running { run_id: 7 };
+        app.on_tool_call_started(
+            7,
+            "call-1",
+            "read",
+            &serde_json::json!({ "path": "/work/project/src/lib.rs" }),
+        );
+        app.on_tool_call_started(
+            7,
+            "call-2",
+            "edit",
+            &serde_json::json!({ "path": format!("/srv/{}main.rs", "nested/".repeat(12)) }),
+        );
+
+        let lines = app
+            .transcript
+            .iter()
+            .filter(|message| message.role == Role::Tool)
+            .map(|message| strip_ansi(&tool_message_display_lines(&app, message)[0]))
+            .collect::<Vec<_>>();
+        assert_eq!(lines[0], "read src/lib.rs");
+        assert_eq!(
+            lines[1],
+            "edit /srv/nested/nested/nested/nes…d/nested/nested/nested/main.rs"
+        );
+    }
+
+    #[test]
+    fn git_diff_results_render_file_counts_and_colourised_hunks() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 7 };
+        app.on_tool_call_started(
+            7,
+            "call-1",
+            "git_diff",
+            &serde_json::json!({ "path": "src/lib.rs", "staged": true }),
+        );
+        let content = serde_json::json!({
+            "staged": true,
+            "path": "src/lib.rs",
+            "files": [{ "path": "src/lib.rs", "additions": 1, "deletions": 1 }],
+            "diff": "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n",
+            "truncated": false,
+        })
+        .to_string();
+        app.on_tool_call_finished(
+            7,
+            "git_diff",
+            "call-1",
+            false,
+            &serde_json::json!(content),
+            &content,
+        );
+
+        let started = app
+            .transcript
+            .iter()
+            .find(|message| message.role == Role::Tool && message.content.contains("started"))
+            .expect("started tool message should exist");
+        assert_eq!(
+            strip_ansi(&tool_message_display_lines(&app, started)[0]),
+            "git diff --staged src/lib.rs"
+        );
+
+        let completed = app
+            .transcript
+            .iter()
+            .find(|message| message.role == Role::Tool && message.content.contains("completed"))
+            .expect("completed tool message should exist");
+        let lines = tool_message_display_lines(&app, completed);
+        assert_eq!(strip_ansi(&lines[1]), "src/lib.rs +1 -1");
+        assert!(lines.contains(&red("-old")));
+        assert!(lines.contains(&green("+new")));
+        assert!(lines.contains(&cyan("@@ -1 +1 @@")));
+    }
+
+    #[test]
+    fn change_review_lists_files_and_scrolls_selected_diff() {
+        let review = vec![
+            FileChange {
+                path: "/ws/src/lib.rs".into(),
+                label: "src/lib.rs".to_string(),
+                status: FileChangeStatus::Modified,
+                additions: 2,
+                deletions: 1,
+                diff: "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1,2 @@\n-old\n+new\n+more\n"
+                    .to_string(),
+                binary: false,
+            },
+            FileChange {
+                path: "/ws/logo.png".into(),
+                label: "logo.png".to_string(),
+                status: FileChangeStatus::Added,
+                additions: 0,
+                deletions: 0,
+                diff: String::new(),
+                binary: true,
+            },
+        ];
+
+        let rendered = render_change_review_lines(80, 20, &review, 0, 0);
+        let plain: Vec<String> = rendered.lines.iter().map(|line| strip_ansi(line)).collect();
+        assert!(plain[0].starts_with("Changes · 2 files"));
+        assert_eq!(plain[2], "› modified  src/lib.rs +2 -1");
+        assert_eq!(plain[3], "  added     logo.png binary");
+        assert!(rendered.lines.contains(&red("-old")));
+        assert!(rendered.lines.contains(&green("+new")));
+
+        let scrolled = render_change_review_lines(80, 7, &review, 0, 99);
+        assert_eq!(scrolled.diff_scroll, 4);
+        assert_eq!(
+            strip_ansi(scrolled.lines.last().expect("diff line")),
+            "+more"
+        );
+
+        let binary = render_change_review_lines(80, 20, &review, 1, 0);
+        assert_eq!(
+            strip_ansi(binary.lines.last().expect("binary line")),
+            "<binary file>"
+        );
+    }
+
+    fn app_with_finished_bash_call(output_lines: usize) -> App {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 7 };
+        let started_at = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let output = (1..=output_lines)
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        app.on_tool_call_started_at(7, "call-1", started_at);
+        app.on_tool_call_started(
+            7,
+            "call-1",
+            "bash",
+            &serde_json::json!({ "command": "seq 10" }),
+        );
+        app.on_tool_call_finished_at(7, "call-1", started_at + Duration::from_millis(1_500));
+        app.on_tool_call_finished(
+            7,
+            "bash",
+            "call-1",
+            false,
+            &serde_json::json!(output),
+            &output,
+        );
+        app
+    }
+
+    #[test]
+    fn bash_cards_reuse_their_cell_across_result_and_expansion_changes() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 7 };
+        app.on_tool_call_started(
+            7,
+            "call-1",
+            "bash",
+            &serde_json::json!({ "command": "echo hi" }),
+        );
+        let message = app.transcript[0].clone();
+        let mut cells = HashMap::new();
+
+        let card = tool_card_for_message(&app, &message).expect("started message is a card");
+        let mut running = Vec::new();
+        render_tool_card_lines(
+            &app,
+            &card,
+            80,
+            false,
+            None,
+            false,
+            &mut cells,
+            &mut running,
+        );
+        let plain: Vec<String> = running.iter().map(|line| strip_ansi(line)).collect();
+        assert!(plain[2].starts_with("  ├─ running ─"), "{:?}", plain[2]);
+        assert_eq!(cells.len(), 1);
+
+        let output = "status: exit_code=0\nstdout:\nhi";
+        app.on_tool_call_finished(
+            7,
+            "bash",
+            "call-1",
+            false,
+            &serde_json::json!(output),
+            output,
+        );
+        let card = tool_card_for_message(&app, &message).expect("started message is a card");
+        let mut finished = Vec::new();
+        render_tool_card_lines(
+            &app,
+            &card,
+            80,
+            true,
+            None,
+            false,
+            &mut cells,
+            &mut finished,
+        );
+        let plain: Vec<String> = finished.iter().map(|line| strip_ansi(line)).collect();
+        assert!(plain[0].starts_with("  ╭─ ▾ bash ─"), "{:?}", plain[0]);
+        assert_eq!(plain[1], "  │ echo hi");
+        assert!(plain[2].starts_with("  ├─ exit 0 ─"), "{:?}", plain[2]);
+        assert_eq!(plain[3], "  │ hi");
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[&card.id()].command, "echo hi");
+    }
+
+    #[test]
+    fn tool_cards_show_status_duration_and_collapse_long_output() {
+        let app = app_with_finished_bash_call(10);
+        let message = app.transcript[0].clone();
+        let card = tool_card_for_message(&app, &message).expect("started message is a card");
+
+        let mut collapsed = Vec::new();
+        render_tool_card_lines(
+            &app,
+            &card,
+            80,
+            false,
+            None,
+            false,
+            &mut HashMap::new(),
+            &mut collapsed,
+        );
+        let plain: Vec<String> = collapsed.iter().map(|line| strip_ansi(line)).collect();
+        assert!(plain[0].starts_with("  ╭─ ▸ bash ─"), "{:?}", plain[0]);
+        assert_eq!(plain[1], "  │ seq 10");
+        assert!(
+            plain[2].starts_with("  ├─ exit 0 · 1.5s ─"),
+            "{:?}",
+            plain[2]
+        );
+        assert_eq!(plain[3], "  │ … 4 earlier lines");
+        assert_eq!(plain[4], "  │ 5");
+        assert_eq!(plain.len(), 5 + TOOL_CARD_COLLAPSED_OUTPUT_LINES);
+        assert!(plain.last().expect("footer").starts_with("  ╰─"));
+
+        let mut expanded = Vec::new();
+        render_tool_card_lines(
+            &app,
+            &card,
+            80,
+            true,
+            None,
+            true,
+            &mut HashMap::new(),
+            &mut expanded,
+        );
+        let plain: Vec<String> = expanded.iter().map(|line| strip_ansi(line)).collect();
+        assert!(plain[0].starts_with("› ╭─ ▾ bash ─"), "{:?}", plain[0]);
+        assert_eq!(plain.len(), 14);
+        assert_eq!(plain[3], "  │ 1");
+        assert_eq!(plain[12], "  │ 10");
+
+        assert!(tool_card_for_message(&app, &app.transcript[1]).is_none());
+        assert_eq!(
+            tool_finished_message_id(&app.transcript[1]),
+            Some((7, "call-1"))
+        );
+    }
+
+    #[test]
+    fn bash_results_split_into_cell_status_and_output() {
+        assert_eq!(
+            parse_bash_result(
+                &serde_json::json!("status: exit_code=2\nstdout:\nok\n\nstderr:\nboom\n"),
+                true,
+            ),
+            (CodeCellStatus::Exited(2), "ok\n\nstderr:\nboom".to_string())
+        );
+        assert_eq!(
+            parse_bash_result(
+                &serde_json::json!("status: exit_code=0\nstdout:\nhi\n\nstderr:\n"),
+                false,
+            ),
+            (CodeCellStatus::Exited(0), "hi".to_string())
+        );
+        assert_eq!(
+            parse_bash_result(
+                &serde_json::json!("status: timeout after 5s\ncwd: /w\noutput:\npartial"),
+                true,
+            ),
+            (
+                CodeCellStatus::Failed("timeout after 5s".to_string()),
+                "cwd: /w\npartial".to_string()
+            )
+        );
+        assert_eq!(
+            parse_bash_result(&serde_json::json!("Failed to launch bash command"), true),
+            (
+                CodeCellStatus::Failed("failed".to_string()),
+                "Failed to launch bash command".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn expanded_capped_cards_show_the_saved_full_output() {
+        let mut app = app_with_finished_bash_call(2);
+        app.on_tool_output_saved(7, "call-1", PathBuf::from("/w/.agent/tool-output/call-1"));
+        assert_eq!(
+            app.tool_output_file(7, "call-1"),
+            Some(Path::new("/w/.agent/tool-output/call-1"))
+        );
+        let message = app.transcript[0].clone();
+        let card = tool_card_for_message(&app, &message).expect("started message is a card");
+
+        let mut focus = TranscriptFocus::default();
+        let id = card.id();
+        focus.toggle_expanded(id.clone());
+        focus.set_full_output(
+            id.clone(),
+            "status: exit_code=0\nstdout:\n1\n2\n3".to_string(),
+        );
+
+        let mut expanded = Vec::new();
+        render_tool_card_lines(
+            &app,
+            &card,
+            80,
+            true,
+            focus.full_output(&id),
+            false,
+            &mut HashMap::new(),
+            &mut expanded,
+        );
+        let plain: Vec<String> = expanded.iter().map(|line| strip_ansi(line)).collect();
+        assert_eq!(plain[3..6], ["  │ 1", "  │ 2", "  │ 3"]);
+
+        focus.toggle_expanded(id.clone());
+        assert_eq!(focus.full_output(&id), None);
+    }
+
+    #[test]
+    fn spawn_agent_cards_nest_sub_agent_tool_calls_above_the_answer() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 7 };
+        app.on_tool_call_started(
+            7,
+            "call-1",
+            "spawn_agent",
+            &serde_json::json!({ "task": "Find the parser bug\nand explain it" }),
+        );
+        app.on_subagent_tool_call_started(
+            7,
+            "call-1",
+            "child-1",
+            "read",
+            serde_json::json!({ "path": "src/parser.rs" }),
+        );
+        let message = app.transcript[0].clone();
+        let card = tool_card_for_message(&app, &message).expect("started message is a card");
+
+        let mut running = Vec::new();
+        render_tool_card_lines(
+            &app,
+            &card,
+            80,
+            false,
+            None,
+            false,
+            &mut HashMap::new(),
+            &mut running,
+        );
+        let plain: Vec<String> = running.iter().map(|line| strip_ansi(line)).collect();
+        assert_eq!(
+            plain,
+            vec![
+                "  ▸ agent Find the parser bug · running",
+                "  │   ↳ read src/parser.rs · running",
+            ]
+        );
+
+        app.on_subagent_tool_call_finished(7, "call-1", "child-1", false);
+        app.on_tool_call_finished(
+            7,
+            "spawn_agent",
+            "call-1",
+            false,
+            &serde_json::json!("Off-by-one in next_token."),
+            "Off-by-one in next_token.",
+        );
+        let card = tool_card_for_message(&app, &message).expect("started message is a card");
+        let mut finished = Vec::new();
+        render_tool_card_lines(
+            &app,
+            &card,
+            80,
+            false,
+            None,
+            false,
+            &mut HashMap::new(),
+            &mut finished,
+        );
+        let plain: Vec<String> = finished.iter().map(|line| strip_ansi(line)).collect();
+        assert_eq!(
+            plain[1..],
+            [
+                "  │   ↳ read src/parser.rs · done",
+                "  │ Off-by-one in next_token.",
+            ]
+        );
+    }
+
+    #[test]
+    fn transcript_selection_moves_between_items_and_returns_to_editor() {
+        let items = vec![0, 2, 5];
+        let mut focus = TranscriptFocus::default();
+
+        assert!(!focus.move_selection(&items, false));
+        assert!(focus.move_selection(&items, true));
+        assert!(focus.is_selected(5));
+        focus.move_selection(&items, true);
+        focus.move_selection(&items, true);
+        focus.move_selection(&items, true);
+        assert!(focus.is_selected(0));
+
+        let revision = focus.revision;
+        focus.toggle_expanded((1, "call".to_string()));
+        assert!(focus.is_expanded(&(1, "call".to_string())));
+        assert!(focus.revision > revision);
+
+        focus.move_selection(&items, false);
+        assert!(focus.is_selected(2));
+        focus.move_selection(&items, false);
+        assert!(!focus.move_selection(&items, false));
+        assert_eq!(focus.selected, None);
+    }
+
+    #[test]
+    fn shift_tab_focuses_transcript_items_for_expand_and_rerun() {
+        let mut app = app_with_finished_bash_call(10);
+        app.transcript.insert(
+            0,
+            Message {
+                role: Role::User,
+                content: "count to ten".to_string(),
+                streaming: false,
+                run_id: None,
+            },
+        );
+        app.mode = Mode::Idle;
+
+        let app = Arc::new(Mutex::new(app));
+        let runtime = TUI::new(NullTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(NoopProvider),
+        );
+        let mut component = AppComponent::new(
+            Arc::clone(&app),
+            host,
+            ProviderProfile {
+                provider_id: "test".to_string(),
+                model_id: "test-model".to_string(),
+                thinking_level: None,
+            },
+        );
+        let key = |key_id: &str| InputEvent::Key {
+            raw: String::new(),
+            key_id: key_id.to_string(),
+            event_type: KeyEventType::Press,
+        };
+        let transcript = |component: &mut AppComponent| {
+            let (lines, _) = component.render_transcript_lines_cached(80);
+            lines
+                .iter()
+                .map(|line| strip_ansi(line))
+                .collect::<Vec<_>>()
+        };
+
+        let collapsed = transcript(&mut component);
+        assert!(collapsed
+            .iter()
+            .any(|line| line.starts_with("  ╭─ ▸ bash ─")));
+        assert!(!collapsed.iter().any(|line| line.contains("completed")));
+        assert!(!collapsed.contains(&"  │ 1".to_string()));
+
+        component.handle_event(&key("shift+tab"));
+        assert!(component.focus.is_focused(&FocusRegion::Transcript));
+        component.handle_event(&key("enter"));
+        let expanded = transcript(&mut component);
+        assert!(expanded
+            .iter()
+            .any(|line| line.starts_with("› ╭─ ▾ bash ─")));
+        assert!(expanded.contains(&"  │ 1".to_string()));
+        assert_eq!(component.editor.get_text(), "");
+
+        component.handle_event(&key("up"));
+        assert!(transcript(&mut component).contains(&"› [user] you: count to ten".to_string()));
+        component.handle_event(&key("r"));
+        assert!(component.focus.is_focused(&FocusRegion::Editor));
+        let prompts = lock_unpoisoned(&app)
+            .transcript
+            .iter()
+            .filter(|message| message.role == Role::User && message.content == "count to ten")
+            .count();
+        assert_eq!(prompts, 2);
+
+        component.handle_event(&key("shift+tab"));
+        component.handle_event(&key("escape"));
+        assert!(component.focus.is_focused(&FocusRegion::Editor));
+        assert!(transcript(&mut component)
+            .iter()
+            .any(|line| line.starts_with("  ╭─ ▾ bash ─")));
+    }
+
+    #[test]
+    fn focused_transcript_items_copy_messages_code_blocks_and_tool_output() {
+        let mut app = app_with_finished_bash_call(2);
+        app.mode = Mode::Idle;
+        app.transcript.push(Message {
+            role: Role::Assistant,
+            content: "Try:\n```\nseq 2\n```".to_string(),
+            streaming: false,
+            run_id: None,
+        });
+        let app = Arc::new(Mutex::new(app));
+        let runtime = TUI::new(NullTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(NoopProvider),
+        );
+        let mut component = AppComponent::new(
+            Arc::clone(&app),
+            Arc::clone(&host),
+            ProviderProfile {
+                provider_id: "test".to_string(),
+                model_id: "test-model".to_string(),
+                thinking_level: None,
+            },
+        );
+        let key = |key_id: &str| InputEvent::Key {
+            raw: String::new(),
+            key_id: key_id.to_string(),
+            event_type: KeyEventType::Press,
+        };
+
+        component.handle_event(&key("shift+tab"));
+        component.handle_event(&key("1"));
+        assert_eq!(
+            host.toast().as_deref(),
+            Some("Copied code block 1 (5 chars)")
+        );
+        component.handle_event(&key("2"));
+        assert_eq!(
+            lock_unpoisoned(&app)
+                .transcript
+                .last()
+                .map(|message| message.content.clone()),
+            Some("No code block 2 in the selected message".to_string())
+        );
+
+        component.handle_event(&key("up"));
+        component.handle_event(&key("c"));
+        assert_eq!(
+            host.toast().as_deref(),
+            Some("Copied bash output (3 chars)")
+        );
+        assert!(component.focus.is_focused(&FocusRegion::Transcript));
+    }
+
+    #[test]
+    fn tool_message_display_lines_leave_non_started_entries_unchanged() {
+        let app = App::new();
+        let message = Message {
+            role: Role::Tool,
+            content: "Tool bash (call-1) completed".to_string(),
+            streaming: false,
+            run_id: Some(7),
+        };
 
         let lines = tool_message_display_lines(&app, &message);
         assert_eq!(lines, vec!["Tool bash (call-1) completed".to_string()]);
@@ -1704,4 +4374,307 @@ mod tests {
             "$ head -c 16 /dev/urandom | xxd -p > hi.txt (timeout 5s)"
         );
     }
+
+    #[test]
+    fn transcript_sanitizes_model_and_tool_output_unless_opted_out() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 7 };
+        app.on_tool_call_started(
+            7,
+            "call-1",
+            "read",
+            &serde_json::json!({ "path": "notes\x1b]0;pwned\x07.txt" }),
+        );
+        let started = app.transcript[0].clone();
+        let reply = Message {
+            role: Role::Assistant,
+            content: "\x1b[2J\x1b[31mred\x1b[39m\x1b]0;pwned\x07 text\x07".to_string(),
+            streaming: false,
+            run_id: Some(7),
+        };
+
+        let mut lines = Vec::new();
+        render_message_lines(&app, &reply, 80, &mut lines);
+        render_message_lines(&app, &started, 80, &mut lines);
+        let rendered = lines.concat();
+        assert!(rendered.contains("\x1b[31mred\x1b[39m"));
+        assert!(!rendered.contains("\x1b[2J"));
+        assert!(!rendered.contains("\x1b]"));
+        assert!(!rendered.contains('\x07'));
+        assert_eq!(strip_ansi(&lines[2]), "read notes.txt");
+
+        app.set_sanitize_output(false);
+        let mut lines = Vec::new();
+        render_message_lines(&app, &reply, 80, &mut lines);
+        assert!(lines.concat().contains("\x1b]0;pwned\x07"));
+    }
+
+    #[test]
+    fn at_mentions_complete_from_the_workspace_index() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("docs")).expect("create docs");
+        std::fs::write(dir.path().join("docs/guide.md"), "").expect("write guide");
+        std::fs::write(dir.path().join("Cargo.toml"), "").expect("write manifest");
+        let index = WorkspaceIndex::new(dir.path());
+        index.refresh().expect("scan workspace");
+
+        let app = Arc::new(Mutex::new(App::new()));
+        let runtime = TUI::new(NullTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(NoopProvider),
+        );
+        let mut component = AppComponent::new(
+            Arc::clone(&app),
+            Arc::clone(&host),
+            ProviderProfile {
+                provider_id: "test".to_string(),
+                model_id: "test-model".to_string(),
+                thinking_level: None,
+            },
+        );
+        component.set_workspace_index(index);
+
+        for event in tape_tui::core::input_event::parse_input_events("see @gui", false) {
+            component.handle_event(&event);
+        }
+        assert!(component.editor.is_showing_autocomplete());
+        let rendered = component.render(80).join("\n");
+        assert!(strip_ansi(&rendered).contains("docs/guide.md"));
+
+        // Escape closes the list instead of reaching the app.
+        component.handle_event(&InputEvent::Key {
+            raw: "\x1b".to_string(),
+            key_id: "escape".to_string(),
+            event_type: KeyEventType::Press,
+        });
+        assert!(!component.editor.is_showing_autocomplete());
+        for data in ["d", "\t"] {
+            for event in tape_tui::core::input_event::parse_input_events(data, false) {
+                component.handle_event(&event);
+            }
+        }
+        assert_eq!(component.editor.get_text(), "see @docs/guide.md ");
+    }
+
+    #[test]
+    fn streaming_reply_is_smoothed_until_the_run_finishes() {
+        let mut app = App::new();
+        app.mode = Mode::Running { run_id: 4 };
+        app.transcript.push(Message {
+            role: Role::Assistant,
+            content: "Streaming partial zqx".to_string(),
+            streaming: true,
+            run_id: Some(4),
+        });
+
+        let app = Arc::new(Mutex::new(app));
+        let runtime = TUI::new(NullTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(NoopProvider),
+        );
+        let mut component = AppComponent::new(
+            Arc::clone(&app),
+            host,
+            ProviderProfile {
+                provider_id: "test".to_string(),
+                model_id: "test-model".to_string(),
+                thinking_level: None,
+            },
+        );
+        let rendered = |component: &mut AppComponent| strip_ansi(&component.render(80).join("\n"));
+
+        // Text is released over later frames, not as soon as it arrives.
+        assert!(!rendered(&mut component).contains("Streaming"));
+
+        lock_unpoisoned(&app).transcript[0].streaming = false;
+        assert!(rendered(&mut component).contains("Streaming partial zqx"));
+    }
+
+    #[test]
+    fn resize_rewraps_each_message_once_from_its_cached_source() {
+        let mut app = App::new();
+        for index in 0..3 {
+            app.transcript.push(Message {
+                role: Role::User,
+                content: format!("message number {index} {}", "word ".repeat(12)),
+                streaming: false,
+                run_id: None,
+            });
+        }
+        let app = Arc::new(Mutex::new(app));
+        let runtime = TUI::new(NullTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(NoopProvider),
+        );
+        let mut component = AppComponent::new(
+            Arc::clone(&app),
+            host,
+            ProviderProfile {
+                provider_id: "test".to_string(),
+                model_id: "test-model".to_string(),
+                thinking_level: None,
+            },
+        );
+        let rows_of = |lines: &[String], needle: &str| {
+            lines
+                .iter()
+                .skip_while(|line| !strip_ansi(line).contains(needle))
+                .take_while(|line| !strip_ansi(line).starts_with('─'))
+                .count()
+        };
+
+        let wide = component.render(120);
+        assert_eq!(rows_of(&wide, "message number 0"), 1);
+        assert_eq!(component.debug_stats.item_rewraps, 3);
+
+        let narrow = component.render(40);
+        assert!(rows_of(&narrow, "message number 0") > 1);
+        assert!(narrow
+            .iter()
+            .all(|line| strip_ansi(line).chars().count() <= 40));
+        assert_eq!(component.debug_stats.item_rewraps, 6);
+
+        // Later transcript changes reuse the reflowed rows.
+        lock_unpoisoned(&app).transcript.push(Message {
+            role: Role::Assistant,
+            content: "streaming".to_string(),
+            streaming: true,
+            run_id: None,
+        });
+        component.transcript_render_cache = None;
+        let updated = component.render(40);
+        assert!(strip_ansi(&updated.join("\n")).contains("streaming"));
+        assert_eq!(component.debug_stats.item_rewraps, 6);
+    }
+
+    #[test]
+    fn transcript_width_caps_and_centers_messages_but_not_the_prompt() {
+        let mut app = App::new();
+        app.transcript.push(Message {
+            role: Role::User,
+            content: "word ".repeat(20),
+            streaming: false,
+            run_id: None,
+        });
+        let app = Arc::new(Mutex::new(app));
+        let runtime = TUI::new(NullTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(NoopProvider),
+        );
+        let mut component = AppComponent::new(
+            Arc::clone(&app),
+            host,
+            ProviderProfile {
+                provider_id: "test".to_string(),
+                model_id: "test-model".to_string(),
+                thinking_level: None,
+            },
+        );
+        let message_rows = |lines: &[String]| {
+            lines
+                .iter()
+                .map(|line| strip_ansi(line))
+                .filter(|line| line.contains("word"))
+                .collect::<Vec<_>>()
+        };
+
+        let full = component.render(120);
+        assert_eq!(message_rows(&full).len(), 1);
+
+        component.set_transcript_width(ContentWidth::max(40).centered(true));
+        let narrowed = component.render(120);
+        let rows = message_rows(&narrowed);
+        assert!(rows.len() > 1);
+        for row in &rows {
+            assert!(row.starts_with(&" ".repeat(40)), "{row:?}");
+            assert!(visible_text_width(row) <= 80, "{row:?}");
+        }
+        let mode_line = narrowed
+            .iter()
+            .map(|line| strip_ansi(line))
+            .find(|line| line.contains(ViewMode::Plan.label()))
+            .expect("mode line rendered");
+        assert_eq!(visible_text_width(&mode_line), 120);
+    }
+
+    #[test]
+    fn scrolled_messages_are_released_over_budget_and_return_on_resize() {
+        let mut app = App::new();
+        for index in 0..6 {
+            app.transcript.push(Message {
+                role: Role::User,
+                content: format!("message number {index}"),
+                streaming: false,
+                run_id: None,
+            });
+        }
+        let app = Arc::new(Mutex::new(app));
+        let runtime = TUI::new(NullTerminal);
+        let host = RuntimeController::new(
+            Arc::clone(&app),
+            runtime.runtime_handle(),
+            Arc::new(NoopProvider),
+        );
+        let mut component = AppComponent::new(
+            Arc::clone(&app),
+            host,
+            ProviderProfile {
+                provider_id: "test".to_string(),
+                model_id: "test-model".to_string(),
+                thinking_level: None,
+            },
+        );
+        let rendered = |component: &mut AppComponent, width: usize| {
+            strip_ansi(&component.render(width).join("\n"))
+        };
+
+        let full = component.render(80);
+        let message_row = full
+            .iter()
+            .position(|line| strip_ansi(line).contains("message number 0"))
+            .expect("first message rendered");
+        let second_row = full
+            .iter()
+            .position(|line| strip_ansi(line).contains("message number 1"))
+            .expect("second message rendered");
+
+        // Without a budget, or under it, nothing is released.
+        component.set_transcript_memory_budget(None);
+        assert_eq!(component.release_scrolled_rows(full.len()), 0);
+        component.set_transcript_memory_budget(Some(usize::MAX));
+        assert_eq!(component.release_scrolled_rows(full.len()), 0);
+
+        // Only whole messages that have fully scrolled off go, with the header.
+        component.set_transcript_memory_budget(Some(1));
+        assert_eq!(component.release_scrolled_rows(message_row), 0);
+        assert_eq!(component.release_scrolled_rows(second_row), second_row);
+
+        let trimmed = rendered(&mut component, 80);
+        assert!(!trimmed.contains("Coding Agent"));
+        assert!(!trimmed.contains("message number 0"));
+        assert!(trimmed.contains("message number 1"));
+        assert_eq!(
+            component.render(80).len(),
+            full.len() - second_row,
+            "released rows are exactly the rows that were reported"
+        );
+
+        // The newest message is never released.
+        let all = component.render(80).len();
+        component.release_scrolled_rows(all);
+        assert!(rendered(&mut component, 80).contains("message number 5"));
+
+        // A width change redraws everything from the transcript.
+        let resized = rendered(&mut component, 60);
+        assert!(resized.contains("Coding Agent"));
+        assert!(resized.contains("message number 0"));
+    }
 }
@@ -0,0 +1,443 @@
+//! Background index of the files in the workspace.
+//!
+//! Workspace-aware features query the index instead of walking the
+//! filesystem on every keystroke. Inside a git repository the file list comes
+//! from `git ls-files`, so `.gitignore`, `.git/info/exclude`, and the global
+//! excludes file apply; elsewhere a plain walk skips hidden entries and the
+//! usual build directories. A worker thread rescans on an interval and swaps
+//! in a new snapshot only when a path or modification time changed.
+//!
+//! [`WorkspaceMentionProvider`] serves `@`-mention completion in the prompt
+//! editor from the index.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use tape_tui::core::autocomplete::{
+    AutocompleteItem, AutocompleteProvider, AutocompleteSuggestions, CompletionResult,
+};
+use tape_tui::fuzzy_filter_top_k;
+
+/// Directories the non-git walk never descends into.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+/// Most files a non-git walk indexes.
+const MAX_WALK_FILES: usize = 100_000;
+const MAX_MENTION_SUGGESTIONS: usize = 20;
+
+/// One indexed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedFile {
+    /// Path relative to the workspace root, `/`-separated.
+    pub path: String,
+    pub modified: Option<SystemTime>,
+}
+
+struct IndexShared {
+    root: PathBuf,
+    files: Mutex<Arc<Vec<IndexedFile>>>,
+    /// Bumped each time a scan changes the snapshot; 0 until the first scan.
+    generation: AtomicU64,
+    /// Serializes scans so a manual refresh never races the worker.
+    scan: Mutex<()>,
+}
+
+/// Shared handle to a workspace file index. Clones share the same snapshot.
+#[derive(Clone)]
+pub struct WorkspaceIndex {
+    shared: Arc<IndexShared>,
+}
+
+impl WorkspaceIndex {
+    /// An empty index over `root`; nothing is scanned until
+    /// [`WorkspaceIndex::refresh`].
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            shared: Arc::new(IndexShared {
+                root: root.into(),
+                files: Mutex::new(Arc::new(Vec::new())),
+                generation: AtomicU64::new(0),
+                scan: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// Starts indexing `root` on a worker thread that rescans every
+    /// `interval`. The worker exits once every handle is dropped.
+    pub fn spawn(root: impl Into<PathBuf>, interval: Duration) -> Self {
+        let index = Self::new(root);
+        let shared = Arc::downgrade(&index.shared);
+        let spawned = thread::Builder::new()
+            .name("workspace-index".to_string())
+            .spawn(move || watch(shared, interval));
+        if spawned.is_err() {
+            // Without a worker the index still serves whatever a manual
+            // refresh finds.
+            let _ = index.refresh();
+        }
+        index
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.shared.root
+    }
+
+    /// Rescans the workspace now. Returns whether the file list changed.
+    pub fn refresh(&self) -> io::Result<bool> {
+        refresh_shared(&self.shared)
+    }
+
+    /// Current snapshot, sorted by path.
+    pub fn files(&self) -> Arc<Vec<IndexedFile>> {
+        Arc::clone(&lock_unpoisoned(&self.shared.files))
+    }
+
+    /// Changes with every snapshot; 0 until the first scan completes.
+    pub fn generation(&self) -> u64 {
+        self.shared.generation.load(Ordering::SeqCst)
+    }
+
+    /// Best `limit` files for `query`, best first. An empty query lists the
+    /// most recently modified files.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<IndexedFile> {
+        let files = self.files();
+        if query.trim().is_empty() {
+            let mut recent: Vec<&IndexedFile> = files.iter().collect();
+            recent.sort_by_key(|file| std::cmp::Reverse(file.modified));
+            return recent.into_iter().take(limit).cloned().collect();
+        }
+
+        fuzzy_filter_top_k(&files, query, Some(limit), |file| file.path.as_str())
+            .into_iter()
+            .map(|hit| files[hit.index].clone())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for WorkspaceIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkspaceIndex")
+            .field("root", &self.shared.root)
+            .field("generation", &self.generation())
+            .finish_non_exhaustive()
+    }
+}
+
+fn watch(shared: Weak<IndexShared>, interval: Duration) {
+    loop {
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        // A failed scan keeps the previous snapshot; the next tick retries.
+        let _ = refresh_shared(&shared);
+        drop(shared);
+        thread::sleep(interval);
+    }
+}
+
+fn refresh_shared(shared: &IndexShared) -> io::Result<bool> {
+    let _scan = lock_unpoisoned(&shared.scan);
+    let next = scan_workspace(&shared.root)?;
+    let mut files = lock_unpoisoned(&shared.files);
+    let first_scan = shared.generation.load(Ordering::SeqCst) == 0;
+    if !first_scan && **files == next {
+        return Ok(false);
+    }
+
+    *files = Arc::new(next);
+    shared.generation.fetch_add(1, Ordering::SeqCst);
+    Ok(true)
+}
+
+/// Lists the files under `root` with their modification times, sorted by
+/// path.
+pub fn scan_workspace(root: &Path) -> io::Result<Vec<IndexedFile>> {
+    let paths = match git_file_list(root) {
+        Some(paths) => paths,
+        None => walk_file_list(root)?,
+    };
+
+    let mut files: Vec<IndexedFile> = paths
+        .into_iter()
+        .filter_map(|path| {
+            // Tracked files deleted from the worktree are still listed by git.
+            let metadata = fs::metadata(root.join(&path)).ok()?;
+            metadata.is_file().then(|| IndexedFile {
+                modified: metadata.modified().ok(),
+                path,
+            })
+        })
+        .collect();
+    files.sort_by(|left, right| left.path.cmp(&right.path));
+    files.dedup_by(|left, right| left.path == right.path);
+    Ok(files)
+}
+
+/// Tracked and untracked, non-ignored files; `None` outside a git
+/// repository or when git is unavailable.
+fn git_file_list(root: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .args([
+            "ls-files",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+            "-z",
+        ])
+        .current_dir(root)
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(
+        output
+            .stdout
+            .split(|byte| *byte == 0)
+            .filter(|path| !path.is_empty())
+            .filter_map(|path| std::str::from_utf8(path).ok())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn walk_file_list(root: &Path) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let entries = match fs::read_dir(root.join(&relative)) {
+            Ok(entries) => entries,
+            Err(error) if relative.as_os_str().is_empty() => return Err(error),
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = relative.join(name);
+            if file_type.is_dir() {
+                if !SKIPPED_DIRS.contains(&name) {
+                    pending.push(path);
+                }
+            } else {
+                files.push(path.to_string_lossy().replace('\\', "/"));
+                if files.len() >= MAX_WALK_FILES {
+                    return Ok(files);
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Completes `@path` mentions in the prompt editor from a [`WorkspaceIndex`].
+pub struct WorkspaceMentionProvider {
+    index: WorkspaceIndex,
+}
+
+impl WorkspaceMentionProvider {
+    pub fn new(index: WorkspaceIndex) -> Self {
+        Self { index }
+    }
+}
+
+/// The `@…` token ending at the cursor, if the cursor is in one.
+fn mention_before_cursor(lines: &[String], cursor_line: usize, cursor_col: usize) -> Option<&str> {
+    let line = lines.get(cursor_line)?;
+    let before_cursor = line.get(..cursor_col)?;
+    let token = before_cursor.rsplit(char::is_whitespace).next()?;
+    token.starts_with('@').then_some(token)
+}
+
+impl AutocompleteProvider for WorkspaceMentionProvider {
+    fn get_suggestions(
+        &self,
+        lines: &[String],
+        cursor_line: usize,
+        cursor_col: usize,
+    ) -> Option<AutocompleteSuggestions> {
+        let prefix = mention_before_cursor(lines, cursor_line, cursor_col)?;
+        let items = self
+            .index
+            .search(&prefix[1..], MAX_MENTION_SUGGESTIONS)
+            .into_iter()
+            .map(|file| AutocompleteItem {
+                value: format!("@{}", file.path),
+                label: file.path,
+                description: None,
+            })
+            .collect();
+        Some(AutocompleteSuggestions {
+            items,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    fn should_trigger_file_completion(
+        &self,
+        lines: &[String],
+        cursor_line: usize,
+        cursor_col: usize,
+    ) -> bool {
+        mention_before_cursor(lines, cursor_line, cursor_col).is_some()
+    }
+
+    fn apply_completion(
+        &self,
+        lines: &[String],
+        cursor_line: usize,
+        cursor_col: usize,
+        item: &AutocompleteItem,
+        prefix: &str,
+    ) -> CompletionResult {
+        let mut lines = lines.to_vec();
+        let Some(line) = lines.get_mut(cursor_line) else {
+            return CompletionResult {
+                lines,
+                cursor_line,
+                cursor_col,
+            };
+        };
+        let start = cursor_col.saturating_sub(prefix.len());
+        let replacement = format!("{} ", item.value);
+        line.replace_range(start..cursor_col, &replacement);
+        CompletionResult {
+            lines,
+            cursor_line,
+            cursor_col: start + replacement.len(),
+        }
+    }
+}
+
+fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::Command;
+
+    use tape_tui::core::autocomplete::AutocompleteProvider;
+
+    use super::{scan_workspace, WorkspaceIndex, WorkspaceMentionProvider};
+
+    fn write(root: &std::path::Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("create parent");
+        fs::write(path, contents).expect("write file");
+    }
+
+    fn paths(index: &WorkspaceIndex) -> Vec<String> {
+        index.files().iter().map(|file| file.path.clone()).collect()
+    }
+
+    #[test]
+    fn walk_skips_hidden_and_build_directories_outside_git() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        write(dir.path(), "src/main.rs", "fn main() {}");
+        write(dir.path(), "README.md", "readme");
+        write(dir.path(), ".hidden/secret", "x");
+        write(dir.path(), "target/debug/out", "x");
+
+        let files = scan_workspace(dir.path()).expect("scan");
+        let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["README.md", "src/main.rs"]);
+        assert!(files.iter().all(|file| file.modified.is_some()));
+    }
+
+    #[test]
+    fn git_workspaces_respect_gitignore() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let git_init = Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(dir.path())
+            .status();
+        if !git_init.is_ok_and(|status| status.success()) {
+            return;
+        }
+        write(dir.path(), ".gitignore", "ignored/\n*.log\n");
+        write(dir.path(), "src/lib.rs", "");
+        write(dir.path(), "ignored/file.rs", "");
+        write(dir.path(), "debug.log", "");
+
+        let index = WorkspaceIndex::new(dir.path());
+        assert!(index.refresh().expect("refresh"));
+        assert_eq!(paths(&index), vec![".gitignore", "src/lib.rs"]);
+    }
+
+    #[test]
+    fn refresh_reports_changes_and_search_ranks_paths() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        write(dir.path(), "src/widgets/editor.rs", "");
+        write(dir.path(), "src/runtime/tui.rs", "");
+
+        let index = WorkspaceIndex::new(dir.path());
+        assert_eq!(index.generation(), 0);
+        assert!(index.refresh().expect("first scan"));
+        assert_eq!(index.generation(), 1);
+        assert!(!index.refresh().expect("unchanged"));
+        assert_eq!(index.generation(), 1);
+
+        write(dir.path(), "src/widgets/markdown.rs", "");
+        assert!(index.refresh().expect("added file"));
+        assert_eq!(index.generation(), 2);
+        let hits = index.search("wided", 5);
+        assert_eq!(hits[0].path, "src/widgets/editor.rs");
+        assert_eq!(index.search("", 10).len(), 3);
+
+        fs::remove_file(dir.path().join("src/runtime/tui.rs")).expect("remove");
+        assert!(index.refresh().expect("removed file"));
+        assert_eq!(
+            paths(&index),
+            vec!["src/widgets/editor.rs", "src/widgets/markdown.rs"]
+        );
+    }
+
+    #[test]
+    fn mention_provider_completes_at_tokens() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        write(dir.path(), "src/app.rs", "");
+        write(dir.path(), "docs/guide.md", "");
+        let index = WorkspaceIndex::new(dir.path());
+        index.refresh().expect("scan");
+        let provider = WorkspaceMentionProvider::new(index);
+
+        let lines = vec!["look at @guid".to_string()];
+        let suggestions = provider
+            .get_suggestions(&lines, 0, lines[0].len())
+            .expect("mention context");
+        assert_eq!(suggestions.prefix, "@guid");
+        assert_eq!(suggestions.items[0].value, "@docs/guide.md");
+
+        let result = provider.apply_completion(
+            &lines,
+            0,
+            lines[0].len(),
+            &suggestions.items[0],
+            &suggestions.prefix,
+        );
+        assert_eq!(result.lines, vec!["look at @docs/guide.md "]);
+        assert_eq!(result.cursor_col, result.lines[0].len());
+
+        let plain = vec!["email me@".to_string(), "plain text".to_string()];
+        assert!(provider.get_suggestions(&plain, 1, 5).is_none());
+        assert!(provider.get_suggestions(&plain, 0, 9).is_none());
+    }
+}
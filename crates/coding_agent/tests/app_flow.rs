@@ -1,16 +1,37 @@
-use coding_agent::app::{App, HostOps, Message, Mode, Role, RunId};
-use coding_agent::commands::{parse_slash_command, SlashCommand};
-use coding_agent::provider::RunMessage;
+use std::fs;
+
+use coding_agent::app::{
+    session_title_from_prompt, App, HostOps, Message, Mode, QueuedTurn, QueuedTurnId, Role, RunId,
+    SessionListing, SessionListings, ToolConfirmation,
+};
+use coding_agent::changes::{FileChangeStatus, FileSnapshot};
+use coding_agent::checkpoints::CheckpointStore;
+use coding_agent::commands::{parse_slash_command, CopyTarget, SlashCommand};
+use coding_agent::provider::{ModelInfo, ProviderProfile, RunMessage};
 
 #[derive(Default)]
 struct HostSpy {
     next_run_id: RunId,
     start_run_error: Option<String>,
+    /// Fails only the next `start_run`.
+    next_start_run_error: Option<String>,
     started_runs: Vec<Vec<RunMessage>>,
     started_instructions: Vec<String>,
     cancelled_runs: Vec<RunId>,
     render_requests: usize,
     stop_requests: usize,
+    queued: Vec<QueuedTurn>,
+    next_queued_id: QueuedTurnId,
+    change_review_open: bool,
+    tool_preview: bool,
+    tool_confirmations: Vec<(String, bool)>,
+    clipboard: Vec<String>,
+    toasts: Vec<String>,
+    selected_model: Option<String>,
+    selected_thinking: Option<String>,
+    model_list_requests: usize,
+    prompt_form_open: bool,
+    session_titles: Vec<String>,
 }
 
 impl HostSpy {
@@ -28,6 +49,19 @@ impl HostSpy {
         }
     }
 
+    fn queued_prompts(&self) -> Vec<String> {
+        self.queued.iter().map(|turn| turn.prompt.clone()).collect()
+    }
+
+    /// Mirrors the runtime: start queued turns, oldest first, until one is
+    /// running or the queue is empty.
+    fn dispatch_next_queued_turn(&mut self, app: &mut App) {
+        while app.can_start_queued_turn() && !self.queued.is_empty() {
+            let turn = self.queued.remove(0);
+            app.start_queued_turn(turn.prompt, self);
+        }
+    }
+
     fn started_prompts(&self) -> Vec<String> {
         self.started_runs
             .iter()
@@ -54,7 +88,11 @@ impl HostOps for HostSpy {
         self.started_runs.push(messages);
         self.started_instructions.push(instructions);
 
-        if let Some(error) = self.start_run_error.clone() {
+        if let Some(error) = self
+            .next_start_run_error
+            .take()
+            .or_else(|| self.start_run_error.clone())
+        {
             return Err(error);
         }
 
@@ -72,6 +110,126 @@ impl HostOps for HostSpy {
     fn request_stop(&mut self) {
         self.stop_requests += 1;
     }
+
+    fn queue_turn(&mut self, prompt: String) -> Result<QueuedTurnId, String> {
+        self.next_queued_id += 1;
+        self.queued.push(QueuedTurn {
+            id: self.next_queued_id,
+            prompt,
+        });
+        Ok(self.next_queued_id)
+    }
+
+    fn queue_turn_next(&mut self, prompt: String) -> Result<QueuedTurnId, String> {
+        self.next_queued_id += 1;
+        self.queued.insert(
+            0,
+            QueuedTurn {
+                id: self.next_queued_id,
+                prompt,
+            },
+        );
+        Ok(self.next_queued_id)
+    }
+
+    fn cancel_queued_turn(&mut self, id: QueuedTurnId) -> bool {
+        let before = self.queued.len();
+        self.queued.retain(|turn| turn.id != id);
+        self.queued.len() != before
+    }
+
+    fn queued_turns(&self) -> Vec<QueuedTurn> {
+        self.queued.clone()
+    }
+
+    fn show_change_review(&mut self) -> Result<(), String> {
+        self.change_review_open = true;
+        Ok(())
+    }
+
+    fn hide_change_review(&mut self) {
+        self.change_review_open = false;
+    }
+
+    fn show_prompt_form(&mut self) -> Result<(), String> {
+        self.prompt_form_open = true;
+        Ok(())
+    }
+
+    fn hide_prompt_form(&mut self) {
+        self.prompt_form_open = false;
+    }
+
+    fn set_tool_preview(&mut self, enabled: bool) -> Result<(), String> {
+        self.tool_preview = enabled;
+        Ok(())
+    }
+
+    fn resolve_tool_confirmation(&mut self, call_id: &str, approved: bool) {
+        self.tool_confirmations
+            .push((call_id.to_string(), approved));
+    }
+
+    fn copy_to_clipboard(&mut self, text: &str) -> Result<(), String> {
+        self.clipboard.push(text.to_string());
+        Ok(())
+    }
+
+    fn show_toast(&mut self, message: String) {
+        self.toasts.push(message);
+    }
+
+    fn set_model(&mut self, model_id: &str) -> Result<ProviderProfile, String> {
+        if model_id != "gpt-5.3-codex" {
+            return Err(format!("Unknown model '{model_id}'"));
+        }
+        self.selected_model = Some(model_id.to_string());
+        Ok(ProviderProfile {
+            provider_id: "spy".to_string(),
+            model_id: model_id.to_string(),
+            thinking_level: self.selected_thinking.clone(),
+        })
+    }
+
+    fn set_thinking_level(&mut self, level: &str) -> Result<ProviderProfile, String> {
+        self.selected_thinking = Some(level.to_string());
+        Ok(ProviderProfile {
+            provider_id: "spy".to_string(),
+            model_id: self.selected_model.clone().unwrap_or_default(),
+            thinking_level: Some(level.to_string()),
+        })
+    }
+
+    fn request_model_list(&mut self) -> Result<(), String> {
+        self.model_list_requests += 1;
+        Ok(())
+    }
+
+    fn set_session_title(&mut self, title: &str) -> Result<(), String> {
+        self.session_titles.push(title.to_string());
+        Ok(())
+    }
+
+    fn list_sessions(&mut self) -> Result<SessionListings, String> {
+        let sessions = vec![
+            SessionListing {
+                path: ".agent/sessions/b.jsonl".into(),
+                created_at: "2026-02-15T00:00:00Z".to_string(),
+                title: self.session_titles.last().cloned(),
+                current: true,
+            },
+            SessionListing {
+                path: ".agent/sessions/a.jsonl".into(),
+                created_at: "2026-02-14T00:00:00Z".to_string(),
+                title: None,
+                current: false,
+            },
+        ];
+        Ok(SessionListings {
+            sessions,
+            unreadable: vec![".agent/sessions/c.jsonl: missing session header".to_string()],
+        })
+    }
 }
 
 #[test]
@@ -193,6 +351,57 @@ fn parser_recognizes_known_and_unknown_slash_commands() {
     assert_eq!(parse_slash_command("/clear"), Some(SlashCommand::Clear));
     assert_eq!(parse_slash_command("/cancel"), Some(SlashCommand::Cancel));
     assert_eq!(parse_slash_command("/quit"), Some(SlashCommand::Quit));
+    assert_eq!(parse_slash_command("/changes"), Some(SlashCommand::Changes));
+    assert_eq!(parse_slash_command("/undo"), Some(SlashCommand::Undo(None)));
+    assert_eq!(
+        parse_slash_command("/undo 2"),
+        Some(SlashCommand::Undo(Some(2)))
+    );
+    assert_eq!(parse_slash_command("/preview"), Some(SlashCommand::Preview));
+    assert_eq!(
+        parse_slash_command("/steer  focus on tests "),
+        Some(SlashCommand::Steer("focus on tests".to_string()))
+    );
+    assert_eq!(
+        parse_slash_command("/dequeue 3"),
+        Some(SlashCommand::Dequeue(Some(3)))
+    );
+    assert_eq!(
+        parse_slash_command("/dequeue zero"),
+        Some(SlashCommand::Dequeue(None))
+    );
+    assert_eq!(
+        parse_slash_command("/copy last"),
+        Some(SlashCommand::Copy(Some(CopyTarget::LastMessage)))
+    );
+    assert_eq!(
+        parse_slash_command("/copy last 2"),
+        Some(SlashCommand::Copy(Some(CopyTarget::LastCodeBlock(2))))
+    );
+    for invalid in ["/copy", "/copy first", "/copy last 0", "/copy last 1 2"] {
+        assert_eq!(parse_slash_command(invalid), Some(SlashCommand::Copy(None)));
+    }
+    assert_eq!(
+        parse_slash_command("/model  gpt-5.3-codex "),
+        Some(SlashCommand::Model("gpt-5.3-codex".to_string()))
+    );
+    assert_eq!(parse_slash_command("/models"), Some(SlashCommand::Models));
+    assert_eq!(
+        parse_slash_command("/title  Fix the build "),
+        Some(SlashCommand::Title("Fix the build".to_string()))
+    );
+    assert_eq!(
+        parse_slash_command("/sessions"),
+        Some(SlashCommand::Sessions)
+    );
+    assert_eq!(
+        parse_slash_command("/prompt review path=a.rs"),
+        Some(SlashCommand::Prompt("review path=a.rs".to_string()))
+    );
+    assert_eq!(
+        parse_slash_command("/thinking"),
+        Some(SlashCommand::Thinking(String::new()))
+    );
     assert_eq!(
         parse_slash_command("/nope extra args"),
         Some(SlashCommand::Unknown("/nope".to_string()))
@@ -261,7 +470,7 @@ fn slash_cancel_cancels_active_run_or_reports_idle_state() {
 }
 
 #[test]
-fn sending_message_while_running_is_non_failing() {
+fn sending_message_while_running_queues_turn_fifo() {
     let mut app = App::new();
     let mut host = HostSpy::with_next_run_id(11);
 
@@ -272,17 +481,229 @@ fn sending_message_while_running_is_non_failing() {
 
     app.on_input_replace("another message".to_string());
     app.on_submit(&mut host);
+    app.on_input_replace("third message".to_string());
+    app.on_submit(&mut host);
 
     assert_eq!(
         app.transcript
             .last()
             .expect("system message exists")
             .content,
-        "Run already in progress. Use /cancel to stop it."
+        "Message queued (2 pending). Use /dequeue <n> to drop it."
     );
     assert_eq!(app.mode, Mode::Running { run_id: 11 });
+    assert!(host.cancelled_runs.is_empty());
+    assert_eq!(host.started_runs.len(), 1);
+    assert_eq!(
+        host.queued_prompts(),
+        vec!["another message".to_string(), "third message".to_string()]
+    );
+
+    host.dispatch_next_queued_turn(&mut app);
+    assert_eq!(host.started_runs.len(), 1, "queue waits for the active run");
+
+    host.next_run_id = 12;
+    app.on_run_finished(11);
+    host.dispatch_next_queued_turn(&mut app);
+
+    assert_eq!(app.mode, Mode::Running { run_id: 12 });
+    assert_eq!(
+        host.started_prompts(),
+        vec![
+            "run while running".to_string(),
+            "another message".to_string()
+        ]
+    );
+    assert_eq!(host.queued_prompts(), vec!["third message".to_string()]);
+}
+
+#[test]
+fn queued_turns_keep_dispatching_after_one_fails_to_start() {
+    let mut app = App::new();
+    let mut host = HostSpy::with_next_run_id(11);
+
+    app.on_input_replace("first".to_string());
+    app.on_submit(&mut host);
+    for prompt in ["second", "third"] {
+        app.on_input_replace(prompt.to_string());
+        app.on_submit(&mut host);
+    }
+    assert_eq!(host.queued_prompts(), vec!["second", "third"]);
+
+    // "second" fails to start, leaving no terminal event to wait for;
+    // "third" starts in the same dispatch.
+    host.next_start_run_error = Some("provider unavailable".to_string());
+    host.next_run_id = 13;
+    app.on_run_finished(11);
+    host.dispatch_next_queued_turn(&mut app);
+    assert!(app
+        .transcript
+        .iter()
+        .any(|message| message.content == "Failed to start run: provider unavailable"));
+    assert_eq!(app.mode, Mode::Running { run_id: 13 });
+    assert_eq!(host.started_prompts(), vec!["first", "second", "third"]);
+    assert!(host.queued_prompts().is_empty());
+}
+
+#[test]
+fn submissions_wait_behind_queued_turns_while_idle() {
+    let mut app = App::new();
+    let mut host = HostSpy::with_next_run_id(11);
+
+    app.on_input_replace("first".to_string());
+    app.on_submit(&mut host);
+    app.on_input_replace("second".to_string());
+    app.on_submit(&mut host);
+
+    // The run ended but the host has not dispatched the queue yet.
+    app.on_run_finished(11);
+    app.on_input_replace("third".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(host.queued_prompts(), vec!["second", "third"]);
+
+    host.next_run_id = 12;
+    host.dispatch_next_queued_turn(&mut app);
+    assert_eq!(host.started_prompts(), vec!["first", "second"]);
+    assert_eq!(host.queued_prompts(), vec!["third"]);
+}
+
+#[test]
+fn dequeue_drops_a_queued_turn_by_position() {
+    let mut app = App::new();
+    let mut host = HostSpy::with_next_run_id(3);
+
+    app.on_input_replace("active".to_string());
+    app.on_submit(&mut host);
+    for prompt in ["one", "two", "three"] {
+        app.on_input_replace(prompt.to_string());
+        app.on_submit(&mut host);
+    }
+
+    app.on_input_replace("/dequeue 2".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        app.transcript.last().expect("dequeue notice").content,
+        "Dropped queued message 2"
+    );
+    assert_eq!(
+        host.queued_prompts(),
+        vec!["one".to_string(), "three".to_string()]
+    );
+
+    app.on_input_replace("/dequeue 5".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        app.transcript.last().expect("dequeue notice").content,
+        "No queued message at position 5"
+    );
+
+    app.on_input_replace("/dequeue".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        app.transcript.last().expect("dequeue usage").content,
+        "Usage: /dequeue <position>"
+    );
+}
+
+#[test]
+fn steer_keeps_partial_output_and_dispatches_queued_prompt_after_terminal_event() {
+    let mut app = App::new();
+    let mut host = HostSpy::with_next_run_id(5);
+
+    app.on_input_replace("refactor the parser".to_string());
+    app.on_submit(&mut host);
+    app.on_run_started(5);
+    app.on_run_chunk(5, "Looking at ");
+    app.on_tool_call_started(5, "call-1", "read", &serde_json::json!({"path": "a.rs"}));
+    app.on_tool_call_finished(
+        5,
+        "read",
+        "call-1",
+        false,
+        &serde_json::json!("fn a() {}"),
+        "fn a() {}",
+    );
+    app.on_tool_call_started(5, "call-2", "read", &serde_json::json!({"path": "b.rs"}));
+
+    app.on_input_replace("queued later".to_string());
+    app.on_submit(&mut host);
+    app.on_input_replace("/steer only touch a.rs".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(host.cancelled_runs, vec![5]);
+    assert_eq!(
+        host.queued_prompts(),
+        vec!["only touch a.rs".to_string(), "queued later".to_string()]
+    );
+
+    host.dispatch_next_queued_turn(&mut app);
+    assert_eq!(
+        host.started_runs.len(),
+        1,
+        "steer must wait for the interrupted run's terminal event"
+    );
+
+    host.next_run_id = 6;
+    app.on_run_cancelled(5);
+    host.dispatch_next_queued_turn(&mut app);
+
+    assert_eq!(app.mode, Mode::Running { run_id: 6 });
+    assert_eq!(host.queued_prompts(), vec!["queued later".to_string()]);
+    assert_eq!(
+        host.started_runs[1],
+        vec![
+            RunMessage::UserText {
+                text: "refactor the parser".to_string(),
+            },
+            RunMessage::AssistantText {
+                text: "Looking at ".to_string(),
+            },
+            RunMessage::ToolCall {
+                call_id: "call-1".to_string(),
+                tool_name: "read".to_string(),
+                arguments: serde_json::json!({"path": "a.rs"}),
+            },
+            RunMessage::ToolResult {
+                call_id: "call-1".to_string(),
+                tool_name: "read".to_string(),
+                content: serde_json::json!("fn a() {}"),
+                is_error: false,
+            },
+            RunMessage::UserText {
+                text: "only touch a.rs".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        app.transcript.last().expect("steer user turn exists"),
+        &Message {
+            role: Role::User,
+            content: "only touch a.rs".to_string(),
+            streaming: false,
+            run_id: None,
+        }
+    );
+}
+
+#[test]
+fn plain_cancel_still_discards_partial_output() {
+    let mut app = App::new();
+    let mut host = HostSpy::with_next_run_id(8);
+
+    app.on_input_replace("start".to_string());
+    app.on_submit(&mut host);
+    app.on_run_started(8);
+    app.on_run_chunk(8, "partial");
+
+    app.on_cancel(&mut host);
+    app.on_run_cancelled(8);
+
+    assert_eq!(
+        app.conversation_messages(),
+        &[RunMessage::UserText {
+            text: "start".to_string(),
+        }]
+    );
     assert_eq!(host.started_runs.len(), 1);
-    assert_eq!(host.render_requests, 2);
 }
 
 #[test]
@@ -324,3 +745,503 @@ fn ctrl_c_exits_when_idle_and_input_is_empty() {
     assert_eq!(host.stop_requests, 1);
     assert_eq!(host.render_requests, 1);
 }
+
+#[test]
+fn changes_command_reviews_touched_files_and_reverts_them() {
+    let workspace = tempfile::tempdir().expect("temp workspace");
+    let path = workspace.path().join("notes.txt");
+    fs::write(&path, "after\n").expect("write notes");
+
+    let mut app = App::new();
+    app.set_workspace_root(workspace.path());
+    let mut host = HostSpy::with_next_run_id(1);
+
+    app.on_input_replace("/changes".to_string());
+    app.on_submit(&mut host);
+    assert!(!host.change_review_open);
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some("No files changed this session")
+    );
+
+    app.on_files_touched(vec![FileSnapshot {
+        path: path.clone(),
+        original: Some(b"before\n".to_vec()),
+    }]);
+    app.on_input_replace("/changes".to_string());
+    app.on_submit(&mut host);
+
+    assert!(host.change_review_open);
+    let review = app.change_review().expect("review should be open");
+    assert_eq!(review.len(), 1);
+    assert_eq!(review[0].label, "notes.txt");
+    assert_eq!(review[0].status, FileChangeStatus::Modified);
+    assert!(review[0].diff.contains("-before\n+after\n"));
+
+    app.mode = Mode::Running { run_id: 1 };
+    app.revert_file_change(&path, &mut host);
+    assert_eq!(fs::read_to_string(&path).expect("read notes"), "after\n");
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some("Cannot revert notes.txt while a run is active")
+    );
+
+    app.mode = Mode::Idle;
+    app.revert_file_change(&path, &mut host);
+    assert_eq!(fs::read_to_string(&path).expect("read notes"), "before\n");
+    assert!(app.file_changes().is_empty());
+    assert!(app.change_review().is_none());
+    assert!(!host.change_review_open);
+}
+
+#[test]
+fn undo_command_restores_the_workspace_to_before_a_chosen_turn() {
+    let workspace = tempfile::tempdir().expect("temp workspace");
+    let notes = workspace.path().join("notes.txt");
+    let added = workspace.path().join("added.txt");
+    fs::write(&notes, "v0").expect("write notes");
+    let store = CheckpointStore::new(workspace.path());
+    let last_message = |app: &App| {
+        app.transcript
+            .last()
+            .map(|message| message.content.clone())
+            .unwrap_or_default()
+    };
+
+    let mut app = App::new();
+    app.set_workspace_root(workspace.path());
+    let mut host = HostSpy::with_next_run_id(1);
+
+    app.on_input_replace("/undo".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(last_message(&app), "No file changes to undo");
+
+    app.on_input_replace("edit notes".to_string());
+    app.on_submit(&mut host);
+    let first = store
+        .create(&[FileSnapshot {
+            path: notes.clone(),
+            original: Some(b"v0".to_vec()),
+        }])
+        .expect("first checkpoint");
+    fs::write(&notes, "v1").expect("first edit");
+    app.on_checkpoint_recorded(1, Ok(first));
+    app.on_checkpoint_recorded(1, Err("disk full".to_string()));
+    assert_eq!(
+        last_message(&app),
+        "Checkpoint failed; /undo cannot restore this tool call: disk full"
+    );
+
+    app.on_input_replace("/undo 1".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(last_message(&app), "Cannot undo while a run is active");
+    app.on_run_finished(1);
+
+    host.next_run_id = 2;
+    app.on_input_replace("add a file".to_string());
+    app.on_submit(&mut host);
+    let second = store
+        .create(&[
+            FileSnapshot {
+                path: notes.clone(),
+                original: Some(b"v1".to_vec()),
+            },
+            FileSnapshot {
+                path: added.clone(),
+                original: None,
+            },
+        ])
+        .expect("second checkpoint");
+    fs::write(&notes, "v2").expect("second edit");
+    fs::write(&added, "new").expect("new file");
+    app.on_checkpoint_recorded(2, Ok(second));
+    app.on_run_finished(2);
+
+    app.on_input_replace("/undo".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        last_message(&app),
+        "Turns with file changes (/undo <n> restores the workspace to before turn n):\n  \
+         1. edit notes (1 tool call)\n  2. add a file (1 tool call)"
+    );
+
+    app.on_input_replace("/undo 3".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        last_message(&app),
+        "No turn 3; /undo lists 2 turns with file changes"
+    );
+
+    app.on_input_replace("/undo 2".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        last_message(&app),
+        "Restored the workspace to before turn 2: added.txt, notes.txt"
+    );
+    assert_eq!(fs::read_to_string(&notes).expect("read notes"), "v1");
+    assert!(!added.exists());
+    assert_eq!(app.turn_checkpoints().len(), 1);
+
+    app.on_input_replace("/undo 1".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(fs::read_to_string(&notes).expect("read notes"), "v0");
+    assert!(app.turn_checkpoints().is_empty());
+    assert_eq!(host.started_runs.len(), 2);
+}
+
+#[test]
+fn prompt_command_expands_templates_and_collects_missing_variables() {
+    let workspace = tempfile::tempdir().expect("temp workspace");
+    let prompts = workspace.path().join(".agent/prompts");
+    fs::create_dir_all(&prompts).expect("prompts dir");
+    fs::write(
+        prompts.join("review.md"),
+        "Review {{path}} with a focus on {{focus}}.\n",
+    )
+    .expect("template");
+
+    let mut app = App::new();
+    app.set_workspace_root(workspace.path());
+    let mut host = HostSpy::default();
+
+    app.on_input_replace(r#"/prompt review path=src/lib.rs focus="error handling""#.to_string());
+    app.on_submit(&mut host);
+    let expected = "Review src/lib.rs with a focus on error handling.";
+    assert!(!host.prompt_form_open);
+    assert_eq!(app.input, expected);
+    assert_eq!(app.take_pending_editor_text().as_deref(), Some(expected));
+    assert!(host.started_runs.is_empty());
+
+    app.on_input_replace("/prompt review path=src/lib.rs".to_string());
+    app.on_submit(&mut host);
+    assert!(host.prompt_form_open);
+    let form = app.prompt_form_mut().expect("form should be open");
+    assert_eq!(form.fields().len(), 1);
+    assert_eq!(form.fields()[0].name, "focus");
+    form.set_selected_value("tests");
+    app.submit_prompt_form(&mut host);
+    assert!(!host.prompt_form_open);
+    assert!(app.prompt_form().is_none());
+    assert_eq!(
+        app.take_pending_editor_text().as_deref(),
+        Some("Review src/lib.rs with a focus on tests.")
+    );
+
+    app.on_input_replace("/prompt review".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(app.prompt_form().map(|form| form.fields().len()), Some(2));
+    app.cancel_prompt_form(&mut host);
+    assert!(!host.prompt_form_open);
+    assert_eq!(app.take_pending_editor_text(), None);
+
+    app.on_input_replace("/prompt review depth=3".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some("Prompt template 'review' has no variable 'depth'")
+    );
+
+    app.on_input_replace("/prompt missing".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some("Unknown prompt template 'missing'; available templates: review")
+    );
+    assert!(!host.prompt_form_open);
+}
+
+#[test]
+fn preview_command_toggles_mode_and_routes_confirmation_decisions() {
+    let mut app = App::new();
+    let mut host = HostSpy::with_next_run_id(3);
+
+    app.on_input_replace("/preview".to_string());
+    app.on_submit(&mut host);
+    assert!(app.tool_preview());
+    assert!(host.tool_preview);
+
+    app.on_input_replace("write it".to_string());
+    app.on_submit(&mut host);
+    app.on_tool_confirmation_requested(ToolConfirmation {
+        run_id: 99,
+        call_id: "stale".to_string(),
+        tool_name: "write".to_string(),
+        diff: String::new(),
+    });
+    assert!(app.pending_tool_confirmation().is_none());
+
+    let confirmation = ToolConfirmation {
+        run_id: 3,
+        call_id: "call-1".to_string(),
+        tool_name: "edit".to_string(),
+        diff: "--- a/x\n+++ b/x\n".to_string(),
+    };
+    app.on_tool_confirmation_requested(confirmation.clone());
+    assert_eq!(app.pending_tool_confirmation(), Some(&confirmation));
+
+    app.resolve_tool_confirmation(false, &mut host);
+    assert_eq!(host.tool_confirmations, vec![("call-1".to_string(), false)]);
+    assert!(app.pending_tool_confirmation().is_none());
+
+    app.on_tool_confirmation_requested(ToolConfirmation {
+        call_id: "call-2".to_string(),
+        ..confirmation
+    });
+    app.on_tool_call_finished(
+        3,
+        "edit",
+        "call-2",
+        true,
+        &serde_json::json!("cancelled"),
+        "cancelled",
+    );
+    assert!(app.pending_tool_confirmation().is_none());
+    app.resolve_tool_confirmation(true, &mut host);
+    assert_eq!(host.tool_confirmations.len(), 1);
+
+    app.on_input_replace("/preview".to_string());
+    app.on_submit(&mut host);
+    assert!(!app.tool_preview());
+    assert!(!host.tool_preview);
+}
+
+#[test]
+fn copy_last_copies_the_newest_assistant_message_or_one_of_its_code_blocks() {
+    let mut app = App::new();
+    let mut host = HostSpy::default();
+
+    app.on_input_replace("/copy last".to_string());
+    app.on_submit(&mut host);
+    assert!(host.clipboard.is_empty());
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some("No assistant message to copy")
+    );
+
+    let reply = "Run this:\n```sh\ncargo test\n```\nthen\n~~~\na\n\nb\n~~~";
+    app.transcript.push(Message {
+        role: Role::Assistant,
+        content: reply.to_string(),
+        streaming: false,
+        run_id: None,
+    });
+
+    app.on_input_replace("/copy last".to_string());
+    app.on_submit(&mut host);
+    app.on_input_replace("/copy last 2".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(
+        host.clipboard,
+        vec![reply.to_string(), "a\n\nb".to_string()]
+    );
+    assert_eq!(
+        host.toasts,
+        vec![
+            format!(
+                "Copied last assistant message ({} chars)",
+                reply.chars().count()
+            ),
+            "Copied code block 2 of the last assistant message (4 chars)".to_string(),
+        ]
+    );
+
+    app.on_input_replace("/copy last 3".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(host.clipboard.len(), 2);
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some("No code block 3 in the last assistant message")
+    );
+}
+
+#[test]
+fn model_and_thinking_commands_select_directly_and_report_failures() {
+    let mut app = App::new();
+    let mut host = HostSpy::with_next_run_id(5);
+    let submit = |app: &mut App, host: &mut HostSpy, input: &str| {
+        app.on_input_replace(input.to_string());
+        app.on_submit(host);
+        app.transcript
+            .last()
+            .map(|message| message.content.clone())
+            .unwrap_or_default()
+    };
+
+    assert_eq!(
+        submit(&mut app, &mut host, "/model gpt-5.3-codex"),
+        "Switched model to gpt-5.3-codex"
+    );
+    assert_eq!(
+        submit(&mut app, &mut host, "/thinking high"),
+        "Switched thinking mode to high"
+    );
+    assert_eq!(
+        submit(&mut app, &mut host, "/model gpt-4o"),
+        "Model switch failed: Unknown model 'gpt-4o'"
+    );
+    assert_eq!(submit(&mut app, &mut host, "/model"), "Usage: /model <id>");
+    assert_eq!(
+        submit(&mut app, &mut host, "/thinking"),
+        "Usage: /thinking <level>"
+    );
+    assert_eq!(host.selected_model.as_deref(), Some("gpt-5.3-codex"));
+    assert_eq!(host.selected_thinking.as_deref(), Some("high"));
+    assert!(host.started_runs.is_empty());
+
+    submit(&mut app, &mut host, "run something");
+    assert_eq!(
+        submit(&mut app, &mut host, "/thinking low"),
+        "Cannot switch thinking mode while a run is active"
+    );
+    assert_eq!(host.selected_thinking.as_deref(), Some("high"));
+}
+
+#[test]
+fn session_titles_come_from_the_first_prompt_line_after_a_reply() {
+    assert_eq!(
+        session_title_from_prompt("\n  Fix   the flaky\tresize test\nmore detail"),
+        Some("Fix the flaky resize test".to_string())
+    );
+    assert_eq!(
+        session_title_from_prompt(
+            "Explain why the diff renderer clears the screen whenever the width changes"
+        ),
+        Some("Explain why the diff renderer clears the screen…".to_string())
+    );
+    assert_eq!(session_title_from_prompt("/model gpt-5"), None);
+    assert_eq!(session_title_from_prompt("   "), None);
+    assert_eq!(
+        session_title_from_prompt("Fix \x1b]0;pwned\x07 the\u{9b}2J build\x7f"),
+        Some("Fix ]0;pwned the 2J build".to_string())
+    );
+    assert_eq!(session_title_from_prompt("\x1b\x07"), None);
+
+    let mut app = App::new();
+    app.restore_conversation(vec![RunMessage::UserText {
+        text: "Fix the build".to_string(),
+    }]);
+    assert_eq!(app.pending_session_title(), None);
+    app.restore_conversation(vec![
+        RunMessage::UserText {
+            text: "Fix the build".to_string(),
+        },
+        RunMessage::AssistantText {
+            text: "Done".to_string(),
+        },
+    ]);
+    assert_eq!(
+        app.pending_session_title().as_deref(),
+        Some("Fix the build")
+    );
+    app.restore_session_title(Some("Renamed".to_string()));
+    assert_eq!(app.pending_session_title(), None);
+    app.restore_session_title(Some("Edited\x1b]0;header\x07".to_string()));
+    assert_eq!(app.session_title(), Some("Edited ]0;header"));
+}
+
+#[test]
+fn title_and_sessions_commands_rename_and_list_saved_sessions() {
+    let mut app = App::new();
+    let mut host = HostSpy::default();
+    let submit = |app: &mut App, host: &mut HostSpy, input: &str| {
+        app.on_input_replace(input.to_string());
+        app.on_submit(host);
+        app.transcript
+            .last()
+            .map(|message| message.content.clone())
+            .unwrap_or_default()
+    };
+
+    assert_eq!(
+        submit(&mut app, &mut host, "/title"),
+        "Usage: /title <name>"
+    );
+    assert_eq!(
+        submit(&mut app, &mut host, "/title \x1b\x07"),
+        "Usage: /title <name>"
+    );
+    assert_eq!(
+        submit(&mut app, &mut host, "/title  Resize \x07  work "),
+        "Renamed session to Resize work"
+    );
+    assert_eq!(host.session_titles, vec!["Resize work".to_string()]);
+    assert_eq!(app.session_title(), Some("Resize work"));
+    assert_eq!(
+        submit(&mut app, &mut host, "/title"),
+        "Session title: Resize work"
+    );
+    assert_eq!(
+        submit(&mut app, &mut host, "/sessions"),
+        "Sessions (* current; resume with --session <path>):\n\
+         * Resize work  2026-02-15T00:00:00Z  .agent/sessions/b.jsonl\n\
+         \x20 (untitled)  2026-02-14T00:00:00Z  .agent/sessions/a.jsonl\n\
+         ! skipped .agent/sessions/c.jsonl: missing session header"
+    );
+    assert!(host.started_runs.is_empty());
+}
+
+#[test]
+fn models_command_requests_a_listing_and_renders_the_delivered_catalog() {
+    let mut app = App::new();
+    let mut host = HostSpy::default();
+
+    app.on_input_replace("/models".to_string());
+    app.on_submit(&mut host);
+    assert_eq!(host.model_list_requests, 1);
+    assert!(app.transcript.is_empty());
+
+    app.on_models_listed(
+        "gpt-5.3-codex",
+        Ok(vec![
+            ModelInfo {
+                model_id: "gpt-5.3-codex".to_string(),
+                display_name: Some("GPT-5.3 Codex".to_string()),
+                context_window: Some(272_000),
+                available: true,
+            },
+            ModelInfo {
+                model_id: "gpt-5.1-codex".to_string(),
+                display_name: None,
+                context_window: None,
+                available: false,
+            },
+            ModelInfo {
+                model_id: "gpt-5.4".to_string(),
+                display_name: None,
+                context_window: Some(800),
+                available: true,
+            },
+        ]),
+    );
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some(
+            "Models (* selected):\n\
+             * gpt-5.3-codex (GPT-5.3 Codex, 272k context)\n\
+             \x20 gpt-5.1-codex (unavailable)\n\
+             \x20 gpt-5.4 (800 context)"
+        )
+    );
+
+    app.on_models_listed("gpt-5.3-codex", Err("backend down".to_string()));
+    assert_eq!(
+        app.transcript
+            .last()
+            .map(|message| message.content.as_str()),
+        Some("Model listing failed: backend down")
+    );
+}
@@ -369,6 +369,163 @@ fn cancel_while_running_results_in_cancelled_state() {
     });
 }
 
+#[test]
+fn steer_while_running_restarts_with_partial_output_and_queued_prompt() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(BlockingCancelProvider);
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+
+        let first_run_id = {
+            let mut app = lock_unpoisoned(&app);
+            app.on_input_replace("long running task".to_string());
+            app.on_submit(&mut host);
+            running_run_id(&app.mode)
+        };
+
+        let streamed = wait_until(
+            Duration::from_secs(3),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || {
+                let app = lock_unpoisoned(&app);
+                app.transcript.iter().any(|message| {
+                    message.role == Role::Assistant && message.content == "working..."
+                })
+            },
+        );
+        assert!(streamed, "first run did not stream before steering");
+
+        {
+            let mut app = lock_unpoisoned(&app);
+            app.on_input_replace("/steer change of plan".to_string());
+            app.on_submit(&mut host);
+        }
+        {
+            let queued: Vec<String> = host
+                .queued_turns()
+                .into_iter()
+                .map(|turn| turn.prompt)
+                .collect();
+            assert_eq!(queued, vec!["change of plan".to_string()]);
+        }
+
+        let restarted = wait_until(
+            Duration::from_secs(3),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || {
+                let app = lock_unpoisoned(&app);
+                matches!(app.mode, Mode::Running { run_id } if run_id != first_run_id)
+            },
+        );
+        assert!(restarted, "steer prompt was not dispatched as a new run");
+
+        {
+            assert!(host.queued_turns().is_empty());
+            let app = lock_unpoisoned(&app);
+            assert_eq!(
+                app.conversation_messages(),
+                &[
+                    coding_agent::provider::RunMessage::UserText {
+                        text: "long running task".to_string(),
+                    },
+                    coding_agent::provider::RunMessage::AssistantText {
+                        text: "working...".to_string(),
+                    },
+                    coding_agent::provider::RunMessage::UserText {
+                        text: "change of plan".to_string(),
+                    },
+                ]
+            );
+        }
+
+        {
+            let mut app = lock_unpoisoned(&app);
+            app.on_cancel(&mut host);
+        }
+        let settled = wait_until(
+            Duration::from_secs(3),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "steered run did not settle after cancel");
+    });
+}
+
+#[test]
+fn queued_turns_start_in_fifo_order_after_each_terminal_event() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(BlockingCancelProvider);
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+
+        let first_run_id = {
+            let mut app = lock_unpoisoned(&app);
+            for prompt in ["first", "second", "third"] {
+                app.on_input_replace(prompt.to_string());
+                app.on_submit(&mut host);
+            }
+            running_run_id(&app.mode)
+        };
+
+        let queued: Vec<String> = host
+            .queued_turns()
+            .into_iter()
+            .map(|turn| turn.prompt)
+            .collect();
+        assert_eq!(queued, vec!["second".to_string(), "third".to_string()]);
+
+        let mut previous_run_id = first_run_id;
+        for expected in ["second", "third"] {
+            lock_unpoisoned(&app).on_cancel(&mut host);
+            let advanced = wait_until(
+                Duration::from_secs(3),
+                || {
+                    runtime_loop.tick();
+                    host.flush_pending_run_events();
+                },
+                || {
+                    matches!(
+                        lock_unpoisoned(&app).mode,
+                        Mode::Running { run_id } if run_id != previous_run_id
+                    )
+                },
+            );
+            assert!(advanced, "queued turn {expected:?} did not start");
+
+            let app = lock_unpoisoned(&app);
+            previous_run_id = running_run_id(&app.mode);
+            let last_user = app
+                .transcript
+                .iter()
+                .rev()
+                .find(|message| message.role == Role::User)
+                .expect("user message exists");
+            assert_eq!(last_user.content, expected);
+        }
+
+        assert!(host.queued_turns().is_empty());
+        lock_unpoisoned(&app).on_cancel(&mut host);
+        let settled = wait_until(
+            Duration::from_secs(3),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "last queued run did not settle after cancel");
+    });
+}
+
 #[test]
 fn repeated_cancel_is_a_noop_after_first_signal() {
     with_runtime_loop(|runtime_loop| {
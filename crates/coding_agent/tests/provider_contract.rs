@@ -11,11 +11,16 @@ use session_store::{session_root, SessionEntry, SessionEntryKind, SessionSeed, S
 use tempfile::TempDir;
 
 use coding_agent::app::{App, HostOps, Mode, Role, RunId};
+use coding_agent::pricing::{ModelPricing, PricingTable};
 use coding_agent::provider::{
-    CancelSignal, ProviderProfile, RunEvent, RunMessage, RunProvider, RunRequest, ToolCallRequest,
-    ToolResult,
+    CancelSignal, ProviderProfile, RunEvent, RunMessage, RunProvider, RunRequest, TokenUsage,
+    ToolCallRequest, ToolDefinition, ToolResult,
 };
-use coding_agent::runtime::{RuntimeController, POST_TERMINAL_TOOL_REJECTION_ERROR};
+use coding_agent::providers::{MockProvider, MockScenario};
+use coding_agent::runtime::{
+    replayed_run_notes, RuntimeController, POST_TERMINAL_TOOL_REJECTION_ERROR,
+};
+use coding_agent::tools::{PlanStep, PlanStepStatus};
 use tape_tui::{Terminal, TUI};
 
 #[derive(Default)]
@@ -91,6 +96,140 @@ impl RunProvider for LifecycleProvider {
     }
 }
 
+struct UsageReportingProvider;
+
+impl RunProvider for UsageReportingProvider {
+    fn profile(&self) -> ProviderProfile {
+        test_provider_profile()
+    }
+
+    fn run(
+        &self,
+        req: RunRequest,
+        _cancel: CancelSignal,
+        _execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        emit: &mut dyn FnMut(RunEvent),
+    ) -> Result<(), String> {
+        emit(RunEvent::Started { run_id: req.run_id });
+        emit(RunEvent::Chunk {
+            run_id: req.run_id,
+            text: "priced".to_string(),
+        });
+        emit(RunEvent::Usage {
+            run_id: req.run_id,
+            usage: TokenUsage {
+                input_tokens: 1_000,
+                cached_input_tokens: 0,
+                output_tokens: 100,
+            },
+        });
+        emit(RunEvent::Finished { run_id: req.run_id });
+        Ok(())
+    }
+}
+
+struct RetryThenFailProvider;
+
+impl RunProvider for RetryThenFailProvider {
+    fn profile(&self) -> ProviderProfile {
+        test_provider_profile()
+    }
+
+    fn run(
+        &self,
+        req: RunRequest,
+        _cancel: CancelSignal,
+        _execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        emit: &mut dyn FnMut(RunEvent),
+    ) -> Result<(), String> {
+        emit(RunEvent::Started { run_id: req.run_id });
+        emit(RunEvent::AttemptFailed {
+            run_id: req.run_id,
+            attempt: 1,
+            status: Some(503),
+            error: "overloaded".to_string(),
+            will_retry: true,
+        });
+        emit(RunEvent::AttemptFailed {
+            run_id: req.run_id,
+            attempt: 2,
+            status: Some(400),
+            error: "invalid request".to_string(),
+            will_retry: false,
+        });
+        emit(RunEvent::Failed {
+            run_id: req.run_id,
+            error: "invalid request".to_string(),
+        });
+        Ok(())
+    }
+}
+
+struct PlanToolProvider;
+
+impl RunProvider for PlanToolProvider {
+    fn profile(&self) -> ProviderProfile {
+        test_provider_profile()
+    }
+
+    fn run(
+        &self,
+        req: RunRequest,
+        _cancel: CancelSignal,
+        execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        emit: &mut dyn FnMut(RunEvent),
+    ) -> Result<(), String> {
+        emit(RunEvent::Started { run_id: req.run_id });
+        for (call_id, status) in [("plan-1", "in_progress"), ("plan-2", "completed")] {
+            let result = execute_tool(ToolCallRequest {
+                call_id: call_id.to_string(),
+                tool_name: "update_plan".to_string(),
+                arguments: json!({
+                    "plan": [
+                        { "step": "Investigate", "status": status },
+                        { "step": "Fix", "status": "pending" }
+                    ]
+                }),
+            });
+            if result.is_error {
+                return Err(tool_result_content_text(&result));
+            }
+        }
+        emit(RunEvent::Finished { run_id: req.run_id });
+        Ok(())
+    }
+}
+
+const PREVIEW_PROBE_PATH: &str = "preview-rejected-probe.txt";
+
+struct PreviewWriteProvider {
+    result: Arc<Mutex<Option<ToolResult>>>,
+}
+
+impl RunProvider for PreviewWriteProvider {
+    fn profile(&self) -> ProviderProfile {
+        test_provider_profile()
+    }
+
+    fn run(
+        &self,
+        req: RunRequest,
+        _cancel: CancelSignal,
+        execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        emit: &mut dyn FnMut(RunEvent),
+    ) -> Result<(), String> {
+        emit(RunEvent::Started { run_id: req.run_id });
+        let result = execute_tool(ToolCallRequest {
+            call_id: "write-1".to_string(),
+            tool_name: "write".to_string(),
+            arguments: json!({ "path": PREVIEW_PROBE_PATH, "content": "should not land\n" }),
+        });
+        *lock_unpoisoned(&self.result) = Some(result);
+        emit(RunEvent::Finished { run_id: req.run_id });
+        Ok(())
+    }
+}
+
 struct InvocationTrackingProvider {
     invoked: Arc<AtomicBool>,
 }
@@ -120,6 +259,61 @@ impl RunProvider for InvocationTrackingProvider {
     }
 }
 
+struct OutputSchemaCaptureProvider {
+    captured: Arc<Mutex<Vec<Option<serde_json::Value>>>>,
+}
+
+impl RunProvider for OutputSchemaCaptureProvider {
+    fn profile(&self) -> ProviderProfile {
+        test_provider_profile()
+    }
+
+    fn run(
+        &self,
+        req: RunRequest,
+        _cancel: CancelSignal,
+        _execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        emit: &mut dyn FnMut(RunEvent),
+    ) -> Result<(), String> {
+        lock_unpoisoned(&self.captured).push(req.output_schema.clone());
+        emit(RunEvent::Started { run_id: req.run_id });
+        emit(RunEvent::Finished { run_id: req.run_id });
+        Ok(())
+    }
+}
+
+struct PromptCacheCaptureProvider {
+    /// Stable prefix length and message count of each request.
+    captured: Arc<Mutex<Vec<(usize, usize)>>>,
+}
+
+impl RunProvider for PromptCacheCaptureProvider {
+    fn profile(&self) -> ProviderProfile {
+        test_provider_profile()
+    }
+
+    fn run(
+        &self,
+        req: RunRequest,
+        _cancel: CancelSignal,
+        _execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        emit: &mut dyn FnMut(RunEvent),
+    ) -> Result<(), String> {
+        let stable_prefix_len = req
+            .prompt_cache
+            .as_ref()
+            .map_or(0, |hint| hint.stable_prefix_len);
+        lock_unpoisoned(&self.captured).push((stable_prefix_len, req.messages.len()));
+        emit(RunEvent::Started { run_id: req.run_id });
+        emit(RunEvent::Chunk {
+            run_id: req.run_id,
+            text: "reply".to_string(),
+        });
+        emit(RunEvent::Finished { run_id: req.run_id });
+        Ok(())
+    }
+}
+
 struct NoisyTerminalProvider;
 
 impl RunProvider for NoisyTerminalProvider {
@@ -268,6 +462,58 @@ impl RunProvider for UnknownToolProvider {
     }
 }
 
+struct SchemaViolationToolProvider;
+
+impl RunProvider for SchemaViolationToolProvider {
+    fn profile(&self) -> ProviderProfile {
+        test_provider_profile()
+    }
+
+    fn tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition {
+            name: "bash".to_string(),
+            description: None,
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string" },
+                    "timeout_sec": { "type": "integer", "minimum": 1 }
+                },
+                "required": ["command"],
+                "additionalProperties": false
+            }),
+        }]
+    }
+
+    fn run(
+        &self,
+        req: RunRequest,
+        _cancel: CancelSignal,
+        execute_tool: &mut dyn FnMut(ToolCallRequest) -> ToolResult,
+        emit: &mut dyn FnMut(RunEvent),
+    ) -> Result<(), String> {
+        emit(RunEvent::Started { run_id: req.run_id });
+
+        let result = execute_tool(ToolCallRequest {
+            call_id: "call-invalid".to_string(),
+            tool_name: "bash".to_string(),
+            arguments: json!({ "command": "echo schema-ran", "timeout_sec": 0 }),
+        });
+
+        if !result.is_error {
+            return Err("expected schema violation to be an explicit error".to_string());
+        }
+
+        emit(RunEvent::Chunk {
+            run_id: req.run_id,
+            text: format!("schema-error:{}", tool_result_content_text(&result)),
+        });
+        emit(RunEvent::Finished { run_id: req.run_id });
+
+        Ok(())
+    }
+}
+
 struct ExecutionFailureToolProvider;
 
 impl RunProvider for ExecutionFailureToolProvider {
@@ -734,6 +980,16 @@ fn deferred_session_first_submit_materializes_store_and_persists_first_turn() {
         );
         assert!(settled, "deferred persistence run did not settle");
         assert!(sessions_root.exists());
+        assert_eq!(
+            lock_unpoisoned(&app).session_title(),
+            Some("persist deferred first turn")
+        );
+        let sessions =
+            SessionStore::list_sessions(session_workspace.path()).expect("sessions should list");
+        assert_eq!(
+            sessions.sessions[0].title.as_deref(),
+            Some("persist deferred first turn")
+        );
 
         assert_eq!(
             replay_latest_session_messages(session_workspace.path()),
@@ -1148,6 +1404,412 @@ fn start_success_persists_user_turn_in_session_replay() {
     });
 }
 
+#[test]
+fn finished_run_persists_cost_summary_that_replay_skips() {
+    with_runtime_loop(|runtime_loop| {
+        let mut app_state = App::new();
+        app_state.set_pricing_table(PricingTable::new(
+            [(
+                "contract-model".to_string(),
+                ModelPricing::from_usd_per_million(2.0, None, 10.0).expect("rates should be valid"),
+            )]
+            .into_iter()
+            .collect(),
+        ));
+        let app = Arc::new(Mutex::new(app_state));
+        let provider: Arc<dyn RunProvider> = Arc::new(UsageReportingProvider);
+        let (_session_workspace, session_store, session_path) = create_session_store_for_test();
+        let mut host = RuntimeController::new_with_session_store(
+            app.clone(),
+            runtime_loop.runtime_handle(),
+            provider,
+            session_store,
+        );
+
+        submit_prompt(&app, &mut host, "what does this cost");
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "run did not settle");
+        // 1000 input * 2000 + 100 output * 10000 nanos.
+        assert_eq!(lock_unpoisoned(&app).session_cost_micros(), Some(3_000));
+
+        let reopened = SessionStore::open(&session_path).expect("session file should reopen");
+        assert_eq!(reopened.entry_count(), 3);
+        assert_eq!(
+            reopened
+                .replay_session_cost_micros(None)
+                .expect("cost replay should succeed"),
+            Some(3_000)
+        );
+        assert_eq!(
+            replay_session_messages(&session_path),
+            vec![
+                RunMessage::UserText {
+                    text: "what does this cost".to_string(),
+                },
+                RunMessage::AssistantText {
+                    text: "priced".to_string(),
+                }
+            ]
+        );
+
+        let raw = fs::read_to_string(&session_path).expect("session file should be readable");
+        let summary_line = raw
+            .lines()
+            .find(|line| line.contains("\"kind\":\"cost_summary\""))
+            .expect("cost summary entry should be written");
+        let summary: serde_json::Value =
+            serde_json::from_str(summary_line).expect("summary line should be JSON");
+        assert_eq!(summary["model_id"], json!("contract-model"));
+        assert_eq!(summary["input_tokens"], json!(1_000));
+        assert_eq!(summary["output_tokens"], json!(100));
+        assert_eq!(summary["run_cost_micros"], json!(3_000));
+    });
+}
+
+#[test]
+fn failed_run_persists_retries_and_replays_them_as_transcript_notes() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(RetryThenFailProvider);
+        let (_session_workspace, session_store, session_path) = create_session_store_for_test();
+        let mut host = RuntimeController::new_with_session_store(
+            app.clone(),
+            runtime_loop.runtime_handle(),
+            provider,
+            session_store,
+        );
+
+        submit_prompt(&app, &mut host, "try this");
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Error(_)),
+        );
+        assert!(settled, "run did not fail");
+
+        let live_notes: Vec<String> = lock_unpoisoned(&app)
+            .transcript
+            .iter()
+            .filter(|message| message.role == Role::System)
+            .map(|message| message.content.clone())
+            .collect();
+        assert_eq!(
+            live_notes,
+            vec![
+                "Attempt 1 failed (HTTP 503), retrying: overloaded".to_string(),
+                "Run failed (HTTP 400, after 2 attempts): invalid request".to_string(),
+            ]
+        );
+
+        let raw = fs::read_to_string(&session_path).expect("session file should be readable");
+        let outcome_line = raw
+            .lines()
+            .find(|line| line.contains("\"kind\":\"run_outcome\""))
+            .expect("run outcome entry should be written");
+        let outcome: serde_json::Value =
+            serde_json::from_str(outcome_line).expect("outcome line should be JSON");
+        assert_eq!(outcome["outcome"], json!("failed"));
+        assert_eq!(outcome["status"], json!(400));
+        assert_eq!(outcome["failed_attempts"][0]["status"], json!(503));
+
+        let reopened = SessionStore::open(&session_path).expect("session file should reopen");
+        let mut resumed = App::new();
+        resumed.restore_conversation(reopened.replay_leaf(None).expect("replay"));
+        resumed.restore_system_notes(replayed_run_notes(
+            &reopened
+                .replay_run_outcomes(None)
+                .expect("outcome replay should succeed"),
+        ));
+        let resumed_transcript: Vec<String> = resumed
+            .transcript
+            .iter()
+            .map(|message| message.content.clone())
+            .collect();
+        let mut expected = vec!["try this".to_string()];
+        expected.extend(live_notes);
+        assert_eq!(resumed_transcript, expected);
+    });
+}
+
+#[test]
+fn update_plan_tool_replaces_app_plan_with_latest_call() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(PlanToolProvider);
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+
+        submit_prompt(&app, &mut host, "make a plan");
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "run did not settle");
+
+        let app = lock_unpoisoned(&app);
+        let plan = app.plan().expect("plan should be recorded");
+        assert_eq!(
+            plan.steps,
+            vec![
+                PlanStep {
+                    step: "Investigate".to_string(),
+                    status: PlanStepStatus::Completed,
+                },
+                PlanStep {
+                    step: "Fix".to_string(),
+                    status: PlanStepStatus::Pending,
+                },
+            ]
+        );
+    });
+}
+
+#[test]
+fn mock_scenario_fixture_drives_tool_roundtrip_through_the_host() {
+    let scenario = MockScenario::parse(
+        r#"{"runs": [[
+            {"type": "chunk", "text": "Planning. "},
+            {"type": "tool_call", "tool_name": "update_plan", "arguments": {"plan": [{"step": "Investigate", "status": "completed"}]}},
+            {"type": "chunk", "text": "Done."},
+            {"type": "finish"}
+        ]]}"#,
+    )
+    .expect("fixture should parse");
+
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(MockProvider::with_scenario(scenario));
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+
+        submit_prompt(&app, &mut host, "plan it");
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "run did not settle");
+
+        let app = lock_unpoisoned(&app);
+        assert_eq!(
+            app.plan().expect("plan should be recorded").steps,
+            vec![PlanStep {
+                step: "Investigate".to_string(),
+                status: PlanStepStatus::Completed,
+            }]
+        );
+        assert!(app.conversation_messages().iter().any(|message| matches!(
+            message,
+            RunMessage::ToolResult { call_id, is_error: false, .. } if call_id == "mock-call-1-1"
+        )));
+        assert!(app
+            .transcript
+            .iter()
+            .any(|message| message.role == Role::Assistant && message.content.contains("Done.")));
+    });
+}
+
+#[test]
+fn spawn_agent_runs_a_restricted_child_and_returns_its_final_answer() {
+    let scenario = MockScenario::parse(
+        r#"{"runs": [
+            [
+                {"type": "tool_call", "tool_name": "spawn_agent", "arguments": {"task": "Summarize the manifest", "context": "We are auditing dependencies."}, "expect": {}},
+                {"type": "chunk", "text": "Parent done."},
+                {"type": "finish"}
+            ],
+            [
+                {"type": "chunk", "text": "Looking. "},
+                {"type": "tool_call", "tool_name": "bash", "arguments": {"command": "ls"}, "expect": {"is_error": true, "contains": "not available to this sub-agent"}},
+                {"type": "tool_call", "tool_name": "read", "arguments": {"path": "Cargo.toml"}, "expect": {}},
+                {"type": "chunk", "text": "The manifest names coding_agent."},
+                {"type": "finish"}
+            ]
+        ]}"#,
+    )
+    .expect("fixture should parse");
+
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(MockProvider::with_scenario(scenario));
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+
+        let run_id = submit_prompt(&app, &mut host, "delegate it");
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "run did not settle");
+
+        let app = lock_unpoisoned(&app);
+        assert!(app.conversation_messages().iter().any(|message| matches!(
+            message,
+            RunMessage::ToolResult { call_id, content, is_error: false, .. }
+                if call_id == "mock-call-1-1"
+                    && content == &json!("The manifest names coding_agent.")
+        )));
+
+        let steps = app.subagent_steps(run_id, "mock-call-1-1");
+        let summary: Vec<(&str, Option<bool>)> = steps
+            .iter()
+            .map(|step| (step.tool_name.as_str(), step.outcome))
+            .collect();
+        assert_eq!(summary, vec![("bash", Some(true)), ("read", Some(false))]);
+
+        let tool_rows: Vec<&str> = app
+            .transcript
+            .iter()
+            .filter(|message| message.role == Role::Tool)
+            .map(|message| message.content.as_str())
+            .collect();
+        assert_eq!(
+            tool_rows,
+            vec![
+                "Tool spawn_agent (mock-call-1-1) started",
+                "Tool spawn_agent (mock-call-1-1) completed",
+            ]
+        );
+    });
+}
+
+#[test]
+fn preview_mode_rejection_returns_error_result_without_touching_disk() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let result = Arc::new(Mutex::new(None));
+        let provider: Arc<dyn RunProvider> = Arc::new(PreviewWriteProvider {
+            result: Arc::clone(&result),
+        });
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+        let probe = std::env::current_dir()
+            .expect("current dir")
+            .join(PREVIEW_PROBE_PATH);
+        assert!(!probe.exists(), "stale probe file at {}", probe.display());
+
+        {
+            let mut app = lock_unpoisoned(&app);
+            app.on_input_replace("/preview".to_string());
+            app.on_submit(&mut host);
+            assert!(app.tool_preview());
+        }
+
+        submit_prompt(&app, &mut host, "write a file");
+        let awaiting = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || lock_unpoisoned(&app).pending_tool_confirmation().is_some(),
+        );
+        assert!(awaiting, "write call should pause for confirmation");
+        {
+            let app = lock_unpoisoned(&app);
+            let confirmation = app
+                .pending_tool_confirmation()
+                .expect("pending confirmation");
+            assert_eq!(confirmation.tool_name, "write");
+            assert!(confirmation.diff.contains("+should not land"));
+        }
+        assert!(!probe.exists(), "preview must not write before approval");
+
+        lock_unpoisoned(&app).resolve_tool_confirmation(false, &mut host);
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "run did not settle");
+
+        let result = lock_unpoisoned(&result).clone().expect("tool result");
+        assert!(result.is_error);
+        assert!(tool_result_content_text(&result).contains("User rejected write in preview mode"));
+        assert!(!probe.exists(), "rejected write must not touch disk");
+        assert!(lock_unpoisoned(&app).pending_tool_confirmation().is_none());
+    });
+}
+
+#[test]
+fn mock_scenario_routes_rejected_preview_result_into_transcript() {
+    let probe_path = "mock-scenario-preview-probe.txt";
+    let scenario = MockScenario::parse(&format!(
+        r#"[{{"type": "tool_call", "tool_name": "write", "arguments": {{"path": "{probe_path}", "content": "nope\n"}}, "expect": {{"is_error": true, "contains": "User rejected write"}}, "echo_result": true}}]"#
+    ))
+    .expect("fixture should parse");
+
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider = Arc::new(MockProvider::with_scenario(scenario));
+        let mut host = RuntimeController::new(
+            app.clone(),
+            runtime_loop.runtime_handle(),
+            Arc::clone(&provider) as Arc<dyn RunProvider>,
+        );
+        let probe = std::env::current_dir()
+            .expect("current dir")
+            .join(probe_path);
+        assert!(!probe.exists(), "stale probe file at {}", probe.display());
+
+        {
+            let mut app = lock_unpoisoned(&app);
+            app.on_input_replace("/preview".to_string());
+            app.on_submit(&mut host);
+        }
+        submit_prompt(&app, &mut host, "write a file");
+        let awaiting = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || lock_unpoisoned(&app).pending_tool_confirmation().is_some(),
+        );
+        assert!(awaiting, "scripted write should pause for confirmation");
+
+        lock_unpoisoned(&app).resolve_tool_confirmation(false, &mut host);
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+        );
+        assert!(settled, "run did not settle");
+
+        let results = provider.tool_results();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_error);
+        assert!(!probe.exists(), "rejected write must not touch disk");
+        let app = lock_unpoisoned(&app);
+        assert!(app.transcript.iter().any(|message| {
+            message.role == Role::Assistant && message.content.contains("User rejected write")
+        }));
+    });
+}
+
 #[test]
 fn start_failure_non_run_active_persists_user_turn_in_session_replay() {
     with_runtime_loop(|runtime_loop| {
@@ -1979,6 +2641,48 @@ fn unknown_tool_call_produces_explicit_error_result_and_tool_failure_timeline()
     });
 }
 
+#[test]
+fn tool_arguments_violating_the_schema_are_rejected_before_execution() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(SchemaViolationToolProvider);
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+
+        let run_id = submit_prompt(&app, &mut host, "invalid tool arguments");
+
+        let settled = wait_until(
+            Duration::from_secs(2),
+            || {
+                runtime_loop.tick();
+                host.flush_pending_run_events();
+            },
+            || {
+                let app = lock_unpoisoned(&app);
+                matches!(app.mode, Mode::Idle)
+                    && app.transcript.iter().any(|message| {
+                        message.role == Role::Assistant && message.run_id == Some(run_id)
+                    })
+            },
+        );
+        assert!(settled, "run with invalid tool arguments did not settle");
+
+        let app = lock_unpoisoned(&app);
+        let reply = app
+            .transcript
+            .iter()
+            .find(|message| message.role == Role::Assistant && message.run_id == Some(run_id))
+            .expect("assistant reply");
+        assert_eq!(
+            reply.content,
+            "schema-error:Invalid arguments for tool 'bash': field 'timeout_sec' must be at least 1, got 0"
+        );
+        assert!(app
+            .transcript
+            .iter()
+            .all(|message| !message.content.contains("schema-ran")));
+    });
+}
+
 #[test]
 fn tool_execution_failure_produces_explicit_error_result_and_tool_failure_timeline() {
     with_runtime_loop(|runtime_loop| {
@@ -2595,3 +3299,70 @@ fn runtime_composes_non_empty_instructions_with_tool_policy() {
         assert!(instructions.contains("apply_patch"));
     });
 }
+
+#[test]
+fn runtime_attaches_host_output_schema_to_subsequent_run_requests() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(OutputSchemaCaptureProvider {
+            captured: Arc::clone(&captured),
+        });
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+        let schema = json!({"type": "object", "properties": {"ok": {"type": "boolean"}}});
+
+        let mut run_and_settle = |host: &mut Arc<RuntimeController>, prompt: &str| {
+            submit_prompt(&app, host, prompt);
+            let settled = wait_until(
+                Duration::from_secs(2),
+                || {
+                    runtime_loop.tick();
+                    host.flush_pending_run_events();
+                },
+                || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+            );
+            assert!(settled, "run for {prompt:?} did not settle");
+        };
+
+        run_and_settle(&mut host, "free text");
+        host.set_output_schema(Some(schema.clone()));
+        run_and_settle(&mut host, "structured");
+        host.set_output_schema(None);
+        run_and_settle(&mut host, "free text again");
+
+        assert_eq!(*lock_unpoisoned(&captured), vec![None, Some(schema), None]);
+    });
+}
+
+#[test]
+fn runtime_marks_the_previous_prompt_as_a_stable_cache_prefix() {
+    with_runtime_loop(|runtime_loop| {
+        let app = Arc::new(Mutex::new(App::new()));
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let provider: Arc<dyn RunProvider> = Arc::new(PromptCacheCaptureProvider {
+            captured: Arc::clone(&captured),
+        });
+        let mut host = RuntimeController::new(app.clone(), runtime_loop.runtime_handle(), provider);
+
+        let mut run_and_settle = |host: &mut Arc<RuntimeController>, prompt: &str| {
+            submit_prompt(&app, host, prompt);
+            let settled = wait_until(
+                Duration::from_secs(2),
+                || {
+                    runtime_loop.tick();
+                    host.flush_pending_run_events();
+                },
+                || matches!(lock_unpoisoned(&app).mode, Mode::Idle),
+            );
+            assert!(settled, "run for {prompt:?} did not settle");
+        };
+
+        run_and_settle(&mut host, "first");
+        run_and_settle(&mut host, "second");
+        run_and_settle(&mut host, "third");
+
+        // Each prompt repeats the previous one, followed by its reply and the
+        // new user message.
+        assert_eq!(*lock_unpoisoned(&captured), vec![(0, 1), (1, 3), (3, 5)]);
+    });
+}
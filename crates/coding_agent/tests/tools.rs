@@ -1,7 +1,9 @@
 use std::fs;
 use std::path::Path;
 
-use coding_agent::tools::{BuiltinToolExecutor, ToolCall, ToolExecutor};
+use coding_agent::tools::{
+    BuiltinToolExecutor, Plan, PlanStep, PlanStepStatus, ToolCall, ToolExecutor,
+};
 use tempfile::tempdir;
 
 fn new_executor(workspace_root: &Path) -> BuiltinToolExecutor {
@@ -391,3 +393,617 @@ fn apply_patch_io_failure_reports_partial_mutation_when_writes_started() {
         "first mutation should remain on disk when later IO fails"
     );
 }
+
+#[test]
+fn update_plan_reports_progress_and_rejects_invalid_plans() {
+    let workspace = tempdir().expect("temp workspace");
+    let mut executor = new_executor(workspace.path());
+    let step = |text: &str, status| PlanStep {
+        step: text.to_string(),
+        status,
+    };
+
+    let result = executor.execute(ToolCall::UpdatePlan {
+        plan: Plan {
+            explanation: None,
+            steps: vec![
+                step("Inspect", PlanStepStatus::Completed),
+                step("Implement", PlanStepStatus::InProgress),
+                step("Verify", PlanStepStatus::Pending),
+            ],
+        },
+    });
+    assert!(result.ok, "valid plan should succeed: {}", result.content);
+    assert_eq!(result.content, "Plan updated: 1/3 steps completed");
+
+    let empty = executor.execute(ToolCall::UpdatePlan {
+        plan: Plan::default(),
+    });
+    assert!(!empty.ok);
+    assert!(empty.content.contains("at least one step"));
+
+    let two_active = executor.execute(ToolCall::UpdatePlan {
+        plan: Plan {
+            explanation: None,
+            steps: vec![
+                step("A", PlanStepStatus::InProgress),
+                step("B", PlanStepStatus::InProgress),
+            ],
+        },
+    });
+    assert!(!two_active.ok);
+    assert!(two_active.content.contains("at most one in_progress"));
+
+    let blank = executor.execute(ToolCall::UpdatePlan {
+        plan: Plan {
+            explanation: None,
+            steps: vec![step("  ", PlanStepStatus::Pending)],
+        },
+    });
+    assert!(!blank.ok);
+    assert!(blank.content.contains("step 1 must have non-empty text"));
+}
+
+fn run_git(workspace_root: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(workspace_root)
+        .status()
+        .expect("git should launch");
+    assert!(status.success(), "git {args:?} should succeed");
+}
+
+fn init_git_repo(workspace_root: &Path) {
+    run_git(
+        workspace_root,
+        &["init", "--quiet", "--initial-branch=main"],
+    );
+    run_git(workspace_root, &["config", "user.name", "Tape Test"]);
+    run_git(
+        workspace_root,
+        &["config", "user.email", "tape@example.com"],
+    );
+    run_git(workspace_root, &["config", "commit.gpgsign", "false"]);
+}
+
+fn json_output(output: &coding_agent::tools::ToolOutput) -> serde_json::Value {
+    assert!(output.ok, "git tool should succeed: {}", output.content);
+    serde_json::from_str(&output.content).expect("git tool output should be JSON")
+}
+
+#[test]
+fn git_tools_report_status_diff_and_commit_as_json() {
+    let workspace = tempdir().expect("temp workspace");
+    init_git_repo(workspace.path());
+    let mut executor = new_executor(workspace.path());
+
+    fs::write(workspace.path().join("a.txt"), "one\n").expect("write a.txt");
+    let status = json_output(&executor.execute(ToolCall::GitStatus));
+    assert_eq!(status["branch"], "main");
+    assert_eq!(status["clean"], false);
+    assert_eq!(status["entries"][0]["path"], "a.txt");
+    assert_eq!(status["entries"][0]["worktree"], "untracked");
+
+    let commit = json_output(&executor.execute(ToolCall::GitCommit {
+        message: "Add a.txt\n\nBody text".to_string(),
+        paths: Some(vec!["a.txt".to_string()]),
+        all: false,
+    }));
+    assert_eq!(commit["summary"], "Add a.txt");
+    assert_eq!(commit["commit"].as_str().map(str::len), Some(40));
+    assert_eq!(
+        commit["files"],
+        serde_json::json!([{ "path": "a.txt", "status": "added" }])
+    );
+
+    fs::write(workspace.path().join("a.txt"), "two\n").expect("rewrite a.txt");
+    let diff = json_output(&executor.execute(ToolCall::GitDiff {
+        path: None,
+        staged: false,
+    }));
+    assert_eq!(
+        diff["files"],
+        serde_json::json!([{ "path": "a.txt", "additions": 1, "deletions": 1 }])
+    );
+    let patch = diff["diff"].as_str().expect("diff text");
+    assert!(patch.contains("-one\n+two"), "unexpected diff: {patch}");
+    assert_eq!(diff["truncated"], false);
+
+    let staged = json_output(&executor.execute(ToolCall::GitDiff {
+        path: Some("a.txt".to_string()),
+        staged: true,
+    }));
+    assert_eq!(staged["files"], serde_json::json!([]));
+
+    let commit_all = json_output(&executor.execute(ToolCall::GitCommit {
+        message: "Update a.txt".to_string(),
+        paths: None,
+        all: true,
+    }));
+    assert_eq!(
+        commit_all["files"],
+        serde_json::json!([{ "path": "a.txt", "status": "modified" }])
+    );
+    let status = json_output(&executor.execute(ToolCall::GitStatus));
+    assert_eq!(status["clean"], true);
+}
+
+#[test]
+fn git_tools_surface_git_errors_and_reject_escaping_paths() {
+    let workspace = tempdir().expect("temp workspace");
+    init_git_repo(workspace.path());
+    let mut executor = new_executor(workspace.path());
+
+    let empty_commit = executor.execute(ToolCall::GitCommit {
+        message: "Nothing".to_string(),
+        paths: None,
+        all: false,
+    });
+    assert!(!empty_commit.ok);
+    assert!(
+        empty_commit.content.starts_with("git commit failed"),
+        "unexpected error: {}",
+        empty_commit.content
+    );
+
+    let blank_message = executor.execute(ToolCall::GitCommit {
+        message: "   ".to_string(),
+        paths: None,
+        all: false,
+    });
+    assert!(!blank_message.ok);
+    assert!(blank_message.content.contains("non-empty message"));
+
+    let escaping = executor.execute(ToolCall::GitDiff {
+        path: Some("../outside.txt".to_string()),
+        staged: false,
+    });
+    assert!(!escaping.ok);
+    assert!(escaping.content.contains("Path escapes workspace root"));
+}
+
+#[test]
+fn mutating_tools_record_pre_mutation_snapshots() {
+    let workspace = tempdir().expect("temp workspace");
+    fs::write(workspace.path().join("existing.txt"), "one\n").expect("seed file");
+    let mut executor = new_executor(workspace.path());
+    let root = executor.workspace_root().to_path_buf();
+
+    assert!(
+        executor
+            .execute(ToolCall::EditFile {
+                path: "existing.txt".to_string(),
+                old_text: "one".to_string(),
+                new_text: "two".to_string(),
+            })
+            .ok
+    );
+    assert!(
+        executor
+            .execute(ToolCall::WriteFile {
+                path: "nested/new.txt".to_string(),
+                content: "fresh".to_string(),
+            })
+            .ok
+    );
+    assert!(
+        executor
+            .execute(ToolCall::ApplyPatch {
+                input: "*** Begin Patch\n*** Delete File: existing.txt\n*** End Patch".to_string(),
+            })
+            .ok
+    );
+    assert!(
+        executor
+            .execute(ToolCall::ReadFile {
+                path: "nested/new.txt".to_string(),
+            })
+            .ok
+    );
+
+    let snapshots = executor.take_file_snapshots();
+    let summary: Vec<_> = snapshots
+        .iter()
+        .map(|snapshot| {
+            (
+                snapshot.path.strip_prefix(&root).map(Path::to_path_buf),
+                snapshot.original.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (Ok("existing.txt".into()), Some(b"one\n".to_vec())),
+            (Ok("nested/new.txt".into()), None),
+            (Ok("existing.txt".into()), Some(b"two\n".to_vec())),
+        ]
+    );
+    assert!(executor.take_file_snapshots().is_empty());
+}
+
+#[test]
+fn preview_reports_would_be_diffs_without_touching_disk() {
+    let workspace = tempdir().expect("temp workspace");
+    fs::write(workspace.path().join("existing.txt"), "one\ntwo\n").expect("seed file");
+    let executor = new_executor(workspace.path());
+
+    let edit = executor
+        .preview(&ToolCall::EditFile {
+            path: "existing.txt".to_string(),
+            old_text: "two".to_string(),
+            new_text: "three".to_string(),
+        })
+        .expect("edit is previewable")
+        .expect("edit preview should succeed");
+    assert!(edit.contains("--- a/existing.txt"));
+    assert!(edit.contains("-two"));
+    assert!(edit.contains("+three"));
+
+    let write = executor
+        .preview(&ToolCall::WriteFile {
+            path: "nested/new.txt".to_string(),
+            content: "fresh\n".to_string(),
+        })
+        .expect("write is previewable")
+        .expect("write preview should succeed");
+    assert!(write.contains("--- /dev/null"));
+    assert!(write.contains("+fresh"));
+
+    let patch = executor
+        .preview(&ToolCall::ApplyPatch {
+            input: "*** Begin Patch\n*** Update File: existing.txt\n*** Move to: moved.txt\n@@\n one\n-two\n+2\n*** End Patch"
+                .to_string(),
+        })
+        .expect("apply_patch is previewable")
+        .expect("patch preview should succeed");
+    assert!(patch.contains("--- a/existing.txt"));
+    assert!(patch.contains("+++ b/moved.txt"));
+    assert!(patch.contains("+2"));
+
+    assert_eq!(
+        fs::read_to_string(workspace.path().join("existing.txt")).expect("read seed"),
+        "one\ntwo\n"
+    );
+    assert!(!workspace.path().join("nested").exists());
+    assert!(!workspace.path().join("moved.txt").exists());
+}
+
+#[test]
+fn preview_mirrors_execution_validation_and_skips_read_only_tools() {
+    let workspace = tempdir().expect("temp workspace");
+    fs::write(workspace.path().join("existing.txt"), "same same\n").expect("seed file");
+    let executor = new_executor(workspace.path());
+
+    let error = executor
+        .preview(&ToolCall::EditFile {
+            path: "existing.txt".to_string(),
+            old_text: "same".to_string(),
+            new_text: "other".to_string(),
+        })
+        .expect("edit is previewable")
+        .expect_err("ambiguous edit should fail preview");
+    assert!(error.contains("exactly one match"), "{error}");
+
+    let escape = executor
+        .preview(&ToolCall::WriteFile {
+            path: "../outside.txt".to_string(),
+            content: "x".to_string(),
+        })
+        .expect("write is previewable");
+    assert!(escape.is_err());
+
+    assert!(executor
+        .preview(&ToolCall::ReadFile {
+            path: "existing.txt".to_string(),
+        })
+        .is_none());
+    assert!(executor.preview(&ToolCall::GitStatus).is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn file_tools_reject_symlink_escapes_including_apply_patch_targets() {
+    use std::os::unix::fs::symlink;
+
+    let outer = tempdir().expect("outer temp dir");
+    let workspace_root = outer.path().join("workspace");
+    let outside = outer.path().join("outside");
+    fs::create_dir_all(&workspace_root).expect("create workspace root");
+    fs::create_dir_all(&outside).expect("create outside dir");
+    fs::write(outside.join("secret.txt"), "secret\n").expect("write outside file");
+    symlink(&outside, workspace_root.join("linked")).expect("create dir symlink");
+    let mut executor = new_executor(&workspace_root);
+
+    let write = executor.execute(ToolCall::WriteFile {
+        path: "linked/new.txt".to_string(),
+        content: "forbidden".to_string(),
+    });
+    assert!(!write.ok);
+    assert!(write.content.contains("via symlink"), "{}", write.content);
+
+    let patch = executor.execute(ToolCall::ApplyPatch {
+        input: "*** Begin Patch\n*** Update File: linked/secret.txt\n@@\n-secret\n+leaked\n*** End Patch"
+            .to_string(),
+    });
+    assert!(!patch.ok);
+    assert!(
+        patch.content.contains("apply_patch path escape rejected"),
+        "{}",
+        patch.content
+    );
+    assert_eq!(
+        fs::read_to_string(outside.join("secret.txt")).expect("read outside file"),
+        "secret\n"
+    );
+    assert!(!outside.join("new.txt").exists());
+}
+
+#[test]
+fn allowlisted_paths_are_writable_outside_the_workspace() {
+    let outer = tempdir().expect("outer temp dir");
+    let workspace_root = outer.path().join("workspace");
+    let shared = outer.path().join("shared");
+    fs::create_dir_all(&workspace_root).expect("create workspace root");
+    fs::create_dir_all(&shared).expect("create shared dir");
+    let mut executor = new_executor(&workspace_root);
+    let target = shared.join("notes.txt").to_string_lossy().into_owned();
+
+    let rejected = executor.execute(ToolCall::WriteFile {
+        path: target.clone(),
+        content: "denied".to_string(),
+    });
+    assert!(!rejected.ok);
+
+    executor.allow_path(&shared).expect("allow shared dir");
+    let allowed = executor.execute(ToolCall::WriteFile {
+        path: target,
+        content: "shared".to_string(),
+    });
+    assert!(allowed.ok, "{}", allowed.content);
+    assert_eq!(
+        fs::read_to_string(shared.join("notes.txt")).expect("read shared file"),
+        "shared"
+    );
+}
+
+fn bash(executor: &mut BuiltinToolExecutor, command: &str) -> coding_agent::tools::ToolOutput {
+    executor.execute(ToolCall::Bash {
+        command: command.to_string(),
+        timeout_sec: Some(5),
+        cwd: None,
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn persistent_shell_keeps_cwd_and_exports_until_restarted() {
+    let workspace = tempdir().expect("temp workspace");
+    fs::create_dir_all(workspace.path().join("sub")).expect("create subdir");
+    let mut executor = new_executor(workspace.path());
+    executor
+        .set_persistent_shell(true)
+        .expect("persistent shell on unix");
+    let root = executor.workspace_root().to_path_buf();
+
+    let first = bash(&mut executor, "cd sub && export TAPE_PERSIST=kept");
+    assert!(first.ok, "{}", first.content);
+    assert!(
+        first
+            .content
+            .contains(&format!("cwd: {}", root.join("sub").display())),
+        "{}",
+        first.content
+    );
+
+    let second = bash(
+        &mut executor,
+        "echo \"$TAPE_PERSIST\"; test -t 1 && echo tty",
+    );
+    assert!(second.ok, "{}", second.content);
+    assert!(
+        second.content.ends_with("output:\nkept\ntty\n"),
+        "{}",
+        second.content
+    );
+
+    let failed = bash(&mut executor, "false");
+    assert!(!failed.ok);
+    assert!(
+        failed.content.starts_with("status: exit_code=1\n"),
+        "{}",
+        failed.content
+    );
+
+    let restart = executor.execute(ToolCall::RestartShell);
+    assert!(restart.ok);
+    assert!(
+        restart.content.contains("Persistent shell stopped"),
+        "{}",
+        restart.content
+    );
+
+    let after = bash(&mut executor, "echo \"[$TAPE_PERSIST]\"");
+    assert!(after
+        .content
+        .contains(&format!("cwd: {}\n", root.display())));
+    assert!(
+        after.content.ends_with("output:\n[]\n"),
+        "{}",
+        after.content
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn persistent_shell_restarts_after_timeout_exit_and_idle_reaping() {
+    let workspace = tempdir().expect("temp workspace");
+    let mut executor = new_executor(workspace.path());
+    executor
+        .set_persistent_shell(true)
+        .expect("persistent shell on unix");
+
+    assert!(bash(&mut executor, "export TAPE_PERSIST=1").ok);
+    let timed_out = executor.execute(ToolCall::Bash {
+        command: "sleep 5".to_string(),
+        timeout_sec: Some(1),
+        cwd: None,
+    });
+    assert!(!timed_out.ok);
+    assert!(timed_out.content.starts_with("status: timeout after 1s"));
+    assert!(timed_out.content.contains("next call starts a fresh shell"));
+    assert!(bash(&mut executor, "echo \"[$TAPE_PERSIST]\"")
+        .content
+        .ends_with("[]\n"));
+
+    let exited = bash(&mut executor, "exit 3");
+    assert!(!exited.ok);
+    assert!(
+        exited.content.contains("shell exited (exit_code=3)"),
+        "{}",
+        exited.content
+    );
+    assert!(bash(&mut executor, "true").ok);
+
+    executor.set_shell_idle_timeout(std::time::Duration::from_millis(100));
+    assert!(executor.execute(ToolCall::RestartShell).ok);
+    assert!(bash(&mut executor, "export TAPE_PERSIST=2").ok);
+    std::thread::sleep(std::time::Duration::from_millis(400));
+    let reaped = bash(&mut executor, "echo \"[$TAPE_PERSIST]\"");
+    assert!(
+        reaped.content.contains("reaped after 0s idle"),
+        "{}",
+        reaped.content
+    );
+    assert!(reaped.content.ends_with("[]\n"), "{}", reaped.content);
+}
+
+#[test]
+fn restart_shell_is_a_no_op_without_persistent_mode() {
+    let workspace = tempdir().expect("temp workspace");
+    let mut executor = new_executor(workspace.path());
+
+    let restart = executor.execute(ToolCall::RestartShell);
+    assert!(restart.ok);
+    assert!(
+        restart.content.contains("mode is off"),
+        "{}",
+        restart.content
+    );
+}
+
+fn process_json(output: &coding_agent::tools::ToolOutput) -> serde_json::Value {
+    assert!(output.ok, "{}", output.content);
+    serde_json::from_str(&output.content).expect("process tool output should be JSON")
+}
+
+#[test]
+fn background_processes_report_logs_status_and_can_be_killed() {
+    let workspace = tempdir().expect("temp workspace");
+    let mut executor = new_executor(workspace.path());
+    let table = executor.process_table();
+
+    let started = process_json(&executor.execute(ToolCall::BashBackground {
+        command: "echo ready; echo oops >&2; exec sleep 30".to_string(),
+        cwd: None,
+    }));
+    let id = started["id"].as_str().expect("id").to_string();
+    assert_eq!(started["status"], "running");
+    assert_eq!(table.running().len(), 1);
+
+    let mut logs = serde_json::Value::Null;
+    for _ in 0..100 {
+        logs = process_json(&executor.execute(ToolCall::ProcessLogs {
+            id: id.clone(),
+            tail_lines: None,
+        }));
+        if logs["logs"]
+            .as_str()
+            .is_some_and(|text| text.contains("oops"))
+        {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let text = logs["logs"].as_str().expect("logs");
+    assert!(
+        text.contains("ready\n") && text.contains("oops\n"),
+        "{text}"
+    );
+
+    let tail = process_json(&executor.execute(ToolCall::ProcessLogs {
+        id: id.clone(),
+        tail_lines: Some(1),
+    }));
+    assert_eq!(tail["truncated"], true);
+    assert_eq!(
+        tail["logs"].as_str().map(|text| text.lines().count()),
+        Some(1)
+    );
+
+    let killed = process_json(&executor.execute(ToolCall::ProcessKill { id: id.clone() }));
+    assert_eq!(killed["status"], "exited");
+    assert!(table.running().is_empty());
+
+    let quick = process_json(&executor.execute(ToolCall::BashBackground {
+        command: "exit 4".to_string(),
+        cwd: None,
+    }));
+    let quick_id = quick["id"].as_str().expect("id").to_string();
+    let mut status = serde_json::Value::Null;
+    for _ in 0..100 {
+        status = process_json(&executor.execute(ToolCall::ProcessStatus {
+            id: Some(quick_id.clone()),
+        }));
+        if status["status"] == "exited" {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert_eq!(status["exit_code"], 4);
+
+    let all = process_json(&executor.execute(ToolCall::ProcessStatus { id: None }));
+    assert_eq!(all["processes"].as_array().map(Vec::len), Some(2));
+
+    let unknown = executor.execute(ToolCall::ProcessKill {
+        id: "proc-99".to_string(),
+    });
+    assert!(!unknown.ok);
+    assert!(unknown.content.contains("Unknown background process"));
+}
+
+#[test]
+fn dropping_the_executor_kills_running_background_processes() {
+    let workspace = tempdir().expect("temp workspace");
+    let marker = workspace.path().join("still-running");
+    let mut executor = new_executor(workspace.path());
+
+    let started = process_json(&executor.execute(ToolCall::BashBackground {
+        command: format!("sleep 1; touch '{}'", marker.display()),
+        cwd: None,
+    }));
+    assert_eq!(started["status"], "running");
+    drop(executor);
+
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    assert!(!marker.exists(), "background process outlived the executor");
+}
+
+#[test]
+fn kill_all_stops_running_processes_while_table_handles_remain() {
+    let workspace = tempdir().expect("temp workspace");
+    let marker = workspace.path().join("still-running");
+    let mut executor = new_executor(workspace.path());
+    let table = executor.process_table();
+
+    let started = process_json(&executor.execute(ToolCall::BashBackground {
+        command: format!("sleep 1; touch '{}'", marker.display()),
+        cwd: None,
+    }));
+    assert_eq!(started["status"], "running");
+    table.kill_all();
+    assert!(table.running().is_empty());
+
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    assert!(!marker.exists(), "background process outlived kill_all");
+}
@@ -5,7 +5,9 @@
 //! - header creation and each append are `sync_data`-durable before success;
 //! - malformed lines, unknown fields/kinds, invalid graph edges, duplicate ids,
 //!   unsupported versions, and invalid replay leaves are hard errors;
-//! - storage root is `<cwd>/.agent/sessions/` for new sessions.
+//! - storage root is `<cwd>/.agent/sessions/` for new sessions;
+//! - the header's optional `title` is the only rewritten field: setting it
+//!   replaces the file through a synced temporary file.
 //!
 //! No tolerant parsing, repair, or reset-marker semantics are included in v1.
 
@@ -17,7 +19,9 @@ mod store;
 
 pub use error::SessionStoreError;
 pub use paths::{session_file_name, session_root};
+pub use replay::{ReplayedRunOutcome, ReplayedTurnCheckpoints};
 pub use schema::{
-    EntryRecordType, SessionEntry, SessionEntryKind, SessionHeader, SessionRecordType,
+    EntryRecordType, FailedAttempt, RunOutcomeKind, SessionEntry, SessionEntryKind, SessionHeader,
+    SessionRecordType,
 };
-pub use store::{SessionSeed, SessionStore};
+pub use store::{SessionList, SessionSeed, SessionStore, SessionSummary, UnreadableSession};
@@ -3,14 +3,147 @@ use std::collections::HashSet;
 use agent_provider::RunMessage;
 
 use crate::error::SessionStoreError;
-use crate::schema::SessionEntryKind;
+use crate::schema::{FailedAttempt, RunOutcomeKind, SessionEntryKind};
 use crate::store::SessionStore;
 
+/// A run outcome entry on a replayed branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayedRunOutcome {
+    /// Number of replayed messages recorded before the outcome, i.e. where
+    /// it falls in [`SessionStore::replay_leaf`]'s result.
+    pub message_index: usize,
+    pub outcome: RunOutcomeKind,
+    pub error: Option<String>,
+    pub status: Option<u16>,
+    pub failed_attempts: Vec<FailedAttempt>,
+}
+
+/// Workspace checkpoints recorded during one user turn on a replayed branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayedTurnCheckpoints {
+    /// The user text that started the turn.
+    pub prompt: String,
+    /// Checkpoint ids in the order the tool calls ran.
+    pub checkpoint_ids: Vec<String>,
+}
+
 impl SessionStore {
     pub fn replay_leaf(
         &self,
         target_leaf: Option<&str>,
     ) -> Result<Vec<RunMessage>, SessionStoreError> {
+        let chain_indices = self.leaf_chain_indices(target_leaf)?;
+
+        let mut messages = Vec::with_capacity(chain_indices.len());
+        for index in chain_indices {
+            if let Some(message) = entry_to_run_message(&self.entries[index]) {
+                messages.push(message);
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Returns the running session cost recorded by the newest cost summary on
+    /// the replayed branch, if any summary carried a priced total.
+    pub fn replay_session_cost_micros(
+        &self,
+        target_leaf: Option<&str>,
+    ) -> Result<Option<u64>, SessionStoreError> {
+        let chain_indices = self.leaf_chain_indices(target_leaf)?;
+
+        Ok(chain_indices
+            .into_iter()
+            .rev()
+            .find_map(|index| match &self.entries[index].kind {
+                SessionEntryKind::CostSummary {
+                    session_cost_micros,
+                    ..
+                } => *session_cost_micros,
+                _ => None,
+            }))
+    }
+
+    /// Returns the run outcome entries on the replayed branch, oldest first.
+    pub fn replay_run_outcomes(
+        &self,
+        target_leaf: Option<&str>,
+    ) -> Result<Vec<ReplayedRunOutcome>, SessionStoreError> {
+        let chain_indices = self.leaf_chain_indices(target_leaf)?;
+
+        let mut message_index = 0;
+        let mut outcomes = Vec::new();
+        for index in chain_indices {
+            let entry = &self.entries[index];
+            match &entry.kind {
+                SessionEntryKind::RunOutcome {
+                    outcome,
+                    error,
+                    status,
+                    failed_attempts,
+                } => outcomes.push(ReplayedRunOutcome {
+                    message_index,
+                    outcome: *outcome,
+                    error: error.clone(),
+                    status: *status,
+                    failed_attempts: failed_attempts.clone(),
+                }),
+                _ => {
+                    if entry_to_run_message(entry).is_some() {
+                        message_index += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Returns the checkpoint ids recorded on tool results of the replayed
+    /// branch, grouped by user turn, oldest first. Turns without checkpoints
+    /// are omitted.
+    pub fn replay_turn_checkpoints(
+        &self,
+        target_leaf: Option<&str>,
+    ) -> Result<Vec<ReplayedTurnCheckpoints>, SessionStoreError> {
+        let chain_indices = self.leaf_chain_indices(target_leaf)?;
+
+        let mut turns: Vec<ReplayedTurnCheckpoints> = Vec::new();
+        let mut current: Option<ReplayedTurnCheckpoints> = None;
+        for index in chain_indices {
+            match &self.entries[index].kind {
+                SessionEntryKind::UserText { text } => {
+                    turns.extend(
+                        current
+                            .take()
+                            .filter(|turn| !turn.checkpoint_ids.is_empty()),
+                    );
+                    current = Some(ReplayedTurnCheckpoints {
+                        prompt: text.clone(),
+                        checkpoint_ids: Vec::new(),
+                    });
+                }
+                SessionEntryKind::ToolResult {
+                    checkpoint_id: Some(checkpoint_id),
+                    ..
+                } => {
+                    if let Some(turn) = current.as_mut() {
+                        turn.checkpoint_ids.push(checkpoint_id.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        turns.extend(current.filter(|turn| !turn.checkpoint_ids.is_empty()));
+
+        Ok(turns)
+    }
+
+    /// Returns entry indices from the root to `target_leaf` (or the current leaf).
+    fn leaf_chain_indices(
+        &self,
+        target_leaf: Option<&str>,
+    ) -> Result<Vec<usize>, SessionStoreError> {
         let start_leaf_id = match target_leaf {
             Some(target) => target.to_string(),
             None => match &self.current_leaf_id {
@@ -51,17 +184,12 @@ impl SessionStore {
 
         chain_indices.reverse();
 
-        let mut messages = Vec::with_capacity(chain_indices.len());
-        for index in chain_indices {
-            messages.push(entry_to_run_message(&self.entries[index]));
-        }
-
-        Ok(messages)
+        Ok(chain_indices)
     }
 }
 
-fn entry_to_run_message(entry: &crate::schema::SessionEntry) -> RunMessage {
-    match &entry.kind {
+fn entry_to_run_message(entry: &crate::schema::SessionEntry) -> Option<RunMessage> {
+    let message = match &entry.kind {
         SessionEntryKind::UserText { text } => RunMessage::UserText { text: text.clone() },
         SessionEntryKind::AssistantText { text } => {
             RunMessage::AssistantText { text: text.clone() }
@@ -80,11 +208,15 @@ fn entry_to_run_message(entry: &crate::schema::SessionEntry) -> RunMessage {
             tool_name,
             content,
             is_error,
+            ..
         } => RunMessage::ToolResult {
             call_id: call_id.clone(),
             tool_name: tool_name.clone(),
             content: content.clone(),
             is_error: *is_error,
         },
-    }
+        SessionEntryKind::CostSummary { .. } | SessionEntryKind::RunOutcome { .. } => return None,
+    };
+
+    Some(message)
 }
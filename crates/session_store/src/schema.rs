@@ -22,6 +22,10 @@ pub struct SessionHeader {
     pub session_id: String,
     pub created_at: String,
     pub cwd: String,
+    /// Short human-readable name, set after the first assistant turn or by
+    /// the user. The one header field rewritten after creation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 impl SessionHeader {
@@ -37,6 +41,7 @@ impl SessionHeader {
             session_id: session_id.into(),
             created_at: created_at.into(),
             cwd: cwd.into(),
+            title: None,
         }
     }
 }
@@ -70,6 +75,27 @@ impl SessionEntry {
     }
 }
 
+/// How a recorded run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcomeKind {
+    Finished,
+    Failed,
+    Cancelled,
+}
+
+/// One failed provider request attempt within a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FailedAttempt {
+    /// 1-based attempt number within its request.
+    pub attempt: u32,
+    /// HTTP status, when the attempt got a response.
+    pub status: Option<u16>,
+    pub error: String,
+    pub will_retry: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum SessionEntryKind {
@@ -89,6 +115,33 @@ pub enum SessionEntryKind {
         tool_name: String,
         content: Value,
         is_error: bool,
+        /// Workspace checkpoint holding the files the call mutated, as they
+        /// were before it ran.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        checkpoint_id: Option<String>,
+    },
+    /// Token usage and spend for one finished run; not part of model replay.
+    ///
+    /// Costs are integer micro-USD. They are `None` when no pricing was
+    /// configured for the run's model.
+    CostSummary {
+        model_id: String,
+        input_tokens: u64,
+        cached_input_tokens: u64,
+        output_tokens: u64,
+        run_cost_micros: Option<u64>,
+        session_cost_micros: Option<u64>,
+    },
+    /// A run that failed, was cancelled, or needed retries; not part of
+    /// model replay.
+    ///
+    /// `status` is the HTTP status of the final failed attempt of a failed
+    /// run.
+    RunOutcome {
+        outcome: RunOutcomeKind,
+        error: Option<String>,
+        status: Option<u16>,
+        failed_attempts: Vec<FailedAttempt>,
     },
 }
 
@@ -110,6 +163,8 @@ impl<'de> Deserialize<'de> for JsonLine {
             RawJsonLine::AssistantTextEntry(raw_entry) => JsonLine::Entry(raw_entry.into()),
             RawJsonLine::ToolCallEntry(raw_entry) => JsonLine::Entry(raw_entry.into()),
             RawJsonLine::ToolResultEntry(raw_entry) => JsonLine::Entry(raw_entry.into()),
+            RawJsonLine::CostSummaryEntry(raw_entry) => JsonLine::Entry(raw_entry.into()),
+            RawJsonLine::RunOutcomeEntry(raw_entry) => JsonLine::Entry(raw_entry.into()),
         })
     }
 }
@@ -122,6 +177,8 @@ enum RawJsonLine {
     AssistantTextEntry(RawAssistantTextEntry),
     ToolCallEntry(RawToolCallEntry),
     ToolResultEntry(RawToolResultEntry),
+    CostSummaryEntry(RawCostSummaryEntry),
+    RunOutcomeEntry(RawRunOutcomeEntry),
 }
 
 #[derive(Debug, Deserialize)]
@@ -133,6 +190,8 @@ struct RawSessionHeader {
     session_id: String,
     created_at: String,
     cwd: String,
+    #[serde(default)]
+    title: Option<String>,
 }
 
 impl From<RawSessionHeader> for SessionHeader {
@@ -143,6 +202,7 @@ impl From<RawSessionHeader> for SessionHeader {
             session_id: raw.session_id,
             created_at: raw.created_at,
             cwd: raw.cwd,
+            title: raw.title,
         }
     }
 }
@@ -285,6 +345,8 @@ struct RawToolResultEntry {
     tool_name: String,
     content: Value,
     is_error: bool,
+    #[serde(default)]
+    checkpoint_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -305,6 +367,7 @@ impl From<RawToolResultEntry> for SessionEntry {
             tool_name,
             content,
             is_error,
+            checkpoint_id,
         } = raw;
 
         Self {
@@ -317,6 +380,113 @@ impl From<RawToolResultEntry> for SessionEntry {
                 tool_name,
                 content,
                 is_error,
+                checkpoint_id,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCostSummaryEntry {
+    #[serde(rename = "type")]
+    record_type: EntryRecordType,
+    id: String,
+    parent_id: Option<String>,
+    ts: String,
+    kind: RawCostSummaryKind,
+    model_id: String,
+    input_tokens: u64,
+    cached_input_tokens: u64,
+    output_tokens: u64,
+    run_cost_micros: Option<u64>,
+    session_cost_micros: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+enum RawCostSummaryKind {
+    #[serde(rename = "cost_summary")]
+    CostSummary,
+}
+
+impl From<RawCostSummaryEntry> for SessionEntry {
+    fn from(raw: RawCostSummaryEntry) -> Self {
+        let RawCostSummaryEntry {
+            record_type,
+            id,
+            parent_id,
+            ts,
+            kind: _kind,
+            model_id,
+            input_tokens,
+            cached_input_tokens,
+            output_tokens,
+            run_cost_micros,
+            session_cost_micros,
+        } = raw;
+
+        Self {
+            record_type,
+            id,
+            parent_id,
+            ts,
+            kind: SessionEntryKind::CostSummary {
+                model_id,
+                input_tokens,
+                cached_input_tokens,
+                output_tokens,
+                run_cost_micros,
+                session_cost_micros,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRunOutcomeEntry {
+    #[serde(rename = "type")]
+    record_type: EntryRecordType,
+    id: String,
+    parent_id: Option<String>,
+    ts: String,
+    kind: RawRunOutcomeKind,
+    outcome: RunOutcomeKind,
+    error: Option<String>,
+    status: Option<u16>,
+    failed_attempts: Vec<FailedAttempt>,
+}
+
+#[derive(Debug, Deserialize)]
+enum RawRunOutcomeKind {
+    #[serde(rename = "run_outcome")]
+    RunOutcome,
+}
+
+impl From<RawRunOutcomeEntry> for SessionEntry {
+    fn from(raw: RawRunOutcomeEntry) -> Self {
+        let RawRunOutcomeEntry {
+            record_type,
+            id,
+            parent_id,
+            ts,
+            kind: _kind,
+            outcome,
+            error,
+            status,
+            failed_attempts,
+        } = raw;
+
+        Self {
+            record_type,
+            id,
+            parent_id,
+            ts,
+            kind: SessionEntryKind::RunOutcome {
+                outcome,
+                error,
+                status,
+                failed_attempts,
             },
         }
     }
@@ -28,6 +28,31 @@ impl SessionSeed {
     }
 }
 
+/// A session file as listed by [`SessionStore::list_sessions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub path: PathBuf,
+    pub session_id: String,
+    pub created_at: String,
+    pub title: Option<String>,
+}
+
+/// Session files found by [`SessionStore::list_sessions`].
+#[derive(Debug, Default)]
+pub struct SessionList {
+    /// Newest first.
+    pub sessions: Vec<SessionSummary>,
+    /// Files whose header could not be read, in path order.
+    pub unreadable: Vec<UnreadableSession>,
+}
+
+/// A session file skipped by [`SessionStore::list_sessions`].
+#[derive(Debug)]
+pub struct UnreadableSession {
+    pub path: PathBuf,
+    pub error: SessionStoreError,
+}
+
 pub struct SessionStore {
     pub(crate) path: PathBuf,
     pub(crate) file: File,
@@ -226,6 +251,105 @@ impl SessionStore {
             .ok_or(SessionStoreError::NoSessionsFound { root })
     }
 
+    /// Headers of the session files under `<cwd>/.agent/sessions/`. Only the
+    /// header line of each file is read and validated; a file that fails is
+    /// reported in [`SessionList::unreadable`] rather than failing the listing.
+    pub fn list_sessions(cwd: &Path) -> Result<SessionList, SessionStoreError> {
+        let cwd = resolve_absolute_cwd(cwd)?;
+        let root = session_root(&cwd);
+        let entries = match fs::read_dir(&root) {
+            Ok(entries) => entries,
+            Err(source) if source.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(SessionList::default());
+            }
+            Err(source) => {
+                return Err(SessionStoreError::io(
+                    "listing session root directory",
+                    &root,
+                    source,
+                ));
+            }
+        };
+
+        let mut list = SessionList::default();
+        for entry in entries {
+            let entry = entry.map_err(|source| {
+                SessionStoreError::io("reading session root entry", &root, source)
+            })?;
+            let path = entry.path();
+            let file_type = entry.file_type().map_err(|source| {
+                SessionStoreError::io("reading session file type", &path, source)
+            })?;
+            if !file_type.is_file()
+                || path.extension().and_then(|ext| ext.to_str()) != Some("jsonl")
+            {
+                continue;
+            }
+
+            match read_header(&path) {
+                Ok(header) => list.sessions.push(SessionSummary {
+                    path,
+                    session_id: header.session_id,
+                    created_at: header.created_at,
+                    title: header.title,
+                }),
+                Err(error) => list.unreadable.push(UnreadableSession { path, error }),
+            }
+        }
+
+        list.sessions.sort_by(|left, right| {
+            right
+                .created_at
+                .cmp(&left.created_at)
+                .then_with(|| right.path.cmp(&left.path))
+        });
+        list.unreadable
+            .sort_by(|left, right| left.path.cmp(&right.path));
+        Ok(list)
+    }
+
+    /// Replaces the header's title (`None` clears it).
+    ///
+    /// The header is the first line, so this rewrites the file: the new
+    /// contents are synced to a sibling temporary file that then replaces
+    /// the session file, and later appends go to the replacement.
+    pub fn set_title(&mut self, title: Option<String>) -> Result<(), SessionStoreError> {
+        let mut header = self.header.clone();
+        header.title = title;
+        validate_header_line(&self.path, 1, &header)?;
+
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|source| SessionStoreError::io("reading session file", &self.path, source))?;
+        let entries = contents.split_once('\n').map_or("", |(_, entries)| entries);
+        let header_json = serde_json::to_string(&header)
+            .map_err(|source| SessionStoreError::json_serialize(&self.path, source))?;
+
+        let temp_path = self.path.with_extension("jsonl.tmp");
+        let mut temp_file = File::create(&temp_path).map_err(|source| {
+            SessionStoreError::io("creating session rewrite file", &temp_path, source)
+        })?;
+        temp_file
+            .write_all(format!("{header_json}\n{entries}").as_bytes())
+            .map_err(|source| {
+                SessionStoreError::io("writing session rewrite file", &temp_path, source)
+            })?;
+        temp_file.sync_data().map_err(|source| {
+            SessionStoreError::io("syncing session rewrite file", &temp_path, source)
+        })?;
+        fs::rename(&temp_path, &self.path).map_err(|source| {
+            SessionStoreError::io("replacing session file", &self.path, source)
+        })?;
+
+        self.file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|source| {
+                SessionStoreError::io("opening session file for append", &self.path, source)
+            })?;
+        self.header = header;
+        Ok(())
+    }
+
     pub fn append(&mut self, entry: SessionEntry) -> Result<(), SessionStoreError> {
         let line_number = self.entries.len() + 2;
         validate_entry_line(&self.path, line_number, &entry)?;
@@ -286,6 +410,11 @@ impl SessionStore {
         &self.header.session_id
     }
 
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.header.title.as_deref()
+    }
+
     #[must_use]
     pub fn entry_count(&self) -> usize {
         self.entries.len()
@@ -297,6 +426,30 @@ impl SessionStore {
     }
 }
 
+/// The validated header line of the session file at `path`.
+fn read_header(path: &Path) -> Result<SessionHeader, SessionStoreError> {
+    let file = File::open(path)
+        .map_err(|source| SessionStoreError::io("opening session file", path, source))?;
+    let line = match BufReader::new(file).lines().next() {
+        Some(line) => line.map_err(|source| SessionStoreError::io_line(path, 1, source))?,
+        None => {
+            return Err(SessionStoreError::MissingHeader {
+                path: path.to_path_buf(),
+            })
+        }
+    };
+    match parse_json_line(path, 1, &line)? {
+        JsonLine::Session(header) => {
+            validate_header_line(path, 1, &header)?;
+            Ok(header)
+        }
+        JsonLine::Entry(_) => Err(SessionStoreError::InvalidHeaderRecord {
+            path: path.to_path_buf(),
+            line: 1,
+        }),
+    }
+}
+
 pub(crate) fn parse_json_line(
     path: &Path,
     line_number: usize,
@@ -5,8 +5,8 @@ use std::path::{Path, PathBuf};
 use agent_provider::RunMessage;
 use serde_json::json;
 use session_store::{
-    session_root, SessionEntry, SessionEntryKind, SessionHeader, SessionSeed, SessionStore,
-    SessionStoreError,
+    session_root, FailedAttempt, ReplayedRunOutcome, ReplayedTurnCheckpoints, RunOutcomeKind,
+    SessionEntry, SessionEntryKind, SessionHeader, SessionSeed, SessionStore, SessionStoreError,
 };
 use tempfile::TempDir;
 use time::format_description::well_known::Rfc3339;
@@ -483,6 +483,7 @@ fn replay_leaf_reconstructs_run_message_sequence() {
                 tool_name: "bash".to_string(),
                 content: json!({"stdout": "hi"}),
                 is_error: false,
+                checkpoint_id: None,
             },
         ))
         .expect("tool result append should succeed");
@@ -515,6 +516,246 @@ fn replay_leaf_reconstructs_run_message_sequence() {
     );
 }
 
+#[test]
+fn cost_summary_entries_round_trip_and_are_skipped_by_replay() {
+    let cwd_dir = tempfile::tempdir().expect("tempdir should be created");
+    let mut store = SessionStore::create_new(cwd_dir.path()).expect("create_new should succeed");
+
+    store
+        .append(SessionEntry::new(
+            "entry-1",
+            None::<String>,
+            "2026-02-14T00:00:01Z",
+            SessionEntryKind::UserText {
+                text: "hello".to_string(),
+            },
+        ))
+        .expect("user append should succeed");
+    store
+        .append(SessionEntry::new(
+            "entry-2",
+            Some("entry-1"),
+            "2026-02-14T00:00:02Z",
+            SessionEntryKind::CostSummary {
+                model_id: "gpt-test".to_string(),
+                input_tokens: 1_200,
+                cached_input_tokens: 200,
+                output_tokens: 50,
+                run_cost_micros: Some(1_750),
+                session_cost_micros: Some(4_000),
+            },
+        ))
+        .expect("cost summary append should succeed");
+    store
+        .append(SessionEntry::new(
+            "entry-3",
+            Some("entry-2"),
+            "2026-02-14T00:00:03Z",
+            SessionEntryKind::CostSummary {
+                model_id: "unpriced".to_string(),
+                input_tokens: 10,
+                cached_input_tokens: 0,
+                output_tokens: 5,
+                run_cost_micros: None,
+                session_cost_micros: None,
+            },
+        ))
+        .expect("unpriced cost summary append should succeed");
+
+    let reopened = SessionStore::open(store.path()).expect("reopen should parse cost summaries");
+    assert_eq!(reopened.entry_count(), 3);
+    assert_eq!(
+        reopened
+            .replay_leaf(None)
+            .expect("replay should skip cost summaries"),
+        vec![RunMessage::UserText {
+            text: "hello".to_string(),
+        }]
+    );
+    assert_eq!(
+        reopened
+            .replay_session_cost_micros(None)
+            .expect("cost replay should succeed"),
+        Some(4_000)
+    );
+    assert_eq!(
+        reopened
+            .replay_session_cost_micros(Some("entry-1"))
+            .expect("cost replay from explicit leaf should succeed"),
+        None
+    );
+}
+
+#[test]
+fn run_outcome_entries_round_trip_and_replay_at_their_message_positions() {
+    let cwd_dir = tempfile::tempdir().expect("tempdir should be created");
+    let mut store = SessionStore::create_new(cwd_dir.path()).expect("create_new should succeed");
+    let failed_attempts = vec![
+        FailedAttempt {
+            attempt: 1,
+            status: Some(503),
+            error: "overloaded".to_string(),
+            will_retry: true,
+        },
+        FailedAttempt {
+            attempt: 2,
+            status: Some(400),
+            error: "bad request".to_string(),
+            will_retry: false,
+        },
+    ];
+
+    store
+        .append(SessionEntry::new(
+            "entry-1",
+            None::<String>,
+            "2026-02-14T00:00:01Z",
+            SessionEntryKind::UserText {
+                text: "hello".to_string(),
+            },
+        ))
+        .expect("user append should succeed");
+    store
+        .append(SessionEntry::new(
+            "entry-2",
+            Some("entry-1"),
+            "2026-02-14T00:00:02Z",
+            SessionEntryKind::RunOutcome {
+                outcome: RunOutcomeKind::Failed,
+                error: Some("bad request".to_string()),
+                status: Some(400),
+                failed_attempts: failed_attempts.clone(),
+            },
+        ))
+        .expect("run outcome append should succeed");
+    store
+        .append(SessionEntry::new(
+            "entry-3",
+            Some("entry-2"),
+            "2026-02-14T00:00:03Z",
+            SessionEntryKind::UserText {
+                text: "again".to_string(),
+            },
+        ))
+        .expect("second user append should succeed");
+    store
+        .append(SessionEntry::new(
+            "entry-4",
+            Some("entry-3"),
+            "2026-02-14T00:00:04Z",
+            SessionEntryKind::RunOutcome {
+                outcome: RunOutcomeKind::Cancelled,
+                error: None,
+                status: None,
+                failed_attempts: Vec::new(),
+            },
+        ))
+        .expect("cancelled outcome append should succeed");
+
+    let raw = std::fs::read_to_string(store.path()).expect("session file should be readable");
+    assert!(raw.contains(r#""kind":"run_outcome","outcome":"failed""#));
+
+    let reopened = SessionStore::open(store.path()).expect("reopen should parse run outcomes");
+    assert_eq!(
+        reopened
+            .replay_leaf(None)
+            .expect("replay should skip run outcomes")
+            .len(),
+        2
+    );
+    assert_eq!(
+        reopened
+            .replay_run_outcomes(None)
+            .expect("outcome replay should succeed"),
+        vec![
+            ReplayedRunOutcome {
+                message_index: 1,
+                outcome: RunOutcomeKind::Failed,
+                error: Some("bad request".to_string()),
+                status: Some(400),
+                failed_attempts,
+            },
+            ReplayedRunOutcome {
+                message_index: 2,
+                outcome: RunOutcomeKind::Cancelled,
+                error: None,
+                status: None,
+                failed_attempts: Vec::new(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn tool_result_checkpoint_ids_round_trip_and_replay_grouped_by_turn() {
+    let cwd_dir = tempfile::tempdir().expect("tempdir should be created");
+    let mut store = SessionStore::create_new(cwd_dir.path()).expect("create_new should succeed");
+    let tool_result = |call_id: &str, checkpoint_id: Option<&str>| SessionEntryKind::ToolResult {
+        call_id: call_id.to_string(),
+        tool_name: "write".to_string(),
+        content: json!("ok"),
+        is_error: false,
+        checkpoint_id: checkpoint_id.map(str::to_string),
+    };
+    let kinds = vec![
+        SessionEntryKind::UserText {
+            text: "first".to_string(),
+        },
+        tool_result("call-1", Some("0000000000001-0")),
+        tool_result("call-2", None),
+        tool_result("call-3", Some("0000000000002-0")),
+        SessionEntryKind::UserText {
+            text: "read only".to_string(),
+        },
+        tool_result("call-4", None),
+        SessionEntryKind::UserText {
+            text: "second".to_string(),
+        },
+        tool_result("call-5", Some("0000000000003-0")),
+    ];
+    for (index, kind) in kinds.into_iter().enumerate() {
+        let parent_id = (index > 0).then(|| format!("entry-{index}"));
+        store
+            .append(SessionEntry::new(
+                format!("entry-{}", index + 1),
+                parent_id,
+                "2026-02-14T00:00:01Z",
+                kind,
+            ))
+            .expect("append should succeed");
+    }
+
+    let raw = std::fs::read_to_string(store.path()).expect("session file should be readable");
+    assert!(raw.contains(r#""is_error":false,"checkpoint_id":"0000000000001-0""#));
+    assert!(
+        raw.contains(r#""call_id":"call-2","tool_name":"write","content":"ok","is_error":false}"#)
+    );
+
+    let reopened = SessionStore::open(store.path()).expect("reopen should parse checkpoint ids");
+    assert_eq!(
+        reopened
+            .replay_turn_checkpoints(None)
+            .expect("checkpoint replay should succeed"),
+        vec![
+            ReplayedTurnCheckpoints {
+                prompt: "first".to_string(),
+                checkpoint_ids: vec!["0000000000001-0".to_string(), "0000000000002-0".to_string()],
+            },
+            ReplayedTurnCheckpoints {
+                prompt: "second".to_string(),
+                checkpoint_ids: vec!["0000000000003-0".to_string()],
+            },
+        ]
+    );
+    assert_eq!(
+        reopened
+            .replay_turn_checkpoints(Some("entry-4"))
+            .expect("checkpoint replay from explicit leaf should succeed")
+            .len(),
+        1
+    );
+}
+
 #[test]
 fn replay_leaf_respects_explicit_target_leaf() {
     let cwd_dir = tempfile::tempdir().expect("tempdir should be created");
@@ -614,3 +855,82 @@ fn latest_session_path_errors_when_no_session_files_exist() {
         .expect_err("missing session root should return explicit no-sessions error");
     assert!(matches!(error, SessionStoreError::NoSessionsFound { .. }));
 }
+
+#[test]
+fn set_title_rewrites_the_header_and_keeps_appending_to_the_same_file() {
+    let cwd_dir = tempfile::tempdir().expect("tempdir should be created");
+    let mut store = SessionStore::create_new(cwd_dir.path()).expect("create_new should succeed");
+    store
+        .append(SessionEntry::new(
+            "entry-1",
+            None::<String>,
+            "2026-02-14T00:00:01Z",
+            SessionEntryKind::UserText {
+                text: "fix the build".to_string(),
+            },
+        ))
+        .expect("append should succeed");
+
+    store
+        .set_title(Some("Fix the build".to_string()))
+        .expect("set_title should succeed");
+    store
+        .append(SessionEntry::new(
+            "entry-2",
+            Some("entry-1"),
+            "2026-02-14T00:00:02Z",
+            SessionEntryKind::AssistantText {
+                text: "done".to_string(),
+            },
+        ))
+        .expect("append after set_title should succeed");
+
+    let reopened = SessionStore::open(store.path()).expect("reopen should succeed");
+    assert_eq!(reopened.title(), Some("Fix the build"));
+    assert_eq!(reopened.entry_count(), 2);
+    assert_eq!(reopened.current_leaf_id(), Some("entry-2"));
+    assert!(!store.path().with_extension("jsonl.tmp").exists());
+
+    store
+        .set_title(None)
+        .expect("clearing the title should succeed");
+    let file = std::fs::read_to_string(store.path()).expect("session file should be readable");
+    assert!(!file.lines().next().expect("header").contains("title"));
+}
+
+#[test]
+fn list_sessions_reads_headers_newest_first() {
+    let cwd = tempfile::tempdir().expect("tempdir should be created");
+    let empty = SessionStore::list_sessions(cwd.path()).expect("missing root lists nothing");
+    assert!(empty.sessions.is_empty() && empty.unreadable.is_empty());
+
+    let seed = |session_id: &str, created_at: &str| SessionSeed {
+        cwd: cwd.path().to_path_buf(),
+        session_id: session_id.to_string(),
+        created_at: created_at.to_string(),
+    };
+    let mut older = SessionStore::create_new_with_seed(&seed("older", "2026-02-14T00:00:00Z"))
+        .expect("older session should be created");
+    older
+        .set_title(Some("Older work".to_string()))
+        .expect("set_title should succeed");
+    let newer = SessionStore::create_new_with_seed(&seed("newer", "2026-02-15T00:00:00Z"))
+        .expect("newer session should be created");
+    let broken = newer.path().with_file_name("broken.jsonl");
+    std::fs::write(&broken, "not a header\n").expect("broken session should be written");
+
+    let list = SessionStore::list_sessions(cwd.path()).expect("sessions should list");
+    let listed: Vec<(&str, Option<&str>)> = list
+        .sessions
+        .iter()
+        .map(|session| (session.session_id.as_str(), session.title.as_deref()))
+        .collect();
+    assert_eq!(listed, vec![("newer", None), ("older", Some("Older work"))]);
+    assert_eq!(list.sessions[0].path, newer.path());
+    assert_eq!(list.unreadable.len(), 1);
+    assert_eq!(list.unreadable[0].path, broken);
+    assert!(matches!(
+        list.unreadable[0].error,
+        SessionStoreError::JsonLineParse { line: 1, .. }
+    ));
+}
@@ -0,0 +1,216 @@
+//! Derive macros for `tape_tui` components.
+//!
+//! `#[derive(Composite)]` writes the `Component` fan-out that host components
+//! otherwise repeat by hand: children are rendered top to bottom in field
+//! order and lifecycle calls reach every child.
+//!
+//! Field attributes:
+//! - `#[child]` marks a field as a child. Its type must implement
+//!   `tape_tui::Component`, directly or through `Box<dyn Component>`.
+//! - `#[child(input)]` also routes input events to the child, in field
+//!   order. Focus is forwarded to the first input child that is focusable.
+//! - `#[child_cursor]` marks an `Option<tape_tui::core::cursor::CursorPos>`
+//!   field that stores the cursor of the last child that reported one. The
+//!   field is what `cursor_pos` returns. Without it the composite reports no
+//!   cursor.
+//!
+//! Fields without an attribute are left alone, so a composite can keep its
+//! own state next to its children.
+//!
+//! Enable this through the `derive` feature of `tape_tui`, which re-exports
+//! the macro as `tape_tui::Composite`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Index, Member};
+
+struct Child {
+    member: Member,
+    input: bool,
+}
+
+#[proc_macro_derive(Composite, attributes(child, child_cursor))]
+pub fn derive_composite(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "Composite can only be derived for structs",
+        ));
+    };
+
+    let mut children = Vec::new();
+    let mut cursor_field: Option<Member> = None;
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+    for (index, field) in fields.into_iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(index)),
+        };
+        for attr in &field.attrs {
+            if attr.path().is_ident("child") {
+                let mut input = false;
+                if !matches!(attr.meta, syn::Meta::Path(_)) {
+                    attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("input") {
+                            input = true;
+                            Ok(())
+                        } else {
+                            Err(meta.error("expected `#[child]` or `#[child(input)]`"))
+                        }
+                    })?;
+                }
+                children.push(Child {
+                    member: member.clone(),
+                    input,
+                });
+            } else if attr.path().is_ident("child_cursor") {
+                if cursor_field.is_some() {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "only one field can be marked `#[child_cursor]`",
+                    ));
+                }
+                cursor_field = Some(member.clone());
+            }
+        }
+    }
+
+    if children.is_empty() {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "Composite needs at least one `#[child]` field",
+        ));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let all: Vec<&Member> = children.iter().map(|child| &child.member).collect();
+    let inputs: Vec<&Member> = children
+        .iter()
+        .filter(|child| child.input)
+        .map(|child| &child.member)
+        .collect();
+
+    let store_cursor = match &cursor_field {
+        Some(field) => quote! { self.#field = cursor; },
+        None => quote! { let _ = cursor; },
+    };
+    let cursor_pos = cursor_field.as_ref().map(|field| {
+        quote! {
+            fn cursor_pos(&self) -> ::std::option::Option<::tape_tui::core::cursor::CursorPos> {
+                self.#field
+            }
+        }
+    });
+    let as_focusable = (!inputs.is_empty()).then(|| {
+        quote! {
+            fn as_focusable(
+                &mut self,
+            ) -> ::std::option::Option<&mut dyn ::tape_tui::Focusable> {
+                #(
+                    if self.#inputs.as_focusable().is_some() {
+                        return self.#inputs.as_focusable();
+                    }
+                )*
+                ::std::option::Option::None
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::tape_tui::Component for #name #ty_generics #where_clause {
+            fn render(&mut self, width: usize) -> ::std::vec::Vec<::std::string::String> {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                let mut lines = ::std::vec::Vec::new();
+                let mut cursor = ::std::option::Option::None;
+                #(
+                    let start_row = lines.len();
+                    lines.extend(self.#all.render(width));
+                    if let ::std::option::Option::Some(pos) = self.#all.cursor_pos() {
+                        cursor = ::std::option::Option::Some(::tape_tui::core::cursor::CursorPos {
+                            row: start_row.saturating_add(pos.row),
+                            col: pos.col,
+                        });
+                    }
+                )*
+                #store_cursor
+                lines
+            }
+
+            #cursor_pos
+
+            fn handle_event(&mut self, event: &::tape_tui::InputEvent) {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                let event_type = event.key_event_type();
+                #(
+                    if event_type.is_none_or(|event_type| {
+                        self.#inputs.wants_key_events().contains(event_type)
+                    }) {
+                        self.#inputs.handle_event(event);
+                    }
+                )*
+                let _ = event_type;
+            }
+
+            fn invalidate(&mut self) {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                #( self.#all.invalidate(); )*
+            }
+
+            fn set_terminal_rows(&mut self, rows: usize) {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                #( self.#all.set_terminal_rows(rows); )*
+            }
+
+            fn on_mount(&mut self) {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                #( self.#all.on_mount(); )*
+            }
+
+            fn on_unmount(&mut self) {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                #( self.#all.on_unmount(); )*
+            }
+
+            fn on_resize(&mut self, columns: u16, rows: u16) {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                #( self.#all.on_resize(columns, rows); )*
+            }
+
+            fn wants_key_release(&self) -> bool {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                false #( || self.#inputs.wants_key_release() )*
+            }
+
+            fn wants_key_events(&self) -> ::tape_tui::KeyEventTypes {
+                #[allow(unused_imports)]
+                use ::tape_tui::Component as _;
+                ::tape_tui::KeyEventTypes::default()
+                    #( .union(self.#inputs.wants_key_events()) )*
+            }
+
+            #as_focusable
+        }
+    })
+}
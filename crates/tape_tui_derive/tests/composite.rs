@@ -0,0 +1,159 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tape_tui::core::cursor::CursorPos;
+use tape_tui::{
+    default_editor_keybindings_handle, Component, Composite, Input, InputEvent, KeyEventType,
+    KeyEventTypes, Text,
+};
+
+struct Probe {
+    name: &'static str,
+    calls: Rc<RefCell<Vec<String>>>,
+}
+
+struct ReleaseProbe {
+    calls: Rc<RefCell<Vec<String>>>,
+}
+
+impl Component for ReleaseProbe {
+    fn render(&mut self, _width: usize) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        self.calls
+            .borrow_mut()
+            .push(format!("hold {:?}", event.key_event_type()));
+    }
+
+    fn wants_key_events(&self) -> KeyEventTypes {
+        KeyEventTypes::PRESS | KeyEventTypes::RELEASE
+    }
+}
+
+#[derive(Composite)]
+struct HoldPanel {
+    #[child(input)]
+    typing: Probe,
+    #[child(input)]
+    hold: ReleaseProbe,
+}
+
+impl Component for Probe {
+    fn render(&mut self, _width: usize) -> Vec<String> {
+        vec![self.name.to_string()]
+    }
+
+    fn handle_event(&mut self, _event: &InputEvent) {
+        self.calls.borrow_mut().push(format!("{} event", self.name));
+    }
+
+    fn on_resize(&mut self, columns: u16, rows: u16) {
+        self.calls
+            .borrow_mut()
+            .push(format!("{} resize {columns}x{rows}", self.name));
+    }
+}
+
+#[derive(Composite)]
+struct Panel {
+    #[child]
+    header: Probe,
+    #[child(input)]
+    body: Box<dyn Component>,
+    #[allow(dead_code)]
+    label: &'static str,
+}
+
+#[derive(Composite)]
+struct Form {
+    #[child]
+    title: Text,
+    #[child(input)]
+    input: Input,
+    #[child_cursor]
+    cursor: Option<CursorPos>,
+}
+
+#[test]
+fn composite_renders_children_in_order_and_routes_input() {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let mut panel = Panel {
+        header: Probe {
+            name: "header",
+            calls: Rc::clone(&calls),
+        },
+        body: Box::new(Probe {
+            name: "body",
+            calls: Rc::clone(&calls),
+        }),
+        label: "panel",
+    };
+
+    assert_eq!(panel.render(10), vec!["header", "body"]);
+    assert_eq!(panel.cursor_pos(), None);
+
+    panel.handle_event(&InputEvent::Resize {
+        columns: 1,
+        rows: 1,
+    });
+    panel.on_resize(40, 12);
+    assert_eq!(
+        *calls.borrow(),
+        vec!["body event", "header resize 40x12", "body resize 40x12"]
+    );
+    assert!(panel.as_focusable().is_none());
+}
+
+#[test]
+fn composite_offsets_child_cursor_and_forwards_focus() {
+    let mut form = Form {
+        title: Text::new("Name"),
+        input: Input::new(default_editor_keybindings_handle()),
+        cursor: None,
+    };
+    form.as_focusable()
+        .expect("input child is focusable")
+        .set_focused(true);
+    for key in ["a", "b"] {
+        for event in tape_tui::core::input_event::parse_input_events(key, false) {
+            form.handle_event(&event);
+        }
+    }
+
+    let lines = form.render(20);
+    let title_rows = lines.len() - 1;
+    assert_eq!(form.input.get_value(), "ab");
+    assert_eq!(
+        form.cursor_pos(),
+        Some(CursorPos {
+            row: title_rows,
+            col: 4,
+        })
+    );
+}
+
+#[test]
+fn composite_routes_key_event_types_to_children_that_want_them() {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let mut panel = HoldPanel {
+        typing: Probe {
+            name: "typing",
+            calls: Rc::clone(&calls),
+        },
+        hold: ReleaseProbe {
+            calls: Rc::clone(&calls),
+        },
+    };
+    assert_eq!(panel.wants_key_events(), KeyEventTypes::ALL);
+
+    for event_type in [KeyEventType::Repeat, KeyEventType::Release] {
+        panel.handle_event(&InputEvent::Text {
+            raw: " ".to_string(),
+            text: " ".to_string(),
+            event_type,
+        });
+    }
+    assert_eq!(*calls.borrow(), vec!["typing event", "hold Some(Release)"]);
+}
@@ -1567,6 +1567,7 @@ fn session_surface_options() -> SurfaceOptions {
             max_height: Some(SurfaceSizeValue::percent(OVERLAY_HEIGHT_PERCENT)),
             ..Default::default()
         },
+        min_update_interval: None,
     }
 }
 
@@ -471,6 +471,7 @@ fn palette_surface_options() -> SurfaceOptions {
             max_height: Some(SurfaceSizeValue::percent(60.0)),
             ..Default::default()
         },
+        min_update_interval: None,
     }
 }
 
@@ -0,0 +1,107 @@
+//! Shared time source for animations.
+//!
+//! Animations derive their current frame from elapsed time, not from how
+//! many times they were ticked or rendered. A runtime that renders late
+//! (busy with a large diff or a burst of input) skips frames instead of
+//! slowing the animation down, and spinners started at the same moment stay
+//! in step.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+static SYSTEM_ORIGIN: OnceLock<Instant> = OnceLock::new();
+
+/// A monotonic clock that animations sample.
+///
+/// [`AnimationClock::system`] (the default) reads the process-wide monotonic
+/// clock; all system clocks share one origin. [`AnimationClock::manual`]
+/// only moves through [`AnimationClock::advance`], which makes animated
+/// output deterministic in tests. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct AnimationClock {
+    manual_nanos: Option<Arc<AtomicU64>>,
+}
+
+impl AnimationClock {
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// A clock stopped at zero until advanced.
+    pub fn manual() -> Self {
+        Self {
+            manual_nanos: Some(Arc::new(AtomicU64::new(0))),
+        }
+    }
+
+    /// Moves a manual clock forward. No-op on the system clock.
+    pub fn advance(&self, by: Duration) {
+        if let Some(nanos) = self.manual_nanos.as_ref() {
+            let by = u64::try_from(by.as_nanos()).unwrap_or(u64::MAX);
+            let _ = nanos.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                Some(current.saturating_add(by))
+            });
+        }
+    }
+
+    /// Time since the clock's origin.
+    pub fn now(&self) -> Duration {
+        match self.manual_nanos.as_ref() {
+            Some(nanos) => Duration::from_nanos(nanos.load(Ordering::SeqCst)),
+            None => SYSTEM_ORIGIN.get_or_init(Instant::now).elapsed(),
+        }
+    }
+
+    /// Index of the frame showing now, for an animation of `frame_count`
+    /// frames started at `start` (a value of [`AnimationClock::now`]) that
+    /// advances every `interval`. Returns 0 without frames.
+    pub fn frame(&self, start: Duration, interval: Duration, frame_count: usize) -> usize {
+        if frame_count == 0 {
+            return 0;
+        }
+        let interval = interval.as_nanos().max(1);
+        let steps = self.now().saturating_sub(start).as_nanos() / interval;
+        (steps % frame_count as u128) as usize
+    }
+
+    /// Time left until the animation started at `start` shows its next frame.
+    pub fn until_next_frame(&self, start: Duration, interval: Duration) -> Duration {
+        let interval_nanos = interval.as_nanos().max(1);
+        let into_frame = self.now().saturating_sub(start).as_nanos() % interval_nanos;
+        Duration::from_nanos((interval_nanos - into_frame) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnimationClock;
+    use std::time::Duration;
+
+    #[test]
+    fn frames_follow_elapsed_time_not_samples() {
+        let clock = AnimationClock::manual();
+        let interval = Duration::from_millis(80);
+        let start = clock.now();
+        assert_eq!(clock.frame(start, interval, 4), 0);
+        assert_eq!(clock.until_next_frame(start, interval), interval);
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(clock.frame(start, interval, 4), 1);
+        assert_eq!(
+            clock.until_next_frame(start, interval),
+            Duration::from_millis(60)
+        );
+
+        // A late sample skips frames instead of stepping through them.
+        let shared = clock.clone();
+        shared.advance(Duration::from_millis(300));
+        assert_eq!(clock.frame(start, interval, 4), 1);
+        assert_eq!(clock.frame(start, interval, 0), 0);
+
+        let system = AnimationClock::system();
+        let before = system.now();
+        system.advance(Duration::from_secs(60));
+        assert!(system.now() < before + Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,278 @@
+//! Terminal capabilities persisted across runs.
+//!
+//! Probing the terminal (kitty keyboard query, cell size query) costs a round
+//! trip at every start. The cache stores what previous runs learned, keyed by
+//! the terminal identity (`$TERM`, `$TERM_PROGRAM`, and the reported
+//! version), so a later run in the same terminal can skip the probes it
+//! already has answers for.
+//!
+//! The file is plain text with one entry per line:
+//! `<key>\t<field>=<value>;...`. Unreadable lines are ignored.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core::terminal_image::{
+    CellDimensions, ImageProtocol, NotificationProtocol, TerminalCapabilities,
+};
+
+const CACHE_FILE_NAME: &str = "capabilities";
+
+/// What earlier runs learned about one terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachedCapabilities {
+    pub capabilities: TerminalCapabilities,
+    /// The cell size reported by the terminal; `None` until a query answered.
+    pub cell_dimensions: Option<CellDimensions>,
+    /// Whether the terminal answered the kitty keyboard query. `false` means
+    /// "not known", so the query is still sent.
+    pub kitty_keyboard: bool,
+}
+
+/// A capability cache file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityCache {
+    path: PathBuf,
+}
+
+impl CapabilityCache {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `$XDG_CONFIG_HOME/tape_tui/capabilities`, falling back to
+    /// `$HOME/.config/tape_tui/capabilities`.
+    pub fn in_config_dir() -> Option<Self> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME")
+                    .filter(|home| !home.is_empty())
+                    .map(|home| PathBuf::from(home).join(".config"))
+            })?;
+        Some(Self::new(config_dir.join("tape_tui").join(CACHE_FILE_NAME)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Identity of the current terminal: `$TERM`, `$TERM_PROGRAM`, and
+    /// `$TERM_PROGRAM_VERSION`. A terminal upgrade changes the key, so stale
+    /// entries are not reused.
+    pub fn terminal_key() -> String {
+        let var = |key: &str| env::var(key).unwrap_or_default();
+        sanitize_key(&format!(
+            "{}|{}|{}",
+            var("TERM"),
+            var("TERM_PROGRAM"),
+            var("TERM_PROGRAM_VERSION")
+        ))
+    }
+
+    pub fn load(&self, key: &str) -> Option<CachedCapabilities> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        contents.lines().find_map(|line| {
+            let (entry_key, fields) = line.split_once('\t')?;
+            (entry_key == key).then(|| parse_entry(fields)).flatten()
+        })
+    }
+
+    /// Writes `entry` for `key`, replacing any previous entry for it.
+    pub fn store(&self, key: &str, entry: &CachedCapabilities) -> io::Result<()> {
+        let key = sanitize_key(key);
+        let mut lines = self.other_entries(&key);
+        lines.push(format!("{key}\t{}", format_entry(entry)));
+        self.write_lines(&lines)
+    }
+
+    /// Drops the entry for `key` so the next run probes from scratch.
+    pub fn forget(&self, key: &str) -> io::Result<()> {
+        let lines = self.other_entries(&sanitize_key(key));
+        self.write_lines(&lines)
+    }
+
+    fn other_entries(&self, key: &str) -> Vec<String> {
+        let contents = fs::read_to_string(&self.path).unwrap_or_default();
+        contents
+            .lines()
+            .filter(|line| {
+                line.split_once('\t')
+                    .is_some_and(|(entry_key, _)| entry_key != key)
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn write_lines(&self, lines: &[String]) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut contents = lines.join("\n");
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        fs::write(&self.path, contents)
+    }
+}
+
+fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .map(|ch| if ch.is_control() { ' ' } else { ch })
+        .collect()
+}
+
+fn format_entry(entry: &CachedCapabilities) -> String {
+    let caps = entry.capabilities;
+    let images = match caps.images {
+        Some(ImageProtocol::Kitty) => "kitty",
+        Some(ImageProtocol::Iterm2) => "iterm2",
+        None => "none",
+    };
+    let notifications = match caps.notifications {
+        NotificationProtocol::Osc777 => "osc777",
+        NotificationProtocol::Osc9 => "osc9",
+        NotificationProtocol::Bell => "bell",
+    };
+    let mut fields = format!(
+        "images={images};true_color={};hyperlinks={};line_attributes={};notifications={notifications};kitty_keyboard={}",
+        u8::from(caps.true_color),
+        u8::from(caps.hyperlinks),
+        u8::from(caps.line_attributes),
+        u8::from(entry.kitty_keyboard),
+    );
+    if let Some(cell) = entry.cell_dimensions {
+        fields.push_str(&format!(";cell={}x{}", cell.width_px, cell.height_px));
+    }
+    fields
+}
+
+fn parse_entry(fields: &str) -> Option<CachedCapabilities> {
+    let mut images = None;
+    let mut true_color = None;
+    let mut hyperlinks = None;
+    let mut line_attributes = None;
+    let mut notifications = None;
+    let mut kitty_keyboard = false;
+    let mut cell_dimensions = None;
+
+    for field in fields.split(';') {
+        let (name, value) = field.split_once('=')?;
+        match name {
+            "images" => {
+                images = Some(match value {
+                    "kitty" => Some(ImageProtocol::Kitty),
+                    "iterm2" => Some(ImageProtocol::Iterm2),
+                    "none" => None,
+                    _ => return None,
+                })
+            }
+            "true_color" => true_color = Some(parse_flag(value)?),
+            "hyperlinks" => hyperlinks = Some(parse_flag(value)?),
+            "line_attributes" => line_attributes = Some(parse_flag(value)?),
+            "notifications" => {
+                notifications = Some(match value {
+                    "osc777" => NotificationProtocol::Osc777,
+                    "osc9" => NotificationProtocol::Osc9,
+                    "bell" => NotificationProtocol::Bell,
+                    _ => return None,
+                })
+            }
+            "kitty_keyboard" => kitty_keyboard = parse_flag(value)?,
+            "cell" => {
+                let (width, height) = value.split_once('x')?;
+                let cell = CellDimensions {
+                    width_px: width.parse().ok()?,
+                    height_px: height.parse().ok()?,
+                };
+                cell_dimensions = (cell.width_px > 0 && cell.height_px > 0).then_some(cell);
+            }
+            // Fields written by newer versions.
+            _ => {}
+        }
+    }
+
+    Some(CachedCapabilities {
+        capabilities: TerminalCapabilities {
+            images: images?,
+            true_color: true_color?,
+            hyperlinks: hyperlinks?,
+            line_attributes: line_attributes?,
+            notifications: notifications?,
+        },
+        cell_dimensions,
+        kitty_keyboard,
+    })
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CachedCapabilities, CapabilityCache};
+    use crate::core::terminal_image::{
+        CellDimensions, ImageProtocol, NotificationProtocol, TerminalCapabilities,
+    };
+
+    #[test]
+    fn entries_round_trip_per_key_and_can_be_forgotten() {
+        let dir = std::env::temp_dir().join(format!("tape-cap-cache-{}", std::process::id()));
+        let cache = CapabilityCache::new(dir.join("nested").join("capabilities"));
+        let kitty = CachedCapabilities {
+            capabilities: TerminalCapabilities {
+                images: Some(ImageProtocol::Kitty),
+                true_color: true,
+                hyperlinks: true,
+                line_attributes: false,
+                notifications: NotificationProtocol::Osc9,
+            },
+            cell_dimensions: Some(CellDimensions {
+                width_px: 10,
+                height_px: 20,
+            }),
+            kitty_keyboard: true,
+        };
+        let plain = CachedCapabilities {
+            capabilities: TerminalCapabilities {
+                images: None,
+                true_color: false,
+                hyperlinks: true,
+                line_attributes: true,
+                notifications: NotificationProtocol::Bell,
+            },
+            cell_dimensions: None,
+            kitty_keyboard: false,
+        };
+
+        assert_eq!(cache.load("kitty"), None);
+        cache.store("kitty", &kitty).expect("store kitty");
+        cache.store("xterm", &plain).expect("store xterm");
+        assert_eq!(cache.load("kitty"), Some(kitty));
+        assert_eq!(cache.load("xterm"), Some(plain));
+
+        let updated = CachedCapabilities {
+            kitty_keyboard: false,
+            ..kitty
+        };
+        cache.store("kitty", &updated).expect("replace kitty");
+        assert_eq!(cache.load("kitty"), Some(updated));
+
+        cache.forget("kitty").expect("forget kitty");
+        assert_eq!(cache.load("kitty"), None);
+        assert_eq!(cache.load("xterm"), Some(plain));
+
+        std::fs::write(cache.path(), "xterm\timages=sixel\ngarbage\n").expect("corrupt");
+        assert_eq!(cache.load("xterm"), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
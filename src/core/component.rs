@@ -1,7 +1,13 @@
 //! Component and Focusable traits.
 
+use std::any::Any;
+
+use crate::core::input::KeyEventTypes;
 use crate::core::input_event::InputEvent;
 
+/// Payload carried by the runtime message bus; receivers downcast it.
+pub type ComponentMessage = Box<dyn Any + Send>;
+
 /// Renderable component interface.
 pub trait Component {
     /// Render to a list of lines at the given width.
@@ -20,6 +26,14 @@ pub trait Component {
     /// Handle input events.
     fn handle_event(&mut self, _event: &InputEvent) {}
 
+    /// Receive a message sent to this component's id through
+    /// `RuntimeHandle::send` or `CustomCommandCtx::send`.
+    ///
+    /// Messages are delivered on the runtime thread during the command stage
+    /// of a tick, in send order, and a render is requested afterwards.
+    /// Unrecognized messages should simply be ignored.
+    fn on_message(&mut self, _message: ComponentMessage) {}
+
     /// Optional cursor position metadata for this component's last render.
     ///
     /// The cursor position is relative to the lines returned from `render()`.
@@ -30,6 +44,45 @@ pub trait Component {
     /// Invalidate any cached state.
     fn invalidate(&mut self) {}
 
+    /// Offered to the first root component after each render: rows
+    /// `0..scrolled_rows` of its last render have scrolled above the viewport
+    /// into the terminal's native scrollback and can no longer be redrawn.
+    ///
+    /// Return how many leading rows (at most `scrolled_rows`) to leave out of
+    /// every later render; the runtime forgets them as well, so the shorter
+    /// frame diffs as unchanged and long transcripts need not stay in memory.
+    /// A width change clears the terminal and redraws the whole frame, so
+    /// rows rendered after one are all shown again. Defaults to keeping
+    /// everything.
+    fn release_scrolled_rows(&mut self, _scrolled_rows: usize) -> usize {
+        0
+    }
+
+    /// Version of this component's rendered content, for render caching.
+    ///
+    /// When this returns `Some`, the runtime (and `Container`) reuse the last
+    /// render while the key and width are unchanged, without calling
+    /// `render`. Return a new key whenever the output would change, including
+    /// changes driven by `set_terminal_rows` or focus. `None` (the default)
+    /// renders every time.
+    fn cache_key(&self) -> Option<u64> {
+        None
+    }
+
+    /// Called when the component starts being shown: added to the root, the
+    /// second pane or the footer, or shown in a visible surface, while it was
+    /// in none of them. Allocate caches here.
+    fn on_mount(&mut self) {}
+
+    /// Called when the component stops being shown anywhere, including when
+    /// a shown component is removed from the runtime. Calls alternate with
+    /// `on_mount`. Release caches here.
+    fn on_unmount(&mut self) {}
+
+    /// Called on every registered component when the terminal is resized,
+    /// before the resize event is routed as input. Invalidate layouts here.
+    fn on_resize(&mut self, _columns: u16, _rows: u16) {}
+
     /// Provide the current terminal row count (optional).
     fn set_terminal_rows(&mut self, _rows: usize) {}
 
@@ -38,6 +91,18 @@ pub trait Component {
         false
     }
 
+    /// Key and text event types the runtime routes to this component. Events
+    /// of other types skip it and bubble to the next input target. Defaults
+    /// to press and repeat, plus release when [`Component::wants_key_release`]
+    /// is true. Components that opt in must check `event_type` themselves.
+    fn wants_key_events(&self) -> KeyEventTypes {
+        if self.wants_key_release() {
+            KeyEventTypes::ALL
+        } else {
+            KeyEventTypes::default()
+        }
+    }
+
     /// Optional focusable behavior for IME cursor handling.
     fn as_focusable(&mut self) -> Option<&mut dyn Focusable> {
         None
@@ -0,0 +1,91 @@
+//! Focus cycling between the regions of a composite component.
+//!
+//! The runtime focuses whole components. A component that hosts several
+//! interactive regions (for example an input line and a scrollback list) can
+//! use [`FocusRing`] to track which region receives keys and to cycle through
+//! them with Tab/Shift+Tab in a fixed order.
+
+/// Ordered, wrapping set of focus targets with exactly one current target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusRing<T> {
+    targets: Vec<T>,
+    current: usize,
+}
+
+impl<T: PartialEq> FocusRing<T> {
+    /// Creates a ring focused on the first target; `None` when `targets` is empty.
+    pub fn new(targets: Vec<T>) -> Option<Self> {
+        if targets.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            targets,
+            current: 0,
+        })
+    }
+
+    pub fn current(&self) -> &T {
+        &self.targets[self.current]
+    }
+
+    pub fn is_focused(&self, target: &T) -> bool {
+        self.current() == target
+    }
+
+    /// Moves focus to `target`; returns `false` when it is not in the ring.
+    pub fn focus(&mut self, target: &T) -> bool {
+        match self
+            .targets
+            .iter()
+            .position(|candidate| candidate == target)
+        {
+            Some(index) => {
+                self.current = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Advances to the next target, wrapping after the last one.
+    pub fn focus_next(&mut self) -> &T {
+        self.current = (self.current + 1) % self.targets.len();
+        self.current()
+    }
+
+    /// Steps back to the previous target, wrapping before the first one.
+    pub fn focus_previous(&mut self) -> &T {
+        self.current = self
+            .current
+            .checked_sub(1)
+            .unwrap_or(self.targets.len() - 1);
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FocusRing;
+
+    #[test]
+    fn ring_wraps_in_both_directions() {
+        let mut ring = FocusRing::new(vec!["input", "list", "status"]).expect("ring");
+        assert_eq!(*ring.current(), "input");
+
+        assert_eq!(*ring.focus_next(), "list");
+        assert_eq!(*ring.focus_next(), "status");
+        assert_eq!(*ring.focus_next(), "input");
+        assert_eq!(*ring.focus_previous(), "status");
+    }
+
+    #[test]
+    fn focus_rejects_unknown_targets() {
+        let mut ring = FocusRing::new(vec![1, 2]).expect("ring");
+        assert!(ring.focus(&2));
+        assert!(ring.is_focused(&2));
+        assert!(!ring.focus(&3));
+        assert!(ring.is_focused(&2));
+        assert!(FocusRing::<u8>::new(Vec::new()).is_none());
+    }
+}
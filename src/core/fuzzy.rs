@@ -1,89 +1,188 @@
 //! Fuzzy matching utilities.
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use std::ops::Range;
+
+/// Result of matching a query against a text.
+///
+/// Lower scores are better matches. `ranges` are the byte ranges of `text`
+/// the query characters matched, merged where adjacent and in order, ready
+/// for highlighting (see `Line::fuzzy_highlighted`).
+#[derive(Debug, Clone, PartialEq)]
 pub struct FuzzyMatch {
     pub matches: bool,
     pub score: f64,
+    pub ranges: Vec<Range<usize>>,
 }
 
-pub fn fuzzy_match(query: &str, text: &str) -> FuzzyMatch {
-    let query_lower = query.to_lowercase();
-    let text_lower = text.to_lowercase();
-
-    let match_query = |normalized_query: &str| -> FuzzyMatch {
-        if normalized_query.is_empty() {
-            return FuzzyMatch {
-                matches: true,
-                score: 0.0,
-            };
+impl FuzzyMatch {
+    fn none() -> Self {
+        Self {
+            matches: false,
+            score: 0.0,
+            ranges: Vec::new(),
         }
+    }
+}
 
-        if normalized_query.len() > text_lower.len() {
-            return FuzzyMatch {
-                matches: false,
-                score: 0.0,
-            };
-        }
+const SCORE_MATCH: i32 = 16;
+const GAP_START: i32 = -3;
+const GAP_EXTENSION: i32 = -1;
+/// First character of the text or of a word (after whitespace or `-_./:`).
+const BONUS_BOUNDARY: i32 = 8;
+/// camelCase hump or a letter/digit transition.
+const BONUS_CAMEL: i32 = 7;
+/// Floor for the bonus of a character continuing a consecutive run.
+const BONUS_CONSECUTIVE: i32 = 4;
+/// The query's first character counts its position bonus this many times.
+const FIRST_CHAR_MULTIPLIER: i32 = 2;
 
-        let mut query_index = 0usize;
-        let mut score = 0.0f64;
-        let mut last_match_index: isize = -1;
-        let mut consecutive_matches = 0i32;
+fn position_bonus(prev: Option<char>, ch: char) -> i32 {
+    let Some(prev) = prev else {
+        return BONUS_BOUNDARY;
+    };
+    if prev.is_whitespace() || matches!(prev, '-' | '_' | '.' | '/' | ':') {
+        BONUS_BOUNDARY
+    } else if (prev.is_lowercase() && ch.is_uppercase())
+        || (!prev.is_ascii_digit() && ch.is_ascii_digit())
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
 
-        let text_chars: Vec<char> = text_lower.chars().collect();
-        let query_chars: Vec<char> = normalized_query.chars().collect();
+fn fold(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
 
-        for (i, ch) in text_chars.iter().enumerate() {
-            if query_index >= query_chars.len() {
-                break;
+/// Whether every query character occurs in `text` in order; a linear scan
+/// that rejects most candidates before the alignment runs.
+fn is_subsequence(query: &[char], text: &str) -> bool {
+    let mut remaining = query.iter().peekable();
+    for ch in text.chars() {
+        match remaining.peek() {
+            Some(&&next) if fold(ch) == next => {
+                remaining.next();
             }
-            if *ch == query_chars[query_index] {
-                let is_word_boundary = if i == 0 {
-                    true
-                } else {
-                    let prev = text_chars[i - 1];
-                    prev.is_whitespace() || matches!(prev, '-' | '_' | '.' | '/' | ':')
-                };
-
-                if last_match_index == i as isize - 1 {
-                    consecutive_matches += 1;
-                    score -= f64::from(consecutive_matches) * 5.0;
-                } else {
-                    consecutive_matches = 0;
-                    if last_match_index >= 0 {
-                        score += ((i as isize - last_match_index - 1) as f64) * 2.0;
-                    }
-                }
-
-                if is_word_boundary {
-                    score -= 10.0;
-                }
+            Some(_) => {}
+            None => break,
+        }
+    }
+    remaining.peek().is_none()
+}
 
-                score += (i as f64) * 0.1;
+/// Best local alignment of `query` (already case-folded) in `text`, scored
+/// Smith-Waterman style: every query character must match, in order;
+/// matches earn position bonuses and gaps between them cost a penalty.
+fn align(query: &[char], text: &str) -> FuzzyMatch {
+    if !is_subsequence(query, text) {
+        return FuzzyMatch::none();
+    }
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let (n, m) = (query.len(), chars.len());
+    let bonuses: Vec<i32> = (0..m)
+        .map(|j| position_bonus(j.checked_sub(1).map(|k| chars[k].1), chars[j].1))
+        .collect();
+    let folded: Vec<char> = chars.iter().map(|(_, ch)| fold(*ch)).collect();
 
-                last_match_index = i as isize;
-                query_index += 1;
+    // Row `i`: best score with query[i] matched at text[j], the bonus its
+    // consecutive run carries, and where query[i - 1] matched.
+    let mut rows: Vec<Vec<Option<i32>>> = Vec::with_capacity(n);
+    let mut runs: Vec<Vec<i32>> = Vec::with_capacity(n);
+    let mut parents: Vec<Vec<usize>> = Vec::with_capacity(n);
+    for (i, query_char) in query.iter().enumerate() {
+        let mut row = vec![None; m];
+        let mut run = vec![0; m];
+        let mut parent = vec![0; m];
+        // Best predecessor at least one cell back, gap penalty included.
+        let mut gapped: Option<(i32, usize)> = None;
+        for j in 0..m {
+            if i > 0 && j >= 2 {
+                let extended = gapped.map(|(score, k)| (score + GAP_EXTENSION, k));
+                let opened = rows[i - 1][j - 2].map(|score| (score + GAP_START, j - 2));
+                gapped = match (extended, opened) {
+                    (Some(a), Some(b)) => Some(if b.0 >= a.0 { b } else { a }),
+                    (a, b) => a.or(b),
+                };
+            }
+            if folded[j] != *query_char {
+                continue;
+            }
+            let bonus = bonuses[j];
+            if i == 0 {
+                row[j] = Some(SCORE_MATCH + bonus * FIRST_CHAR_MULTIPLIER);
+                run[j] = bonus;
+                continue;
+            }
+            let consecutive = j
+                .checked_sub(1)
+                .and_then(|k| rows[i - 1][k].map(|score| (score, k)))
+                .map(|(score, k)| {
+                    let run_bonus = bonus.max(runs[i - 1][k]).max(BONUS_CONSECUTIVE);
+                    (score + SCORE_MATCH + run_bonus, k, run_bonus)
+                });
+            let gap = gapped.map(|(score, k)| (score + SCORE_MATCH + bonus, k, bonus));
+            let best = match (consecutive, gap) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            };
+            if let Some((score, k, run_bonus)) = best {
+                row[j] = Some(score);
+                run[j] = run_bonus;
+                parent[j] = k;
             }
         }
+        rows.push(row);
+        runs.push(run);
+        parents.push(parent);
+    }
 
-        if query_index < query_chars.len() {
-            return FuzzyMatch {
-                matches: false,
-                score: 0.0,
-            };
+    let Some((best, end)) = rows[n - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (score, j)))
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+    else {
+        return FuzzyMatch::none();
+    };
+
+    let mut positions = vec![end; n];
+    for i in (1..n).rev() {
+        positions[i - 1] = parents[i][positions[i]];
+    }
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for j in positions {
+        let (start, ch) = chars[j];
+        let end = start + ch.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
         }
+    }
+
+    FuzzyMatch {
+        matches: true,
+        score: -f64::from(best),
+        ranges,
+    }
+}
 
-        FuzzyMatch {
+pub fn fuzzy_match(query: &str, text: &str) -> FuzzyMatch {
+    let query_lower: Vec<char> = query.chars().map(fold).collect();
+    if query_lower.is_empty() {
+        return FuzzyMatch {
             matches: true,
-            score,
-        }
-    };
+            score: 0.0,
+            ranges: Vec::new(),
+        };
+    }
 
-    let primary_match = match_query(&query_lower);
+    let primary_match = align(&query_lower, text);
     if primary_match.matches {
         return primary_match;
     }
 
+    let query_lower: String = query_lower.into_iter().collect();
     let swapped_query = if let Some((letters, digits)) = alpha_numeric_split(&query_lower) {
         format!("{digits}{letters}")
     } else if let Some((digits, letters)) = numeric_alpha_split(&query_lower) {
@@ -96,14 +195,14 @@ pub fn fuzzy_match(query: &str, text: &str) -> FuzzyMatch {
         return primary_match;
     }
 
-    let swapped_match = match_query(&swapped_query);
+    let swapped_match = align(&swapped_query.chars().collect::<Vec<_>>(), text);
     if !swapped_match.matches {
         return primary_match;
     }
 
     FuzzyMatch {
-        matches: true,
         score: swapped_match.score + 5.0,
+        ..swapped_match
     }
 }
 
@@ -125,22 +224,8 @@ where
     let mut results: Vec<(T, f64)> = Vec::new();
 
     for item in items {
-        let text = get_text(item);
-        let mut total_score = 0.0f64;
-        let mut all_match = true;
-
-        for token in &tokens {
-            let matched = fuzzy_match(token, text.as_ref());
-            if matched.matches {
-                total_score += matched.score;
-            } else {
-                all_match = false;
-                break;
-            }
-        }
-
-        if all_match {
-            results.push((item.clone(), total_score));
+        if let Some(score) = score_tokens(&tokens, get_text(item).as_ref()) {
+            results.push((item.clone(), score));
         }
     }
 
@@ -148,6 +233,179 @@ where
     results.into_iter().map(|(item, _)| item).collect()
 }
 
+/// Summed score of every whitespace-separated `tokens` entry, or `None` as
+/// soon as one fails to match.
+fn score_tokens(tokens: &[&str], text: &str) -> Option<f64> {
+    tokens.iter().try_fold(0.0, |total, token| {
+        let matched = fuzzy_match(token, text);
+        matched.matches.then_some(total + matched.score)
+    })
+}
+
+/// A candidate kept by [`fuzzy_filter_top_k`] or [`FuzzyMatcher`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuzzyHit {
+    /// Index into the candidate list.
+    pub index: usize,
+    pub score: f64,
+}
+
+/// Below this many candidates filtering stays on the calling thread:
+/// spawning workers costs more than it saves.
+const PARALLEL_THRESHOLD: usize = 4096;
+
+/// Scores `indices` of `items` against `tokens`, split across scoped
+/// threads for large sets. Hits come back in `indices` order.
+fn collect_hits<T, F>(
+    items: &[T],
+    indices: &[usize],
+    tokens: &[&str],
+    get_text: &F,
+) -> Vec<FuzzyHit>
+where
+    T: Sync,
+    F: Fn(&T) -> &str + Sync,
+{
+    let score_chunk = |chunk: &[usize]| -> Vec<FuzzyHit> {
+        chunk
+            .iter()
+            .filter_map(|&index| {
+                score_tokens(tokens, get_text(&items[index])).map(|score| FuzzyHit { index, score })
+            })
+            .collect()
+    };
+
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |cores| cores.get())
+        .min(indices.len() / (PARALLEL_THRESHOLD / 4).max(1));
+    if indices.len() < PARALLEL_THRESHOLD || workers < 2 {
+        return score_chunk(indices);
+    }
+
+    let chunk_size = indices.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = indices
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || score_chunk(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Orders hits best first (ties by index) and keeps the first `limit`,
+/// selecting them before sorting so a small limit over many hits is cheap.
+fn top_k(mut hits: Vec<FuzzyHit>, limit: Option<usize>) -> Vec<FuzzyHit> {
+    let order = |a: &FuzzyHit, b: &FuzzyHit| {
+        a.score
+            .partial_cmp(&b.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.index.cmp(&b.index))
+    };
+    if let Some(limit) = limit.filter(|limit| *limit < hits.len()) {
+        if limit == 0 {
+            return Vec::new();
+        }
+        hits.select_nth_unstable_by(limit - 1, order);
+        hits.truncate(limit);
+    }
+    hits.sort_unstable_by(order);
+    hits
+}
+
+/// [`fuzzy_filter`] for large candidate sets: scores in parallel across
+/// cores and returns only the best `limit` hits (all when `None`), best
+/// first, as indices into `items`.
+pub fn fuzzy_filter_top_k<T, F>(
+    items: &[T],
+    query: &str,
+    limit: Option<usize>,
+    get_text: F,
+) -> Vec<FuzzyHit>
+where
+    T: Sync,
+    F: Fn(&T) -> &str + Sync,
+{
+    let tokens: Vec<&str> = query.split_whitespace().collect();
+    let indices: Vec<usize> = (0..items.len()).collect();
+    let hits = if tokens.is_empty() {
+        indices
+            .into_iter()
+            .map(|index| FuzzyHit { index, score: 0.0 })
+            .collect()
+    } else {
+        collect_hits(items, &indices, &tokens, &get_text)
+    };
+    top_k(hits, limit)
+}
+
+/// Incremental filter over a fixed candidate list, for palettes and path
+/// completion that re-filter on every keystroke.
+///
+/// When a query extends the previous one, only the previous matches are
+/// rescored: a candidate that failed a query fails every extension of it.
+pub struct FuzzyMatcher {
+    candidates: Vec<String>,
+    last_query: Option<String>,
+    /// Indices matching `last_query`, in candidate order.
+    last_matches: Vec<usize>,
+}
+
+impl FuzzyMatcher {
+    pub fn new(candidates: Vec<String>) -> Self {
+        Self {
+            candidates,
+            last_query: None,
+            last_matches: Vec::new(),
+        }
+    }
+
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    /// Replaces the candidates and forgets the previous query.
+    pub fn set_candidates(&mut self, candidates: Vec<String>) {
+        self.candidates = candidates;
+        self.last_query = None;
+        self.last_matches.clear();
+    }
+
+    /// The best `limit` hits for `query` (all when `None`), best first.
+    pub fn filter(&mut self, query: &str, limit: Option<usize>) -> Vec<FuzzyHit> {
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        if tokens.is_empty() {
+            self.last_query = None;
+            self.last_matches.clear();
+            return top_k(
+                (0..self.candidates.len())
+                    .map(|index| FuzzyHit { index, score: 0.0 })
+                    .collect(),
+                limit,
+            );
+        }
+
+        let refines = self
+            .last_query
+            .as_deref()
+            .is_some_and(|last| query.starts_with(last));
+        let all;
+        let scope: &[usize] = if refines {
+            &self.last_matches
+        } else {
+            all = (0..self.candidates.len()).collect::<Vec<_>>();
+            &all
+        };
+        let hits = collect_hits(&self.candidates, scope, &tokens, &String::as_str);
+
+        self.last_matches = hits.iter().map(|hit| hit.index).collect();
+        self.last_query = Some(query.to_string());
+        top_k(hits, limit)
+    }
+}
+
 fn alpha_numeric_split(input: &str) -> Option<(&str, &str)> {
     let mut idx = 0;
     let bytes = input.as_bytes();
@@ -182,7 +440,7 @@ fn numeric_alpha_split(input: &str) -> Option<(&str, &str)> {
 
 #[cfg(test)]
 mod tests {
-    use super::{fuzzy_filter, fuzzy_match};
+    use super::{fuzzy_filter, fuzzy_filter_top_k, fuzzy_match, FuzzyMatcher};
 
     #[test]
     fn fuzzy_match_prefers_consecutive_matches() {
@@ -205,4 +463,85 @@ mod tests {
         let filtered = fuzzy_filter(&items, "alpha beta", |item| *item);
         assert_eq!(filtered, vec!["alpha beta", "beta alpha"]);
     }
+
+    #[test]
+    fn fuzzy_match_rewards_word_starts_and_camel_humps() {
+        let camel = fuzzy_match("fb", "FooBar");
+        let inner = fuzzy_match("fb", "fabric");
+        assert!(camel.score < inner.score);
+
+        let boundary = fuzzy_match("cfg", "core/fuzzy_gen");
+        let buried = fuzzy_match("cfg", "scaffolding");
+        assert!(boundary.score < buried.score);
+    }
+
+    #[test]
+    fn fuzzy_match_reports_byte_ranges_of_the_best_alignment() {
+        // A greedy scan would take "br" inside "abr"; the word start wins.
+        let matched = fuzzy_match("br", "abr_bar");
+        assert_eq!(matched.ranges, vec![4..5, 6..7]);
+
+        let consecutive = fuzzy_match("bar", "foo_bar");
+        assert_eq!(consecutive.ranges, vec![4..7]);
+
+        // Ranges are byte offsets into the original text.
+        let unicode = fuzzy_match("éb", "Café Bar");
+        assert_eq!(unicode.ranges, vec![3..5, 6..7]);
+
+        assert!(fuzzy_match("", "anything").ranges.is_empty());
+        assert!(!fuzzy_match("zz", "abc").matches);
+    }
+
+    fn sample_paths(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| format!("src/module_{}/file_{}_{}.rs", i % 97, i, i % 7))
+            .collect()
+    }
+
+    #[test]
+    fn top_k_matches_the_serial_filter_across_threads() {
+        let paths = sample_paths(20_000);
+        let query = "mod5 fil3";
+
+        let serial: Vec<usize> =
+            fuzzy_filter(&(0..paths.len()).collect::<Vec<_>>(), query, |index| {
+                paths[*index].as_str()
+            });
+        let parallel: Vec<usize> = fuzzy_filter_top_k(&paths, query, None, String::as_str)
+            .into_iter()
+            .map(|hit| hit.index)
+            .collect();
+        assert!(serial.len() > 100);
+        assert_eq!(parallel, serial);
+
+        let top = fuzzy_filter_top_k(&paths, query, Some(10), String::as_str);
+        assert_eq!(
+            top.iter().map(|hit| hit.index).collect::<Vec<_>>(),
+            serial[..10]
+        );
+        assert_eq!(
+            fuzzy_filter_top_k(&paths, "", Some(3), String::as_str).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn matcher_refines_previous_matches_as_the_query_grows() {
+        let mut matcher = FuzzyMatcher::new(sample_paths(5_000));
+        let broad = matcher.filter("file_1", None);
+        let broad_count = matcher.last_matches.len();
+        assert_eq!(broad.len(), broad_count);
+
+        let narrow = matcher.filter("file_12", Some(5));
+        assert!(matcher.last_matches.len() < broad_count);
+        let fresh = fuzzy_filter_top_k(matcher.candidates(), "file_12", Some(5), String::as_str);
+        assert_eq!(narrow, fresh);
+
+        // Not an extension: the full list is scored again.
+        let other = matcher.filter("module_3", None);
+        assert_eq!(
+            other,
+            fuzzy_filter_top_k(matcher.candidates(), "module_3", None, String::as_str)
+        );
+    }
 }
@@ -147,6 +147,71 @@ pub enum KeyEventType {
     Release,
 }
 
+/// A set of [`KeyEventType`]s, used by components to choose which key
+/// events they receive. Combine with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEventTypes {
+    press: bool,
+    repeat: bool,
+    release: bool,
+}
+
+impl KeyEventTypes {
+    pub const NONE: Self = Self {
+        press: false,
+        repeat: false,
+        release: false,
+    };
+    pub const PRESS: Self = Self {
+        press: true,
+        ..Self::NONE
+    };
+    pub const REPEAT: Self = Self {
+        repeat: true,
+        ..Self::NONE
+    };
+    pub const RELEASE: Self = Self {
+        release: true,
+        ..Self::NONE
+    };
+    pub const ALL: Self = Self {
+        press: true,
+        repeat: true,
+        release: true,
+    };
+
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            press: self.press || other.press,
+            repeat: self.repeat || other.repeat,
+            release: self.release || other.release,
+        }
+    }
+
+    pub const fn contains(self, event_type: KeyEventType) -> bool {
+        match event_type {
+            KeyEventType::Press => self.press,
+            KeyEventType::Repeat => self.repeat,
+            KeyEventType::Release => self.release,
+        }
+    }
+}
+
+/// Press and repeat: what components receive unless they opt in to more.
+impl Default for KeyEventTypes {
+    fn default() -> Self {
+        Self::PRESS.union(Self::REPEAT)
+    }
+}
+
+impl std::ops::BitOr for KeyEventTypes {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct ParsedKittySequence {
     codepoint: i32,
@@ -180,6 +245,21 @@ pub fn is_kitty_query_response(sequence: &str) -> bool {
     !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit())
 }
 
+/// Whether `sequence` is a legacy key encoding that a terminal with the kitty
+/// keyboard protocol enabled never sends: a C0 control other than Escape,
+/// Tab, Enter, Ctrl+J and Backspace, or Escape followed by one printable
+/// character (legacy Alt+key). Under the protocol these keys arrive as
+/// `CSI ... u`. A lone Escape is not evidence: the stdin buffer also flushes
+/// the start of a sequence split across a slow link as one.
+pub fn is_legacy_only_sequence(sequence: &str) -> bool {
+    match sequence.as_bytes() {
+        [0x1b] => false,
+        [byte] => *byte < 0x20 && !matches!(byte, b'\t' | b'\r' | b'\n' | 0x08),
+        [0x1b, byte] => (0x20..=0x7f).contains(byte),
+        _ => false,
+    }
+}
+
 pub fn matches_key(data: &str, key_id: &str, kitty_active: bool) -> bool {
     let parsed = parse_key_id(key_id);
     let Some(parsed) = parsed else {
@@ -1232,7 +1312,8 @@ const LEGACY_CTRL_END: [&str; 1] = ["\x1b[8^"];
 #[cfg(test)]
 mod tests {
     use super::{
-        is_key_release, is_key_repeat, is_kitty_query_response, matches_key, parse_key, Key,
+        is_key_release, is_key_repeat, is_kitty_query_response, is_legacy_only_sequence,
+        matches_key, parse_key, Key,
     };
 
     #[test]
@@ -1291,4 +1372,16 @@ mod tests {
         assert!(!is_kitty_query_response("X\x1b[?1u"));
         assert!(!is_kitty_query_response("\x1b[?1uX"));
     }
+
+    #[test]
+    fn legacy_only_sequences_are_keys_kitty_would_encode_as_csi_u() {
+        for legacy in ["\x03", "\x00", "\x1ba", "\x1b ", "\x1b\x7f"] {
+            assert!(is_legacy_only_sequence(legacy), "{legacy:?}");
+        }
+        for shared in [
+            "\x1b", "\r", "\t", "\n", "\x7f", "a", "\x1b[A", "\x1b\r", "\x1b[27u",
+        ] {
+            assert!(!is_legacy_only_sequence(shared), "{shared:?}");
+        }
+    }
 }
@@ -1,5 +1,7 @@
 //! Structured input events produced by the runtime.
 
+use std::path::PathBuf;
+
 use crate::core::input::{parse_key, parse_key_event_type, parse_text, KeyEventType};
 
 /// Input event delivered to components.
@@ -24,6 +26,15 @@ pub enum InputEvent {
         raw: String,
         text: String,
     },
+    /// A bracketed paste that consists only of existing absolute paths,
+    /// which is how terminals deliver files dragged onto the window.
+    /// `text` is the pasted text, so widgets without file support can treat
+    /// this like [`InputEvent::Paste`].
+    FilesDropped {
+        raw: String,
+        text: String,
+        paths: Vec<PathBuf>,
+    },
     Resize {
         columns: u16,
         rows: u16,
@@ -31,6 +42,36 @@ pub enum InputEvent {
     UnknownRaw {
         raw: String,
     },
+    /// IME composition progress, fed by hosts that receive it from the
+    /// platform (see [`crate::runtime::tui::Command::Composition`]); terminals
+    /// do not report it on stdin.
+    Composition {
+        phase: CompositionPhase,
+        /// The preedit for `Update`, the final text for `Commit`, empty
+        /// otherwise.
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositionPhase {
+    Start,
+    Update,
+    /// Composition finished; `text` should be inserted as typed text.
+    Commit,
+    Cancel,
+}
+
+impl InputEvent {
+    /// Press, repeat or release for key and text events; `None` otherwise.
+    pub fn key_event_type(&self) -> Option<KeyEventType> {
+        match self {
+            InputEvent::Key { event_type, .. } | InputEvent::Text { event_type, .. } => {
+                Some(*event_type)
+            }
+            _ => None,
+        }
+    }
 }
 
 pub fn parse_input_events(data: &str, kitty_active: bool) -> Vec<InputEvent> {
@@ -49,9 +90,6 @@ pub fn parse_input_events(data: &str, kitty_active: bool) -> Vec<InputEvent> {
         let event_type = parse_key_event_type(data);
 
         if let Some(text) = parse_text(data, kitty_active) {
-            if event_type == KeyEventType::Release {
-                return Vec::new();
-            }
             return vec![InputEvent::Text {
                 raw: data.to_string(),
                 text,
@@ -93,9 +131,11 @@ pub fn parse_input_events(data: &str, kitty_active: bool) -> Vec<InputEvent> {
 
         let paste_text = &after_start[..end_rel];
         let raw_end = start + PASTE_START.len() + end_rel + PASTE_END.len();
-        events.push(InputEvent::Paste {
-            raw: remaining[start..raw_end].to_string(),
-            text: paste_text.to_string(),
+        let raw = remaining[start..raw_end].to_string();
+        let text = paste_text.to_string();
+        events.push(match parse_dropped_paths(paste_text) {
+            Some(paths) => InputEvent::FilesDropped { raw, text, paths },
+            None => InputEvent::Paste { raw, text },
         });
 
         remaining = &after_start[end_rel + PASTE_END.len()..];
@@ -107,9 +147,89 @@ pub fn parse_input_events(data: &str, kitty_active: bool) -> Vec<InputEvent> {
     events
 }
 
+/// Parses pasted text as a list of dropped files.
+///
+/// Terminals paste dropped files as paths separated by spaces or newlines,
+/// shell-quoted (`'/a b'`, `"/a b"`), backslash-escaped (`/a\ b`) or as
+/// `file://` URIs depending on the emulator. Returns `None` unless every
+/// entry is an absolute path that exists, so ordinary pastes that merely
+/// mention a path stay pastes.
+pub fn parse_dropped_paths(text: &str) -> Option<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for token in split_dropped_tokens(text)? {
+        let path = match token.strip_prefix("file://") {
+            Some(uri) => PathBuf::from(percent_decode(
+                uri.strip_prefix("localhost").unwrap_or(uri),
+            )?),
+            None => PathBuf::from(token),
+        };
+        if !path.is_absolute() || !path.exists() {
+            return None;
+        }
+        paths.push(path);
+    }
+    (!paths.is_empty()).then_some(paths)
+}
+
+/// Splits on unquoted whitespace, honouring single quotes, double quotes
+/// and (outside Windows) backslash escapes. `None` on an unterminated quote.
+fn split_dropped_tokens(text: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' | '"' => {
+                in_token = true;
+                loop {
+                    match chars.next()? {
+                        close if close == ch => break,
+                        '\\' if ch == '"' && cfg!(not(windows)) => current.push(chars.next()?),
+                        other => current.push(other),
+                    }
+                }
+            }
+            '\\' if cfg!(not(windows)) => {
+                in_token = true;
+                current.push(chars.next()?);
+            }
+            ch if ch.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            other => {
+                in_token = true;
+                current.push(other);
+            }
+        }
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Some(tokens)
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{parse_input_events, InputEvent};
+    use super::{parse_dropped_paths, parse_input_events, InputEvent};
     use crate::core::input::KeyEventType;
 
     #[test]
@@ -189,4 +309,52 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn paste_of_existing_absolute_paths_is_a_file_drop() {
+        let dir = std::env::temp_dir().join(format!("tape-dropped-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let first = dir.join("a b.txt");
+        let second = dir.join("c.txt");
+        std::fs::write(&first, "a").expect("write first");
+        std::fs::write(&second, "c").expect("write second");
+        let first_str = first.display().to_string();
+        let second_str = second.display().to_string();
+
+        let quoted = format!("'{first_str}' {second_str}\n");
+        let escaped = format!("{} {second_str}", first_str.replace(' ', "\\ "));
+        let uri = format!(
+            "file://{}\r\n\"{second_str}\"",
+            first_str.replace(' ', "%20")
+        );
+        for text in [quoted, escaped, uri] {
+            assert_eq!(
+                parse_dropped_paths(&text),
+                Some(vec![first.clone(), second.clone()]),
+                "{text:?}"
+            );
+        }
+
+        let raw = format!("\x1b[200~{second_str}\x1b[201~");
+        assert_eq!(
+            parse_input_events(&raw, false),
+            vec![InputEvent::FilesDropped {
+                raw: raw.clone(),
+                text: second_str.clone(),
+                paths: vec![second.clone()],
+            }]
+        );
+
+        // Anything else in the paste keeps it an ordinary paste.
+        assert_eq!(parse_dropped_paths(&format!("see {second_str}")), None);
+        assert_eq!(
+            parse_dropped_paths(&dir.join("missing").display().to_string()),
+            None
+        );
+        assert_eq!(parse_dropped_paths("c.txt"), None);
+        assert_eq!(parse_dropped_paths(&format!("'{second_str}")), None);
+        assert_eq!(parse_dropped_paths("  \n"), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
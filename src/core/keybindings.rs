@@ -1,6 +1,13 @@
 //! Editor keybindings.
+//!
+//! Besides matching, the manager can be introspected: [`EditorKeybindingsManager::conflicts`]
+//! reports chords bound twice in one context, and
+//! [`EditorKeybindingsManager::help_sections`] lists the active bindings grouped by context.
+//!
+//! Every action has a stable id ([`EditorAction::id`]), so user keymap files
+//! can remap them; [`EditorKeybindingsConfig::from_toml`] reads such a file.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, LazyLock, Mutex};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -17,12 +24,16 @@ pub enum EditorAction {
     JumpBackward,
     PageUp,
     PageDown,
+    /// Scroll the editor viewport without moving the cursor.
+    ScrollUp,
+    ScrollDown,
     DeleteCharBackward,
     DeleteCharForward,
     DeleteWordBackward,
     DeleteWordForward,
     DeleteToLineStart,
     DeleteToLineEnd,
+    TransposeChars,
     NewLine,
     Submit,
     Tab,
@@ -32,6 +43,8 @@ pub enum EditorAction {
     SelectPageDown,
     SelectConfirm,
     SelectCancel,
+    TreeExpand,
+    TreeCollapse,
     Copy,
     Yank,
     YankPop,
@@ -44,6 +57,319 @@ pub enum EditorAction {
     DeleteSessionNoninvasive,
 }
 
+impl EditorAction {
+    pub const ALL: &'static [EditorAction] = &[
+        EditorAction::CursorUp,
+        EditorAction::CursorDown,
+        EditorAction::CursorLeft,
+        EditorAction::CursorRight,
+        EditorAction::CursorWordLeft,
+        EditorAction::CursorWordRight,
+        EditorAction::CursorLineStart,
+        EditorAction::CursorLineEnd,
+        EditorAction::JumpForward,
+        EditorAction::JumpBackward,
+        EditorAction::PageUp,
+        EditorAction::PageDown,
+        EditorAction::ScrollUp,
+        EditorAction::ScrollDown,
+        EditorAction::DeleteCharBackward,
+        EditorAction::DeleteCharForward,
+        EditorAction::DeleteWordBackward,
+        EditorAction::DeleteWordForward,
+        EditorAction::DeleteToLineStart,
+        EditorAction::DeleteToLineEnd,
+        EditorAction::TransposeChars,
+        EditorAction::NewLine,
+        EditorAction::Submit,
+        EditorAction::Tab,
+        EditorAction::SelectUp,
+        EditorAction::SelectDown,
+        EditorAction::SelectPageUp,
+        EditorAction::SelectPageDown,
+        EditorAction::SelectConfirm,
+        EditorAction::SelectCancel,
+        EditorAction::TreeExpand,
+        EditorAction::TreeCollapse,
+        EditorAction::Copy,
+        EditorAction::Yank,
+        EditorAction::YankPop,
+        EditorAction::Undo,
+        EditorAction::ExpandTools,
+        EditorAction::ToggleSessionPath,
+        EditorAction::ToggleSessionSort,
+        EditorAction::RenameSession,
+        EditorAction::DeleteSession,
+        EditorAction::DeleteSessionNoninvasive,
+    ];
+
+    /// The widget context that consults this action.
+    pub fn context(self) -> KeybindingContext {
+        use EditorAction::*;
+
+        match self {
+            CursorUp | CursorDown | CursorLeft | CursorRight | CursorWordLeft | CursorWordRight
+            | CursorLineStart | CursorLineEnd | JumpForward | JumpBackward | PageUp | PageDown
+            | ScrollUp | ScrollDown | DeleteCharBackward | DeleteCharForward
+            | DeleteWordBackward | DeleteWordForward | DeleteToLineStart | DeleteToLineEnd
+            | TransposeChars | NewLine | Submit | Tab | Copy | Yank | YankPop | Undo => {
+                KeybindingContext::Editing
+            }
+            SelectUp | SelectDown | SelectPageUp | SelectPageDown | SelectConfirm
+            | SelectCancel => KeybindingContext::Selection,
+            TreeExpand | TreeCollapse => KeybindingContext::Tree,
+            ToggleSessionPath
+            | ToggleSessionSort
+            | RenameSession
+            | DeleteSession
+            | DeleteSessionNoninvasive => KeybindingContext::Sessions,
+            ExpandTools => KeybindingContext::App,
+        }
+    }
+
+    /// Stable camelCase identifier (`"cursorUp"`, `"selectConfirm"`) used
+    /// by keymap files; see [`EditorKeybindingsConfig::from_toml`].
+    pub fn id(self) -> &'static str {
+        use EditorAction::*;
+
+        match self {
+            CursorUp => "cursorUp",
+            CursorDown => "cursorDown",
+            CursorLeft => "cursorLeft",
+            CursorRight => "cursorRight",
+            CursorWordLeft => "cursorWordLeft",
+            CursorWordRight => "cursorWordRight",
+            CursorLineStart => "cursorLineStart",
+            CursorLineEnd => "cursorLineEnd",
+            JumpForward => "jumpForward",
+            JumpBackward => "jumpBackward",
+            PageUp => "pageUp",
+            PageDown => "pageDown",
+            ScrollUp => "scrollUp",
+            ScrollDown => "scrollDown",
+            DeleteCharBackward => "deleteCharBackward",
+            DeleteCharForward => "deleteCharForward",
+            DeleteWordBackward => "deleteWordBackward",
+            DeleteWordForward => "deleteWordForward",
+            DeleteToLineStart => "deleteToLineStart",
+            DeleteToLineEnd => "deleteToLineEnd",
+            TransposeChars => "transposeChars",
+            NewLine => "newLine",
+            Submit => "submit",
+            Tab => "tab",
+            SelectUp => "selectUp",
+            SelectDown => "selectDown",
+            SelectPageUp => "selectPageUp",
+            SelectPageDown => "selectPageDown",
+            SelectConfirm => "selectConfirm",
+            SelectCancel => "selectCancel",
+            TreeExpand => "treeExpand",
+            TreeCollapse => "treeCollapse",
+            Copy => "copy",
+            Yank => "yank",
+            YankPop => "yankPop",
+            Undo => "undo",
+            ExpandTools => "expandTools",
+            ToggleSessionPath => "toggleSessionPath",
+            ToggleSessionSort => "toggleSessionSort",
+            RenameSession => "renameSession",
+            DeleteSession => "deleteSession",
+            DeleteSessionNoninvasive => "deleteSessionNoninvasive",
+        }
+    }
+
+    /// The action with identifier `id`, matched exactly.
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|action| action.id() == id)
+    }
+
+    pub fn description(self) -> &'static str {
+        use EditorAction::*;
+
+        match self {
+            CursorUp => "Move up",
+            CursorDown => "Move down",
+            CursorLeft => "Move left",
+            CursorRight => "Move right",
+            CursorWordLeft => "Move one word left",
+            CursorWordRight => "Move one word right",
+            CursorLineStart => "Move to line start",
+            CursorLineEnd => "Move to line end",
+            JumpForward => "Jump forward to character",
+            JumpBackward => "Jump backward to character",
+            PageUp => "Page up",
+            PageDown => "Page down",
+            ScrollUp => "Scroll up",
+            ScrollDown => "Scroll down",
+            DeleteCharBackward => "Delete character before cursor",
+            DeleteCharForward => "Delete character at cursor",
+            DeleteWordBackward => "Delete word before cursor",
+            DeleteWordForward => "Delete word after cursor",
+            DeleteToLineStart => "Delete to line start",
+            DeleteToLineEnd => "Delete to line end",
+            TransposeChars => "Transpose characters",
+            NewLine => "Insert newline",
+            Submit => "Submit",
+            Tab => "Complete",
+            SelectUp => "Previous item",
+            SelectDown => "Next item",
+            SelectPageUp => "Previous page",
+            SelectPageDown => "Next page",
+            SelectConfirm => "Confirm selection",
+            SelectCancel => "Cancel",
+            TreeExpand => "Expand node",
+            TreeCollapse => "Collapse node",
+            Copy => "Copy",
+            Yank => "Paste last deletion",
+            YankPop => "Cycle pasted deletion",
+            Undo => "Undo",
+            ExpandTools => "Expand tool output",
+            ToggleSessionPath => "Toggle session path",
+            ToggleSessionSort => "Toggle session sort",
+            RenameSession => "Rename session",
+            DeleteSession => "Delete session",
+            DeleteSessionNoninvasive => "Delete session (keep selection)",
+        }
+    }
+}
+
+/// Where a binding is consulted. Chords only collide within one context,
+/// except [`KeybindingContext::Global`]: the runtime handles those before
+/// any component sees the key, so they shadow every other context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeybindingContext {
+    Global,
+    Editing,
+    Selection,
+    Tree,
+    Sessions,
+    App,
+}
+
+impl KeybindingContext {
+    pub fn title(self) -> &'static str {
+        match self {
+            KeybindingContext::Global => "Global",
+            KeybindingContext::Editing => "Editing",
+            KeybindingContext::Selection => "Selection",
+            KeybindingContext::Tree => "Tree",
+            KeybindingContext::Sessions => "Sessions",
+            KeybindingContext::App => "App",
+        }
+    }
+}
+
+/// What a chord is bound to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeybindingOwner {
+    Action(EditorAction),
+    /// A key the runtime itself intercepts (e.g. `"debug"` on ctrl+shift+d).
+    Runtime(&'static str),
+    /// A host command registered with the runtime, by id.
+    Command(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeybindingConflictKind {
+    /// Two owners claim the chord in the same context; only the first one
+    /// a widget checks will ever fire.
+    Duplicate,
+    /// A global binding takes the chord before components see it; the
+    /// non-global owners never fire.
+    Shadowed,
+}
+
+/// A chord claimed more than once. For [`KeybindingConflictKind::Shadowed`]
+/// the global owners come first, followed by the owners they shadow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeybindingConflict {
+    pub key: KeyId,
+    pub kind: KeybindingConflictKind,
+    pub context: KeybindingContext,
+    pub owners: Vec<KeybindingOwner>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeybindingHelpEntry {
+    pub owner: KeybindingOwner,
+    pub keys: Vec<KeyId>,
+    pub description: String,
+}
+
+/// Active bindings of one context, in registration order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeybindingHelpSection {
+    pub context: KeybindingContext,
+    pub entries: Vec<KeybindingHelpEntry>,
+}
+
+/// Finds duplicate and shadowed chords among `entries` (in priority order).
+pub(crate) fn detect_conflicts(
+    entries: &[(KeybindingContext, KeybindingHelpEntry)],
+) -> Vec<KeybindingConflict> {
+    let mut by_context: BTreeMap<(KeybindingContext, KeyId), Vec<KeybindingOwner>> =
+        BTreeMap::new();
+    for (context, entry) in entries {
+        for key in &entry.keys {
+            let owners = by_context
+                .entry((*context, normalize_key_id(key)))
+                .or_default();
+            if !owners.contains(&entry.owner) {
+                owners.push(entry.owner.clone());
+            }
+        }
+    }
+
+    let mut conflicts: Vec<KeybindingConflict> = by_context
+        .iter()
+        .filter(|(_, owners)| owners.len() > 1)
+        .map(|((context, key), owners)| KeybindingConflict {
+            key: key.clone(),
+            kind: KeybindingConflictKind::Duplicate,
+            context: *context,
+            owners: owners.clone(),
+        })
+        .collect();
+
+    for ((context, key), global) in by_context.range(
+        (KeybindingContext::Global, KeyId::new())..(KeybindingContext::Editing, KeyId::new()),
+    ) {
+        debug_assert_eq!(*context, KeybindingContext::Global);
+        let mut owners = global.clone();
+        for ((other, other_key), shadowed) in &by_context {
+            if *other != KeybindingContext::Global && other_key == key {
+                owners.extend(shadowed.iter().cloned());
+            }
+        }
+        if owners.len() > global.len() {
+            conflicts.push(KeybindingConflict {
+                key: key.clone(),
+                kind: KeybindingConflictKind::Shadowed,
+                context: KeybindingContext::Global,
+                owners,
+            });
+        }
+    }
+
+    conflicts
+}
+
+/// Groups `entries` into sections ordered by context.
+pub(crate) fn group_help(
+    entries: Vec<(KeybindingContext, KeybindingHelpEntry)>,
+) -> Vec<KeybindingHelpSection> {
+    let mut by_context: BTreeMap<KeybindingContext, Vec<KeybindingHelpEntry>> = BTreeMap::new();
+    for (context, entry) in entries {
+        if !entry.keys.is_empty() {
+            by_context.entry(context).or_default().push(entry);
+        }
+    }
+    by_context
+        .into_iter()
+        .map(|(context, entries)| KeybindingHelpSection { context, entries })
+        .collect()
+}
+
 pub type KeyId = String;
 
 #[derive(Debug, Clone)]
@@ -91,6 +417,264 @@ impl EditorKeybindingsConfig {
     pub fn set<K: Into<KeyBinding>>(&mut self, action: EditorAction, keys: K) {
         self.entries.insert(action, keys.into());
     }
+
+    /// GNU readline (emacs mode) defaults on top of the default map.
+    ///
+    /// Adds `ctrl+t` transpose, `ctrl+h` backspace and `ctrl+p`/`ctrl+n` for
+    /// up/down, and drops the non-readline `alt+delete` word kill. Undo stays
+    /// on `ctrl+-`, which is the byte terminals send for `ctrl+_`.
+    pub fn readline() -> Self {
+        use EditorAction::*;
+
+        let mut config = Self::new();
+        config.set(CursorUp, vec!["up", "ctrl+p"]);
+        config.set(CursorDown, vec!["down", "ctrl+n"]);
+        config.set(CursorLeft, vec!["left", "ctrl+b"]);
+        config.set(CursorRight, vec!["right", "ctrl+f"]);
+        config.set(CursorWordLeft, vec!["alt+left", "ctrl+left", "alt+b"]);
+        config.set(CursorWordRight, vec!["alt+right", "ctrl+right", "alt+f"]);
+        config.set(CursorLineStart, vec!["home", "ctrl+a"]);
+        config.set(CursorLineEnd, vec!["end", "ctrl+e"]);
+        config.set(DeleteCharBackward, vec!["backspace", "ctrl+h"]);
+        config.set(DeleteCharForward, vec!["delete", "ctrl+d"]);
+        config.set(DeleteWordBackward, vec!["ctrl+w", "alt+backspace"]);
+        config.set(DeleteWordForward, "alt+d");
+        config.set(DeleteToLineStart, "ctrl+u");
+        config.set(DeleteToLineEnd, "ctrl+k");
+        config.set(TransposeChars, "ctrl+t");
+        config.set(Yank, "ctrl+y");
+        config.set(YankPop, "alt+y");
+        config.set(Undo, "ctrl+-");
+        config
+    }
+
+    /// Preset names accepted by [`EditorKeybindingsConfig::preset`].
+    pub const PRESETS: &'static [&'static str] = &["default", "readline"];
+
+    /// Looks up a preset by name (case-insensitive), for hosts that select
+    /// the keymap from a config file. Returns `None` for unknown names.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "default" => Some(Self::default()),
+            "readline" | "emacs" => Some(Self::readline()),
+            _ => None,
+        }
+    }
+}
+
+/// A keymap file that could not be read, with the 1-based line at fault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeymapParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl KeymapParseError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for KeymapParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for KeymapParseError {}
+
+impl EditorKeybindingsConfig {
+    /// Reads a keymap file: an optional top-level `preset` naming one of
+    /// [`Self::PRESETS`], then an `[editor]` table of action ids bound to
+    /// a key or an array of keys (an empty array unbinds the action).
+    ///
+    /// ```toml
+    /// preset = "readline"
+    ///
+    /// [editor]
+    /// submit = "ctrl+enter"
+    /// newLine = ["enter", "shift+enter"]
+    /// ```
+    ///
+    /// Only this subset of TOML is accepted: `#` comments, one-line string
+    /// values and string arrays. Unknown tables, action ids, presets and
+    /// modifiers, and actions bound twice, are errors.
+    pub fn from_toml(source: &str) -> Result<Self, KeymapParseError> {
+        let mut config = Self::new();
+        let mut table: Option<String> = None;
+        let mut bound: HashMap<EditorAction, usize> = HashMap::new();
+
+        for (index, raw_line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = strip_toml_comment(raw_line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[') {
+                let name = name
+                    .strip_suffix(']')
+                    .ok_or_else(|| KeymapParseError::new(line_number, "unclosed table header"))?
+                    .trim();
+                if name != "editor" {
+                    return Err(KeymapParseError::new(
+                        line_number,
+                        format!("unknown table `[{name}]`; expected `[editor]`"),
+                    ));
+                }
+                table = Some(name.to_string());
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| KeymapParseError::new(line_number, "expected `key = value`"))?;
+            let key = unquote_toml_key(key.trim());
+            let keys = parse_toml_keys(value.trim())
+                .map_err(|message| KeymapParseError::new(line_number, message))?;
+
+            if table.is_none() {
+                if key != "preset" {
+                    return Err(KeymapParseError::new(
+                        line_number,
+                        format!("unknown setting `{key}`; bindings go in an `[editor]` table"),
+                    ));
+                }
+                let [name] = keys.as_slice() else {
+                    return Err(KeymapParseError::new(
+                        line_number,
+                        "`preset` takes one name",
+                    ));
+                };
+                let preset = Self::preset(name).ok_or_else(|| {
+                    KeymapParseError::new(
+                        line_number,
+                        format!(
+                            "unknown preset `{name}`; expected one of {}",
+                            Self::PRESETS.join(", ")
+                        ),
+                    )
+                })?;
+                config.entries.extend(preset.entries);
+                continue;
+            }
+
+            let action = EditorAction::from_id(key).ok_or_else(|| {
+                KeymapParseError::new(line_number, format!("unknown action `{key}`"))
+            })?;
+            if let Some(previous) = bound.insert(action, line_number) {
+                return Err(KeymapParseError::new(
+                    line_number,
+                    format!("`{key}` is already bound on line {previous}"),
+                ));
+            }
+            for key_id in &keys {
+                validate_key_id(key_id)
+                    .map_err(|message| KeymapParseError::new(line_number, message))?;
+            }
+            config.set(action, keys);
+        }
+
+        Ok(config)
+    }
+}
+
+/// `line` up to a `#` that is not inside a string.
+fn strip_toml_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, ch) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if ch == '\\' => escaped = true,
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => return &line[..index],
+            None => {}
+        }
+    }
+    line
+}
+
+fn unquote_toml_key(key: &str) -> &str {
+    key.strip_prefix('"')
+        .and_then(|key| key.strip_suffix('"'))
+        .unwrap_or(key)
+}
+
+/// A string or an array of strings.
+fn parse_toml_keys(value: &str) -> Result<Vec<KeyId>, String> {
+    let Some(items) = value.strip_prefix('[') else {
+        let (key, rest) = parse_toml_string(value)?;
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected `{}` after value", rest.trim()));
+        }
+        return Ok(vec![key]);
+    };
+
+    let mut keys = Vec::new();
+    let mut rest = items.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix(']') {
+            if !after.trim().is_empty() {
+                return Err(format!("unexpected `{}` after array", after.trim()));
+            }
+            return Ok(keys);
+        }
+        let (key, after) = parse_toml_string(rest)?;
+        keys.push(key);
+        let after = after.trim_start();
+        rest = match after.strip_prefix(',') {
+            Some(after) => after.trim_start(),
+            None if after.starts_with(']') => after,
+            None if after.is_empty() => return Err("unclosed array".to_string()),
+            None => return Err("expected `,` or `]` in array".to_string()),
+        };
+    }
+}
+
+/// A leading basic (`"..."`) or literal (`'...'`) string and what follows.
+fn parse_toml_string(value: &str) -> Result<(String, &str), String> {
+    let mut chars = value.char_indices();
+    let quote = match chars.next() {
+        Some((_, quote @ ('"' | '\''))) => quote,
+        _ => return Err(format!("expected a quoted key, found `{value}`")),
+    };
+    let mut parsed = String::new();
+    let mut escaped = false;
+    for (index, ch) in chars {
+        if escaped {
+            parsed.push(ch);
+            escaped = false;
+        } else if quote == '"' && ch == '\\' {
+            escaped = true;
+        } else if ch == quote {
+            return Ok((parsed, &value[index + ch.len_utf8()..]));
+        } else {
+            parsed.push(ch);
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+fn validate_key_id(key_id: &str) -> Result<(), String> {
+    let parts: Vec<&str> = key_id.split('+').collect();
+    let (key, modifiers) = parts.split_last().unwrap_or((&"", &[]));
+    if key.trim().is_empty() {
+        return Err(format!("`{key_id}` names no key"));
+    }
+    match modifiers.iter().find(|part| {
+        !matches!(
+            part.trim().to_ascii_lowercase().as_str(),
+            "ctrl" | "shift" | "alt"
+        )
+    }) {
+        Some(part) => Err(format!("unknown modifier `{part}` in `{key_id}`")),
+        None => Ok(()),
+    }
 }
 
 pub static DEFAULT_EDITOR_KEYBINDINGS: LazyLock<HashMap<EditorAction, Vec<KeyId>>> =
@@ -127,6 +711,14 @@ pub static DEFAULT_EDITOR_KEYBINDINGS: LazyLock<HashMap<EditorAction, Vec<KeyId>
         map.insert(JumpBackward, vec!["ctrl+alt+]".to_string()]);
         map.insert(PageUp, vec!["pageUp".to_string()]);
         map.insert(PageDown, vec!["pageDown".to_string()]);
+        map.insert(
+            ScrollUp,
+            vec!["shift+pageUp".to_string(), "ctrl+up".to_string()],
+        );
+        map.insert(
+            ScrollDown,
+            vec!["shift+pageDown".to_string(), "ctrl+down".to_string()],
+        );
         map.insert(DeleteCharBackward, vec!["backspace".to_string()]);
         map.insert(
             DeleteCharForward,
@@ -154,6 +746,8 @@ pub static DEFAULT_EDITOR_KEYBINDINGS: LazyLock<HashMap<EditorAction, Vec<KeyId>
             SelectCancel,
             vec!["escape".to_string(), "ctrl+c".to_string()],
         );
+        map.insert(TreeExpand, vec!["right".to_string()]);
+        map.insert(TreeCollapse, vec!["left".to_string()]);
         map.insert(Copy, vec!["ctrl+c".to_string()]);
         map.insert(Yank, vec!["ctrl+y".to_string()]);
         map.insert(YankPop, vec!["alt+y".to_string()]);
@@ -222,6 +816,33 @@ impl EditorKeybindingsManager {
     pub fn set_config(&mut self, config: EditorKeybindingsConfig) {
         self.build_maps(&config);
     }
+
+    /// Every action as a help entry, in [`EditorAction::ALL`] order.
+    pub(crate) fn help_entries(&self) -> Vec<(KeybindingContext, KeybindingHelpEntry)> {
+        EditorAction::ALL
+            .iter()
+            .map(|action| {
+                (
+                    action.context(),
+                    KeybindingHelpEntry {
+                        owner: KeybindingOwner::Action(*action),
+                        keys: self.get_keys(*action),
+                        description: action.description().to_string(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Chords bound to more than one action within a context.
+    pub fn conflicts(&self) -> Vec<KeybindingConflict> {
+        detect_conflicts(&self.help_entries())
+    }
+
+    /// Active bindings grouped by context; unbound actions are omitted.
+    pub fn help_sections(&self) -> Vec<KeybindingHelpSection> {
+        group_help(self.help_entries())
+    }
 }
 
 pub type EditorKeybindingsHandle = Arc<Mutex<EditorKeybindingsManager>>;
@@ -232,7 +853,7 @@ pub fn default_editor_keybindings_handle() -> EditorKeybindingsHandle {
     )))
 }
 
-fn normalize_key_id(key_id: &str) -> String {
+pub(crate) fn normalize_key_id(key_id: &str) -> String {
     let lowered = key_id.to_ascii_lowercase();
     let parts: Vec<&str> = lowered.split('+').collect();
     let key = parts.last().copied().unwrap_or("").trim();
@@ -282,7 +903,8 @@ fn normalize_key_id(key_id: &str) -> String {
 mod tests {
     use super::{
         default_editor_keybindings_handle, EditorAction, EditorKeybindingsConfig,
-        EditorKeybindingsManager, KeyBinding,
+        EditorKeybindingsManager, KeyBinding, KeybindingConflictKind, KeybindingContext,
+        KeybindingOwner,
     };
 
     #[test]
@@ -305,6 +927,127 @@ mod tests {
         assert!(!manager.matches(Some("enter"), EditorAction::Submit));
     }
 
+    #[test]
+    fn readline_preset_is_selectable_by_name() {
+        let manager = EditorKeybindingsManager::new(
+            EditorKeybindingsConfig::preset("Readline").expect("readline preset"),
+        );
+        assert!(manager.matches(Some("ctrl+t"), EditorAction::TransposeChars));
+        assert!(manager.matches(Some("ctrl+p"), EditorAction::CursorUp));
+        assert!(manager.matches(Some("ctrl+n"), EditorAction::CursorDown));
+        assert!(manager.matches(Some("alt+d"), EditorAction::DeleteWordForward));
+        assert!(!manager.matches(Some("alt+delete"), EditorAction::DeleteWordForward));
+        assert!(manager.matches(Some("enter"), EditorAction::Submit));
+
+        let default = EditorKeybindingsManager::new(
+            EditorKeybindingsConfig::preset("default").expect("default preset"),
+        );
+        assert!(default.get_keys(EditorAction::TransposeChars).is_empty());
+        assert!(EditorKeybindingsConfig::preset("vi").is_none());
+    }
+
+    #[test]
+    fn action_ids_round_trip() {
+        for action in EditorAction::ALL {
+            assert_eq!(EditorAction::from_id(action.id()), Some(*action));
+        }
+        assert_eq!(EditorAction::Submit.id(), "submit");
+        assert_eq!(EditorAction::from_id("Submit"), None);
+    }
+
+    #[test]
+    fn keymap_files_select_a_preset_and_override_actions() {
+        let config = EditorKeybindingsConfig::from_toml(
+            r#"
+# my keymap
+preset = "readline"
+
+[editor]
+submit = "ctrl+j"  # send
+newLine = ["shift+enter", "alt+enter"]
+"transposeChars" = []
+"#,
+        )
+        .expect("valid keymap");
+        let manager = EditorKeybindingsManager::new(config);
+        assert!(manager.matches(Some("ctrl+j"), EditorAction::Submit));
+        assert!(!manager.matches(Some("enter"), EditorAction::Submit));
+        assert!(manager.matches(Some("alt+enter"), EditorAction::NewLine));
+        assert!(!manager.matches(Some("ctrl+t"), EditorAction::TransposeChars));
+        assert!(manager.matches(Some("ctrl+p"), EditorAction::CursorUp));
+    }
+
+    #[test]
+    fn keymap_errors_point_at_the_offending_line() {
+        let error = |source: &str| {
+            EditorKeybindingsConfig::from_toml(source)
+                .expect_err("invalid keymap")
+                .to_string()
+        };
+        assert_eq!(
+            error("[editor]\nsubmit = \"enter\"\nsubmitt = \"ctrl+j\""),
+            "line 3: unknown action `submitt`"
+        );
+        assert_eq!(
+            error("[editor]\nsubmit = \"enter\"\nsubmit = \"ctrl+j\""),
+            "line 3: `submit` is already bound on line 2"
+        );
+        assert_eq!(
+            error("\n[editor]\nundo = \"hyper+z\""),
+            "line 3: unknown modifier `hyper` in `hyper+z`"
+        );
+        assert_eq!(
+            error("preset = \"vim\""),
+            "line 1: unknown preset `vim`; expected one of default, readline"
+        );
+    }
+
+    #[test]
+    fn conflicts_report_duplicates_within_a_context_only() {
+        let manager = EditorKeybindingsManager::new(EditorKeybindingsConfig::default());
+        assert!(manager.conflicts().is_empty());
+        assert!(
+            EditorKeybindingsManager::new(EditorKeybindingsConfig::readline())
+                .conflicts()
+                .is_empty()
+        );
+
+        // `ctrl+d` is also DeleteSession, but that lives in another context.
+        let mut config = EditorKeybindingsConfig::default();
+        config.set(EditorAction::Yank, vec!["ctrl+y", "Ctrl+D"]);
+        let manager = EditorKeybindingsManager::new(config);
+        let conflicts = manager.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].key, "ctrl+d");
+        assert_eq!(conflicts[0].kind, KeybindingConflictKind::Duplicate);
+        assert_eq!(conflicts[0].context, KeybindingContext::Editing);
+        assert_eq!(
+            conflicts[0].owners,
+            vec![
+                KeybindingOwner::Action(EditorAction::DeleteCharForward),
+                KeybindingOwner::Action(EditorAction::Yank),
+            ]
+        );
+
+        let sections = manager.help_sections();
+        let contexts: Vec<KeybindingContext> =
+            sections.iter().map(|section| section.context).collect();
+        assert_eq!(
+            contexts,
+            vec![
+                KeybindingContext::Editing,
+                KeybindingContext::Selection,
+                KeybindingContext::Tree,
+                KeybindingContext::Sessions,
+                KeybindingContext::App,
+            ]
+        );
+        assert!(sections[0]
+            .entries
+            .iter()
+            .all(|entry| entry.owner != KeybindingOwner::Action(EditorAction::TransposeChars)));
+    }
+
     #[test]
     fn handles_are_not_process_global() {
         let a = default_editor_keybindings_handle();
@@ -1,14 +1,18 @@
 //! Core interfaces and types.
 
+pub mod animation;
 pub mod autocomplete;
+pub mod capability_cache;
 pub mod component;
 pub mod cursor;
 pub mod editor_component;
+pub mod focus;
 pub mod fuzzy;
 pub mod input;
 pub mod input_event;
 pub mod keybindings;
 pub mod output;
+pub mod render_cache;
 pub mod terminal;
 pub mod terminal_image;
 pub mod text;
@@ -3,12 +3,16 @@
 //! Invariant: all terminal writes must flow through `OutputGate::flush(..)`.
 
 use crate::core::terminal::Terminal;
+use crate::core::terminal_image::NotificationProtocol;
 
 // When a frame is large, coalescing all output into a new String doubles peak
 // memory usage (payload + coalesced copy). Stream large flushes in chunks to
 // avoid this.
 const OUTPUT_GATE_STREAM_THRESHOLD_BYTES: usize = 64 * 1024;
 const OUTPUT_GATE_STREAM_CHUNK_BYTES: usize = 16 * 1024;
+// A single multi-megabyte write (an inline image, say) can stall the terminal
+// while it parses; larger payloads are split into writes of at most this size.
+const OUTPUT_GATE_MAX_WRITE_BYTES: usize = 512 * 1024;
 
 fn decimal_len(mut n: usize) -> usize {
     let mut len = 1;
@@ -27,6 +31,46 @@ pub(crate) fn osc_title_sequence(title: &str) -> String {
     seq
 }
 
+/// OSC 52 sequence that sets the system clipboard (`c` selection) to `text`.
+///
+/// Terminals that do not support OSC 52, or have it disabled, ignore it.
+pub(crate) fn osc_clipboard_sequence(text: &str) -> String {
+    let payload = crate::core::terminal_image::base64_encode(text.as_bytes());
+    let mut seq = String::with_capacity(payload.len() + 8);
+    seq.push_str("\x1b]52;c;");
+    seq.push_str(&payload);
+    seq.push('\x07');
+    seq
+}
+
+/// Desktop notification sequence for `protocol`.
+///
+/// Control characters are dropped from `title` and `body` so they cannot end
+/// the OSC early, and OSC 777 titles lose `;`, its field separator.
+pub(crate) fn notification_sequence(
+    protocol: NotificationProtocol,
+    title: &str,
+    body: &str,
+) -> String {
+    let clean = |text: &str| -> String { text.chars().filter(|ch| !ch.is_control()).collect() };
+    match protocol {
+        NotificationProtocol::Osc777 => format!(
+            "\x1b]777;notify;{};{}\x07",
+            clean(title).replace(';', ","),
+            clean(body)
+        ),
+        NotificationProtocol::Osc9 => {
+            let message = match (title.is_empty(), body.is_empty()) {
+                (_, true) => clean(title),
+                (true, false) => clean(body),
+                (false, false) => format!("{}: {}", clean(title), clean(body)),
+            };
+            format!("\x1b]9;{message}\x07")
+        }
+        NotificationProtocol::Bell => "\x07".to_string(),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TerminalCmd {
     /// Raw bytes/control sequences (UTF-8 string) to be written to the terminal.
@@ -174,6 +218,23 @@ impl OutputGate {
         }
     }
 
+    /// Writes `data` in pieces of at most `OUTPUT_GATE_MAX_WRITE_BYTES`,
+    /// split on UTF-8 boundaries.
+    fn write_bounded<T: Terminal + ?Sized>(term: &mut T, mut data: &str) {
+        while data.len() > OUTPUT_GATE_MAX_WRITE_BYTES {
+            let mut end = OUTPUT_GATE_MAX_WRITE_BYTES;
+            while !data.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (head, rest) = data.split_at(end);
+            term.write(head);
+            data = rest;
+        }
+        if !data.is_empty() {
+            term.write(data);
+        }
+    }
+
     fn flush_streaming<T: Terminal + ?Sized>(&mut self, term: &mut T) {
         let mut buffer = String::with_capacity(OUTPUT_GATE_STREAM_CHUNK_BYTES);
 
@@ -184,9 +245,7 @@ impl OutputGate {
                         term.write(&buffer);
                         buffer.clear();
                     }
-                    if !data.is_empty() {
-                        term.write(&data);
-                    }
+                    Self::write_bounded(term, &data);
                     continue;
                 }
                 TerminalCmd::BytesStatic(data) if data.len() >= OUTPUT_GATE_STREAM_CHUNK_BYTES => {
@@ -194,7 +253,7 @@ impl OutputGate {
                         term.write(&buffer);
                         buffer.clear();
                     }
-                    term.write(data);
+                    Self::write_bounded(term, data);
                     continue;
                 }
                 cmd => {
@@ -260,8 +319,29 @@ impl<T: Terminal + ?Sized> TerminalTitleExt for T {}
 
 #[cfg(test)]
 mod tests {
-    use super::{OutputGate, TerminalCmd, TerminalTitleExt};
+    use super::{notification_sequence, OutputGate, TerminalCmd, TerminalTitleExt};
     use crate::core::terminal::Terminal;
+    use crate::core::terminal_image::NotificationProtocol;
+
+    #[test]
+    fn notification_sequences_follow_protocol_and_strip_controls() {
+        assert_eq!(
+            notification_sequence(NotificationProtocol::Osc777, "Done; ok", "tests\x07 pass"),
+            "\x1b]777;notify;Done, ok;tests pass\x07"
+        );
+        assert_eq!(
+            notification_sequence(NotificationProtocol::Osc9, "Done", "tests pass"),
+            "\x1b]9;Done: tests pass\x07"
+        );
+        assert_eq!(
+            notification_sequence(NotificationProtocol::Osc9, "", "tests pass"),
+            "\x1b]9;tests pass\x07"
+        );
+        assert_eq!(
+            notification_sequence(NotificationProtocol::Bell, "Done", "tests pass"),
+            "\x07"
+        );
+    }
 
     #[derive(Default)]
     struct RecordingTerminal {
@@ -460,6 +540,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flush_splits_oversized_payloads_on_char_boundaries() {
+        let big = format!(
+            "{}é{}",
+            "x".repeat(super::OUTPUT_GATE_MAX_WRITE_BYTES - 1),
+            "y".repeat(super::OUTPUT_GATE_MAX_WRITE_BYTES)
+        );
+
+        let mut gate = OutputGate::new();
+        gate.extend([TerminalCmd::HideCursor, TerminalCmd::Bytes(big.clone())]);
+
+        let mut term = RecordingTerminal::default();
+        gate.flush(&mut term);
+
+        assert_eq!(term.output, format!("\x1b[?25l{big}"));
+        assert_eq!(term.writes.len(), 4);
+        assert_eq!(term.writes[1].len(), super::OUTPUT_GATE_MAX_WRITE_BYTES - 1);
+        assert!(term
+            .writes
+            .iter()
+            .all(|write| write.len() <= super::OUTPUT_GATE_MAX_WRITE_BYTES));
+    }
+
     #[test]
     fn cursor_cmds_encode_to_ansi_sequences() {
         let mut gate = OutputGate::new();
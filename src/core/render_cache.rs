@@ -0,0 +1,111 @@
+//! Keyed reuse of a component's last render.
+//!
+//! Components opt in through [`Component::cache_key`]. While the key and the
+//! width match the previous render, [`RenderCache::render`] returns the stored
+//! lines and cursor instead of calling `render` again, which keeps static
+//! content such as highlighted transcript history cheap to redraw.
+
+use crate::core::component::Component;
+use crate::core::cursor::CursorPos;
+
+#[derive(Debug)]
+struct CachedRender {
+    key: u64,
+    width: usize,
+    lines: Vec<String>,
+    cursor: Option<CursorPos>,
+}
+
+/// The last keyed render of one component.
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    entry: Option<CachedRender>,
+}
+
+impl RenderCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders `component` at `width`, or reuses the previous render when its
+    /// cache key is unchanged. Returns the lines and the cursor position.
+    pub fn render(
+        &mut self,
+        component: &mut dyn Component,
+        width: usize,
+    ) -> (Vec<String>, Option<CursorPos>) {
+        let key = component.cache_key();
+        if let (Some(key), Some(entry)) = (key, self.entry.as_ref()) {
+            if entry.key == key && entry.width == width {
+                return (entry.lines.clone(), entry.cursor);
+            }
+        }
+
+        let lines = component.render(width);
+        let cursor = component.cursor_pos();
+        self.entry = key.map(|key| CachedRender {
+            key,
+            width,
+            lines: lines.clone(),
+            cursor,
+        });
+        (lines, cursor)
+    }
+
+    /// Drops the stored render so the next call renders again.
+    pub fn clear(&mut self) {
+        self.entry = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenderCache;
+    use crate::core::component::Component;
+
+    struct Versioned {
+        version: Option<u64>,
+        renders: usize,
+    }
+
+    impl Component for Versioned {
+        fn render(&mut self, width: usize) -> Vec<String> {
+            self.renders += 1;
+            vec![format!("v{:?} at {width}", self.version)]
+        }
+
+        fn cache_key(&self) -> Option<u64> {
+            self.version
+        }
+    }
+
+    #[test]
+    fn reuses_render_while_key_and_width_match() {
+        let mut cache = RenderCache::new();
+        let mut component = Versioned {
+            version: Some(1),
+            renders: 0,
+        };
+
+        let (first, _) = cache.render(&mut component, 10);
+        let (second, _) = cache.render(&mut component, 10);
+        assert_eq!(first, second);
+        assert_eq!(component.renders, 1);
+
+        cache.render(&mut component, 12);
+        assert_eq!(component.renders, 2);
+
+        component.version = Some(2);
+        cache.render(&mut component, 12);
+        assert_eq!(component.renders, 3);
+
+        cache.clear();
+        cache.render(&mut component, 12);
+        assert_eq!(component.renders, 4);
+
+        component.version = None;
+        cache.render(&mut component, 12);
+        cache.render(&mut component, 12);
+        assert_eq!(component.renders, 6);
+    }
+}
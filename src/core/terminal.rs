@@ -12,6 +12,15 @@ pub trait Terminal {
     /// Stop the terminal and restore state.
     fn stop(&mut self) -> std::io::Result<()>;
 
+    /// Stop the process until the shell continues it (job control suspend).
+    ///
+    /// Called between [`Terminal::stop`] and [`Terminal::start`], so the
+    /// terminal is already restored. Returns once the process runs again.
+    /// The default does nothing, for terminals not backed by a process.
+    fn suspend(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
     /// Drain stdin before exiting to prevent key release leakage over slow connections.
     fn drain_input(&mut self, max_ms: u64, idle_ms: u64);
 
@@ -11,11 +11,25 @@ pub enum ImageProtocol {
     Iterm2,
 }
 
+/// How the terminal surfaces desktop notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationProtocol {
+    /// `OSC 777;notify;title;body` (foot, Ghostty, WezTerm, urxvt).
+    Osc777,
+    /// `OSC 9;message` (iTerm2, kitty). Carries no separate title.
+    Osc9,
+    /// No notification support; ring the bell instead.
+    Bell,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TerminalCapabilities {
     pub images: Option<ImageProtocol>,
     pub true_color: bool,
     pub hyperlinks: bool,
+    /// DEC double-width/double-height rows (`ESC # 3`..`ESC # 6`).
+    pub line_attributes: bool,
+    pub notifications: NotificationProtocol,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,12 +44,27 @@ pub struct ImageDimensions {
     pub height_px: u32,
 }
 
+/// How kitty images are attached to the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KittyPlacement {
+    /// Place the image at the cursor. The image is tied to the screen
+    /// position it was drawn at, so it has to be redrawn after scrolling.
+    #[default]
+    Direct,
+    /// Transmit a virtual placement and draw the image through Unicode
+    /// placeholder cells (`U+10EEEE`) coloured with the image id. The cells
+    /// are ordinary text, so the image scrolls into history with its line.
+    UnicodePlaceholder,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ImageRenderOptions {
     pub max_width_cells: Option<u32>,
     pub max_height_cells: Option<u32>,
     pub preserve_aspect_ratio: Option<bool>,
     pub image_id: Option<u32>,
+    /// Ignored for iTerm2.
+    pub kitty_placement: KittyPlacement,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,6 +72,10 @@ pub struct ImageRenderResult {
     pub sequence: String,
     pub rows: u32,
     pub image_id: Option<u32>,
+    /// One line of placeholder cells per row for
+    /// [`KittyPlacement::UnicodePlaceholder`]; `sequence` (the transmission)
+    /// must be written before the first of them.
+    pub placeholder_lines: Option<Vec<String>>,
 }
 
 #[derive(Debug)]
@@ -67,14 +100,37 @@ impl Default for TerminalImageState {
 
 const KITTY_PREFIX: &str = "\x1b_G";
 const ITERM2_PREFIX: &str = "\x1b]1337;File=";
+const ITERM2_MULTIPART_PREFIX: &str = "\x1b]1337;MultipartFile=";
+/// Payloads above this are sent as a multipart transfer by `render_image`.
+const ITERM2_MULTIPART_THRESHOLD_BYTES: usize = 1024 * 1024;
+const ITERM2_PART_BYTES: usize = 64 * 1024;
 const KITTY_CHUNK_SIZE: usize = 4096;
 const KITTY_ID_MAX: u32 = 0xffff_fffe;
+const KITTY_PLACEHOLDER: char = '\u{10EEEE}';
+/// Row/column diacritics from kitty's `rowcolumn-diacritics.txt`, in order:
+/// the diacritic at index `n` encodes row (or column) `n`.
+const KITTY_ROW_COLUMN_DIACRITICS: [char; 96] = [
+    '\u{0305}', '\u{030D}', '\u{030E}', '\u{0310}', '\u{0312}', '\u{033D}', '\u{033E}', '\u{033F}',
+    '\u{0346}', '\u{034A}', '\u{034B}', '\u{034C}', '\u{0350}', '\u{0351}', '\u{0352}', '\u{0357}',
+    '\u{035B}', '\u{0363}', '\u{0364}', '\u{0365}', '\u{0366}', '\u{0367}', '\u{0368}', '\u{0369}',
+    '\u{036A}', '\u{036B}', '\u{036C}', '\u{036D}', '\u{036E}', '\u{036F}', '\u{0483}', '\u{0484}',
+    '\u{0485}', '\u{0486}', '\u{0487}', '\u{0592}', '\u{0593}', '\u{0594}', '\u{0595}', '\u{0597}',
+    '\u{0598}', '\u{0599}', '\u{059C}', '\u{059D}', '\u{059E}', '\u{059F}', '\u{05A0}', '\u{05A1}',
+    '\u{05A8}', '\u{05A9}', '\u{05AB}', '\u{05AC}', '\u{05AF}', '\u{05C4}', '\u{0610}', '\u{0611}',
+    '\u{0612}', '\u{0613}', '\u{0614}', '\u{0615}', '\u{0616}', '\u{0617}', '\u{0657}', '\u{0658}',
+    '\u{0659}', '\u{065A}', '\u{065B}', '\u{065D}', '\u{065E}', '\u{06D6}', '\u{06D7}', '\u{06D8}',
+    '\u{06D9}', '\u{06DA}', '\u{06DB}', '\u{06DC}', '\u{06DF}', '\u{06E0}', '\u{06E1}', '\u{06E2}',
+    '\u{06E4}', '\u{06E7}', '\u{06E8}', '\u{06EB}', '\u{06EC}', '\u{0730}', '\u{0732}', '\u{0733}',
+    '\u{0735}', '\u{0736}', '\u{073A}', '\u{073D}', '\u{073F}', '\u{0740}', '\u{0741}', '\u{0743}',
+];
 
 #[derive(Debug, Clone, Default)]
 pub struct KittyEncodeOptions {
     pub columns: Option<u32>,
     pub rows: Option<u32>,
     pub image_id: Option<u32>,
+    /// Create a virtual placement (`U=1`) for Unicode placeholder cells.
+    pub unicode_placeholder: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -84,6 +140,10 @@ pub struct Iterm2EncodeOptions {
     pub name: Option<String>,
     pub preserve_aspect_ratio: Option<bool>,
     pub inline: Option<bool>,
+    /// Split payloads longer than this many bytes into a multipart transfer
+    /// (`MultipartFile`, `FilePart`..., `FileEnd`; iTerm2 3.5+) so no single
+    /// escape sequence carries the whole file.
+    pub part_bytes: Option<usize>,
 }
 
 pub fn get_cell_dimensions(state: &TerminalImageState) -> CellDimensions {
@@ -105,12 +165,19 @@ pub fn detect_capabilities() -> TerminalCapabilities {
     let term_program = env::var("TERM_PROGRAM").unwrap_or_default().to_lowercase();
     let term = env::var("TERM").unwrap_or_default().to_lowercase();
     let color_term = env::var("COLORTERM").unwrap_or_default().to_lowercase();
+    // Terminals known to draw DEC line attributes; others (kitty, Ghostty,
+    // iTerm2, Alacritty, xterm.js) ignore them or render them incorrectly.
+    let line_attributes = env::var("XTERM_VERSION").is_ok()
+        || env::var("KONSOLE_VERSION").is_ok()
+        || env::var("WT_SESSION").is_ok();
 
     if env::var("KITTY_WINDOW_ID").is_ok() || term_program == "kitty" {
         return TerminalCapabilities {
             images: Some(ImageProtocol::Kitty),
             true_color: true,
             hyperlinks: true,
+            line_attributes: false,
+            notifications: NotificationProtocol::Osc9,
         };
     }
 
@@ -122,6 +189,8 @@ pub fn detect_capabilities() -> TerminalCapabilities {
             images: Some(ImageProtocol::Kitty),
             true_color: true,
             hyperlinks: true,
+            line_attributes: false,
+            notifications: NotificationProtocol::Osc777,
         };
     }
 
@@ -130,6 +199,8 @@ pub fn detect_capabilities() -> TerminalCapabilities {
             images: Some(ImageProtocol::Kitty),
             true_color: true,
             hyperlinks: true,
+            line_attributes: true,
+            notifications: NotificationProtocol::Osc777,
         };
     }
 
@@ -138,6 +209,8 @@ pub fn detect_capabilities() -> TerminalCapabilities {
             images: Some(ImageProtocol::Iterm2),
             true_color: true,
             hyperlinks: true,
+            line_attributes: false,
+            notifications: NotificationProtocol::Osc9,
         };
     }
 
@@ -146,6 +219,8 @@ pub fn detect_capabilities() -> TerminalCapabilities {
             images: None,
             true_color: true,
             hyperlinks: true,
+            line_attributes: false,
+            notifications: NotificationProtocol::Bell,
         };
     }
 
@@ -154,14 +229,23 @@ pub fn detect_capabilities() -> TerminalCapabilities {
             images: None,
             true_color: true,
             hyperlinks: true,
+            line_attributes: false,
+            notifications: NotificationProtocol::Bell,
         };
     }
 
     let true_color = color_term == "truecolor" || color_term == "24bit";
+    let notifications = if term.starts_with("foot") || term.contains("rxvt") {
+        NotificationProtocol::Osc777
+    } else {
+        NotificationProtocol::Bell
+    };
     TerminalCapabilities {
         images: None,
         true_color,
         hyperlinks: true,
+        line_attributes,
+        notifications,
     }
 }
 
@@ -178,6 +262,17 @@ pub fn get_capabilities(state: &TerminalImageState) -> TerminalCapabilities {
     detected
 }
 
+/// Seeds the cached capabilities, e.g. from a persisted
+/// [`CapabilityCache`](crate::core::capability_cache::CapabilityCache) entry,
+/// so detection does not run.
+pub fn set_capabilities(state: &TerminalImageState, capabilities: TerminalCapabilities) {
+    let mut cached = state
+        .capabilities
+        .lock()
+        .expect("capabilities lock poisoned");
+    *cached = Some(capabilities);
+}
+
 pub fn reset_capabilities_cache(state: &TerminalImageState) {
     let mut cached = state
         .capabilities
@@ -186,11 +281,48 @@ pub fn reset_capabilities_cache(state: &TerminalImageState) {
     *cached = None;
 }
 
+/// Whether `line` carries an image drawn at the cursor. Lines that only
+/// transmit a kitty virtual placement (`U=1`) are ordinary text: the image is
+/// drawn by their placeholder cells.
 pub fn is_image_line(line: &str) -> bool {
-    if line.starts_with(KITTY_PREFIX) || line.starts_with(ITERM2_PREFIX) {
+    if line.contains(ITERM2_PREFIX) || line.contains(ITERM2_MULTIPART_PREFIX) {
         return true;
     }
-    line.contains(KITTY_PREFIX) || line.contains(ITERM2_PREFIX)
+    let Some(start) = line.find(KITTY_PREFIX) else {
+        return false;
+    };
+    let params = &line[start + KITTY_PREFIX.len()..];
+    let params = params.split(';').next().unwrap_or_default();
+    !params.split(',').any(|param| param == "U=1")
+}
+
+/// Lines of kitty Unicode placeholder cells showing the virtual placement
+/// of `image_id`, `columns` wide and `rows` tall.
+///
+/// The id is carried in the 24-bit foreground colour, so it must fit in 24
+/// bits. The first cell of each row names its row and column with
+/// diacritics; the rest of the row continues from it. Rows beyond the
+/// diacritic table are not addressable and are dropped.
+pub fn kitty_placeholder_lines(image_id: u32, columns: u32, rows: u32) -> Vec<String> {
+    let color = format!(
+        "\x1b[38;2;{};{};{}m",
+        (image_id >> 16) & 0xff,
+        (image_id >> 8) & 0xff,
+        image_id & 0xff
+    );
+    let rest = KITTY_PLACEHOLDER
+        .to_string()
+        .repeat(columns.saturating_sub(1) as usize);
+    KITTY_ROW_COLUMN_DIACRITICS
+        .iter()
+        .take(rows as usize)
+        .map(|row| {
+            format!(
+                "{color}{KITTY_PLACEHOLDER}{row}{column}{rest}\x1b[39m",
+                column = KITTY_ROW_COLUMN_DIACRITICS[0]
+            )
+        })
+        .collect()
 }
 
 pub fn allocate_image_id(state: &TerminalImageState) -> u32 {
@@ -216,6 +348,9 @@ pub fn encode_kitty(base64_data: &str, options: &KittyEncodeOptions) -> String {
     if let Some(image_id) = options.image_id {
         params.push(format!("i={image_id}"));
     }
+    if options.unicode_placeholder {
+        params.push("U=1".to_string());
+    }
 
     if base64_data.len() <= KITTY_CHUNK_SIZE {
         return format!(
@@ -289,12 +424,36 @@ pub fn encode_iterm2(base64_data: &str, options: &Iterm2EncodeOptions) -> String
         params.push("preserveAspectRatio=0".to_string());
     }
 
-    format!(
-        "{prefix}{params}:{data}\x07",
-        prefix = ITERM2_PREFIX,
-        params = params.join(";"),
-        data = base64_data
-    )
+    let params = params.join(";");
+    match options.part_bytes {
+        Some(part_bytes) if base64_data.len() > part_bytes => {
+            encode_iterm2_multipart(base64_data, &params, part_bytes)
+        }
+        _ => format!(
+            "{prefix}{params}:{data}\x07",
+            prefix = ITERM2_PREFIX,
+            data = base64_data
+        ),
+    }
+}
+
+fn encode_iterm2_multipart(base64_data: &str, params: &str, part_bytes: usize) -> String {
+    // Whole base64 quanta per part, so each part decodes on its own.
+    let part_bytes = (part_bytes / 4).max(1) * 4;
+    let parts = base64_data.len().div_ceil(part_bytes);
+    let mut out = String::with_capacity(base64_data.len() + parts * 20 + params.len() + 48);
+    out.push_str(ITERM2_MULTIPART_PREFIX);
+    out.push_str(params);
+    out.push('\x07');
+    // base64 is ASCII, so byte offsets are char boundaries.
+    for start in (0..base64_data.len()).step_by(part_bytes) {
+        let end = (start + part_bytes).min(base64_data.len());
+        out.push_str("\x1b]1337;FilePart=");
+        out.push_str(&base64_data[start..end]);
+        out.push('\x07');
+    }
+    out.push_str("\x1b]1337;FileEnd\x07");
+    out
 }
 
 pub fn calculate_image_rows(
@@ -468,6 +627,28 @@ pub fn render_image(
     );
 
     match images {
+        ImageProtocol::Kitty if options.kitty_placement == KittyPlacement::UnicodePlaceholder => {
+            // Placeholder cells carry the id as a 24-bit colour.
+            let image_id =
+                options.image_id.unwrap_or_else(|| allocate_image_id(state)) & 0x00ff_ffff;
+            let image_id = image_id.max(1);
+            let rows = rows.min(KITTY_ROW_COLUMN_DIACRITICS.len() as u32);
+            let sequence = encode_kitty(
+                base64_data,
+                &KittyEncodeOptions {
+                    columns: Some(width_cells),
+                    rows: Some(rows),
+                    image_id: Some(image_id),
+                    unicode_placeholder: true,
+                },
+            );
+            Some(ImageRenderResult {
+                sequence,
+                rows,
+                image_id: Some(image_id),
+                placeholder_lines: Some(kitty_placeholder_lines(image_id, width_cells, rows)),
+            })
+        }
         ImageProtocol::Kitty => {
             let sequence = encode_kitty(
                 base64_data,
@@ -475,12 +656,14 @@ pub fn render_image(
                     columns: Some(width_cells),
                     rows: Some(rows),
                     image_id: options.image_id,
+                    unicode_placeholder: false,
                 },
             );
             Some(ImageRenderResult {
                 sequence,
                 rows,
                 image_id: options.image_id,
+                placeholder_lines: None,
             })
         }
         ImageProtocol::Iterm2 => {
@@ -492,18 +675,21 @@ pub fn render_image(
                     name: None,
                     preserve_aspect_ratio: Some(options.preserve_aspect_ratio.unwrap_or(true)),
                     inline: None,
+                    part_bytes: (base64_data.len() > ITERM2_MULTIPART_THRESHOLD_BYTES)
+                        .then_some(ITERM2_PART_BYTES),
                 },
             );
             Some(ImageRenderResult {
                 sequence,
                 rows,
                 image_id: None,
+                placeholder_lines: None,
             })
         }
     }
 }
 
-fn fit_image_within_cells(
+pub(crate) fn fit_image_within_cells(
     image_dimensions: ImageDimensions,
     cell_dimensions: CellDimensions,
     max_width_cells: u32,
@@ -564,7 +750,7 @@ pub fn image_fallback(
     format!("[Image: {}]", parts.join(" "))
 }
 
-fn base64_encode(data: &[u8]) -> String {
+pub(crate) fn base64_encode(data: &[u8]) -> String {
     const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
     if data.is_empty() {
         return String::new();
@@ -667,9 +853,9 @@ mod tests {
         allocate_image_id, delete_all_kitty_images, delete_kitty_image, encode_iterm2,
         encode_kitty, get_cell_dimensions, get_gif_dimensions, get_image_dimensions,
         get_jpeg_dimensions, get_png_dimensions, get_webp_dimensions, image_fallback,
-        is_image_line, render_image, reset_capabilities_cache, set_cell_dimensions, CellDimensions,
-        ImageDimensions, ImageRenderOptions, Iterm2EncodeOptions, KittyEncodeOptions,
-        TerminalImageState,
+        is_image_line, kitty_placeholder_lines, render_image, reset_capabilities_cache,
+        set_cell_dimensions, CellDimensions, ImageDimensions, ImageRenderOptions,
+        Iterm2EncodeOptions, KittyEncodeOptions, KittyPlacement, TerminalImageState,
     };
     use std::env;
     use std::sync::{Mutex, OnceLock};
@@ -766,6 +952,7 @@ mod tests {
             columns: Some(2),
             rows: Some(3),
             image_id: Some(7),
+            unicode_placeholder: false,
         };
         let encoded = encode_kitty("AAAA", &options);
         assert_eq!(encoded, "\x1b_Ga=T,f=100,q=2,c=2,r=3,i=7;AAAA\x1b\\");
@@ -793,6 +980,7 @@ mod tests {
             name: Some("foo.png".to_string()),
             preserve_aspect_ratio: Some(false),
             inline: Some(false),
+            part_bytes: None,
         };
         let encoded = encode_iterm2("AAAA", &options);
         assert_eq!(
@@ -801,6 +989,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn encode_iterm2_splits_large_payloads_into_multipart_transfer() {
+        let options = Iterm2EncodeOptions {
+            width: Some("10".to_string()),
+            part_bytes: Some(6),
+            ..Iterm2EncodeOptions::default()
+        };
+        assert_eq!(
+            encode_iterm2("AAAA", &options),
+            "\x1b]1337;File=inline=1;width=10:AAAA\x07"
+        );
+
+        let encoded = encode_iterm2("AAAABBBBCC==", &options);
+        assert_eq!(
+            encoded,
+            "\x1b]1337;MultipartFile=inline=1;width=10\x07\
+             \x1b]1337;FilePart=AAAA\x07\
+             \x1b]1337;FilePart=BBBB\x07\
+             \x1b]1337;FilePart=CC==\x07\
+             \x1b]1337;FileEnd\x07"
+        );
+        assert!(is_image_line(&encoded));
+    }
+
     #[test]
     fn png_dimensions_parsed() {
         let mut buffer = vec![0u8; 24];
@@ -985,6 +1197,7 @@ mod tests {
             max_height_cells: None,
             preserve_aspect_ratio: None,
             image_id: Some(9),
+            kitty_placement: KittyPlacement::Direct,
         };
         let result = render_image(&state, "AAAA", dims, &options).expect("kitty render");
         assert!(result.sequence.starts_with("\x1b_G"));
@@ -1025,6 +1238,7 @@ mod tests {
             max_height_cells: Some(3),
             preserve_aspect_ratio: None,
             image_id: Some(9),
+            kitty_placement: KittyPlacement::Direct,
         };
         let result = render_image(&state, "AAAA", dims, &options).expect("kitty render");
         assert!(result.rows <= 3);
@@ -1056,6 +1270,7 @@ mod tests {
             max_height_cells: None,
             preserve_aspect_ratio: Some(false),
             image_id: None,
+            kitty_placement: KittyPlacement::Direct,
         };
         let result = render_image(&state, "AAAA", dims, &options).expect("iterm render");
         assert!(result.sequence.starts_with("\x1b]1337;File="));
@@ -1069,4 +1284,57 @@ mod tests {
 
         reset_capabilities_cache(&state);
     }
+
+    #[test]
+    fn render_image_unicode_placeholder_mode_emits_placeholder_cells() {
+        let _guard = env_test_lock().lock().expect("test lock poisoned");
+        let _term = set_env_guard("TERM", Some("xterm-256color"));
+        let _term_program = set_env_guard("TERM_PROGRAM", Some("kitty"));
+        let _kitty = set_env_guard("KITTY_WINDOW_ID", Some("1"));
+        let _wezterm = set_env_guard("WEZTERM_PANE", None);
+        let _iterm = set_env_guard("ITERM_SESSION_ID", None);
+        let _ghostty = set_env_guard("GHOSTTY_RESOURCES_DIR", None);
+        let state = TerminalImageState::default();
+        reset_capabilities_cache(&state);
+
+        let original = get_cell_dimensions(&state);
+        set_cell_dimensions(
+            &state,
+            CellDimensions {
+                width_px: 10,
+                height_px: 10,
+            },
+        );
+
+        let dims = ImageDimensions {
+            width_px: 30,
+            height_px: 20,
+        };
+        let options = ImageRenderOptions {
+            max_width_cells: Some(3),
+            max_height_cells: None,
+            preserve_aspect_ratio: None,
+            image_id: Some(0x0102_0304),
+            kitty_placement: KittyPlacement::UnicodePlaceholder,
+        };
+        let result = render_image(&state, "AAAA", dims, &options).expect("kitty render");
+        assert_eq!(result.image_id, Some(0x02_0304));
+        assert_eq!(
+            result.sequence,
+            "\x1b_Ga=T,f=100,q=2,c=3,r=2,i=131844,U=1;AAAA\x1b\\"
+        );
+        assert!(!is_image_line(&result.sequence));
+        assert!(is_image_line("\x1b_Ga=T,f=100,q=2;AAAA\x1b\\"));
+
+        let lines = result.placeholder_lines.expect("placeholder lines");
+        assert_eq!(lines, kitty_placeholder_lines(0x02_0304, 3, 2));
+        assert_eq!(
+            lines[0],
+            "\x1b[38;2;2;3;4m\u{10EEEE}\u{0305}\u{0305}\u{10EEEE}\u{10EEEE}\x1b[39m"
+        );
+        assert!(lines[1].contains("\u{10EEEE}\u{030D}\u{0305}"));
+
+        set_cell_dimensions(&state, original);
+        reset_capabilities_cache(&state);
+    }
 }
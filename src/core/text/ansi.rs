@@ -1,4 +1,8 @@
 //! ANSI parsing and style tracking.
+//!
+//! [`sanitize_ansi`] neutralizes untrusted text (model output, tool output)
+//! before it is rendered: SGR styling survives, every other escape sequence
+//! and control character is removed.
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AnsiCodeKind {
@@ -7,6 +11,9 @@ pub enum AnsiCodeKind {
     Apc,
     Dcs,
     Ss3,
+    /// DEC line attributes (`ESC # 3` through `ESC # 6`): double-height and
+    /// double-width rows.
+    LineAttribute,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -31,6 +38,7 @@ pub fn extract_ansi_code(input: &str, pos: usize) -> Option<AnsiCode> {
         b'_' => extract_apc(input, pos),
         b'P' => extract_dcs(input, pos),
         b'O' => extract_ss3(input, pos),
+        b'#' => extract_line_attribute(input, pos),
         _ => None,
     }
 }
@@ -67,7 +75,7 @@ fn extract_dcs(input: &str, pos: usize) -> Option<AnsiCode> {
 
 fn extract_ss3(input: &str, pos: usize) -> Option<AnsiCode> {
     let bytes = input.as_bytes();
-    if pos + 2 >= bytes.len() {
+    if pos + 2 >= bytes.len() || !bytes[pos + 2].is_ascii() {
         return None;
     }
     let end = pos + 3;
@@ -78,6 +86,19 @@ fn extract_ss3(input: &str, pos: usize) -> Option<AnsiCode> {
     })
 }
 
+fn extract_line_attribute(input: &str, pos: usize) -> Option<AnsiCode> {
+    let bytes = input.as_bytes();
+    if pos + 2 >= bytes.len() || !(b'3'..=b'6').contains(&bytes[pos + 2]) {
+        return None;
+    }
+    let end = pos + 3;
+    Some(AnsiCode {
+        code: input[pos..end].to_string(),
+        length: end - pos,
+        kind: AnsiCodeKind::LineAttribute,
+    })
+}
+
 fn extract_string_terminated(input: &str, pos: usize, kind: AnsiCodeKind) -> Option<AnsiCode> {
     let bytes = input.as_bytes();
     let mut idx = pos + 2;
@@ -103,6 +124,61 @@ fn extract_string_terminated(input: &str, pos: usize, kind: AnsiCodeKind) -> Opt
     None
 }
 
+/// Strips everything from `input` that could do more than style text.
+///
+/// Kept: SGR sequences (`ESC [ … m` with numeric parameters), `\n` and `\t`.
+/// Removed: other CSI sequences (cursor movement, erasing, device and mode
+/// queries), OSC (titles, hyperlinks, clipboard), DCS, APC, SS3 and other
+/// escapes, C0 controls such as BEL and backspace, DEL and C1 controls.
+/// `\r\n` becomes `\n`; a lone `\r` is dropped so it cannot overwrite the
+/// line. An unterminated sequence drops the rest of the input.
+pub fn sanitize_ansi(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut output = String::with_capacity(input.len());
+    let mut pos = 0;
+    while pos < input.len() {
+        if bytes[pos] == 0x1b {
+            match extract_ansi_code(input, pos) {
+                Some(code) => {
+                    if code.kind == AnsiCodeKind::Csi && is_sgr(&code.code) {
+                        output.push_str(&code.code);
+                    }
+                    pos += code.length;
+                }
+                None => match bytes.get(pos + 1) {
+                    // SS3 followed by a non-ASCII character is not a sequence.
+                    Some(b'O') if pos + 2 < bytes.len() => pos += 1,
+                    Some(b'[' | b']' | b'_' | b'P' | b'O') | None => break,
+                    Some(next) if (0x20..0x7f).contains(next) => pos += 2,
+                    Some(_) => pos += 1,
+                },
+            }
+            continue;
+        }
+
+        let ch = input[pos..].chars().next().expect("char boundary");
+        pos += ch.len_utf8();
+        match ch {
+            '\n' | '\t' => output.push(ch),
+            '\u{0}'..='\u{1f}' | '\u{7f}'..='\u{9f}' => {}
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
+/// `ESC [` numeric parameters `m`; private markers and intermediates are
+/// not SGR.
+fn is_sgr(code: &str) -> bool {
+    code.strip_prefix("\x1b[")
+        .and_then(|rest| rest.strip_suffix('m'))
+        .is_some_and(|params| {
+            params
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || byte == b';' || byte == b':')
+        })
+}
+
 #[derive(Debug, Default)]
 pub struct AnsiCodeTracker {
     bold: bool,
@@ -265,3 +341,32 @@ impl AnsiCodeTracker {
         self.bg_color = None;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize_ansi;
+
+    #[test]
+    fn sanitizer_keeps_sgr_and_drops_everything_else() {
+        assert_eq!(
+            sanitize_ansi("\x1b[1;31mred\x1b[0m \x1b[38;5;208mok\x1b[39m"),
+            "\x1b[1;31mred\x1b[0m \x1b[38;5;208mok\x1b[39m"
+        );
+        assert_eq!(sanitize_ansi("a\x1b[2J\x1b[Hb\x1b[6nc\x1b[?1049hd"), "abcd");
+        assert_eq!(
+            sanitize_ansi("\x1b]0;pwned\x07title\x1b]8;;http://x\x1b\\link\x1b]8;;\x1b\\"),
+            "titlelink"
+        );
+        assert_eq!(sanitize_ansi("\x1bPq#0\x1b\\x\x1bcy\x1b7z"), "xyz");
+        assert_eq!(
+            sanitize_ansi("a\r\nb\rc\x08\x07\u{9b}2Jd\te"),
+            "a\nbc2Jd\te"
+        );
+        assert_eq!(sanitize_ansi("ok\x1b]0;unterminated"), "ok");
+        assert_eq!(sanitize_ansi("ok\x1b"), "ok");
+        assert_eq!(sanitize_ansi("ok\x1bOé"), "okOé");
+        assert_eq!(sanitize_ansi("ok\x1bO"), "ok");
+        assert_eq!(sanitize_ansi("\x1bOPok"), "ok");
+        assert_eq!(sanitize_ansi("héllo ✓"), "héllo ✓");
+    }
+}
@@ -0,0 +1,96 @@
+//! Extended grapheme cluster boundaries.
+//!
+//! Cursor movement, deletion and length limits work on user-perceived
+//! characters: an emoji ZWJ sequence, a base letter with combining marks or
+//! a regional-indicator flag is one step, never a single `char`. Offsets are
+//! byte offsets into the text.
+
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
+
+/// Start of the grapheme cluster containing `offset` (or `text.len()` past
+/// the end).
+pub fn floor_grapheme_boundary(text: &str, offset: usize) -> usize {
+    if offset >= text.len() {
+        return text.len();
+    }
+    let mut offset = offset;
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let mut cursor = GraphemeCursor::new(offset, text.len(), true);
+    match cursor.is_boundary(text, 0) {
+        Ok(true) => offset,
+        _ => cursor.prev_boundary(text, 0).ok().flatten().unwrap_or(0),
+    }
+}
+
+/// Boundary before the cluster that ends at (or contains) `offset`.
+pub fn prev_grapheme_boundary(text: &str, offset: usize) -> usize {
+    let floor = floor_grapheme_boundary(text, offset);
+    if floor < offset.min(text.len()) {
+        return floor;
+    }
+    let mut cursor = GraphemeCursor::new(floor, text.len(), true);
+    cursor.prev_boundary(text, 0).ok().flatten().unwrap_or(0)
+}
+
+/// Boundary after the cluster that starts at (or contains) `offset`.
+pub fn next_grapheme_boundary(text: &str, offset: usize) -> usize {
+    let floor = floor_grapheme_boundary(text, offset);
+    let mut cursor = GraphemeCursor::new(floor, text.len(), true);
+    cursor
+        .next_boundary(text, 0)
+        .ok()
+        .flatten()
+        .unwrap_or(text.len())
+}
+
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// The first `count` grapheme clusters of `text`.
+pub fn take_graphemes(text: &str, count: usize) -> &str {
+    match text.grapheme_indices(true).nth(count) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        floor_grapheme_boundary, grapheme_count, next_grapheme_boundary, prev_grapheme_boundary,
+        take_graphemes,
+    };
+
+    #[test]
+    fn boundaries_step_over_whole_clusters() {
+        // Family emoji (ZWJ sequence), "e" + combining acute, a flag.
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let text = format!("a{family}e\u{301}🇳🇿b");
+        let after_family = 1 + family.len();
+        let after_accent = after_family + "e\u{301}".len();
+        let after_flag = after_accent + "🇳🇿".len();
+
+        assert_eq!(next_grapheme_boundary(&text, 1), after_family);
+        assert_eq!(next_grapheme_boundary(&text, after_family), after_accent);
+        assert_eq!(prev_grapheme_boundary(&text, after_flag), after_accent);
+        assert_eq!(prev_grapheme_boundary(&text, after_family), 1);
+        assert_eq!(next_grapheme_boundary(&text, text.len()), text.len());
+        assert_eq!(prev_grapheme_boundary(&text, 0), 0);
+
+        // Offsets inside a cluster (even inside a char) snap to its start.
+        assert_eq!(floor_grapheme_boundary(&text, 3), 1);
+        assert_eq!(
+            floor_grapheme_boundary(&text, after_accent + 4),
+            after_accent
+        );
+        assert_eq!(prev_grapheme_boundary(&text, after_family - 2), 1);
+        assert_eq!(next_grapheme_boundary(&text, after_accent + 4), after_flag);
+
+        assert_eq!(grapheme_count(&text), 5);
+        assert_eq!(take_graphemes(&text, 2), format!("a{family}"));
+        assert_eq!(take_graphemes(&text, 9), text);
+    }
+}
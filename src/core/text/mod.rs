@@ -1,9 +1,12 @@
-//! Text helpers (ANSI parsing, width calculations, slicing/wrapping, truncation).
+//! Text helpers (ANSI parsing, grapheme boundaries, width calculations, slicing/wrapping,
+//! truncation, compact path display).
 //!
 //! These helpers are pure (string in/string out) and live under `core` so widgets can depend on
 //! them without importing anything from the render layer.
 
 pub mod ansi;
+pub mod grapheme;
+pub mod paths;
 pub mod slice;
 pub mod utils;
 pub mod width;
@@ -0,0 +1,201 @@
+//! Compact path display.
+//!
+//! [`PathDisplay`] shortens paths for rendering: paths inside the workspace
+//! root become relative to it, paths inside the home directory start with
+//! `~`, and [`PathDisplay::display`] truncates what is left in the middle so
+//! the file name stays visible. Nothing here touches the filesystem or the
+//! environment; hosts pass the roots in.
+
+use std::path::{Component, Path, PathBuf};
+
+use super::utils::grapheme_segments;
+use super::width::visible_width;
+
+const ELLIPSIS: &str = "…";
+
+/// Roots used to shorten displayed paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathDisplay {
+    workspace_root: Option<PathBuf>,
+    home: Option<PathBuf>,
+}
+
+impl PathDisplay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Paths inside `root` are shown relative to it, and `root` itself as
+    /// `.`. An empty root is ignored.
+    pub fn with_workspace_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.workspace_root = non_empty(root.into());
+        self
+    }
+
+    /// Paths inside `home` outside the workspace are shown as `~/…`. An
+    /// empty home is ignored.
+    pub fn with_home(mut self, home: impl Into<PathBuf>) -> Self {
+        self.home = non_empty(home.into());
+        self
+    }
+
+    pub fn workspace_root(&self) -> Option<&Path> {
+        self.workspace_root.as_deref()
+    }
+
+    pub fn home(&self) -> Option<&Path> {
+        self.home.as_deref()
+    }
+
+    /// `path` relative to the workspace root or abbreviated with `~`.
+    /// Relative paths and paths outside both roots are returned as given.
+    pub fn abbreviate(&self, path: &str) -> String {
+        let candidate = Path::new(path);
+        if !candidate.is_absolute() {
+            return path.to_string();
+        }
+        if let Some(root) = self.workspace_root.as_deref() {
+            if let Some(relative) = relative_to(candidate, root) {
+                return if relative.is_empty() {
+                    ".".to_string()
+                } else {
+                    relative
+                };
+            }
+        }
+        if let Some(home) = self.home.as_deref() {
+            if let Some(relative) = relative_to(candidate, home) {
+                return if relative.is_empty() {
+                    "~".to_string()
+                } else {
+                    format!("~/{relative}")
+                };
+            }
+        }
+        path.to_string()
+    }
+
+    /// [`abbreviate`](Self::abbreviate)d `path`, middle-truncated to at most
+    /// `max_width` columns.
+    pub fn display(&self, path: &str, max_width: usize) -> String {
+        truncate_path_middle(&self.abbreviate(path), max_width)
+    }
+}
+
+fn non_empty(path: PathBuf) -> Option<PathBuf> {
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// `path` below `root` joined with `/`, or `None` when it is not inside
+/// `root`. Matches whole components, so `/work2` is not inside `/work`.
+fn relative_to(path: &Path, root: &Path) -> Option<String> {
+    let rest = path.strip_prefix(root).ok()?;
+    let parts = rest
+        .components()
+        .map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+/// Shortens `path` to at most `max_width` columns by replacing its middle
+/// with `…`. The tail keeps the whole file name when it fits in all but one
+/// column; otherwise head and tail split the width evenly.
+pub fn truncate_path_middle(path: &str, max_width: usize) -> String {
+    if visible_width(path) <= max_width {
+        return path.to_string();
+    }
+    let ellipsis_width = visible_width(ELLIPSIS);
+    if max_width <= ellipsis_width {
+        return ELLIPSIS.chars().take(max_width).collect();
+    }
+
+    let target = max_width - ellipsis_width;
+    let name = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path);
+    let name_width = visible_width(name) + usize::from(name.len() < path.len());
+    let tail_width = target.div_ceil(2).max(name_width.min(target - 1));
+    let head_width = target - tail_width;
+
+    let graphemes = grapheme_segments(path).collect::<Vec<_>>();
+    let mut head = String::new();
+    let mut used = 0;
+    for grapheme in &graphemes {
+        let width = visible_width(grapheme);
+        if used + width > head_width {
+            break;
+        }
+        head.push_str(grapheme);
+        used += width;
+    }
+
+    let mut tail = Vec::new();
+    let mut used = 0;
+    for grapheme in graphemes.iter().rev() {
+        let width = visible_width(grapheme);
+        if used + width > tail_width {
+            break;
+        }
+        tail.push(*grapheme);
+        used += width;
+    }
+    tail.reverse();
+
+    format!("{head}{ELLIPSIS}{}", tail.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate_path_middle, PathDisplay};
+    use crate::core::text::width::visible_width;
+
+    #[test]
+    fn paths_are_relative_to_the_workspace_then_abbreviated_under_home() {
+        let paths = PathDisplay::new()
+            .with_workspace_root("/home/dev/project")
+            .with_home("/home/dev");
+        assert_eq!(
+            paths.abbreviate("/home/dev/project/src/lib.rs"),
+            "src/lib.rs"
+        );
+        assert_eq!(paths.abbreviate("/home/dev/project"), ".");
+        assert_eq!(
+            paths.abbreviate("/home/dev/project2/a.rs"),
+            "~/project2/a.rs"
+        );
+        assert_eq!(paths.abbreviate("/home/dev"), "~");
+        assert_eq!(paths.abbreviate("/etc/hosts"), "/etc/hosts");
+        assert_eq!(paths.abbreviate("src/main.rs"), "src/main.rs");
+
+        let unrooted = PathDisplay::new().with_workspace_root("").with_home("");
+        assert_eq!(unrooted.workspace_root(), None);
+        assert_eq!(unrooted.abbreviate("/etc/hosts"), "/etc/hosts");
+    }
+
+    #[test]
+    fn middle_truncation_keeps_the_file_name_and_fits_the_width() {
+        assert_eq!(truncate_path_middle("src/lib.rs", 20), "src/lib.rs");
+        let long = "crates/coding_agent/src/runtime/session.rs";
+        let truncated = truncate_path_middle(long, 24);
+        assert_eq!(truncated, "crates/codi…e/session.rs");
+        assert_eq!(visible_width(&truncated), 24);
+        assert_eq!(truncate_path_middle(long, 12), "c…session.rs");
+        assert_eq!(
+            truncate_path_middle("/a/very_long_file_name.rs", 10),
+            "/…_name.rs"
+        );
+        assert_eq!(truncate_path_middle(long, 1), "…");
+        assert_eq!(truncate_path_middle(long, 0), "");
+
+        let paths = PathDisplay::new().with_home("/Users/dev");
+        assert_eq!(
+            paths.display("/Users/dev/work/tape_tui/src/core/text/paths.rs", 20),
+            "~/work/ta…t/paths.rs"
+        );
+    }
+}
@@ -40,29 +40,38 @@ pub use crate::core::autocomplete::{
 
 /// Built-in UI components.
 pub use crate::widgets::{
-    highlight_markdown_code_ansi, prewarm_markdown_highlighting, Box, CancellableLoader, Container,
-    DefaultTextStyle, Editor, EditorHeightMode, EditorOptions, EditorPasteMode, EditorTheme, Image,
-    ImageOptions, ImageTheme, Input, Loader, Markdown, MarkdownTheme, SelectItem, SelectList,
-    SelectListTheme, SettingItem, SettingsList, SettingsListTheme, Spacer, Text, TruncatedText,
+    highlight_markdown_code_ansi, prewarm_markdown_highlighting, AutocompleteDropdown, Box,
+    BoxBorderSides, BoxBorderStyle, BoxTitleAlign, CancellableLoader, CodeCell, CodeCellStatus,
+    CodeCellStyleFn, CodeCellTheme, Container, ContentWidth, DefaultTextStyle, Divider,
+    DividerAlign, DividerStyleFn, DividerTheme, Editor, EditorCounts, EditorHeightMode,
+    EditorOptions, EditorPasteMode, EditorTheme, Image, ImageOptions, ImageTheme, Input,
+    InputStyleFn, InputTheme, InputValidation, InputValidator, Loader, LoaderOptions, Markdown,
+    MarkdownTheme, SelectItem, SelectItemProvider, SelectList, SelectListTheme, SettingChangeFn,
+    SettingItem, SettingKind, SettingsList, SettingsListTheme, Spacer, SpinnerStyle, Text,
+    TreeChildLoader, TreeNode, TreeView, TreeViewTheme, TruncatedText,
 };
 
 /// Editor component behavior contract.
 pub use crate::core::editor_component::EditorComponent;
 
 /// Fuzzy matching helpers.
-pub use crate::core::fuzzy::{fuzzy_filter, fuzzy_match, FuzzyMatch};
+pub use crate::core::fuzzy::{
+    fuzzy_filter, fuzzy_filter_top_k, fuzzy_match, FuzzyHit, FuzzyMatch, FuzzyMatcher,
+};
 
 /// Keybinding configuration and default mappings.
 pub use crate::core::keybindings::{
     default_editor_keybindings_handle, EditorAction, EditorKeybindingsConfig,
-    EditorKeybindingsHandle, EditorKeybindingsManager, KeyId, DEFAULT_EDITOR_KEYBINDINGS,
+    EditorKeybindingsHandle, EditorKeybindingsManager, KeyId, KeybindingConflict,
+    KeybindingConflictKind, KeybindingContext, KeybindingHelpEntry, KeybindingHelpSection,
+    KeybindingOwner, KeymapParseError, DEFAULT_EDITOR_KEYBINDINGS,
 };
 
 /// Keyboard input parsing and matching helpers.
 pub use crate::core::input::{
-    is_key_release, is_key_repeat, matches_key, parse_key, Key, KeyEventType,
+    is_key_release, is_key_repeat, matches_key, parse_key, Key, KeyEventType, KeyEventTypes,
 };
-pub use crate::core::input_event::InputEvent;
+pub use crate::core::input_event::{CompositionPhase, InputEvent};
 
 /// Input buffering types for chunked terminal streams.
 pub use crate::platform::stdin_buffer::{StdinBuffer, StdinBufferEventMap, StdinBufferOptions};
@@ -77,26 +86,42 @@ pub use crate::core::terminal_image::{
     allocate_image_id, calculate_image_rows, delete_all_kitty_images, delete_kitty_image,
     detect_capabilities, encode_iterm2, encode_kitty, get_capabilities, get_cell_dimensions,
     get_gif_dimensions, get_image_dimensions, get_jpeg_dimensions, get_png_dimensions,
-    get_webp_dimensions, image_fallback, render_image, reset_capabilities_cache,
-    set_cell_dimensions, CellDimensions, ImageDimensions, ImageProtocol, ImageRenderOptions,
+    get_webp_dimensions, image_fallback, kitty_placeholder_lines, render_image,
+    reset_capabilities_cache, set_capabilities, set_cell_dimensions, CellDimensions,
+    ImageDimensions, ImageProtocol, ImageRenderOptions, KittyPlacement, NotificationProtocol,
     TerminalCapabilities, TerminalImageState,
 };
 
+/// Shared time source for spinners and other animations.
+pub use crate::core::animation::AnimationClock;
+/// Terminal capabilities persisted across runs.
+pub use crate::core::capability_cache::{CachedCapabilities, CapabilityCache};
 /// Runtime component traits and cursor marker helper.
-pub use crate::core::component::{Component, Focusable};
+pub use crate::core::component::{Component, ComponentMessage, Focusable};
 pub use crate::core::cursor::CURSOR_MARKER;
+/// Focus cycling between regions of a composite component.
+pub use crate::core::focus::FocusRing;
+pub use crate::core::render_cache::RenderCache;
 /// Render-layer frame types.
-pub use crate::render::{Frame, Line, Span};
+pub use crate::render::{Frame, Line, LineSize, Span, WrapOptions};
 /// Stable component identifier type.
 pub use crate::runtime::component_registry::ComponentId;
 /// Handle used to mutate shown surface layers at runtime.
 pub use crate::runtime::tui::SurfaceHandle;
 /// Runtime and surface option/model types.
 pub use crate::runtime::{
-    CustomCommand, CustomCommandCtx, CustomCommandError, SurfaceAnchor, SurfaceId,
-    SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions, SurfaceMargin, SurfaceOptions,
-    SurfaceSizeValue, SurfaceTransactionMutation, SurfaceVisibility,
+    command_reply, CommandReply, ContextMenuItem, ContextMenuOptions, ContextMenuTheme,
+    CopyModeOptions, CopyModeTheme, CustomCommand, CustomCommandCtx, CustomCommandError,
+    Diagnostic, DiagnosticCode, DiagnosticLevel, HelpEntry, HelpOverlay, HelpOverlayOptions,
+    HelpOverlayTheme, HelpSection, HostCommand, InputProtocol, Rect, ReplySender, SplitDirection,
+    SplitPane, SurfaceAnchor, SurfaceId, SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions,
+    SurfaceMargin, SurfaceOptions, SurfacePlacement, SurfaceSizeValue, SurfaceTransactionMutation,
+    SurfaceVisibility, CONTEXT_MENU_COMMAND_ID, COPY_MODE_COMMAND_ID, DEBUG_ACTION_ID,
+    DIAGNOSTIC_CODES, HELP_COMMAND_ID, NEXT_PANE_COMMAND_ID, RUNTIME_ACTIONS,
 };
+/// Derive `Component` for structs whose fields are child components.
+#[cfg(feature = "derive")]
+pub use tape_tui_derive::Composite;
 
 /// Alias for the main runtime type.
 pub type TUI<T> = crate::runtime::tui::TuiRuntime<T>;
@@ -106,6 +131,10 @@ pub fn is_focusable(component: &mut dyn Component) -> bool {
     component.as_focusable().is_some()
 }
 
+/// Removes non-styling escape sequences and controls from untrusted text.
+pub use crate::core::text::ansi::sanitize_ansi;
+/// Workspace-relative, `~`-abbreviated, middle-truncated path display.
+pub use crate::core::text::paths::{truncate_path_middle, PathDisplay};
 /// ANSI-aware wrapping helper.
 pub use crate::core::text::slice::wrap_text_with_ansi;
 /// ANSI-aware truncation helper.
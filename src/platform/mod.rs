@@ -4,5 +4,6 @@ pub mod process_terminal;
 pub mod stdin_buffer;
 
 pub use process_terminal::{
-    install_panic_hook, install_signal_handlers, PanicHookGuard, ProcessTerminal, SignalHookGuard,
+    install_panic_hook, install_signal_handlers, install_suspend_handler, PanicHookGuard,
+    ProcessTerminal, SignalHookGuard,
 };
@@ -1,5 +1,6 @@
 //! Process-based terminal implementation.
 
+use std::cell::Cell;
 use std::env;
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -150,6 +151,10 @@ fn read_winsize(fd: c_int) -> Option<(u16, u16)> {
     }
 }
 
+/// Upper bound for stdin drained into one chunk after a full read.
+#[cfg(unix)]
+const MAX_BURST_READ: usize = 256 * 1024;
+
 #[cfg(unix)]
 fn poll_readable(fd: c_int, timeout_ms: i32) -> bool {
     let mut fds = libc::pollfd {
@@ -291,7 +296,30 @@ impl ProcessTerminal {
                         Vec::new()
                     } else {
                         last_input_time.store(now_ms(), Ordering::SeqCst);
-                        stdin_buffer.process(&buffer[..read_len as usize])
+                        let read_len = read_len as usize;
+                        if read_len == buffer.len() {
+                            // A full read means more is waiting (typically a
+                            // large paste): drain it into one chunk so it is
+                            // parsed and dispatched once, up to a cap that
+                            // keeps the handler responsive.
+                            let mut burst = buffer.to_vec();
+                            while burst.len() < MAX_BURST_READ && poll_readable(stdin_fd, 0) {
+                                let more = unsafe {
+                                    libc::read(
+                                        stdin_fd,
+                                        buffer.as_mut_ptr() as *mut _,
+                                        buffer.len(),
+                                    )
+                                };
+                                if more <= 0 {
+                                    break;
+                                }
+                                burst.extend_from_slice(&buffer[..more as usize]);
+                            }
+                            stdin_buffer.process(&burst)
+                        } else {
+                            stdin_buffer.process(&buffer[..read_len])
+                        }
                     }
                 } else {
                     stdin_buffer.flush_due(now)
@@ -467,6 +495,13 @@ impl Terminal for ProcessTerminal {
         self.restore_raw_mode()
     }
 
+    fn suspend(&mut self) -> std::io::Result<()> {
+        // A SIGTSTP handler may be registered (see `install_suspend_handler`), so
+        // raising SIGTSTP would only reach it again. This stops the process with
+        // SIGSTOP and returns after SIGCONT.
+        signal_hook::low_level::emulate_default_handler(libc::SIGTSTP)
+    }
+
     fn drain_input(&mut self, max_ms: u64, idle_ms: u64) {
         self.drain_mode.store(true, Ordering::SeqCst);
         self.last_input_time.store(now_ms(), Ordering::SeqCst);
@@ -649,6 +684,35 @@ fn run_all_panic_cleanups() {
     }
 }
 
+thread_local! {
+    static PANIC_CLEANUP_SUPPRESSED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Runs `f` with the panic hook stood down on this thread: a panic inside `f`
+/// neither runs the cleanup hooks nor reaches the hook they wrap. For panics
+/// the caller catches and recovers from, where tearing down terminal modes
+/// (and printing over the UI) would break the session that keeps running.
+pub(crate) fn suppress_panic_cleanup<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore;
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            PANIC_CLEANUP_SUPPRESSED.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+
+    PANIC_CLEANUP_SUPPRESSED.with(|depth| depth.set(depth.get() + 1));
+    let _restore = Restore;
+    f()
+}
+
+#[cfg(unix)]
+fn panic_cleanup_suppressed() -> bool {
+    PANIC_CLEANUP_SUPPRESSED
+        .try_with(Cell::get)
+        .is_ok_and(|depth| depth > 0)
+}
+
 #[cfg(unix)]
 fn sync_panic_hook_state() {
     let mut state = match PANIC_HOOK_WRAPPER_STATE.lock() {
@@ -676,6 +740,9 @@ fn sync_panic_hook_state() {
                 let previous = Arc::new(current);
                 let previous_for_hook = Arc::clone(&previous);
                 let hook: Box<PanicHookFn> = Box::new(move |info| {
+                    if panic_cleanup_suppressed() {
+                        return;
+                    }
                     run_all_panic_cleanups();
                     (previous_for_hook)(info);
                 });
@@ -770,6 +837,30 @@ where
     }
 }
 
+/// Install a SIGTSTP hook that asks the app to suspend.
+///
+/// Raw mode turns Ctrl+Z into input, so this only sees SIGTSTP sent from
+/// outside (`kill -TSTP`). `on_suspend` should restore the terminal and stop
+/// the process itself, e.g. through `Terminal::suspend`.
+#[cfg(unix)]
+pub fn install_suspend_handler<F>(on_suspend: F) -> SignalHookGuard
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let mut signals = Signals::new([libc::SIGTSTP]).expect("failed to register SIGTSTP handler");
+    let handle = signals.handle();
+    let thread = thread::spawn(move || {
+        for _ in signals.forever() {
+            on_suspend();
+        }
+    });
+
+    SignalHookGuard {
+        handle,
+        thread: Some(thread),
+    }
+}
+
 /// Install panic hook that runs cleanup once, then delegates to the previous hook.
 #[cfg(unix)]
 pub fn install_panic_hook<F>(cleanup: F) -> PanicHookGuard
@@ -942,18 +1033,25 @@ impl Terminal for ProcessTerminal {
     }
 }
 
+/// Serializes tests that install panic hooks; the hook is process-global.
+#[cfg(all(test, unix))]
+pub(crate) fn panic_hook_test_lock() -> &'static Mutex<()> {
+    static LOCK: std::sync::OnceLock<Mutex<()>> = std::sync::OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+}
+
 #[cfg(all(test, unix))]
 mod tests {
     use std::io;
     use std::sync::{
         atomic::{AtomicUsize, Ordering},
-        mpsc, Arc, Mutex, OnceLock,
+        mpsc, Arc,
     };
     use std::time::{Duration, Instant};
 
     use super::{
-        get_termios, install_panic_hook, poll_readable, write_all_fd_with, HookTerminal,
-        ProcessTerminal, StopTestHooks,
+        get_termios, install_panic_hook, panic_hook_test_lock, poll_readable,
+        suppress_panic_cleanup, write_all_fd_with, HookTerminal, ProcessTerminal, StopTestHooks,
     };
     use crate::core::terminal::Terminal;
 
@@ -1043,11 +1141,6 @@ mod tests {
         result
     }
 
-    fn panic_hook_test_lock() -> &'static Mutex<()> {
-        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
-        LOCK.get_or_init(|| Mutex::new(()))
-    }
-
     #[test]
     fn panic_hook_guard_drop_does_not_clobber_later_hooks() {
         let _guard = panic_hook_test_lock()
@@ -1152,6 +1245,49 @@ mod tests {
         assert_eq!(current_id, base_hook_id, "base hook not restored");
     }
 
+    #[test]
+    fn suppressed_panics_skip_cleanup_and_the_wrapped_hook() {
+        let _guard = panic_hook_test_lock()
+            .lock()
+            .expect("panic hook test lock poisoned");
+
+        let original = std::panic::take_hook();
+        let base_calls = Arc::new(AtomicUsize::new(0));
+        std::panic::set_hook({
+            let base_calls = Arc::clone(&base_calls);
+            Box::new(move |_| {
+                base_calls.fetch_add(1, Ordering::SeqCst);
+            })
+        });
+
+        let test_thread = std::thread::current().id();
+        let cleanups = Arc::new(AtomicUsize::new(0));
+        let guard = install_panic_hook({
+            let cleanups = Arc::clone(&cleanups);
+            move || {
+                if std::thread::current().id() == test_thread {
+                    cleanups.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+
+        let _ = std::panic::catch_unwind(|| suppress_panic_cleanup(|| panic!("caught")));
+        let suppressed = (
+            cleanups.load(Ordering::SeqCst),
+            base_calls.load(Ordering::SeqCst),
+        );
+        let _ = std::panic::catch_unwind(|| panic!("boom"));
+        let unsuppressed = (
+            cleanups.load(Ordering::SeqCst),
+            base_calls.load(Ordering::SeqCst),
+        );
+
+        drop(guard);
+        std::panic::set_hook(original);
+        assert_eq!(suppressed, (0, 0));
+        assert_eq!(unsuppressed, (1, 1));
+    }
+
     #[test]
     fn hook_terminal_write_best_effort_returns_on_would_block() {
         let mut fds = [0 as c_int; 2];
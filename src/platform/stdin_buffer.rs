@@ -5,6 +5,10 @@ use std::time::{Duration, Instant};
 const ESC: char = '\x1b';
 const BRACKETED_PASTE_START: &str = "\x1b[200~";
 const BRACKETED_PASTE_END: &str = "\x1b[201~";
+/// Reads at least this large switch the buffer into burst mode.
+const DEFAULT_BURST_THRESHOLD: usize = 1024;
+/// How long burst mode outlasts the last large read.
+const BURST_HOLD: Duration = Duration::from_millis(50);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StdinEvent {
@@ -44,11 +48,22 @@ struct SequenceSplit {
 }
 
 /// Buffers stdin input and emits complete sequences.
+///
+/// Large reads (a paste without bracketed paste, or a fast typist on a slow
+/// link) switch the buffer into burst mode for a short while: runs of
+/// printable text are emitted as one `Data` event instead of one event per
+/// character, so the runtime parses and dispatches them once.
 pub struct StdinBuffer {
     buffer: String,
     timeout_ms: u64,
     paste_mode: bool,
     paste_buffer: String,
+    /// Byte offset in `paste_buffer` already searched for the paste end.
+    paste_scanned: usize,
+    /// Trailing bytes of a UTF-8 character split across reads.
+    pending_utf8: Vec<u8>,
+    burst_threshold: usize,
+    burst_until: Option<Instant>,
     flush_deadline: Option<Instant>,
 }
 
@@ -59,6 +74,10 @@ impl StdinBuffer {
             timeout_ms,
             paste_mode: false,
             paste_buffer: String::new(),
+            paste_scanned: 0,
+            pending_utf8: Vec::new(),
+            burst_threshold: DEFAULT_BURST_THRESHOLD,
+            burst_until: None,
             flush_deadline: None,
         }
     }
@@ -68,19 +87,32 @@ impl StdinBuffer {
         Self::new(timeout)
     }
 
+    /// Read size (bytes) that switches to burst mode. `usize::MAX` disables
+    /// burst mode.
+    pub fn set_burst_threshold(&mut self, bytes: usize) {
+        self.burst_threshold = bytes.max(1);
+    }
+
+    pub fn in_burst(&self) -> bool {
+        self.burst_until.is_some_and(|until| Instant::now() < until)
+    }
+
     pub fn process(&mut self, data: &[u8]) -> Vec<StdinEvent> {
         self.flush_deadline = None;
+        if data.len() >= self.burst_threshold {
+            self.burst_until = Some(Instant::now() + BURST_HOLD);
+        }
 
-        let str_data = if data.len() == 1 && data[0] > 127 {
+        let str_data = if data.len() == 1 && data[0] > 127 && self.pending_utf8.is_empty() {
             let byte = data[0] - 128;
             let mut converted = String::from("\x1b");
             converted.push(byte as char);
             converted
         } else {
-            String::from_utf8_lossy(data).to_string()
+            self.decode_utf8(data)
         };
 
-        if str_data.is_empty() && self.buffer.is_empty() {
+        if data.is_empty() && self.buffer.is_empty() {
             return vec![StdinEvent::Data(String::new())];
         }
 
@@ -88,7 +120,7 @@ impl StdinBuffer {
     }
 
     pub fn flush_due(&mut self, now: Instant) -> Vec<StdinEvent> {
-        if self.buffer.is_empty() {
+        if self.buffer.is_empty() && self.pending_utf8.is_empty() {
             self.flush_deadline = None;
             return Vec::new();
         }
@@ -119,6 +151,10 @@ impl StdinBuffer {
 
     pub fn flush(&mut self) -> Vec<String> {
         self.flush_deadline = None;
+        if !self.pending_utf8.is_empty() {
+            let pending = std::mem::take(&mut self.pending_utf8);
+            self.buffer.push_str(&String::from_utf8_lossy(&pending));
+        }
         if self.buffer.is_empty() {
             return Vec::new();
         }
@@ -132,6 +168,9 @@ impl StdinBuffer {
         self.buffer.clear();
         self.paste_mode = false;
         self.paste_buffer.clear();
+        self.paste_scanned = 0;
+        self.pending_utf8.clear();
+        self.burst_until = None;
     }
 
     pub fn buffer(&self) -> &str {
@@ -145,22 +184,7 @@ impl StdinBuffer {
         if self.paste_mode {
             self.paste_buffer.push_str(&self.buffer);
             self.buffer.clear();
-
-            if let Some(end_index) = self.paste_buffer.find(BRACKETED_PASTE_END) {
-                let pasted = self.paste_buffer[..end_index].to_string();
-                let remaining =
-                    self.paste_buffer[end_index + BRACKETED_PASTE_END.len()..].to_string();
-
-                self.paste_mode = false;
-                self.paste_buffer.clear();
-
-                events.push(StdinEvent::Paste(pasted));
-
-                if !remaining.is_empty() {
-                    events.extend(self.process_str(&remaining));
-                }
-            }
-
+            self.finish_paste(&mut events);
             return events;
         }
 
@@ -175,24 +199,10 @@ impl StdinBuffer {
 
             self.buffer = self.buffer[start_index + BRACKETED_PASTE_START.len()..].to_string();
             self.paste_mode = true;
+            self.paste_scanned = 0;
             self.paste_buffer.push_str(&self.buffer);
             self.buffer.clear();
-
-            if let Some(end_index) = self.paste_buffer.find(BRACKETED_PASTE_END) {
-                let pasted = self.paste_buffer[..end_index].to_string();
-                let remaining =
-                    self.paste_buffer[end_index + BRACKETED_PASTE_END.len()..].to_string();
-
-                self.paste_mode = false;
-                self.paste_buffer.clear();
-
-                events.push(StdinEvent::Paste(pasted));
-
-                if !remaining.is_empty() {
-                    events.extend(self.process_str(&remaining));
-                }
-            }
-
+            self.finish_paste(&mut events);
             return events;
         }
 
@@ -201,16 +211,102 @@ impl StdinBuffer {
         // dropped or reordered. This can intentionally head-of-line block
         // following bytes when tails are malformed; timeout flush emits verbatim.
         self.buffer = result.remainder;
-        for sequence in result.sequences {
-            events.push(StdinEvent::Data(sequence));
+        if self.in_burst() {
+            events.extend(
+                coalesce_text_runs(result.sequences)
+                    .into_iter()
+                    .map(StdinEvent::Data),
+            );
+        } else {
+            for sequence in result.sequences {
+                events.push(StdinEvent::Data(sequence));
+            }
         }
 
-        if !self.buffer.is_empty() {
+        if !self.buffer.is_empty() || !self.pending_utf8.is_empty() {
             self.flush_deadline = Some(Instant::now() + Duration::from_millis(self.timeout_ms));
         }
 
         events
     }
+
+    /// Emits the paste once its end marker arrived and processes whatever
+    /// follows it. Only the bytes added since the last call are searched, so
+    /// a paste arriving in many chunks is scanned once overall.
+    fn finish_paste(&mut self, events: &mut Vec<StdinEvent>) {
+        let scan_from = self
+            .paste_scanned
+            .saturating_sub(BRACKETED_PASTE_END.len() - 1);
+        let end_index = self.paste_buffer.as_bytes()[scan_from..]
+            .windows(BRACKETED_PASTE_END.len())
+            .position(|window| window == BRACKETED_PASTE_END.as_bytes())
+            .map(|offset| scan_from + offset);
+        let Some(end_index) = end_index else {
+            self.paste_scanned = self.paste_buffer.len();
+            return;
+        };
+
+        let remaining = self
+            .paste_buffer
+            .split_off(end_index + BRACKETED_PASTE_END.len());
+        self.paste_buffer.truncate(end_index);
+        self.paste_mode = false;
+        self.paste_scanned = 0;
+        events.push(StdinEvent::Paste(std::mem::take(&mut self.paste_buffer)));
+
+        if !remaining.is_empty() {
+            events.extend(self.process_str(&remaining));
+        }
+    }
+
+    /// Decodes `data`, holding back an incomplete UTF-8 character at the end
+    /// until the rest of it arrives.
+    fn decode_utf8(&mut self, data: &[u8]) -> String {
+        let mut bytes = std::mem::take(&mut self.pending_utf8);
+        bytes.extend_from_slice(data);
+        let complete = bytes.len() - incomplete_utf8_tail(&bytes);
+        self.pending_utf8 = bytes.split_off(complete);
+        match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        }
+    }
+}
+
+/// Length of a truncated multi-byte character at the end of `bytes`.
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xC0 == 0x80 {
+            continue;
+        }
+        let needed = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return 0,
+        };
+        return if back < needed { back } else { 0 };
+    }
+    0
+}
+
+/// Joins adjacent printable sequences into one so a burst of text is
+/// dispatched as a single text event. Escape sequences and control
+/// characters stay separate.
+fn coalesce_text_runs(sequences: Vec<String>) -> Vec<String> {
+    let is_text = |sequence: &str| sequence.chars().all(|ch| !ch.is_control() && ch != ESC);
+    let mut out: Vec<String> = Vec::with_capacity(sequences.len());
+    let mut last_is_text = false;
+    for sequence in sequences {
+        let text = is_text(&sequence);
+        match out.last_mut() {
+            Some(last) if text && last_is_text => last.push_str(&sequence),
+            _ => out.push(sequence),
+        }
+        last_is_text = text;
+    }
+    out
 }
 
 fn extract_complete_sequences(buffer: &str) -> SequenceSplit {
@@ -592,4 +688,75 @@ mod tests {
             "second timeout flush must not duplicate prior bytes"
         );
     }
+
+    #[test]
+    fn large_reads_coalesce_text_runs_but_keep_keys_separate() {
+        let mut buffer = StdinBuffer::new(10);
+        buffer.set_burst_threshold(8);
+
+        let events = buffer.process(b"ab\x1b[Acd\ref");
+        assert!(buffer.in_burst());
+        assert_eq!(
+            events,
+            vec![
+                StdinEvent::Data("ab".to_string()),
+                StdinEvent::Data("\x1b[A".to_string()),
+                StdinEvent::Data("cd".to_string()),
+                StdinEvent::Data("\r".to_string()),
+                StdinEvent::Data("ef".to_string()),
+            ]
+        );
+
+        let mut quiet = StdinBuffer::new(10);
+        assert_eq!(
+            quiet.process(b"ab"),
+            vec![
+                StdinEvent::Data("a".to_string()),
+                StdinEvent::Data("b".to_string()),
+            ]
+        );
+        assert!(!quiet.in_burst());
+    }
+
+    #[test]
+    fn utf8_split_across_reads_is_not_replaced() {
+        let mut buffer = StdinBuffer::new(10);
+        let bytes = "é€".as_bytes();
+
+        assert_eq!(
+            buffer.process(&bytes[..3]),
+            vec![StdinEvent::Data("é".to_string())]
+        );
+        assert_eq!(
+            buffer.process(&bytes[3..]),
+            vec![StdinEvent::Data("€".to_string())]
+        );
+
+        // A truncated character is flushed lossily once the timeout passes.
+        assert!(buffer.process(&bytes[2..4]).is_empty());
+        assert_eq!(
+            buffer.flush_due(Instant::now() + Duration::from_millis(15)),
+            vec![StdinEvent::Data("\u{fffd}".to_string())]
+        );
+    }
+
+    #[test]
+    fn chunked_large_paste_is_one_event() {
+        let mut buffer = StdinBuffer::new(10);
+        let content = "línea €\n".repeat(40_000);
+        let wire = format!("\x1b[200~{content}\x1b[201~x");
+
+        let mut events = Vec::new();
+        for chunk in wire.as_bytes().chunks(4093) {
+            events.extend(buffer.process(chunk));
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                StdinEvent::Paste(content),
+                StdinEvent::Data("x".to_string()),
+            ]
+        );
+    }
 }
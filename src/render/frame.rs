@@ -2,8 +2,22 @@
 //!
 //! `Span`/`Line`/`Frame` provide typed render containers while preserving
 //! compatibility with existing `Vec<String>` call sites.
+//!
+//! Simple hosts can build them without touching spans directly:
+//! `Line::styled` applies a style function, `Frame::wrapped` word-wraps text,
+//! and `+`/`+=` concatenate spans onto lines and lines onto frames.
+//!
+//! Lines can also ask for DEC double-width or double-height rendering (see
+//! [`LineSize`]). The size travels as an `ESC # n` prefix on the rendered
+//! string, so it survives components that return `Vec<String>`; the renderer
+//! keeps it only when the terminal supports line attributes.
+
+use std::ops::{Add, AddAssign, Range};
 
 use crate::core::cursor::CursorPos;
+use crate::core::fuzzy::FuzzyMatch;
+use crate::core::text::slice::wrap_text_with_ansi;
+use crate::core::text::width::visible_width;
 
 /// A contiguous run of rendered text.
 ///
@@ -18,6 +32,11 @@ impl Span {
         Self { text }
     }
 
+    /// A span holding `style(text)`, e.g. a theme color function.
+    pub fn styled(text: impl AsRef<str>, style: impl Fn(&str) -> String) -> Self {
+        Self::new(style(text.as_ref()))
+    }
+
     pub fn as_str(&self) -> &str {
         &self.text
     }
@@ -33,6 +52,57 @@ impl From<String> for Span {
     }
 }
 
+impl From<&str> for Span {
+    fn from(text: &str) -> Self {
+        Self::new(text.to_string())
+    }
+}
+
+/// DEC line attribute of a rendered row.
+///
+/// Double-width rows show half as many cells as the terminal is wide. A
+/// double-height row is drawn as two lines holding the same text: the top
+/// half followed by the bottom half.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineSize {
+    #[default]
+    Single,
+    /// `ESC # 6` (DECDWL).
+    DoubleWidth,
+    /// `ESC # 3` (DECDHL, top half).
+    DoubleHeightTop,
+    /// `ESC # 4` (DECDHL, bottom half).
+    DoubleHeightBottom,
+}
+
+impl LineSize {
+    /// The escape sequence that selects this size, empty for `Single`.
+    pub fn escape(self) -> &'static str {
+        match self {
+            Self::Single => "",
+            Self::DoubleWidth => "\x1b#6",
+            Self::DoubleHeightTop => "\x1b#3",
+            Self::DoubleHeightBottom => "\x1b#4",
+        }
+    }
+
+    /// The size selected by a leading line attribute escape in `text`.
+    pub fn from_prefix(text: &str) -> Self {
+        [
+            Self::DoubleWidth,
+            Self::DoubleHeightTop,
+            Self::DoubleHeightBottom,
+        ]
+        .into_iter()
+        .find(|size| text.starts_with(size.escape()))
+        .unwrap_or(Self::Single)
+    }
+
+    pub fn is_double(self) -> bool {
+        self != Self::Single
+    }
+}
+
 /// A single rendered line.
 ///
 /// A line is represented as a sequence of spans to support future per-span styling
@@ -41,6 +111,7 @@ impl From<String> for Span {
 pub struct Line {
     spans: Vec<Span>,
     is_image: bool,
+    size: LineSize,
 }
 
 impl Line {
@@ -48,6 +119,7 @@ impl Line {
         Self {
             spans,
             is_image: false,
+            size: LineSize::Single,
         }
     }
 
@@ -55,29 +127,117 @@ impl Line {
         Self {
             spans,
             is_image: true,
+            size: LineSize::Single,
+        }
+    }
+
+    /// A single-span line holding `style(text)`.
+    pub fn styled(text: impl AsRef<str>, style: impl Fn(&str) -> String) -> Self {
+        Self::new(vec![Span::styled(text, style)])
+    }
+
+    /// `text` with the byte `ranges` styled by `highlight` and the rest by
+    /// `normal`. Ranges must be ordered; ones that overlap an earlier range,
+    /// run past the text or split a character are skipped.
+    pub fn highlighted(
+        text: &str,
+        ranges: &[Range<usize>],
+        normal: impl Fn(&str) -> String,
+        highlight: impl Fn(&str) -> String,
+    ) -> Self {
+        let mut line = Self::new(Vec::new());
+        let mut cursor = 0;
+        for range in ranges {
+            if range.start < cursor
+                || range.start >= range.end
+                || range.end > text.len()
+                || !text.is_char_boundary(range.start)
+                || !text.is_char_boundary(range.end)
+            {
+                continue;
+            }
+            if range.start > cursor {
+                line.push_span(Span::styled(&text[cursor..range.start], &normal));
+            }
+            line.push_span(Span::styled(&text[range.clone()], &highlight));
+            cursor = range.end;
+        }
+        if cursor < text.len() || line.spans.is_empty() {
+            line.push_span(Span::styled(&text[cursor..], &normal));
         }
+        line
+    }
+
+    /// `text` with the characters `matched` hit styled by `highlight`, for
+    /// rendering filtered list items.
+    pub fn fuzzy_highlighted(
+        text: &str,
+        matched: &FuzzyMatch,
+        normal: impl Fn(&str) -> String,
+        highlight: impl Fn(&str) -> String,
+    ) -> Self {
+        Self::highlighted(text, &matched.ranges, normal, highlight)
     }
 
     pub fn spans(&self) -> &[Span] {
         &self.spans
     }
 
+    pub fn push_span(&mut self, span: impl Into<Span>) {
+        self.spans.push(span.into());
+    }
+
+    /// Visible width in terminal columns, ignoring ANSI escapes.
+    pub fn width(&self) -> usize {
+        self.spans
+            .iter()
+            .map(|span| visible_width(span.as_str()))
+            .sum()
+    }
+
     pub fn is_image(&self) -> bool {
         self.is_image
     }
 
+    /// Renders this line at double width in terminals that support DEC line
+    /// attributes, e.g. for a banner. Elsewhere it renders normally.
+    pub fn double_width(self) -> Self {
+        self.with_size(LineSize::DoubleWidth)
+    }
+
+    /// The top and bottom halves of a double-height, double-width line.
+    /// Without terminal support the top half renders normally and the bottom
+    /// half renders blank.
+    pub fn double_height(self) -> [Line; 2] {
+        let bottom = self.clone().with_size(LineSize::DoubleHeightBottom);
+        [self.with_size(LineSize::DoubleHeightTop), bottom]
+    }
+
+    pub fn with_size(mut self, size: LineSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn size(&self) -> LineSize {
+        self.size
+    }
+
+    /// The concatenated spans, prefixed with the line attribute escape for
+    /// double-width and double-height lines.
     pub fn into_string(self) -> String {
+        let prefix = self.size.escape();
         // Fast path: a single span is the common case. Move the inner String out
         // without allocating/copying.
         let spans = self.spans;
         match spans.len() {
-            0 => String::new(),
-            1 => spans.into_iter().next().unwrap().into_string(),
+            0 => prefix.to_string(),
+            1 if prefix.is_empty() => spans.into_iter().next().unwrap().into_string(),
             _ => {
                 // Multi-span lines must be concatenated. Preallocate to avoid
                 // repeated growth as we append spans.
                 let capacity: usize = spans.iter().map(|span| span.as_str().len()).sum();
-                let mut out = String::with_capacity(capacity);
+                let mut out = String::with_capacity(prefix.len() + capacity);
+                out.push_str(prefix);
                 for span in spans {
                     out.push_str(span.as_str());
                 }
@@ -93,6 +253,63 @@ impl From<String> for Line {
     }
 }
 
+impl From<&str> for Line {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_string())
+    }
+}
+
+impl From<Span> for Line {
+    fn from(span: Span) -> Self {
+        Self::new(vec![span])
+    }
+}
+
+impl<S: Into<Span>> Add<S> for Line {
+    type Output = Line;
+
+    fn add(mut self, span: S) -> Line {
+        self.push_span(span);
+        self
+    }
+}
+
+impl<S: Into<Span>> AddAssign<S> for Line {
+    fn add_assign(&mut self, span: S) {
+        self.push_span(span);
+    }
+}
+
+impl Add<Line> for Line {
+    type Output = Line;
+
+    fn add(mut self, other: Line) -> Line {
+        self.spans.extend(other.spans);
+        if other.is_image {
+            self.is_image = true;
+        }
+        self
+    }
+}
+
+impl Add<Span> for Span {
+    type Output = Line;
+
+    fn add(self, other: Span) -> Line {
+        Line::new(vec![self, other])
+    }
+}
+
+/// Options for [`Frame::wrapped`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WrapOptions {
+    /// Prefix for every wrapped line.
+    pub indent: String,
+    /// Prefix for the first line of each paragraph instead of `indent`, e.g.
+    /// a list bullet. Text wraps to the width left by the wider prefix.
+    pub initial_indent: Option<String>,
+}
+
 /// A rendered frame (collection of lines).
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Frame {
@@ -115,6 +332,30 @@ impl Frame {
         frame
     }
 
+    /// Word-wraps `text` to `width` columns, one paragraph per `\n`. ANSI
+    /// styles carry across wrapped lines.
+    pub fn wrapped(text: &str, width: usize, options: WrapOptions) -> Self {
+        let initial = options.initial_indent.as_deref().unwrap_or(&options.indent);
+        let indent_width = visible_width(initial).max(visible_width(&options.indent));
+        let available = width.saturating_sub(indent_width).max(1);
+
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            for (index, line) in wrap_text_with_ansi(paragraph, available)
+                .into_iter()
+                .enumerate()
+            {
+                let prefix = if index == 0 { initial } else { &options.indent };
+                lines.push(Line::from(format!("{prefix}{line}")));
+            }
+        }
+        Self::new(lines)
+    }
+
+    pub fn push_line(&mut self, line: impl Into<Line>) {
+        self.lines.push(line.into());
+    }
+
     pub fn with_cursor(mut self, cursor: Option<CursorPos>) -> Self {
         self.cursor = cursor;
         self
@@ -147,18 +388,72 @@ impl From<Vec<String>> for Frame {
                 .into_iter()
                 .map(|text| {
                     let is_image = crate::core::terminal_image::is_image_line(&text);
-                    let spans = vec![Span::new(text)];
                     if is_image {
-                        Line::image(spans)
-                    } else {
-                        Line::new(spans)
+                        return Line::image(vec![Span::new(text)]);
                     }
+                    let size = LineSize::from_prefix(&text);
+                    let text = match text.strip_prefix(size.escape()) {
+                        Some(rest) if size.is_double() => rest.to_string(),
+                        _ => text,
+                    };
+                    Line::new(vec![Span::new(text)]).with_size(size)
                 })
                 .collect(),
         )
     }
 }
 
+impl<L: Into<Line>> FromIterator<L> for Frame {
+    fn from_iter<I: IntoIterator<Item = L>>(iter: I) -> Self {
+        Self::new(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<L: Into<Line>> Extend<L> for Frame {
+    fn extend<I: IntoIterator<Item = L>>(&mut self, iter: I) {
+        self.lines.extend(iter.into_iter().map(Into::into));
+    }
+}
+
+impl Add<Line> for Frame {
+    type Output = Frame;
+
+    fn add(mut self, line: Line) -> Frame {
+        self.lines.push(line);
+        self
+    }
+}
+
+impl AddAssign<Line> for Frame {
+    fn add_assign(&mut self, line: Line) {
+        self.lines.push(line);
+    }
+}
+
+/// Stacks `other` below `self`. The cursor of `self` wins; otherwise the
+/// cursor of `other` is kept, shifted down by the rows above it.
+impl Add<Frame> for Frame {
+    type Output = Frame;
+
+    fn add(mut self, other: Frame) -> Frame {
+        if self.cursor.is_none() {
+            self.cursor = other.cursor.map(|cursor| CursorPos {
+                row: cursor.row + self.lines.len(),
+                col: cursor.col,
+            });
+        }
+        self.lines.extend(other.lines);
+        self
+    }
+}
+
+impl AddAssign<Frame> for Frame {
+    fn add_assign(&mut self, other: Frame) {
+        let this = std::mem::replace(self, Frame::new(Vec::new()));
+        *self = this + other;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,4 +506,75 @@ mod tests {
         assert_eq!(out.capacity(), cap);
         assert_eq!(out.as_ptr(), ptr);
     }
+
+    #[test]
+    fn builders_style_wrap_and_concatenate() {
+        let bold = |text: &str| format!("\x1b[1m{text}\x1b[22m");
+        let line = Line::styled("Hi", bold) + " there" + Span::from("!");
+        assert_eq!(line.spans().len(), 3);
+        assert_eq!(line.width(), 9);
+        assert_eq!(line.clone().into_string(), "\x1b[1mHi\x1b[22m there!");
+
+        let wrapped = Frame::wrapped(
+            "alpha beta gamma\nz",
+            10,
+            WrapOptions {
+                indent: "  ".to_string(),
+                initial_indent: Some("- ".to_string()),
+            },
+        );
+        let rows: Vec<String> = wrapped
+            .into_strings()
+            .into_iter()
+            .map(|row| row.trim_end().to_string())
+            .collect();
+        assert_eq!(rows, vec!["- alpha", "  beta", "  gamma", "- z"]);
+
+        let top = Frame::from_iter(["one", "two"]);
+        let bottom = Frame::new(vec![line]).with_cursor(Some(CursorPos { row: 0, col: 2 }));
+        let mut frame = top + Line::from("three");
+        frame += bottom;
+        assert_eq!(frame.lines().len(), 4);
+        assert_eq!(frame.cursor(), Some(CursorPos { row: 3, col: 2 }));
+    }
+
+    #[test]
+    fn line_size_survives_string_round_trip() {
+        let [top, bottom] = Line::from("Title").double_height();
+        let strings = vec![
+            Line::from("Wide").double_width().into_string(),
+            top.into_string(),
+            bottom.into_string(),
+            "plain".to_string(),
+        ];
+        assert_eq!(strings[0], "\x1b#6Wide");
+        assert_eq!(visible_width(&strings[0]), 4);
+
+        let frame = Frame::from(strings);
+        let sizes: Vec<LineSize> = frame.lines().iter().map(Line::size).collect();
+        assert_eq!(
+            sizes,
+            vec![
+                LineSize::DoubleWidth,
+                LineSize::DoubleHeightTop,
+                LineSize::DoubleHeightBottom,
+                LineSize::Single,
+            ]
+        );
+        assert_eq!(frame.lines()[1].spans()[0].as_str(), "Title");
+    }
+
+    #[test]
+    fn fuzzy_highlighted_styles_only_the_matched_ranges() {
+        let text = "src/fuzzy.rs";
+        let matched = crate::core::fuzzy::fuzzy_match("fz", text);
+        let line =
+            Line::fuzzy_highlighted(text, &matched, str::to_string, |part| format!("[{part}]"));
+        assert_eq!(line.into_string(), "src/[f]u[z]zy.rs");
+
+        let skipped = Line::highlighted("abc", &[1..2, 0..1, 2..9], str::to_string, |part| {
+            format!("[{part}]")
+        });
+        assert_eq!(skipped.into_string(), "a[b]c");
+    }
 }
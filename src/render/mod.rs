@@ -4,4 +4,4 @@ pub mod frame;
 pub mod renderer;
 pub mod surface;
 
-pub use frame::{Frame, Line, Span};
+pub use frame::{Frame, Line, LineSize, Span, WrapOptions};
@@ -6,12 +6,14 @@ use crate::core::text::width::visible_width;
 use crate::logging::{
     debug_redraw_enabled, log_debug_redraw, log_tui_debug, tui_debug_enabled, RenderDebugInfo,
 };
-use crate::render::Frame;
+use crate::render::{Frame, LineSize};
 
 const SEGMENT_RESET: &str = "\x1b[0m\x1b]8;;\x07";
 const SYNC_START: &str = "\x1b[?2026h";
 const SYNC_END: &str = "\x1b[?2026l";
 const CLEAR_ALL: &str = "\x1b[3J\x1b[2J\x1b[H";
+/// DECSWL: returns a row to single width/height.
+const SINGLE_WIDTH_LINE: &str = "\x1b#5";
 
 #[derive(Debug, Default)]
 pub struct DiffRenderer {
@@ -22,6 +24,11 @@ pub struct DiffRenderer {
     hardware_cursor_row: usize,
     previous_viewport_top: usize,
     force_full_redraw_next: bool,
+    line_attributes: bool,
+    /// Set once a double-width/height row has been drawn. Line attributes
+    /// stick to a terminal row until reset, so from then on single rows are
+    /// emitted with `ESC # 5`.
+    line_attributes_used: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -58,6 +65,25 @@ impl DiffRenderer {
         self.hardware_cursor_row = 0;
         self.previous_viewport_top = 0;
         self.force_full_redraw_next = false;
+        self.line_attributes_used = false;
+    }
+
+    /// Rows of the last frame above the viewport, i.e. in native scrollback.
+    pub fn rows_above_viewport(&self) -> usize {
+        self.previous_viewport_top.min(self.previous_lines.len())
+    }
+
+    /// Drops the first `count` rows (at most [`Self::rows_above_viewport`])
+    /// from the diff state, as if the last frame had started after them. The
+    /// terminal keeps showing them in scrollback; the next frame is expected
+    /// to omit them.
+    pub fn forget_leading_lines(&mut self, count: usize) {
+        let count = count.min(self.rows_above_viewport());
+        self.previous_lines.drain(..count);
+        self.max_lines_rendered -= count;
+        self.previous_viewport_top -= count;
+        self.cursor_row = self.cursor_row.saturating_sub(count);
+        self.hardware_cursor_row = self.hardware_cursor_row.saturating_sub(count);
     }
 
     /// Applies a cursor move that happened out-of-band (only `CSI nA` / `CSI nB`, no scrolling).
@@ -82,10 +108,97 @@ impl DiffRenderer {
         self.force_full_redraw_next = true;
     }
 
+    /// Enables DEC line attributes for lines with a [`LineSize`] other than
+    /// `Single`. When disabled (the default) those lines render at normal
+    /// size, with the bottom half of a double-height line left blank.
+    pub fn set_line_attributes(&mut self, enabled: bool) {
+        if self.line_attributes == enabled {
+            return;
+        }
+        self.line_attributes = enabled;
+        if !self.previous_lines.is_empty() {
+            self.force_full_redraw_next = true;
+        }
+    }
+
+    pub fn line_attributes(&self) -> bool {
+        self.line_attributes
+    }
+
+    /// Applies the line attribute policy to one rendered line: sized lines
+    /// keep their escape and are clamped to half the width, or lose it when
+    /// line attributes are disabled.
+    fn apply_line_size(&self, text: String, size: LineSize, width: usize) -> String {
+        let text = if !size.is_double() {
+            text
+        } else if self.line_attributes {
+            let body = text.strip_prefix(size.escape()).unwrap_or(&text);
+            let half = width / 2;
+            if visible_width(body) > half {
+                return format!("{}{}", size.escape(), slice_by_column(body, 0, half, true));
+            }
+            return text;
+        } else if size == LineSize::DoubleHeightBottom {
+            String::new()
+        } else {
+            text.strip_prefix(size.escape())
+                .unwrap_or(&text)
+                .to_string()
+        };
+        if self.line_attributes_used {
+            format!("{SINGLE_WIDTH_LINE}{text}")
+        } else {
+            text
+        }
+    }
+
+    /// Re-emits the previous frame at `width` without asking for new content:
+    /// lines are clipped to the new width instead of being laid out again.
+    /// Used for interim frames while a resize settles.
+    pub fn render_previous_clipped(&mut self, width: usize, height: usize) -> Vec<TerminalCmd> {
+        let lines: Vec<String> = self
+            .previous_lines
+            .iter()
+            .map(|line| {
+                if crate::core::terminal_image::is_image_line(line) {
+                    return line.clone();
+                }
+                let line = line.strip_suffix(SEGMENT_RESET).unwrap_or(line);
+                let line = line.strip_prefix(SINGLE_WIDTH_LINE).unwrap_or(line);
+                let size = LineSize::from_prefix(line);
+                let body = line.strip_prefix(size.escape()).unwrap_or(line);
+                format!("{}{}", size.escape(), slice_by_column(body, 0, width, true))
+            })
+            .collect();
+        self.render(Frame::from(lines), width, height, false, false)
+    }
+
+    /// The last frame's lines as they were passed to [`Self::render`].
+    pub(crate) fn previous_frame_lines(&self) -> Vec<String> {
+        self.previous_lines
+            .iter()
+            .map(|line| {
+                let line = line.strip_suffix(SEGMENT_RESET).unwrap_or(line);
+                line.strip_prefix(SINGLE_WIDTH_LINE)
+                    .unwrap_or(line)
+                    .to_string()
+            })
+            .collect()
+    }
+
     pub fn previous_lines_len(&self) -> usize {
         self.previous_lines.len()
     }
 
+    /// The last rendered lines without their trailing style resets.
+    #[cfg(test)]
+    pub(crate) fn previous_lines(&self) -> Vec<&str> {
+        self.previous_lines
+            .iter()
+            .map(|line| line.strip_suffix(SEGMENT_RESET).unwrap_or(line))
+            .collect()
+    }
+
     pub fn max_lines_rendered(&self) -> usize {
         self.max_lines_rendered
     }
@@ -131,11 +244,19 @@ impl DiffRenderer {
         clear_on_shrink: bool,
         has_surfaces: bool,
     ) -> Vec<TerminalCmd> {
+        if self.line_attributes && frame.lines().iter().any(|line| line.size().is_double()) {
+            self.line_attributes_used = true;
+        }
         let mut lines = Vec::new();
         let mut is_image = Vec::new();
         for line in frame.into_lines() {
             is_image.push(line.is_image());
-            lines.push(line.into_string());
+            if line.is_image() {
+                lines.push(line.into_string());
+            } else {
+                let size = line.size();
+                lines.push(self.apply_line_size(line.into_string(), size, width));
+            }
         }
         let mut cmds = Vec::new();
 
@@ -1508,6 +1629,35 @@ mod tests {
         assert!(output.contains("hello\x1b[0m\x1b]8;;\x07"));
     }
 
+    #[test]
+    fn double_width_lines_are_gated_by_line_attributes() {
+        let banner = || {
+            let mut frame = Frame::new(vec![Line::from("abcdefgh").double_width()]);
+            frame.extend(Line::from("Hi").double_height());
+            frame.push_line("body");
+            frame
+        };
+
+        let mut plain = DiffRenderer::new();
+        let output = cmds_to_bytes(plain.render(banner(), 6, 5, false, false));
+        assert!(!output.contains("\x1b#"), "{output:?}");
+        assert!(output.contains("abcdefgh\x1b[0m"));
+        assert!(output.contains("Hi\x1b[0m\x1b]8;;\x07\r\n\x1b[0m"));
+
+        let mut sized = DiffRenderer::new();
+        sized.set_line_attributes(true);
+        let output = cmds_to_bytes(sized.render(banner(), 6, 5, false, false));
+        assert!(output.contains("\x1b#6abc\x1b[0m"), "{output:?}");
+        assert!(output.contains("\x1b#3Hi\x1b[0m"));
+        assert!(output.contains("\x1b#4Hi\x1b[0m"));
+        assert!(output.contains("\x1b#5body\x1b[0m"));
+
+        // Switching support off redraws the rows at normal size.
+        sized.set_line_attributes(false);
+        let output = cmds_to_bytes(sized.render(banner(), 6, 5, false, false));
+        assert!(output.contains("\x1b#5abcdef"), "{output:?}");
+    }
+
     #[test]
     fn multi_span_line_renders_identically_to_concatenated_line() {
         let mut renderer_multi = DiffRenderer::new();
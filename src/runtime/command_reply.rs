@@ -0,0 +1,61 @@
+//! One-shot replies from commands applied on the runtime thread.
+//!
+//! A [`CustomCommand`](crate::runtime::tui::CustomCommand) can carry a
+//! [`ReplySender`] and answer once it has run (or later, from a worker),
+//! while the dispatching thread waits on the matching [`CommandReply`].
+//! [`RuntimeHandle::query`](crate::runtime::tui::RuntimeHandle::query)
+//! wraps the common case.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+/// Creates a connected reply pair.
+pub fn command_reply<R>() -> (ReplySender<R>, CommandReply<R>) {
+    let (tx, rx) = mpsc::channel();
+    (ReplySender { tx }, CommandReply { rx })
+}
+
+/// Sending half; consumed by [`ReplySender::send`]. Dropping it unanswered
+/// makes the waiting side return `None`.
+pub struct ReplySender<R> {
+    tx: Sender<R>,
+}
+
+impl<R> ReplySender<R> {
+    /// Delivers the reply. Ignored if the receiver is gone.
+    pub fn send(self, value: R) {
+        let _ = self.tx.send(value);
+    }
+}
+
+/// Receiving half of a command reply.
+///
+/// Waiting blocks the calling thread, so never wait on the runtime thread
+/// itself: the command cannot run until the wait returns.
+pub struct CommandReply<R> {
+    rx: Receiver<R>,
+}
+
+impl<R> CommandReply<R> {
+    /// Blocks until the reply arrives. `None` if the command was dropped
+    /// without replying (it failed, or the runtime stopped first).
+    pub fn wait(self) -> Option<R> {
+        self.rx.recv().ok()
+    }
+
+    /// Like [`CommandReply::wait`], giving up after `timeout`.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<R> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+
+    /// The reply if it has already arrived.
+    pub fn try_take(&self) -> Option<R> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl<R> std::fmt::Debug for CommandReply<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandReply").finish_non_exhaustive()
+    }
+}
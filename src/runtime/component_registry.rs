@@ -42,8 +42,26 @@ impl ComponentRegistry {
         id
     }
 
+    /// The id the next registered component gets.
+    pub fn next_id(&self) -> ComponentId {
+        ComponentId(self.next_id)
+    }
+
     pub fn get_mut(&mut self, id: ComponentId) -> Option<&mut Box<dyn Component>> {
         let idx: usize = id.raw().try_into().ok()?;
         self.entries.get_mut(idx).and_then(|entry| entry.as_mut())
     }
+
+    /// Takes the component out of the registry; its id stays retired.
+    pub fn remove(&mut self, id: ComponentId) -> Option<Box<dyn Component>> {
+        let idx: usize = id.raw().try_into().ok()?;
+        self.entries.get_mut(idx).and_then(Option::take)
+    }
+
+    /// Visits every registered component in id order.
+    pub fn for_each_mut(&mut self, mut f: impl FnMut(&mut dyn Component)) {
+        for component in self.entries.iter_mut().flatten() {
+            f(component.as_mut());
+        }
+    }
 }
@@ -0,0 +1,375 @@
+//! Anchored context menus.
+//!
+//! A widget or host opens a menu with
+//! [`RuntimeHandle::open_context_menu`](crate::runtime::tui::RuntimeHandle::open_context_menu),
+//! or the runtime builds the focused component's items, registered with
+//! [`TuiRuntime::set_context_menu_items`](crate::runtime::tui::TuiRuntime::set_context_menu_items),
+//! when a key registered through
+//! [`TuiRuntime::set_context_menu`](crate::runtime::tui::TuiRuntime::set_context_menu)
+//! is pressed. The runtime shows the menu next to its anchor component and
+//! owns navigation: Up/Down (or Tab) move, Enter picks, Escape dismisses, and
+//! typing a letter jumps to the next item starting with it. Picking an item
+//! closes the menu, gives focus back, and queues the [`Command`] the item
+//! produces.
+
+use std::sync::Arc;
+
+use crate::core::component::Component;
+use crate::core::input_event::InputEvent;
+use crate::core::keybindings::KeyId;
+use crate::core::text::utils::truncate_to_width;
+use crate::core::text::width::visible_width;
+use crate::runtime::tui::Command;
+
+/// One entry of a context menu.
+pub struct ContextMenuItem {
+    pub label: String,
+    /// Shown right-aligned, typically the item's own shortcut.
+    pub hint: Option<String>,
+    /// Disabled items are shown but skipped by navigation.
+    pub enabled: bool,
+    run: Box<dyn FnOnce() -> Command + Send>,
+}
+
+impl ContextMenuItem {
+    pub fn new(label: impl Into<String>, run: impl FnOnce() -> Command + Send + 'static) -> Self {
+        Self {
+            label: label.into(),
+            hint: None,
+            enabled: true,
+            run: Box::new(run),
+        }
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    pub(crate) fn run(self) -> Command {
+        (self.run)()
+    }
+}
+
+impl std::fmt::Debug for ContextMenuItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextMenuItem")
+            .field("label", &self.label)
+            .field("hint", &self.hint)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct ContextMenuTheme {
+    pub item: Arc<dyn Fn(&str) -> String>,
+    pub selected: Arc<dyn Fn(&str) -> String>,
+    pub disabled: Arc<dyn Fn(&str) -> String>,
+    pub hint: Arc<dyn Fn(&str) -> String>,
+}
+
+impl Default for ContextMenuTheme {
+    fn default() -> Self {
+        Self {
+            item: Arc::new(str::to_string),
+            selected: Arc::new(|text| format!("\x1b[7m{text}\x1b[27m")),
+            disabled: Arc::new(|text| format!("\x1b[2m{text}\x1b[22m")),
+            hint: Arc::new(|text| format!("\x1b[2m{text}\x1b[22m")),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ContextMenuOptions {
+    /// Keys that open the focused component's menu.
+    pub keys: Vec<KeyId>,
+    /// Most items shown at once; the rest scroll.
+    pub max_rows: usize,
+    pub theme: ContextMenuTheme,
+}
+
+impl Default for ContextMenuOptions {
+    fn default() -> Self {
+        Self {
+            keys: vec!["f10".to_string()],
+            max_rows: 12,
+            theme: ContextMenuTheme::default(),
+        }
+    }
+}
+
+struct MenuRow {
+    label: String,
+    hint: Option<String>,
+    enabled: bool,
+}
+
+/// The popup the runtime shows for an open menu. It only knows labels; the
+/// runtime keeps the items and runs the chosen one.
+pub(crate) struct ContextMenu {
+    rows: Vec<MenuRow>,
+    theme: ContextMenuTheme,
+    selected: Option<usize>,
+    scroll: usize,
+    max_rows: usize,
+    on_choose: Box<dyn FnMut(usize)>,
+    on_close: Box<dyn FnMut()>,
+}
+
+impl ContextMenu {
+    pub(crate) fn new(
+        items: &[ContextMenuItem],
+        options: &ContextMenuOptions,
+        on_choose: Box<dyn FnMut(usize)>,
+        on_close: Box<dyn FnMut()>,
+    ) -> Self {
+        let rows: Vec<MenuRow> = items
+            .iter()
+            .map(|item| MenuRow {
+                label: item.label.clone(),
+                hint: item.hint.clone(),
+                enabled: item.enabled,
+            })
+            .collect();
+        let selected = rows.iter().position(|row| row.enabled);
+        Self {
+            rows,
+            theme: options.theme.clone(),
+            selected,
+            scroll: 0,
+            max_rows: options.max_rows.max(1),
+            on_choose,
+            on_close,
+        }
+    }
+
+    /// Cells the widest row needs, borders of padding included.
+    pub(crate) fn preferred_width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| {
+                let hint = row
+                    .hint
+                    .as_deref()
+                    .map_or(0, |hint| visible_width(hint) + 2);
+                visible_width(&row.label) + hint + 2
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Next enabled row after (or before) the selection, wrapping around.
+    fn step(&mut self, forward: bool) {
+        let len = self.rows.len();
+        let Some(start) = self.selected else {
+            return;
+        };
+        for offset in 1..=len {
+            let index = if forward {
+                (start + offset) % len
+            } else {
+                (start + len - offset % len) % len
+            };
+            if self.rows[index].enabled {
+                self.select(index);
+                return;
+            }
+        }
+    }
+
+    fn select_edge(&mut self, first: bool) {
+        let mut enabled = self.rows.iter().enumerate().filter(|(_, row)| row.enabled);
+        let index = if first {
+            enabled.next()
+        } else {
+            enabled.next_back()
+        };
+        if let Some((index, _)) = index {
+            self.select(index);
+        }
+    }
+
+    fn jump_to_letter(&mut self, letter: char) {
+        let letter = letter.to_lowercase().to_string();
+        let start = self.selected.map_or(0, |selected| selected + 1);
+        let len = self.rows.len();
+        let found = (0..len).map(|offset| (start + offset) % len).find(|index| {
+            let row = &self.rows[*index];
+            row.enabled && row.label.to_lowercase().starts_with(&letter)
+        });
+        if let Some(index) = found {
+            self.select(index);
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = Some(index);
+        if index < self.scroll {
+            self.scroll = index;
+        } else if index >= self.scroll + self.max_rows {
+            self.scroll = index + 1 - self.max_rows;
+        }
+    }
+}
+
+impl Component for ContextMenu {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        let inner = width.saturating_sub(2);
+        let mut lines = Vec::new();
+        for (index, row) in self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.max_rows)
+        {
+            let hint = row
+                .hint
+                .as_deref()
+                .map(|hint| truncate_to_width(hint, inner / 2, "…", false))
+                .unwrap_or_default();
+            let hint_width = visible_width(&hint);
+            let label_room = inner.saturating_sub(if hint_width > 0 { hint_width + 2 } else { 0 });
+            let label = truncate_to_width(&row.label, label_room, "…", false);
+            let padding = " ".repeat(inner.saturating_sub(visible_width(&label) + hint_width));
+            let line = if self.selected == Some(index) {
+                (self.theme.selected)(&format!(" {label}{padding}{hint} "))
+            } else if !row.enabled {
+                (self.theme.disabled)(&format!(" {label}{padding}{hint} "))
+            } else {
+                format!(
+                    " {}{padding}{} ",
+                    (self.theme.item)(&label),
+                    (self.theme.hint)(&hint)
+                )
+            };
+            lines.push(line);
+        }
+        if self.rows.len() > self.max_rows {
+            let end = (self.scroll + self.max_rows).min(self.rows.len());
+            lines.push((self.theme.hint)(&format!(
+                " ({}-{end} of {})",
+                self.scroll + 1,
+                self.rows.len()
+            )));
+        }
+        lines
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::Key { key_id, .. } => match key_id.as_str() {
+                "escape" => (self.on_close)(),
+                "enter" => {
+                    if let Some(index) = self.selected {
+                        (self.on_choose)(index);
+                    }
+                }
+                "up" | "shift+tab" => self.step(false),
+                "down" | "tab" => self.step(true),
+                "home" | "pageUp" => self.select_edge(true),
+                "end" | "pageDown" => self.select_edge(false),
+                _ => {}
+            },
+            InputEvent::Text { text, .. } => {
+                if let Some(letter) = text.chars().find(|ch| ch.is_alphanumeric()) {
+                    self.jump_to_letter(letter);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use super::{ContextMenu, ContextMenuItem, ContextMenuOptions, ContextMenuTheme};
+    use crate::core::component::Component;
+    use crate::core::input_event::parse_input_events;
+    use crate::runtime::tui::Command;
+
+    fn plain_options(max_rows: usize) -> ContextMenuOptions {
+        ContextMenuOptions {
+            keys: Vec::new(),
+            max_rows,
+            theme: ContextMenuTheme {
+                item: Arc::new(str::to_string),
+                selected: Arc::new(|text| format!("[{}]", text.trim())),
+                disabled: Arc::new(|text| format!("({})", text.trim())),
+                hint: Arc::new(str::to_string),
+            },
+        }
+    }
+
+    fn press(menu: &mut ContextMenu, data: &str) {
+        for event in parse_input_events(data, false) {
+            menu.handle_event(&event);
+        }
+    }
+
+    #[test]
+    fn navigation_skips_disabled_items_and_reports_the_choice() {
+        let items = vec![
+            ContextMenuItem::new("Copy", || Command::RequestRender).with_hint("ctrl+c"),
+            ContextMenuItem::new("Delete", || Command::RequestRender).disabled(),
+            ContextMenuItem::new("Retry", || Command::RequestRender),
+            ContextMenuItem::new("Rename", || Command::RequestRender),
+        ];
+        let chosen = Rc::new(RefCell::new(Vec::new()));
+        let closed = Rc::new(RefCell::new(0));
+        let chosen_sink = Rc::clone(&chosen);
+        let closed_sink = Rc::clone(&closed);
+        let mut menu = ContextMenu::new(
+            &items,
+            &plain_options(12),
+            Box::new(move |index| chosen_sink.borrow_mut().push(index)),
+            Box::new(move || *closed_sink.borrow_mut() += 1),
+        );
+        assert_eq!(menu.preferred_width(), 14);
+        assert_eq!(
+            menu.render(16),
+            vec![
+                "[Copy    ctrl+c]",
+                "(Delete)",
+                " Retry          ",
+                " Rename         "
+            ]
+        );
+
+        press(&mut menu, "\x1b[B");
+        assert_eq!(menu.render(16)[2], "[Retry]");
+        press(&mut menu, "\x1b[A");
+        press(&mut menu, "\x1b[A");
+        assert_eq!(menu.render(16)[3], "[Rename]");
+        press(&mut menu, "r");
+        press(&mut menu, "\r");
+        assert_eq!(*chosen.borrow(), vec![2]);
+
+        press(&mut menu, "\x1b");
+        assert_eq!(*closed.borrow(), 1);
+    }
+
+    #[test]
+    fn long_menus_scroll_to_keep_the_selection_visible() {
+        let items: Vec<ContextMenuItem> = (0..5)
+            .map(|index| ContextMenuItem::new(format!("Item {index}"), || Command::RequestRender))
+            .collect();
+        let mut menu =
+            ContextMenu::new(&items, &plain_options(2), Box::new(|_| {}), Box::new(|| {}));
+        press(&mut menu, "\x1b[F");
+        assert_eq!(
+            menu.render(12),
+            vec![" Item 3     ", "[Item 4]", " (4-5 of 5)"]
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Keyboard copy-mode over the frozen viewport.
+//!
+//! Mouse selection in a terminal sees the cells, not the frame: it breaks
+//! across rows the diff renderer redraws and picks up borders and padding.
+//! [`TuiRuntime::set_copy_mode`](crate::runtime::tui::TuiRuntime::set_copy_mode)
+//! registers keys (`f7` by default) that freeze the viewport instead, like
+//! tmux copy-mode. While frozen, renders are held back and the runtime owns
+//! the keyboard: arrows or `h`/`j`/`k`/`l` move the cursor, `0`/`$` (or
+//! Home/End) jump within the row, `g`/`G` to the first and last row, `v` (or
+//! Space) starts a character selection and `V` a line selection, `y` (or
+//! Enter) copies the selection without its styling through OSC 52 and leaves,
+//! Escape drops the selection or leaves, and `q` leaves.
+
+use std::sync::Arc;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::core::input_event::InputEvent;
+use crate::core::keybindings::KeyId;
+use crate::core::terminal_image::is_image_line;
+use crate::core::text::ansi::extract_ansi_code;
+use crate::core::text::width::grapheme_width;
+use crate::render::LineSize;
+
+#[derive(Clone)]
+pub struct CopyModeTheme {
+    /// Selected text.
+    pub selection: Arc<dyn Fn(&str) -> String>,
+    /// The cell under the copy cursor.
+    pub cursor: Arc<dyn Fn(&str) -> String>,
+}
+
+impl Default for CopyModeTheme {
+    fn default() -> Self {
+        Self {
+            selection: Arc::new(|text| format!("\x1b[7m{text}\x1b[27m")),
+            cursor: Arc::new(|text| format!("\x1b[4;7m{text}\x1b[24;27m")),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CopyModeOptions {
+    /// Keys that enter copy-mode.
+    pub keys: Vec<KeyId>,
+    pub theme: CopyModeTheme,
+}
+
+impl Default for CopyModeOptions {
+    fn default() -> Self {
+        Self {
+            keys: vec!["f7".to_string()],
+            theme: CopyModeTheme::default(),
+        }
+    }
+}
+
+/// What the runtime does after copy-mode handled a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CopyModeAction {
+    /// Redraw the frozen viewport.
+    Redraw,
+    /// Copy this text and leave copy-mode.
+    Copy(String),
+    /// Leave copy-mode without copying.
+    Exit,
+    Ignored,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectionKind {
+    Characters,
+    Lines,
+}
+
+struct CopyRow {
+    /// The row as drawn, shown while nothing on it is highlighted.
+    styled: String,
+    size: LineSize,
+    /// The row's text without styling, one entry per grapheme.
+    graphemes: Vec<String>,
+}
+
+/// A frozen frame and the copy cursor and selection over its viewport rows.
+pub(crate) struct CopySession {
+    /// Frame rows above the viewport, re-emitted unchanged.
+    scrollback: Vec<String>,
+    rows: Vec<CopyRow>,
+    theme: CopyModeTheme,
+    /// Row and grapheme index of the copy cursor.
+    cursor: (usize, usize),
+    anchor: Option<((usize, usize), SelectionKind)>,
+}
+
+impl CopySession {
+    /// Freezes `lines`, the last frame, of which the first `rows_above`
+    /// rows are in native scrollback. The cursor starts on the last
+    /// viewport row with text.
+    pub(crate) fn new(lines: Vec<String>, rows_above: usize, theme: CopyModeTheme) -> Self {
+        let rows_above = rows_above.min(lines.len());
+        let mut scrollback = lines;
+        let rows: Vec<CopyRow> = scrollback
+            .split_off(rows_above)
+            .into_iter()
+            .map(|styled| {
+                let size = LineSize::from_prefix(&styled);
+                let graphemes = if is_image_line(&styled) {
+                    Vec::new()
+                } else {
+                    plain_text(&styled)
+                        .graphemes(true)
+                        .map(str::to_string)
+                        .collect()
+                };
+                CopyRow {
+                    styled,
+                    size,
+                    graphemes,
+                }
+            })
+            .collect();
+        let last_row = rows
+            .iter()
+            .rposition(|row| row.graphemes.iter().any(|g| !g.trim().is_empty()))
+            .unwrap_or(rows.len().saturating_sub(1));
+        Self {
+            scrollback,
+            rows,
+            theme,
+            cursor: (last_row, 0),
+            anchor: None,
+        }
+    }
+
+    pub(crate) fn handle_event(&mut self, event: &InputEvent) -> CopyModeAction {
+        let key = match event {
+            InputEvent::Key { key_id, .. } => key_id.as_str(),
+            InputEvent::Text { text, .. } => text.as_str(),
+            _ => return CopyModeAction::Ignored,
+        };
+        let (row, col) = self.cursor;
+        match key {
+            "left" | "h" => self.move_to(row, col.saturating_sub(1)),
+            "right" | "l" => self.move_to(row, col + 1),
+            "up" | "k" => self.move_to(row.saturating_sub(1), col),
+            "down" | "j" => self.move_to(row + 1, col),
+            "home" | "0" => self.move_to(row, 0),
+            "end" | "$" => self.move_to(row, usize::MAX),
+            "g" => self.move_to(0, col),
+            "G" => self.move_to(usize::MAX, col),
+            "v" | "space" | " " => self.toggle_selection(SelectionKind::Characters),
+            "V" => self.toggle_selection(SelectionKind::Lines),
+            "y" | "enter" => {
+                return match self.selected_text() {
+                    Some(text) => CopyModeAction::Copy(text),
+                    None => CopyModeAction::Exit,
+                }
+            }
+            "escape" if self.anchor.is_some() => self.anchor = None,
+            "escape" | "q" => return CopyModeAction::Exit,
+            _ => return CopyModeAction::Ignored,
+        }
+        CopyModeAction::Redraw
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        let row = row.min(self.rows.len().saturating_sub(1));
+        let len = self.rows.get(row).map_or(0, |row| row.graphemes.len());
+        self.cursor = (row, col.min(len.saturating_sub(1)));
+    }
+
+    fn toggle_selection(&mut self, kind: SelectionKind) {
+        self.anchor = match self.anchor {
+            Some((_, current)) if current == kind => None,
+            Some((anchor, _)) => Some((anchor, kind)),
+            None => Some((self.cursor, kind)),
+        };
+    }
+
+    /// The selected graphemes of `row` as a half-open range, if any.
+    fn selected_range(&self, row: usize) -> Option<(usize, usize)> {
+        let (anchor, kind) = self.anchor?;
+        let (start, end) = if anchor <= self.cursor {
+            (anchor, self.cursor)
+        } else {
+            (self.cursor, anchor)
+        };
+        if row < start.0 || row > end.0 {
+            return None;
+        }
+        let len = self.rows[row].graphemes.len();
+        if kind == SelectionKind::Lines {
+            return Some((0, len));
+        }
+        let from = if row == start.0 { start.1 } else { 0 };
+        let to = if row == end.0 { end.1 + 1 } else { len };
+        Some((from.min(len), to.min(len)))
+    }
+
+    /// The selection as plain text, rows joined by newlines with trailing
+    /// blanks trimmed.
+    pub(crate) fn selected_text(&self) -> Option<String> {
+        self.anchor?;
+        let rows: Vec<String> = (0..self.rows.len())
+            .filter_map(|row| {
+                let (from, to) = self.selected_range(row)?;
+                let text: String = self.rows[row].graphemes[from..to].concat();
+                Some(text.trim_end().to_string())
+            })
+            .collect();
+        Some(rows.join("\n"))
+    }
+
+    /// The frozen frame with the selection and copy cursor drawn in.
+    pub(crate) fn frame_lines(&self) -> Vec<String> {
+        let mut lines = self.scrollback.clone();
+        lines.extend((0..self.rows.len()).map(|row| self.render_row(row)));
+        lines
+    }
+
+    fn render_row(&self, row: usize) -> String {
+        let copy_row = &self.rows[row];
+        let selected = self.selected_range(row);
+        let cursor = (self.cursor.0 == row).then_some(self.cursor.1);
+        if (selected.is_none() && cursor.is_none()) || is_image_line(&copy_row.styled) {
+            return copy_row.styled.clone();
+        }
+        let mut line = copy_row.size.escape().to_string();
+        if copy_row.graphemes.is_empty() {
+            line.push_str(&(self.theme.cursor)(" "));
+            return line;
+        }
+        let mut run = String::new();
+        let mut run_selected = false;
+        for (index, grapheme) in copy_row.graphemes.iter().enumerate() {
+            if cursor == Some(index) {
+                flush_run(&mut line, &mut run, run_selected, &self.theme);
+                line.push_str(&(self.theme.cursor)(grapheme));
+                continue;
+            }
+            let is_selected = selected.is_some_and(|(from, to)| index >= from && index < to);
+            if is_selected != run_selected {
+                flush_run(&mut line, &mut run, run_selected, &self.theme);
+                run_selected = is_selected;
+            }
+            run.push_str(grapheme);
+        }
+        flush_run(&mut line, &mut run, run_selected, &self.theme);
+        line
+    }
+
+    /// Cells the copy cursor sits at, for tests.
+    #[cfg(test)]
+    fn cursor_cell(&self) -> (usize, usize) {
+        let (row, index) = self.cursor;
+        let col = self.rows[row].graphemes[..index]
+            .iter()
+            .map(|grapheme| grapheme_width(grapheme))
+            .sum();
+        (row, col)
+    }
+}
+
+fn flush_run(line: &mut String, run: &mut String, selected: bool, theme: &CopyModeTheme) {
+    if run.is_empty() {
+        return;
+    }
+    if selected {
+        line.push_str(&(theme.selection)(run));
+    } else {
+        line.push_str(run);
+    }
+    run.clear();
+}
+
+/// `line` without escape sequences or a line size prefix. Zero-width
+/// graphemes left over (such as stray control bytes) are dropped.
+pub(crate) fn plain_text(line: &str) -> String {
+    let size = LineSize::from_prefix(line);
+    let line = line.strip_prefix(size.escape()).unwrap_or(line);
+    let mut text = String::new();
+    let mut idx = 0;
+    while idx < line.len() {
+        if let Some(ansi) = extract_ansi_code(line, idx) {
+            idx += ansi.length;
+            continue;
+        }
+        let next = line[idx..]
+            .find('\x1b')
+            .map_or(line.len(), |offset| idx + offset.max(1));
+        text.extend(
+            line[idx..next]
+                .graphemes(true)
+                .filter(|grapheme| grapheme_width(grapheme) > 0),
+        );
+        idx = next;
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{plain_text, CopyModeAction, CopyModeTheme, CopySession};
+    use crate::core::input_event::parse_input_events;
+
+    fn plain_theme() -> CopyModeTheme {
+        CopyModeTheme {
+            selection: Arc::new(|text| format!("[{text}]")),
+            cursor: Arc::new(|text| format!("<{text}>")),
+        }
+    }
+
+    fn press(session: &mut CopySession, data: &str) -> CopyModeAction {
+        parse_input_events(data, false)
+            .iter()
+            .map(|event| session.handle_event(event))
+            .last()
+            .expect("an event")
+    }
+
+    #[test]
+    fn plain_text_drops_styles_links_and_line_sizes() {
+        assert_eq!(
+            plain_text("\x1b#6\x1b[1mbold\x1b[0m \x1b]8;;https://x\x07link\x1b]8;;\x07"),
+            "bold link"
+        );
+    }
+
+    #[test]
+    fn character_selection_spans_rows_and_copies_plain_text() {
+        let lines = vec![
+            "scrolled away".to_string(),
+            "\x1b[32mfirst row\x1b[39m".to_string(),
+            "second  ".to_string(),
+            "third".to_string(),
+            String::new(),
+        ];
+        let mut session = CopySession::new(lines, 1, plain_theme());
+        assert_eq!(session.cursor_cell(), (2, 0));
+
+        press(&mut session, "k");
+        press(&mut session, "k");
+        for _ in 0..6 {
+            press(&mut session, "l");
+        }
+        assert_eq!(press(&mut session, "v"), CopyModeAction::Redraw);
+        press(&mut session, "\x1b[B");
+        press(&mut session, "$");
+        assert_eq!(
+            session.frame_lines(),
+            vec!["scrolled away", "first [row]", "[second ]< >", "third", ""]
+        );
+        assert_eq!(
+            press(&mut session, "y"),
+            CopyModeAction::Copy("row\nsecond".to_string())
+        );
+    }
+
+    #[test]
+    fn line_selection_escape_and_quit() {
+        let lines = vec!["alpha".to_string(), "beta".to_string()];
+        let mut session = CopySession::new(lines, 0, plain_theme());
+        press(&mut session, "V");
+        press(&mut session, "g");
+        assert_eq!(session.selected_text().as_deref(), Some("alpha\nbeta"));
+        assert_eq!(press(&mut session, "\x1b"), CopyModeAction::Redraw);
+        assert_eq!(session.selected_text(), None);
+        assert_eq!(session.frame_lines(), vec!["<a>lpha", "beta"]);
+        assert_eq!(press(&mut session, "x"), CopyModeAction::Ignored);
+        assert_eq!(press(&mut session, "q"), CopyModeAction::Exit);
+    }
+}
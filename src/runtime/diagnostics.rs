@@ -0,0 +1,241 @@
+//! Runtime diagnostics.
+//!
+//! The runtime reports recoverable problems (a command naming a missing
+//! component, a failed capability cache write, a panicking component) as
+//! [`Diagnostic`]s instead of failing. Every code it emits is listed in
+//! [`DIAGNOSTIC_CODES`] with its level and meaning, so hosts can match on
+//! codes without scraping messages.
+//!
+//! Hosts receive them through
+//! [`TuiRuntime::set_on_diagnostic_event`](crate::runtime::tui::TuiRuntime::set_on_diagnostic_event)
+//! as structs, or through
+//! [`TuiRuntime::set_on_diagnostic`](crate::runtime::tui::TuiRuntime::set_on_diagnostic)
+//! as formatted lines (`[tape_tui][level][code] message`). With neither sink
+//! installed the formatted line goes to stderr.
+
+use std::fmt;
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticLevel {
+    /// Something did not work, but the runtime state is as intended (for
+    /// example, a cache write failed).
+    Warning,
+    /// A request was dropped or part of the UI stopped working.
+    Error,
+}
+
+impl DiagnosticLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A registered diagnostic code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticCode {
+    pub code: &'static str,
+    pub level: DiagnosticLevel,
+    pub description: &'static str,
+}
+
+const fn error(code: &'static str, description: &'static str) -> DiagnosticCode {
+    DiagnosticCode {
+        code,
+        level: DiagnosticLevel::Error,
+        description,
+    }
+}
+
+const fn warning(code: &'static str, description: &'static str) -> DiagnosticCode {
+    DiagnosticCode {
+        code,
+        level: DiagnosticLevel::Warning,
+        description,
+    }
+}
+
+/// Every code the runtime emits.
+pub const DIAGNOSTIC_CODES: &[DiagnosticCode] = &[
+    warning(
+        "capability_cache.write_failed",
+        "Learned terminal capabilities could not be saved; they are probed again next run.",
+    ),
+    warning(
+        "input.kitty_degraded",
+        "Kitty keyboard was enabled but keys arrive in legacy encodings; input is read as legacy.",
+    ),
+    warning(
+        "runtime.suspend_failed",
+        "Handing the terminal back to the shell for job control failed.",
+    ),
+    error(
+        "command.custom.failed",
+        "A custom command returned an error.",
+    ),
+    error(
+        "command.root_set.missing_component_id",
+        "A root set named an unregistered component; the root was left unchanged.",
+    ),
+    error(
+        "command.root_push.missing_component_id",
+        "A root push named an unregistered component.",
+    ),
+    error(
+        "command.footer_set.missing_component_id",
+        "A footer set named an unregistered component.",
+    ),
+    error(
+        "command.send_message.missing_component_id",
+        "A message was sent to an unregistered component and dropped.",
+    ),
+    error(
+        "command.show_surface.missing_component_id",
+        "A surface was shown for an unregistered component.",
+    ),
+    error(
+        "command.hide_surface.missing_surface_id",
+        "An unknown surface was hidden.",
+    ),
+    error(
+        "command.set_surface_hidden.missing_surface_id",
+        "The visibility of an unknown surface was changed.",
+    ),
+    error(
+        "command.update_surface_options.missing_surface_id",
+        "The options of an unknown surface were updated.",
+    ),
+    error(
+        "command.bring_surface_to_front.missing_surface_id",
+        "An unknown surface was brought to the front.",
+    ),
+    error(
+        "command.send_surface_to_back.missing_surface_id",
+        "An unknown surface was sent to the back.",
+    ),
+    error(
+        "command.raise_surface.missing_surface_id",
+        "An unknown surface was raised.",
+    ),
+    error(
+        "command.lower_surface.missing_surface_id",
+        "An unknown surface was lowered.",
+    ),
+    error(
+        "command.surface_transaction.missing_component_id",
+        "A surface transaction named an unregistered component; the transaction was dropped.",
+    ),
+    error(
+        "command.surface_transaction.missing_surface_id",
+        "A surface transaction named an unknown surface; the transaction was dropped.",
+    ),
+    error(
+        "command.context_menu.missing_anchor",
+        "A context menu was anchored to an unregistered component and not opened.",
+    ),
+    error(
+        "focus.missing_previous_component_id",
+        "The focused component was missing when focus moved.",
+    ),
+    error(
+        "focus.missing_target_component_id",
+        "Focus was moved to an unregistered component.",
+    ),
+    error(
+        "component.render_panicked",
+        "A component panicked while rendering and now shows a placeholder.",
+    ),
+    error(
+        "component.input_panicked",
+        "A component panicked while handling input and no longer receives it.",
+    ),
+];
+
+/// Looks up a registered code.
+pub fn diagnostic_code(code: &str) -> Option<&'static DiagnosticCode> {
+    DIAGNOSTIC_CODES.iter().find(|entry| entry.code == code)
+}
+
+/// One problem reported by the runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// A code from [`DIAGNOSTIC_CODES`].
+    pub code: &'static str,
+    pub level: DiagnosticLevel,
+    /// Human-readable detail.
+    pub message: String,
+    /// Structured detail, such as `("component_id", "3")`.
+    pub context: Vec<(&'static str, String)>,
+}
+
+impl Diagnostic {
+    /// A diagnostic with the registered level of `code`.
+    pub(crate) fn new(code: &'static str, message: impl Into<String>) -> Self {
+        let registered = diagnostic_code(code);
+        debug_assert!(registered.is_some(), "unregistered diagnostic code {code}");
+        Self {
+            code,
+            level: registered.map_or(DiagnosticLevel::Error, |entry| entry.level),
+            message: message.into(),
+            context: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_context(mut self, key: &'static str, value: impl ToString) -> Self {
+        self.context.push((key, value.to_string()));
+        self
+    }
+
+    /// The value of context entry `key`.
+    pub fn context_value(&self, key: &str) -> Option<&str> {
+        self.context
+            .iter()
+            .find(|(entry, _)| *entry == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[tape_tui][{}][{}] {}",
+            self.level.as_str(),
+            self.code,
+            self.message
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{diagnostic_code, Diagnostic, DiagnosticLevel, DIAGNOSTIC_CODES};
+
+    #[test]
+    fn registry_codes_are_unique_and_namespaced() {
+        let mut seen = HashSet::new();
+        for entry in DIAGNOSTIC_CODES {
+            assert!(seen.insert(entry.code), "duplicate code {}", entry.code);
+            assert!(entry.code.contains('.'), "unnamespaced code {}", entry.code);
+            assert!(!entry.description.is_empty());
+        }
+        assert_eq!(diagnostic_code("nope.unknown"), None);
+    }
+
+    #[test]
+    fn diagnostics_take_the_registered_level_and_format_as_lines() {
+        let diagnostic =
+            Diagnostic::new("runtime.suspend_failed", "no job control").with_context("errno", 38);
+        assert_eq!(diagnostic.level, DiagnosticLevel::Warning);
+        assert_eq!(diagnostic.context_value("errno"), Some("38"));
+        assert_eq!(
+            diagnostic.to_string(),
+            "[tape_tui][warning][runtime.suspend_failed] no job control"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Per-component error boundaries.
+//!
+//! The runtime calls `Component::render` and `Component::handle_event`
+//! inside `catch_unwind`. A component that panics is marked faulted: the
+//! runtime reports it through the diagnostics sink, draws a one-line
+//! placeholder where its output would go, and stops routing input to it
+//! (input bubbles past it as if it ignored the event). The rest of the UI
+//! keeps running, and the terminal is restored normally on stop.
+//! [`TuiRuntime::clear_component_fault`](crate::runtime::tui::TuiRuntime::clear_component_fault)
+//! lets the host retry a component it has repaired.
+//!
+//! The runtime's panic hook stands down inside the boundary: crash cleanup
+//! does not tear down terminal modes and the hook it wraps (by default the
+//! one printing to stderr) is not called, so the panic is reported only
+//! through diagnostics.
+
+use std::any::Any;
+
+use crate::core::text::utils::truncate_to_width;
+
+/// Style applied to the placeholder line of a faulted component.
+pub type ComponentErrorStyle = Box<dyn Fn(&str) -> String>;
+
+pub(crate) fn default_error_style() -> ComponentErrorStyle {
+    Box::new(|text| format!("\x1b[31m{text}\x1b[39m"))
+}
+
+/// The message a panic was raised with, for `panic!("...")` and
+/// `panic!("{}", ..)` payloads.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return (*message).to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    "panicked".to_string()
+}
+
+/// What a faulted component renders as.
+pub(crate) fn placeholder_lines(
+    style: &ComponentErrorStyle,
+    message: &str,
+    width: usize,
+) -> Vec<String> {
+    let first_line = message.lines().next().unwrap_or("");
+    let text = format!("⚠ component failed: {first_line}");
+    vec![style(&truncate_to_width(&text, width, "…", false))]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::{panic_message, placeholder_lines, ComponentErrorStyle};
+
+    #[test]
+    fn panic_payloads_become_single_line_placeholders() {
+        let literal = panic::catch_unwind(|| panic!("boom")).expect_err("panics");
+        assert_eq!(panic_message(literal.as_ref()), "boom");
+        let formatted =
+            panic::catch_unwind(|| panic!("index {} out of range\nmore", 3)).expect_err("panics");
+        assert_eq!(
+            panic_message(formatted.as_ref()),
+            "index 3 out of range\nmore"
+        );
+        let other = panic::catch_unwind(|| panic::panic_any(7u8)).expect_err("panics");
+        assert_eq!(panic_message(other.as_ref()), "panicked");
+
+        let style: ComponentErrorStyle = Box::new(|text| format!("<{text}>"));
+        assert_eq!(
+            placeholder_lines(&style, &panic_message(formatted.as_ref()), 30),
+            vec!["<⚠ component failed: index 3 \x1b[0m…>"]
+        );
+    }
+}
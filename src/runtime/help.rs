@@ -0,0 +1,310 @@
+//! Built-in help overlay.
+//!
+//! [`TuiRuntime::set_help_overlay`](crate::runtime::tui::TuiRuntime::set_help_overlay)
+//! registers a toggle key (`?` by default). The overlay is assembled when it
+//! opens: host commands grouped by category, slash commands, then the editor
+//! keymap grouped by context. Typing filters the list; Escape or the toggle
+//! key closes it.
+
+use std::sync::Arc;
+
+use crate::core::component::Component;
+use crate::core::input_event::InputEvent;
+use crate::core::keybindings::KeyId;
+use crate::core::text::utils::truncate_to_width;
+use crate::core::text::width::visible_width;
+
+#[derive(Clone)]
+pub struct HelpOverlayTheme {
+    pub heading: Arc<dyn Fn(&str) -> String>,
+    pub keys: Arc<dyn Fn(&str) -> String>,
+    pub description: Arc<dyn Fn(&str) -> String>,
+    pub hint: Arc<dyn Fn(&str) -> String>,
+}
+
+impl Default for HelpOverlayTheme {
+    fn default() -> Self {
+        Self {
+            heading: Arc::new(|text| format!("\x1b[1m{text}\x1b[22m")),
+            keys: Arc::new(|text| format!("\x1b[36m{text}\x1b[39m")),
+            description: Arc::new(str::to_string),
+            hint: Arc::new(|text| format!("\x1b[2m{text}\x1b[22m")),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HelpOverlayOptions {
+    /// Keys that open and close the overlay.
+    pub toggle_keys: Vec<KeyId>,
+    /// Most entry rows shown at once; the rest scroll.
+    pub max_rows: usize,
+    pub theme: HelpOverlayTheme,
+}
+
+impl Default for HelpOverlayOptions {
+    fn default() -> Self {
+        Self {
+            toggle_keys: vec!["?".to_string()],
+            max_rows: 20,
+            theme: HelpOverlayTheme::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    /// Display form of the keys, e.g. `"ctrl+a, home"` or `"/model"`.
+    pub keys: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpSection {
+    pub title: String,
+    pub entries: Vec<HelpEntry>,
+}
+
+enum HelpRow<'a> {
+    Heading(&'a str),
+    Entry(&'a HelpEntry),
+}
+
+/// Searchable cheat sheet over [`HelpSection`]s.
+pub struct HelpOverlay {
+    sections: Vec<HelpSection>,
+    theme: HelpOverlayTheme,
+    query: String,
+    scroll: usize,
+    max_rows: usize,
+    on_close: Option<Box<dyn FnMut()>>,
+}
+
+impl HelpOverlay {
+    pub fn new(sections: Vec<HelpSection>, theme: HelpOverlayTheme) -> Self {
+        Self {
+            sections,
+            theme,
+            query: String::new(),
+            scroll: 0,
+            max_rows: 20,
+            on_close: None,
+        }
+    }
+
+    pub fn set_max_rows(&mut self, max_rows: usize) {
+        self.max_rows = max_rows.max(1);
+    }
+
+    /// Called on Escape.
+    pub fn set_on_close(&mut self, handler: Option<Box<dyn FnMut()>>) {
+        self.on_close = handler;
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+        self.scroll = 0;
+    }
+
+    /// Matching rows: a section heading followed by its matching entries.
+    /// A query matching a section title keeps the whole section.
+    fn rows(&self) -> Vec<HelpRow<'_>> {
+        let query = self.query.to_lowercase();
+        let mut rows = Vec::new();
+        for section in &self.sections {
+            let title_matches = section.title.to_lowercase().contains(&query);
+            let mut entries = section
+                .entries
+                .iter()
+                .filter(|entry| {
+                    title_matches
+                        || entry.keys.to_lowercase().contains(&query)
+                        || entry.description.to_lowercase().contains(&query)
+                })
+                .peekable();
+            if entries.peek().is_none() {
+                continue;
+            }
+            rows.push(HelpRow::Heading(&section.title));
+            rows.extend(entries.map(HelpRow::Entry));
+        }
+        rows
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        let max_scroll = self.rows().len().saturating_sub(self.max_rows);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max_scroll);
+    }
+}
+
+impl Component for HelpOverlay {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        let rows = self.rows();
+        let header = if self.query.is_empty() {
+            "Help · type to search, esc to close".to_string()
+        } else {
+            format!("Search: {}", self.query)
+        };
+        let mut lines = vec![(self.theme.hint)(&truncate_to_width(
+            &header, width, "…", false,
+        ))];
+
+        if rows.is_empty() {
+            lines.push((self.theme.hint)("No matches"));
+            return lines;
+        }
+
+        let key_width = rows
+            .iter()
+            .filter_map(|row| match row {
+                HelpRow::Entry(entry) => Some(visible_width(&entry.keys)),
+                HelpRow::Heading(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+            .min(width / 2);
+        let scroll = self.scroll.min(rows.len().saturating_sub(self.max_rows));
+        for row in rows.iter().skip(scroll).take(self.max_rows) {
+            let line = match row {
+                HelpRow::Heading(title) => {
+                    (self.theme.heading)(&truncate_to_width(title, width, "…", false))
+                }
+                HelpRow::Entry(entry) => {
+                    let keys = truncate_to_width(&entry.keys, key_width, "…", false);
+                    let padding = " ".repeat(key_width.saturating_sub(visible_width(&keys)));
+                    let description = truncate_to_width(
+                        &entry.description,
+                        width.saturating_sub(key_width + 4),
+                        "…",
+                        false,
+                    );
+                    format!(
+                        "  {}{padding}  {}",
+                        (self.theme.keys)(&keys),
+                        (self.theme.description)(&description)
+                    )
+                }
+            };
+            lines.push(line);
+        }
+        if rows.len() > self.max_rows {
+            let end = (scroll + self.max_rows).min(rows.len());
+            lines.push((self.theme.hint)(&format!(
+                "({}-{end} of {})",
+                scroll + 1,
+                rows.len()
+            )));
+        }
+        lines
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::Key { key_id, .. } => match key_id.as_str() {
+                "escape" => {
+                    if let Some(handler) = self.on_close.as_mut() {
+                        handler();
+                    }
+                }
+                "backspace" => {
+                    self.query.pop();
+                    self.scroll = 0;
+                }
+                "up" => self.scroll_by(-1),
+                "down" => self.scroll_by(1),
+                "pageUp" => self.scroll_by(-(self.max_rows as isize)),
+                "pageDown" => self.scroll_by(self.max_rows as isize),
+                _ => {}
+            },
+            InputEvent::Text { text, .. } | InputEvent::Paste { text, .. } => {
+                self.query
+                    .extend(text.chars().filter(|ch| !ch.is_control()));
+                self.scroll = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{HelpEntry, HelpOverlay, HelpOverlayTheme, HelpSection};
+    use crate::core::component::Component;
+    use crate::core::input_event::parse_input_events;
+
+    fn plain_theme() -> HelpOverlayTheme {
+        HelpOverlayTheme {
+            heading: Arc::new(|text| format!("# {text}")),
+            keys: Arc::new(str::to_string),
+            description: Arc::new(str::to_string),
+            hint: Arc::new(str::to_string),
+        }
+    }
+
+    fn entry(keys: &str, description: &str) -> HelpEntry {
+        HelpEntry {
+            keys: keys.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn overlay_filters_entries_and_scrolls() {
+        let mut overlay = HelpOverlay::new(
+            vec![
+                HelpSection {
+                    title: "Slash commands".to_string(),
+                    entries: vec![entry("/model", "Switch model"), entry("/quit", "Exit")],
+                },
+                HelpSection {
+                    title: "Editing".to_string(),
+                    entries: vec![entry("ctrl+a, home", "Move to line start")],
+                },
+            ],
+            plain_theme(),
+        );
+        assert_eq!(
+            overlay.render(40),
+            vec![
+                "Help · type to search, esc to close",
+                "# Slash commands",
+                "  /model        Switch model",
+                "  /quit         Exit",
+                "# Editing",
+                "  ctrl+a, home  Move to line start",
+            ]
+        );
+
+        for event in parse_input_events("MOD", false) {
+            overlay.handle_event(&event);
+        }
+        assert_eq!(overlay.query(), "MOD");
+        assert_eq!(
+            overlay.render(40),
+            vec!["Search: MOD", "# Slash commands", "  /model  Switch model"]
+        );
+
+        overlay.set_query("editing");
+        assert_eq!(overlay.render(40).len(), 3);
+        overlay.set_query("nothing like this");
+        assert_eq!(
+            overlay.render(40),
+            vec!["Search: nothing like this", "No matches"]
+        );
+
+        overlay.set_query("");
+        overlay.set_max_rows(2);
+        for event in parse_input_events("\x1b[B", false) {
+            overlay.handle_event(&event);
+        }
+        let lines = overlay.render(40);
+        assert_eq!(lines[1], "  /model        Switch model");
+        assert_eq!(lines[3], "(2-3 of 5)");
+    }
+}
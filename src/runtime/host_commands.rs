@@ -0,0 +1,179 @@
+//! Host commands: named, key-bound actions registered with the runtime.
+//!
+//! Their keys are global. The runtime matches them before dispatching input
+//! to components and queues the [`Command`] the host command produces, so a
+//! host command shadows any widget binding on the same chord (see
+//! [`TuiRuntime::keybinding_conflicts`](crate::runtime::tui::TuiRuntime::keybinding_conflicts)).
+
+use crate::core::keybindings::{
+    normalize_key_id, KeyId, KeybindingContext, KeybindingHelpEntry, KeybindingOwner,
+};
+use crate::runtime::tui::Command;
+
+/// Id of the help overlay toggle (see
+/// [`TuiRuntime::set_help_overlay`](crate::runtime::tui::TuiRuntime::set_help_overlay)).
+pub const HELP_COMMAND_ID: &str = "help";
+/// Id of the context menu key (see
+/// [`TuiRuntime::set_context_menu`](crate::runtime::tui::TuiRuntime::set_context_menu)).
+pub const CONTEXT_MENU_COMMAND_ID: &str = "contextMenu";
+/// Id of the copy-mode key (see
+/// [`TuiRuntime::set_copy_mode`](crate::runtime::tui::TuiRuntime::set_copy_mode)).
+pub const COPY_MODE_COMMAND_ID: &str = "copyMode";
+/// Id of the pane focus key registered while the root is split.
+pub const NEXT_PANE_COMMAND_ID: &str = "pane.next";
+/// Id of the debug hook key, which is fixed to `ctrl+shift+d`.
+pub const DEBUG_ACTION_ID: &str = "debug";
+
+/// Every runtime action id with its description. All but
+/// [`DEBUG_ACTION_ID`] are host commands, so hosts can remap them with
+/// [`TuiRuntime::set_host_command_keys`](crate::runtime::tui::TuiRuntime::set_host_command_keys)
+/// once enabled.
+pub const RUNTIME_ACTIONS: &[(&str, &str)] = &[
+    (HELP_COMMAND_ID, "Show this help"),
+    (CONTEXT_MENU_COMMAND_ID, "Open the context menu"),
+    (COPY_MODE_COMMAND_ID, "Select and copy text on screen"),
+    (NEXT_PANE_COMMAND_ID, "Focus the other pane"),
+    (DEBUG_ACTION_ID, "Debug hook"),
+];
+
+/// The description of runtime action `id`.
+pub(crate) fn runtime_action_description(id: &str) -> &'static str {
+    RUNTIME_ACTIONS
+        .iter()
+        .find(|(action, _)| *action == id)
+        .map_or("", |(_, description)| description)
+}
+
+/// Keys the runtime intercepts itself: (id, key, description).
+pub(crate) const RUNTIME_KEYBINDINGS: &[(&str, &str, &str)] =
+    &[(DEBUG_ACTION_ID, "ctrl+shift+d", "Debug hook")];
+
+pub struct HostCommand {
+    pub id: String,
+    pub description: String,
+    /// Heading the help overlay lists the command under.
+    pub category: Option<String>,
+    pub keys: Vec<KeyId>,
+    run: Box<dyn FnMut() -> Command>,
+}
+
+impl HostCommand {
+    pub fn new(
+        id: impl Into<String>,
+        description: impl Into<String>,
+        run: impl FnMut() -> Command + 'static,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            description: description.into(),
+            category: None,
+            keys: Vec::new(),
+            run: Box::new(run),
+        }
+    }
+
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn with_key(mut self, key: impl Into<KeyId>) -> Self {
+        self.keys.push(key.into());
+        self
+    }
+
+    pub fn run(&mut self) -> Command {
+        (self.run)()
+    }
+}
+
+impl std::fmt::Debug for HostCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HostCommand")
+            .field("id", &self.id)
+            .field("description", &self.description)
+            .field("category", &self.category)
+            .field("keys", &self.keys)
+            .finish()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct HostCommandRegistry {
+    commands: Vec<HostCommand>,
+}
+
+impl HostCommandRegistry {
+    /// Adds `command`, replacing one with the same id in place.
+    pub(crate) fn register(&mut self, command: HostCommand) {
+        match self.commands.iter_mut().find(|slot| slot.id == command.id) {
+            Some(slot) => *slot = command,
+            None => self.commands.push(command),
+        }
+    }
+
+    /// Replaces the keys of command `id`; `false` if none is registered.
+    pub(crate) fn set_keys(&mut self, id: &str, keys: Vec<KeyId>) -> bool {
+        match self.commands.iter_mut().find(|command| command.id == id) {
+            Some(command) => {
+                command.keys = keys;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn unregister(&mut self, id: &str) -> bool {
+        let before = self.commands.len();
+        self.commands.retain(|command| command.id != id);
+        self.commands.len() != before
+    }
+
+    /// Runs the first command bound to `key_id`, if any.
+    pub(crate) fn run_for_key(&mut self, key_id: &str) -> Option<Command> {
+        let key_id = normalize_key_id(key_id);
+        self.commands
+            .iter_mut()
+            .find(|command| {
+                command
+                    .keys
+                    .iter()
+                    .any(|key| normalize_key_id(key) == key_id)
+            })
+            .map(HostCommand::run)
+    }
+
+    /// Runs the first command bound to the typed `text`, for printable
+    /// chords such as `?` that arrive as text rather than keys.
+    pub(crate) fn run_for_text(&mut self, text: &str) -> Option<Command> {
+        self.commands
+            .iter_mut()
+            .find(|command| command.keys.iter().any(|key| key == text))
+            .map(HostCommand::run)
+    }
+
+    pub(crate) fn commands(&self) -> &[HostCommand] {
+        &self.commands
+    }
+
+    /// Runtime keys followed by host commands, all in the global context.
+    pub(crate) fn help_entries(&self) -> Vec<(KeybindingContext, KeybindingHelpEntry)> {
+        let runtime =
+            RUNTIME_KEYBINDINGS
+                .iter()
+                .map(|(id, key, description)| KeybindingHelpEntry {
+                    owner: KeybindingOwner::Runtime(id),
+                    keys: vec![key.to_string()],
+                    description: description.to_string(),
+                });
+        let commands = self.commands.iter().map(|command| KeybindingHelpEntry {
+            owner: KeybindingOwner::Command(command.id.clone()),
+            keys: command.keys.clone(),
+            description: command.description.clone(),
+        });
+        runtime
+            .chain(commands)
+            .map(|entry| (KeybindingContext::Global, entry))
+            .collect()
+    }
+}
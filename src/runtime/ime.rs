@@ -1,7 +1,25 @@
-//! IME hardware cursor positioning.
+//! IME hardware cursor positioning and composition (preedit) rendering.
 
 use crate::core::cursor::CursorPos;
 use crate::core::output::TerminalCmd;
+use crate::core::terminal_image::is_image_line;
+use crate::core::text::utils::truncate_to_width;
+use crate::core::text::width::visible_width;
+use crate::render::surface::composite_line_at;
+
+/// Draws the in-progress composition `preedit` underlined over `line` at
+/// `col`, clipped to `width`. Returns the line and the column just after the
+/// preedit, where the hardware cursor goes while composing.
+pub fn overlay_preedit(line: &str, col: usize, preedit: &str, width: usize) -> (String, usize) {
+    let preedit = truncate_to_width(preedit, width.saturating_sub(col), "", false);
+    let preedit_width = visible_width(&preedit);
+    if preedit_width == 0 || is_image_line(line) {
+        return (line.to_string(), col);
+    }
+    let styled = format!("\x1b[4m{preedit}\x1b[24m");
+    let line = composite_line_at(line, &styled, col, preedit_width, width, is_image_line);
+    (line, col + preedit_width)
+}
 
 pub fn position_hardware_cursor(
     cursor_pos: Option<CursorPos>,
@@ -43,7 +61,8 @@ pub fn position_hardware_cursor(
 mod tests {
     use crate::core::cursor::CursorPos;
     use crate::core::output::TerminalCmd;
-    use crate::runtime::ime::position_hardware_cursor;
+    use crate::core::text::width::visible_width;
+    use crate::runtime::ime::{overlay_preedit, position_hardware_cursor};
 
     #[test]
     fn positions_hardware_cursor_with_row_and_col() {
@@ -60,4 +79,18 @@ mod tests {
             "unexpected cursor positioning cmds: {cmds:?}"
         );
     }
+
+    #[test]
+    fn preedit_is_drawn_underlined_at_the_cursor_and_clipped() {
+        let (line, col) = overlay_preedit("ab\x1b[1mcdefgh\x1b[0m", 2, "日本", 10);
+        assert_eq!(col, 6);
+        assert_eq!(visible_width(&line), 10);
+        assert!(line.starts_with("ab"), "{line:?}");
+        assert!(line.contains("\x1b[4m日本"), "{line:?}");
+        assert!(line.ends_with("gh\x1b[0m  "), "{line:?}");
+
+        let (line, col) = overlay_preedit("", 7, "日本語", 10);
+        assert_eq!(col, 9);
+        assert!(line.contains("\x1b[4m日"), "{line:?}");
+    }
 }
@@ -5,6 +5,43 @@
 
 use crate::core::cursor::CursorPos;
 
+/// How many terminal rows the runtime owns and redraws.
+///
+/// Lines above the viewport are left to the terminal's native scrollback, so
+/// a smaller viewport gives a compact prompt-style UI and `Terminal` gives a
+/// near-fullscreen layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewportHeight {
+    /// The full terminal height.
+    #[default]
+    Terminal,
+    /// Grows and shrinks with content, up to `max` rows.
+    Auto { max: u16 },
+    /// Always occupies `rows` rows, padding shorter content with blank lines.
+    Fixed(u16),
+}
+
+impl ViewportHeight {
+    /// Viewport rows for a terminal `terminal_rows` tall. A cap never
+    /// exceeds the terminal and never drops below one row.
+    pub(crate) fn rows(self, terminal_rows: usize) -> usize {
+        match self {
+            Self::Terminal => terminal_rows,
+            Self::Auto { max: rows } | Self::Fixed(rows) => {
+                (rows as usize).max(1).min(terminal_rows)
+            }
+        }
+    }
+
+    /// Rows the rendered content is padded to.
+    pub(crate) fn min_rows(self, terminal_rows: usize) -> usize {
+        match self {
+            Self::Fixed(_) => self.rows(terminal_rows),
+            Self::Terminal | Self::Auto { .. } => 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct InlineViewportState {
     total_lines: usize,
@@ -75,9 +112,21 @@ impl InlineViewportState {
 
 #[cfg(test)]
 mod tests {
-    use super::InlineViewportState;
+    use super::{InlineViewportState, ViewportHeight};
     use crate::core::cursor::CursorPos;
 
+    #[test]
+    fn viewport_height_caps_rows_to_terminal() {
+        assert_eq!(ViewportHeight::Terminal.rows(24), 24);
+        assert_eq!(ViewportHeight::Auto { max: 8 }.rows(24), 8);
+        assert_eq!(ViewportHeight::Auto { max: 80 }.rows(24), 24);
+        assert_eq!(ViewportHeight::Fixed(0).rows(24), 1);
+
+        assert_eq!(ViewportHeight::Auto { max: 8 }.min_rows(24), 0);
+        assert_eq!(ViewportHeight::Fixed(6).min_rows(24), 6);
+        assert_eq!(ViewportHeight::Fixed(60).min_rows(24), 24);
+    }
+
     #[test]
     fn follow_tail_anchor_tracks_latest_lines() {
         let mut state = InlineViewportState::default();
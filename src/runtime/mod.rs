@@ -1,17 +1,38 @@
 //! Runtime orchestration.
 
+mod command_reply;
 pub mod component_registry;
+pub mod context_menu;
+pub mod copy_mode;
+pub mod diagnostics;
+pub mod error_boundary;
+pub mod help;
+pub mod host_commands;
 pub mod ime;
 mod inline_viewport;
+pub mod split;
 pub mod surface;
 pub mod tui;
+mod worker_pool;
 
+pub use command_reply::{command_reply, CommandReply, ReplySender};
 pub use component_registry::ComponentId;
+pub use context_menu::{ContextMenuItem, ContextMenuOptions, ContextMenuTheme};
+pub use copy_mode::{CopyModeOptions, CopyModeTheme};
+pub use diagnostics::{Diagnostic, DiagnosticCode, DiagnosticLevel, DIAGNOSTIC_CODES};
+pub use help::{HelpEntry, HelpOverlay, HelpOverlayOptions, HelpOverlayTheme, HelpSection};
+pub use host_commands::{
+    HostCommand, CONTEXT_MENU_COMMAND_ID, COPY_MODE_COMMAND_ID, DEBUG_ACTION_ID, HELP_COMMAND_ID,
+    NEXT_PANE_COMMAND_ID, RUNTIME_ACTIONS,
+};
+pub use inline_viewport::ViewportHeight;
+pub use split::{SplitDirection, SplitPane};
 pub use surface::{
-    SurfaceAnchor, SurfaceId, SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions, SurfaceMargin,
-    SurfaceOptions, SurfaceSizeValue, SurfaceVisibility,
+    Rect, SurfaceAnchor, SurfaceId, SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions,
+    SurfaceMargin, SurfaceOptions, SurfacePlacement, SurfaceSizeValue, SurfaceVisibility,
 };
 pub use tui::{
-    Command, CustomCommand, CustomCommandCtx, CustomCommandError, RuntimeHandle,
-    RuntimeRenderTelemetrySnapshot, SurfaceHandle, SurfaceTransactionMutation, TerminalOp,
+    Command, CustomCommand, CustomCommandCtx, CustomCommandError, InputProtocol, MinimumSize,
+    RuntimeEvent, RuntimeHandle, RuntimeRenderTelemetrySnapshot, SurfaceHandle,
+    SurfaceTransactionMutation, TerminalOp,
 };
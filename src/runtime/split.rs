@@ -0,0 +1,158 @@
+//! Split-pane root layout.
+//!
+//! [`TuiRuntime::split_root`](crate::runtime::tui::TuiRuntime::split_root)
+//! divides the root into two panes, each a stack of components rendered like
+//! the unsplit root. The existing root becomes the first pane. A split root is
+//! a screen region rather than a growing transcript: each pane shows the last
+//! rows of its stack that fit the viewport, so nothing scrolls into native
+//! scrollback while split. Panes scroll their own content if they need to.
+
+use crate::core::text::utils::truncate_to_width;
+use crate::core::text::width::visible_width;
+use crate::runtime::component_registry::ComponentId;
+
+/// Smallest share of the split a pane can be resized to.
+const MIN_RATIO: f32 = 0.1;
+const MAX_RATIO: f32 = 1.0 - MIN_RATIO;
+
+/// How a split root arranges its panes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// Panes side by side, the first on the left, divided by a `│` column.
+    Horizontal,
+    /// Panes stacked, the first on top, divided by a `─` row.
+    Vertical,
+}
+
+/// One of the two panes of a split root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitPane {
+    First,
+    Second,
+}
+
+impl SplitPane {
+    pub fn other(self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+}
+
+/// Runtime state of a split root. The first pane's components stay in the
+/// runtime's root list; the second pane's live here.
+#[derive(Debug, Clone)]
+pub(crate) struct RootSplit {
+    pub(crate) direction: SplitDirection,
+    ratio: f32,
+    pub(crate) second: Vec<ComponentId>,
+    pub(crate) active: SplitPane,
+    /// Last component focused in each pane, restored when the pane is
+    /// focused again.
+    pub(crate) pane_focus: [Option<ComponentId>; 2],
+}
+
+impl RootSplit {
+    pub(crate) fn new(direction: SplitDirection, ratio: f32) -> Self {
+        Self {
+            direction,
+            ratio: clamp_ratio(ratio),
+            second: Vec::new(),
+            active: SplitPane::First,
+            pane_focus: [None, None],
+        }
+    }
+
+    /// Share of the split the first pane takes.
+    pub(crate) fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    pub(crate) fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = clamp_ratio(ratio);
+    }
+
+    /// Cells (columns or rows) of the first and second pane when `total`
+    /// cells are split, one going to the divider. Each pane keeps at least
+    /// one cell while `total` allows.
+    pub(crate) fn pane_sizes(&self, total: usize) -> (usize, usize) {
+        let available = total.saturating_sub(1);
+        if available < 2 {
+            return (available, 0);
+        }
+        let first = ((available as f32) * self.ratio).round() as usize;
+        let first = first.clamp(1, available - 1);
+        (first, available - first)
+    }
+}
+
+fn clamp_ratio(ratio: f32) -> f32 {
+    if ratio.is_nan() {
+        return 0.5;
+    }
+    ratio.clamp(MIN_RATIO, MAX_RATIO)
+}
+
+/// First row of the last `rows` rows of `lines`.
+pub(crate) fn tail_offset(lines: usize, rows: usize) -> usize {
+    lines.saturating_sub(rows)
+}
+
+/// Side-by-side rows: `left` padded to `left_width`, a divider, then `right`
+/// clipped to `right_width`. Both are already tail-clipped.
+pub(crate) fn join_columns(
+    left: &[String],
+    left_width: usize,
+    right: &[String],
+    right_width: usize,
+) -> Vec<String> {
+    let rows = left.len().max(right.len());
+    (0..rows)
+        .map(|row| {
+            let left = left.get(row).map(String::as_str).unwrap_or("");
+            let left = truncate_to_width(left, left_width, "", false);
+            let padding = " ".repeat(left_width.saturating_sub(visible_width(&left)));
+            let right = right.get(row).map(String::as_str).unwrap_or("");
+            let right = truncate_to_width(right, right_width, "", false);
+            format!("{left}\x1b[0m{padding}│{right}")
+        })
+        .collect()
+}
+
+/// Divider row between stacked panes.
+pub(crate) fn divider_row(width: usize) -> String {
+    "─".repeat(width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{join_columns, RootSplit, SplitDirection};
+
+    #[test]
+    fn pane_sizes_follow_the_ratio_and_keep_both_panes_visible() {
+        let mut split = RootSplit::new(SplitDirection::Horizontal, 0.5);
+        assert_eq!(split.pane_sizes(81), (40, 40));
+        split.set_ratio(0.25);
+        assert_eq!(split.pane_sizes(81), (20, 60));
+        split.set_ratio(2.0);
+        assert_eq!(split.ratio(), 0.9);
+        assert_eq!(split.pane_sizes(4), (2, 1));
+        assert_eq!(split.pane_sizes(2), (1, 0));
+        split.set_ratio(f32::NAN);
+        assert_eq!(split.ratio(), 0.5);
+    }
+
+    #[test]
+    fn columns_are_padded_and_clipped_around_the_divider() {
+        let left = vec!["transcript line".to_string(), "short".to_string()];
+        let right = vec!["fn main() {}".to_string()];
+        assert_eq!(
+            join_columns(&left, 8, &right, 6),
+            vec![
+                "transcri\x1b[0m\x1b[0m│fn mai\x1b[0m".to_string(),
+                "short\x1b[0m   │".to_string()
+            ]
+        );
+    }
+}
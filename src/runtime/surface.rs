@@ -4,6 +4,8 @@
 //! They provide deterministic lifecycle, visibility, input policy, and lane semantics
 //! while preserving inline-first rendering behavior.
 
+use std::time::Duration;
+
 use crate::render::surface as render_surface;
 use crate::runtime::component_registry::ComponentId;
 
@@ -23,6 +25,18 @@ impl SurfaceId {
     }
 }
 
+/// Resolved on-screen cell rectangle of a rendered surface.
+///
+/// `x`/`y` are the column and row from the top-left of the visible viewport
+/// (not the transcript), `height` the rows it actually rendered.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
 /// Dimension value represented as absolute cells or percent of terminal size.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SurfaceSizeValue {
@@ -65,6 +79,30 @@ pub enum SurfaceAnchor {
     LeftCenter,
     /// Right edge, vertically centered.
     RightCenter,
+    /// Next to where a component was drawn this render.
+    ///
+    /// Root components and components shown in surfaces lower in the stack
+    /// are tracked; children rendered inside another component are not. The
+    /// surface is skipped while its component is not on screen. `row`/`col`
+    /// are ignored; offsets still apply.
+    Component {
+        id: ComponentId,
+        placement: SurfacePlacement,
+    },
+}
+
+/// Side of an anchor component a [`SurfaceAnchor::Component`] surface opens on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfacePlacement {
+    /// Below the component, left edges aligned.
+    Below,
+    /// Above the component, left edges aligned.
+    Above,
+    /// Below if the surface fits there, otherwise the side with more rows.
+    Auto,
+    /// Like `Auto`, around the component's cursor cell and starting at the
+    /// cursor column; falls back to the component when it has no cursor.
+    Cursor,
 }
 
 /// Optional non-negative margins around surface layout bounds.
@@ -186,6 +224,8 @@ impl From<SurfaceAnchor> for render_surface::SurfaceAnchor {
             SurfaceAnchor::BottomCenter => Self::BottomCenter,
             SurfaceAnchor::LeftCenter => Self::LeftCenter,
             SurfaceAnchor::RightCenter => Self::RightCenter,
+            // Resolved to an explicit row/col by the runtime before layout.
+            SurfaceAnchor::Component { .. } => Self::TopLeft,
         }
     }
 }
@@ -281,6 +321,11 @@ pub struct SurfaceOptions {
     pub kind: SurfaceKind,
     /// Input routing behavior.
     pub input_policy: SurfaceInputPolicy,
+    /// Shortest time between renders of the surface's component. Frames
+    /// requested sooner reuse its previous lines, and the runtime schedules
+    /// one catch-up render for when the interval ends. `None` renders the
+    /// surface on every frame.
+    pub min_update_interval: Option<Duration>,
 }
 
 impl Default for SurfaceOptions {
@@ -289,6 +334,7 @@ impl Default for SurfaceOptions {
             layout: SurfaceLayoutOptions::default(),
             kind: SurfaceKind::default(),
             input_policy: SurfaceInputPolicy::default(),
+            min_update_interval: None,
         }
     }
 }
@@ -365,6 +411,7 @@ impl From<SurfaceLayoutOptions> for SurfaceOptions {
             layout,
             kind: SurfaceKind::Modal,
             input_policy: SurfaceInputPolicy::Capture,
+            min_update_interval: None,
         }
     }
 }
@@ -387,6 +434,28 @@ impl From<&SurfaceOptions> for SurfaceLayoutOptions {
     }
 }
 
+/// Rows free below and above `target` in a viewport `term_height` rows tall.
+pub(crate) fn anchored_room(target: Rect, term_height: usize) -> (usize, usize) {
+    let below = term_height.saturating_sub(target.y.saturating_add(target.height));
+    (below, target.y.min(term_height))
+}
+
+/// Whether a surface `surface_height` rows tall opens below `target`.
+pub(crate) fn anchored_below(
+    placement: SurfacePlacement,
+    surface_height: usize,
+    below: usize,
+    above: usize,
+) -> bool {
+    match placement {
+        SurfacePlacement::Below => true,
+        SurfacePlacement::Above => false,
+        SurfacePlacement::Auto | SurfacePlacement::Cursor => {
+            surface_height <= below || below >= above
+        }
+    }
+}
+
 /// Internal ordered lifecycle mutation applied to runtime-managed surfaces.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum SurfaceMutation {
@@ -449,6 +518,7 @@ impl SurfaceEntry {
 /// Render-time snapshot entry.
 #[derive(Clone, Copy)]
 pub(crate) struct SurfaceRenderEntry {
+    pub(crate) id: SurfaceId,
     pub(crate) component_id: ComponentId,
     pub(crate) options: Option<SurfaceOptions>,
 }
@@ -668,6 +738,7 @@ impl SurfaceState {
             .iter()
             .filter(|entry| entry.is_visible(columns, rows))
             .map(|entry| SurfaceRenderEntry {
+                id: entry.id,
                 component_id: entry.component_id,
                 options: entry.options,
             })
@@ -716,6 +787,7 @@ mod tests {
         let component_ids = build_component_ids(4);
         let entries = vec![
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(0),
                 component_id: component_ids[0],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Toast,
@@ -729,6 +801,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(1),
                 component_id: component_ids[1],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Drawer,
@@ -741,6 +814,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(2),
                 component_id: component_ids[2],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Corner,
@@ -752,6 +826,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(3),
                 component_id: component_ids[3],
                 options: None,
             },
@@ -790,6 +865,7 @@ mod tests {
         let component_ids = build_component_ids(3);
         let entries = vec![
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(0),
                 component_id: component_ids[0],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Toast,
@@ -801,6 +877,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(1),
                 component_id: component_ids[1],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Drawer,
@@ -812,6 +889,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(2),
                 component_id: component_ids[2],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Modal,
@@ -850,6 +928,7 @@ mod tests {
         let component_ids = build_component_ids(4);
         let entries = vec![
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(0),
                 component_id: component_ids[0],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Toast,
@@ -861,6 +940,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(1),
                 component_id: component_ids[1],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Corner,
@@ -872,6 +952,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(2),
                 component_id: component_ids[2],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::Drawer,
@@ -883,6 +964,7 @@ mod tests {
                 }),
             },
             SurfaceRenderEntry {
+                id: SurfaceId::from_raw(3),
                 component_id: component_ids[3],
                 options: Some(SurfaceOptions {
                     kind: SurfaceKind::AttachmentRow,
@@ -930,6 +1012,7 @@ mod tests {
             },
             kind: SurfaceKind::Modal,
             input_policy: SurfaceInputPolicy::Capture,
+            min_update_interval: None,
         };
 
         let adjusted = options.with_lane_reservations(2, 3);
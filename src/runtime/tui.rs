@@ -1,34 +1,65 @@
 //! TUI runtime.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::io;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Condvar, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 
-use crate::core::component::Component;
+use crate::core::autocomplete::SlashCommand;
+use crate::core::capability_cache::{CachedCapabilities, CapabilityCache};
+use crate::core::component::{Component, ComponentMessage};
 use crate::core::cursor::{CursorPos, CURSOR_MARKER};
-use crate::core::input::{is_kitty_query_response, KeyEventType};
-use crate::core::input_event::{parse_input_events, InputEvent};
-use crate::core::output::{osc_title_sequence, OutputGate, TerminalCmd};
+use crate::core::input::{is_kitty_query_response, is_legacy_only_sequence, KeyEventType};
+use crate::core::input_event::{parse_input_events, CompositionPhase, InputEvent};
+use crate::core::keybindings::{
+    detect_conflicts, group_help, EditorKeybindingsHandle, KeyId, KeybindingConflict,
+    KeybindingContext, KeybindingHelpEntry, KeybindingHelpSection,
+};
+use crate::core::output::{
+    notification_sequence, osc_clipboard_sequence, osc_title_sequence, OutputGate, TerminalCmd,
+};
+use crate::core::render_cache::RenderCache;
 use crate::core::terminal::Terminal;
 use crate::core::terminal_image::{
-    get_capabilities, is_image_line, set_cell_dimensions, CellDimensions, TerminalImageState,
+    get_capabilities, is_image_line, reset_capabilities_cache, set_capabilities,
+    set_cell_dimensions, CellDimensions, TerminalImageState,
 };
+use crate::core::text::utils::truncate_to_width;
+use crate::platform::process_terminal::suppress_panic_cleanup;
 use crate::render::renderer::DiffRenderer;
 use crate::render::surface::{composite_surfaces, resolve_surface_layout, RenderedSurface};
 use crate::render::Frame;
+use crate::runtime::command_reply::{command_reply, CommandReply, ReplySender};
 use crate::runtime::component_registry::{ComponentId, ComponentRegistry};
-use crate::runtime::ime::position_hardware_cursor;
-use crate::runtime::inline_viewport::InlineViewportState;
+use crate::runtime::context_menu::{ContextMenu, ContextMenuItem, ContextMenuOptions};
+use crate::runtime::copy_mode::{CopyModeAction, CopyModeOptions, CopySession};
+use crate::runtime::diagnostics::Diagnostic;
+use crate::runtime::error_boundary::{
+    default_error_style, panic_message, placeholder_lines, ComponentErrorStyle,
+};
+use crate::runtime::help::{HelpEntry, HelpOverlay, HelpOverlayOptions, HelpSection};
+use crate::runtime::host_commands::{
+    runtime_action_description, HostCommand, HostCommandRegistry, CONTEXT_MENU_COMMAND_ID,
+    COPY_MODE_COMMAND_ID, HELP_COMMAND_ID, NEXT_PANE_COMMAND_ID,
+};
+use crate::runtime::ime::{overlay_preedit, position_hardware_cursor};
+use crate::runtime::inline_viewport::{InlineViewportState, ViewportHeight};
+use crate::runtime::split::{
+    divider_row, join_columns, tail_offset, RootSplit, SplitDirection, SplitPane,
+};
 #[cfg(test)]
 use crate::runtime::surface::SurfaceKind;
 use crate::runtime::surface::{
-    allocate_surface_budgets, measure_visible_surfaces, SurfaceAllocation, SurfaceEntry, SurfaceId,
-    SurfaceInputPolicy, SurfaceMeasurement, SurfaceMutation, SurfaceOptions, SurfaceRenderEntry,
-    SurfaceState,
+    allocate_surface_budgets, anchored_below, anchored_room, measure_visible_surfaces, Rect,
+    SurfaceAllocation, SurfaceAnchor, SurfaceEntry, SurfaceId, SurfaceInputPolicy,
+    SurfaceLayoutOptions, SurfaceMeasurement, SurfaceMutation, SurfaceOptions, SurfacePlacement,
+    SurfaceRenderEntry, SurfaceSizeValue, SurfaceState,
 };
+use crate::runtime::worker_pool::WorkerPool;
 
 const STOP_DRAIN_MAX_MS: u64 = 1000;
 const STOP_DRAIN_IDLE_MS: u64 = 50;
@@ -107,26 +138,79 @@ pub struct TuiRuntime<T: Terminal> {
     terminal_image_state: Arc<TerminalImageState>,
     components: ComponentRegistry,
     root: Vec<ComponentId>,
+    /// Rendered below the root at the bottom of the viewport; see
+    /// [`TuiRuntime::set_footer`].
+    footer: Option<ComponentId>,
+    /// Components whose `on_mount` ran and whose `on_unmount` has not: those
+    /// in the root, the second pane, the footer, or a visible surface.
+    mounted: Vec<ComponentId>,
     focused: Option<ComponentId>,
     renderer: DiffRenderer,
     surfaces: SurfaceState,
     on_debug: Option<Box<dyn FnMut()>>,
+    on_surface_layout: Option<Box<dyn FnMut(&[(SurfaceId, Rect)])>>,
+    host_commands: HostCommandRegistry,
+    /// Editor keymap consulted by keybinding introspection; the runtime
+    /// does not match editor actions itself.
+    editor_keybindings: Option<EditorKeybindingsHandle>,
+    /// Slash commands listed by the help overlay.
+    slash_commands: Vec<SlashCommand>,
+    help_overlay: Option<HelpOverlayOptions>,
+    /// The open help overlay's component.
+    help_overlay_component: Option<ComponentId>,
+    context_menu: Option<ContextMenuOptions>,
+    /// The open context menu: its popup component and the items it offers.
+    open_context_menu: Option<(ComponentId, Vec<ContextMenuItem>)>,
+    /// Builds each component's context menu items; see
+    /// [`TuiRuntime::set_context_menu_items`].
+    context_menu_items: HashMap<ComponentId, Box<dyn FnMut() -> Vec<ContextMenuItem>>>,
+    copy_mode: Option<CopyModeOptions>,
+    /// The frozen viewport while copy-mode is active.
+    copy_session: Option<CopySession>,
     on_diagnostic: Option<Box<dyn FnMut(&str)>>,
+    on_diagnostic_event: Option<Box<dyn FnMut(&Diagnostic)>>,
     clear_on_shrink: bool,
     show_hardware_cursor: bool,
+    /// In-progress IME composition, drawn at the cursor while set.
+    ime_preedit: Option<String>,
+    /// Host override for DEC line attributes; `None` uses detection.
+    line_attributes: Option<bool>,
     stopped: bool,
     wake: Arc<RuntimeWake>,
     coalesce_budget: CoalesceBudget,
     inline_viewport: InlineViewportState,
+    viewport_height: ViewportHeight,
+    minimum_size: Option<MinimumSize>,
+    render_caches: HashMap<ComponentId, RenderCache>,
+    /// Last render of each surface component with a `min_update_interval`.
+    throttled_renders: HashMap<ComponentId, ThrottledRender>,
+    /// Where each root component landed in the last rendered transcript.
+    root_frames: Vec<RootFrame>,
+    /// Set while the root is split into panes; see [`TuiRuntime::split_root`].
+    split: Option<RootSplit>,
+    /// Components that panicked, with the panic message; see
+    /// [`crate::runtime::error_boundary`].
+    component_faults: HashMap<ComponentId, String>,
+    component_error_style: ComponentErrorStyle,
     input_buffer: String,
     cell_size_query_pending: bool,
     kitty_keyboard_enabled: bool,
     kitty_enable_pending: bool,
+    /// Kitty keyboard was enabled but keys still arrive in legacy encodings;
+    /// input is parsed as legacy until a re-probe enables it again.
+    kitty_degraded: bool,
+    capability_cache: Option<CapabilityCache>,
+    /// What this run knows about the terminal, written back to the cache as
+    /// probes answer. `None` without a cache.
+    cached_capabilities: Option<(String, CachedCapabilities)>,
     render_telemetry: Arc<RuntimeRenderTelemetry>,
+    workers: Arc<WorkerPool>,
     #[cfg(all(unix, not(test)))]
     signal_hook_guard: Option<crate::platform::SignalHookGuard>,
     #[cfg(all(unix, not(test)))]
     panic_hook_guard: Option<crate::platform::PanicHookGuard>,
+    #[cfg(all(unix, not(test)))]
+    suspend_hook_guard: Option<crate::platform::SignalHookGuard>,
 }
 
 /// Handle used to mutate a shown surface entry.
@@ -214,8 +298,22 @@ impl std::fmt::Display for CustomCommandError {
 
 impl std::error::Error for CustomCommandError {}
 
-fn format_runtime_diagnostic(level: &str, code: &str, message: &str) -> String {
-    format!("[tape_tui][{level}][{code}] {message}")
+/// Focus-in report sent by terminals with focus reporting (`CSI ? 1004 h`)
+/// enabled. The runtime does not enable it; hosts that do get a kitty
+/// keyboard re-probe on focus-in.
+const TERMINAL_FOCUS_IN: &str = "\x1b[I";
+
+/// Placeholder lines shown while the terminal is smaller than `minimum`.
+fn too_small_notice(minimum: MinimumSize, width: usize, height: usize) -> Vec<String> {
+    let lines = [
+        "terminal too small".to_string(),
+        format!("need ≥ {}x{}", minimum.columns, minimum.rows),
+    ];
+    lines
+        .iter()
+        .take(height.max(1))
+        .map(|line| truncate_to_width(line, width, "", false))
+        .collect()
 }
 
 pub trait CustomCommand: Send + 'static {
@@ -223,6 +321,29 @@ pub trait CustomCommand: Send + 'static {
     fn apply(self: Box<Self>, ctx: &mut CustomCommandCtx) -> Result<(), CustomCommandError>;
 }
 
+/// Runs a closure on the runtime thread and replies with its result; see
+/// [`RuntimeHandle::query`].
+struct QueryCommand<F, R> {
+    query: F,
+    reply: ReplySender<R>,
+}
+
+impl<F, R> CustomCommand for QueryCommand<F, R>
+where
+    F: FnOnce(&mut CustomCommandCtx<'_>) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    fn name(&self) -> &'static str {
+        "query"
+    }
+
+    fn apply(self: Box<Self>, ctx: &mut CustomCommandCtx) -> Result<(), CustomCommandError> {
+        let Self { query, reply } = *self;
+        reply.send(query(ctx));
+        Ok(())
+    }
+}
+
 trait CustomCommandRuntimeOps {
     fn terminal(&mut self, op: TerminalOp) -> bool;
     fn focus_set(&mut self, target: Option<ComponentId>) -> Result<bool, CustomCommandError>;
@@ -253,12 +374,16 @@ trait CustomCommandRuntimeOps {
         component_id: ComponentId,
         f: &mut dyn FnMut(&mut dyn Component),
     ) -> Result<(), CustomCommandError>;
+    fn terminal_size(&self) -> (u16, u16);
+    fn focused(&self) -> Option<ComponentId>;
+    fn runtime_handle(&self) -> RuntimeHandle;
 }
 
 pub struct CustomCommandCtx<'a> {
     runtime: &'a mut dyn CustomCommandRuntimeOps,
     pending_title: &'a mut Option<String>,
     render_requested: &'a mut bool,
+    follow_ups: &'a mut VecDeque<Command>,
 }
 
 impl<'a> CustomCommandCtx<'a> {
@@ -266,14 +391,37 @@ impl<'a> CustomCommandCtx<'a> {
         runtime: &'a mut dyn CustomCommandRuntimeOps,
         pending_title: &'a mut Option<String>,
         render_requested: &'a mut bool,
+        follow_ups: &'a mut VecDeque<Command>,
     ) -> Self {
         Self {
             runtime,
             pending_title,
             render_requested,
+            follow_ups,
         }
     }
 
+    /// Schedules `command` to run in this same tick, after the commands
+    /// that were already queued.
+    pub fn dispatch(&mut self, command: Command) {
+        self.follow_ups.push_back(command);
+    }
+
+    /// A handle for completing work asynchronously: move it (with any
+    /// [`ReplySender`]) into a worker and dispatch the result later.
+    pub fn runtime_handle(&self) -> RuntimeHandle {
+        self.runtime.runtime_handle()
+    }
+
+    /// Terminal `(columns, rows)`.
+    pub fn terminal_size(&self) -> (u16, u16) {
+        self.runtime.terminal_size()
+    }
+
+    pub fn focused(&self) -> Option<ComponentId> {
+        self.runtime.focused()
+    }
+
     pub fn terminal(&mut self, op: TerminalOp) {
         if self.runtime.terminal(op) {
             self.request_render();
@@ -369,6 +517,17 @@ impl<'a> CustomCommandCtx<'a> {
             })?;
         Ok(result.expect("custom command with_component_mut closure did not run"))
     }
+
+    /// Delivers `message` to `target` immediately, before this command returns.
+    pub fn send(
+        &mut self,
+        target: ComponentId,
+        message: ComponentMessage,
+    ) -> Result<(), CustomCommandError> {
+        self.with_component_mut(target, |component| component.on_message(message))?;
+        self.request_render();
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -424,10 +583,61 @@ impl SurfaceTransactionMutation {
 pub enum Command {
     RequestRender,
     RequestStop,
+    /// Hand the terminal back to the shell and stop the process (job control),
+    /// as Ctrl+Z does in a cooked terminal; see [`TuiRuntime::suspend`].
+    Suspend,
+    /// Open the help overlay, or close it if open; see
+    /// [`TuiRuntime::set_help_overlay`].
+    ToggleHelp,
+    /// Close the help overlay if it is open.
+    CloseHelp,
+    /// Show a context menu next to `anchor`, replacing an open one; see
+    /// [`RuntimeHandle::open_context_menu`].
+    OpenContextMenu {
+        anchor: ComponentId,
+        items: Vec<ContextMenuItem>,
+    },
+    /// Open the focused component's items; see
+    /// [`TuiRuntime::set_context_menu_items`].
+    OpenFocusedContextMenu,
+    /// Close the context menu shown by the `menu` component and queue the
+    /// command of its item at `index`. Ignored once that menu was closed or
+    /// replaced.
+    ChooseContextMenuItem {
+        menu: ComponentId,
+        index: usize,
+    },
+    /// Close the open context menu without choosing.
+    CloseContextMenu,
+    /// Freeze the viewport for keyboard selection; see
+    /// [`TuiRuntime::set_copy_mode`].
+    EnterCopyMode,
+    /// Leave copy-mode without copying.
+    ExitCopyMode,
+    /// Focus the other pane of a split root; see [`TuiRuntime::focus_pane`].
+    FocusNextPane,
+    /// Grow the first pane of a split root by this share of the space
+    /// (negative shrinks it); see [`TuiRuntime::set_split_ratio`].
+    ResizeSplit(f32),
+    /// Feed IME composition progress from a host that receives it from the
+    /// platform; see [`TuiRuntime::handle_composition`].
+    Composition {
+        phase: CompositionPhase,
+        text: String,
+    },
     /// Update terminal title without forcing a render.
     SetTitle(String),
+    /// Copy text to the system clipboard via OSC 52 without forcing a render.
+    SetClipboard(String),
+    /// Post a desktop notification (or ring the bell) without forcing a render.
+    NotifyDesktop {
+        title: String,
+        body: String,
+    },
     RootSet(Vec<ComponentId>),
     RootPush(ComponentId),
+    /// Set or clear the footer; see [`TuiRuntime::set_footer`].
+    FooterSet(Option<ComponentId>),
     FocusSet(ComponentId),
     FocusClear,
     ShowSurface {
@@ -454,6 +664,11 @@ pub enum Command {
     },
     Terminal(TerminalOp),
     Custom(Box<dyn CustomCommand>),
+    /// Deliver a message to a component's `Component::on_message`.
+    SendMessage {
+        target: ComponentId,
+        message: ComponentMessage,
+    },
 }
 
 impl std::fmt::Debug for Command {
@@ -461,9 +676,43 @@ impl std::fmt::Debug for Command {
         match self {
             Self::RequestRender => write!(f, "RequestRender"),
             Self::RequestStop => write!(f, "RequestStop"),
+            Self::Suspend => write!(f, "Suspend"),
+            Self::ToggleHelp => write!(f, "ToggleHelp"),
+            Self::CloseHelp => write!(f, "CloseHelp"),
+            Self::OpenContextMenu { anchor, items } => f
+                .debug_struct("OpenContextMenu")
+                .field("anchor", anchor)
+                .field("items", items)
+                .finish(),
+            Self::OpenFocusedContextMenu => write!(f, "OpenFocusedContextMenu"),
+            Self::ChooseContextMenuItem { menu, index } => f
+                .debug_struct("ChooseContextMenuItem")
+                .field("menu", menu)
+                .field("index", index)
+                .finish(),
+            Self::CloseContextMenu => write!(f, "CloseContextMenu"),
+            Self::EnterCopyMode => write!(f, "EnterCopyMode"),
+            Self::ExitCopyMode => write!(f, "ExitCopyMode"),
+            Self::FocusNextPane => write!(f, "FocusNextPane"),
+            Self::ResizeSplit(delta) => f.debug_tuple("ResizeSplit").field(delta).finish(),
+            Self::Composition { phase, text } => f
+                .debug_struct("Composition")
+                .field("phase", phase)
+                .field("text", text)
+                .finish(),
             Self::SetTitle(title) => f.debug_tuple("SetTitle").field(title).finish(),
+            Self::SetClipboard(text) => f
+                .debug_struct("SetClipboard")
+                .field("len", &text.len())
+                .finish(),
+            Self::NotifyDesktop { title, body } => f
+                .debug_struct("NotifyDesktop")
+                .field("title", title)
+                .field("body", body)
+                .finish(),
             Self::RootSet(components) => f.debug_tuple("RootSet").field(components).finish(),
             Self::RootPush(component_id) => f.debug_tuple("RootPush").field(component_id).finish(),
+            Self::FooterSet(footer) => f.debug_tuple("FooterSet").field(footer).finish(),
             Self::FocusSet(component_id) => f.debug_tuple("FocusSet").field(component_id).finish(),
             Self::FocusClear => write!(f, "FocusClear"),
             Self::ShowSurface {
@@ -514,6 +763,10 @@ impl std::fmt::Debug for Command {
                 .finish(),
             Self::Terminal(op) => f.debug_tuple("Terminal").field(op).finish(),
             Self::Custom(command) => f.debug_tuple("Custom").field(&command.name()).finish(),
+            Self::SendMessage { target, .. } => f
+                .debug_struct("SendMessage")
+                .field("target", target)
+                .finish_non_exhaustive(),
         }
     }
 }
@@ -535,11 +788,43 @@ struct RuntimeWakeState {
     next_surface_id: u64,
     pending_inputs: Vec<String>,
     pending_resize: bool,
+    /// Quiet period for resize debouncing; `None` dispatches every resize.
+    resize_quiet: Option<Duration>,
+    /// When the latest debounced resize settles.
+    resize_settle_at: Option<Instant>,
+    /// A debounced resize arrived and the last frame should be re-clipped.
+    pending_interim_resize: bool,
     pending_commands: VecDeque<Command>,
     render_requested: bool,
+    /// A deferred render, e.g. the catch-up frame for a throttled surface.
+    render_due_at: Option<Instant>,
     stop_requested: bool,
 }
 
+impl RuntimeWakeState {
+    fn resize_settled(&self, now: Instant) -> bool {
+        self.resize_settle_at.is_some_and(|at| at <= now)
+    }
+
+    /// Turns a deferred render that came due into a render request.
+    fn promote_due_render(&mut self, now: Instant) {
+        if self.render_due_at.is_some_and(|at| at <= now) {
+            self.render_due_at = None;
+            self.render_requested = true;
+        }
+    }
+
+    fn has_pending_event(&self, now: Instant) -> bool {
+        !self.pending_inputs.is_empty()
+            || self.pending_resize
+            || self.pending_interim_resize
+            || self.resize_settled(now)
+            || !self.pending_commands.is_empty()
+            || self.render_requested
+            || self.render_due_at.is_some_and(|at| at <= now)
+    }
+}
+
 #[derive(Debug, Default)]
 struct RuntimeRenderTelemetry {
     last_render_output_bytes: AtomicUsize,
@@ -553,34 +838,246 @@ pub struct RuntimeRenderTelemetrySnapshot {
     pub pending_input_depth: usize,
 }
 
+/// Smallest terminal size the host's layout supports.
+///
+/// Below it the runtime renders a short "terminal too small" notice instead
+/// of the component tree, and restores the tree once a resize makes room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinimumSize {
+    pub columns: u16,
+    pub rows: u16,
+}
+
+/// Keyboard encoding the runtime is reading, as reported by
+/// [`TuiRuntime::input_protocol`].
+///
+/// Hosts use it to advertise key hints that actually work: legacy encodings
+/// cannot tell Shift+Enter from Enter, so a newline binding is better shown
+/// as Alt+Enter there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputProtocol {
+    /// Legacy encodings. Also reported while the kitty keyboard query is
+    /// unanswered.
+    Legacy,
+    /// The kitty keyboard protocol is enabled (or being enabled).
+    Kitty,
+    /// The kitty keyboard protocol was enabled but keys still arrive in
+    /// legacy encodings, typically because a multiplexer or a reattached
+    /// terminal dropped it. Input is parsed as legacy, and the runtime probes
+    /// again on the next resize or terminal focus-in.
+    Degraded,
+}
+
+/// Notification delivered to [`RuntimeHandle::subscribe`] receivers.
+///
+/// Events are published from the runtime thread as the change is applied, so
+/// a receiver observes them in the same order the runtime did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeEvent {
+    /// The terminal was resized; carries the new dimensions.
+    Resized { columns: u16, rows: u16 },
+    /// Keyboard focus moved between components.
+    FocusChanged {
+        previous: Option<ComponentId>,
+        focused: Option<ComponentId>,
+    },
+    /// A surface became visible (shown, or unhidden).
+    SurfaceShown(SurfaceId),
+    /// A surface was hidden or removed.
+    SurfaceHidden(SurfaceId),
+    /// A stop was requested; the run loop exits after the current tick.
+    StopRequested,
+}
+
+/// Transcript rows a root component rendered to, with its cursor.
+#[derive(Clone, Copy, Debug)]
+struct RootFrame {
+    id: ComponentId,
+    start_row: usize,
+    rows: usize,
+    /// First column and width; a pane of a side-by-side split is narrower
+    /// than the terminal.
+    col: usize,
+    width: usize,
+    cursor: Option<CursorPos>,
+}
+
+impl RootFrame {
+    /// The visible part of this frame in the viewport starting at transcript
+    /// row `viewport_start`.
+    fn in_viewport(&self, viewport_start: usize, height: usize) -> Option<ComponentFrame> {
+        let viewport_end = viewport_start.saturating_add(height);
+        let top = self.start_row.max(viewport_start);
+        let bottom = self.start_row.saturating_add(self.rows).min(viewport_end);
+        if top >= bottom {
+            return None;
+        }
+        let cursor = self.cursor.and_then(|pos| {
+            let row = self.start_row.saturating_add(pos.row);
+            (viewport_start..viewport_end)
+                .contains(&row)
+                .then(|| CursorPos {
+                    row: row - viewport_start,
+                    col: self.col + pos.col,
+                })
+        });
+        Some(ComponentFrame {
+            rect: Rect {
+                x: self.col,
+                y: top - viewport_start,
+                width: self.width,
+                height: bottom - top,
+            },
+            cursor,
+        })
+    }
+}
+
+/// `frame` with its first `offset` rows cut off, as a pane clipped to its
+/// tail shows it; `None` if nothing of it is left.
+fn clip_frame(frame: RootFrame, offset: usize) -> Option<RootFrame> {
+    let end = frame.start_row + frame.rows;
+    if end <= offset {
+        return None;
+    }
+    let start_row = frame.start_row.max(offset);
+    let cut = start_row - frame.start_row;
+    Some(RootFrame {
+        start_row: start_row - offset,
+        rows: end - start_row,
+        cursor: frame
+            .cursor
+            .filter(|pos| pos.row >= cut)
+            .map(|pos| CursorPos {
+                row: pos.row - cut,
+                col: pos.col,
+            }),
+        ..frame
+    })
+}
+
+/// A throttled surface's last render, reused until its interval elapses.
+#[derive(Clone, Debug)]
+struct ThrottledRender {
+    rendered_at: Instant,
+    width: usize,
+    lines: Vec<String>,
+    cursor: Option<CursorPos>,
+}
+
+/// Where a component was drawn this render, in viewport cells.
+#[derive(Clone, Copy, Debug)]
+struct ComponentFrame {
+    rect: Rect,
+    cursor: Option<CursorPos>,
+}
+
+impl ComponentFrame {
+    /// What a [`SurfaceAnchor::Component`] surface is placed around.
+    fn anchor_target(&self, placement: SurfacePlacement) -> Rect {
+        match (placement, self.cursor) {
+            (SurfacePlacement::Cursor, Some(cursor)) => Rect {
+                x: cursor.col,
+                y: cursor.row,
+                width: 1,
+                height: 1,
+            },
+            _ => self.rect,
+        }
+    }
+}
+
 #[derive(Default)]
 struct RuntimeWake {
     state: Mutex<RuntimeWakeState>,
     cvar: Condvar,
+    subscribers: Mutex<Vec<Sender<RuntimeEvent>>>,
+    /// Geometry of the surfaces drawn by the last composite pass.
+    surface_geometry: Mutex<Vec<(SurfaceId, Rect)>>,
 }
 
 impl RuntimeWake {
+    fn surface_geometry(&self, surface_id: SurfaceId) -> Option<Rect> {
+        let geometry = match self.surface_geometry.lock() {
+            Ok(geometry) => geometry,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        geometry
+            .iter()
+            .find(|(id, _)| *id == surface_id)
+            .map(|(_, rect)| *rect)
+    }
+
+    /// Replaces the stored geometry; `true` if it changed.
+    fn replace_surface_geometry(&self, next: Vec<(SurfaceId, Rect)>) -> bool {
+        let mut geometry = match self.surface_geometry.lock() {
+            Ok(geometry) => geometry,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if *geometry == next {
+            return false;
+        }
+        *geometry = next;
+        true
+    }
+
+    fn subscribe(&self) -> Receiver<RuntimeEvent> {
+        let (tx, rx) = mpsc::channel();
+        let mut subscribers = match self.subscribers.lock() {
+            Ok(subscribers) => subscribers,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        subscribers.push(tx);
+        rx
+    }
+
+    /// Sends `event` to every live subscriber, dropping disconnected ones.
+    fn publish(&self, event: RuntimeEvent) {
+        let mut subscribers = match self.subscribers.lock() {
+            Ok(subscribers) => subscribers,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        subscribers.retain(|tx| tx.send(event).is_ok());
+    }
+
     fn wait_for_event(&self) -> bool {
         let mut state = match self.state.lock() {
             Ok(state) => state,
             Err(poisoned) => poisoned.into_inner(),
         };
 
-        while !state.stop_requested
-            && state.pending_inputs.is_empty()
-            && !state.pending_resize
-            && state.pending_commands.is_empty()
-            && !state.render_requested
-        {
-            state = self
-                .cvar
-                .wait(state)
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while !state.stop_requested && !state.has_pending_event(Instant::now()) {
+            state = self.wait_on(state);
         }
 
         !state.stop_requested
     }
 
+    /// Waits for a notification, or until a pending resize settles or a
+    /// deferred render comes due.
+    fn wait_on<'a>(
+        &self,
+        state: MutexGuard<'a, RuntimeWakeState>,
+    ) -> MutexGuard<'a, RuntimeWakeState> {
+        let wake_at = match (state.resize_settle_at, state.render_due_at) {
+            (Some(settle_at), Some(due_at)) => Some(settle_at.min(due_at)),
+            (settle_at, due_at) => settle_at.or(due_at),
+        };
+        match wake_at {
+            Some(wake_at) => {
+                let timeout = wake_at.saturating_duration_since(Instant::now());
+                self.cvar
+                    .wait_timeout(state, timeout)
+                    .map(|(state, _)| state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner().0)
+            }
+            None => self
+                .cvar
+                .wait(state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        }
+    }
+
     fn enqueue_input(&self, data: String) {
         let mut state = match self.state.lock() {
             Ok(state) => state,
@@ -595,10 +1092,24 @@ impl RuntimeWake {
             Ok(state) => state,
             Err(poisoned) => poisoned.into_inner(),
         };
-        state.pending_resize = true;
+        match state.resize_quiet {
+            Some(quiet) => {
+                state.resize_settle_at = Some(Instant::now() + quiet);
+                state.pending_interim_resize = true;
+            }
+            None => state.pending_resize = true,
+        }
         self.cvar.notify_one();
     }
 
+    fn set_resize_quiet(&self, quiet: Option<Duration>) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        state.resize_quiet = quiet.filter(|quiet| !quiet.is_zero());
+    }
+
     fn request_render(&self) {
         let mut state = match self.state.lock() {
             Ok(state) => state,
@@ -608,6 +1119,18 @@ impl RuntimeWake {
         self.cvar.notify_one();
     }
 
+    /// Requests a render at `at` unless one is already due sooner.
+    fn schedule_render_at(&self, at: Instant) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if state.render_due_at.is_none_or(|due_at| at < due_at) {
+            state.render_due_at = Some(at);
+            self.cvar.notify_one();
+        }
+    }
+
     fn set_render_requested(&self) {
         let mut state = match self.state.lock() {
             Ok(state) => state,
@@ -621,11 +1144,24 @@ impl RuntimeWake {
             Ok(state) => state,
             Err(poisoned) => poisoned.into_inner(),
         };
-        let pending = state.pending_resize;
+        let settled = state.resize_settle_at.take_if(|at| *at <= Instant::now());
+        let pending = state.pending_resize || settled.is_some();
         state.pending_resize = false;
+        if pending && state.resize_settle_at.is_none() {
+            // The settled layout replaces any interim frame still queued.
+            state.pending_interim_resize = false;
+        }
         pending
     }
 
+    fn take_interim_resize(&self) -> bool {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        std::mem::take(&mut state.pending_interim_resize)
+    }
+
     fn enqueue_command(&self, command: Command) {
         let mut state = match self.state.lock() {
             Ok(state) => state,
@@ -664,16 +1200,18 @@ impl RuntimeWake {
             Ok(state) => state,
             Err(poisoned) => poisoned.into_inner(),
         };
+        state.promote_due_render(Instant::now());
         let requested = state.render_requested;
         state.render_requested = false;
         requested
     }
 
     fn peek_render_requested(&self) -> bool {
-        let state = match self.state.lock() {
+        let mut state = match self.state.lock() {
             Ok(state) => state,
             Err(poisoned) => poisoned.into_inner(),
         };
+        state.promote_due_render(Instant::now());
         state.render_requested
     }
 
@@ -691,6 +1229,8 @@ impl RuntimeWake {
             Err(poisoned) => poisoned.into_inner(),
         };
         state.pending_resize
+            || state.pending_interim_resize
+            || state.resize_settled(Instant::now())
             || !state.pending_inputs.is_empty()
             || !state.pending_commands.is_empty()
     }
@@ -702,9 +1242,12 @@ impl RuntimeWake {
         };
         state.stop_requested = false;
         state.pending_resize = false;
+        state.resize_settle_at = None;
+        state.pending_interim_resize = false;
         state.pending_inputs.clear();
         state.pending_commands.clear();
         state.render_requested = false;
+        state.render_due_at = None;
     }
 
     fn alloc_surface_id(&self) -> SurfaceId {
@@ -725,8 +1268,13 @@ impl RuntimeWake {
             Ok(state) => state,
             Err(poisoned) => poisoned.into_inner(),
         };
+        let already_requested = state.stop_requested;
         state.stop_requested = true;
         self.cvar.notify_all();
+        drop(state);
+        if !already_requested {
+            self.publish(RuntimeEvent::StopRequested);
+        }
     }
 
     #[cfg(test)]
@@ -737,19 +1285,11 @@ impl RuntimeWake {
         };
 
         let mut before_wait = Some(before_wait);
-        while !state.stop_requested
-            && state.pending_inputs.is_empty()
-            && !state.pending_resize
-            && state.pending_commands.is_empty()
-            && !state.render_requested
-        {
+        while !state.stop_requested && !state.has_pending_event(Instant::now()) {
             if let Some(before_wait) = before_wait.take() {
                 before_wait();
             }
-            state = self
-                .cvar
-                .wait(state)
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            state = self.wait_on(state);
         }
 
         !state.stop_requested
@@ -760,6 +1300,7 @@ impl RuntimeWake {
 pub struct RuntimeHandle {
     wake: Arc<RuntimeWake>,
     render_telemetry: Arc<RuntimeRenderTelemetry>,
+    workers: Arc<WorkerPool>,
 }
 
 impl RuntimeHandle {
@@ -771,6 +1312,85 @@ impl RuntimeHandle {
         self.wake.alloc_surface_id()
     }
 
+    /// Runs `work` on the runtime's worker pool and delivers its result to
+    /// `target`'s `Component::on_message`, which requests a render.
+    ///
+    /// For pure, expensive preparation (highlighting, image encoding, layout)
+    /// that would otherwise stall a tick. The pool starts on first use.
+    pub fn spawn_work<T: Send + 'static>(
+        &self,
+        target: ComponentId,
+        work: impl FnOnce() -> T + Send + 'static,
+    ) {
+        self.spawn_command(move || Command::SendMessage {
+            target,
+            message: Box::new(work()),
+        });
+    }
+
+    /// Where `surface_id` was drawn by the last render, in viewport cells.
+    /// `None` if it was not drawn (unknown, hidden, not visible at this size,
+    /// or not rendered yet).
+    pub fn surface_geometry(&self, surface_id: SurfaceId) -> Option<Rect> {
+        self.wake.surface_geometry(surface_id)
+    }
+
+    /// Runs `query` on the runtime thread at the next command stage and
+    /// returns a reply to wait on, for reading runtime state (terminal size,
+    /// focus, component state) from another thread.
+    ///
+    /// The closure gets the full [`CustomCommandCtx`], so it may also mutate
+    /// and dispatch follow-up commands. Do not wait on the reply from the
+    /// runtime thread; it would block the tick that runs the query.
+    pub fn query<R: Send + 'static>(
+        &self,
+        query: impl FnOnce(&mut CustomCommandCtx<'_>) -> R + Send + 'static,
+    ) -> CommandReply<R> {
+        let (reply, receiver) = command_reply();
+        self.dispatch(Command::Custom(Box::new(QueryCommand { query, reply })));
+        receiver
+    }
+
+    /// Runs `work` on the worker pool and dispatches the command it returns.
+    pub fn spawn_command(&self, work: impl FnOnce() -> Command + Send + 'static) {
+        let wake = Arc::clone(&self.wake);
+        self.workers.execute(move || wake.enqueue_command(work()));
+    }
+
+    /// Queue a desktop notification; see [`TuiRuntime::notify_desktop`].
+    pub fn notify_desktop(&self, title: impl Into<String>, body: impl Into<String>) {
+        self.dispatch(Command::NotifyDesktop {
+            title: title.into(),
+            body: body.into(),
+        });
+    }
+
+    /// Queue showing a context menu next to `anchor`; see
+    /// [`TuiRuntime::open_context_menu`].
+    ///
+    /// Widgets call this from their own event handlers (for example on a
+    /// key of their own) with items for whatever they have selected.
+    pub fn open_context_menu(&self, anchor: ComponentId, items: Vec<ContextMenuItem>) {
+        self.dispatch(Command::OpenContextMenu { anchor, items });
+    }
+
+    /// Queue `message` for `target`'s `Component::on_message`.
+    ///
+    /// Delivery happens on the runtime thread at the next command stage, so a
+    /// component can message another from inside its own event handler.
+    pub fn send(&self, target: ComponentId, message: ComponentMessage) {
+        self.dispatch(Command::SendMessage { target, message });
+    }
+
+    /// Returns a receiver of [`RuntimeEvent`]s published after this call.
+    ///
+    /// Intended for host background threads that need to react to resizes,
+    /// focus moves, or surface visibility without polling the terminal.
+    /// Dropping the receiver unsubscribes it on the next published event.
+    pub fn subscribe(&self) -> Receiver<RuntimeEvent> {
+        self.wake.subscribe()
+    }
+
     pub fn render_telemetry_snapshot(&self) -> RuntimeRenderTelemetrySnapshot {
         RuntimeRenderTelemetrySnapshot {
             out_bytes: self
@@ -834,6 +1454,16 @@ impl RuntimeHandle {
 }
 
 impl SurfaceHandle {
+    pub fn id(&self) -> SurfaceId {
+        self.id
+    }
+
+    /// Where this surface was drawn by the last render; see
+    /// [`RuntimeHandle::surface_geometry`].
+    pub fn geometry(&self) -> Option<Rect> {
+        self.runtime.surface_geometry(self.id)
+    }
+
     /// Hide (remove) this surface from the runtime stack.
     pub fn hide(&self) {
         self.runtime.dispatch(Command::HideSurface(self.id));
@@ -994,6 +1624,18 @@ impl<T: Terminal> CustomCommandRuntimeOps for TuiRuntime<T> {
         f(component.as_mut());
         Ok(())
     }
+
+    fn terminal_size(&self) -> (u16, u16) {
+        (self.terminal.columns(), self.terminal.rows())
+    }
+
+    fn focused(&self) -> Option<ComponentId> {
+        self.focused
+    }
+
+    fn runtime_handle(&self) -> RuntimeHandle {
+        TuiRuntime::runtime_handle(self)
+    }
 }
 
 impl<T: Terminal> TuiRuntime<T> {
@@ -1006,26 +1648,56 @@ impl<T: Terminal> TuiRuntime<T> {
             terminal_image_state: Arc::new(TerminalImageState::default()),
             components: ComponentRegistry::new(),
             root: Vec::new(),
+            footer: None,
+            mounted: Vec::new(),
             focused: None,
             renderer: DiffRenderer::new(),
             surfaces: SurfaceState::default(),
             on_debug: None,
+            on_surface_layout: None,
+            host_commands: HostCommandRegistry::default(),
+            editor_keybindings: None,
+            slash_commands: Vec::new(),
+            help_overlay: None,
+            help_overlay_component: None,
+            context_menu: None,
+            open_context_menu: None,
+            context_menu_items: HashMap::new(),
+            copy_mode: None,
+            copy_session: None,
             on_diagnostic: None,
+            on_diagnostic_event: None,
             clear_on_shrink,
             show_hardware_cursor,
+            ime_preedit: None,
+            line_attributes: None,
             stopped: true,
             wake: Arc::new(RuntimeWake::default()),
             coalesce_budget: CoalesceBudget::default(),
             inline_viewport: InlineViewportState::default(),
+            viewport_height: ViewportHeight::default(),
+            minimum_size: None,
+            render_caches: HashMap::new(),
+            throttled_renders: HashMap::new(),
+            root_frames: Vec::new(),
+            split: None,
+            component_faults: HashMap::new(),
+            component_error_style: default_error_style(),
             input_buffer: String::new(),
             cell_size_query_pending: false,
             kitty_keyboard_enabled: false,
             kitty_enable_pending: false,
+            kitty_degraded: false,
+            capability_cache: None,
+            cached_capabilities: None,
             render_telemetry: Arc::new(RuntimeRenderTelemetry::default()),
+            workers: Arc::new(WorkerPool::with_default_size()),
             #[cfg(all(unix, not(test)))]
             signal_hook_guard: None,
             #[cfg(all(unix, not(test)))]
             panic_hook_guard: None,
+            #[cfg(all(unix, not(test)))]
+            suspend_hook_guard: None,
         }
     }
 
@@ -1033,87 +1705,506 @@ impl<T: Terminal> TuiRuntime<T> {
         self.on_debug = handler;
     }
 
-    /// Install a diagnostics sink for runtime warnings/errors.
-    ///
-    /// Diagnostics are always emitted in release builds. If no sink is installed, they are written
-    /// to stderr.
-    pub fn set_on_diagnostic(&mut self, handler: Option<Box<dyn FnMut(&str)>>) {
-        self.on_diagnostic = handler;
+    /// Called after a render whose surface layout differs from the previous
+    /// one, with every drawn surface's geometry in stacking order
+    /// (bottom first). Use it to reposition dependent surfaces; changes made
+    /// by dispatching commands show on the next render.
+    pub fn set_on_surface_layout(&mut self, handler: Option<Box<dyn FnMut(&[(SurfaceId, Rect)])>>) {
+        self.on_surface_layout = handler;
     }
 
-    #[cfg(test)]
-    fn set_coalesce_budget_for_tests(&mut self, budget: CoalesceBudget) {
-        self.coalesce_budget = budget;
+    /// In-thread form of [`RuntimeHandle::surface_geometry`].
+    pub fn surface_geometry(&self, surface_id: SurfaceId) -> Option<Rect> {
+        self.wake.surface_geometry(surface_id)
     }
 
-    /// Enable or disable low-latency command/input coalescing.
-    ///
-    /// When enabled, `run_blocking_once` prefers one coalescing iteration per wake,
-    /// which increases render frequency for bursty incremental producers.
-    pub fn set_low_latency_coalescing(&mut self, enabled: bool) {
-        self.coalesce_budget = if enabled {
-            CoalesceBudget::low_latency()
-        } else {
-            CoalesceBudget::default()
-        };
+    /// Register a host command, replacing any with the same id. Its keys are
+    /// global: a key press matching one queues the command's result instead
+    /// of reaching components.
+    pub fn register_host_command(&mut self, command: HostCommand) {
+        self.host_commands.register(command);
     }
 
-    pub fn runtime_handle(&self) -> RuntimeHandle {
-        RuntimeHandle {
-            wake: Arc::clone(&self.wake),
-            render_telemetry: Arc::clone(&self.render_telemetry),
-        }
+    /// Remove a host command by id; `false` if none was registered.
+    pub fn unregister_host_command(&mut self, id: &str) -> bool {
+        self.host_commands.unregister(id)
     }
 
-    /// Feature-gated explicit escape hatch for raw terminal operations.
-    ///
-    /// This is intended for rare extensions that truly need direct access to the underlying
-    /// terminal write path (e.g. to emit an unsupported control sequence).
-    ///
-    /// # Safety and determinism
-    /// - This bypasses the runtime's single write gate, so it is only available when the crate is
-    ///   compiled with `--features unsafe-terminal-access`.
-    /// - The returned guard is *self-healing*: when dropped it requests a full redraw next render
-    ///   and requests a render so the viewport is repainted even if content is unchanged.
-    /// - The guard only exposes raw writes (not terminal lifecycle control).
-    /// - The guard does not flush the runtime output gate; the requested repaint is emitted on
-    ///   the next tick boundary.
-    /// - The runtime cannot query terminal state; callers must not scroll/clear/leave cursor state
-    ///   incompatible with subsequent diff renders.
-    ///
-    /// # Panics
-    /// Panics if called while the runtime is stopped or if there is pending output in the runtime
-    /// output gate (to avoid out-of-order writes).
-    #[cfg(feature = "unsafe-terminal-access")]
-    pub fn terminal_guard_unsafe(&mut self) -> TerminalGuard<'_, T> {
-        assert!(
-            !self.stopped,
-            "terminal_guard_unsafe() requires a started TuiRuntime; call start() first"
-        );
-        assert!(
-            self.output.is_empty(),
-            "terminal_guard_unsafe() requires an empty OutputGate; flush_pending_output()/tick first"
-        );
-        TerminalGuard { runtime: self }
+    /// Rebinds host command `id`, including the runtime's own commands in
+    /// [`RUNTIME_ACTIONS`](crate::runtime::host_commands::RUNTIME_ACTIONS)
+    /// once they are enabled; `false` if no such command is registered.
+    /// Enabling a runtime feature again restores the keys in its options.
+    pub fn set_host_command_keys(&mut self, id: &str, keys: Vec<KeyId>) -> bool {
+        self.host_commands.set_keys(id, keys)
     }
 
-    /// Set the terminal window/tab title.
-    ///
-    /// While running, this queues a title update that flushes on the runtime thread
-    /// without forcing a render. When stopped, it writes immediately.
-    pub fn set_title(&mut self, title: impl Into<String>) {
-        let title = title.into();
-        if self.stopped {
-            let mut output = OutputGate::new();
-            output.push(TerminalCmd::Bytes(osc_title_sequence(&title)));
-            output.flush(&mut self.terminal);
-            return;
+    pub fn host_commands(&self) -> &[HostCommand] {
+        self.host_commands.commands()
+    }
+
+    /// Share the editor keymap with the runtime so conflicts and help cover
+    /// editor actions too.
+    pub fn set_editor_keybindings(&mut self, keybindings: Option<EditorKeybindingsHandle>) {
+        self.editor_keybindings = keybindings;
+    }
+
+    fn keybinding_entries(&self) -> Vec<(KeybindingContext, KeybindingHelpEntry)> {
+        let mut entries = self.host_commands.help_entries();
+        if let Some(keybindings) = self.editor_keybindings.as_ref() {
+            let manager = keybindings
+                .lock()
+                .expect("editor keybindings lock poisoned");
+            entries.extend(manager.help_entries());
         }
-        self.output
-            .push(TerminalCmd::Bytes(osc_title_sequence(&title)));
+        entries
     }
 
-    /// Enqueue a show-cursor command.
+    /// Chords claimed twice in one context, and global chords (runtime keys
+    /// and host commands) that shadow editor actions.
+    pub fn keybinding_conflicts(&self) -> Vec<KeybindingConflict> {
+        detect_conflicts(&self.keybinding_entries())
+    }
+
+    /// Active bindings grouped by context, global bindings first.
+    pub fn keybinding_help(&self) -> Vec<KeybindingHelpSection> {
+        group_help(self.keybinding_entries())
+    }
+
+    /// Slash commands for the help overlay to list. The runtime does not
+    /// run them; they stay with the host's autocomplete provider.
+    pub fn set_slash_commands(&mut self, commands: Vec<SlashCommand>) {
+        self.slash_commands = commands;
+    }
+
+    /// Enable the built-in help overlay, toggled by `options.toggle_keys`
+    /// (registered as the `"help"` host command), or disable it with `None`.
+    ///
+    /// The toggle keys are global, so a plain `?` can no longer be typed
+    /// into focused inputs; hosts with a text editor focused usually pick a
+    /// chord instead.
+    pub fn set_help_overlay(&mut self, options: Option<HelpOverlayOptions>) {
+        self.close_help_overlay();
+        self.host_commands.unregister(HELP_COMMAND_ID);
+        if let Some(options) = options.as_ref() {
+            let mut command = HostCommand::new(
+                HELP_COMMAND_ID,
+                runtime_action_description(HELP_COMMAND_ID),
+                || Command::ToggleHelp,
+            )
+            .with_category("General");
+            command.keys = options.toggle_keys.clone();
+            self.host_commands.register(command);
+        }
+        self.help_overlay = options;
+    }
+
+    pub fn is_help_overlay_open(&self) -> bool {
+        self.help_overlay_component.is_some()
+    }
+
+    /// What the help overlay lists: host commands by category, slash
+    /// commands, then the editor keymap by context.
+    pub fn help_sections(&self) -> Vec<HelpSection> {
+        let mut sections: Vec<HelpSection> = Vec::new();
+        for command in self.host_commands.commands() {
+            let title = command.category.as_deref().unwrap_or("Commands");
+            let entry = HelpEntry {
+                keys: command.keys.join(", "),
+                description: command.description.clone(),
+            };
+            match sections.iter_mut().find(|section| section.title == title) {
+                Some(section) => section.entries.push(entry),
+                None => sections.push(HelpSection {
+                    title: title.to_string(),
+                    entries: vec![entry],
+                }),
+            }
+        }
+
+        if !self.slash_commands.is_empty() {
+            sections.push(HelpSection {
+                title: "Slash commands".to_string(),
+                entries: self
+                    .slash_commands
+                    .iter()
+                    .map(|command| HelpEntry {
+                        keys: format!("/{}", command.name),
+                        description: command.description.clone().unwrap_or_default(),
+                    })
+                    .collect(),
+            });
+        }
+
+        if let Some(keybindings) = self.editor_keybindings.as_ref() {
+            let manager = keybindings
+                .lock()
+                .expect("editor keybindings lock poisoned");
+            sections.extend(manager.help_sections().into_iter().map(|section| {
+                HelpSection {
+                    title: section.context.title().to_string(),
+                    entries: section
+                        .entries
+                        .into_iter()
+                        .map(|entry| HelpEntry {
+                            keys: entry.keys.join(", "),
+                            description: entry.description,
+                        })
+                        .collect(),
+                }
+            }));
+        }
+        sections
+    }
+
+    fn open_help_overlay(&mut self) {
+        let Some(options) = self.help_overlay.as_ref() else {
+            return;
+        };
+        let mut overlay = HelpOverlay::new(self.help_sections(), options.theme.clone());
+        overlay.set_max_rows(options.max_rows);
+        let handle = self.runtime_handle();
+        overlay.set_on_close(Some(Box::new(move || handle.dispatch(Command::CloseHelp))));
+
+        let component_id = self.register_component(overlay);
+        let surface_id = self.wake.alloc_surface_id();
+        let options = SurfaceOptions {
+            layout: SurfaceLayoutOptions {
+                width: Some(SurfaceSizeValue::Percent(80.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        if self.apply_show_surface(surface_id, component_id, Some(options), false) {
+            self.help_overlay_component = Some(component_id);
+            self.request_render();
+        }
+    }
+
+    fn close_help_overlay(&mut self) {
+        if let Some(component_id) = self.help_overlay_component.take() {
+            self.remove_component(component_id);
+        }
+    }
+
+    /// Set the context menu look, and the keys (registered as the
+    /// `"contextMenu"` host command) that open the focused component's
+    /// items (see [`Self::set_context_menu_items`]). `None` unregisters the keys;
+    /// menus opened with [`TuiRuntime::open_context_menu`] then use the
+    /// default options.
+    pub fn set_context_menu(&mut self, options: Option<ContextMenuOptions>) {
+        self.close_context_menu();
+        self.host_commands.unregister(CONTEXT_MENU_COMMAND_ID);
+        if let Some(options) = options.as_ref().filter(|options| !options.keys.is_empty()) {
+            let mut command = HostCommand::new(
+                CONTEXT_MENU_COMMAND_ID,
+                runtime_action_description(CONTEXT_MENU_COMMAND_ID),
+                || Command::OpenFocusedContextMenu,
+            )
+            .with_category("General");
+            command.keys = options.keys.clone();
+            self.host_commands.register(command);
+        }
+        self.context_menu = options;
+    }
+
+    /// Set how `component_id`'s context menu items are built when it has
+    /// focus and a context menu key is pressed; `None` removes them. An
+    /// empty list opens nothing.
+    pub fn set_context_menu_items(
+        &mut self,
+        component_id: ComponentId,
+        items: Option<Box<dyn FnMut() -> Vec<ContextMenuItem>>>,
+    ) {
+        match items {
+            Some(items) => {
+                self.context_menu_items.insert(component_id, items);
+            }
+            None => {
+                self.context_menu_items.remove(&component_id);
+            }
+        }
+    }
+
+    pub fn is_context_menu_open(&self) -> bool {
+        self.open_context_menu.is_some()
+    }
+
+    /// Show `items` in a popup next to `anchor` (at its cursor when it has
+    /// one), replacing an open menu. The popup takes focus until an item is
+    /// chosen or it is dismissed, then focus returns. Nothing opens for an
+    /// empty list or an anchor that is not registered.
+    pub fn open_context_menu(&mut self, anchor: ComponentId, items: Vec<ContextMenuItem>) {
+        self.close_context_menu();
+        if items.is_empty() {
+            return;
+        }
+        if self.components.get_mut(anchor).is_none() {
+            self.emit_runtime_diagnostic(
+                "command.context_menu.missing_anchor",
+                format!(
+                    "context menu anchor references missing component id {}",
+                    anchor.raw()
+                ),
+            );
+            return;
+        }
+        let options = self.context_menu.clone().unwrap_or_default();
+        let choose = self.runtime_handle();
+        let close = self.runtime_handle();
+        let menu_id = self.components.next_id();
+        let menu = ContextMenu::new(
+            &items,
+            &options,
+            Box::new(move |index| {
+                choose.dispatch(Command::ChooseContextMenuItem {
+                    menu: menu_id,
+                    index,
+                })
+            }),
+            Box::new(move || close.dispatch(Command::CloseContextMenu)),
+        );
+        let width = menu.preferred_width();
+
+        let component_id = self.register_component(menu);
+        debug_assert_eq!(component_id, menu_id);
+        let surface_id = self.wake.alloc_surface_id();
+        let options = SurfaceOptions {
+            layout: SurfaceLayoutOptions {
+                width: Some(SurfaceSizeValue::Absolute(width)),
+                anchor: Some(SurfaceAnchor::Component {
+                    id: anchor,
+                    placement: SurfacePlacement::Cursor,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        if self.apply_show_surface(surface_id, component_id, Some(options), false) {
+            self.open_context_menu = Some((component_id, items));
+            self.request_render();
+        } else {
+            self.remove_component(component_id);
+        }
+    }
+
+    fn open_focused_context_menu(&mut self) {
+        let Some(focused) = self.focused else {
+            return;
+        };
+        let Some(items) = self.context_menu_items.get_mut(&focused) else {
+            return;
+        };
+        let items = items();
+        self.open_context_menu(focused, items);
+    }
+
+    fn choose_context_menu_item(&mut self, menu: ComponentId, index: usize) -> Option<Command> {
+        if self.open_context_menu.as_ref().map(|(id, _)| *id) != Some(menu) {
+            return None;
+        }
+        let (component_id, mut items) = self.open_context_menu.take()?;
+        self.remove_component(component_id);
+        if index >= items.len() || !items[index].enabled {
+            return None;
+        }
+        Some(items.swap_remove(index).run())
+    }
+
+    fn close_context_menu(&mut self) {
+        if let Some((component_id, _)) = self.open_context_menu.take() {
+            self.remove_component(component_id);
+        }
+    }
+
+    /// Set the copy-mode look, and the keys (registered as the `"copyMode"`
+    /// host command) that enter it; `None` unregisters the keys and leaves
+    /// copy-mode. See [`crate::runtime::copy_mode`].
+    pub fn set_copy_mode(&mut self, options: Option<CopyModeOptions>) {
+        self.exit_copy_mode();
+        self.host_commands.unregister(COPY_MODE_COMMAND_ID);
+        if let Some(options) = options.as_ref().filter(|options| !options.keys.is_empty()) {
+            let mut command = HostCommand::new(
+                COPY_MODE_COMMAND_ID,
+                runtime_action_description(COPY_MODE_COMMAND_ID),
+                || Command::EnterCopyMode,
+            )
+            .with_category("General");
+            command.keys = options.keys.clone();
+            self.host_commands.register(command);
+        }
+        self.copy_mode = options;
+    }
+
+    pub fn is_copy_mode_active(&self) -> bool {
+        self.copy_session.is_some()
+    }
+
+    /// Freeze the last frame and hand the keyboard to copy-mode until the
+    /// selection is copied or copy-mode is left. Uses the default options
+    /// when [`Self::set_copy_mode`] was not called.
+    pub fn enter_copy_mode(&mut self) {
+        if self.copy_session.is_some() || self.renderer.previous_lines_len() == 0 {
+            return;
+        }
+        let theme = self.copy_mode.clone().unwrap_or_default().theme;
+        self.copy_session = Some(CopySession::new(
+            self.renderer.previous_frame_lines(),
+            self.renderer.rows_above_viewport(),
+            theme,
+        ));
+        self.request_render();
+    }
+
+    /// Leave copy-mode; the next render shows the live frame again.
+    pub fn exit_copy_mode(&mut self) {
+        if self.copy_session.take().is_some() {
+            self.request_render();
+        }
+    }
+
+    fn handle_copy_mode_event(&mut self, event: &InputEvent) {
+        let Some(session) = self.copy_session.as_mut() else {
+            return;
+        };
+        match session.handle_event(event) {
+            CopyModeAction::Redraw => self.request_render(),
+            CopyModeAction::Copy(text) => {
+                self.set_clipboard(text);
+                self.exit_copy_mode();
+            }
+            CopyModeAction::Exit => self.exit_copy_mode(),
+            CopyModeAction::Ignored => {}
+        }
+    }
+
+    /// Install a diagnostics sink for runtime warnings/errors.
+    ///
+    /// Diagnostics are always emitted in release builds. If no sink is installed, they are written
+    /// to stderr.
+    pub fn set_on_diagnostic(&mut self, handler: Option<Box<dyn FnMut(&str)>>) {
+        self.on_diagnostic = handler;
+    }
+
+    /// Install a sink receiving each diagnostic as a [`Diagnostic`], for
+    /// hosts that filter or route by code or level. It runs alongside the
+    /// [`Self::set_on_diagnostic`] sink; with either installed nothing is
+    /// written to stderr.
+    pub fn set_on_diagnostic_event(&mut self, handler: Option<Box<dyn FnMut(&Diagnostic)>>) {
+        self.on_diagnostic_event = handler;
+    }
+
+    #[cfg(test)]
+    fn set_coalesce_budget_for_tests(&mut self, budget: CoalesceBudget) {
+        self.coalesce_budget = budget;
+    }
+
+    /// Enable or disable low-latency command/input coalescing.
+    ///
+    /// When enabled, `run_blocking_once` prefers one coalescing iteration per wake,
+    /// which increases render frequency for bursty incremental producers.
+    pub fn set_low_latency_coalescing(&mut self, enabled: bool) {
+        self.coalesce_budget = if enabled {
+            CoalesceBudget::low_latency()
+        } else {
+            CoalesceBudget::default()
+        };
+    }
+
+    pub fn runtime_handle(&self) -> RuntimeHandle {
+        RuntimeHandle {
+            wake: Arc::clone(&self.wake),
+            render_telemetry: Arc::clone(&self.render_telemetry),
+            workers: Arc::clone(&self.workers),
+        }
+    }
+
+    /// Feature-gated explicit escape hatch for raw terminal operations.
+    ///
+    /// This is intended for rare extensions that truly need direct access to the underlying
+    /// terminal write path (e.g. to emit an unsupported control sequence).
+    ///
+    /// # Safety and determinism
+    /// - This bypasses the runtime's single write gate, so it is only available when the crate is
+    ///   compiled with `--features unsafe-terminal-access`.
+    /// - The returned guard is *self-healing*: when dropped it requests a full redraw next render
+    ///   and requests a render so the viewport is repainted even if content is unchanged.
+    /// - The guard only exposes raw writes (not terminal lifecycle control).
+    /// - The guard does not flush the runtime output gate; the requested repaint is emitted on
+    ///   the next tick boundary.
+    /// - The runtime cannot query terminal state; callers must not scroll/clear/leave cursor state
+    ///   incompatible with subsequent diff renders.
+    ///
+    /// # Panics
+    /// Panics if called while the runtime is stopped or if there is pending output in the runtime
+    /// output gate (to avoid out-of-order writes).
+    #[cfg(feature = "unsafe-terminal-access")]
+    pub fn terminal_guard_unsafe(&mut self) -> TerminalGuard<'_, T> {
+        assert!(
+            !self.stopped,
+            "terminal_guard_unsafe() requires a started TuiRuntime; call start() first"
+        );
+        assert!(
+            self.output.is_empty(),
+            "terminal_guard_unsafe() requires an empty OutputGate; flush_pending_output()/tick first"
+        );
+        TerminalGuard { runtime: self }
+    }
+
+    /// Set the terminal window/tab title.
+    ///
+    /// While running, this queues a title update that flushes on the runtime thread
+    /// without forcing a render. When stopped, it writes immediately.
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        let title = title.into();
+        if self.stopped {
+            let mut output = OutputGate::new();
+            output.push(TerminalCmd::Bytes(osc_title_sequence(&title)));
+            output.flush(&mut self.terminal);
+            return;
+        }
+        self.output
+            .push(TerminalCmd::Bytes(osc_title_sequence(&title)));
+    }
+
+    /// Copy text to the system clipboard using OSC 52.
+    ///
+    /// Like [`TuiRuntime::set_title`], this queues the sequence without forcing a render while
+    /// running and writes immediately when stopped. Delivery is best-effort: terminals without
+    /// OSC 52 support ignore the sequence.
+    pub fn set_clipboard(&mut self, text: impl AsRef<str>) {
+        let sequence = osc_clipboard_sequence(text.as_ref());
+        if self.stopped {
+            let mut output = OutputGate::new();
+            output.push(TerminalCmd::Bytes(sequence));
+            output.flush(&mut self.terminal);
+            return;
+        }
+        self.output.push(TerminalCmd::Bytes(sequence));
+    }
+
+    /// Post a desktop notification, e.g. when a long task finishes.
+    ///
+    /// Uses OSC 777 or OSC 9 as reported by
+    /// [`TerminalCapabilities::notifications`](crate::TerminalCapabilities) and
+    /// rings the bell on terminals without either. Queued and written like
+    /// [`TuiRuntime::set_clipboard`].
+    pub fn notify_desktop(&mut self, title: impl AsRef<str>, body: impl AsRef<str>) {
+        let sequence = self.notification_sequence(title.as_ref(), body.as_ref());
+        if self.stopped {
+            let mut output = OutputGate::new();
+            output.push(TerminalCmd::Bytes(sequence));
+            output.flush(&mut self.terminal);
+            return;
+        }
+        self.output.push(TerminalCmd::Bytes(sequence));
+    }
+
+    fn notification_sequence(&self, title: &str, body: &str) -> String {
+        let protocol = get_capabilities(self.terminal_image_state.as_ref()).notifications;
+        notification_sequence(protocol, title, body)
+    }
+
+    /// Enqueue a show-cursor command.
     ///
     /// This only enqueues terminal protocol bytes into the runtime output gate. The bytes are
     /// normally flushed at tick boundaries (e.g. `run_once` / `render_if_needed` / `render_now`).
@@ -1203,19 +2294,134 @@ impl<T: Terminal> TuiRuntime<T> {
             self.output.push(TerminalCmd::MoveUp((-lines) as usize));
         }
         self.renderer
-            .apply_out_of_band_move_by(lines, self.terminal.rows() as usize);
+            .apply_out_of_band_move_by(lines, self.viewport_rows() as usize);
     }
 
     pub fn terminal_rows(&self) -> u16 {
         self.terminal.rows()
     }
 
+    /// Sets how many rows the inline viewport occupies. Content above the
+    /// viewport is left to the terminal's scrollback.
+    pub fn set_viewport_height(&mut self, height: ViewportHeight) {
+        if self.viewport_height == height {
+            return;
+        }
+        self.viewport_height = height;
+        self.inline_viewport
+            .note_terminal_height(self.viewport_rows() as usize);
+        self.request_render();
+    }
+
+    /// Shorthand for [`ViewportHeight::Auto`]: the viewport grows with
+    /// content up to `rows` rows.
+    pub fn set_max_viewport_rows(&mut self, rows: u16) {
+        self.set_viewport_height(ViewportHeight::Auto { max: rows });
+    }
+
+    pub fn viewport_height(&self) -> ViewportHeight {
+        self.viewport_height
+    }
+
+    /// Sets the size below which a "terminal too small" notice replaces the
+    /// component tree; `None` (the default) always renders the tree.
+    pub fn set_minimum_size(&mut self, minimum: Option<MinimumSize>) {
+        self.minimum_size = minimum;
+        self.request_render();
+    }
+
+    /// Debounces resizes: while the terminal keeps resizing (for example during
+    /// a tiling window manager drag), the last frame is re-emitted clipped to
+    /// the new size, and components only see [`Component::on_resize`] and a
+    /// full render once no resize arrived for `quiet`. `None` (the default)
+    /// handles every resize immediately.
+    pub fn set_resize_debounce(&mut self, quiet: Option<Duration>) {
+        self.wake.set_resize_quiet(quiet);
+    }
+
+    /// Persists probed terminal capabilities across runs; takes effect on the
+    /// next [`TuiRuntime::start`].
+    ///
+    /// On start the entry for [`CapabilityCache::terminal_key`] seeds the
+    /// detected capabilities and cell size, and the kitty keyboard query and
+    /// cell size query are skipped when the entry already answers them.
+    /// Answers to probes that still run are written back. `None` (the
+    /// default) probes every run.
+    pub fn set_capability_cache(&mut self, cache: Option<CapabilityCache>) {
+        self.capability_cache = cache;
+    }
+
+    /// Forgets what the capability cache knows about the current terminal,
+    /// in memory (via `reset_capabilities_cache`) and on disk, so the next
+    /// start probes again and stores fresh answers.
+    pub fn reset_capability_cache(&mut self) {
+        reset_capabilities_cache(self.terminal_image_state.as_ref());
+        self.cached_capabilities = None;
+        let Some(cache) = self.capability_cache.as_ref() else {
+            return;
+        };
+        if let Err(err) = cache.forget(&CapabilityCache::terminal_key()) {
+            let path = cache.path().display().to_string();
+            self.emit_runtime_diagnostic(
+                "capability_cache.write_failed",
+                format!("could not write {path}: {err}"),
+            );
+        }
+    }
+
+    /// The unmet minimum size, if the terminal is currently below it.
+    fn unmet_minimum_size(&self) -> Option<MinimumSize> {
+        self.minimum_size.filter(|minimum| {
+            self.terminal.columns() < minimum.columns || self.terminal.rows() < minimum.rows
+        })
+    }
+
+    /// Rows currently owned by the inline viewport.
+    pub fn viewport_rows(&self) -> u16 {
+        self.viewport_height.rows(self.terminal.rows() as usize) as u16
+    }
+
     pub fn terminal_columns(&self) -> u16 {
         self.terminal.columns()
     }
 
+    /// Whether keys are read in the kitty keyboard encoding; false while
+    /// [`InputProtocol::Degraded`].
     pub fn kitty_protocol_active(&self) -> bool {
-        self.kitty_keyboard_enabled || self.kitty_enable_pending
+        self.input_protocol() == InputProtocol::Kitty
+    }
+
+    /// Keyboard encoding the runtime is currently reading. Changes request a
+    /// render, so hosts can read it while rendering key hints.
+    pub fn input_protocol(&self) -> InputProtocol {
+        if self.kitty_degraded {
+            InputProtocol::Degraded
+        } else if self.kitty_keyboard_enabled || self.kitty_enable_pending {
+            InputProtocol::Kitty
+        } else {
+            InputProtocol::Legacy
+        }
+    }
+
+    /// Keys arrived in a legacy encoding while kitty keyboard was enabled.
+    fn degrade_kitty_keyboard(&mut self) {
+        self.kitty_degraded = true;
+        self.remember_capabilities(|cached| cached.kitty_keyboard = false);
+        self.emit_runtime_diagnostic(
+            "input.kitty_degraded",
+            "kitty keyboard was enabled but keys arrive in legacy encodings; reading legacy input",
+        );
+        self.request_render();
+    }
+
+    /// Asks the terminal about kitty keyboard again unless it is known to
+    /// work, so a protocol lost (or gained) since start is picked up.
+    fn reprobe_kitty_keyboard(&mut self) {
+        if self.stopped || self.input_protocol() == InputProtocol::Kitty {
+            return;
+        }
+        self.output.push(TerminalCmd::KittyQuery);
+        self.flush_output();
     }
 
     /// Force the next render to redraw the entire viewport (without clearing scrollback).
@@ -1259,6 +2465,13 @@ impl<T: Terminal> TuiRuntime<T> {
         self.clear_on_shrink = enabled;
     }
 
+    /// Forces DEC double-width/double-height lines on or off, overriding
+    /// [`TerminalCapabilities::line_attributes`](crate::TerminalCapabilities).
+    pub fn set_line_attributes(&mut self, enabled: bool) {
+        self.line_attributes = Some(enabled);
+        self.request_render();
+    }
+
     pub fn terminal_image_state(&self) -> Arc<TerminalImageState> {
         Arc::clone(&self.terminal_image_state)
     }
@@ -1267,20 +2480,213 @@ impl<T: Terminal> TuiRuntime<T> {
         self.register_component_boxed(Box::new(component))
     }
 
+    /// Registers `component`. Its `Component::on_mount` runs once it is first
+    /// shown: added to the root, the second pane or the footer, or shown in a
+    /// visible surface.
     pub fn register_component_boxed(&mut self, component: Box<dyn Component>) -> ComponentId {
         self.components.register_boxed(component)
     }
 
-    pub fn set_root(&mut self, components: Vec<ComponentId>) {
-        self.root = components;
+    /// Removes a component from the runtime and returns it.
+    ///
+    /// Surfaces showing the component are hidden, it is dropped from the root
+    /// list (or stops being the footer) and loses focus, and
+    /// `Component::on_unmount` runs last if it was shown. The id is never
+    /// reused; later commands referencing it report missing-id diagnostics.
+    pub fn remove_component(&mut self, component_id: ComponentId) -> Option<Box<dyn Component>> {
+        self.components.get_mut(component_id)?;
+        let surface_ids: Vec<SurfaceId> = self
+            .surfaces
+            .entries
+            .iter()
+            .filter(|entry| entry.component_id == component_id)
+            .map(|entry| entry.id)
+            .collect();
+        for surface_id in surface_ids {
+            self.apply_hide_surface(surface_id);
+        }
+        self.root.retain(|id| *id != component_id);
+        if let Some(split) = self.split.as_mut() {
+            split.second.retain(|id| *id != component_id);
+        }
+        if self.footer == Some(component_id) {
+            self.footer = None;
+        }
+        if self.focused == Some(component_id) {
+            self.set_focused(None);
+        }
+        self.sync_mounted_components();
+        let component = self.components.remove(component_id)?;
+        self.render_caches.remove(&component_id);
+        self.throttled_renders.remove(&component_id);
+        self.component_faults.remove(&component_id);
+        self.context_menu_items.remove(&component_id);
+        self.request_render();
+        Some(component)
+    }
+
+    pub fn set_root(&mut self, components: Vec<ComponentId>) {
+        self.root = components;
+        self.sync_mounted_components();
         self.request_render();
     }
 
     pub fn push_root(&mut self, component: ComponentId) {
         self.root.push(component);
+        self.sync_mounted_components();
+        self.request_render();
+    }
+
+    /// Pins `component` to the bottom of the viewport, below the root.
+    ///
+    /// The footer is rendered at its own height (at most the viewport height
+    /// minus one row) after the root components, so it follows the end of
+    /// the transcript rather than scrolling with it; with a fixed viewport
+    /// height the root is padded so the footer sits on the last rows.
+    /// Surfaces still composite over it and may anchor to it. It receives
+    /// input only while focused. Replaces any previous footer.
+    pub fn set_footer(&mut self, component: ComponentId) {
+        self.footer = Some(component);
+        self.sync_mounted_components();
+        self.request_render();
+    }
+
+    /// Removes the footer set by [`Self::set_footer`]; the component stays
+    /// registered.
+    pub fn clear_footer(&mut self) {
+        if self.footer.take().is_some() {
+            self.sync_mounted_components();
+            self.request_render();
+        }
+    }
+
+    /// Splits the root into two panes laid out in `direction`, the first
+    /// taking `ratio` (clamped to `0.1..=0.9`) of the space. The current
+    /// root becomes the first pane and stays what [`Self::set_root`] and
+    /// [`Self::push_root`] change; fill the second with
+    /// [`Self::set_pane_root`]. Splitting again keeps both panes and only
+    /// changes the layout.
+    ///
+    /// While split, the root renders as a screen region: each pane shows the
+    /// last rows of its stack that fit the viewport (above the footer), and
+    /// the `"pane.next"` host command (`f6`) moves focus to the other pane.
+    pub fn split_root(&mut self, direction: SplitDirection, ratio: f32) {
+        match self.split.as_mut() {
+            Some(split) => {
+                split.direction = direction;
+                split.set_ratio(ratio);
+            }
+            None => {
+                let mut split = RootSplit::new(direction, ratio);
+                split.pane_focus[0] = self.focused.filter(|id| self.root.contains(id));
+                self.split = Some(split);
+                self.host_commands.register(
+                    HostCommand::new(
+                        NEXT_PANE_COMMAND_ID,
+                        runtime_action_description(NEXT_PANE_COMMAND_ID),
+                        || Command::FocusNextPane,
+                    )
+                    .with_category("General")
+                    .with_key("f6"),
+                );
+            }
+        }
+        self.invalidate_root_components();
+        self.request_render();
+    }
+
+    /// Joins a split root back into one stack of the first pane's
+    /// components and returns the second pane's, which stay registered.
+    /// Focus in the second pane moves to the first.
+    pub fn unsplit_root(&mut self) -> Vec<ComponentId> {
+        let Some(split) = self.split.take() else {
+            return Vec::new();
+        };
+        self.host_commands.unregister(NEXT_PANE_COMMAND_ID);
+        if self
+            .focused
+            .is_some_and(|focused| split.second.contains(&focused))
+        {
+            let target = split.pane_focus[0]
+                .filter(|id| self.root.contains(id))
+                .or_else(|| self.root.last().copied());
+            self.set_focused(target);
+        }
+        self.invalidate_root_components();
+        self.sync_mounted_components();
+        self.request_render();
+        split.second
+    }
+
+    pub fn is_root_split(&self) -> bool {
+        self.split.is_some()
+    }
+
+    /// Replaces the components of `pane` of a split root; the first pane is
+    /// the root list, as with [`Self::set_root`]. Ignored while unsplit.
+    pub fn set_pane_root(&mut self, pane: SplitPane, components: Vec<ComponentId>) {
+        match (pane, self.split.as_mut()) {
+            (_, None) => {}
+            (SplitPane::First, Some(_)) => self.set_root(components),
+            (SplitPane::Second, Some(split)) => {
+                split.second = components;
+                self.sync_mounted_components();
+                self.request_render();
+            }
+        }
+    }
+
+    /// Sets the first pane's share of a split root, clamped to `0.1..=0.9`.
+    pub fn set_split_ratio(&mut self, ratio: f32) {
+        if let Some(split) = self.split.as_mut() {
+            split.set_ratio(ratio);
+            self.request_render();
+        }
+    }
+
+    pub fn split_ratio(&self) -> Option<f32> {
+        self.split.as_ref().map(RootSplit::ratio)
+    }
+
+    /// The pane holding focus, or the last one that did.
+    pub fn active_pane(&self) -> Option<SplitPane> {
+        self.split.as_ref().map(|split| split.active)
+    }
+
+    /// Focuses the component last focused in `pane`, else its last
+    /// component; an empty pane becomes active without taking focus.
+    pub fn focus_pane(&mut self, pane: SplitPane) {
+        let Some(split) = self.split.as_mut() else {
+            return;
+        };
+        split.active = pane;
+        let (remembered, components) = match pane {
+            SplitPane::First => (split.pane_focus[0], &self.root),
+            SplitPane::Second => (split.pane_focus[1], &split.second),
+        };
+        let target = remembered
+            .filter(|id| components.contains(id))
+            .or_else(|| components.last().copied());
+        if let Some(target) = target {
+            self.set_focused(Some(target));
+        }
         self.request_render();
     }
 
+    /// Records `target` as the focus of the pane it is in.
+    fn note_pane_focus(&mut self, target: ComponentId) {
+        let Some(split) = self.split.as_mut() else {
+            return;
+        };
+        if self.root.contains(&target) {
+            split.active = SplitPane::First;
+            split.pane_focus[0] = Some(target);
+        } else if split.second.contains(&target) {
+            split.active = SplitPane::Second;
+            split.pane_focus[1] = Some(target);
+        }
+    }
+
     pub fn set_focus(&mut self, target: ComponentId) {
         self.dispatch_focus_surface_command(Command::FocusSet(target));
     }
@@ -1329,7 +2735,7 @@ impl<T: Terminal> TuiRuntime<T> {
     pub fn has_surface(&self) -> bool {
         self.surfaces.has_visible(
             self.terminal.columns() as usize,
-            self.terminal.rows() as usize,
+            self.viewport_rows() as usize,
         )
     }
 
@@ -1347,6 +2753,7 @@ impl<T: Terminal> TuiRuntime<T> {
         self.output.clear();
         self.kitty_keyboard_enabled = false;
         self.kitty_enable_pending = false;
+        self.kitty_degraded = false;
         self.wake.reset_for_start();
 
         // Mark running early so Drop can attempt cleanup if `Terminal::start()` panics.
@@ -1355,37 +2762,47 @@ impl<T: Terminal> TuiRuntime<T> {
         #[cfg(all(unix, not(test)))]
         self.install_cleanup_hooks();
 
-        let wake_input = Arc::clone(&self.wake);
-        let wake_resize = Arc::clone(&self.wake);
-        if let Err(err) = self.terminal.start(
-            Box::new(move |data| {
-                wake_input.enqueue_input(data);
-            }),
-            Box::new(move || {
-                wake_resize.signal_resize();
-            }),
-        ) {
+        if let Err(err) = self.start_terminal() {
             self.stopped = true;
             #[cfg(all(unix, not(test)))]
             self.uninstall_cleanup_hooks();
             return Err(err);
         }
 
+        let cached = self.load_cached_capabilities();
         self.output.push(TerminalCmd::BracketedPasteEnable);
-        self.output.push(TerminalCmd::KittyQuery);
+        if cached.is_some_and(|cached| cached.kitty_keyboard) {
+            self.output.push(TerminalCmd::KittyEnable);
+            self.kitty_enable_pending = true;
+        } else {
+            self.output.push(TerminalCmd::KittyQuery);
+        }
         self.output.push(TerminalCmd::HideCursor);
-        self.query_cell_size();
+        if cached.and_then(|cached| cached.cell_dimensions).is_none() {
+            self.query_cell_size();
+        }
         self.flush_output();
         self.request_render();
 
         Ok(())
     }
 
-    pub fn stop(&mut self) -> io::Result<()> {
-        if self.stopped {
-            return Ok(());
-        }
-        self.wake.request_stop();
+    fn start_terminal(&mut self) -> io::Result<()> {
+        let wake_input = Arc::clone(&self.wake);
+        let wake_resize = Arc::clone(&self.wake);
+        self.terminal.start(
+            Box::new(move |data| {
+                wake_input.enqueue_input(data);
+            }),
+            Box::new(move || {
+                wake_resize.signal_resize();
+            }),
+        )
+    }
+
+    /// Leaves the frame where the shell can continue below it: cursor after
+    /// the content and shown, bracketed paste and kitty keyboard off.
+    fn restore_terminal_modes(&mut self) {
         self.place_cursor_at_end();
         self.output.push(TerminalCmd::ShowCursor);
         self.output.push(TerminalCmd::BracketedPasteDisable);
@@ -1395,6 +2812,15 @@ impl<T: Terminal> TuiRuntime<T> {
         self.flush_output();
         self.kitty_keyboard_enabled = false;
         self.kitty_enable_pending = false;
+        self.kitty_degraded = false;
+    }
+
+    pub fn stop(&mut self) -> io::Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+        self.wake.request_stop();
+        self.restore_terminal_modes();
         self.terminal
             .drain_input(STOP_DRAIN_MAX_MS, STOP_DRAIN_IDLE_MS);
         let result = self.terminal.stop();
@@ -1404,6 +2830,50 @@ impl<T: Terminal> TuiRuntime<T> {
         result
     }
 
+    /// Suspends the process the way Ctrl+Z does in a cooked terminal.
+    ///
+    /// The terminal is restored first (cursor shown below the frame, cooked
+    /// mode, kitty keyboard and bracketed paste off), then
+    /// [`Terminal::suspend`] stops the process. Once the shell continues it,
+    /// raw mode and the protocols are re-enabled and the frame is drawn again
+    /// from scratch below whatever the shell printed meanwhile.
+    ///
+    /// In raw mode Ctrl+Z arrives as input rather than as SIGTSTP, so apps
+    /// bind it to [`Command::Suspend`] themselves. A SIGTSTP sent from outside
+    /// (`kill -TSTP`) is handled the same way. No-op when stopped.
+    pub fn suspend(&mut self) -> io::Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+        // A degraded protocol is probed again rather than re-enabled blindly.
+        let kitty_keyboard = self.input_protocol() == InputProtocol::Kitty;
+        self.restore_terminal_modes();
+        self.terminal.stop()?;
+
+        let suspended = self.terminal.suspend();
+
+        if let Err(err) = self.start_terminal() {
+            // Without raw mode the runtime cannot continue; shut down like `stop`.
+            self.wake.request_stop();
+            self.stopped = true;
+            #[cfg(all(unix, not(test)))]
+            self.uninstall_cleanup_hooks();
+            return Err(err);
+        }
+        self.output.push(TerminalCmd::BracketedPasteEnable);
+        if kitty_keyboard {
+            self.output.push(TerminalCmd::KittyEnable);
+            self.kitty_enable_pending = true;
+        } else {
+            self.output.push(TerminalCmd::KittyQuery);
+        }
+        self.output.push(TerminalCmd::HideCursor);
+        self.flush_output();
+        self.renderer.reset_for_external_clear_screen();
+        self.request_render();
+        suspended
+    }
+
     #[cfg(all(unix, not(test)))]
     fn install_cleanup_hooks(&mut self) {
         let cleanup = Arc::new(CrashCleanup::default());
@@ -1415,12 +2885,17 @@ impl<T: Terminal> TuiRuntime<T> {
         self.panic_hook_guard = Some(crate::platform::install_panic_hook(move || {
             panic_cleanup.run_best_effort()
         }));
+        let wake = Arc::clone(&self.wake);
+        self.suspend_hook_guard = Some(crate::platform::install_suspend_handler(move || {
+            wake.enqueue_command(Command::Suspend)
+        }));
     }
 
     #[cfg(all(unix, not(test)))]
     fn uninstall_cleanup_hooks(&mut self) {
         self.signal_hook_guard = None;
         self.panic_hook_guard = None;
+        self.suspend_hook_guard = None;
     }
 
     /// Block until at least one input/resize/render event is available, then
@@ -1484,6 +2959,10 @@ impl<T: Terminal> TuiRuntime<T> {
                 self.request_render();
                 did_work = true;
             }
+            if self.wake.take_interim_resize() {
+                self.render_interim_resize();
+                did_work = true;
+            }
 
             let inputs = self.wake.drain_inputs();
             if !inputs.is_empty() {
@@ -1535,6 +3014,9 @@ impl<T: Terminal> TuiRuntime<T> {
             self.dispatch_resize_event();
             self.request_render();
         }
+        if self.wake.take_interim_resize() {
+            self.render_interim_resize();
+        }
 
         let inputs = self.wake.drain_inputs();
 
@@ -1545,6 +3027,25 @@ impl<T: Terminal> TuiRuntime<T> {
         self.render_if_needed();
     }
 
+    /// Applies IME composition progress. The preedit is drawn underlined at
+    /// the focused component's cursor until the composition commits or is
+    /// cancelled, and the phase is routed to the input target as
+    /// [`InputEvent::Composition`] (a commit is what inserts the text).
+    pub fn handle_composition(&mut self, phase: CompositionPhase, text: &str) {
+        self.ime_preedit = match phase {
+            CompositionPhase::Start => Some(String::new()),
+            CompositionPhase::Update => Some(text.to_string()),
+            CompositionPhase::Commit | CompositionPhase::Cancel => None,
+        };
+        let event = InputEvent::Composition {
+            phase,
+            text: text.to_string(),
+        };
+        let (capture_target, fallback_target) = self.input_dispatch_targets();
+        self.dispatch_event_with_bubbling(&event, capture_target, fallback_target);
+        self.request_render();
+    }
+
     pub fn handle_input(&mut self, data: &str) {
         let mut data = data;
         let owned;
@@ -1561,18 +3062,38 @@ impl<T: Terminal> TuiRuntime<T> {
         }
 
         if is_kitty_query_response(data) {
-            if !self.kitty_keyboard_enabled && !self.kitty_enable_pending {
+            if self.kitty_degraded || (!self.kitty_keyboard_enabled && !self.kitty_enable_pending) {
                 self.output.push(TerminalCmd::KittyEnable);
                 self.kitty_enable_pending = true;
+                self.kitty_degraded = false;
+                self.request_render();
             }
+            self.remember_capabilities(|cached| cached.kitty_keyboard = true);
             return;
         }
 
-        let events = parse_input_events(data, self.kitty_keyboard_enabled);
+        if data == TERMINAL_FOCUS_IN {
+            self.reprobe_kitty_keyboard();
+        }
+        if self.kitty_keyboard_enabled && !self.kitty_degraded && is_legacy_only_sequence(data) {
+            self.degrade_kitty_keyboard();
+        }
+
+        let events = parse_input_events(data, self.kitty_protocol_active());
         if events.is_empty() {
             return;
         }
 
+        if self.copy_session.is_some() {
+            for event in events
+                .iter()
+                .filter(|event| event.key_event_type() != Some(KeyEventType::Release))
+            {
+                self.handle_copy_mode_event(event);
+            }
+            return;
+        }
+
         let (capture_target, fallback_target) = self.input_dispatch_targets();
 
         let mut dispatch_result = DispatchResult::Ignored;
@@ -1589,6 +3110,21 @@ impl<T: Terminal> TuiRuntime<T> {
                     }
                     continue;
                 }
+                if let Some(command) = self.host_commands.run_for_key(key_id) {
+                    self.wake.enqueue_command(command);
+                    continue;
+                }
+            }
+            if let InputEvent::Text {
+                text,
+                event_type: KeyEventType::Press,
+                ..
+            } = &event
+            {
+                if let Some(command) = self.host_commands.run_for_text(text) {
+                    self.wake.enqueue_command(command);
+                    continue;
+                }
             }
 
             let event_result =
@@ -1625,7 +3161,7 @@ impl<T: Terminal> TuiRuntime<T> {
 
     fn topmost_visible_capture_entry(&self) -> Option<SurfaceEntry> {
         let columns = self.terminal.columns() as usize;
-        let rows = self.terminal.rows() as usize;
+        let rows = self.viewport_rows() as usize;
         self.surfaces
             .entries
             .iter()
@@ -1638,7 +3174,11 @@ impl<T: Terminal> TuiRuntime<T> {
     }
 
     fn root_input_fallback(&self, excluded: Option<ComponentId>) -> Option<ComponentId> {
-        self.root
+        let components = match self.split.as_ref() {
+            Some(split) if split.active == SplitPane::Second => &split.second,
+            _ => &self.root,
+        };
+        components
             .iter()
             .rev()
             .copied()
@@ -1685,20 +3225,98 @@ impl<T: Terminal> TuiRuntime<T> {
             return DispatchResult::Ignored;
         };
 
-        if let InputEvent::Key {
-            event_type: KeyEventType::Release,
-            ..
-        } = event
-        {
-            if !component.wants_key_release() {
+        if let Some(event_type) = event.key_event_type() {
+            if !component.wants_key_events().contains(event_type) {
                 return DispatchResult::Ignored;
             }
         }
 
-        component.handle_event(event);
+        if self.component_faults.contains_key(&target_id) {
+            return DispatchResult::Ignored;
+        }
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            suppress_panic_cleanup(|| component.handle_event(event))
+        }));
+        if let Err(payload) = result {
+            self.record_component_fault(target_id, "input", panic_message(payload.as_ref()));
+        }
         DispatchResult::Consumed
     }
 
+    /// Renders `id` through its render cache inside an error boundary. A
+    /// faulted component renders as its placeholder.
+    fn render_component(
+        &mut self,
+        id: ComponentId,
+        width: usize,
+    ) -> (Vec<String>, Option<CursorPos>) {
+        if let Some(message) = self.component_faults.get(&id) {
+            return (
+                placeholder_lines(&self.component_error_style, message, width),
+                None,
+            );
+        }
+        let Some(component) = self.components.get_mut(id) else {
+            return (Vec::new(), None);
+        };
+        let cache = self.render_caches.entry(id).or_default();
+        match panic::catch_unwind(AssertUnwindSafe(|| {
+            suppress_panic_cleanup(|| cache.render(component.as_mut(), width))
+        })) {
+            Ok(rendered) => rendered,
+            Err(payload) => {
+                let message = panic_message(payload.as_ref());
+                let lines = placeholder_lines(&self.component_error_style, &message, width);
+                self.record_component_fault(id, "render", message);
+                (lines, None)
+            }
+        }
+    }
+
+    fn record_component_fault(&mut self, id: ComponentId, stage: &str, message: String) {
+        self.render_caches.remove(&id);
+        self.throttled_renders.remove(&id);
+        let code = match stage {
+            "render" => "component.render_panicked",
+            _ => "component.input_panicked",
+        };
+        self.emit_diagnostic(
+            Diagnostic::new(
+                code,
+                format!("component id {} panicked in {stage}: {message}", id.raw()),
+            )
+            .with_context("component_id", id.raw()),
+        );
+        self.component_faults.insert(id, message);
+        self.request_render();
+    }
+
+    /// The panic message of a component that failed in `render` or
+    /// `handle_event`; see [`crate::runtime::error_boundary`].
+    pub fn component_fault(&self, id: ComponentId) -> Option<&str> {
+        self.component_faults.get(&id).map(String::as_str)
+    }
+
+    /// Lets a faulted component render and receive input again. Returns
+    /// whether it was faulted.
+    pub fn clear_component_fault(&mut self, id: ComponentId) -> bool {
+        if self.component_faults.remove(&id).is_none() {
+            return false;
+        }
+        if let Some(component) = self.components.get_mut(id) {
+            component.invalidate();
+        }
+        self.request_render();
+        true
+    }
+
+    /// Style of the placeholder line drawn for a faulted component; `None`
+    /// restores the default red text.
+    pub fn set_component_error_style(&mut self, style: Option<ComponentErrorStyle>) {
+        self.component_error_style = style.unwrap_or_else(default_error_style);
+        self.request_render();
+    }
+
     pub fn request_render(&mut self) {
         self.wake.request_render();
     }
@@ -1728,14 +3346,23 @@ impl<T: Terminal> TuiRuntime<T> {
         self.flush_output();
     }
 
-    fn emit_runtime_diagnostic(&mut self, level: &str, code: &str, message: impl Into<String>) {
-        let message = message.into();
-        let formatted = format_runtime_diagnostic(level, code, &message);
+    fn emit_runtime_diagnostic(&mut self, code: &'static str, message: impl Into<String>) {
+        self.emit_diagnostic(Diagnostic::new(code, message));
+    }
+
+    fn emit_diagnostic(&mut self, diagnostic: Diagnostic) {
+        let mut delivered = false;
+        if let Some(handler) = self.on_diagnostic_event.as_mut() {
+            handler(&diagnostic);
+            delivered = true;
+        }
         if let Some(handler) = self.on_diagnostic.as_mut() {
-            handler(&formatted);
-            return;
+            handler(&diagnostic.to_string());
+            delivered = true;
+        }
+        if !delivered {
+            eprintln!("{diagnostic}");
         }
-        eprintln!("{formatted}");
     }
 
     /// Flush queued terminal protocol bytes without rendering.
@@ -1755,15 +3382,58 @@ impl<T: Terminal> TuiRuntime<T> {
 
     fn do_render(&mut self) {
         let width = self.terminal.columns() as usize;
-        let height = self.terminal.rows() as usize;
-        let (mut lines, mut cursor_pos) = self.render_root(width, height);
+        let height = self.viewport_rows() as usize;
+        if let Some(session) = self.copy_session.as_ref() {
+            // The frozen frame stands in for the live one; components are
+            // not rendered, so nothing scrolls away underneath the selection.
+            let frame = Frame::from(session.frame_lines());
+            let render_cmds = self.renderer.render(frame, width, height, false, false);
+            self.output.extend(render_cmds);
+            self.output.push(TerminalCmd::HideCursor);
+            return;
+        }
+        let too_small = self.unmet_minimum_size();
+        // The footer renders first so a split root knows the rows left for
+        // it; its frame goes back after the root's.
+        let footer = match too_small {
+            Some(_) => None,
+            None => self.render_footer(width, height),
+        };
+        let footer_frame = footer.as_ref().and_then(|_| self.root_frames.pop());
+        let footer_rows = footer
+            .as_ref()
+            .map_or(0, |(footer_lines, _)| footer_lines.len());
+        let (mut lines, mut cursor_pos) = match too_small {
+            Some(minimum) => (too_small_notice(minimum, width, height), None),
+            None => self.render_root(width, height, footer_rows),
+        };
+        self.root_frames.extend(footer_frame);
+        let min_rows = self.viewport_height.min_rows(height);
+        if lines.len() + footer_rows < min_rows {
+            lines.resize(min_rows - footer_rows, String::new());
+        }
+        if let Some((footer_lines, footer_cursor)) = footer {
+            let start_row = lines.len();
+            if let Some(frame) = self.root_frames.last_mut() {
+                frame.start_row = start_row;
+            }
+            if let Some(pos) = footer_cursor {
+                cursor_pos = Some(CursorPos {
+                    row: start_row + pos.row,
+                    col: pos.col,
+                });
+            }
+            lines.extend(footer_lines);
+        }
 
-        if self.has_surface() {
+        if too_small.is_none() && self.has_surface() {
             let (composited, surface_cursor) = self.composite_surface_lines(lines, width, height);
             lines = composited;
             if surface_cursor.is_some() {
                 cursor_pos = surface_cursor;
             }
+        } else {
+            self.publish_surface_geometry(Vec::new());
         }
 
         // Components may emit the CURSOR_MARKER APC sequence. Ensure it never
@@ -1780,6 +3450,14 @@ impl<T: Terminal> TuiRuntime<T> {
             cursor_pos = extracted_marker_pos;
         }
 
+        if let (Some(preedit), Some(pos)) = (self.ime_preedit.as_deref(), cursor_pos.as_mut()) {
+            if let Some(line) = lines.get_mut(pos.row) {
+                let (composed, col) = overlay_preedit(line, pos.col, preedit, width);
+                *line = composed;
+                pos.col = col;
+            }
+        }
+
         self.inline_viewport.note_terminal_height(height);
         self.inline_viewport.update_total_lines(lines.len());
         cursor_pos = self.inline_viewport.clamp_cursor(cursor_pos);
@@ -1794,6 +3472,10 @@ impl<T: Terminal> TuiRuntime<T> {
         let frame = Frame::from(lines).with_cursor(cursor_pos);
         let cursor_pos = frame.cursor();
         let total_lines = frame.lines().len();
+        let line_attributes = self.line_attributes.unwrap_or_else(|| {
+            get_capabilities(self.terminal_image_state.as_ref()).line_attributes
+        });
+        self.renderer.set_line_attributes(line_attributes);
         let render_cmds =
             self.renderer
                 .render(frame, width, height, self.clear_on_shrink, has_surfaces);
@@ -1810,12 +3492,81 @@ impl<T: Terminal> TuiRuntime<T> {
         );
         self.output.extend(cursor_cmds);
         self.renderer.set_hardware_cursor_row(updated_row);
+
+        if too_small.is_none() {
+            self.release_scrolled_root_rows();
+        }
+    }
+
+    /// Offers the first root component the rows of this frame that scrolled
+    /// into native scrollback, and forgets the ones it releases.
+    fn release_scrolled_root_rows(&mut self) {
+        if self.split.is_some() {
+            return;
+        }
+        let Some(frame) = self.root_frames.first().copied() else {
+            return;
+        };
+        if frame.start_row != 0 || Some(frame.id) == self.footer {
+            return;
+        }
+        let scrolled_rows = self.renderer.rows_above_viewport().min(frame.rows);
+        if scrolled_rows == 0 {
+            return;
+        }
+        let Some(component) = self.components.get_mut(frame.id) else {
+            return;
+        };
+        let released = component
+            .release_scrolled_rows(scrolled_rows)
+            .min(scrolled_rows);
+        if released > 0 {
+            self.renderer.forget_leading_lines(released);
+            if let Some(cache) = self.render_caches.get_mut(&frame.id) {
+                cache.clear();
+            }
+        }
+    }
+
+    /// Interim frame while a debounced resize settles: the previous frame,
+    /// clipped to the new width, without rendering any component.
+    fn render_interim_resize(&mut self) {
+        self.exit_copy_mode();
+        let width = self.terminal.columns() as usize;
+        let height = self.viewport_rows() as usize;
+        self.inline_viewport.note_terminal_height(height);
+        let render_cmds = self.renderer.render_previous_clipped(width, height);
+        self.output.extend(render_cmds);
+        // The cursor belongs to the settled layout; keep it hidden until then.
+        self.output.push(TerminalCmd::HideCursor);
+    }
+
+    /// Renders the root: the component stack, or both panes when split.
+    /// `footer_rows` is what the footer takes below a split root.
+    fn render_root(
+        &mut self,
+        width: usize,
+        height: usize,
+        footer_rows: usize,
+    ) -> (Vec<String>, Option<CursorPos>) {
+        if self.split.is_some() {
+            return self.render_split_root(width, height, height.saturating_sub(footer_rows));
+        }
+        let (lines, cursor_pos, root_frames) = self.render_stack(self.root.clone(), width, height);
+        self.root_frames = root_frames;
+        (lines, cursor_pos)
     }
 
-    fn render_root(&mut self, width: usize, height: usize) -> (Vec<String>, Option<CursorPos>) {
-        let root_ids = self.root.clone();
+    /// Renders `ids` one below the other, recording where each landed.
+    fn render_stack(
+        &mut self,
+        root_ids: Vec<ComponentId>,
+        width: usize,
+        height: usize,
+    ) -> (Vec<String>, Option<CursorPos>, Vec<RootFrame>) {
         let mut lines = Vec::new();
         let mut cursor_pos = None;
+        let mut root_frames = Vec::with_capacity(root_ids.len());
         for id in root_ids {
             let Some(component) = self.components.get_mut(id) else {
                 debug_assert!(false, "root component {:?} missing", id);
@@ -1823,8 +3574,15 @@ impl<T: Terminal> TuiRuntime<T> {
             };
             component.set_terminal_rows(height);
             let start_row = lines.len();
-            let child_lines = component.render(width);
-            let child_cursor = component.cursor_pos();
+            let (child_lines, child_cursor) = self.render_component(id, width);
+            root_frames.push(RootFrame {
+                id,
+                start_row,
+                rows: child_lines.len(),
+                col: 0,
+                width,
+                cursor: child_cursor,
+            });
             lines.extend(child_lines);
             if let Some(pos) = child_cursor {
                 cursor_pos = Some(CursorPos {
@@ -1833,27 +3591,155 @@ impl<T: Terminal> TuiRuntime<T> {
                 });
             }
         }
-        (lines, cursor_pos)
+        (lines, cursor_pos, root_frames)
     }
 
-    fn invalidate_root_components(&mut self) {
-        let root_ids = self.root.clone();
-        for id in root_ids {
-            let Some(component) = self.components.get_mut(id) else {
-                debug_assert!(false, "root component {:?} missing", id);
-                continue;
-            };
-            component.invalidate();
-        }
-    }
+    /// Renders both panes of a split root into `rows` rows at most. Each
+    /// pane shows the tail of its stack; the cursor is the focused pane's.
+    fn render_split_root(
+        &mut self,
+        width: usize,
+        height: usize,
+        rows: usize,
+    ) -> (Vec<String>, Option<CursorPos>) {
+        let Some(split) = self.split.clone() else {
+            return (Vec::new(), None);
+        };
+        let rows = rows.max(1);
+        let (first_size, second_size) = match split.direction {
+            SplitDirection::Horizontal => split.pane_sizes(width),
+            SplitDirection::Vertical => split.pane_sizes(rows),
+        };
+        let pane_width = |size: usize| match split.direction {
+            SplitDirection::Horizontal => size,
+            SplitDirection::Vertical => width,
+        };
+        let pane_rows = |size: usize| match split.direction {
+            SplitDirection::Horizontal => rows,
+            SplitDirection::Vertical => size,
+        };
 
-    fn apply_pending_commands(&mut self, commands: VecDeque<Command>) {
-        // Commands are applied at a single, explicit stage in the tick to preserve deterministic
-        // ordering relative to input handling and render decisions.
+        let mut panes = Vec::with_capacity(2);
+        for (ids, size) in [
+            (self.root.clone(), first_size),
+            (split.second.clone(), second_size),
+        ] {
+            let (lines, cursor, frames) = self.render_stack(ids, pane_width(size), height);
+            let offset = tail_offset(lines.len(), pane_rows(size));
+            let cursor = cursor.filter(|pos| pos.row >= offset).map(|pos| CursorPos {
+                row: pos.row - offset,
+                col: pos.col,
+            });
+            let frames: Vec<RootFrame> = frames
+                .into_iter()
+                .filter_map(|frame| clip_frame(frame, offset))
+                .collect();
+            panes.push((lines[offset..].to_vec(), cursor, frames));
+        }
+        let (second_lines, second_cursor, second_frames) = panes.pop().expect("second pane");
+        let (mut first_lines, first_cursor, first_frames) = panes.pop().expect("first pane");
+
+        // The pane the focused component is in, else the active one.
+        let focused_pane = match self.focused {
+            Some(id) if split.second.contains(&id) => SplitPane::Second,
+            Some(id) if self.root.contains(&id) => SplitPane::First,
+            _ => split.active,
+        };
+        let (lines, cursor, second_origin) = match split.direction {
+            SplitDirection::Horizontal => {
+                let second_col = first_size + 1;
+                let lines = join_columns(&first_lines, first_size, &second_lines, second_size);
+                let cursor = match focused_pane {
+                    SplitPane::First => first_cursor,
+                    SplitPane::Second => second_cursor.map(|pos| CursorPos {
+                        row: pos.row,
+                        col: second_col + pos.col,
+                    }),
+                };
+                (lines, cursor, (0, second_col))
+            }
+            SplitDirection::Vertical => {
+                let second_row = first_size + 1;
+                first_lines.resize(first_size, String::new());
+                first_lines.push(divider_row(width));
+                first_lines.extend(second_lines);
+                let cursor = match focused_pane {
+                    SplitPane::First => first_cursor,
+                    SplitPane::Second => second_cursor.map(|pos| CursorPos {
+                        row: second_row + pos.row,
+                        col: pos.col,
+                    }),
+                };
+                (first_lines, cursor, (second_row, 0))
+            }
+        };
+        self.root_frames = first_frames
+            .into_iter()
+            .chain(second_frames.into_iter().map(|frame| RootFrame {
+                start_row: frame.start_row + second_origin.0,
+                col: frame.col + second_origin.1,
+                ..frame
+            }))
+            .collect();
+        (lines, cursor)
+    }
+
+    /// Renders the footer, clamped to leave at least one row for the root,
+    /// and records its frame last in `root_frames` with a start row that
+    /// `do_render` fixes up once the root is padded. The cursor is
+    /// footer-relative.
+    fn render_footer(
+        &mut self,
+        width: usize,
+        height: usize,
+    ) -> Option<(Vec<String>, Option<CursorPos>)> {
+        let id = self.footer?;
+        let Some(component) = self.components.get_mut(id) else {
+            debug_assert!(false, "footer component {:?} missing", id);
+            return None;
+        };
+        component.set_terminal_rows(height);
+        let (mut lines, cursor) = self.render_component(id, width);
+        lines.truncate(height.saturating_sub(1).max(1));
+        let cursor = cursor.filter(|pos| pos.row < lines.len());
+        self.root_frames.push(RootFrame {
+            id,
+            start_row: 0,
+            rows: lines.len(),
+            col: 0,
+            width,
+            cursor,
+        });
+        Some((lines, cursor))
+    }
+
+    fn invalidate_root_components(&mut self) {
+        let second_pane = self.split.iter().flat_map(|split| split.second.iter());
+        let root_ids: Vec<ComponentId> = self
+            .root
+            .iter()
+            .chain(second_pane)
+            .copied()
+            .chain(self.footer)
+            .collect();
+        for id in root_ids {
+            let Some(component) = self.components.get_mut(id) else {
+                debug_assert!(false, "root component {:?} missing", id);
+                continue;
+            };
+            component.invalidate();
+            self.render_caches.remove(&id);
+        }
+    }
+
+    fn apply_pending_commands(&mut self, commands: VecDeque<Command>) {
+        // Commands are applied at a single, explicit stage in the tick to preserve deterministic
+        // ordering relative to input handling and render decisions.
         let mut pending_title: Option<String> = None;
         let mut render_requested = false;
 
-        for command in commands {
+        let mut commands = commands;
+        while let Some(command) = commands.pop_front() {
             match command {
                 Command::RequestRender => {
                     render_requested = true;
@@ -1861,16 +3747,64 @@ impl<T: Terminal> TuiRuntime<T> {
                 Command::RequestStop => {
                     self.wake.request_stop();
                 }
+                Command::ToggleHelp => {
+                    if self.help_overlay_component.is_some() {
+                        self.close_help_overlay();
+                    } else {
+                        self.open_help_overlay();
+                    }
+                }
+                Command::CloseHelp => self.close_help_overlay(),
+                Command::OpenContextMenu { anchor, items } => {
+                    self.open_context_menu(anchor, items);
+                }
+                Command::OpenFocusedContextMenu => self.open_focused_context_menu(),
+                Command::ChooseContextMenuItem { menu, index } => {
+                    if let Some(command) = self.choose_context_menu_item(menu, index) {
+                        commands.push_front(command);
+                    }
+                }
+                Command::CloseContextMenu => self.close_context_menu(),
+                Command::EnterCopyMode => self.enter_copy_mode(),
+                Command::ExitCopyMode => self.exit_copy_mode(),
+                Command::FocusNextPane => {
+                    if let Some(active) = self.active_pane() {
+                        self.focus_pane(active.other());
+                    }
+                }
+                Command::ResizeSplit(delta) => {
+                    if let Some(ratio) = self.split_ratio() {
+                        self.set_split_ratio(ratio + delta);
+                    }
+                }
+                Command::Suspend => {
+                    if let Err(err) = self.suspend() {
+                        self.emit_runtime_diagnostic(
+                            "runtime.suspend_failed",
+                            format!("could not suspend: {err}"),
+                        );
+                    }
+                }
+                Command::Composition { phase, text } => {
+                    self.handle_composition(phase, &text);
+                }
                 Command::SetTitle(title) => {
                     pending_title = Some(title);
                 }
+                Command::SetClipboard(text) => {
+                    self.output
+                        .push(TerminalCmd::Bytes(osc_clipboard_sequence(&text)));
+                }
+                Command::NotifyDesktop { title, body } => {
+                    let sequence = self.notification_sequence(&title, &body);
+                    self.output.push(TerminalCmd::Bytes(sequence));
+                }
                 Command::RootSet(components) => {
                     let mut resolved = Vec::with_capacity(components.len());
                     let mut had_missing = false;
                     for component_id in components {
                         if self.components.get_mut(component_id).is_none() {
                             self.emit_runtime_diagnostic(
-                                "error",
                                 "command.root_set.missing_component_id",
                                 format!(
                                     "root set references missing component id {}",
@@ -1891,7 +3825,6 @@ impl<T: Terminal> TuiRuntime<T> {
                 Command::RootPush(component) => {
                     if self.components.get_mut(component).is_none() {
                         self.emit_runtime_diagnostic(
-                            "error",
                             "command.root_push.missing_component_id",
                             format!(
                                 "root push references missing component id {}",
@@ -1903,10 +3836,39 @@ impl<T: Terminal> TuiRuntime<T> {
                         render_requested = true;
                     }
                 }
+                Command::FooterSet(footer) => match footer {
+                    Some(component) if self.components.get_mut(component).is_none() => {
+                        self.emit_runtime_diagnostic(
+                            "command.footer_set.missing_component_id",
+                            format!(
+                                "footer set references missing component id {}",
+                                component.raw()
+                            ),
+                        );
+                    }
+                    footer => {
+                        self.footer = footer;
+                        render_requested = true;
+                    }
+                },
                 Command::FocusSet(component_id) => {
                     self.set_focused(Some(component_id));
                     render_requested = true;
                 }
+                Command::SendMessage { target, message } => {
+                    if let Some(component) = self.components.get_mut(target) {
+                        component.on_message(message);
+                        render_requested = true;
+                    } else {
+                        self.emit_runtime_diagnostic(
+                            "command.send_message.missing_component_id",
+                            format!(
+                                "send message references missing component id {}",
+                                target.raw()
+                            ),
+                        );
+                    }
+                }
                 Command::FocusClear => {
                     self.set_focused(None);
                     render_requested = true;
@@ -1981,15 +3943,15 @@ impl<T: Terminal> TuiRuntime<T> {
                 }
                 Command::Custom(custom_command) => {
                     let command_name = custom_command.name();
-                    let mut ctx =
-                        CustomCommandCtx::new(self, &mut pending_title, &mut render_requested);
+                    let mut ctx = CustomCommandCtx::new(
+                        self,
+                        &mut pending_title,
+                        &mut render_requested,
+                        &mut commands,
+                    );
                     if let Err(error) = custom_command.apply(&mut ctx) {
                         let diagnostic = format!("custom command {command_name} failed: {error}");
-                        self.emit_runtime_diagnostic(
-                            "error",
-                            "command.custom.failed",
-                            diagnostic.clone(),
-                        );
+                        self.emit_runtime_diagnostic("command.custom.failed", diagnostic.clone());
                         debug_assert!(false, "{diagnostic}");
                     }
                 }
@@ -2001,6 +3963,9 @@ impl<T: Terminal> TuiRuntime<T> {
                 .push(TerminalCmd::Bytes(osc_title_sequence(&title)));
         }
 
+        // Root and footer commands change what is shown.
+        self.sync_mounted_components();
+
         if render_requested {
             self.wake.set_render_requested();
         }
@@ -2041,7 +4006,7 @@ impl<T: Terminal> TuiRuntime<T> {
                     self.output.push(TerminalCmd::MoveUp((-lines) as usize));
                 }
                 self.renderer
-                    .apply_out_of_band_move_by(lines, self.terminal.rows() as usize);
+                    .apply_out_of_band_move_by(lines, self.viewport_rows() as usize);
                 false
             }
             TerminalOp::RequestFullRedraw => {
@@ -2086,6 +4051,61 @@ impl<T: Terminal> TuiRuntime<T> {
         self.renderer.set_hardware_cursor_row(target_row);
     }
 
+    /// Seeds capabilities from the capability cache. Returns the stored entry
+    /// on a hit; on a miss, records the detected capabilities for next time.
+    fn load_cached_capabilities(&mut self) -> Option<CachedCapabilities> {
+        let cache = self.capability_cache.as_ref()?;
+        let key = CapabilityCache::terminal_key();
+        let state = self.terminal_image_state.as_ref();
+        match cache.load(&key) {
+            Some(cached) => {
+                set_capabilities(state, cached.capabilities);
+                if let Some(cell_dimensions) = cached.cell_dimensions {
+                    set_cell_dimensions(state, cell_dimensions);
+                }
+                self.cached_capabilities = Some((key, cached));
+                Some(cached)
+            }
+            None => {
+                let detected = CachedCapabilities {
+                    capabilities: get_capabilities(state),
+                    cell_dimensions: None,
+                    kitty_keyboard: false,
+                };
+                self.cached_capabilities = Some((key, detected));
+                self.store_cached_capabilities();
+                None
+            }
+        }
+    }
+
+    fn remember_capabilities(&mut self, update: impl FnOnce(&mut CachedCapabilities)) {
+        let Some((_, cached)) = self.cached_capabilities.as_mut() else {
+            return;
+        };
+        let before = *cached;
+        update(cached);
+        if *cached != before {
+            self.store_cached_capabilities();
+        }
+    }
+
+    fn store_cached_capabilities(&mut self) {
+        let (Some(cache), Some((key, cached))) = (
+            self.capability_cache.as_ref(),
+            self.cached_capabilities.as_ref(),
+        ) else {
+            return;
+        };
+        if let Err(err) = cache.store(key, cached) {
+            let path = cache.path().display().to_string();
+            self.emit_runtime_diagnostic(
+                "capability_cache.write_failed",
+                format!("could not write {path}: {err}"),
+            );
+        }
+    }
+
     fn query_cell_size(&mut self) {
         if get_capabilities(self.terminal_image_state.as_ref())
             .images
@@ -2103,13 +4123,14 @@ impl<T: Terminal> TuiRuntime<T> {
         if let Some((start, end, height_px, width_px)) = find_cell_size_response(&self.input_buffer)
         {
             if height_px > 0 && width_px > 0 {
-                set_cell_dimensions(
-                    self.terminal_image_state.as_ref(),
-                    CellDimensions {
-                        width_px,
-                        height_px,
-                    },
-                );
+                let cell_dimensions = CellDimensions {
+                    width_px,
+                    height_px,
+                };
+                set_cell_dimensions(self.terminal_image_state.as_ref(), cell_dimensions);
+                self.remember_capabilities(|cached| {
+                    cached.cell_dimensions = Some(cell_dimensions);
+                });
                 self.invalidate_root_components();
                 self.request_render();
             }
@@ -2162,8 +4183,7 @@ impl<T: Terminal> TuiRuntime<T> {
                 SurfaceTransactionMutation::Show { component, .. } => {
                     if self.components.get_mut(component).is_none() {
                         self.emit_runtime_diagnostic(
-                            "error",
-                            "command.surface_transaction.missing_component_id",
+"command.surface_transaction.missing_component_id",
                             format!(
                                 "surface transaction mutation[{index}] show references missing component id {}",
                                 component.raw()
@@ -2177,8 +4197,7 @@ impl<T: Terminal> TuiRuntime<T> {
                 SurfaceTransactionMutation::Hide { surface_id } => {
                     if !self.surfaces.contains(surface_id) {
                         self.emit_runtime_diagnostic(
-                            "error",
-                            "command.surface_transaction.missing_surface_id",
+"command.surface_transaction.missing_surface_id",
                             format!(
                                 "surface transaction mutation[{index}] hide references missing surface id {}",
                                 surface_id.raw()
@@ -2192,8 +4211,7 @@ impl<T: Terminal> TuiRuntime<T> {
                 SurfaceTransactionMutation::SetHidden { surface_id, .. } => {
                     if !self.surfaces.contains(surface_id) {
                         self.emit_runtime_diagnostic(
-                            "error",
-                            "command.surface_transaction.missing_surface_id",
+"command.surface_transaction.missing_surface_id",
                             format!(
                                 "surface transaction mutation[{index}] set-hidden references missing surface id {}",
                                 surface_id.raw()
@@ -2207,8 +4225,7 @@ impl<T: Terminal> TuiRuntime<T> {
                 SurfaceTransactionMutation::UpdateOptions { surface_id, .. } => {
                     if !self.surfaces.contains(surface_id) {
                         self.emit_runtime_diagnostic(
-                            "error",
-                            "command.surface_transaction.missing_surface_id",
+"command.surface_transaction.missing_surface_id",
                             format!(
                                 "surface transaction mutation[{index}] update-options references missing surface id {}",
                                 surface_id.raw()
@@ -2261,7 +4278,6 @@ impl<T: Terminal> TuiRuntime<T> {
     ) -> bool {
         if self.components.get_mut(component).is_none() {
             self.emit_runtime_diagnostic(
-                "error",
                 missing_component_code,
                 format!(
                     "{action_label} references missing component id {}",
@@ -2290,13 +4306,16 @@ impl<T: Terminal> TuiRuntime<T> {
         });
 
         let columns = self.terminal.columns() as usize;
-        let rows = self.terminal.rows() as usize;
+        let rows = self.viewport_rows() as usize;
         if let Some(entry) = self.surfaces.entries.last().copied() {
             let is_capture = entry.input_policy() == SurfaceInputPolicy::Capture;
             if !hidden && is_capture && entry.is_visible(columns, rows) {
                 self.set_focused(Some(component));
             }
         }
+        if !hidden {
+            self.notify_surface_visibility(surface_id, true);
+        }
 
         true
     }
@@ -2317,7 +4336,6 @@ impl<T: Terminal> TuiRuntime<T> {
     ) -> bool {
         let Some(index) = self.surfaces.index_of(surface_id) else {
             self.emit_runtime_diagnostic(
-                "error",
                 missing_id_code,
                 format!(
                     "{action_label} references missing surface id {}",
@@ -2333,6 +4351,9 @@ impl<T: Terminal> TuiRuntime<T> {
         {
             self.restore_focus_after_surface_loss(removed.pre_focus);
         }
+        if !removed.hidden {
+            self.notify_surface_visibility(surface_id, false);
+        }
         true
     }
 
@@ -2354,7 +4375,6 @@ impl<T: Terminal> TuiRuntime<T> {
     ) -> bool {
         let Some(index) = self.surfaces.index_of(surface_id) else {
             self.emit_runtime_diagnostic(
-                "error",
                 missing_id_code,
                 format!(
                     "{action_label} references missing surface id {}",
@@ -2380,6 +4400,7 @@ impl<T: Terminal> TuiRuntime<T> {
             if was_capture && self.focused == Some(component_id) {
                 self.restore_focus_after_surface_loss(pre_focus);
             }
+            self.notify_surface_visibility(surface_id, false);
             return true;
         }
 
@@ -2403,7 +4424,7 @@ impl<T: Terminal> TuiRuntime<T> {
         self.surfaces.entries.push(entry);
 
         let columns = self.terminal.columns() as usize;
-        let rows = self.terminal.rows() as usize;
+        let rows = self.viewport_rows() as usize;
         if is_capture
             && self
                 .surfaces
@@ -2413,6 +4434,7 @@ impl<T: Terminal> TuiRuntime<T> {
         {
             self.set_focused(Some(component_id));
         }
+        self.notify_surface_visibility(surface_id, true);
 
         true
     }
@@ -2424,7 +4446,6 @@ impl<T: Terminal> TuiRuntime<T> {
     ) -> bool {
         let Some(index) = self.surfaces.index_of(surface_id) else {
             self.emit_runtime_diagnostic(
-                "error",
                 "command.update_surface_options.missing_surface_id",
                 format!(
                     "update surface options references missing surface id {}",
@@ -2486,7 +4507,6 @@ impl<T: Terminal> TuiRuntime<T> {
     {
         if !self.surfaces.contains(surface_id) {
             self.emit_runtime_diagnostic(
-                "error",
                 missing_id_code,
                 format!(
                     "{action_label} references missing surface id {}",
@@ -2512,11 +4532,14 @@ impl<T: Terminal> TuiRuntime<T> {
         if self.focused == target {
             return;
         }
+        let previous_focus = self.focused;
+        if let Some(target) = target {
+            self.note_pane_focus(target);
+        }
 
         if let Some(previous) = self.focused.take() {
             let Some(component) = self.components.get_mut(previous) else {
                 self.emit_runtime_diagnostic(
-                    "error",
                     "focus.missing_previous_component_id",
                     format!("focused component id {} is missing", previous.raw()),
                 );
@@ -2528,12 +4551,15 @@ impl<T: Terminal> TuiRuntime<T> {
         }
 
         let Some(next) = target else {
+            self.wake.publish(RuntimeEvent::FocusChanged {
+                previous: previous_focus,
+                focused: None,
+            });
             return;
         };
 
         let Some(component) = self.components.get_mut(next) else {
             self.emit_runtime_diagnostic(
-                "error",
                 "focus.missing_target_component_id",
                 format!("focus target component id {} is missing", next.raw()),
             );
@@ -2543,6 +4569,10 @@ impl<T: Terminal> TuiRuntime<T> {
             focusable.set_focused(true);
         }
         self.focused = Some(next);
+        self.wake.publish(RuntimeEvent::FocusChanged {
+            previous: previous_focus,
+            focused: Some(next),
+        });
     }
 
     fn restore_focus_after_surface_loss(&mut self, pre_focus: Option<ComponentId>) {
@@ -2564,7 +4594,7 @@ impl<T: Terminal> TuiRuntime<T> {
     fn visible_surface_snapshot(&self) -> Vec<SurfaceRenderEntry> {
         self.surfaces.visible_snapshot(
             self.terminal.columns() as usize,
-            self.terminal.rows() as usize,
+            self.viewport_rows() as usize,
         )
     }
 
@@ -2585,6 +4615,17 @@ impl<T: Terminal> TuiRuntime<T> {
             .collect()
     }
 
+    /// Stores the geometry drawn this pass and notifies the layout handler
+    /// when it differs from the previous pass.
+    fn publish_surface_geometry(&mut self, geometry: Vec<(SurfaceId, Rect)>) {
+        if !self.wake.replace_surface_geometry(geometry.clone()) {
+            return;
+        }
+        if let Some(handler) = self.on_surface_layout.as_mut() {
+            handler(&geometry);
+        }
+    }
+
     fn composite_surface_lines(
         &mut self,
         lines: Vec<String>,
@@ -2592,7 +4633,24 @@ impl<T: Terminal> TuiRuntime<T> {
         height: usize,
     ) -> (Vec<String>, Option<CursorPos>) {
         let measured_entries = self.measured_visible_surface_snapshot(width, height);
+        let now = Instant::now();
         let mut rendered: Vec<(RenderedSurface, Option<CursorPos>)> = Vec::new();
+        let mut geometry: Vec<(SurfaceId, Rect)> = Vec::new();
+        // Matches the viewport start computed below: surfaces are clamped
+        // inside the viewport, so they never extend the working height.
+        let viewport_start = self
+            .renderer
+            .max_lines_rendered()
+            .max(lines.len())
+            .saturating_sub(height);
+        let mut frames: HashMap<ComponentId, ComponentFrame> = self
+            .root_frames
+            .iter()
+            .filter_map(|root| {
+                root.in_viewport(viewport_start, height)
+                    .map(|frame| (root.id, frame))
+            })
+            .collect();
 
         for (entry, measurement, allocation) in measured_entries {
             debug_assert_eq!(measurement.kind, allocation.kind);
@@ -2605,12 +4663,26 @@ impl<T: Terminal> TuiRuntime<T> {
             layout_options.width = Some(crate::runtime::surface::SurfaceSizeValue::absolute(
                 allocation.allocated_width,
             ));
+            let anchored = match layout_options.anchor {
+                Some(SurfaceAnchor::Component { id, placement }) => {
+                    let Some(frame) = frames.get(&id) else {
+                        continue;
+                    };
+                    Some((frame.anchor_target(placement), placement))
+                }
+                _ => None,
+            };
+            let mut allocated_rows = allocation.allocated_rows;
+            if let Some((target, placement)) = anchored {
+                let (below, above) = anchored_room(target, height);
+                allocated_rows = allocated_rows.min(match placement {
+                    SurfacePlacement::Below => below,
+                    SurfacePlacement::Above => above,
+                    SurfacePlacement::Auto | SurfacePlacement::Cursor => below.max(above),
+                });
+            }
             layout_options.max_height = Some(crate::runtime::surface::SurfaceSizeValue::absolute(
-                allocation.allocated_rows,
-            ));
-
-            let render_options = Some(crate::render::surface::SurfaceOptions::from(
-                &layout_options,
+                allocated_rows,
             ));
 
             let Some(component) = self.components.get_mut(entry.component_id) else {
@@ -2623,18 +4695,57 @@ impl<T: Terminal> TuiRuntime<T> {
             };
 
             component.set_terminal_rows(height);
-            component.set_viewport_size(allocation.allocated_width, allocation.allocated_rows);
+            component.set_viewport_size(allocation.allocated_width, allocated_rows);
 
-            let mut surface_lines = if allocation.allocated_rows == 0 {
-                Vec::new()
+            let throttled = surface_options.min_update_interval.and_then(|interval| {
+                let last = self.throttled_renders.get(&entry.component_id)?;
+                let due_at = last.rendered_at + interval;
+                (last.width == allocation.allocated_width && now < due_at)
+                    .then(|| (due_at, last.lines.clone(), last.cursor))
+            });
+            let (mut surface_lines, mut cursor_pos) = if allocated_rows == 0 {
+                (Vec::new(), component.cursor_pos())
+            } else if let Some((due_at, lines, cursor)) = throttled {
+                self.wake.schedule_render_at(due_at);
+                (lines, cursor)
             } else {
-                component.render(allocation.allocated_width)
+                let (lines, cursor) =
+                    self.render_component(entry.component_id, allocation.allocated_width);
+                if surface_options.min_update_interval.is_some() {
+                    self.throttled_renders.insert(
+                        entry.component_id,
+                        ThrottledRender {
+                            rendered_at: now,
+                            width: allocation.allocated_width,
+                            lines: lines.clone(),
+                            cursor,
+                        },
+                    );
+                }
+                (lines, cursor)
             };
-            let mut cursor_pos = component.cursor_pos();
 
-            if surface_lines.len() > allocation.allocated_rows {
-                surface_lines.truncate(allocation.allocated_rows);
+            if surface_lines.len() > allocated_rows {
+                surface_lines.truncate(allocated_rows);
+            }
+            if let Some((target, placement)) = anchored {
+                let (below, above) = anchored_room(target, height);
+                let row = if anchored_below(placement, surface_lines.len(), below, above) {
+                    surface_lines.truncate(below);
+                    target.y.saturating_add(target.height)
+                } else {
+                    surface_lines.truncate(above);
+                    target.y.saturating_sub(surface_lines.len())
+                };
+                layout_options.anchor = None;
+                layout_options.row = Some(crate::runtime::surface::SurfaceSizeValue::absolute(row));
+                layout_options.col = Some(crate::runtime::surface::SurfaceSizeValue::absolute(
+                    target.x,
+                ));
             }
+            let render_options = Some(crate::render::surface::SurfaceOptions::from(
+                &layout_options,
+            ));
             if let Some(pos) = cursor_pos {
                 if pos.row >= surface_lines.len() || pos.col >= allocation.allocated_width {
                     cursor_pos = None;
@@ -2643,6 +4754,30 @@ impl<T: Terminal> TuiRuntime<T> {
 
             let final_layout =
                 resolve_surface_layout(render_options.as_ref(), surface_lines.len(), width, height);
+            geometry.push((
+                entry.id,
+                Rect {
+                    x: final_layout.col,
+                    y: final_layout.row,
+                    width: final_layout.width,
+                    height: surface_lines.len(),
+                },
+            ));
+            frames.insert(
+                entry.component_id,
+                ComponentFrame {
+                    rect: Rect {
+                        x: final_layout.col,
+                        y: final_layout.row,
+                        width: final_layout.width,
+                        height: surface_lines.len(),
+                    },
+                    cursor: cursor_pos.map(|pos| CursorPos {
+                        row: final_layout.row.saturating_add(pos.row),
+                        col: final_layout.col.saturating_add(pos.col),
+                    }),
+                },
+            );
 
             rendered.push((
                 RenderedSurface {
@@ -2655,6 +4790,8 @@ impl<T: Terminal> TuiRuntime<T> {
             ));
         }
 
+        self.publish_surface_geometry(geometry);
+
         let mut min_lines_needed = lines.len();
         for (surface, _) in rendered.iter() {
             min_lines_needed =
@@ -2702,22 +4839,77 @@ impl<T: Terminal> TuiRuntime<T> {
         (composited, surface_cursor)
     }
 
+    /// Calls `Component::on_mount` on components that started being shown
+    /// and `Component::on_unmount` on those that stopped, so each component
+    /// sees the hooks alternate however many places show it.
+    fn sync_mounted_components(&mut self) {
+        let mut shown = self.root.clone();
+        if let Some(split) = self.split.as_ref() {
+            shown.extend(split.second.iter().copied());
+        }
+        shown.extend(self.footer);
+        shown.extend(
+            self.surfaces
+                .entries
+                .iter()
+                .filter(|entry| !entry.hidden)
+                .map(|entry| entry.component_id),
+        );
+
+        let mut mounted = std::mem::take(&mut self.mounted);
+        mounted.retain(|id| {
+            if shown.contains(id) {
+                return true;
+            }
+            if let Some(component) = self.components.get_mut(*id) {
+                component.on_unmount();
+            }
+            false
+        });
+        for id in shown {
+            if mounted.contains(&id) {
+                continue;
+            }
+            if let Some(component) = self.components.get_mut(id) {
+                component.on_mount();
+                mounted.push(id);
+            }
+        }
+        self.mounted = mounted;
+    }
+
     fn topmost_visible_capture_surface(&self) -> Option<ComponentId> {
         self.surfaces.topmost_visible_component(
             self.terminal.columns() as usize,
-            self.terminal.rows() as usize,
+            self.viewport_rows() as usize,
             true,
         )
     }
 
+    /// Runs mount/unmount hooks for the visibility change and publishes the
+    /// matching [`RuntimeEvent`].
+    fn notify_surface_visibility(&mut self, surface_id: SurfaceId, shown: bool) {
+        self.sync_mounted_components();
+        self.wake.publish(if shown {
+            RuntimeEvent::SurfaceShown(surface_id)
+        } else {
+            RuntimeEvent::SurfaceHidden(surface_id)
+        });
+    }
+
     fn dispatch_resize_event(&mut self) {
+        // The frozen frame was laid out for the old size.
+        self.exit_copy_mode();
+        self.reprobe_kitty_keyboard();
         let rows = self.terminal.rows();
-        self.inline_viewport.note_terminal_height(rows as usize);
-
-        let event = InputEvent::Resize {
-            columns: self.terminal.columns(),
-            rows,
-        };
+        self.inline_viewport
+            .note_terminal_height(self.viewport_rows() as usize);
+
+        let columns = self.terminal.columns();
+        self.components
+            .for_each_mut(|component| component.on_resize(columns, rows));
+        self.wake.publish(RuntimeEvent::Resized { columns, rows });
+        let event = InputEvent::Resize { columns, rows };
         let (capture_target, fallback_target) = self.input_dispatch_targets();
         let _ = self.dispatch_event_with_bubbling(&event, capture_target, fallback_target);
     }
@@ -2853,23 +5045,40 @@ fn is_partial_cell_size(buffer: &str) -> bool {
 mod tests {
     use super::{
         apply_lane_reservations, find_cell_size_response, CoalesceBudget, Command, ComponentId,
-        CrashCleanup, CustomCommand, CustomCommandCtx, CustomCommandError, RuntimeHandle,
-        SurfaceTransactionMutation, TerminalOp, TuiRuntime,
+        CrashCleanup, CustomCommand, CustomCommandCtx, CustomCommandError, InputProtocol,
+        MinimumSize, RuntimeEvent, RuntimeHandle, RuntimeWake, SurfaceTransactionMutation,
+        TerminalOp, TuiRuntime, ViewportHeight,
     };
-    use crate::core::component::Component;
+    use crate::core::autocomplete::SlashCommand;
+    use crate::core::capability_cache::CapabilityCache;
+    use crate::core::component::{Component, ComponentMessage};
     use crate::core::cursor::CursorPos;
+    use crate::core::input::{KeyEventType, KeyEventTypes};
+    use crate::core::input_event::{CompositionPhase, InputEvent};
+    use crate::core::keybindings::{
+        EditorAction, KeybindingConflict, KeybindingConflictKind, KeybindingContext,
+        KeybindingOwner,
+    };
+    use crate::core::output::notification_sequence;
     use crate::core::output::TerminalCmd;
     use crate::core::terminal::Terminal;
-    use crate::core::terminal_image::get_cell_dimensions;
+    use crate::core::terminal_image::{get_capabilities, get_cell_dimensions, CellDimensions};
+    use crate::runtime::context_menu::{ContextMenuItem, ContextMenuOptions};
+    use crate::runtime::copy_mode::CopyModeOptions;
+    use crate::runtime::diagnostics::{Diagnostic, DiagnosticLevel};
+    use crate::runtime::help::HelpOverlayOptions;
+    use crate::runtime::host_commands::HostCommand;
+    use crate::runtime::split::{SplitDirection, SplitPane};
     use crate::runtime::surface::{
-        SurfaceAnchor, SurfaceId, SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions,
-        SurfaceMargin, SurfaceMutation, SurfaceOptions, SurfaceSizeValue, SurfaceVisibility,
+        Rect, SurfaceAnchor, SurfaceId, SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions,
+        SurfaceMargin, SurfaceMutation, SurfaceOptions, SurfacePlacement, SurfaceSizeValue,
+        SurfaceVisibility,
     };
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
     use std::sync::{Arc, Mutex, OnceLock};
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     #[derive(Default)]
     struct TestTerminal {
@@ -2899,6 +5108,10 @@ mod tests {
         fn stop(&mut self) -> std::io::Result<()> {
             Ok(())
         }
+        fn suspend(&mut self) -> std::io::Result<()> {
+            self.output.push_str("<suspended>");
+            Ok(())
+        }
         fn drain_input(&mut self, _max_ms: u64, _idle_ms: u64) {}
         fn write(&mut self, data: &str) {
             self.output.push_str(data);
@@ -2981,6 +5194,51 @@ mod tests {
                 *self.text.borrow_mut() = text.clone();
             }
         }
+
+        fn on_message(&mut self, message: crate::core::component::ComponentMessage) {
+            if let Ok(text) = message.downcast::<String>() {
+                *self.text.borrow_mut() = *text;
+            }
+        }
+    }
+
+    struct LifecycleComponent {
+        calls: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Component for LifecycleComponent {
+        fn render(&mut self, _width: usize) -> Vec<String> {
+            vec!["lifecycle".to_string()]
+        }
+
+        fn on_mount(&mut self) {
+            self.calls.borrow_mut().push("mount".to_string());
+        }
+
+        fn on_unmount(&mut self) {
+            self.calls.borrow_mut().push("unmount".to_string());
+        }
+
+        fn on_resize(&mut self, columns: u16, rows: u16) {
+            self.calls
+                .borrow_mut()
+                .push(format!("resize {columns}x{rows}"));
+        }
+    }
+
+    struct SendMessageCustomCommand {
+        target: ComponentId,
+        text: &'static str,
+    }
+
+    impl CustomCommand for SendMessageCustomCommand {
+        fn name(&self) -> &'static str {
+            "send_message"
+        }
+
+        fn apply(self: Box<Self>, ctx: &mut CustomCommandCtx) -> Result<(), CustomCommandError> {
+            ctx.send(self.target, Box::new(self.text.to_string()))
+        }
     }
 
     struct MutateComponentCustomCommand {
@@ -3129,8 +5387,10 @@ mod tests {
                 crate::core::input_event::InputEvent::Key { raw, .. } => raw.as_str(),
                 crate::core::input_event::InputEvent::Text { raw, .. } => raw.as_str(),
                 crate::core::input_event::InputEvent::Paste { raw, .. } => raw.as_str(),
+                crate::core::input_event::InputEvent::FilesDropped { raw, .. } => raw.as_str(),
                 crate::core::input_event::InputEvent::UnknownRaw { raw } => raw.as_str(),
-                crate::core::input_event::InputEvent::Resize { .. } => return,
+                crate::core::input_event::InputEvent::Resize { .. }
+                | crate::core::input_event::InputEvent::Composition { .. } => return,
             };
             self.inputs.borrow_mut().push(raw.to_string());
         }
@@ -3164,30 +5424,607 @@ mod tests {
             self.lines.clone()
         }
 
-        fn cursor_pos(&self) -> Option<CursorPos> {
-            self.cursor
-        }
+        fn cursor_pos(&self) -> Option<CursorPos> {
+            self.cursor
+        }
+    }
+
+    #[test]
+    fn composition_preedit_is_drawn_at_cursor_until_commit() {
+        let terminal = TestTerminal::new(20, 5);
+        let (mut runtime, root_id) = runtime_with_root(
+            terminal,
+            StaticLinesComponent {
+                lines: vec!["> ab".to_string()],
+                cursor: Some(CursorPos { row: 0, col: 2 }),
+            },
+        );
+        runtime.show_hardware_cursor = true;
+        runtime.set_focus(root_id);
+        runtime.start().expect("runtime start");
+        runtime.render_now();
+        runtime.terminal.output.clear();
+
+        runtime.handle_composition(CompositionPhase::Start, "");
+        runtime.handle_composition(CompositionPhase::Update, "にほ");
+        runtime.render_now();
+        let output = runtime.terminal.output.clone();
+        assert!(output.contains("\x1b[4mにほ"), "{output:?}");
+        assert!(output.ends_with("\x1b[7G\x1b[?25h"), "{output:?}");
+
+        runtime.terminal.output.clear();
+        runtime.handle_composition(CompositionPhase::Commit, "日本");
+        runtime.render_now();
+        let output = runtime.terminal.output.clone();
+        assert!(!output.contains("\x1b[4m"), "{output:?}");
+        assert!(output.ends_with("\x1b[3G\x1b[?25h"), "{output:?}");
+    }
+
+    #[test]
+    fn root_stack_concatenates_children_and_offsets_cursor() {
+        let terminal = TestTerminal::default();
+        let mut runtime = TuiRuntime::new(terminal);
+        let first = StaticLinesComponent {
+            lines: vec!["one".to_string()],
+            cursor: Some(CursorPos { row: 0, col: 0 }),
+        };
+        let second = StaticLinesComponent {
+            lines: vec!["two".to_string(), "three".to_string()],
+            cursor: Some(CursorPos { row: 1, col: 2 }),
+        };
+        let first_id = runtime.register_component(first);
+        let second_id = runtime.register_component(second);
+        runtime.set_root(vec![first_id, second_id]);
+
+        let (lines, cursor) = runtime.render_root(10, 24, 0);
+        assert_eq!(lines, vec!["one", "two", "three"]);
+        assert_eq!(cursor, Some(CursorPos { row: 2, col: 2 }));
+    }
+
+    struct FragileComponent {
+        fail_render: Rc<Cell<bool>>,
+    }
+
+    impl Component for FragileComponent {
+        fn render(&mut self, _width: usize) -> Vec<String> {
+            if self.fail_render.get() {
+                panic!("render exploded");
+            }
+            vec!["fragile".to_string()]
+        }
+
+        fn handle_event(&mut self, event: &InputEvent) {
+            if let InputEvent::Text { text, .. } = event {
+                if text == "!" {
+                    panic!("bad key");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn panicking_components_are_replaced_by_placeholders_and_reported() {
+        let diagnostics = Arc::new(Mutex::new(Vec::new()));
+        let fail_render = Rc::new(Cell::new(false));
+        let mut runtime = TuiRuntime::new(TestTerminal::new(40, 6));
+        let sink = Arc::clone(&diagnostics);
+        runtime.set_on_diagnostic(Some(Box::new(move |line: &str| {
+            sink.lock()
+                .expect("diagnostics lock")
+                .push(line.to_string());
+        })));
+        runtime.set_component_error_style(Some(Box::new(|text| format!("<{text}>"))));
+        let fragile = runtime.register_component(FragileComponent {
+            fail_render: Rc::clone(&fail_render),
+        });
+        let sibling = runtime.register_component(static_lines(&["still here"], None));
+        runtime.set_root(vec![fragile, sibling]);
+        runtime.start().expect("runtime start");
+
+        fail_render.set(true);
+        let (lines, _) = runtime.render_root(40, 6, 0);
+        assert_eq!(
+            lines,
+            vec!["<⚠ component failed: render exploded>", "still here"]
+        );
+        assert_eq!(runtime.component_fault(fragile), Some("render exploded"));
+        assert!(diagnostics.lock().expect("diagnostics lock")[0]
+            .contains("[component.render_panicked] component id 0 panicked in render"));
+
+        // Faulted components stay on their placeholder until cleared.
+        fail_render.set(false);
+        assert_eq!(
+            runtime.render_root(40, 6, 0).0[0],
+            "<⚠ component failed: render exploded>"
+        );
+        assert!(runtime.clear_component_fault(fragile));
+        assert_eq!(runtime.render_root(40, 6, 0).0[0], "fragile");
+
+        runtime.set_focus(fragile);
+        runtime.run_once();
+        runtime.handle_input("!");
+        runtime.run_once();
+        assert_eq!(runtime.component_fault(fragile), Some("bad key"));
+        assert!(
+            diagnostics.lock().expect("diagnostics lock")[1].contains("[component.input_panicked]")
+        );
+        runtime.render_now();
+        assert!(runtime.terminal.output.contains("still here"));
+        // Input bubbles past the faulted component instead of panicking again.
+        runtime.handle_input("!");
+        runtime.run_once();
+        assert_eq!(diagnostics.lock().expect("diagnostics lock").len(), 2);
+    }
+
+    #[test]
+    fn typed_diagnostics_carry_registered_codes_alongside_formatted_lines() {
+        let events: Rc<RefCell<Vec<Diagnostic>>> = Rc::new(RefCell::new(Vec::new()));
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let mut runtime = TuiRuntime::new(TestTerminal::new(20, 3));
+        let event_sink = Rc::clone(&events);
+        runtime.set_on_diagnostic_event(Some(Box::new(move |diagnostic| {
+            event_sink.borrow_mut().push(diagnostic.clone());
+        })));
+        let line_sink = Rc::clone(&lines);
+        runtime.set_on_diagnostic(Some(Box::new(move |message| {
+            line_sink.borrow_mut().push(message.to_string());
+        })));
+        let fail_render = Rc::new(Cell::new(true));
+        let fragile = runtime.register_component(FragileComponent {
+            fail_render: Rc::clone(&fail_render),
+        });
+        runtime.set_root(vec![fragile]);
+        runtime.start().expect("runtime start");
+        runtime.render_now();
+
+        let missing = runtime.register_component(static_lines(&[], None));
+        runtime.remove_component(missing);
+        runtime
+            .runtime_handle()
+            .dispatch(Command::RootPush(missing));
+        runtime.run_once();
+
+        let events = events.borrow();
+        assert_eq!(
+            events.iter().map(|event| event.code).collect::<Vec<_>>(),
+            vec![
+                "component.render_panicked",
+                "command.root_push.missing_component_id"
+            ]
+        );
+        assert_eq!(events[0].level, DiagnosticLevel::Error);
+        assert_eq!(
+            events[0].context_value("component_id"),
+            Some(fragile.raw().to_string().as_str())
+        );
+        assert_eq!(
+            *lines.borrow(),
+            events.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+    }
+
+    fn static_lines(lines: &[&str], cursor: Option<CursorPos>) -> StaticLinesComponent {
+        StaticLinesComponent {
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            cursor,
+        }
+    }
+
+    #[test]
+    fn split_root_renders_pane_tails_side_by_side_or_stacked() {
+        let mut runtime = TuiRuntime::new(TestTerminal::new(11, 4));
+        let transcript = runtime.register_component(static_lines(
+            &["one", "two", "three", "four", "five"],
+            Some(CursorPos { row: 4, col: 1 }),
+        ));
+        let viewer = runtime.register_component(static_lines(
+            &["fn a", "fn b"],
+            Some(CursorPos { row: 1, col: 3 }),
+        ));
+        runtime.set_root(vec![transcript]);
+        runtime.split_root(SplitDirection::Horizontal, 0.5);
+        runtime.set_pane_root(SplitPane::Second, vec![viewer]);
+        runtime.set_focus(transcript);
+        runtime.start().expect("runtime start");
+        runtime.run_once();
+
+        let (lines, cursor) = runtime.render_root(11, 4, 1);
+        assert_eq!(
+            lines,
+            vec!["three\x1b[0m│fn a", "four\x1b[0m │fn b", "five\x1b[0m │",]
+        );
+        assert_eq!(cursor, Some(CursorPos { row: 2, col: 1 }));
+
+        // Focus moves to the viewer; the cursor and frames follow the pane.
+        runtime.handle_input("\x1b[17~");
+        runtime.run_once();
+        assert_eq!(runtime.focused, Some(viewer));
+        assert_eq!(runtime.active_pane(), Some(SplitPane::Second));
+        let (_, cursor) = runtime.render_root(11, 4, 1);
+        assert_eq!(cursor, Some(CursorPos { row: 1, col: 9 }));
+        let viewer_frame = runtime
+            .root_frames
+            .iter()
+            .find(|frame| frame.id == viewer)
+            .copied()
+            .expect("viewer frame");
+        assert_eq!((viewer_frame.col, viewer_frame.width), (6, 5));
+
+        runtime.split_root(SplitDirection::Vertical, 0.5);
+        let (lines, _) = runtime.render_root(11, 4, 0);
+        assert_eq!(lines, vec!["four", "five", "───────────", "fn b"]);
+
+        runtime.handle_input("\x1b[17~");
+        runtime.run_once();
+        assert_eq!(runtime.focused, Some(transcript));
+
+        runtime.runtime_handle().dispatch(Command::ResizeSplit(0.3));
+        runtime.run_once();
+        assert_eq!(runtime.split_ratio(), Some(0.8));
+
+        assert_eq!(runtime.unsplit_root(), vec![viewer]);
+        assert!(!runtime.is_root_split());
+        let (lines, _) = runtime.render_root(11, 4, 0);
+        assert_eq!(lines.len(), 5);
+        runtime.handle_input("\x1b[17~");
+        runtime.run_once();
+        assert_eq!(runtime.focused, Some(transcript));
+    }
+
+    #[test]
+    fn viewport_height_caps_redrawn_rows_and_pads_fixed_viewports() {
+        let terminal = TestTerminal::new(20, 10);
+        let mut runtime = TuiRuntime::new(terminal);
+        let text = Rc::new(RefCell::new("top".to_string()));
+        let renders = Rc::new(RefCell::new(0usize));
+        let top_id = runtime.register_component(MutableTextComponent::new(
+            Rc::clone(&text),
+            Rc::clone(&renders),
+        ));
+        let body_id = runtime.register_component(StaticLinesComponent {
+            lines: (1..6).map(|row| format!("row {row}")).collect(),
+            cursor: None,
+        });
+        runtime.set_root(vec![top_id, body_id]);
+        runtime.show_hardware_cursor = false;
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        let handle = runtime.runtime_handle();
+
+        // The full-height viewport still owns the first row, so it is patched in place.
+        runtime.terminal.output.clear();
+        handle.send(top_id, Box::new("edited".to_string()));
+        runtime.run_once();
+        assert!(runtime.terminal.output.contains("edited"));
+        assert!(!runtime.terminal.output.contains("\x1b[2J"));
+
+        // With a three-row cap the first row belongs to scrollback.
+        runtime.set_max_viewport_rows(3);
+        assert_eq!(runtime.viewport_rows(), 3);
+        runtime.run_once();
+        runtime.terminal.output.clear();
+        handle.send(top_id, Box::new("again".to_string()));
+        runtime.run_once();
+        assert!(
+            runtime.terminal.output.contains("\x1b[2J"),
+            "expected a full redraw, got: {:?}",
+            runtime.terminal.output
+        );
+
+        runtime.set_viewport_height(ViewportHeight::Fixed(8));
+        runtime.run_once();
+        assert_eq!(runtime.viewport_rows(), 8);
+        assert_eq!(runtime.renderer.previous_lines_len(), 8);
+
+        runtime.set_viewport_height(ViewportHeight::Fixed(40));
+        assert_eq!(runtime.viewport_rows(), 10);
+    }
+
+    #[test]
+    fn footer_renders_below_the_root_and_is_pinned_to_fixed_viewports() {
+        let terminal = TestTerminal::new(20, 10);
+        let mut runtime = TuiRuntime::new(terminal);
+        let body_id = runtime.register_component(StaticLinesComponent {
+            lines: vec!["body".to_string()],
+            cursor: Some(CursorPos { row: 0, col: 1 }),
+        });
+        let footer_id = runtime.register_component(StaticLinesComponent {
+            lines: vec!["hints".to_string(), "status".to_string()],
+            cursor: Some(CursorPos { row: 1, col: 3 }),
+        });
+        runtime.set_root(vec![body_id]);
+        runtime.set_footer(footer_id);
+        runtime.show_hardware_cursor = false;
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        assert_eq!(
+            runtime.renderer.previous_lines(),
+            ["body", "hints", "status"]
+        );
+
+        runtime.set_viewport_height(ViewportHeight::Fixed(5));
+        runtime.render_if_needed();
+        assert_eq!(
+            runtime.renderer.previous_lines(),
+            ["body", "", "", "hints", "status"]
+        );
+        let footer_frame = runtime.root_frames.last().expect("footer frame");
+        assert_eq!((footer_frame.id, footer_frame.start_row), (footer_id, 3));
+        assert_eq!(footer_frame.cursor, Some(CursorPos { row: 1, col: 3 }));
+
+        runtime.remove_component(footer_id);
+        runtime.render_if_needed();
+        assert_eq!(runtime.renderer.previous_lines(), ["body", "", "", "", ""]);
+    }
+
+    #[test]
+    fn footer_leaves_a_root_row_and_footer_set_rejects_missing_ids() {
+        let terminal = TestTerminal::new(20, 3);
+        let mut runtime = TuiRuntime::new(terminal);
+        let body_id = runtime.register_component(StaticLinesComponent {
+            lines: vec!["body".to_string()],
+            cursor: None,
+        });
+        let footer_id = runtime.register_component(StaticLinesComponent {
+            lines: (1..6).map(|row| format!("footer {row}")).collect(),
+            cursor: None,
+        });
+        let diagnostics = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&diagnostics);
+        runtime.set_on_diagnostic(Some(Box::new(move |message| {
+            sink.borrow_mut().push(message.to_string());
+        })));
+        runtime.set_root(vec![body_id]);
+        runtime.show_hardware_cursor = false;
+        runtime.start().expect("runtime start");
+        let handle = runtime.runtime_handle();
+
+        handle.dispatch(Command::FooterSet(Some(footer_id)));
+        runtime.run_once();
+        assert_eq!(
+            runtime.renderer.previous_lines(),
+            ["body", "footer 1", "footer 2"]
+        );
+
+        let missing_id = runtime.register_component(StaticLinesComponent {
+            lines: Vec::new(),
+            cursor: None,
+        });
+        runtime.remove_component(missing_id);
+        handle.dispatch(Command::FooterSet(Some(missing_id)));
+        runtime.run_once();
+        assert!(diagnostics
+            .borrow()
+            .iter()
+            .any(|message| message.contains("command.footer_set.missing_component_id")));
+        assert_eq!(runtime.footer, Some(footer_id));
+
+        handle.dispatch(Command::FooterSet(None));
+        runtime.run_once();
+        assert_eq!(runtime.renderer.previous_lines(), ["body"]);
+    }
+
+    #[test]
+    fn throttled_surface_reuses_its_render_until_the_interval_elapses() {
+        let terminal = TestTerminal::new(20, 6);
+        let mut runtime = TuiRuntime::new(terminal);
+        let root_id = runtime.register_component(StaticLinesComponent {
+            lines: vec!["root".to_string()],
+            cursor: None,
+        });
+        let text = Rc::new(RefCell::new("tokens 1".to_string()));
+        let renders = Rc::new(RefCell::new(0usize));
+        let counter_id = runtime.register_component(MutableTextComponent::new(
+            Rc::clone(&text),
+            Rc::clone(&renders),
+        ));
+        runtime.set_root(vec![root_id]);
+        runtime.show_hardware_cursor = false;
+        runtime.start().expect("runtime start");
+        runtime.show_surface(
+            counter_id,
+            Some(SurfaceOptions {
+                input_policy: SurfaceInputPolicy::Passthrough,
+                min_update_interval: Some(Duration::from_secs(3600)),
+                ..Default::default()
+            }),
+        );
+        runtime.run_once();
+        assert_eq!(*renders.borrow(), 1);
+
+        *text.borrow_mut() = "tokens 2".to_string();
+        runtime.terminal.output.clear();
+        runtime.request_render();
+        runtime.run_once();
+        assert_eq!(*renders.borrow(), 1);
+        assert!(!runtime.terminal.output.contains("tokens 2"));
+        assert!(runtime.wake.state.lock().unwrap().render_due_at.is_some());
+
+        // Once the interval has elapsed the next frame renders it again.
+        let throttled = runtime
+            .throttled_renders
+            .get_mut(&counter_id)
+            .expect("throttled render");
+        if let Some(earlier) = throttled.rendered_at.checked_sub(Duration::from_secs(7200)) {
+            throttled.rendered_at = earlier;
+            runtime.request_render();
+            runtime.run_once();
+            assert_eq!(*renders.borrow(), 2);
+            assert!(runtime.terminal.output.contains("tokens 2"));
+        }
+    }
+
+    #[test]
+    fn deferred_render_wakes_the_loop_once_due() {
+        let wake = RuntimeWake::default();
+        wake.schedule_render_at(Instant::now() + Duration::from_secs(3600));
+        assert!(!wake.peek_render_requested());
+
+        wake.schedule_render_at(Instant::now());
+        assert!(wake.wait_for_event());
+        assert!(wake.take_render_requested());
+        assert!(!wake.take_render_requested());
+    }
+
+    struct ReleasingTranscript {
+        rows: Rc<RefCell<usize>>,
+        released: usize,
+    }
+
+    impl Component for ReleasingTranscript {
+        fn render(&mut self, _width: usize) -> Vec<String> {
+            (self.released..*self.rows.borrow())
+                .map(|row| format!("row {row}"))
+                .collect()
+        }
+
+        fn release_scrolled_rows(&mut self, scrolled_rows: usize) -> usize {
+            self.released += scrolled_rows;
+            scrolled_rows
+        }
+    }
+
+    #[test]
+    fn released_scrollback_rows_are_forgotten_without_a_redraw() {
+        let terminal = TestTerminal::new(20, 4);
+        let mut runtime = TuiRuntime::new(terminal);
+        let rows = Rc::new(RefCell::new(10));
+        let transcript_id = runtime.register_component(ReleasingTranscript {
+            rows: Rc::clone(&rows),
+            released: 0,
+        });
+        runtime.set_root(vec![transcript_id]);
+        runtime.show_hardware_cursor = false;
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        assert_eq!(
+            runtime.renderer.previous_lines(),
+            ["row 6", "row 7", "row 8", "row 9"]
+        );
+
+        *rows.borrow_mut() = 11;
+        runtime.terminal.output.clear();
+        runtime.request_render();
+        runtime.render_if_needed();
+        assert!(runtime.terminal.output.contains("row 10"));
+        assert!(!runtime.terminal.output.contains("row 7"));
+        assert!(!runtime.terminal.output.contains("\x1b[2J"));
+        assert_eq!(
+            runtime.renderer.previous_lines(),
+            ["row 7", "row 8", "row 9", "row 10"]
+        );
+
+        // Unchanged content diffs clean against the forgotten rows.
+        runtime.terminal.output.clear();
+        runtime.request_render();
+        runtime.render_if_needed();
+        assert!(!runtime.terminal.output.contains("row"));
+    }
+
+    #[test]
+    fn minimum_size_shows_notice_until_resize_makes_room() {
+        let terminal = TestTerminal::new(12, 5);
+        let text = Rc::new(RefCell::new("content".to_string()));
+        let renders = Rc::new(RefCell::new(0usize));
+        let component = MutableTextComponent::new(Rc::clone(&text), Rc::clone(&renders));
+        let (mut runtime, _root_id) = runtime_with_root(terminal, component);
+        runtime.show_hardware_cursor = false;
+        runtime.set_minimum_size(Some(MinimumSize {
+            columns: 30,
+            rows: 4,
+        }));
+
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        assert_eq!(*renders.borrow(), 0);
+        assert!(
+            runtime.terminal.output.contains("terminal too")
+                && runtime.terminal.output.contains("need ≥ 30x4"),
+            "expected the notice, got: {:?}",
+            runtime.terminal.output
+        );
+        assert!(!runtime.terminal.output.contains("content"));
+
+        runtime.terminal.output.clear();
+        runtime.terminal.columns = 40;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        assert_eq!(*renders.borrow(), 1);
+        assert!(runtime.terminal.output.contains("content"));
+        assert!(!runtime.terminal.output.contains("too small"));
+    }
+
+    struct KeyedComponent {
+        key: Rc<RefCell<u64>>,
+        renders: Rc<RefCell<usize>>,
+    }
+
+    impl Component for KeyedComponent {
+        fn render(&mut self, _width: usize) -> Vec<String> {
+            *self.renders.borrow_mut() += 1;
+            vec![format!("version {}", self.key.borrow())]
+        }
+
+        fn cache_key(&self) -> Option<u64> {
+            Some(*self.key.borrow())
+        }
+    }
+
+    #[test]
+    fn root_render_reuses_cached_lines_until_key_or_width_changes() {
+        let terminal = TestTerminal::new(20, 5);
+        let key = Rc::new(RefCell::new(1u64));
+        let renders = Rc::new(RefCell::new(0usize));
+        let component = KeyedComponent {
+            key: Rc::clone(&key),
+            renders: Rc::clone(&renders),
+        };
+        let (mut runtime, _root_id) = runtime_with_root(terminal, component);
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        assert_eq!(*renders.borrow(), 1);
+
+        runtime.request_render();
+        runtime.run_once();
+        assert_eq!(*renders.borrow(), 1);
+
+        *key.borrow_mut() = 2;
+        runtime.request_render();
+        runtime.run_once();
+        assert_eq!(*renders.borrow(), 2);
+        assert!(runtime.terminal.output.contains("version 2"));
+
+        runtime.terminal.columns = 30;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        assert_eq!(*renders.borrow(), 3);
     }
 
     #[test]
-    fn root_stack_concatenates_children_and_offsets_cursor() {
-        let terminal = TestTerminal::default();
-        let mut runtime = TuiRuntime::new(terminal);
-        let first = StaticLinesComponent {
-            lines: vec!["one".to_string()],
-            cursor: Some(CursorPos { row: 0, col: 0 }),
-        };
-        let second = StaticLinesComponent {
-            lines: vec!["two".to_string(), "three".to_string()],
-            cursor: Some(CursorPos { row: 1, col: 2 }),
-        };
-        let first_id = runtime.register_component(first);
-        let second_id = runtime.register_component(second);
-        runtime.set_root(vec![first_id, second_id]);
+    fn spawned_work_result_reaches_component_and_renders() {
+        let terminal = TestTerminal::new(20, 5);
+        let text = Rc::new(RefCell::new("pending".to_string()));
+        let renders = Rc::new(RefCell::new(0usize));
+        let component = MutableTextComponent::new(Rc::clone(&text), Rc::clone(&renders));
+        let (mut runtime, component_id) = runtime_with_root(terminal, component);
+        runtime.show_hardware_cursor = false;
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        runtime.terminal.output.clear();
 
-        let (lines, cursor) = runtime.render_root(10, 24);
-        assert_eq!(lines, vec!["one", "two", "three"]);
-        assert_eq!(cursor, Some(CursorPos { row: 2, col: 2 }));
+        let runtime_thread = std::thread::current().id();
+        runtime.runtime_handle().spawn_work(component_id, move || {
+            assert_ne!(std::thread::current().id(), runtime_thread);
+            "highlighted".to_string()
+        });
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while text.borrow().as_str() != "highlighted" {
+            assert!(std::time::Instant::now() < deadline, "work never delivered");
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            runtime.run_once();
+        }
+        assert!(runtime.terminal.output.contains("highlighted"));
     }
 
     #[test]
@@ -3221,6 +6058,149 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lifecycle_hooks_follow_registration_surfaces_and_resize() {
+        let terminal = TestTerminal::new(80, 24);
+        let (mut runtime, root_id) = runtime_with_root(terminal, DummyComponent::default());
+        runtime.start().expect("runtime start");
+        runtime.set_focus(root_id);
+        runtime.run_once();
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let component_id = runtime.register_component(LifecycleComponent {
+            calls: Rc::clone(&calls),
+        });
+        let surface = runtime.show_surface(component_id, None);
+        runtime.run_once();
+        surface.set_hidden(true);
+        runtime.run_once();
+        surface.set_hidden(false);
+        runtime.run_once();
+
+        runtime.terminal.columns = 100;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+
+        // Shown in the root as well, hiding the surface keeps it mounted.
+        runtime.push_root(component_id);
+        surface.set_hidden(true);
+        runtime.run_once();
+        surface.set_hidden(false);
+        runtime.run_once();
+
+        let removed = runtime.remove_component(component_id);
+        assert!(removed.is_some());
+        assert!(runtime.surfaces.entries.is_empty());
+        assert_eq!(runtime.root, vec![root_id]);
+        assert!(runtime.remove_component(component_id).is_none());
+
+        assert_eq!(
+            *calls.borrow(),
+            vec!["mount", "unmount", "mount", "resize 100x24", "unmount"]
+        );
+
+        let never_shown = runtime.register_component(LifecycleComponent {
+            calls: Rc::clone(&calls),
+        });
+        calls.borrow_mut().clear();
+        assert!(runtime.remove_component(never_shown).is_some());
+        assert!(calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn debounced_resize_clips_last_frame_until_it_settles() {
+        struct ResizeProbe {
+            calls: Rc<RefCell<Vec<String>>>,
+        }
+
+        impl Component for ResizeProbe {
+            fn render(&mut self, width: usize) -> Vec<String> {
+                self.calls.borrow_mut().push(format!("render {width}"));
+                vec!["abcdefghij".to_string()]
+            }
+
+            fn on_resize(&mut self, columns: u16, rows: u16) {
+                self.calls
+                    .borrow_mut()
+                    .push(format!("resize {columns}x{rows}"));
+            }
+        }
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (mut runtime, _root_id) = runtime_with_root(
+            TestTerminal::new(10, 5),
+            ResizeProbe {
+                calls: Rc::clone(&calls),
+            },
+        );
+        runtime.set_resize_debounce(Some(Duration::from_millis(30)));
+        runtime.start().expect("runtime start");
+        runtime.run_once();
+        assert_eq!(*calls.borrow(), vec!["render 10"]);
+
+        runtime.terminal.output.clear();
+        runtime.terminal.columns = 6;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        assert_eq!(*calls.borrow(), vec!["render 10"]);
+        assert!(runtime.terminal.output.contains("abcdef"));
+        assert!(!runtime.terminal.output.contains("abcdefg"));
+
+        runtime.terminal.columns = 4;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        assert_eq!(*calls.borrow(), vec!["render 10"]);
+
+        // Blocks until the quiet period passes, then settles once.
+        runtime.run_blocking_once();
+        assert_eq!(*calls.borrow(), vec!["render 10", "resize 4x5", "render 4"]);
+    }
+
+    #[test]
+    fn sent_messages_reach_on_message_and_request_render() {
+        let terminal = TestTerminal::new(20, 5);
+        let text = Rc::new(RefCell::new("before".to_string()));
+        let renders = Rc::new(RefCell::new(0usize));
+        let component = MutableTextComponent::new(Rc::clone(&text), Rc::clone(&renders));
+        let (mut runtime, component_id) = runtime_with_root(terminal, component);
+        let diagnostics = Rc::new(RefCell::new(Vec::<String>::new()));
+        let sink = Rc::clone(&diagnostics);
+        runtime.set_on_diagnostic(Some(Box::new(move |message| {
+            sink.borrow_mut().push(message.to_string());
+        })));
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        let baseline_renders = *renders.borrow();
+
+        let handle = runtime.runtime_handle();
+        handle.send(component_id, Box::new("flash".to_string()));
+        handle.send(component_id, Box::new(42_u32));
+        runtime.run_once();
+        assert_eq!(text.borrow().as_str(), "flash");
+        assert_eq!(*renders.borrow(), baseline_renders + 1);
+
+        handle.dispatch(Command::Custom(Box::new(SendMessageCustomCommand {
+            target: component_id,
+            text: "from command",
+        })));
+        runtime.run_once();
+        assert_eq!(text.borrow().as_str(), "from command");
+
+        let mut id_source_runtime = TuiRuntime::new(TestTerminal::default());
+        let _ = id_source_runtime.register_component(DummyComponent::default());
+        let missing = id_source_runtime.register_component(DummyComponent::default());
+        handle.send(missing, Box::new("lost".to_string()));
+        runtime.run_once();
+        assert!(
+            diagnostics
+                .borrow()
+                .iter()
+                .any(|message| message.contains("command.send_message.missing_component_id")),
+            "expected missing component diagnostic, got: {:?}",
+            diagnostics.borrow()
+        );
+    }
+
     #[test]
     fn custom_command_terminal_ops_flush_only_at_tick_boundary() {
         let _guard = env_test_lock().lock().expect("test lock poisoned");
@@ -3556,6 +6536,53 @@ mod tests {
         assert_eq!(terminal.output, "\x1b[?25h\x1b[?2004l\x1b[<u");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn caught_component_panics_leave_terminal_modes_and_crash_cleanup_armed() {
+        let _lock = crate::platform::process_terminal::panic_hook_test_lock()
+            .lock()
+            .expect("panic hook test lock poisoned");
+        let cleanup = Arc::new(CrashCleanup::default());
+        let crash_terminal = Arc::new(Mutex::new(TestTerminal::default()));
+        let test_thread = thread::current().id();
+        let hook_guard = crate::platform::install_panic_hook({
+            let cleanup = Arc::clone(&cleanup);
+            let crash_terminal = Arc::clone(&crash_terminal);
+            move || {
+                if thread::current().id() == test_thread {
+                    cleanup.run(&mut *crash_terminal.lock().expect("crash terminal lock"));
+                }
+            }
+        });
+
+        let fail_render = Rc::new(Cell::new(true));
+        let mut runtime = TuiRuntime::new(TestTerminal::new(40, 6));
+        let fragile = runtime.register_component(FragileComponent {
+            fail_render: Rc::clone(&fail_render),
+        });
+        runtime.set_root(vec![fragile]);
+        runtime.start().expect("runtime start");
+        runtime.render_now();
+        assert_eq!(runtime.component_fault(fragile), Some("render exploded"));
+        let output = runtime.terminal.output.clone();
+        let caught = crash_terminal
+            .lock()
+            .expect("crash terminal lock")
+            .output
+            .clone();
+
+        // A panic outside a component boundary still restores the terminal.
+        let _ = std::panic::catch_unwind(|| panic!("outside"));
+        drop(hook_guard);
+        assert_eq!(caught, "");
+        assert!(!output.contains("\x1b[?2004l"), "{output:?}");
+        assert!(!output.contains("\x1b[<u"), "{output:?}");
+        assert_eq!(
+            crash_terminal.lock().expect("crash terminal lock").output,
+            "\x1b[?25h\x1b[?2004l\x1b[<u"
+        );
+    }
+
     #[test]
     fn key_release_filtered_unless_requested() {
         let terminal = TestTerminal::default();
@@ -3579,6 +6606,71 @@ mod tests {
         assert_eq!(inputs_release.borrow().len(), 1);
     }
 
+    struct KeyTypesComponent {
+        types: KeyEventTypes,
+        seen: Rc<RefCell<Vec<(String, KeyEventType)>>>,
+    }
+
+    impl Component for KeyTypesComponent {
+        fn render(&mut self, _width: usize) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn handle_event(&mut self, event: &InputEvent) {
+            if let InputEvent::Text {
+                text, event_type, ..
+            } = event
+            {
+                self.seen.borrow_mut().push((text.clone(), *event_type));
+            }
+        }
+
+        fn wants_key_events(&self) -> KeyEventTypes {
+            self.types
+        }
+    }
+
+    #[test]
+    fn kitty_text_repeat_and_release_route_by_wanted_key_events() {
+        let terminal = TestTerminal::default();
+        let (mut runtime, _root_id) = runtime_with_root(terminal, DummyComponent::default());
+        runtime.kitty_keyboard_enabled = true;
+
+        let hold = Rc::new(RefCell::new(Vec::new()));
+        let hold_id = runtime.register_component(KeyTypesComponent {
+            types: KeyEventTypes::PRESS | KeyEventTypes::RELEASE,
+            seen: Rc::clone(&hold),
+        });
+        runtime.set_focus(hold_id);
+        for data in [" ", "\x1b[32;1:2u", "\x1b[32;1:3u"] {
+            runtime.handle_input(data);
+        }
+        assert_eq!(
+            hold.borrow().as_slice(),
+            &[
+                (" ".to_string(), KeyEventType::Press),
+                (" ".to_string(), KeyEventType::Release),
+            ]
+        );
+
+        let typing = Rc::new(RefCell::new(Vec::new()));
+        let typing_id = runtime.register_component(KeyTypesComponent {
+            types: KeyEventTypes::default(),
+            seen: Rc::clone(&typing),
+        });
+        runtime.set_focus(typing_id);
+        for data in [" ", "\x1b[32;1:2u", "\x1b[32;1:3u"] {
+            runtime.handle_input(data);
+        }
+        assert_eq!(
+            typing.borrow().as_slice(),
+            &[
+                (" ".to_string(), KeyEventType::Press),
+                (" ".to_string(), KeyEventType::Repeat),
+            ]
+        );
+    }
+
     #[test]
     fn parse_cell_size_response_extracts_dimensions() {
         let data = "\x1b[6;18;9t";
@@ -3615,6 +6707,112 @@ mod tests {
         std::env::remove_var("TERM_PROGRAM");
     }
 
+    #[test]
+    fn capability_cache_records_probe_answers_and_skips_them_next_start() {
+        let _guard = env_test_lock().lock().expect("test lock poisoned");
+        std::env::set_var("TERM_PROGRAM", "kitty");
+        let dir = std::env::temp_dir().join(format!("tape-runtime-caps-{}", std::process::id()));
+        let cache = CapabilityCache::new(dir.join("capabilities"));
+
+        let (mut runtime, _root_id) = runtime_with_root(
+            TestTerminal::default(),
+            StaticLinesComponent {
+                lines: Vec::new(),
+                cursor: None,
+            },
+        );
+        runtime.set_capability_cache(Some(cache.clone()));
+        runtime.start().expect("runtime start");
+        assert!(runtime.terminal.output.contains("\x1b[?u"));
+        assert!(runtime.terminal.output.contains("\x1b[16t"));
+        let stored = cache
+            .load(&CapabilityCache::terminal_key())
+            .expect("detected capabilities stored on a miss");
+        assert_eq!(stored.cell_dimensions, None);
+        assert!(!stored.kitty_keyboard);
+
+        runtime.handle_input("\x1b[?1u\x1b[6;20;10t");
+        let stored = cache
+            .load(&CapabilityCache::terminal_key())
+            .expect("probe answers stored");
+        assert!(stored.kitty_keyboard);
+        assert_eq!(
+            stored.cell_dimensions,
+            Some(CellDimensions {
+                width_px: 10,
+                height_px: 20,
+            })
+        );
+        runtime.stop().expect("runtime stop");
+
+        let (mut runtime, _root_id) = runtime_with_root(
+            TestTerminal::default(),
+            StaticLinesComponent {
+                lines: Vec::new(),
+                cursor: None,
+            },
+        );
+        runtime.set_capability_cache(Some(cache.clone()));
+        runtime.start().expect("runtime start");
+        assert!(!runtime.terminal.output.contains("\x1b[?u"));
+        assert!(!runtime.terminal.output.contains("\x1b[16t"));
+        assert!(runtime.terminal.output.contains("\x1b[>7u"));
+        assert!(runtime.kitty_protocol_active());
+        let dims = get_cell_dimensions(runtime.terminal_image_state.as_ref());
+        assert_eq!((dims.width_px, dims.height_px), (10, 20));
+        runtime.stop().expect("runtime stop");
+
+        runtime.reset_capability_cache();
+        assert_eq!(cache.load(&CapabilityCache::terminal_key()), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+        std::env::remove_var("TERM_PROGRAM");
+    }
+
+    #[test]
+    fn suspend_restores_terminal_then_reenables_protocols_and_redraws() {
+        let (mut runtime, _root_id) = runtime_with_root(
+            TestTerminal::default(),
+            StaticLinesComponent {
+                lines: vec!["frame".to_string()],
+                cursor: None,
+            },
+        );
+        runtime.start().expect("runtime start");
+        runtime.handle_input("\x1b[?1u");
+        runtime.render_if_needed();
+        assert!(runtime.kitty_protocol_active());
+        runtime.terminal.output.clear();
+
+        let runtime_handle = runtime.runtime_handle();
+        runtime_handle.dispatch(Command::Suspend);
+        runtime.run_once();
+
+        let output = runtime.terminal.output.clone();
+        let (before, after) = output
+            .split_once("<suspended>")
+            .expect("terminal suspended");
+        for restore in ["\x1b[?25h", "\x1b[?2004l", "\x1b[<u"] {
+            assert!(
+                before.contains(restore),
+                "missing {restore:?} before suspend"
+            );
+        }
+        for reenable in ["\x1b[?2004h", "\x1b[>7u", "\x1b[?25l"] {
+            assert!(
+                after.contains(reenable),
+                "missing {reenable:?} after resume"
+            );
+        }
+        assert!(after.contains("frame"), "frame redrawn after resume");
+        assert!(runtime.kitty_protocol_active());
+
+        runtime.stop().expect("runtime stop");
+        runtime.terminal.output.clear();
+        runtime.suspend().expect("suspend while stopped");
+        assert!(runtime.terminal.output.is_empty());
+    }
+
     #[test]
     fn output_order_is_protocol_then_frame_then_cursor() {
         let _guard = env_test_lock().lock().expect("test lock poisoned");
@@ -4110,6 +7308,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn legacy_keys_under_kitty_degrade_input_until_a_reprobe_answers() {
+        struct KeyLog(Rc<RefCell<Vec<String>>>);
+
+        impl Component for KeyLog {
+            fn render(&mut self, _width: usize) -> Vec<String> {
+                Vec::new()
+            }
+
+            fn handle_event(&mut self, event: &InputEvent) {
+                if let InputEvent::Key { key_id, .. } = event {
+                    self.0.borrow_mut().push(key_id.clone());
+                }
+            }
+        }
+
+        let keys = Rc::new(RefCell::new(Vec::new()));
+        let (mut runtime, root_id) =
+            runtime_with_root(TestTerminal::new(20, 5), KeyLog(Rc::clone(&keys)));
+        let diagnostics = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&diagnostics);
+        runtime.set_on_diagnostic(Some(Box::new(move |message| {
+            sink.borrow_mut().push(message.to_string());
+        })));
+        runtime.set_focus(root_id);
+        runtime.start().expect("runtime start");
+        assert_eq!(runtime.input_protocol(), InputProtocol::Legacy);
+        runtime.handle_input("\x1b[?1u");
+        runtime.run_once();
+        assert_eq!(runtime.input_protocol(), InputProtocol::Kitty);
+
+        // A lone Escape may be a sequence split by a slow link, not a key.
+        runtime.handle_input("\x1b");
+        assert_eq!(runtime.input_protocol(), InputProtocol::Kitty);
+
+        // Alt+Enter in the legacy encoding is read as Shift+Enter under kitty;
+        // a legacy-only key reveals the protocol is not actually in effect.
+        runtime.handle_input("\x1bq");
+        assert_eq!(runtime.input_protocol(), InputProtocol::Degraded);
+        assert!(!runtime.kitty_protocol_active());
+        runtime.handle_input("\x1b\r");
+        assert_eq!(*keys.borrow(), vec!["escape", "alt+q", "alt+enter"]);
+        assert_eq!(diagnostics.borrow().len(), 1);
+        assert!(diagnostics.borrow()[0].contains("[input.kitty_degraded]"));
+
+        runtime.terminal.output.clear();
+        runtime.terminal.columns = 30;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        assert!(runtime.terminal.output.contains("\x1b[?u"));
+        runtime.handle_input("\x1b[?1u");
+        runtime.run_once();
+        assert_eq!(runtime.input_protocol(), InputProtocol::Kitty);
+        assert!(runtime.terminal.output.contains("\x1b[>7u"));
+
+        // Once kitty works, resizes and focus-in do not probe again.
+        runtime.terminal.output.clear();
+        runtime.handle_input("\x1b[I");
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        assert!(!runtime.terminal.output.contains("\x1b[?u"));
+    }
+
     #[test]
     fn cell_dimensions_are_runtime_scoped() {
         let terminal_a = TestTerminal::default();
@@ -4436,6 +7697,7 @@ mod tests {
                 visibility: SurfaceVisibility::MinCols(120),
                 ..Default::default()
             },
+            min_update_interval: None,
         }));
         runtime.run_once();
 
@@ -4754,6 +8016,7 @@ mod tests {
                     visibility: SurfaceVisibility::MinCols(10),
                     ..Default::default()
                 },
+                min_update_interval: None,
             }),
         );
         runtime.run_once();
@@ -4992,15 +8255,81 @@ mod tests {
         });
         runtime.run_once();
 
-        assert!(!runtime.surfaces.entries[0].hidden);
-        assert!(*surface_focus.borrow());
-        assert!(!*root_focus.borrow());
+        assert!(!runtime.surfaces.entries[0].hidden);
+        assert!(*surface_focus.borrow());
+        assert!(!*root_focus.borrow());
+
+        handle.dispatch(Command::HideSurface(surface_id));
+        runtime.run_once();
+
+        assert!(runtime.surfaces.entries.is_empty());
+        assert!(*root_focus.borrow());
+    }
+
+    #[test]
+    fn runtime_handle_subscribers_observe_runtime_events_in_order() {
+        let terminal = TestTerminal::new(80, 24);
+        let root_component = TestComponent::new(
+            false,
+            Rc::new(RefCell::new(Vec::new())),
+            Rc::new(RefCell::new(false)),
+        );
+        let (mut runtime, root_id) = runtime_with_root(terminal, root_component);
+        runtime.start().expect("runtime start");
+        runtime.set_focus(root_id);
+        runtime.run_once();
+
+        let surface_component = TestComponent::new(
+            false,
+            Rc::new(RefCell::new(Vec::new())),
+            Rc::new(RefCell::new(false)),
+        );
+        let surface_component_id = runtime.register_component(surface_component);
+
+        let handle = runtime.runtime_handle();
+        let events = handle.subscribe();
+        let dropped = handle.subscribe();
+        drop(dropped);
 
-        handle.dispatch(Command::HideSurface(surface_id));
+        let surface = handle.show_surface(
+            surface_component_id,
+            Some(SurfaceOptions {
+                input_policy: SurfaceInputPolicy::Capture,
+                kind: SurfaceKind::Modal,
+                ..Default::default()
+            }),
+            false,
+        );
+        runtime.run_once();
+        surface.set_hidden(true);
+        runtime.run_once();
+        runtime.terminal.columns = 100;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        handle.dispatch(Command::RequestStop);
         runtime.run_once();
 
-        assert!(runtime.surfaces.entries.is_empty());
-        assert!(*root_focus.borrow());
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![
+                RuntimeEvent::FocusChanged {
+                    previous: Some(root_id),
+                    focused: Some(surface_component_id),
+                },
+                RuntimeEvent::SurfaceShown(surface.id),
+                RuntimeEvent::FocusChanged {
+                    previous: Some(surface_component_id),
+                    focused: Some(root_id),
+                },
+                RuntimeEvent::SurfaceHidden(surface.id),
+                RuntimeEvent::Resized {
+                    columns: 100,
+                    rows: 24,
+                },
+                RuntimeEvent::StopRequested,
+            ]
+        );
+        assert!(runtime.wake.subscribers.lock().unwrap().len() == 1);
     }
 
     #[test]
@@ -5917,6 +9246,7 @@ mod tests {
                         visibility: SurfaceVisibility::MinCols(6),
                         ..Default::default()
                     },
+                    min_update_interval: None,
                 }),
                 hidden: false,
             },
@@ -5929,6 +9259,7 @@ mod tests {
                         visibility: SurfaceVisibility::MinCols(20),
                         ..Default::default()
                     },
+                    min_update_interval: None,
                 }),
             },
             SurfaceTransactionMutation::UpdateOptions {
@@ -5940,6 +9271,7 @@ mod tests {
                         visibility: SurfaceVisibility::MinCols(6),
                         ..Default::default()
                     },
+                    min_update_interval: None,
                 }),
             },
             SurfaceTransactionMutation::SetHidden {
@@ -5959,6 +9291,7 @@ mod tests {
                         visibility: SurfaceVisibility::MinCols(6),
                         ..Default::default()
                     },
+                    min_update_interval: None,
                 }),
             },
         ]);
@@ -6212,12 +9545,13 @@ mod tests {
                 width: Some(SurfaceSizeValue::absolute(7)),
                 ..Default::default()
             },
+            min_update_interval: None,
         };
 
         runtime.show_surface(toast_a_id, Some(toast_options));
         runtime.show_surface(toast_b_id, Some(toast_options));
 
-        let (lines, _cursor) = runtime.render_root(12, 2);
+        let (lines, _cursor) = runtime.render_root(12, 2, 0);
         let (composited, _surface_cursor) = runtime.composite_surface_lines(lines, 12, 2);
 
         assert_eq!(composited.len(), 4);
@@ -6261,6 +9595,7 @@ mod tests {
             layout: layout_options,
             kind: SurfaceKind::Modal,
             input_policy: SurfaceInputPolicy::Capture,
+            min_update_interval: None,
         };
         let lane_adjusted_layout = surface_options.with_lane_reservations(0, 0);
         let surface_render_options =
@@ -6346,12 +9681,13 @@ mod tests {
                 width: Some(SurfaceSizeValue::absolute(10)),
                 ..Default::default()
             },
+            min_update_interval: None,
         };
 
         runtime.show_surface(toast_a_id, Some(toast_options));
         runtime.show_surface(toast_b_id, Some(toast_options));
 
-        let (lines, _cursor) = runtime.render_root(40, 6);
+        let (lines, _cursor) = runtime.render_root(40, 6, 0);
         let (composited, _surface_cursor) = runtime.composite_surface_lines(lines, 40, 6);
 
         assert!(
@@ -6851,6 +10187,59 @@ mod tests {
         assert_eq!(runtime.terminal.output, "\x1b]0;tape\x07");
     }
 
+    #[test]
+    fn clipboard_handle_flushes_osc52_without_render() {
+        let terminal = TestTerminal::default();
+        let state = Rc::new(RefCell::new(RenderState::default()));
+        let component = CountingComponent {
+            state: Rc::clone(&state),
+        };
+        let (mut runtime, _root_id) = runtime_with_root(terminal, component);
+
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        let baseline = state.borrow().renders;
+        runtime.terminal.output.clear();
+
+        let handle = runtime.runtime_handle();
+        handle.dispatch(Command::SetClipboard("hi".to_string()));
+        handle.dispatch(Command::SetClipboard("tape".to_string()));
+
+        runtime.run_once();
+        assert_eq!(state.borrow().renders, baseline);
+        assert_eq!(
+            runtime.terminal.output,
+            "\x1b]52;c;aGk=\x07\x1b]52;c;dGFwZQ==\x07"
+        );
+    }
+
+    #[test]
+    fn notify_desktop_handle_flushes_notification_without_render() {
+        let terminal = TestTerminal::default();
+        let state = Rc::new(RefCell::new(RenderState::default()));
+        let component = CountingComponent {
+            state: Rc::clone(&state),
+        };
+        let (mut runtime, _root_id) = runtime_with_root(terminal, component);
+
+        runtime.start().expect("runtime start");
+        runtime.render_if_needed();
+        let baseline = state.borrow().renders;
+        runtime.terminal.output.clear();
+
+        runtime
+            .runtime_handle()
+            .notify_desktop("Run finished", "3 files changed");
+        runtime.run_once();
+
+        let protocol = get_capabilities(runtime.terminal_image_state.as_ref()).notifications;
+        assert_eq!(state.borrow().renders, baseline);
+        assert_eq!(
+            runtime.terminal.output,
+            notification_sequence(protocol, "Run finished", "3 files changed")
+        );
+    }
+
     #[test]
     fn title_last_wins_coalescing() {
         let terminal = TestTerminal::default();
@@ -7313,4 +10702,451 @@ mod tests {
             "expected no scrollback clear (ESC[3J), got: {output:?}"
         );
     }
+
+    #[test]
+    fn host_commands_take_their_keys_before_components_and_report_shadowing() {
+        let inputs = Rc::new(RefCell::new(Vec::new()));
+        let focused = Rc::new(RefCell::new(false));
+        let component = TestComponent::new(false, Rc::clone(&inputs), focused);
+        let (mut runtime, _root_id) = runtime_with_root(TestTerminal::default(), component);
+
+        let runs = Rc::new(RefCell::new(0));
+        let counter = Rc::clone(&runs);
+        runtime.register_host_command(
+            HostCommand::new("clear", "Clear transcript", move || {
+                *counter.borrow_mut() += 1;
+                Command::RequestRender
+            })
+            .with_key("ctrl+k"),
+        );
+
+        runtime.handle_input("\x0b");
+        runtime.handle_input("a");
+        assert_eq!(*runs.borrow(), 1);
+        assert_eq!(inputs.borrow().as_slice(), &["a".to_string()]);
+
+        runtime.set_editor_keybindings(Some(crate::default_editor_keybindings_handle()));
+        assert_eq!(
+            runtime.keybinding_conflicts(),
+            vec![KeybindingConflict {
+                key: "ctrl+k".to_string(),
+                kind: KeybindingConflictKind::Shadowed,
+                context: KeybindingContext::Global,
+                owners: vec![
+                    KeybindingOwner::Command("clear".to_string()),
+                    KeybindingOwner::Action(EditorAction::DeleteToLineEnd),
+                ],
+            }]
+        );
+
+        let help = runtime.keybinding_help();
+        assert_eq!(help[0].context, KeybindingContext::Global);
+        assert_eq!(
+            help[0]
+                .entries
+                .iter()
+                .map(|entry| entry.owner.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                KeybindingOwner::Runtime("debug"),
+                KeybindingOwner::Command("clear".to_string()),
+            ]
+        );
+        assert_eq!(help[1].context, KeybindingContext::Editing);
+
+        assert!(runtime.set_host_command_keys("clear", vec!["ctrl+g".to_string()]));
+        assert!(runtime.keybinding_conflicts().is_empty());
+        runtime.handle_input("\x07");
+        assert_eq!(*runs.borrow(), 2);
+        assert!(!runtime.set_host_command_keys("missing", Vec::new()));
+
+        assert!(runtime.unregister_host_command("clear"));
+        runtime.handle_input("\x07");
+        assert_eq!(*runs.borrow(), 2);
+        assert_eq!(inputs.borrow().len(), 2);
+    }
+
+    #[test]
+    fn help_overlay_toggles_and_lists_commands_slash_commands_and_keymap() {
+        let inputs = Rc::new(RefCell::new(Vec::new()));
+        let focused = Rc::new(RefCell::new(false));
+        let component = TestComponent::new(false, Rc::clone(&inputs), Rc::clone(&focused));
+        let (mut runtime, root_id) = runtime_with_root(TestTerminal::new(60, 30), component);
+        runtime.start().expect("runtime start");
+        runtime.set_focus(root_id);
+        runtime.run_once();
+
+        runtime.register_host_command(
+            HostCommand::new("clear", "Clear transcript", || Command::RequestRender)
+                .with_category("Session")
+                .with_key("ctrl+l"),
+        );
+        runtime.set_slash_commands(vec![SlashCommand {
+            name: "model".to_string(),
+            description: Some("Switch model".to_string()),
+            get_argument_completions: None,
+        }]);
+        runtime.set_editor_keybindings(Some(crate::default_editor_keybindings_handle()));
+        runtime.set_help_overlay(Some(HelpOverlayOptions::default()));
+
+        let titles: Vec<String> = runtime
+            .help_sections()
+            .into_iter()
+            .map(|section| section.title)
+            .collect();
+        assert_eq!(
+            &titles[..4],
+            &["Session", "General", "Slash commands", "Editing"]
+        );
+
+        runtime.handle_input("?");
+        runtime.run_once();
+        assert!(runtime.is_help_overlay_open());
+        assert!(inputs.borrow().is_empty());
+        assert!(!*focused.borrow());
+
+        // Typing filters the overlay instead of reaching the focused root.
+        runtime.handle_input("model");
+        runtime.run_once();
+        assert!(inputs.borrow().is_empty());
+        runtime.render_now();
+        assert!(runtime.terminal.output.contains("/model"));
+
+        runtime.handle_input("?");
+        runtime.run_once();
+        assert!(!runtime.is_help_overlay_open());
+        assert!(*focused.borrow());
+
+        runtime.handle_input("?");
+        runtime.run_once();
+        runtime.handle_input("\x1b");
+        runtime.run_once();
+        assert!(!runtime.is_help_overlay_open());
+
+        runtime.set_help_overlay(None);
+        runtime.handle_input("?");
+        runtime.run_once();
+        assert!(!runtime.is_help_overlay_open());
+        assert_eq!(inputs.borrow().as_slice(), &["?".to_string()]);
+    }
+
+    struct MenuOwner {
+        messages: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Component for MenuOwner {
+        fn render(&mut self, _width: usize) -> Vec<String> {
+            vec!["menu owner".to_string()]
+        }
+
+        fn on_message(&mut self, message: ComponentMessage) {
+            if let Ok(message) = message.downcast::<&'static str>() {
+                self.messages.borrow_mut().push(*message);
+            }
+        }
+    }
+
+    fn menu_owner_items(target: ComponentId) -> Box<dyn FnMut() -> Vec<ContextMenuItem>> {
+        Box::new(move || {
+            let item = move |label: &'static str| {
+                ContextMenuItem::new(label, move || Command::SendMessage {
+                    target,
+                    message: Box::new(label),
+                })
+            };
+            vec![item("copy"), item("delete").disabled(), item("retry")]
+        })
+    }
+
+    #[test]
+    fn context_menu_opens_for_the_focused_component_and_runs_the_choice() {
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let owner = MenuOwner {
+            messages: Rc::clone(&messages),
+        };
+        let (mut runtime, root_id) = runtime_with_root(TestTerminal::new(60, 20), owner);
+        runtime.set_context_menu_items(root_id, Some(menu_owner_items(root_id)));
+        runtime.start().expect("runtime start");
+        runtime.set_focus(root_id);
+        runtime.set_context_menu(Some(ContextMenuOptions::default()));
+        runtime.run_once();
+
+        // f10
+        runtime.handle_input("\x1b[21~");
+        runtime.run_once();
+        assert!(runtime.is_context_menu_open());
+        assert_ne!(runtime.focused, Some(root_id));
+        runtime.render_now();
+        assert!(runtime.terminal.output.contains("retry"));
+
+        // Down skips the disabled item.
+        runtime.handle_input("\x1b[B");
+        runtime.handle_input("\r");
+        runtime.run_once();
+        assert!(!runtime.is_context_menu_open());
+        assert_eq!(runtime.focused, Some(root_id));
+        assert_eq!(messages.borrow().as_slice(), &["retry"]);
+
+        let handle = runtime.runtime_handle();
+        handle.open_context_menu(
+            root_id,
+            vec![ContextMenuItem::new("quit", || Command::RequestStop)],
+        );
+        runtime.run_once();
+        assert!(runtime.is_context_menu_open());
+        runtime.handle_input("\x1b");
+        runtime.run_once();
+        assert!(!runtime.is_context_menu_open());
+        assert_eq!(runtime.focused, Some(root_id));
+        assert_eq!(messages.borrow().len(), 1);
+
+        // No items, no menu.
+        handle.open_context_menu(root_id, Vec::new());
+        runtime.run_once();
+        assert!(!runtime.is_context_menu_open());
+    }
+
+    #[test]
+    fn context_menu_choices_from_a_replaced_menu_are_ignored() {
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let owner = MenuOwner {
+            messages: Rc::clone(&messages),
+        };
+        let (mut runtime, root_id) = runtime_with_root(TestTerminal::new(60, 20), owner);
+        runtime.set_context_menu_items(root_id, Some(menu_owner_items(root_id)));
+        runtime.start().expect("runtime start");
+        runtime.set_focus(root_id);
+        runtime.set_context_menu(Some(ContextMenuOptions::default()));
+
+        let handle = runtime.runtime_handle();
+        handle.dispatch(Command::OpenFocusedContextMenu);
+        runtime.run_once();
+        let (stale_menu, _) = runtime.open_context_menu.as_ref().expect("menu open");
+        let stale_menu = *stale_menu;
+
+        handle.open_context_menu(
+            root_id,
+            vec![ContextMenuItem::new("quit", || Command::RequestStop)],
+        );
+        handle.dispatch(Command::ChooseContextMenuItem {
+            menu: stale_menu,
+            index: 0,
+        });
+        runtime.run_once();
+        assert!(runtime.is_context_menu_open());
+        assert!(messages.borrow().is_empty());
+
+        // Removing the owner drops its items.
+        runtime.close_context_menu();
+        runtime.remove_component(root_id);
+        assert!(runtime.context_menu_items.is_empty());
+    }
+
+    #[test]
+    fn copy_mode_freezes_the_viewport_and_copies_the_selection_as_plain_text() {
+        let mut runtime = TuiRuntime::new(TestTerminal::new(20, 4));
+        let root = runtime.register_component(static_lines(
+            &[
+                "history",
+                "alpha \x1b[1mbeta\x1b[22m",
+                "\x1b[32mgamma\x1b[39m",
+                "delta",
+                "epsilon",
+            ],
+            None,
+        ));
+        runtime.set_root(vec![root]);
+        runtime.set_copy_mode(Some(CopyModeOptions::default()));
+        runtime.start().expect("runtime start");
+        runtime.render_now();
+
+        runtime.handle_input("\x1b[18~");
+        runtime.run_once();
+        assert!(runtime.is_copy_mode_active());
+
+        // The cursor starts on the last row; select gamma..delta by line.
+        for key in ["k", "k", "V", "j"] {
+            runtime.handle_input(key);
+        }
+        runtime.terminal.output.clear();
+        runtime.render_now();
+        assert!(runtime.terminal.output.contains("\x1b[7mgamma\x1b[27m"));
+        assert!(!runtime.terminal.output.contains("history"));
+
+        runtime.terminal.output.clear();
+        runtime.handle_input("y");
+        assert!(!runtime.is_copy_mode_active());
+        runtime.run_once();
+        assert!(runtime
+            .terminal
+            .output
+            .contains(&crate::core::output::osc_clipboard_sequence("gamma\ndelta")));
+
+        runtime.handle_input("\x1b[18~");
+        runtime.run_once();
+        assert!(runtime.is_copy_mode_active());
+        runtime.terminal.columns = 30;
+        runtime.wake.signal_resize();
+        runtime.run_once();
+        assert!(!runtime.is_copy_mode_active());
+    }
+
+    #[test]
+    fn query_replies_from_the_runtime_thread_and_schedules_follow_ups() {
+        let (mut runtime, root_id) =
+            runtime_with_root(TestTerminal::new(40, 12), DummyComponent::default());
+        let handle = runtime.runtime_handle();
+
+        // Queued before start: `start` drops pending commands, and with
+        // them the reply sender.
+        let dropped = handle.query(|ctx| ctx.terminal_size());
+        runtime.start().expect("runtime start");
+        assert_eq!(dropped.wait(), None);
+
+        runtime.set_focus(root_id);
+        runtime.run_once();
+
+        let worker_handle = handle.clone();
+        let worker = std::thread::spawn(move || {
+            worker_handle
+                .query(|ctx| {
+                    ctx.dispatch(Command::SetTitle("queried".to_string()));
+                    (ctx.terminal_size(), ctx.focused())
+                })
+                .wait_timeout(Duration::from_secs(5))
+        });
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !worker.is_finished() && std::time::Instant::now() < deadline {
+            runtime.run_once();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(
+            worker.join().expect("worker thread"),
+            Some(((40, 12), Some(root_id)))
+        );
+        // The follow-up ran in the same tick as the query.
+        assert!(runtime.terminal.output.contains("queried"));
+    }
+
+    #[test]
+    fn surface_geometry_reports_drawn_rect_and_notifies_layout_changes() {
+        let (mut runtime, _root_id) =
+            runtime_with_root(TestTerminal::new(40, 12), DummyComponent::default());
+        let layouts: Rc<RefCell<Vec<Vec<(SurfaceId, Rect)>>>> = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&layouts);
+        runtime.set_on_surface_layout(Some(Box::new(move |geometry| {
+            recorded.borrow_mut().push(geometry.to_vec());
+        })));
+        runtime.start().expect("runtime start");
+
+        let popup = runtime.register_component(StaticLinesComponent {
+            lines: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            cursor: None,
+        });
+        let handle = runtime.show_surface(
+            popup,
+            Some(SurfaceOptions::from(SurfaceLayoutOptions {
+                row: Some(SurfaceSizeValue::absolute(2)),
+                col: Some(SurfaceSizeValue::absolute(5)),
+                width: Some(SurfaceSizeValue::absolute(10)),
+                ..Default::default()
+            })),
+        );
+        assert_eq!(runtime.surface_geometry(handle.id()), None);
+        runtime.run_once();
+
+        let rect = Rect {
+            x: 5,
+            y: 2,
+            width: 10,
+            height: 3,
+        };
+        assert_eq!(runtime.surface_geometry(handle.id()), Some(rect));
+        assert_eq!(handle.geometry(), Some(rect));
+
+        // An unchanged layout does not notify again.
+        runtime.request_render();
+        runtime.run_once();
+        assert_eq!(*layouts.borrow(), vec![vec![(handle.id(), rect)]]);
+
+        handle.hide();
+        runtime.run_once();
+        assert_eq!(runtime.surface_geometry(handle.id()), None);
+        assert_eq!(layouts.borrow().len(), 2);
+        assert!(layouts.borrow()[1].is_empty());
+    }
+
+    #[test]
+    fn component_anchored_surfaces_follow_the_component_and_its_cursor() {
+        let (mut runtime, root_id) = runtime_with_root(
+            TestTerminal::new(30, 10),
+            StaticLinesComponent {
+                lines: (0..9).map(|row| format!("line {row}")).collect(),
+                cursor: Some(CursorPos { row: 8, col: 4 }),
+            },
+        );
+        runtime.start().expect("runtime start");
+
+        let popup = || StaticLinesComponent {
+            lines: vec!["one".to_string(), "two".to_string(), "three".to_string()],
+            cursor: None,
+        };
+        let anchored = |id, placement| {
+            Some(SurfaceOptions {
+                layout: SurfaceLayoutOptions {
+                    width: Some(SurfaceSizeValue::absolute(8)),
+                    anchor: Some(SurfaceAnchor::Component { id, placement }),
+                    ..Default::default()
+                },
+                input_policy: SurfaceInputPolicy::Passthrough,
+                ..Default::default()
+            })
+        };
+
+        let below_id = runtime.register_component(popup());
+        let below = runtime.show_surface(below_id, anchored(root_id, SurfacePlacement::Below));
+        let cursor_id = runtime.register_component(popup());
+        let at_cursor =
+            runtime.show_surface(cursor_id, anchored(root_id, SurfacePlacement::Cursor));
+        let stacked_id = runtime.register_component(popup());
+        let stacked =
+            runtime.show_surface(stacked_id, anchored(cursor_id, SurfacePlacement::Above));
+        let unshown_id = runtime.register_component(popup());
+        let orphan_id = runtime.register_component(popup());
+        let orphan = runtime.show_surface(orphan_id, anchored(unshown_id, SurfacePlacement::Auto));
+        runtime.run_once();
+
+        // One row is left under the nine-line root, so `Below` is cut to it.
+        assert_eq!(
+            below.geometry(),
+            Some(Rect {
+                x: 0,
+                y: 9,
+                width: 8,
+                height: 1,
+            })
+        );
+        // The cursor sits on the last root row: no room below, so it flips up.
+        assert_eq!(
+            at_cursor.geometry(),
+            Some(Rect {
+                x: 4,
+                y: 5,
+                width: 8,
+                height: 3,
+            })
+        );
+        // Anchored to the surface above, which is a tracked component too.
+        assert_eq!(
+            stacked.geometry(),
+            Some(Rect {
+                x: 4,
+                y: 2,
+                width: 8,
+                height: 3,
+            })
+        );
+        assert_eq!(orphan.geometry(), None);
+        assert!(runtime.terminal.output.contains("three"));
+    }
 }
@@ -0,0 +1,110 @@
+//! Background workers for expensive render preparation.
+//!
+//! Pure computations such as syntax highlighting, image encoding, or
+//! markdown layout run here instead of on the runtime thread. Results come
+//! back as runtime commands (see `RuntimeHandle::spawn_work`), so the tick
+//! loop never waits on the work itself.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+const MAX_DEFAULT_WORKERS: usize = 4;
+
+/// A fixed set of worker threads, started on the first job.
+///
+/// Dropping the pool closes the queue; workers finish the job they are
+/// running and exit without being joined.
+pub(crate) struct WorkerPool {
+    size: usize,
+    sender: Mutex<Option<Sender<Job>>>,
+}
+
+impl WorkerPool {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            size: size.max(1),
+            sender: Mutex::new(None),
+        }
+    }
+
+    /// One worker per spare core, leaving a core for the runtime thread,
+    /// capped at four.
+    pub(crate) fn with_default_size() -> Self {
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+        Self::new(cores.saturating_sub(1).clamp(1, MAX_DEFAULT_WORKERS))
+    }
+
+    /// Queues `job`. A panicking job is contained to that job.
+    pub(crate) fn execute(&self, job: impl FnOnce() + Send + 'static) {
+        let mut sender = match self.sender.lock() {
+            Ok(sender) => sender,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let sender = sender.get_or_insert_with(|| self.start());
+        // Workers only exit once the sender is gone, so sending cannot fail.
+        let _ = sender.send(Box::new(job));
+    }
+
+    fn start(&self) -> Sender<Job> {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+        for index in 0..self.size {
+            let rx = Arc::clone(&rx);
+            let spawned = thread::Builder::new()
+                .name(format!("tape-worker-{index}"))
+                .spawn(move || worker_loop(&rx));
+            debug_assert!(spawned.is_ok(), "failed to spawn worker thread");
+        }
+        tx
+    }
+}
+
+fn worker_loop(rx: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = {
+            let rx = match rx.lock() {
+                Ok(rx) => rx,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            match rx.recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            }
+        };
+        let _ = catch_unwind(AssertUnwindSafe(job));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkerPool;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn jobs_run_off_thread_and_survive_panics() {
+        let pool = WorkerPool::new(2);
+        let (tx, rx) = mpsc::channel();
+        pool.execute(|| panic!("job panic"));
+        for value in 0..8 {
+            let tx = tx.clone();
+            pool.execute(move || {
+                let name = std::thread::current().name().map(str::to_string);
+                let _ = tx.send((value, name));
+            });
+        }
+
+        let mut values = Vec::new();
+        for _ in 0..8 {
+            let (value, name) = rx.recv_timeout(Duration::from_secs(5)).expect("job result");
+            assert!(name.is_some_and(|name| name.starts_with("tape-worker-")));
+            values.push(value);
+        }
+        values.sort_unstable();
+        assert_eq!(values, (0..8).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,173 @@
+//! Editor autocomplete drawn as a dropdown surface.
+//!
+//! By default an [`Editor`](crate::widgets::editor::Editor) appends its
+//! suggestion list below its own lines, pushing the content underneath down.
+//! With a dropdown attached, the list is drawn in a passthrough surface
+//! anchored at the editor's cursor instead and floats over what follows. The
+//! editor keeps focus and all input handling: Up/Down move the selection,
+//! Tab or Enter apply it to the buffer, and Escape, focus loss or a request
+//! that yields no items dismiss it.
+//!
+//! The anchor only resolves while the editor is a root component or is shown
+//! in a surface (see [`SurfaceAnchor::Component`]).
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::component::Component;
+use crate::core::text::utils::truncate_to_width;
+use crate::runtime::component_registry::ComponentId;
+use crate::runtime::surface::{
+    SurfaceAnchor, SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions, SurfaceOptions,
+    SurfacePlacement, SurfaceSizeValue,
+};
+use crate::runtime::tui::{RuntimeHandle, SurfaceHandle};
+
+const DEFAULT_MAX_WIDTH: usize = 48;
+
+struct Attachment {
+    handle: RuntimeHandle,
+    editor_id: ComponentId,
+    dropdown_id: ComponentId,
+}
+
+struct DropdownState {
+    lines: Vec<String>,
+    attachment: Option<Attachment>,
+    /// The shown surface and the width it was shown with.
+    surface: Option<(SurfaceHandle, usize)>,
+    max_width: usize,
+    placement: SurfacePlacement,
+}
+
+/// Surface component showing an editor's suggestions.
+///
+/// Create it with [`Editor::autocomplete_dropdown`](crate::widgets::editor::Editor::autocomplete_dropdown),
+/// register both components, then [`attach`](Self::attach) the ids. Clones
+/// share state, so keep one to attach after the other is registered.
+#[derive(Clone)]
+pub struct AutocompleteDropdown {
+    state: Rc<RefCell<DropdownState>>,
+}
+
+impl AutocompleteDropdown {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(DropdownState {
+                lines: Vec::new(),
+                attachment: None,
+                surface: None,
+                max_width: DEFAULT_MAX_WIDTH,
+                placement: SurfacePlacement::Cursor,
+            })),
+        }
+    }
+
+    /// Starts drawing the editor `editor_id`'s suggestions in a surface
+    /// showing `dropdown_id` (this component's id).
+    pub fn attach(&self, handle: RuntimeHandle, editor_id: ComponentId, dropdown_id: ComponentId) {
+        self.detach();
+        self.state.borrow_mut().attachment = Some(Attachment {
+            handle,
+            editor_id,
+            dropdown_id,
+        });
+    }
+
+    /// Closes the surface; the editor draws its list inline again.
+    pub fn detach(&self) {
+        let mut state = self.state.borrow_mut();
+        state.attachment = None;
+        state.lines.clear();
+        if let Some((surface, _)) = state.surface.take() {
+            surface.hide();
+        }
+    }
+
+    /// Widest the list is drawn (default 48), within the editor's content
+    /// width.
+    pub fn set_max_width(&self, max_width: usize) {
+        self.state.borrow_mut().max_width = max_width.max(1);
+    }
+
+    /// Where the list opens relative to the editor (default
+    /// [`SurfacePlacement::Cursor`]).
+    pub fn set_placement(&self, placement: SurfacePlacement) {
+        let mut state = self.state.borrow_mut();
+        state.placement = placement;
+        // Re-shown with the new anchor on the next sync.
+        if let Some((surface, _)) = state.surface.take() {
+            surface.hide();
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.borrow().surface.is_some()
+    }
+
+    pub(crate) fn is_attached(&self) -> bool {
+        self.state.borrow().attachment.is_some()
+    }
+
+    pub(crate) fn list_width(&self, available: usize) -> usize {
+        self.state.borrow().max_width.min(available).max(1)
+    }
+
+    /// Publishes the list drawn at `width`, or closes the dropdown for
+    /// `None`, showing or hiding the surface to match.
+    pub(crate) fn sync(&self, list: Option<(Vec<String>, usize)>) {
+        let mut state = self.state.borrow_mut();
+        let Some((handle, editor_id, dropdown_id)) = state.attachment.as_ref().map(|attachment| {
+            (
+                attachment.handle.clone(),
+                attachment.editor_id,
+                attachment.dropdown_id,
+            )
+        }) else {
+            return;
+        };
+        let Some((lines, width)) = list.filter(|(lines, _)| !lines.is_empty()) else {
+            state.lines.clear();
+            if let Some((surface, _)) = state.surface.take() {
+                surface.hide();
+            }
+            return;
+        };
+
+        let options = Some(SurfaceOptions {
+            layout: SurfaceLayoutOptions {
+                width: Some(SurfaceSizeValue::absolute(width)),
+                anchor: Some(SurfaceAnchor::Component {
+                    id: editor_id,
+                    placement: state.placement,
+                }),
+                ..Default::default()
+            },
+            kind: SurfaceKind::Modal,
+            input_policy: SurfaceInputPolicy::Passthrough,
+            min_update_interval: None,
+        });
+        let surface = match state.surface.take() {
+            Some((surface, shown_width)) => {
+                if shown_width != width {
+                    surface.update_options(options);
+                }
+                surface
+            }
+            None => handle.show_surface(dropdown_id, options, false),
+        };
+        state.surface = Some((surface, width));
+        state.lines = lines;
+    }
+}
+
+impl Component for AutocompleteDropdown {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        self.state
+            .borrow()
+            .lines
+            .iter()
+            .map(|line| truncate_to_width(line, width, "", false))
+            .collect()
+    }
+}
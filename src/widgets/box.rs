@@ -1,14 +1,98 @@
 //! Box widget.
+//!
+//! Pads its children and optionally draws a border around them. The border
+//! can carry a title in the top edge, use one of several glyph sets, omit
+//! individual sides, and switch color while the box is focused so the
+//! active pane stands out.
 
 use std::boxed::Box as StdBox;
 
-use crate::core::component::Component;
+use crate::core::component::{Component, Focusable};
 use crate::core::cursor::CursorPos;
-use crate::core::text::utils::apply_background_to_line;
+use crate::core::text::utils::{apply_background_to_line, truncate_to_width};
 use crate::core::text::width::visible_width;
 
 pub type BoxBgFn = std::boxed::Box<dyn Fn(&str) -> String>;
 
+/// Glyph set used for a [`Box`] border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoxBorderStyle {
+    #[default]
+    Single,
+    Double,
+    Rounded,
+    Heavy,
+    Ascii,
+}
+
+struct BorderGlyphs {
+    top_left: &'static str,
+    top_right: &'static str,
+    bottom_left: &'static str,
+    bottom_right: &'static str,
+    horizontal: &'static str,
+    vertical: &'static str,
+}
+
+impl BoxBorderStyle {
+    fn glyphs(self) -> BorderGlyphs {
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] = match self {
+            Self::Single => ["┌", "┐", "└", "┘", "─", "│"],
+            Self::Double => ["╔", "╗", "╚", "╝", "═", "║"],
+            Self::Rounded => ["╭", "╮", "╰", "╯", "─", "│"],
+            Self::Heavy => ["┏", "┓", "┗", "┛", "━", "┃"],
+            Self::Ascii => ["+", "+", "+", "+", "-", "|"],
+        };
+        BorderGlyphs {
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        }
+    }
+}
+
+/// Which sides of a [`Box`] border are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxBorderSides {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+impl BoxBorderSides {
+    pub const ALL: Self = Self {
+        top: true,
+        right: true,
+        bottom: true,
+        left: true,
+    };
+    pub const HORIZONTAL: Self = Self {
+        top: true,
+        right: false,
+        bottom: true,
+        left: false,
+    };
+}
+
+impl Default for BoxBorderSides {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Position of a [`Box`] title within the top border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoxTitleAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
 struct RenderCache {
     child_lines: Vec<String>,
     width: usize,
@@ -22,6 +106,12 @@ pub struct Box {
     padding_y: usize,
     last_cursor_pos: Option<CursorPos>,
     bg_fn: Option<BoxBgFn>,
+    border: Option<BoxBorderStyle>,
+    border_sides: BoxBorderSides,
+    border_fn: Option<BoxBgFn>,
+    focused_border_fn: Option<BoxBgFn>,
+    title: Option<(String, BoxTitleAlign)>,
+    focused: bool,
     cache: Option<RenderCache>,
 }
 
@@ -33,6 +123,12 @@ impl Box {
             padding_y,
             last_cursor_pos: None,
             bg_fn,
+            border: None,
+            border_sides: BoxBorderSides::ALL,
+            border_fn: None,
+            focused_border_fn: None,
+            title: None,
+            focused: false,
             cache: None,
         }
     }
@@ -60,6 +156,32 @@ impl Box {
         self.bg_fn = bg_fn;
     }
 
+    /// Draws a border in `style`; `None` (the default) draws no border.
+    pub fn set_border(&mut self, style: Option<BoxBorderStyle>) {
+        self.border = style;
+        self.invalidate_cache();
+    }
+
+    pub fn set_border_sides(&mut self, sides: BoxBorderSides) {
+        self.border_sides = sides;
+        self.invalidate_cache();
+    }
+
+    /// Styles border glyphs; `focused` is used instead while the box is
+    /// focused and falls back to `normal` when unset.
+    pub fn set_border_colors(&mut self, normal: Option<BoxBgFn>, focused: Option<BoxBgFn>) {
+        self.border_fn = normal;
+        self.focused_border_fn = focused;
+        self.invalidate_cache();
+    }
+
+    /// Shows `title` in the top border. Needs a border with the top side
+    /// enabled; it is truncated to the available width.
+    pub fn set_title(&mut self, title: Option<String>, align: BoxTitleAlign) {
+        self.title = title.map(|title| (title, align));
+        self.invalidate_cache();
+    }
+
     fn invalidate_cache(&mut self) {
         self.cache = None;
     }
@@ -83,6 +205,59 @@ impl Box {
                 .all(|(cached, current)| cached == current)
     }
 
+    fn style_border(&self, text: &str) -> String {
+        let style = if self.focused {
+            self.focused_border_fn.as_ref().or(self.border_fn.as_ref())
+        } else {
+            self.border_fn.as_ref()
+        };
+        match style {
+            Some(style) => style(text),
+            None => text.to_string(),
+        }
+    }
+
+    /// Top or bottom edge spanning `inner_width` columns between the corners.
+    fn horizontal_edge(
+        &self,
+        glyphs: &BorderGlyphs,
+        inner_width: usize,
+        corners: (&str, &str),
+        title: Option<&(String, BoxTitleAlign)>,
+    ) -> String {
+        let left = if self.border_sides.left {
+            corners.0
+        } else {
+            ""
+        };
+        let right = if self.border_sides.right {
+            corners.1
+        } else {
+            ""
+        };
+
+        let Some((title, align)) = title.filter(|_| inner_width >= 5) else {
+            let edge = format!("{left}{}{right}", glyphs.horizontal.repeat(inner_width));
+            return self.style_border(&edge);
+        };
+
+        // Keep at least one fill glyph on each side of the padded title.
+        let title = truncate_to_width(title, inner_width - 4, "…", false);
+        let label = format!(" {title} ");
+        let free = inner_width.saturating_sub(visible_width(&label));
+        let before = match align {
+            BoxTitleAlign::Left => free.min(1),
+            BoxTitleAlign::Center => free / 2,
+            BoxTitleAlign::Right => free.saturating_sub(1),
+        };
+        let after = free - before;
+        format!(
+            "{}{label}{}",
+            self.style_border(&format!("{left}{}", glyphs.horizontal.repeat(before))),
+            self.style_border(&format!("{}{right}", glyphs.horizontal.repeat(after)))
+        )
+    }
+
     fn apply_bg(&self, line: &str, width: usize) -> String {
         let visible_len = visible_width(line);
         let pad_needed = width.saturating_sub(visible_len);
@@ -113,7 +288,19 @@ impl Component for Box {
             return Vec::new();
         }
 
-        let content_width = width.saturating_sub(self.padding_x * 2).max(1);
+        let glyphs = self.border.map(BoxBorderStyle::glyphs);
+        let sides = match glyphs {
+            Some(_) => self.border_sides,
+            None => BoxBorderSides {
+                top: false,
+                right: false,
+                bottom: false,
+                left: false,
+            },
+        };
+        let border_cols = usize::from(sides.left) + usize::from(sides.right);
+        let inner_width = width.saturating_sub(border_cols);
+        let content_width = inner_width.saturating_sub(self.padding_x * 2).max(1);
         let left_pad = " ".repeat(self.padding_x);
 
         let mut child_lines = Vec::new();
@@ -125,11 +312,13 @@ impl Component for Box {
 
             if let Some(pos) = cursor_pos {
                 last_cursor_pos = Some(CursorPos {
-                    row: self
-                        .padding_y
+                    row: usize::from(sides.top)
+                        .saturating_add(self.padding_y)
                         .saturating_add(start_row)
                         .saturating_add(pos.row),
-                    col: self.padding_x.saturating_add(pos.col),
+                    col: usize::from(sides.left)
+                        .saturating_add(self.padding_x)
+                        .saturating_add(pos.col),
                 });
             }
 
@@ -151,13 +340,48 @@ impl Component for Box {
 
         let mut result = Vec::new();
         for _ in 0..self.padding_y {
-            result.push(self.apply_bg("", width));
+            result.push(self.apply_bg("", inner_width));
         }
         for line in child_lines.iter() {
-            result.push(self.apply_bg(line, width));
+            result.push(self.apply_bg(line, inner_width));
         }
         for _ in 0..self.padding_y {
-            result.push(self.apply_bg("", width));
+            result.push(self.apply_bg("", inner_width));
+        }
+
+        if let Some(glyphs) = glyphs.as_ref() {
+            if sides.left || sides.right {
+                let left = if sides.left {
+                    self.style_border(glyphs.vertical)
+                } else {
+                    String::new()
+                };
+                let right = if sides.right {
+                    self.style_border(glyphs.vertical)
+                } else {
+                    String::new()
+                };
+                for line in result.iter_mut() {
+                    *line = format!("{left}{line}{right}");
+                }
+            }
+            if sides.top {
+                let top = self.horizontal_edge(
+                    glyphs,
+                    inner_width,
+                    (glyphs.top_left, glyphs.top_right),
+                    self.title.as_ref(),
+                );
+                result.insert(0, top);
+            }
+            if sides.bottom {
+                result.push(self.horizontal_edge(
+                    glyphs,
+                    inner_width,
+                    (glyphs.bottom_left, glyphs.bottom_right),
+                    None,
+                ));
+            }
         }
 
         self.cache = Some(RenderCache {
@@ -180,12 +404,35 @@ impl Component for Box {
             child.invalidate();
         }
     }
+
+    fn as_focusable(&mut self) -> Option<&mut dyn Focusable> {
+        Some(self)
+    }
+}
+
+impl Focusable for Box {
+    /// Switches the border color and forwards focus to focusable children.
+    fn set_focused(&mut self, focused: bool) {
+        if self.focused != focused {
+            self.focused = focused;
+            self.invalidate_cache();
+        }
+        for child in self.children.iter_mut() {
+            if let Some(focusable) = child.as_focusable() {
+                focusable.set_focused(focused);
+            }
+        }
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Box as BoxWidget;
-    use crate::core::component::Component;
+    use super::{Box as BoxWidget, BoxBorderSides, BoxBorderStyle, BoxTitleAlign};
+    use crate::core::component::{Component, Focusable};
     use crate::core::cursor::CursorPos;
     use crate::core::text::width::visible_width;
     use std::boxed::Box as StdBox;
@@ -269,4 +516,51 @@ mod tests {
         assert_eq!(boxed.render(10), vec!["two       "]);
         assert!(!boxed.remove_child(1));
     }
+
+    #[test]
+    fn box_draws_titled_borders_in_each_style() {
+        let mut boxed = BoxWidget::new(1, 0, None);
+        boxed.add_child(StdBox::new(StaticComponent {
+            lines: vec!["hi".to_string()],
+        }));
+        boxed.set_border(Some(BoxBorderStyle::Rounded));
+        boxed.set_title(Some("Tools".to_string()), BoxTitleAlign::Center);
+        assert_eq!(
+            boxed.render(13),
+            vec!["╭── Tools ──╮", "│ hi        │", "╰───────────╯"]
+        );
+
+        boxed.set_border(Some(BoxBorderStyle::Ascii));
+        boxed.set_title(Some("A very long title".to_string()), BoxTitleAlign::Right);
+        let lines = boxed.render(13);
+        assert!(lines[0].starts_with("+- A very"), "{:?}", lines[0]);
+        assert!(lines[0].ends_with("… -+"), "{:?}", lines[0]);
+        assert!(lines.iter().all(|line| visible_width(line) == 13));
+
+        boxed.set_border(Some(BoxBorderStyle::Double));
+        boxed.set_title(None, BoxTitleAlign::Left);
+        boxed.set_border_sides(BoxBorderSides::HORIZONTAL);
+        assert_eq!(boxed.render(6), vec!["══════", " hi   ", "══════"]);
+    }
+
+    #[test]
+    fn box_border_colors_follow_focus_and_offset_cursor() {
+        let mut boxed = BoxWidget::new(0, 0, None);
+        boxed.add_child(StdBox::new(CursorComponent {
+            lines: vec!["ab".to_string()],
+            cursor: Some(CursorPos { row: 0, col: 1 }),
+        }));
+        boxed.set_border(Some(BoxBorderStyle::Heavy));
+        boxed.set_border_colors(
+            Some(StdBox::new(|text| format!("<{text}>"))),
+            Some(StdBox::new(|text| format!("[{text}]"))),
+        );
+
+        assert_eq!(boxed.render(4)[1], "<┃>ab<┃>");
+        assert_eq!(boxed.cursor_pos(), Some(CursorPos { row: 1, col: 2 }));
+
+        boxed.set_focused(true);
+        assert!(boxed.is_focused());
+        assert_eq!(boxed.render(4)[0], "[┏━━┓]");
+    }
 }
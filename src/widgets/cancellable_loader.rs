@@ -1,13 +1,23 @@
 //! Cancellable loader widget.
+//!
+//! Wraps [`Loader`] with an abort signal plus optional progress details: an
+//! elapsed-time counter, named phases the host advances, and a cancel hint
+//! line. Elapsed time is computed at render time, so the spinner's own tick
+//! keeps it current without the host requesting renders.
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::core::animation::AnimationClock;
 use crate::core::component::Component;
 use crate::core::input_event::InputEvent;
 use crate::core::keybindings::{EditorAction, EditorKeybindingsHandle};
+use crate::core::text::utils::truncate_to_width;
 use crate::runtime::tui::RuntimeHandle;
-use crate::widgets::loader::Loader;
+use crate::widgets::loader::{Loader, SpinnerStyle};
+
+const DEFAULT_MESSAGE: &str = "Loading...";
 
 #[derive(Clone)]
 pub struct AbortSignal {
@@ -25,6 +35,14 @@ pub struct CancellableLoader {
     abort_signal: AbortSignal,
     keybindings: EditorKeybindingsHandle,
     on_abort: Option<Box<dyn FnMut()>>,
+    message: String,
+    phases: Vec<String>,
+    phase_index: usize,
+    show_elapsed: bool,
+    /// Clock time when the spinner started, for the elapsed display.
+    started_at: Duration,
+    cancel_hint: Option<String>,
+    hint_color_fn: Option<Box<dyn Fn(&str) -> String>>,
 }
 
 impl CancellableLoader {
@@ -35,13 +53,31 @@ impl CancellableLoader {
         message: Option<String>,
         keybindings: EditorKeybindingsHandle,
     ) -> Self {
-        let loader = Loader::new(runtime_handle, spinner_color_fn, message_color_fn, message);
+        let message = message.unwrap_or_else(|| DEFAULT_MESSAGE.to_string());
+        let loader = Loader::new(
+            runtime_handle,
+            spinner_color_fn,
+            message_color_fn,
+            Some(message.clone()),
+        );
+        Self::from_loader(loader, message, keybindings)
+    }
+
+    fn from_loader(loader: Loader, message: String, keybindings: EditorKeybindingsHandle) -> Self {
         let aborted = Arc::new(AtomicBool::new(false));
+        let started_at = loader.animation_clock().now();
         Self {
             loader,
             abort_signal: AbortSignal { aborted },
             keybindings,
             on_abort: None,
+            message,
+            phases: Vec::new(),
+            phase_index: 0,
+            show_elapsed: false,
+            started_at,
+            cancel_hint: None,
+            hint_color_fn: None,
         }
     }
 
@@ -58,10 +94,75 @@ impl CancellableLoader {
     }
 
     pub fn set_message(&mut self, message: impl Into<String>) {
-        self.loader.set_message(message);
+        self.message = message.into();
+        self.loader.set_message(self.message.clone());
+    }
+
+    /// Switches the spinner animation; see [`Loader::set_style`].
+    pub fn set_spinner_style(&mut self, style: SpinnerStyle) {
+        self.loader.set_style(style);
+    }
+
+    /// Samples the spinner and the elapsed counter from `clock`; both restart.
+    pub fn set_animation_clock(&mut self, clock: AnimationClock) {
+        self.loader.set_animation_clock(clock);
+        self.started_at = self.loader.animation_clock().now();
+    }
+
+    /// Appends an elapsed-time counter (`12s`, `1m 05s`) to the message.
+    pub fn set_show_elapsed(&mut self, show_elapsed: bool) {
+        self.show_elapsed = show_elapsed;
+        self.loader.request_render();
+    }
+
+    /// Replaces the phase list and moves to the first phase.
+    pub fn set_phases(&mut self, phases: Vec<String>) {
+        self.phases = phases;
+        self.phase_index = 0;
+        self.loader.request_render();
     }
 
+    /// Moves to the next phase; `false` when already on the last one.
+    pub fn advance_phase(&mut self) -> bool {
+        if self.phase_index + 1 >= self.phases.len() {
+            return false;
+        }
+        self.phase_index += 1;
+        self.loader.request_render();
+        true
+    }
+
+    /// Jumps to the phase named `phase`; `false` when it is not in the list.
+    pub fn set_phase(&mut self, phase: &str) -> bool {
+        let Some(index) = self.phases.iter().position(|name| name == phase) else {
+            return false;
+        };
+        self.phase_index = index;
+        self.loader.request_render();
+        true
+    }
+
+    pub fn current_phase(&self) -> Option<&str> {
+        self.phases.get(self.phase_index).map(String::as_str)
+    }
+
+    /// Shows `hint` (for example "esc to cancel") below the spinner until the
+    /// loader is aborted.
+    pub fn set_cancel_hint(
+        &mut self,
+        hint: Option<String>,
+        color_fn: Option<Box<dyn Fn(&str) -> String>>,
+    ) {
+        self.cancel_hint = hint;
+        self.hint_color_fn = color_fn;
+        self.loader.request_render();
+    }
+
+    /// Starts the spinner; restarting after `stop` also resets elapsed time.
     pub fn start(&mut self) {
+        if !self.loader.is_running() {
+            self.started_at = self.loader.animation_clock().now();
+        }
         self.loader.start();
     }
 
@@ -81,25 +182,50 @@ impl CancellableLoader {
         message: Option<String>,
         keybindings: EditorKeybindingsHandle,
     ) -> Self {
+        let message = message.unwrap_or_else(|| DEFAULT_MESSAGE.to_string());
         let loader = super::loader::Loader::with_requester(
             render_requester,
             spinner_color_fn,
             message_color_fn,
-            message,
+            Some(message.clone()),
         );
-        let aborted = Arc::new(AtomicBool::new(false));
-        Self {
-            loader,
-            abort_signal: AbortSignal { aborted },
-            keybindings,
-            on_abort: None,
+        Self::from_loader(loader, message, keybindings)
+    }
+
+    fn composed_message(&self) -> String {
+        let mut message = self.message.clone();
+        if let Some(phase) = self.current_phase() {
+            message.push_str(" · ");
+            message.push_str(phase);
+            if self.phases.len() > 1 {
+                message.push_str(&format!(
+                    " ({}/{})",
+                    self.phase_index + 1,
+                    self.phases.len()
+                ));
+            }
         }
+        if self.show_elapsed {
+            message.push_str(" · ");
+            let now = self.loader.animation_clock().now();
+            message.push_str(&format_elapsed(now.saturating_sub(self.started_at)));
+        }
+        message
     }
 }
 
 impl Component for CancellableLoader {
     fn render(&mut self, width: usize) -> Vec<String> {
-        self.loader.render(width)
+        self.loader.replace_message(self.composed_message());
+        let mut lines = self.loader.render(width);
+        if let Some(hint) = self.cancel_hint.as_deref().filter(|_| !self.aborted()) {
+            let hint = truncate_to_width(&format!(" {hint}"), width, "...", false);
+            lines.push(match self.hint_color_fn.as_ref() {
+                Some(color) => color(&hint),
+                None => hint,
+            });
+        }
+        lines
     }
 
     fn handle_event(&mut self, event: &InputEvent) {
@@ -125,13 +251,26 @@ impl Component for CancellableLoader {
     }
 }
 
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    if seconds < 60 {
+        format!("{seconds}s")
+    } else if seconds < 3600 {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}h {:02}m", seconds / 3600, (seconds % 3600) / 60)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::CancellableLoader;
+    use super::{format_elapsed, CancellableLoader};
+    use crate::core::animation::AnimationClock;
     use crate::core::component::Component;
     use crate::core::input_event::parse_input_events;
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Arc;
+    use std::time::Duration;
 
     #[test]
     fn cancellable_loader_aborts_on_cancel() {
@@ -159,4 +298,45 @@ mod tests {
 
         loader.stop();
     }
+
+    #[test]
+    fn cancellable_loader_shows_phases_elapsed_and_cancel_hint() {
+        let keybindings = crate::core::keybindings::default_editor_keybindings_handle();
+        let mut loader = CancellableLoader::with_requester(
+            None,
+            Box::new(|text| text.to_string()),
+            Box::new(|text| text.to_string()),
+            Some("Working".to_string()),
+            keybindings,
+        );
+        loader.set_phases(vec!["connecting".to_string(), "streaming".to_string()]);
+        loader.set_show_elapsed(true);
+        loader.set_cancel_hint(Some("esc to cancel".to_string()), None);
+        let clock = AnimationClock::manual();
+        loader.set_animation_clock(clock.clone());
+        clock.advance(Duration::from_secs(65));
+
+        let lines = loader.render(60);
+        assert!(
+            lines[1]
+                .trim_end()
+                .ends_with(" Working · connecting (1/2) · 1m 05s"),
+            "{lines:?}"
+        );
+        assert_eq!(lines.last().map(String::as_str), Some(" esc to cancel"));
+
+        assert!(loader.advance_phase());
+        assert!(!loader.advance_phase());
+        assert_eq!(loader.current_phase(), Some("streaming"));
+        assert!(!loader.set_phase("applying patch"));
+        assert!(loader.render(60)[1].contains("streaming (2/2)"));
+
+        loader.handle_event(&parse_input_events("\x1b", false)[0]);
+        assert!(!loader.render(60).concat().contains("esc to cancel"));
+
+        assert_eq!(format_elapsed(Duration::from_secs(7)), "7s");
+        assert_eq!(format_elapsed(Duration::from_secs(3725)), "1h 02m");
+
+        loader.stop();
+    }
 }
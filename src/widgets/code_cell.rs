@@ -0,0 +1,343 @@
+//! Code cell widget.
+//!
+//! A syntax-highlighted code block with an attached output region, drawn
+//! like a notebook cell:
+//!
+//! ```text
+//! ╭─ bash ───────────────
+//! │ cargo test
+//! ├─ exit 0 · 1.2s ──────
+//! │ test result: ok
+//! ╰──────────────────────
+//! ```
+//!
+//! Output can be streamed in with [`CodeCell::append_output`]. A collapsed
+//! cell shows only the last few output lines behind a note counting the
+//! rest; `enter` or `space` toggles the cell when it receives input.
+
+use std::time::Duration;
+
+use crate::core::component::Component;
+use crate::core::input_event::InputEvent;
+use crate::core::text::slice::wrap_text_with_ansi;
+use crate::core::text::utils::truncate_to_width;
+use crate::core::text::width::visible_width;
+use crate::widgets::markdown::highlight_markdown_code_ansi;
+
+pub type CodeCellStyleFn = Box<dyn Fn(&str) -> String>;
+
+/// Output lines a collapsed cell shows by default.
+const DEFAULT_COLLAPSED_OUTPUT_LINES: usize = 6;
+
+pub struct CodeCellTheme {
+    /// The frame: corners, rules and the left edge.
+    pub border: CodeCellStyleFn,
+    pub label: CodeCellStyleFn,
+    /// A zero exit code.
+    pub success: CodeCellStyleFn,
+    /// A non-zero exit code or a failure message.
+    pub failure: CodeCellStyleFn,
+    /// Running state, duration and hidden-line notes.
+    pub note: CodeCellStyleFn,
+}
+
+/// How the cell's code finished, if it has.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CodeCellStatus {
+    #[default]
+    Running,
+    /// Exited with a code; `0` is shown as success.
+    Exited(i32),
+    /// Ended without an exit code, e.g. `timeout after 30s`.
+    Failed(String),
+}
+
+pub struct CodeCell {
+    code: String,
+    language: Option<String>,
+    label: Option<String>,
+    output: String,
+    status: CodeCellStatus,
+    duration: Option<Duration>,
+    collapsed: bool,
+    collapsed_output_lines: usize,
+    theme: Option<CodeCellTheme>,
+    highlighted: Option<Vec<String>>,
+}
+
+impl CodeCell {
+    /// A running cell with no output. `language` selects the highlighting
+    /// syntax, as for markdown code fences.
+    pub fn new(code: impl Into<String>, language: Option<&str>) -> Self {
+        Self {
+            code: code.into(),
+            language: language.map(ToString::to_string),
+            label: None,
+            output: String::new(),
+            status: CodeCellStatus::Running,
+            duration: None,
+            collapsed: false,
+            collapsed_output_lines: DEFAULT_COLLAPSED_OUTPUT_LINES,
+            theme: None,
+            highlighted: None,
+        }
+    }
+
+    /// Text in the top rule, e.g. the tool or language name.
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    pub fn set_theme(&mut self, theme: Option<CodeCellTheme>) {
+        self.theme = theme;
+    }
+
+    /// Appends streamed output.
+    pub fn append_output(&mut self, chunk: &str) {
+        self.output.push_str(chunk);
+    }
+
+    pub fn set_output(&mut self, output: impl Into<String>) {
+        self.output = output.into();
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn set_status(&mut self, status: CodeCellStatus) {
+        self.status = status;
+    }
+
+    pub fn status(&self) -> &CodeCellStatus {
+        &self.status
+    }
+
+    /// Shown after the status once set.
+    pub fn set_duration(&mut self, duration: Option<Duration>) {
+        self.duration = duration;
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    pub fn toggle_collapsed(&mut self) {
+        self.collapsed = !self.collapsed;
+    }
+
+    /// Output lines kept while collapsed (the newest ones).
+    pub fn set_collapsed_output_lines(&mut self, lines: usize) {
+        self.collapsed_output_lines = lines;
+    }
+
+    fn style(&self, pick: fn(&CodeCellTheme) -> &CodeCellStyleFn, text: &str) -> String {
+        match self.theme.as_ref() {
+            Some(theme) => pick(theme)(text),
+            None => text.to_string(),
+        }
+    }
+
+    /// `corner` followed by a rule to `width`, carrying `text` if it fits.
+    fn rule(&self, corner: &str, text: Option<String>, width: usize) -> String {
+        let mut line = self.style(|theme| &theme.border, corner);
+        let mut used = visible_width(corner);
+        if let Some(text) = text.filter(|_| width >= used + 4) {
+            let text = truncate_to_width(&text, width - used - 3, "…", false);
+            line.push_str(&self.style(|theme| &theme.border, "─"));
+            line.push(' ');
+            line.push_str(&text);
+            line.push(' ');
+            used += visible_width(&text) + 3;
+        }
+        line.push_str(&self.style(|theme| &theme.border, &"─".repeat(width - used.min(width))));
+        line
+    }
+
+    fn status_text(&self) -> String {
+        let mut text = match &self.status {
+            CodeCellStatus::Running => self.style(|theme| &theme.note, "running"),
+            CodeCellStatus::Exited(0) => self.style(|theme| &theme.success, "exit 0"),
+            CodeCellStatus::Exited(code) => {
+                self.style(|theme| &theme.failure, &format!("exit {code}"))
+            }
+            CodeCellStatus::Failed(message) => self.style(|theme| &theme.failure, message),
+        };
+        if let Some(duration) = self.duration {
+            text.push_str(&self.style(
+                |theme| &theme.note,
+                &format!(" · {}", format_duration(duration)),
+            ));
+        }
+        text
+    }
+
+    fn push_body(&self, lines: &mut Vec<String>, line: &str, width: usize) {
+        let edge = self.style(|theme| &theme.border, "│");
+        for row in wrap_text_with_ansi(line, width.saturating_sub(2).max(1)) {
+            lines.push(format!("{edge} {row}"));
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{millis}ms")
+    } else if millis < 60_000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        let secs = duration.as_secs();
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+impl Component for CodeCell {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        if width == 0 {
+            return Vec::new();
+        }
+        let code = self.code.trim_end_matches('\n');
+        let language = self.language.as_deref();
+        let highlighted = self
+            .highlighted
+            .get_or_insert_with(|| highlight_markdown_code_ansi(code, language))
+            .clone();
+
+        let label = self
+            .label
+            .as_deref()
+            .map(|label| self.style(|theme| &theme.label, label));
+        let mut lines = vec![self.rule("╭", label, width)];
+        for line in &highlighted {
+            self.push_body(&mut lines, line, width);
+        }
+        lines.push(self.rule("├", Some(self.status_text()), width));
+
+        let output = self.output.trim_end_matches('\n');
+        let output_lines = if output.is_empty() {
+            Vec::new()
+        } else {
+            output.split('\n').collect::<Vec<_>>()
+        };
+        let hidden = if self.collapsed {
+            output_lines
+                .len()
+                .saturating_sub(self.collapsed_output_lines)
+        } else {
+            0
+        };
+        if hidden > 0 {
+            let note = format!(
+                "… {hidden} earlier line{}",
+                if hidden == 1 { "" } else { "s" }
+            );
+            self.push_body(&mut lines, &self.style(|theme| &theme.note, &note), width);
+        }
+        for line in &output_lines[hidden..] {
+            self.push_body(&mut lines, line, width);
+        }
+        lines.push(self.rule("╰", None, width));
+        lines
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        if let InputEvent::Key { key_id, .. } = event {
+            if matches!(key_id.as_str(), "enter" | "space") {
+                self.toggle_collapsed();
+            }
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.highlighted = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{CodeCell, CodeCellStatus};
+    use crate::core::component::Component;
+    use crate::core::input_event::parse_input_events;
+    use crate::core::text::width::visible_width;
+
+    fn plain(lines: Vec<String>) -> Vec<String> {
+        lines
+            .into_iter()
+            .map(|line| {
+                let mut out = String::new();
+                let mut chars = line.chars();
+                while let Some(ch) = chars.next() {
+                    if ch == '\x1b' {
+                        for next in chars.by_ref() {
+                            if next.is_ascii_alphabetic() {
+                                break;
+                            }
+                        }
+                    } else {
+                        out.push(ch);
+                    }
+                }
+                out
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cell_frames_code_and_output_with_status() {
+        let mut cell = CodeCell::new("echo hi", None);
+        cell.set_label(Some("bash".to_string()));
+        assert_eq!(
+            plain(cell.render(16)),
+            vec![
+                "╭─ bash ────────",
+                "│ echo hi",
+                "├─ running ─────",
+                "╰───────────────",
+            ]
+        );
+
+        cell.append_output("hi\n");
+        cell.append_output("there\n");
+        cell.set_status(CodeCellStatus::Exited(2));
+        cell.set_duration(Some(Duration::from_millis(1500)));
+        let lines = cell.render(20);
+        assert_eq!(
+            plain(lines.clone()),
+            vec![
+                "╭─ bash ────────────",
+                "│ echo hi",
+                "├─ exit 2 · 1.5s ───",
+                "│ hi",
+                "│ there",
+                "╰───────────────────",
+            ]
+        );
+        assert!(lines.iter().all(|line| visible_width(line) <= 20));
+    }
+
+    #[test]
+    fn collapsed_cells_keep_the_newest_output_and_toggle_on_enter() {
+        let mut cell = CodeCell::new("seq 5", None);
+        cell.set_output("1\n2\n3\n4\n5");
+        cell.set_status(CodeCellStatus::Exited(0));
+        cell.set_collapsed_output_lines(2);
+        cell.set_collapsed(true);
+        assert_eq!(
+            plain(cell.render(20))[3..6],
+            ["│ … 3 earlier lines", "│ 4", "│ 5"]
+        );
+
+        for event in parse_input_events("\r", false) {
+            cell.handle_event(&event);
+        }
+        assert!(!cell.is_collapsed());
+        assert_eq!(cell.render(20).len(), 9);
+    }
+}
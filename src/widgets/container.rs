@@ -1,11 +1,71 @@
 //! Simple container widget.
+//!
+//! Children that report a `Component::cache_key` are rendered through a
+//! per-child `RenderCache`, so unchanged history is not re-rendered.
+//!
+//! A [`ContentWidth`] caps the width children render at, so long lines stay
+//! readable on very wide terminals; the narrowed column is drawn against the
+//! left edge or centered.
 
 use crate::core::component::Component;
 use crate::core::cursor::CursorPos;
+use crate::core::render_cache::RenderCache;
+
+/// Width constraint for rendered content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContentWidth {
+    /// Widest content is rendered; `None` uses the full width.
+    pub max_width: Option<usize>,
+    /// Centers narrowed content instead of keeping it at the left edge.
+    pub centered: bool,
+}
+
+impl ContentWidth {
+    /// Content at most `max_width` columns wide, at the left edge.
+    pub fn max(max_width: usize) -> Self {
+        Self {
+            max_width: Some(max_width),
+            centered: false,
+        }
+    }
+
+    pub fn centered(mut self, centered: bool) -> Self {
+        self.centered = centered;
+        self
+    }
+
+    /// Content width and left margin when `width` columns are available.
+    /// A zero `max_width` is treated as unconstrained.
+    pub fn layout(&self, width: usize) -> (usize, usize) {
+        let content_width = match self.max_width {
+            Some(max_width) if max_width > 0 => width.min(max_width),
+            _ => width,
+        };
+        let margin = if self.centered {
+            (width - content_width) / 2
+        } else {
+            0
+        };
+        (content_width, margin)
+    }
+
+    /// Shifts `lines` right by `margin` columns.
+    pub fn indent(lines: &mut [String], margin: usize) {
+        if margin == 0 {
+            return;
+        }
+        let padding = " ".repeat(margin);
+        for line in lines {
+            line.insert_str(0, &padding);
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct Container {
     children: Vec<Box<dyn Component>>,
+    caches: Vec<RenderCache>,
+    content_width: ContentWidth,
     last_cursor_pos: Option<CursorPos>,
 }
 
@@ -16,6 +76,7 @@ impl Container {
 
     pub fn add_child(&mut self, component: Box<dyn Component>) {
         self.children.push(component);
+        self.caches.push(RenderCache::new());
     }
 
     pub fn remove_child(&mut self, index: usize) -> bool {
@@ -23,31 +84,43 @@ impl Container {
             return false;
         }
         self.children.remove(index);
+        self.caches.remove(index);
         true
     }
 
     pub fn clear(&mut self) {
         self.children.clear();
+        self.caches.clear();
+    }
+
+    /// Constrains the width children render at.
+    pub fn set_content_width(&mut self, content_width: ContentWidth) {
+        self.content_width = content_width;
+    }
+
+    pub fn content_width(&self) -> ContentWidth {
+        self.content_width
     }
 }
 
 impl Component for Container {
     fn render(&mut self, width: usize) -> Vec<String> {
         self.last_cursor_pos = None;
+        let (content_width, margin) = self.content_width.layout(width);
         let mut lines = Vec::new();
-        for child in self.children.iter_mut() {
+        for (child, cache) in self.children.iter_mut().zip(self.caches.iter_mut()) {
             let start_row = lines.len();
-            let child_lines = child.render(width);
-            let child_cursor = child.cursor_pos();
+            let (child_lines, child_cursor) = cache.render(child.as_mut(), content_width);
 
             lines.extend(child_lines);
             if let Some(pos) = child_cursor {
                 self.last_cursor_pos = Some(CursorPos {
                     row: start_row.saturating_add(pos.row),
-                    col: pos.col,
+                    col: pos.col + margin,
                 });
             }
         }
+        ContentWidth::indent(&mut lines, margin);
         lines
     }
 
@@ -56,15 +129,16 @@ impl Component for Container {
     }
 
     fn invalidate(&mut self) {
-        for child in self.children.iter_mut() {
+        for (child, cache) in self.children.iter_mut().zip(self.caches.iter_mut()) {
             child.invalidate();
+            cache.clear();
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Container;
+    use super::{Container, ContentWidth};
     use crate::core::component::Component;
     use crate::core::cursor::CursorPos;
 
@@ -128,6 +202,33 @@ mod tests {
         assert_eq!(container.cursor_pos(), Some(CursorPos { row: 2, col: 2 }));
     }
 
+    struct WidthComponent;
+
+    impl Component for WidthComponent {
+        fn render(&mut self, width: usize) -> Vec<String> {
+            vec!["x".repeat(width)]
+        }
+
+        fn cursor_pos(&self) -> Option<CursorPos> {
+            Some(CursorPos { row: 0, col: 1 })
+        }
+    }
+
+    #[test]
+    fn content_width_caps_children_and_centers_them() {
+        let mut container = Container::new();
+        container.add_child(Box::new(WidthComponent));
+        container.set_content_width(ContentWidth::max(4));
+        assert_eq!(container.render(10), vec!["xxxx"]);
+        assert_eq!(container.render(3), vec!["xxx"]);
+
+        container.set_content_width(ContentWidth::max(4).centered(true));
+        assert_eq!(container.render(11), vec!["   xxxx"]);
+        assert_eq!(container.cursor_pos(), Some(CursorPos { row: 0, col: 4 }));
+        assert_eq!(ContentWidth::max(0).centered(true).layout(9), (9, 0));
+        assert_eq!(ContentWidth::default().layout(9), (9, 0));
+    }
+
     #[test]
     fn remove_child_by_index() {
         let mut container = Container::new();
@@ -0,0 +1,181 @@
+//! Divider widget.
+//!
+//! A one-line horizontal rule, optionally carrying a label ("── Tools ────"),
+//! used to separate groups of content such as transcript sections or
+//! settings groups.
+
+use crate::core::component::Component;
+use crate::core::text::utils::truncate_to_width;
+use crate::core::text::width::visible_width;
+
+pub type DividerStyleFn = Box<dyn Fn(&str) -> String>;
+
+/// Position of a [`Divider`] label within the rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DividerAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+pub struct DividerTheme {
+    pub rule: DividerStyleFn,
+    pub label: DividerStyleFn,
+}
+
+pub struct Divider {
+    label: Option<String>,
+    fill: String,
+    align: DividerAlign,
+    /// Fill cells kept between the edge and a left/right aligned label.
+    inset: usize,
+    theme: Option<DividerTheme>,
+}
+
+impl Divider {
+    /// An unlabeled rule drawn with `─`.
+    pub fn new() -> Self {
+        Self {
+            label: None,
+            fill: "─".to_string(),
+            align: DividerAlign::Left,
+            inset: 2,
+            theme: None,
+        }
+    }
+
+    pub fn labeled(label: impl Into<String>) -> Self {
+        Self {
+            label: Some(label.into()),
+            ..Self::new()
+        }
+    }
+
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    /// Sets the fill glyph(s); an empty fill falls back to `─`.
+    pub fn set_fill(&mut self, fill: impl Into<String>) {
+        let fill = fill.into();
+        self.fill = if visible_width(&fill) == 0 {
+            "─".to_string()
+        } else {
+            fill
+        };
+    }
+
+    pub fn set_align(&mut self, align: DividerAlign) {
+        self.align = align;
+    }
+
+    pub fn set_inset(&mut self, inset: usize) {
+        self.inset = inset;
+    }
+
+    pub fn set_theme(&mut self, theme: Option<DividerTheme>) {
+        self.theme = theme;
+    }
+
+    /// `width` columns of fill; a wide fill that does not divide evenly is
+    /// padded with spaces so the line never overflows.
+    fn rule(&self, width: usize) -> String {
+        if width == 0 {
+            return String::new();
+        }
+        let fill_width = visible_width(&self.fill).max(1);
+        let count = width / fill_width;
+        let rule = format!(
+            "{}{}",
+            self.fill.repeat(count),
+            " ".repeat(width - count * fill_width)
+        );
+        match self.theme.as_ref() {
+            Some(theme) => (theme.rule)(&rule),
+            None => rule,
+        }
+    }
+}
+
+impl Default for Divider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for Divider {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        let label = self
+            .label
+            .as_deref()
+            .filter(|label| !label.is_empty() && width >= 5);
+        let Some(label) = label else {
+            return vec![self.rule(width)];
+        };
+
+        let inset = self.inset.min(width.saturating_sub(5) / 2);
+        let max_label = match self.align {
+            DividerAlign::Center => width - 2,
+            DividerAlign::Left | DividerAlign::Right => width - 2 - inset,
+        };
+        let label = truncate_to_width(label, max_label, "…", false);
+        let text = format!(" {label} ");
+        let free = width.saturating_sub(visible_width(&text));
+        let before = match self.align {
+            DividerAlign::Left => inset.min(free),
+            DividerAlign::Center => free / 2,
+            DividerAlign::Right => free.saturating_sub(inset),
+        };
+        let text = match self.theme.as_ref() {
+            Some(theme) => (theme.label)(&text),
+            None => text,
+        };
+
+        vec![format!(
+            "{}{text}{}",
+            self.rule(before),
+            self.rule(free - before)
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Divider, DividerAlign, DividerTheme};
+    use crate::core::component::Component;
+    use crate::core::text::width::visible_width;
+
+    #[test]
+    fn divider_renders_plain_and_labeled_rules() {
+        assert_eq!(Divider::new().render(6), vec!["──────"]);
+
+        let mut divider = Divider::labeled("Tools");
+        assert_eq!(divider.render(16), vec!["── Tools ───────"]);
+
+        divider.set_align(DividerAlign::Center);
+        assert_eq!(divider.render(16), vec!["──── Tools ─────"]);
+
+        divider.set_align(DividerAlign::Right);
+        divider.set_fill("=");
+        assert_eq!(divider.render(16), vec!["======= Tools =="]);
+
+        divider.set_label(Some("A much longer section name".to_string()));
+        let line = divider.render(16).remove(0);
+        assert_eq!(visible_width(&line), 16);
+        assert!(line.ends_with("… =="), "{line:?}");
+    }
+
+    #[test]
+    fn divider_applies_theme_and_pads_wide_fill() {
+        let mut divider = Divider::labeled("Run");
+        divider.set_fill("<>");
+        divider.set_inset(1);
+        divider.set_theme(Some(DividerTheme {
+            rule: Box::new(|text| format!("[{text}]")),
+            label: Box::new(|text| text.to_uppercase()),
+        }));
+
+        assert_eq!(divider.render(10), vec!["[ ] RUN [<><>]"]);
+    }
+}
@@ -4,6 +4,7 @@ use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use unicode_segmentation::UnicodeSegmentation;
 
@@ -13,11 +14,18 @@ use crate::core::autocomplete::{
 use crate::core::component::{Component, Focusable};
 use crate::core::cursor::CursorPos;
 use crate::core::editor_component::EditorComponent;
-use crate::core::input_event::InputEvent;
+use crate::core::input_event::{CompositionPhase, InputEvent};
 use crate::core::keybindings::{EditorAction, EditorKeybindingsHandle};
-use crate::core::text::utils::{grapheme_segments, is_punctuation_char, is_whitespace_char};
+use crate::core::text::grapheme::{
+    floor_grapheme_boundary, next_grapheme_boundary, prev_grapheme_boundary,
+};
+use crate::core::text::slice::slice_by_column;
+use crate::core::text::utils::{
+    grapheme_segments, is_punctuation_char, is_whitespace_char, truncate_to_width,
+};
 use crate::core::text::width::visible_width;
 use crate::runtime::tui::{Command, RuntimeHandle};
+use crate::widgets::autocomplete_dropdown::AutocompleteDropdown;
 use crate::widgets::select_list::{SelectItem, SelectList, SelectListTheme};
 
 const MAX_PASTE_LINES: usize = 10;
@@ -146,6 +154,50 @@ pub enum EditorPasteMode {
     Literal,
 }
 
+/// How the editor tells "submit" from "insert a newline".
+///
+/// Text inside a bracketed paste never submits: it arrives as one paste
+/// event and its line breaks become newlines. Everything else is a key, and
+/// the default resolves it through the `Submit` (Enter) and `NewLine`
+/// (Shift+Enter) bindings. That breaks in two places:
+/// - Legacy terminals report Shift+Enter as Enter. `legacy_newline_keys`
+///   adds the keys they can send (Alt+Enter and Ctrl+J).
+/// - Terminals without bracketed paste deliver a multi-line paste as text and
+///   Enter keys. `unbracketed_paste_window` treats an Enter arriving right
+///   after text as part of the paste.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EditorEnterOptions {
+    /// Swap the roles: the `Submit` binding (Enter) inserts a newline and the
+    /// newline keys submit. For editors where multi-line input is the norm.
+    pub enter_inserts_newline: bool,
+    /// Treat Alt+Enter and Ctrl+J like the `NewLine` binding.
+    pub legacy_newline_keys: bool,
+    /// An Enter that would submit and arrives within this long of the
+    /// previous text (or pasted newline) inserts a newline instead. `None`
+    /// disables the guard.
+    pub unbracketed_paste_window: Option<Duration>,
+}
+
+impl EditorEnterOptions {
+    /// Enter submits, and newlines work in every terminal and paste mode.
+    pub fn paste_safe() -> Self {
+        Self {
+            enter_inserts_newline: false,
+            legacy_newline_keys: true,
+            unbracketed_paste_window: Some(Duration::from_millis(10)),
+        }
+    }
+}
+
+/// What an Enter-family key resolved to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EnterRole {
+    Submit,
+    Newline,
+    /// A newline that is part of an unbracketed paste.
+    PastedNewline,
+}
+
 #[derive(Clone, Default)]
 pub struct EditorOptions {
     pub padding_x: Option<usize>,
@@ -153,6 +205,30 @@ pub struct EditorOptions {
     pub height_mode: Option<EditorHeightMode>,
     pub paste_mode: Option<EditorPasteMode>,
     pub render_handle: Option<RuntimeHandle>,
+    /// Wrap long lines at the editor width (the default). With `Some(false)`
+    /// each line stays on one row and scrolls horizontally with the cursor.
+    pub soft_wrap: Option<bool>,
+    /// Hint rendered dimly while the editor is empty.
+    pub placeholder: Option<String>,
+    /// Estimates the token count of the expanded text for
+    /// [`EditorCounts::estimated_tokens`]. Runs on every edit, so keep it cheap.
+    pub token_estimator: Option<Arc<dyn Fn(&str) -> usize + Send + Sync>>,
+    /// Caps the content rows below what [`EditorHeightMode`] allows. Longer
+    /// content scrolls inside the editor between overflow indicators.
+    pub max_height: Option<usize>,
+    /// Enter, newline and paste handling; see [`EditorEnterOptions`]. `None`
+    /// keeps the keybinding-only behaviour.
+    pub enter: Option<EditorEnterOptions>,
+}
+
+/// Size of the editor content, measured on the expanded text (paste markers
+/// replaced by their content) so it matches what a submit would send.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EditorCounts {
+    pub chars: usize,
+    pub lines: usize,
+    /// `None` without an [`EditorOptions::token_estimator`].
+    pub estimated_tokens: Option<usize>,
 }
 
 enum JumpMode {
@@ -193,12 +269,25 @@ pub struct Editor {
     autocomplete_update_slot: Option<Arc<Mutex<Vec<AutocompleteSuggestions>>>>,
     autocomplete_async_handle: Option<JoinHandle<Option<AutocompleteSuggestions>>>,
     autocomplete_has_updates: bool,
+    autocomplete_dropdown: Option<AutocompleteDropdown>,
     last_width: usize,
     scroll_offset: usize,
+    max_height: Option<usize>,
+    /// Content rows shown by the last render; the viewport scroll step.
+    last_visible_rows: usize,
+    /// Set while the viewport was scrolled away from the cursor; cleared by
+    /// the next input that is not a viewport scroll.
+    viewport_detached: bool,
+    soft_wrap: bool,
+    /// First visible column when soft wrap is off.
+    horizontal_scroll: usize,
     border_color: Box<dyn Fn(&str) -> String>,
     terminal_rows: usize,
     height_mode: EditorHeightMode,
     paste_mode: EditorPasteMode,
+    enter: EditorEnterOptions,
+    /// When text was last typed, for [`EditorEnterOptions::unbracketed_paste_window`].
+    last_text_at: Option<Instant>,
     preferred_visual_col: Option<usize>,
     jump_mode: Option<JumpMode>,
     disable_submit: bool,
@@ -209,6 +298,13 @@ pub struct Editor {
     undo_stack: Vec<EditorState>,
     on_submit: Option<Box<dyn FnMut(String)>>,
     on_change: Option<Box<dyn FnMut(String)>>,
+    placeholder: Option<String>,
+    token_estimator: Option<Arc<dyn Fn(&str) -> usize + Send + Sync>>,
+    on_counts_change: Option<Box<dyn FnMut(EditorCounts)>>,
+    last_counts: Option<EditorCounts>,
+    /// An IME composition is in progress; the runtime draws its preedit at
+    /// the cursor, so the block cursor is not drawn.
+    composing: bool,
     history: Vec<String>,
     history_index: isize,
 }
@@ -225,6 +321,7 @@ impl Editor {
         let height_mode = options.height_mode.unwrap_or(EditorHeightMode::Default);
         let paste_mode = options.paste_mode.unwrap_or(EditorPasteMode::Default);
         let render_handle = options.render_handle;
+        let soft_wrap = options.soft_wrap.unwrap_or(true);
         let border_color = theme.border_color;
         let select_list_theme = theme.select_list;
         Self {
@@ -251,12 +348,20 @@ impl Editor {
             autocomplete_update_slot: None,
             autocomplete_async_handle: None,
             autocomplete_has_updates: false,
+            autocomplete_dropdown: None,
             last_width: 80,
             scroll_offset: 0,
+            max_height: options.max_height.map(|rows| rows.max(1)),
+            last_visible_rows: 0,
+            viewport_detached: false,
+            soft_wrap,
+            horizontal_scroll: 0,
             border_color,
             terminal_rows: 0,
             height_mode,
             paste_mode,
+            enter: options.enter.unwrap_or_default(),
+            last_text_at: None,
             preferred_visual_col: None,
             jump_mode: None,
             disable_submit: false,
@@ -267,6 +372,11 @@ impl Editor {
             undo_stack: Vec::new(),
             on_submit: None,
             on_change: None,
+            placeholder: options.placeholder,
+            token_estimator: options.token_estimator,
+            on_counts_change: None,
+            last_counts: None,
+            composing: false,
             history: Vec::new(),
             history_index: -1,
         }
@@ -276,6 +386,18 @@ impl Editor {
         self.terminal_rows = rows;
     }
 
+    /// See [`EditorOptions::soft_wrap`].
+    pub fn set_soft_wrap(&mut self, soft_wrap: bool) {
+        self.soft_wrap = soft_wrap;
+        self.horizontal_scroll = 0;
+        self.preferred_visual_col = None;
+    }
+
+    /// See [`EditorOptions::max_height`].
+    pub fn set_max_height(&mut self, max_height: Option<usize>) {
+        self.max_height = max_height.map(|rows| rows.max(1));
+    }
+
     pub fn get_lines(&self) -> Vec<String> {
         self.state.lines.clone()
     }
@@ -335,6 +457,20 @@ impl Editor {
         self.autocomplete_max_visible
     }
 
+    /// The dropdown that draws this editor's suggestions once attached; see
+    /// [`AutocompleteDropdown`]. Repeated calls return the same dropdown.
+    pub fn autocomplete_dropdown(&mut self) -> AutocompleteDropdown {
+        self.autocomplete_dropdown
+            .get_or_insert_with(AutocompleteDropdown::new)
+            .clone()
+    }
+
+    fn has_attached_dropdown(&self) -> bool {
+        self.autocomplete_dropdown
+            .as_ref()
+            .is_some_and(AutocompleteDropdown::is_attached)
+    }
+
     pub fn set_autocomplete_provider(&mut self, provider: Box<dyn AutocompleteProvider>) {
         self.autocomplete_provider = Some(provider);
     }
@@ -355,6 +491,29 @@ impl Editor {
         self.disable_submit = disabled;
     }
 
+    /// See [`EditorOptions::placeholder`].
+    pub fn set_placeholder(&mut self, placeholder: Option<String>) {
+        self.placeholder = placeholder;
+    }
+
+    pub fn counts(&self) -> EditorCounts {
+        let text = self.get_expanded_text();
+        EditorCounts {
+            chars: text.chars().count(),
+            lines: text.split('\n').count(),
+            estimated_tokens: self
+                .token_estimator
+                .as_ref()
+                .map(|estimate| estimate(&text)),
+        }
+    }
+
+    /// Called with the new [`EditorCounts`] whenever an edit changes them.
+    pub fn set_on_counts_change(&mut self, handler: Option<Box<dyn FnMut(EditorCounts)>>) {
+        self.on_counts_change = handler;
+        self.last_counts = None;
+    }
+
     fn emit_change(&mut self) {
         if self.on_change.is_some() {
             let text = self.get_text();
@@ -362,6 +521,15 @@ impl Editor {
                 handler(text);
             }
         }
+        if self.on_counts_change.is_some() {
+            let counts = self.counts();
+            if self.last_counts != Some(counts) {
+                self.last_counts = Some(counts);
+                if let Some(handler) = self.on_counts_change.as_mut() {
+                    handler(counts);
+                }
+            }
+        }
     }
 
     fn request_render(&self) {
@@ -475,6 +643,7 @@ impl Editor {
             self.select_list_theme.clone(),
             self.keybindings.clone(),
         );
+        list.set_path_descriptions(suggestions.prefix.starts_with('@'));
         if let Some(index) = selected_index {
             list.set_selected_index(index);
         }
@@ -661,12 +830,13 @@ impl Editor {
                 )
             })
             .collect::<Vec<_>>();
-        let list = SelectList::new(
+        let mut list = SelectList::new(
             items,
             self.autocomplete_max_visible,
             self.select_list_theme.clone(),
             self.keybindings.clone(),
         );
+        list.set_path_descriptions(true);
 
         self.autocomplete_prefix = suggestions.prefix;
         self.autocomplete_list = Some(list);
@@ -801,9 +971,7 @@ impl Editor {
             self.state.cursor_col = line_len;
         }
         if let Some(line) = self.state.lines.get(self.state.cursor_line) {
-            while self.state.cursor_col > 0 && !line.is_char_boundary(self.state.cursor_col) {
-                self.state.cursor_col = self.state.cursor_col.saturating_sub(1);
-            }
+            self.state.cursor_col = floor_grapheme_boundary(line, self.state.cursor_col);
         }
     }
 
@@ -1031,6 +1199,72 @@ impl Editor {
         self.emit_change();
     }
 
+    /// Submits, unless the line ends in `\\`, which is replaced by a newline.
+    fn submit_or_continue_line(&mut self) {
+        if self.disable_submit {
+            return;
+        }
+
+        let current_line = self
+            .state
+            .lines
+            .get(self.state.cursor_line)
+            .map(String::as_str)
+            .unwrap_or("");
+        if self.state.cursor_col > 0 && current_line[..self.state.cursor_col].ends_with('\\') {
+            self.handle_backspace();
+            self.add_new_line();
+            return;
+        }
+
+        self.submit_value();
+    }
+
+    /// Resolves an Enter-family key through [`EditorEnterOptions`]. `None`
+    /// leaves the key to the keybindings: the options are off, the key is
+    /// not Enter-like, or an open autocomplete owns a typed Enter.
+    fn resolve_enter(&self, event: &InputEvent) -> Option<EnterRole> {
+        if self.enter == EditorEnterOptions::default() {
+            return None;
+        }
+        let InputEvent::Key { key_id, raw, .. } = event else {
+            return None;
+        };
+        let (is_submit, is_newline) = {
+            let kb = self
+                .keybindings
+                .lock()
+                .expect("editor keybindings lock poisoned");
+            let legacy_newline =
+                self.enter.legacy_newline_keys && (key_id == "alt+enter" || raw == "\n");
+            (
+                kb.matches(Some(key_id), EditorAction::Submit) && !legacy_newline,
+                kb.matches(Some(key_id), EditorAction::NewLine) || legacy_newline,
+            )
+        };
+        if !is_submit && !is_newline {
+            return None;
+        }
+        let submits = is_newline == self.enter.enter_inserts_newline;
+        let in_paste = is_submit
+            && self
+                .enter
+                .unbracketed_paste_window
+                .zip(self.last_text_at)
+                .is_some_and(|(window, at)| at.elapsed() <= window);
+        if in_paste {
+            return Some(EnterRole::PastedNewline);
+        }
+        if self.autocomplete_state.is_some() && is_submit {
+            return None;
+        }
+        Some(if submits {
+            EnterRole::Submit
+        } else {
+            EnterRole::Newline
+        })
+    }
+
     fn submit_value(&mut self) {
         let text = self.get_text();
         let mut result = text.trim().to_string();
@@ -1066,10 +1300,7 @@ impl Editor {
                 .get(self.state.cursor_line)
                 .cloned()
                 .unwrap_or_default();
-            let before_cursor = &line[..self.state.cursor_col];
-            let mut graphemes: Vec<&str> = grapheme_segments(before_cursor).collect();
-            let grapheme_len = graphemes.pop().map(|seg| seg.len()).unwrap_or(1);
-            let start = self.state.cursor_col.saturating_sub(grapheme_len);
+            let start = prev_grapheme_boundary(&line, self.state.cursor_col);
             let after = &line[self.state.cursor_col..];
             self.state.lines[self.state.cursor_line] = format!("{}{}", &line[..start], after);
             self.set_cursor_col(start);
@@ -1130,10 +1361,7 @@ impl Editor {
 
         if self.state.cursor_col < current_line.len() {
             self.push_undo_snapshot();
-            let after_cursor = &current_line[self.state.cursor_col..];
-            let mut graphemes = grapheme_segments(after_cursor);
-            let first = graphemes.next().unwrap_or("");
-            let end = self.state.cursor_col.saturating_add(first.len());
+            let end = next_grapheme_boundary(&current_line, self.state.cursor_col);
             self.state.lines[self.state.cursor_line] = format!(
                 "{}{}",
                 &current_line[..self.state.cursor_col],
@@ -1229,6 +1457,50 @@ impl Editor {
         self.emit_change();
     }
 
+    /// Readline `transpose-chars`: swaps the clusters either side of the
+    /// cursor and moves past them; at the end of a line swaps the last two.
+    fn transpose_chars(&mut self) {
+        self.history_index = -1;
+        self.last_action = None;
+
+        let current_line = self
+            .state
+            .lines
+            .get(self.state.cursor_line)
+            .cloned()
+            .unwrap_or_default();
+        if self.state.cursor_col == 0 {
+            return;
+        }
+
+        let (mid, end) = if self.state.cursor_col >= current_line.len() {
+            (
+                prev_grapheme_boundary(&current_line, current_line.len()),
+                current_line.len(),
+            )
+        } else {
+            (
+                self.state.cursor_col,
+                next_grapheme_boundary(&current_line, self.state.cursor_col),
+            )
+        };
+        let start = prev_grapheme_boundary(&current_line, mid);
+        if start == mid {
+            return;
+        }
+
+        self.push_undo_snapshot();
+        self.state.lines[self.state.cursor_line] = format!(
+            "{}{}{}{}",
+            &current_line[..start],
+            &current_line[mid..end],
+            &current_line[start..mid],
+            &current_line[end..]
+        );
+        self.state.cursor_col = end;
+        self.emit_change();
+    }
+
     fn delete_word_backwards(&mut self) {
         self.history_index = -1;
 
@@ -1583,6 +1855,10 @@ impl Editor {
             return layout_lines;
         }
 
+        if !self.soft_wrap {
+            return self.layout_unwrapped(content_width);
+        }
+
         for (line_idx, line) in self.state.lines.iter().enumerate() {
             let is_current = line_idx == self.state.cursor_line;
             let line_visible_width = visible_width(line);
@@ -1645,12 +1921,61 @@ impl Editor {
         layout_lines
     }
 
+    /// One row per logical line, showing the columns from `horizontal_scroll`.
+    fn layout_unwrapped(&self, content_width: usize) -> Vec<LayoutLine> {
+        let start = self.horizontal_scroll;
+        self.state
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(line_idx, line)| {
+                let text = slice_by_column(line, start, content_width, true);
+                let cursor_pos = (line_idx == self.state.cursor_line).then(|| {
+                    let before = &line[..self.state.cursor_col.min(line.len())];
+                    let cursor_col = visible_width(before).saturating_sub(start);
+                    slice_by_column(line, start, cursor_col, true).len()
+                });
+                LayoutLine {
+                    text,
+                    has_cursor: cursor_pos.is_some(),
+                    cursor_pos,
+                }
+            })
+            .collect()
+    }
+
+    /// Keeps the cursor inside the visible columns when soft wrap is off.
+    fn update_horizontal_scroll(&mut self, content_width: usize) {
+        if self.soft_wrap {
+            self.horizontal_scroll = 0;
+            return;
+        }
+        let line = self
+            .state
+            .lines
+            .get(self.state.cursor_line)
+            .map(String::as_str)
+            .unwrap_or("");
+        let cursor_col = visible_width(&line[..self.state.cursor_col.min(line.len())]);
+        if cursor_col < self.horizontal_scroll {
+            self.horizontal_scroll = cursor_col;
+        } else if cursor_col >= self.horizontal_scroll + content_width {
+            self.horizontal_scroll = cursor_col + 1 - content_width;
+        }
+    }
+
     fn build_visual_line_map(&self, width: usize) -> Vec<VisualLine> {
         let mut visual_lines = Vec::new();
 
         for (idx, line) in self.state.lines.iter().enumerate() {
             let line_width = visible_width(line);
-            if line.is_empty() {
+            if !self.soft_wrap {
+                visual_lines.push(VisualLine {
+                    logical_line: idx,
+                    start_col: 0,
+                    length: line.len(),
+                });
+            } else if line.is_empty() {
                 visual_lines.push(VisualLine {
                     logical_line: idx,
                     start_col: 0,
@@ -1727,13 +2052,10 @@ impl Editor {
 
             if delta_col > 0 {
                 if self.state.cursor_col < current_line.len() {
-                    let after_cursor = &current_line[self.state.cursor_col..];
-                    let mut graphemes = grapheme_segments(after_cursor);
-                    if let Some(first) = graphemes.next() {
-                        self.set_cursor_col(self.state.cursor_col + first.len());
-                    } else {
-                        self.set_cursor_col(self.state.cursor_col + 1);
-                    }
+                    self.set_cursor_col(next_grapheme_boundary(
+                        current_line,
+                        self.state.cursor_col,
+                    ));
                 } else if self.state.cursor_line + 1 < self.state.lines.len() {
                     self.state.cursor_line += 1;
                     self.set_cursor_col(0);
@@ -1742,13 +2064,7 @@ impl Editor {
                         Some(self.state.cursor_col.saturating_sub(current_vl.start_col));
                 }
             } else if self.state.cursor_col > 0 {
-                let before_cursor = &current_line[..self.state.cursor_col];
-                let mut graphemes: Vec<&str> = grapheme_segments(before_cursor).collect();
-                if let Some(last) = graphemes.pop() {
-                    self.set_cursor_col(self.state.cursor_col.saturating_sub(last.len()));
-                } else {
-                    self.set_cursor_col(self.state.cursor_col.saturating_sub(1));
-                }
+                self.set_cursor_col(prev_grapheme_boundary(current_line, self.state.cursor_col));
             } else if self.state.cursor_line > 0 {
                 self.state.cursor_line = self.state.cursor_line.saturating_sub(1);
                 let prev_line = self.state.lines[self.state.cursor_line].as_str();
@@ -1792,13 +2108,15 @@ impl Editor {
             self.compute_vertical_move_column(current_visual_col, source_max, target_max);
         self.state.cursor_line = target_vl.logical_line;
         let target_col = target_vl.start_col.saturating_add(move_col);
-        let line_len = self
+        // Visual columns are byte offsets; land on a cluster start, not
+        // inside a multi-byte character or grapheme.
+        let line = self
             .state
             .lines
             .get(self.state.cursor_line)
-            .map(|line| line.len())
-            .unwrap_or(0);
-        self.state.cursor_col = min(target_col, line_len);
+            .map(String::as_str)
+            .unwrap_or("");
+        self.state.cursor_col = floor_grapheme_boundary(line, target_col);
     }
 
     fn compute_vertical_move_column(
@@ -1844,6 +2162,56 @@ impl Editor {
         }
     }
 
+    /// Start of the wrapped row holding the cursor; from there, the start of
+    /// the logical line.
+    fn move_to_visual_line_start(&mut self) {
+        let visual_lines = self.build_visual_line_map(self.last_width);
+        let current = self.find_current_visual_line(&visual_lines);
+        match visual_lines.get(current) {
+            Some(row) if self.state.cursor_col != row.start_col => {
+                self.last_action = None;
+                self.set_cursor_col(row.start_col);
+            }
+            _ => self.move_to_line_start(),
+        }
+    }
+
+    /// End of the wrapped row holding the cursor; from there, the end of the
+    /// logical line. A wrapped row's end column already belongs to the next
+    /// row, so the cursor stops before the row's last grapheme.
+    fn move_to_visual_line_end(&mut self) {
+        let visual_lines = self.build_visual_line_map(self.last_width);
+        let current = self.find_current_visual_line(&visual_lines);
+        let Some(row) = visual_lines.get(current) else {
+            self.move_to_line_end();
+            return;
+        };
+        let is_last_row = visual_lines
+            .get(current + 1)
+            .is_none_or(|next| next.logical_line != row.logical_line);
+        if is_last_row {
+            self.move_to_line_end();
+            return;
+        }
+        let line = self
+            .state
+            .lines
+            .get(row.logical_line)
+            .map(String::as_str)
+            .unwrap_or("");
+        let row_end = row.start_col + row.length;
+        let last_grapheme = grapheme_segments(&line[row.start_col..row_end])
+            .next_back()
+            .map_or(0, str::len);
+        let target = row_end - last_grapheme;
+        if self.state.cursor_col == target {
+            self.move_to_line_end();
+        } else {
+            self.last_action = None;
+            self.set_cursor_col(target);
+        }
+    }
+
     fn move_word_backwards(&mut self) {
         self.last_action = None;
         let current_line = self
@@ -1974,16 +2342,30 @@ impl Editor {
         self.move_to_visual_line(&visual_lines, current_visual_line, target_visual);
     }
 
+    /// Scrolls the viewport by most of a screen without moving the cursor.
+    fn scroll_viewport(&mut self, direction: isize) {
+        let step = max(1, self.last_visible_rows.saturating_sub(1));
+        self.scroll_offset = if direction.is_negative() {
+            self.scroll_offset.saturating_sub(step)
+        } else {
+            self.scroll_offset.saturating_add(step)
+        };
+        self.viewport_detached = true;
+    }
+
+    fn overflow_indicator(&self, arrow: &str, hidden_rows: usize, width: usize) -> String {
+        let noun = if hidden_rows == 1 { "line" } else { "lines" };
+        let indicator = format!("─── {arrow} {hidden_rows} more {noun} ");
+        let indicator = truncate_to_width(&indicator, width, "", false);
+        let remaining = width.saturating_sub(visible_width(&indicator));
+        (self.border_color)(&format!("{indicator}{}", "─".repeat(remaining)))
+    }
+
     fn set_cursor_col(&mut self, col: usize) {
         self.state.cursor_col = col;
         self.preferred_visual_col = None;
         if let Some(line) = self.state.lines.get(self.state.cursor_line) {
-            if self.state.cursor_col > line.len() {
-                self.state.cursor_col = line.len();
-            }
-            while self.state.cursor_col > 0 && !line.is_char_boundary(self.state.cursor_col) {
-                self.state.cursor_col = self.state.cursor_col.saturating_sub(1);
-            }
+            self.state.cursor_col = floor_grapheme_boundary(line, self.state.cursor_col);
         }
     }
 
@@ -2038,6 +2420,7 @@ impl Editor {
         let last_len = self.state.lines[self.state.cursor_line].len();
         self.set_cursor_col(last_len);
         self.scroll_offset = 0;
+        self.viewport_detached = false;
         self.emit_change();
     }
 
@@ -2060,12 +2443,7 @@ impl Editor {
 
             let found = if is_forward {
                 let start_index = if is_current {
-                    let after = &line[self.state.cursor_col..];
-                    if let Some(first) = after.chars().next() {
-                        self.state.cursor_col + first.len_utf8()
-                    } else {
-                        line.len()
-                    }
+                    next_grapheme_boundary(line, self.state.cursor_col)
                 } else {
                     0
                 };
@@ -2112,6 +2490,7 @@ impl Component for Editor {
             content_width.saturating_sub(if padding_x > 0 { 0 } else { 1 }),
         );
         self.last_width = layout_width;
+        self.update_horizontal_scroll(layout_width);
 
         let horizontal = (self.border_color)("─");
         let layout_lines = self.layout_text(layout_width);
@@ -2128,12 +2507,19 @@ impl Component for Editor {
                 }
             }
         };
+        let (max_visible_lines, fill_exact_height) = match self.max_height {
+            Some(max_height) if max_height < max_visible_lines => (max_height, false),
+            _ => (max_visible_lines, fill_exact_height),
+        };
+        self.last_visible_rows = max_visible_lines;
         let cursor_line_index = layout_lines
             .iter()
             .position(|line| line.has_cursor)
             .unwrap_or(0);
 
-        if cursor_line_index < self.scroll_offset {
+        if self.viewport_detached {
+            // Keep the scrolled viewport; the cursor may be off screen.
+        } else if cursor_line_index < self.scroll_offset {
             self.scroll_offset = cursor_line_index;
         } else if cursor_line_index >= self.scroll_offset + max_visible_lines {
             self.scroll_offset =
@@ -2166,22 +2552,43 @@ impl Component for Editor {
         let right_padding = left_padding.clone();
 
         if self.scroll_offset > 0 {
-            let indicator = format!("─── ↑ {} more ", self.scroll_offset);
-            let remaining = width.saturating_sub(visible_width(&indicator));
-            let line = format!("{}{}", indicator, "─".repeat(remaining));
-            result.push((self.border_color)(&line));
+            result.push(self.overflow_indicator("▲", self.scroll_offset, width));
         } else {
             result.push(horizontal.repeat(width));
         }
 
-        let emit_cursor = self.focused && self.autocomplete_state.is_none();
+        // The dropdown opens at the cursor, so keep reporting it.
+        let emit_cursor =
+            self.focused && (self.autocomplete_state.is_none() || self.has_attached_dropdown());
+        let placeholder = self
+            .placeholder
+            .as_deref()
+            .filter(|placeholder| !placeholder.is_empty())
+            .filter(|_| self.state.lines.len() == 1 && self.state.lines[0].is_empty())
+            .map(|placeholder| truncate_to_width(placeholder, content_width, "…", false));
 
         for (visible_idx, layout_line) in visible_lines.iter().enumerate() {
             let mut display_text = layout_line.text.clone();
             let mut line_visible_width = visible_width(&display_text);
             let mut cursor_in_padding = false;
 
-            if layout_line.has_cursor {
+            if let Some(placeholder) = placeholder
+                .as_deref()
+                .filter(|_| layout_line.has_cursor && !self.composing)
+            {
+                // The cursor sits on the first hint grapheme so the hint does
+                // not shift when typing starts.
+                if emit_cursor {
+                    self.last_cursor_pos = Some(CursorPos {
+                        row: 1 + visible_idx,
+                        col: padding_x,
+                    });
+                }
+                let first = grapheme_segments(placeholder).next().unwrap_or("");
+                let rest = &placeholder[first.len()..];
+                display_text = format!("\x1b[7m{first}\x1b[0m\x1b[2m{rest}\x1b[22m");
+                line_visible_width = visible_width(placeholder);
+            } else if layout_line.has_cursor {
                 if let Some(cursor_pos) = layout_line.cursor_pos {
                     let cursor_pos = min(cursor_pos, display_text.len());
                     let (before, after) = display_text.split_at(cursor_pos);
@@ -2192,7 +2599,9 @@ impl Component for Editor {
                         self.last_cursor_pos = Some(CursorPos { row, col });
                     }
 
-                    if !after.is_empty() {
+                    if self.composing {
+                        // The runtime overlays the preedit here.
+                    } else if !after.is_empty() {
                         let mut graphemes = grapheme_segments(after);
                         let first = graphemes.next().unwrap_or("");
                         let rest = &after[first.len()..];
@@ -2225,16 +2634,27 @@ impl Component for Editor {
                 .len()
                 .saturating_sub(self.scroll_offset + visible_lines.len());
             if lines_below > 0 {
-                let indicator = format!("─── ↓ {} more ", lines_below);
-                let remaining = width.saturating_sub(visible_width(&indicator));
-                let line = format!("{}{}", indicator, "─".repeat(remaining));
-                result.push((self.border_color)(&line));
+                result.push(self.overflow_indicator("▼", lines_below, width));
             } else {
                 result.push(horizontal.repeat(width));
             }
         }
 
-        if self.autocomplete_state.is_some() {
+        if let Some(dropdown) = self
+            .autocomplete_dropdown
+            .as_ref()
+            .filter(|dropdown| dropdown.is_attached())
+        {
+            let list = self
+                .autocomplete_list
+                .as_mut()
+                .filter(|_| self.focused && self.autocomplete_state.is_some())
+                .map(|list| {
+                    let list_width = dropdown.list_width(content_width);
+                    (list.render(list_width), list_width)
+                });
+            dropdown.sync(list);
+        } else if self.autocomplete_state.is_some() {
             if let Some(list) = self.autocomplete_list.as_mut() {
                 let autocomplete_result = list.render(content_width);
                 for line in autocomplete_result {
@@ -2259,6 +2679,7 @@ impl Component for Editor {
     fn handle_event(&mut self, event: &InputEvent) {
         self.clamp_cursor();
         self.poll_autocomplete_async();
+        self.viewport_detached = false;
 
         let key_id = match event {
             InputEvent::Key { key_id, .. } => Some(key_id.as_str()),
@@ -2288,13 +2709,36 @@ impl Component for Editor {
             }
         }
 
-        if let InputEvent::Paste { text, .. } = event {
+        if let InputEvent::Paste { text, .. } | InputEvent::FilesDropped { text, .. } = event {
             if !text.is_empty() {
                 self.handle_paste(text);
             }
             return;
         }
 
+        if let InputEvent::Composition { phase, text } = event {
+            self.composing = matches!(phase, CompositionPhase::Start | CompositionPhase::Update);
+            if *phase == CompositionPhase::Commit {
+                for ch in text.chars() {
+                    self.insert_character(&ch.to_string(), false);
+                }
+            }
+            return;
+        }
+
+        if let Some(role) = self.resolve_enter(event) {
+            match role {
+                EnterRole::Submit => self.submit_or_continue_line(),
+                EnterRole::Newline => self.add_new_line(),
+                EnterRole::PastedNewline => {
+                    self.cancel_autocomplete();
+                    self.add_new_line();
+                    self.last_text_at = Some(Instant::now());
+                }
+            }
+            return;
+        }
+
         let (
             is_copy,
             is_undo,
@@ -2446,8 +2890,11 @@ impl Component for Editor {
             CursorLeft,
             PageUp,
             PageDown,
+            ScrollUp,
+            ScrollDown,
             JumpForward,
             JumpBackward,
+            TransposeChars,
         }
 
         let action = {
@@ -2472,6 +2919,8 @@ impl Component for Editor {
                 || key_id == Some("shift+delete")
             {
                 Some(Action::ForwardDelete)
+            } else if kb.matches(key_id, EditorAction::TransposeChars) {
+                Some(Action::TransposeChars)
             } else if kb.matches(key_id, EditorAction::Yank) {
                 Some(Action::Yank)
             } else if kb.matches(key_id, EditorAction::YankPop) {
@@ -2500,6 +2949,10 @@ impl Component for Editor {
                 Some(Action::PageUp)
             } else if kb.matches(key_id, EditorAction::PageDown) {
                 Some(Action::PageDown)
+            } else if kb.matches(key_id, EditorAction::ScrollUp) {
+                Some(Action::ScrollUp)
+            } else if kb.matches(key_id, EditorAction::ScrollDown) {
+                Some(Action::ScrollDown)
             } else if kb.matches(key_id, EditorAction::JumpForward) {
                 Some(Action::JumpForward)
             } else if kb.matches(key_id, EditorAction::JumpBackward) {
@@ -2534,6 +2987,10 @@ impl Component for Editor {
                 self.handle_forward_delete();
                 return;
             }
+            Some(Action::TransposeChars) => {
+                self.transpose_chars();
+                return;
+            }
             Some(Action::Yank) => {
                 self.yank();
                 return;
@@ -2543,11 +3000,11 @@ impl Component for Editor {
                 return;
             }
             Some(Action::CursorLineStart) => {
-                self.move_to_line_start();
+                self.move_to_visual_line_start();
                 return;
             }
             Some(Action::CursorLineEnd) => {
-                self.move_to_line_end();
+                self.move_to_visual_line_end();
                 return;
             }
             Some(Action::CursorWordLeft) => {
@@ -2563,25 +3020,7 @@ impl Component for Editor {
                 return;
             }
             Some(Action::Submit) => {
-                if self.disable_submit {
-                    return;
-                }
-
-                let current_line = self
-                    .state
-                    .lines
-                    .get(self.state.cursor_line)
-                    .map(String::as_str)
-                    .unwrap_or("");
-                if self.state.cursor_col > 0
-                    && current_line[..self.state.cursor_col].ends_with('\\')
-                {
-                    self.handle_backspace();
-                    self.add_new_line();
-                    return;
-                }
-
-                self.submit_value();
+                self.submit_or_continue_line();
                 return;
             }
             Some(Action::CursorUp) => {
@@ -2622,6 +3061,14 @@ impl Component for Editor {
                 self.page_scroll(1);
                 return;
             }
+            Some(Action::ScrollUp) => {
+                self.scroll_viewport(-1);
+                return;
+            }
+            Some(Action::ScrollDown) => {
+                self.scroll_viewport(1);
+                return;
+            }
             Some(Action::JumpForward) => {
                 self.jump_mode = Some(JumpMode::Forward);
                 return;
@@ -2637,6 +3084,7 @@ impl Component for Editor {
             for ch in text.chars() {
                 self.insert_character(&ch.to_string(), false);
             }
+            self.last_text_at = Some(Instant::now());
         }
     }
 
@@ -2650,6 +3098,9 @@ impl Component for Editor {
 impl Focusable for Editor {
     fn set_focused(&mut self, focused: bool) {
         self.focused = focused;
+        if !focused && self.has_attached_dropdown() {
+            self.cancel_autocomplete();
+        }
     }
 
     fn is_focused(&self) -> bool {
@@ -2744,7 +3195,8 @@ fn is_punctuation_segment(segment: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::{
-        word_wrap_line, Editor, EditorHeightMode, EditorOptions, EditorPasteMode, EditorTheme,
+        word_wrap_line, Editor, EditorCounts, EditorEnterOptions, EditorHeightMode, EditorOptions,
+        EditorPasteMode, EditorTheme,
     };
     use crate::core::autocomplete::{
         AutocompleteItem, AutocompleteProvider, AutocompleteSuggestions,
@@ -2753,9 +3205,10 @@ mod tests {
     use crate::core::component::Component;
     use crate::core::cursor::CursorPos;
     use crate::core::editor_component::EditorComponent;
-    use crate::core::input_event::parse_input_events;
-    use crate::default_editor_keybindings_handle;
+    use crate::core::input_event::{parse_input_events, CompositionPhase, InputEvent};
+    use crate::core::text::width::visible_width;
     use crate::widgets::select_list::SelectListTheme;
+    use crate::{default_editor_keybindings_handle, EditorKeybindingsConfig};
     use std::cell::RefCell;
     use std::path::PathBuf;
     use std::rc::Rc;
@@ -2848,6 +3301,301 @@ mod tests {
         assert_eq!(editor.cursor_pos(), Some(CursorPos { row: 1, col: 1 }));
     }
 
+    #[test]
+    fn editor_home_end_stop_at_wrapped_row_then_logical_line() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions::default(),
+        );
+        editor.set_text("hello world again");
+        let _ = editor.render(11);
+        editor.state.cursor_col = 8;
+
+        send(&mut editor, "\x1b[H");
+        assert_eq!(editor.get_cursor(), (0, 6));
+        send(&mut editor, "\x1b[H");
+        assert_eq!(editor.get_cursor(), (0, 0));
+
+        editor.state.cursor_col = 8;
+        send(&mut editor, "\x1b[F");
+        assert_eq!(editor.get_cursor(), (0, 11));
+        send(&mut editor, "\x1b[F");
+        assert_eq!(editor.get_cursor(), (0, 17));
+
+        // Up/Down step through wrapped rows of the same logical line.
+        send(&mut editor, "\x1b[A");
+        assert_eq!(editor.get_cursor(), (0, 11));
+    }
+
+    #[test]
+    fn editor_without_soft_wrap_scrolls_horizontally() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions {
+                soft_wrap: Some(false),
+                ..EditorOptions::default()
+            },
+        );
+        editor.focused = true;
+        editor.set_text("abcdefghijklmnop\nxy");
+        editor.state.cursor_line = 0;
+        editor.state.cursor_col = 16;
+
+        let lines = editor.render(11);
+        assert_eq!(lines.len(), 4);
+        assert!(
+            lines[1].starts_with("hijklmnop\x1b[7m \x1b[0m"),
+            "{lines:?}"
+        );
+        assert_eq!(editor.cursor_pos(), Some(CursorPos { row: 1, col: 9 }));
+
+        send(&mut editor, "\x1b[B");
+        assert_eq!(editor.get_cursor(), (1, 2));
+        let lines = editor.render(11);
+        assert!(lines[1].starts_with("cdefghijkl"), "{lines:?}");
+        assert_eq!(editor.cursor_pos(), Some(CursorPos { row: 2, col: 0 }));
+
+        send(&mut editor, "\x1b[H");
+        assert_eq!(editor.get_cursor(), (1, 0));
+        let lines = editor.render(11);
+        assert!(lines[1].starts_with("abcdefghij"), "{lines:?}");
+    }
+
+    #[test]
+    fn editor_placeholder_renders_dimly_only_while_empty() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions {
+                placeholder: Some("Ask anything".to_string()),
+                ..EditorOptions::default()
+            },
+        );
+        editor.focused = true;
+
+        let lines = editor.render(20);
+        assert!(
+            lines[1].starts_with("\x1b[7mA\x1b[0m\x1b[2msk anything\x1b[22m"),
+            "{lines:?}"
+        );
+        assert_eq!(visible_width(&lines[1]), 20);
+        assert_eq!(editor.cursor_pos(), Some(CursorPos { row: 1, col: 0 }));
+
+        send(&mut editor, "x");
+        let lines = editor.render(20);
+        assert!(!lines[1].contains("Ask"), "{lines:?}");
+    }
+
+    #[test]
+    fn editor_placeholder_gives_way_to_a_composition_on_an_empty_buffer() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions {
+                placeholder: Some("Ask anything".to_string()),
+                ..EditorOptions::default()
+            },
+        );
+        editor.focused = true;
+
+        for (phase, text) in [
+            (CompositionPhase::Start, ""),
+            (CompositionPhase::Update, "にほ"),
+        ] {
+            editor.handle_event(&InputEvent::Composition {
+                phase,
+                text: text.to_string(),
+            });
+        }
+        let lines = editor.render(20);
+        assert!(!lines[1].contains("sk anything"), "{lines:?}");
+        assert!(!lines[1].contains("\x1b[7m"), "{lines:?}");
+        assert_eq!(editor.cursor_pos(), Some(CursorPos { row: 1, col: 0 }));
+
+        editor.handle_event(&InputEvent::Composition {
+            phase: CompositionPhase::Cancel,
+            text: String::new(),
+        });
+        let lines = editor.render(20);
+        assert!(lines[1].contains("sk anything"), "{lines:?}");
+    }
+
+    #[test]
+    fn editor_counts_report_expanded_text_and_notify_on_change() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions {
+                token_estimator: Some(Arc::new(|text: &str| text.len().div_ceil(4))),
+                ..EditorOptions::default()
+            },
+        );
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&seen);
+        editor.set_on_counts_change(Some(Box::new(move |counts| {
+            sink.borrow_mut().push(counts);
+        })));
+
+        assert_eq!(
+            editor.counts(),
+            EditorCounts {
+                chars: 0,
+                lines: 1,
+                estimated_tokens: Some(0),
+            }
+        );
+
+        editor.set_text("héllo\nworld");
+        let expected = EditorCounts {
+            chars: 11,
+            lines: 2,
+            estimated_tokens: Some(3),
+        };
+        assert_eq!(editor.counts(), expected);
+        assert_eq!(seen.borrow().as_slice(), &[expected]);
+
+        // Cursor movement does not change the counts and is not reported.
+        send(&mut editor, "\x1b[D");
+        editor.set_text("héllo\nworld");
+        assert_eq!(seen.borrow().len(), 1);
+    }
+
+    #[test]
+    fn editor_max_height_scrolls_viewport_without_moving_cursor() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions {
+                max_height: Some(3),
+                ..EditorOptions::default()
+            },
+        );
+        editor.focused = true;
+        let text = (0..10)
+            .map(|idx| format!("line {idx}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        editor.set_text(&text);
+
+        let lines = editor.render(30);
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("─── ▲ 7 more lines ─"), "{lines:?}");
+        assert!(lines[3].starts_with("line 9"), "{lines:?}");
+        assert_eq!(visible_width(&lines[0]), 30);
+
+        // shift+pageUp scrolls by a screen minus one row; the cursor stays.
+        send(&mut editor, "\x1b[5;2~");
+        let lines = editor.render(30);
+        assert_eq!(editor.get_cursor(), (9, 6));
+        assert!(lines[0].starts_with("─── ▲ 5 more lines ─"), "{lines:?}");
+        assert!(lines[1].starts_with("line 5"), "{lines:?}");
+        assert!(lines[4].starts_with("─── ▼ 2 more lines ─"), "{lines:?}");
+        assert_eq!(editor.cursor_pos(), None);
+
+        // Any other input brings the cursor back into view.
+        send(&mut editor, "x");
+        let lines = editor.render(30);
+        assert!(lines[3].starts_with("line 9x"), "{lines:?}");
+        assert!(lines[4].starts_with("───────"), "{lines:?}");
+    }
+
+    #[test]
+    fn editor_inserts_committed_composition_and_hides_block_cursor_while_composing() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions::default(),
+        );
+        editor.focused = true;
+        send(&mut editor, "a");
+
+        for (phase, text) in [
+            (CompositionPhase::Start, ""),
+            (CompositionPhase::Update, "にほ"),
+        ] {
+            editor.handle_event(&InputEvent::Composition {
+                phase,
+                text: text.to_string(),
+            });
+        }
+        let lines = editor.render(20);
+        assert!(!lines[1].contains("\x1b[7m"), "{lines:?}");
+        assert_eq!(editor.cursor_pos(), Some(CursorPos { row: 1, col: 1 }));
+        assert_eq!(editor.get_text(), "a");
+
+        editor.handle_event(&InputEvent::Composition {
+            phase: CompositionPhase::Commit,
+            text: "日本".to_string(),
+        });
+        assert_eq!(editor.get_text(), "a日本");
+        let lines = editor.render(20);
+        assert!(lines[1].contains("\x1b[7m \x1b[0m"), "{lines:?}");
+    }
+
+    #[test]
+    fn editor_moves_and_deletes_whole_grapheme_clusters() {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions::default(),
+        );
+        editor.focused = true;
+        let family = "👨\u{200d}👩\u{200d}👧";
+        editor.set_text(&format!("a{family}e\u{301}🇳🇿b"));
+
+        // Left steps over "b", the flag and "e" + combining accent.
+        for _ in 0..3 {
+            send(&mut editor, "\x1b[D");
+        }
+        assert_eq!(editor.state.cursor_col, 1 + family.len());
+        send(&mut editor, "\x7f");
+        assert_eq!(editor.get_text(), "ae\u{301}🇳🇿b");
+        send(&mut editor, "\x1b[3~");
+        assert_eq!(editor.get_text(), "a🇳🇿b");
+        send(&mut editor, "\x1b[C");
+        assert_eq!(editor.state.cursor_col, 1 + "🇳🇿".len());
+        send(&mut editor, "\x7f");
+        assert_eq!(editor.get_text(), "ab");
+
+        // Vertical movement never lands inside a cluster.
+        editor.set_text(&format!("abcdef\nx{family}"));
+        editor.state.cursor_line = 0;
+        editor.state.cursor_col = 3;
+        send(&mut editor, "\x1b[B");
+        assert_eq!(editor.state.cursor_line, 1);
+        assert_eq!(editor.state.cursor_col, 1);
+    }
+
+    #[test]
+    fn editor_transposes_chars_with_readline_preset() {
+        let keybindings = default_editor_keybindings_handle();
+        keybindings
+            .lock()
+            .expect("keybindings lock poisoned")
+            .set_config(EditorKeybindingsConfig::readline());
+        let mut editor = Editor::new(theme(), keybindings, EditorOptions::default());
+        editor.focused = true;
+        editor.set_text("teh");
+
+        // At end of line the last two clusters swap.
+        send(&mut editor, "\x14");
+        assert_eq!(editor.get_text(), "the");
+        assert_eq!(editor.state.cursor_col, 3);
+
+        // Mid-line, the clusters around the cursor swap and the cursor advances.
+        editor.set_text("a🇳🇿b");
+        editor.state.cursor_col = 1;
+        send(&mut editor, "\x14");
+        assert_eq!(editor.get_text(), "🇳🇿ab");
+        assert_eq!(editor.state.cursor_col, "🇳🇿a".len());
+
+        send(&mut editor, "\x01");
+        send(&mut editor, "\x14");
+        assert_eq!(editor.get_text(), "🇳🇿ab");
+    }
+
     #[test]
     fn editor_getters_reflect_options() {
         let options = EditorOptions {
@@ -2922,7 +3670,7 @@ mod tests {
         editor.state.cursor_line = 8;
         editor.state.cursor_col = 0;
         let lines = editor.render(20);
-        assert!(lines[0].contains("↑"));
+        assert!(lines[0].contains("▲ 4 more lines"));
     }
 
     #[test]
@@ -3128,6 +3876,60 @@ mod tests {
         assert_eq!(editor.get_text(), "");
     }
 
+    fn editor_with_enter(enter: EditorEnterOptions) -> (Editor, Rc<RefCell<Vec<String>>>) {
+        let mut editor = Editor::new(
+            theme(),
+            default_editor_keybindings_handle(),
+            EditorOptions {
+                enter: Some(enter),
+                ..EditorOptions::default()
+            },
+        );
+        let submitted = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&submitted);
+        editor.set_on_submit(Some(Box::new(move |text| sink.borrow_mut().push(text))));
+        (editor, submitted)
+    }
+
+    #[test]
+    fn paste_safe_enter_keeps_unbracketed_pastes_and_legacy_newline_keys() {
+        let (mut editor, submitted) = editor_with_enter(EditorEnterOptions {
+            unbracketed_paste_window: Some(Duration::from_secs(3600)),
+            ..EditorEnterOptions::paste_safe()
+        });
+        // Without bracketed paste a pasted "a\r\rb" arrives as text and keys.
+        for data in ["a", "\r", "\r", "b"] {
+            send(&mut editor, data);
+        }
+        assert_eq!(editor.get_text(), "a\n\nb");
+        editor.last_text_at = None;
+        send(&mut editor, "\r");
+        assert_eq!(*submitted.borrow(), vec!["a\n\nb"]);
+
+        let (mut editor, submitted) = editor_with_enter(EditorEnterOptions {
+            unbracketed_paste_window: None,
+            ..EditorEnterOptions::paste_safe()
+        });
+        // Legacy Alt+Enter and Ctrl+J insert newlines; Enter still submits.
+        for data in ["x", "\x1b\r", "\n", "y", "\r"] {
+            send(&mut editor, data);
+        }
+        assert_eq!(*submitted.borrow(), vec!["x\n\ny"]);
+    }
+
+    #[test]
+    fn enter_can_insert_newlines_while_the_newline_keys_submit() {
+        let (mut editor, submitted) = editor_with_enter(EditorEnterOptions {
+            enter_inserts_newline: true,
+            legacy_newline_keys: true,
+            unbracketed_paste_window: None,
+        });
+        for data in ["a", "\r", "\x1b[200~b\rc\x1b[201~", "\x1b\r"] {
+            send(&mut editor, data);
+        }
+        assert_eq!(*submitted.borrow(), vec!["a\nb\nc"]);
+    }
+
     #[test]
     fn editor_large_paste_inserts_marker_and_expands() {
         let mut editor = Editor::new(
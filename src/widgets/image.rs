@@ -1,12 +1,33 @@
 //! Image widget.
+//!
+//! An image is either built from base64 data (`Image::new`) or loaded from a
+//! file (`Image::from_path`). With a runtime handle, reading, decoding, and
+//! protocol encoding run on the runtime's worker pool; until they finish the
+//! widget shows a placeholder with the file name, the dimensions once known,
+//! and a spinner frame. Encoded payloads are kept per protocol, size, and
+//! cell dimensions, so a resize or a cell-size report re-encodes only once
+//! per distinct layout.
 
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crate::core::animation::AnimationClock;
 use crate::core::component::Component;
 use crate::core::terminal_image::{
-    get_capabilities, get_image_dimensions, image_fallback, render_image, ImageDimensions,
-    ImageRenderOptions, TerminalImageState,
+    base64_encode, fit_image_within_cells, get_capabilities, get_cell_dimensions,
+    get_image_dimensions, image_fallback, render_image, CellDimensions, ImageDimensions,
+    ImageProtocol, ImageRenderOptions, ImageRenderResult, KittyPlacement, TerminalImageState,
 };
+use crate::runtime::tui::{Command, RuntimeHandle};
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(80);
+/// Distinct layouts whose encoded payload is kept per image.
+const ENCODED_CACHE_LIMIT: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+type Spawner = Arc<dyn Fn(Job) + Send + Sync>;
 
 pub struct ImageTheme {
     pub fallback_color: Box<dyn Fn(&str) -> String>,
@@ -19,18 +40,50 @@ pub struct ImageOptions {
     pub filename: Option<String>,
     pub image_id: Option<u32>,
     pub terminal_image_state: Option<Arc<TerminalImageState>>,
+    /// `KittyPlacement::UnicodePlaceholder` keeps kitty images in plain
+    /// placeholder text that scrolls into history with the transcript.
+    pub kitty_placement: KittyPlacement,
 }
 
-pub struct Image {
-    base64_data: String,
+#[derive(Debug, Clone)]
+struct Decoded {
+    base64_data: Arc<str>,
     mime_type: String,
     dimensions: ImageDimensions,
+}
+
+/// Everything an encoded payload depends on besides the image itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EncodeKey {
+    protocol: ImageProtocol,
+    max_width_cells: u32,
+    max_height_cells: Option<u32>,
+    cell_dimensions: CellDimensions,
+}
+
+/// Results handed back from worker jobs, drained on the next render.
+#[derive(Default)]
+struct Inbox {
+    decoded: Option<Result<Decoded, String>>,
+    encoded: Vec<(EncodeKey, Option<ImageRenderResult>)>,
+}
+
+pub struct Image {
+    path: Option<PathBuf>,
+    decoded: Option<Decoded>,
+    decode_error: Option<String>,
     theme: ImageTheme,
     options: ImageOptions,
     terminal_image_state: Arc<TerminalImageState>,
     image_id: Option<u32>,
+    spawner: Option<Spawner>,
+    inbox: Arc<Mutex<Inbox>>,
+    encoded: Vec<(EncodeKey, Option<ImageRenderResult>)>,
+    pending: Vec<EncodeKey>,
+    clock: AnimationClock,
+    started: Duration,
     cached_lines: Option<Vec<String>>,
-    cached_width: Option<usize>,
+    cached_layout: Option<(usize, CellDimensions)>,
 }
 
 impl Image {
@@ -49,6 +102,51 @@ impl Image {
                 width_px: 800,
                 height_px: 600,
             });
+        let mut image = Self::empty(theme, options);
+        image.decoded = Some(Decoded {
+            base64_data: base64_data.into(),
+            mime_type,
+            dimensions,
+        });
+        image
+    }
+
+    /// Loads the image at `path` on the runtime's worker pool, showing a
+    /// placeholder until it is read and encoded. The file name labels the
+    /// placeholder and fallback unless `options.filename` is set.
+    pub fn from_path(
+        path: impl Into<PathBuf>,
+        theme: ImageTheme,
+        options: ImageOptions,
+        runtime_handle: RuntimeHandle,
+    ) -> Self {
+        Self::from_path_with_spawner(path.into(), theme, options, runtime_spawner(runtime_handle))
+    }
+
+    fn from_path_with_spawner(
+        path: PathBuf,
+        theme: ImageTheme,
+        mut options: ImageOptions,
+        spawner: Spawner,
+    ) -> Self {
+        if options.filename.is_none() {
+            options.filename = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned());
+        }
+        let mut image = Self::empty(theme, options);
+        image.path = Some(path.clone());
+        image.spawner = Some(Arc::clone(&spawner));
+
+        let inbox = Arc::clone(&image.inbox);
+        spawner(Box::new(move || {
+            let decoded = decode_file(&path);
+            lock_inbox(&inbox).decoded = Some(decoded);
+        }));
+        image
+    }
+
+    fn empty(theme: ImageTheme, options: ImageOptions) -> Self {
         let terminal_image_state = options
             .terminal_image_state
             .as_ref()
@@ -56,31 +154,156 @@ impl Image {
             .unwrap_or_else(|| Arc::new(TerminalImageState::default()));
         let image_id = options.image_id;
         Self {
-            base64_data,
-            mime_type,
-            dimensions,
+            path: None,
+            decoded: None,
+            decode_error: None,
             theme,
             options,
             terminal_image_state,
             image_id,
+            spawner: None,
+            inbox: Arc::new(Mutex::new(Inbox::default())),
+            encoded: Vec::new(),
+            pending: Vec::new(),
+            clock: AnimationClock::system(),
+            started: AnimationClock::system().now(),
             cached_lines: None,
-            cached_width: None,
+            cached_layout: None,
         }
     }
 
+    /// Samples the loading spinner from `clock`; the spinner restarts.
+    pub fn set_animation_clock(&mut self, clock: AnimationClock) {
+        self.started = clock.now();
+        self.clock = clock;
+    }
+
+    /// Moves protocol encoding onto the runtime's worker pool. Without a
+    /// handle, `Image::new` encodes during render.
+    pub fn set_runtime_handle(&mut self, runtime_handle: RuntimeHandle) {
+        self.spawner = Some(runtime_spawner(runtime_handle));
+    }
+
     pub fn get_image_id(&self) -> Option<u32> {
         self.image_id
     }
+
+    /// The file this image was loaded from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// `false` while the file is being read or the current layout is being
+    /// encoded in the background.
+    pub fn is_loaded(&self) -> bool {
+        (self.decoded.is_some() || self.decode_error.is_some()) && self.pending.is_empty()
+    }
+
+    fn drain_inbox(&mut self) {
+        let mut inbox = lock_inbox(&self.inbox);
+        let mut changed = false;
+        if let Some(decoded) = inbox.decoded.take() {
+            match decoded {
+                Ok(decoded) => self.decoded = Some(decoded),
+                Err(error) => self.decode_error = Some(error),
+            }
+            changed = true;
+        }
+        for (key, result) in inbox.encoded.drain(..) {
+            self.pending.retain(|pending| *pending != key);
+            store_encoded(&mut self.encoded, key, result);
+            changed = true;
+        }
+        drop(inbox);
+        if changed {
+            self.cached_lines = None;
+            self.cached_layout = None;
+        }
+    }
+
+    fn fallback_line(&self, decoded: &Decoded) -> String {
+        let fallback = image_fallback(
+            &decoded.mime_type,
+            Some(decoded.dimensions),
+            self.options.filename.as_deref(),
+        );
+        (self.theme.fallback_color)(&fallback)
+    }
+
+    /// A spinner, the file name, and the dimensions once known, followed by
+    /// blank rows reserving the height the image will take.
+    fn placeholder(&self, dimensions: Option<ImageDimensions>, rows: u32) -> Vec<String> {
+        let frame_index = self
+            .clock
+            .frame(self.started, SPINNER_INTERVAL, SPINNER_FRAMES.len());
+        let mut label = format!(
+            "{} Loading {}",
+            SPINNER_FRAMES[frame_index],
+            self.options.filename.as_deref().unwrap_or("image")
+        );
+        if let Some(dimensions) = dimensions {
+            label.push_str(&format!(
+                " {}x{}",
+                dimensions.width_px, dimensions.height_px
+            ));
+        }
+        let mut lines = vec![(self.theme.fallback_color)(&label)];
+        lines.resize(rows.max(1) as usize, String::new());
+        lines
+    }
+
+    fn spawn_encode(&mut self, key: EncodeKey, decoded: &Decoded, spawner: &Spawner) {
+        if self.pending.contains(&key) {
+            return;
+        }
+        self.pending.push(key);
+        let state = Arc::clone(&self.terminal_image_state);
+        let base64_data = Arc::clone(&decoded.base64_data);
+        let dimensions = decoded.dimensions;
+        let image_id = self.image_id;
+        let kitty_placement = self.options.kitty_placement;
+        let inbox = Arc::clone(&self.inbox);
+        spawner(Box::new(move || {
+            let result = render_image(
+                state.as_ref(),
+                &base64_data,
+                dimensions,
+                &ImageRenderOptions {
+                    max_width_cells: Some(key.max_width_cells),
+                    max_height_cells: key.max_height_cells,
+                    preserve_aspect_ratio: None,
+                    image_id,
+                    kitty_placement,
+                },
+            );
+            lock_inbox(&inbox).encoded.push((key, result));
+        }));
+    }
 }
 
 impl Component for Image {
     fn render(&mut self, width: usize) -> Vec<String> {
-        if let (Some(lines), Some(cached_width)) = (self.cached_lines.as_ref(), self.cached_width) {
-            if cached_width == width {
+        self.drain_inbox();
+
+        let cell_dimensions = get_cell_dimensions(self.terminal_image_state.as_ref());
+        if let (Some(lines), Some(layout)) = (self.cached_lines.as_ref(), self.cached_layout) {
+            if layout == (width, cell_dimensions) {
                 return lines.clone();
             }
         }
 
+        let Some(decoded) = self.decoded.clone() else {
+            return match self.decode_error.as_deref() {
+                Some(error) => {
+                    let name = self.options.filename.as_deref().unwrap_or("image");
+                    vec![(self.theme.fallback_color)(&format!(
+                        "[Image: {name}: {error}]"
+                    ))]
+                }
+                None => self.placeholder(None, 1),
+            };
+        };
+
         let max_width_limit = width.saturating_sub(2) as u32;
         let max_width = self
             .options
@@ -89,73 +312,155 @@ impl Component for Image {
             .min(max_width_limit);
 
         let caps = get_capabilities(self.terminal_image_state.as_ref());
-        let mut lines = Vec::new();
+        let Some(protocol) = caps.images else {
+            let lines = vec![self.fallback_line(&decoded)];
+            self.cached_lines = Some(lines.clone());
+            self.cached_layout = Some((width, cell_dimensions));
+            return lines;
+        };
 
-        if caps.images.is_some() {
-            let result = render_image(
-                self.terminal_image_state.as_ref(),
-                &self.base64_data,
-                self.dimensions,
-                &ImageRenderOptions {
-                    max_width_cells: Some(max_width),
-                    max_height_cells: self.options.max_height_cells,
-                    preserve_aspect_ratio: None,
-                    image_id: self.image_id,
-                },
-            );
+        let key = EncodeKey {
+            protocol,
+            max_width_cells: max_width,
+            max_height_cells: self.options.max_height_cells,
+            cell_dimensions,
+        };
+        let result = match self.encoded.iter().find(|(cached, _)| *cached == key) {
+            Some((_, result)) => result.clone(),
+            None => match self.spawner.clone() {
+                Some(spawner) => {
+                    self.spawn_encode(key, &decoded, &spawner);
+                    let (_, rows) = fit_image_within_cells(
+                        decoded.dimensions,
+                        cell_dimensions,
+                        max_width.max(1),
+                        key.max_height_cells,
+                    );
+                    return self.placeholder(Some(decoded.dimensions), rows);
+                }
+                None => {
+                    let result = render_image(
+                        self.terminal_image_state.as_ref(),
+                        &decoded.base64_data,
+                        decoded.dimensions,
+                        &ImageRenderOptions {
+                            max_width_cells: Some(max_width),
+                            max_height_cells: self.options.max_height_cells,
+                            preserve_aspect_ratio: None,
+                            image_id: self.image_id,
+                            kitty_placement: self.options.kitty_placement,
+                        },
+                    );
+                    store_encoded(&mut self.encoded, key, result.clone());
+                    result
+                }
+            },
+        };
 
-            if let Some(result) = result {
-                if result.image_id.is_some() {
-                    self.image_id = result.image_id;
+        let mut lines = Vec::new();
+        if let Some(result) = result {
+            if result.image_id.is_some() {
+                self.image_id = result.image_id;
+            }
+            let rows = result.rows as usize;
+            if let Some(placeholder_lines) = result.placeholder_lines {
+                // The transmission is zero-width; the cells draw the image.
+                lines = placeholder_lines;
+                if let Some(first) = lines.first_mut() {
+                    first.insert_str(0, &result.sequence);
                 }
-                let rows = result.rows as usize;
-                if rows > 0 {
-                    for _ in 0..rows.saturating_sub(1) {
-                        lines.push(String::new());
-                    }
-                    let move_up = if rows > 1 {
-                        format!("\x1b[{}A", rows - 1)
-                    } else {
-                        String::new()
-                    };
-                    lines.push(format!("{move_up}{}", result.sequence));
+            } else if rows > 0 {
+                for _ in 0..rows.saturating_sub(1) {
+                    lines.push(String::new());
                 }
-            } else {
-                let fallback = image_fallback(
-                    &self.mime_type,
-                    Some(self.dimensions),
-                    self.options.filename.as_deref(),
-                );
-                lines.push((self.theme.fallback_color)(&fallback));
+                let move_up = if rows > 1 {
+                    format!("\x1b[{}A", rows - 1)
+                } else {
+                    String::new()
+                };
+                lines.push(format!("{move_up}{}", result.sequence));
             }
         } else {
-            let fallback = image_fallback(
-                &self.mime_type,
-                Some(self.dimensions),
-                self.options.filename.as_deref(),
-            );
-            lines.push((self.theme.fallback_color)(&fallback));
+            lines.push(self.fallback_line(&decoded));
         }
 
         self.cached_lines = Some(lines.clone());
-        self.cached_width = Some(width);
+        self.cached_layout = Some((width, cell_dimensions));
 
         lines
     }
 
     fn invalidate(&mut self) {
         self.cached_lines = None;
-        self.cached_width = None;
+        self.cached_layout = None;
+    }
+}
+
+fn runtime_spawner(runtime_handle: RuntimeHandle) -> Spawner {
+    Arc::new(move |job: Job| {
+        runtime_handle.spawn_command(move || {
+            job();
+            Command::RequestRender
+        });
+    })
+}
+
+fn lock_inbox(inbox: &Mutex<Inbox>) -> std::sync::MutexGuard<'_, Inbox> {
+    match inbox.lock() {
+        Ok(inbox) => inbox,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Keeps the most recent layouts, dropping the oldest past the limit.
+fn store_encoded(
+    encoded: &mut Vec<(EncodeKey, Option<ImageRenderResult>)>,
+    key: EncodeKey,
+    result: Option<ImageRenderResult>,
+) {
+    encoded.retain(|(cached, _)| *cached != key);
+    if encoded.len() >= ENCODED_CACHE_LIMIT {
+        encoded.remove(0);
+    }
+    encoded.push((key, result));
+}
+
+fn decode_file(path: &Path) -> Result<Decoded, String> {
+    let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+    let mime_type = sniff_mime_type(&bytes).ok_or_else(|| "unsupported format".to_string())?;
+    let base64_data = base64_encode(&bytes);
+    let dimensions = get_image_dimensions(&base64_data, mime_type)
+        .ok_or_else(|| "unreadable dimensions".to_string())?;
+    Ok(Decoded {
+        base64_data: base64_data.into(),
+        mime_type: mime_type.to_string(),
+        dimensions,
+    })
+}
+
+fn sniff_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Image, ImageOptions, ImageTheme};
+    use super::{Image, ImageOptions, ImageTheme, Job, KittyPlacement, Spawner};
     use crate::core::component::Component;
     use crate::core::terminal_image::{
-        reset_capabilities_cache, ImageDimensions, TerminalImageState,
+        is_image_line, reset_capabilities_cache, set_cell_dimensions, CellDimensions,
+        ImageDimensions, TerminalImageState,
     };
+    use crate::core::text::width::visible_width;
     use std::env;
     use std::sync::Arc;
     use std::sync::{Mutex, OnceLock};
@@ -214,6 +519,7 @@ mod tests {
             filename: None,
             image_id: Some(5),
             terminal_image_state: Some(Arc::clone(&terminal_image_state)),
+            kitty_placement: KittyPlacement::Direct,
         };
         let dims = ImageDimensions {
             width_px: 100,
@@ -230,6 +536,43 @@ mod tests {
         reset_capabilities_cache(terminal_image_state.as_ref());
     }
 
+    #[test]
+    fn image_renders_kitty_placeholder_cells_as_plain_lines() {
+        let _guard = env_test_lock().lock().expect("test lock poisoned");
+        let _term = set_env_guard("TERM", Some("xterm-256color"));
+        let _term_program = set_env_guard("TERM_PROGRAM", Some("kitty"));
+        let _kitty = set_env_guard("KITTY_WINDOW_ID", Some("1"));
+        let _wezterm = set_env_guard("WEZTERM_PANE", None);
+        let _iterm = set_env_guard("ITERM_SESSION_ID", None);
+        let _ghostty = set_env_guard("GHOSTTY_RESOURCES_DIR", None);
+        let terminal_image_state = Arc::new(TerminalImageState::default());
+        reset_capabilities_cache(terminal_image_state.as_ref());
+
+        let options = ImageOptions {
+            max_width_cells: Some(10),
+            image_id: Some(5),
+            terminal_image_state: Some(Arc::clone(&terminal_image_state)),
+            kitty_placement: KittyPlacement::UnicodePlaceholder,
+            ..ImageOptions::default()
+        };
+        let dims = ImageDimensions {
+            width_px: 90,
+            height_px: 36,
+        };
+        let mut image = Image::new("AAAA", "image/png", theme(), options, Some(dims));
+        let lines = image.render(20);
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("\x1b_G"));
+        assert!(lines[0].contains(",U=1;"));
+        for line in &lines {
+            assert!(!is_image_line(line));
+            assert_eq!(visible_width(line), 10);
+        }
+
+        reset_capabilities_cache(terminal_image_state.as_ref());
+    }
+
     #[test]
     fn image_falls_back_without_capabilities() {
         let _guard = env_test_lock().lock().expect("test lock poisoned");
@@ -248,6 +591,7 @@ mod tests {
             filename: Some("file.png".to_string()),
             image_id: None,
             terminal_image_state: Some(Arc::clone(&terminal_image_state)),
+            kitty_placement: KittyPlacement::Direct,
         };
         let dims = ImageDimensions {
             width_px: 200,
@@ -261,4 +605,99 @@ mod tests {
 
         reset_capabilities_cache(terminal_image_state.as_ref());
     }
+
+    /// Queues jobs so a test decides when background work completes.
+    fn queued_spawner() -> (Spawner, Arc<Mutex<Vec<Job>>>) {
+        let queue: Arc<Mutex<Vec<Job>>> = Arc::new(Mutex::new(Vec::new()));
+        let jobs = Arc::clone(&queue);
+        let spawner: Spawner = Arc::new(move |job| jobs.lock().unwrap().push(job));
+        (spawner, queue)
+    }
+
+    fn run_jobs(queue: &Mutex<Vec<Job>>) -> usize {
+        let jobs: Vec<Job> = queue.lock().unwrap().drain(..).collect();
+        let count = jobs.len();
+        for job in jobs {
+            job();
+        }
+        count
+    }
+
+    #[test]
+    fn image_from_path_shows_placeholder_and_caches_encoded_layouts() {
+        let _guard = env_test_lock().lock().expect("test lock poisoned");
+        let _term = set_env_guard("TERM", Some("xterm-256color"));
+        let _term_program = set_env_guard("TERM_PROGRAM", Some("kitty"));
+        let _kitty = set_env_guard("KITTY_WINDOW_ID", Some("1"));
+        let _wezterm = set_env_guard("WEZTERM_PANE", None);
+        let _iterm = set_env_guard("ITERM_SESSION_ID", None);
+        let _ghostty = set_env_guard("GHOSTTY_RESOURCES_DIR", None);
+        let terminal_image_state = Arc::new(TerminalImageState::default());
+        reset_capabilities_cache(terminal_image_state.as_ref());
+
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&90u32.to_be_bytes());
+        png.extend_from_slice(&36u32.to_be_bytes());
+        png.extend_from_slice(&[8, 6, 0, 0, 0]);
+        let path = env::temp_dir().join(format!("tape-image-{}.png", std::process::id()));
+        std::fs::write(&path, &png).expect("write png");
+
+        let options = ImageOptions {
+            max_width_cells: Some(10),
+            max_height_cells: None,
+            filename: None,
+            image_id: Some(7),
+            terminal_image_state: Some(Arc::clone(&terminal_image_state)),
+            kitty_placement: KittyPlacement::Direct,
+        };
+        let (spawner, queue) = queued_spawner();
+        let mut image = Image::from_path_with_spawner(path.clone(), theme(), options, spawner);
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+
+        let lines = image.render(20);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains(&format!("Loading {name}>")), "{lines:?}");
+        assert!(!image.is_loaded());
+
+        assert_eq!(run_jobs(&queue), 1);
+        let lines = image.render(20);
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[0].contains(&format!("Loading {name} 90x36")),
+            "{lines:?}"
+        );
+
+        assert_eq!(run_jobs(&queue), 1);
+        let lines = image.render(20);
+        assert!(image.is_loaded());
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("\x1b[1A\x1b_G"), "{lines:?}");
+        assert_eq!(image.render(20), lines);
+        assert_eq!(run_jobs(&queue), 0);
+
+        set_cell_dimensions(
+            terminal_image_state.as_ref(),
+            CellDimensions {
+                width_px: 18,
+                height_px: 18,
+            },
+        );
+        assert!(image.render(20)[0].contains("Loading"));
+        assert_eq!(run_jobs(&queue), 1);
+        let resized = image.render(20);
+        assert_eq!(resized.len(), 4);
+
+        set_cell_dimensions(
+            terminal_image_state.as_ref(),
+            CellDimensions {
+                width_px: 9,
+                height_px: 18,
+            },
+        );
+        assert_eq!(image.render(20), lines);
+        assert_eq!(run_jobs(&queue), 0);
+
+        let _ = std::fs::remove_file(&path);
+        reset_capabilities_cache(terminal_image_state.as_ref());
+    }
 }
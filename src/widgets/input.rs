@@ -1,12 +1,45 @@
 //! Input widget.
+//!
+//! Beyond plain editing, an input can mask its value (passwords, tokens),
+//! cap its length, and show a validation message under the field. A
+//! validator and the `on_change` callback run after every user edit.
 
 use crate::core::component::{Component, Focusable};
 use crate::core::cursor::CursorPos;
-use crate::core::input_event::InputEvent;
+use crate::core::input_event::{CompositionPhase, InputEvent};
 use crate::core::keybindings::{EditorAction, EditorKeybindingsHandle};
-use crate::core::text::utils::{grapheme_segments, is_punctuation_char, is_whitespace_char};
+use crate::core::text::grapheme::{
+    floor_grapheme_boundary, grapheme_count, next_grapheme_boundary, prev_grapheme_boundary,
+    take_graphemes,
+};
+use crate::core::text::utils::{
+    grapheme_segments, is_punctuation_char, is_whitespace_char, truncate_to_width,
+};
 use crate::core::text::width::visible_width;
 
+pub type InputStyleFn = Box<dyn Fn(&str) -> String>;
+pub type InputValidator = Box<dyn Fn(&str) -> Option<InputValidation>>;
+
+/// Message shown under an [`Input`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputValidation {
+    Error(String),
+    Warning(String),
+}
+
+impl InputValidation {
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Error(_))
+    }
+}
+
+/// Styles for the validation line; unset styles render plain text.
+#[derive(Default)]
+pub struct InputTheme {
+    pub error: Option<InputStyleFn>,
+    pub warning: Option<InputStyleFn>,
+}
+
 /// Single-line input component with horizontal scrolling.
 pub struct Input {
     value: String,
@@ -17,6 +50,12 @@ pub struct Input {
     keybindings: EditorKeybindingsHandle,
     on_submit: Option<Box<dyn FnMut(String)>>,
     on_escape: Option<Box<dyn FnMut()>>,
+    on_change: Option<Box<dyn FnMut(String)>>,
+    mask: Option<char>,
+    max_length: Option<usize>,
+    validation: Option<InputValidation>,
+    validator: Option<InputValidator>,
+    theme: InputTheme,
 }
 
 impl Input {
@@ -30,6 +69,12 @@ impl Input {
             keybindings,
             on_submit: None,
             on_escape: None,
+            on_change: None,
+            mask: None,
+            max_length: None,
+            validation: None,
+            validator: None,
+            theme: InputTheme::default(),
         }
     }
 
@@ -37,10 +82,18 @@ impl Input {
         &self.value
     }
 
+    /// Replaces the value (truncated to the max length). Does not call
+    /// `on_change`, but does re-run the validator.
     pub fn set_value(&mut self, value: impl Into<String>) {
-        self.value = value.into();
+        let mut value = value.into();
+        if let Some(max_length) = self.max_length {
+            let kept = take_graphemes(&value, max_length).len();
+            value.truncate(kept);
+        }
+        self.value = value;
         self.cursor = self.cursor.min(self.value.len());
         self.clamp_cursor();
+        self.run_validator();
     }
 
     pub fn set_prompt(&mut self, prompt: impl Into<String>) {
@@ -55,16 +108,95 @@ impl Input {
         self.on_escape = handler;
     }
 
+    /// Called with the new value after every edit made through input events.
+    pub fn set_on_change(&mut self, handler: Option<Box<dyn FnMut(String)>>) {
+        self.on_change = handler;
+    }
+
+    /// Renders every character as `mask` (e.g. `'•'`); `None` shows the value.
+    /// Word motions jump to the start or end while masked so they do not
+    /// reveal word boundaries.
+    pub fn set_mask(&mut self, mask: Option<char>) {
+        self.mask = mask;
+    }
+
+    /// Caps the value at `max_length` characters; typing and pastes beyond
+    /// it are cut off.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+        let value = std::mem::take(&mut self.value);
+        self.set_value(value);
+    }
+
+    /// Sets the message shown under the field until the next validator run.
+    pub fn set_validation(&mut self, validation: Option<InputValidation>) {
+        self.validation = validation;
+    }
+
+    pub fn validation(&self) -> Option<&InputValidation> {
+        self.validation.as_ref()
+    }
+
+    /// Installs a validator that runs on every value change and replaces the
+    /// current validation message.
+    pub fn set_validator(&mut self, validator: Option<InputValidator>) {
+        self.validator = validator;
+        self.run_validator();
+    }
+
+    pub fn set_theme(&mut self, theme: InputTheme) {
+        self.theme = theme;
+    }
+
+    fn run_validator(&mut self) {
+        if let Some(validator) = self.validator.as_ref() {
+            self.validation = validator(&self.value);
+        }
+    }
+
+    /// The rendered value and cursor byte offset, with masking applied.
+    fn display_value(&self) -> (String, usize) {
+        let Some(mask) = self.mask else {
+            return (self.value.clone(), self.cursor);
+        };
+        let before = grapheme_segments(&self.value[..self.cursor]).count();
+        let total = before + grapheme_segments(&self.value[self.cursor..]).count();
+        (mask.to_string().repeat(total), before * mask.len_utf8())
+    }
+
+    fn validation_line(&self, width: usize) -> Option<String> {
+        let (message, style) = match self.validation.as_ref()? {
+            InputValidation::Error(message) => (message, self.theme.error.as_ref()),
+            InputValidation::Warning(message) => (message, self.theme.warning.as_ref()),
+        };
+        let indent = " ".repeat(visible_width(&self.prompt));
+        let line = truncate_to_width(&format!("{indent}{message}"), width, "...", false);
+        Some(match style {
+            Some(style) => style(&line),
+            None => line,
+        })
+    }
+
+    /// Places the cursor after the last character, e.g. after prefilling.
+    pub fn move_cursor_to_end(&mut self) {
+        self.cursor = self.value.len();
+    }
+
     fn clamp_cursor(&mut self) {
         if self.cursor > self.value.len() {
             self.cursor = self.value.len();
         }
-        while self.cursor > 0 && !self.value.is_char_boundary(self.cursor) {
-            self.cursor -= 1;
-        }
+        self.cursor = floor_grapheme_boundary(&self.value, self.cursor);
     }
 
     fn insert_text(&mut self, text: &str) {
+        let text = match self.max_length {
+            Some(max_length) => {
+                let room = max_length.saturating_sub(grapheme_count(&self.value));
+                take_graphemes(text, room)
+            }
+            None => text,
+        };
         if text.is_empty() {
             return;
         }
@@ -105,6 +237,10 @@ impl Input {
         if self.cursor == 0 {
             return;
         }
+        if self.mask.is_some() {
+            self.cursor = 0;
+            return;
+        }
         let text_before_cursor = &self.value[..self.cursor];
         let mut graphemes: Vec<&str> = grapheme_segments(text_before_cursor).collect();
 
@@ -144,6 +280,10 @@ impl Input {
         if self.cursor >= self.value.len() {
             return;
         }
+        if self.mask.is_some() {
+            self.cursor = self.value.len();
+            return;
+        }
         let text_after_cursor = &self.value[self.cursor..];
         let mut iter = grapheme_segments(text_after_cursor);
         let mut next = iter.next();
@@ -181,110 +321,8 @@ impl Input {
             }
         }
     }
-}
-
-impl Component for Input {
-    fn render(&mut self, width: usize) -> Vec<String> {
-        self.clamp_cursor();
-        self.last_cursor_pos = None;
-
-        let prompt = &self.prompt;
-        let available_width = width.saturating_sub(prompt.len());
-        if available_width == 0 {
-            return vec![prompt.to_string()];
-        }
-
-        let (visible_text, cursor_display) = if self.value.len() < available_width {
-            (self.value.clone(), self.cursor)
-        } else {
-            let scroll_width = if self.cursor == self.value.len() {
-                available_width.saturating_sub(1)
-            } else {
-                available_width
-            };
-            let half_width = scroll_width / 2;
-
-            let find_valid_start = |value: &str, mut start: usize| {
-                while start < value.len() && !value.is_char_boundary(start) {
-                    start += 1;
-                }
-                start
-            };
-
-            let find_valid_end = |value: &str, mut end: usize| {
-                while end > 0 && !value.is_char_boundary(end) {
-                    end -= 1;
-                }
-                end
-            };
-
-            if self.cursor < half_width {
-                let end = find_valid_end(&self.value, scroll_width.min(self.value.len()));
-                let text = self.value[..end].to_string();
-                let cursor = self.cursor.min(text.len());
-                (text, cursor)
-            } else if self.cursor > self.value.len().saturating_sub(half_width) {
-                let start =
-                    find_valid_start(&self.value, self.value.len().saturating_sub(scroll_width));
-                let text = self.value[start..].to_string();
-                let cursor = self.cursor.saturating_sub(start);
-                (text, cursor)
-            } else {
-                let start = find_valid_start(&self.value, self.cursor.saturating_sub(half_width));
-                let end = find_valid_end(
-                    &self.value,
-                    start.saturating_add(scroll_width).min(self.value.len()),
-                );
-                let text = self.value[start..end].to_string();
-                let mut cursor = self.cursor.saturating_sub(start);
-                if !text.is_char_boundary(cursor) {
-                    while cursor > 0 && !text.is_char_boundary(cursor) {
-                        cursor -= 1;
-                    }
-                }
-                (text, cursor)
-            }
-        };
-
-        let cursor_display = cursor_display.min(visible_text.len());
-        let before_cursor = &visible_text[..cursor_display];
-        let after_slice = &visible_text[cursor_display..];
-        let mut graphemes = grapheme_segments(after_slice);
-        let cursor_grapheme = graphemes.next();
-
-        self.last_cursor_pos = if self.focused {
-            let col = visible_width(prompt).saturating_add(visible_width(before_cursor));
-            Some(CursorPos { row: 0, col })
-        } else {
-            None
-        };
-
-        let (at_cursor, after_cursor) = if let Some(grapheme) = cursor_grapheme {
-            let after_start = cursor_display + grapheme.len();
-            let after_cursor = &visible_text[after_start..];
-            (grapheme, after_cursor)
-        } else {
-            (" ", "")
-        };
-
-        let cursor_char = format!("\x1b[7m{at_cursor}\x1b[27m");
-        let mut text_with_cursor = String::with_capacity(visible_text.len() + cursor_char.len());
-        text_with_cursor.push_str(before_cursor);
-        text_with_cursor.push_str(&cursor_char);
-        text_with_cursor.push_str(after_cursor);
-
-        let visual_length = visible_width(&text_with_cursor);
-        let padding = " ".repeat(available_width.saturating_sub(visual_length));
-        let line = format!("{prompt}{text_with_cursor}{padding}");
-
-        vec![line]
-    }
 
-    fn cursor_pos(&self) -> Option<CursorPos> {
-        self.last_cursor_pos
-    }
-
-    fn handle_event(&mut self, event: &InputEvent) {
+    fn apply_event(&mut self, event: &InputEvent) {
         self.clamp_cursor();
 
         let key_id = match event {
@@ -292,10 +330,17 @@ impl Component for Input {
                 self.insert_text(text);
                 return;
             }
-            InputEvent::Paste { text, .. } => {
+            InputEvent::Paste { text, .. } | InputEvent::FilesDropped { text, .. } => {
                 self.handle_paste(text);
                 return;
             }
+            InputEvent::Composition {
+                phase: CompositionPhase::Commit,
+                text,
+            } => {
+                self.insert_text(text);
+                return;
+            }
             InputEvent::Key { key_id, .. } => Some(key_id.as_str()),
             _ => return,
         };
@@ -352,10 +397,7 @@ impl Component for Input {
 
         if is_delete_backward {
             if self.cursor > 0 {
-                let before_cursor = &self.value[..self.cursor];
-                let last = grapheme_segments(before_cursor).next_back();
-                let grapheme_len = last.map(|segment| segment.len()).unwrap_or(1);
-                let start = self.cursor.saturating_sub(grapheme_len);
+                let start = prev_grapheme_boundary(&self.value, self.cursor);
                 self.value.replace_range(start..self.cursor, "");
                 self.cursor = start;
             }
@@ -364,10 +406,7 @@ impl Component for Input {
 
         if is_delete_forward {
             if self.cursor < self.value.len() {
-                let after_cursor = &self.value[self.cursor..];
-                let first = grapheme_segments(after_cursor).next();
-                let grapheme_len = first.map(|segment| segment.len()).unwrap_or(1);
-                let end = (self.cursor + grapheme_len).min(self.value.len());
+                let end = next_grapheme_boundary(&self.value, self.cursor);
                 self.value.replace_range(self.cursor..end, "");
             }
             return;
@@ -391,20 +430,14 @@ impl Component for Input {
 
         if is_left {
             if self.cursor > 0 {
-                let before_cursor = &self.value[..self.cursor];
-                let last = grapheme_segments(before_cursor).next_back();
-                let grapheme_len = last.map(|segment| segment.len()).unwrap_or(1);
-                self.cursor = self.cursor.saturating_sub(grapheme_len);
+                self.cursor = prev_grapheme_boundary(&self.value, self.cursor);
             }
             return;
         }
 
         if is_right {
             if self.cursor < self.value.len() {
-                let after_cursor = &self.value[self.cursor..];
-                let first = grapheme_segments(after_cursor).next();
-                let grapheme_len = first.map(|segment| segment.len()).unwrap_or(1);
-                self.cursor = (self.cursor + grapheme_len).min(self.value.len());
+                self.cursor = next_grapheme_boundary(&self.value, self.cursor);
             }
             return;
         }
@@ -428,6 +461,120 @@ impl Component for Input {
             self.move_word_forwards();
         }
     }
+}
+
+impl Component for Input {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        self.clamp_cursor();
+        self.last_cursor_pos = None;
+        let (value, cursor) = self.display_value();
+
+        let prompt = &self.prompt;
+        let available_width = width.saturating_sub(prompt.len());
+        if available_width == 0 {
+            return vec![prompt.to_string()];
+        }
+
+        let (visible_text, cursor_display) = if value.len() < available_width {
+            (value.clone(), cursor)
+        } else {
+            let scroll_width = if cursor == value.len() {
+                available_width.saturating_sub(1)
+            } else {
+                available_width
+            };
+            let half_width = scroll_width / 2;
+
+            let find_valid_start = |value: &str, mut start: usize| {
+                while start < value.len() && !value.is_char_boundary(start) {
+                    start += 1;
+                }
+                start
+            };
+
+            let find_valid_end = |value: &str, mut end: usize| {
+                while end > 0 && !value.is_char_boundary(end) {
+                    end -= 1;
+                }
+                end
+            };
+
+            if cursor < half_width {
+                let end = find_valid_end(&value, scroll_width.min(value.len()));
+                let text = value[..end].to_string();
+                let cursor = cursor.min(text.len());
+                (text, cursor)
+            } else if cursor > value.len().saturating_sub(half_width) {
+                let start = find_valid_start(&value, value.len().saturating_sub(scroll_width));
+                let text = value[start..].to_string();
+                let cursor = cursor.saturating_sub(start);
+                (text, cursor)
+            } else {
+                let start = find_valid_start(&value, cursor.saturating_sub(half_width));
+                let end =
+                    find_valid_end(&value, start.saturating_add(scroll_width).min(value.len()));
+                let text = value[start..end].to_string();
+                let mut cursor = cursor.saturating_sub(start);
+                if !text.is_char_boundary(cursor) {
+                    while cursor > 0 && !text.is_char_boundary(cursor) {
+                        cursor -= 1;
+                    }
+                }
+                (text, cursor)
+            }
+        };
+
+        let cursor_display = cursor_display.min(visible_text.len());
+        let before_cursor = &visible_text[..cursor_display];
+        let after_slice = &visible_text[cursor_display..];
+        let mut graphemes = grapheme_segments(after_slice);
+        let cursor_grapheme = graphemes.next();
+
+        self.last_cursor_pos = if self.focused {
+            let col = visible_width(prompt).saturating_add(visible_width(before_cursor));
+            Some(CursorPos { row: 0, col })
+        } else {
+            None
+        };
+
+        let (at_cursor, after_cursor) = if let Some(grapheme) = cursor_grapheme {
+            let after_start = cursor_display + grapheme.len();
+            let after_cursor = &visible_text[after_start..];
+            (grapheme, after_cursor)
+        } else {
+            (" ", "")
+        };
+
+        let cursor_char = format!("\x1b[7m{at_cursor}\x1b[27m");
+        let mut text_with_cursor = String::with_capacity(visible_text.len() + cursor_char.len());
+        text_with_cursor.push_str(before_cursor);
+        text_with_cursor.push_str(&cursor_char);
+        text_with_cursor.push_str(after_cursor);
+
+        let visual_length = visible_width(&text_with_cursor);
+        let padding = " ".repeat(available_width.saturating_sub(visual_length));
+        let line = format!("{prompt}{text_with_cursor}{padding}");
+
+        let mut lines = vec![line];
+        lines.extend(self.validation_line(width));
+        lines
+    }
+
+    fn cursor_pos(&self) -> Option<CursorPos> {
+        self.last_cursor_pos
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        let previous = self.value.clone();
+        self.apply_event(event);
+        if self.value != previous {
+            self.run_validator();
+            let value = self.value.clone();
+            if let Some(handler) = self.on_change.as_mut() {
+                handler(value);
+            }
+        }
+    }
 
     fn invalidate(&mut self) {
         // No cached state to invalidate.
@@ -450,10 +597,12 @@ impl Focusable for Input {
 
 #[cfg(test)]
 mod tests {
-    use super::Input;
+    use super::{Input, InputTheme, InputValidation};
     use crate::core::component::Component;
     use crate::core::input_event::parse_input_events;
     use crate::default_editor_keybindings_handle;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     fn send(input: &mut Input, data: &str) {
         for event in parse_input_events(data, false) {
@@ -509,4 +658,63 @@ mod tests {
         assert_eq!(lines.len(), 1);
         assert!(lines[0].starts_with("> "));
     }
+
+    #[test]
+    fn input_caps_length_and_deletes_by_grapheme_cluster() {
+        let mut input = Input::new(default_editor_keybindings_handle());
+        input.set_max_length(Some(3));
+        send(&mut input, "e\u{301}🇳🇿👍🏽x");
+        assert_eq!(input.get_value(), "e\u{301}🇳🇿👍🏽");
+
+        send(&mut input, "\x1b[D");
+        send(&mut input, "\x7f");
+        assert_eq!(input.get_value(), "e\u{301}👍🏽");
+        send(&mut input, "\x1b[3~");
+        assert_eq!(input.get_value(), "e\u{301}");
+        send(&mut input, "\x1b[D");
+        send(&mut input, "\x1b[3~");
+        assert_eq!(input.get_value(), "");
+    }
+
+    #[test]
+    fn input_masks_caps_length_and_validates() {
+        let mut input = Input::new(default_editor_keybindings_handle());
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&changes);
+        input.set_on_change(Some(Box::new(move |value| sink.borrow_mut().push(value))));
+        input.set_mask(Some('*'));
+        input.set_max_length(Some(6));
+        input.set_validator(Some(Box::new(|value: &str| {
+            (value.len() < 6).then(|| InputValidation::Error("Too short".to_string()))
+        })));
+        input.set_theme(InputTheme {
+            error: Some(Box::new(|text| format!("<{text}>"))),
+            warning: None,
+        });
+        assert_eq!(
+            input.validation(),
+            Some(&InputValidation::Error("Too short".to_string()))
+        );
+
+        send(&mut input, "s3cr");
+        send(&mut input, "\x1b[200~et-value\x1b[201~");
+        assert_eq!(input.get_value(), "s3cret");
+        assert_eq!(*changes.borrow(), vec!["s3cr", "s3cret"]);
+        send(&mut input, "x");
+        assert_eq!(changes.borrow().len(), 2);
+
+        let lines = input.render(12);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("> ******"), "{:?}", lines[0]);
+        assert!(!lines[0].contains("s3cret"));
+
+        send(&mut input, "\x17");
+        assert_eq!(input.get_value(), "");
+        let lines = input.render(12);
+        assert_eq!(lines[1], "<  Too short>");
+
+        input.set_validator(None);
+        input.set_validation(Some(InputValidation::Warning("Caps lock".to_string())));
+        assert_eq!(input.render(12)[1], "  Caps lock");
+    }
 }
@@ -1,18 +1,88 @@
 //! Loader widget.
-
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+//!
+//! The spinner animation is chosen with [`LoaderOptions::style`]: one of the
+//! built-in [`SpinnerStyle`]s or a custom frame sequence. Each style carries a
+//! default frame interval, which the spinner thread reads on every tick, so
+//! switching styles on a running loader takes effect at the next frame.
+//!
+//! The frame shown is sampled from an [`AnimationClock`] when rendering; the
+//! spinner thread only requests renders at frame boundaries. A late render
+//! shows the frame that is current by then rather than the next one in line.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
+use crate::core::animation::AnimationClock;
 use crate::core::component::Component;
 use crate::runtime::tui::{Command, RuntimeHandle};
 use crate::widgets::text::Text;
 
 type RenderRequester = Arc<dyn Fn() + Send + Sync>;
 
-const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-const SPINNER_INTERVAL_MS: u64 = 80;
+const DOTS_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const LINE_FRAMES: [&str; 4] = ["-", "\\", "|", "/"];
+const ARC_FRAMES: [&str; 6] = ["◜", "◠", "◝", "◞", "◡", "◟"];
+const BOUNCING_BAR_FRAMES: [&str; 14] = [
+    "[    ]", "[=   ]", "[==  ]", "[=== ]", "[ ===]", "[  ==]", "[   =]", "[    ]", "[   =]",
+    "[  ==]", "[ ===]", "[====]", "[=== ]", "[==  ]",
+];
+const BRAILLE_FRAMES: [&str; 8] = ["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"];
+
+/// Spinner animation drawn by [`Loader`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SpinnerStyle {
+    /// `⠋ ⠙ ⠹ …`, the classic braille dots spinner.
+    #[default]
+    Dots,
+    /// `- \ | /`, plain ASCII for terminals without braille glyphs.
+    Line,
+    /// `◜ ◠ ◝ ◞ ◡ ◟`.
+    Arc,
+    /// A `[=== ]` bar bouncing between brackets.
+    BouncingBar,
+    /// `⣾ ⣽ ⣻ …`, a dense rotating braille cell.
+    Braille,
+    /// Caller-supplied frames shown every `interval`. Empty frames render a
+    /// blank spinner.
+    Custom {
+        frames: Vec<String>,
+        interval: Duration,
+    },
+}
+
+impl SpinnerStyle {
+    pub fn frames(&self) -> Vec<String> {
+        let frames: &[&str] = match self {
+            Self::Dots => &DOTS_FRAMES,
+            Self::Line => &LINE_FRAMES,
+            Self::Arc => &ARC_FRAMES,
+            Self::BouncingBar => &BOUNCING_BAR_FRAMES,
+            Self::Braille => &BRAILLE_FRAMES,
+            Self::Custom { frames, .. } => return frames.clone(),
+        };
+        frames.iter().map(|frame| frame.to_string()).collect()
+    }
+
+    /// Default time between frames for this style.
+    pub fn interval(&self) -> Duration {
+        match self {
+            Self::Dots | Self::Braille | Self::BouncingBar => Duration::from_millis(80),
+            Self::Line => Duration::from_millis(130),
+            Self::Arc => Duration::from_millis(100),
+            Self::Custom { interval, .. } => *interval,
+        }
+    }
+}
+
+/// Construction options for [`Loader::with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct LoaderOptions {
+    pub style: SpinnerStyle,
+    /// Overrides the style's default frame interval.
+    pub interval: Option<Duration>,
+}
 
 trait Sleeper: Send + Sync {
     fn sleep(&self, duration: Duration);
@@ -60,7 +130,13 @@ pub struct Loader {
     message: String,
     text: Text,
     render_requester: Option<RenderRequester>,
-    current_frame: Arc<AtomicUsize>,
+    frames: Vec<String>,
+    clock: AnimationClock,
+    /// Clock time of the first frame in nanoseconds, shared with the spinner
+    /// thread.
+    started_nanos: Arc<AtomicU64>,
+    /// Frame interval in milliseconds, shared with the spinner thread.
+    interval_ms: Arc<AtomicU64>,
     stop_flag: Arc<AtomicBool>,
     sleeper: Arc<dyn Sleeper>,
     thread: Option<JoinHandle<()>>,
@@ -79,6 +155,18 @@ impl Loader {
         Self::with_requester(Some(requester), spinner_color_fn, message_color_fn, message)
     }
 
+    pub fn with_options(
+        runtime_handle: RuntimeHandle,
+        spinner_color_fn: Box<dyn Fn(&str) -> String>,
+        message_color_fn: Box<dyn Fn(&str) -> String>,
+        message: Option<String>,
+        options: LoaderOptions,
+    ) -> Self {
+        let mut loader = Self::new(runtime_handle, spinner_color_fn, message_color_fn, message);
+        loader.apply_options(options);
+        loader
+    }
+
     pub(crate) fn with_requester(
         render_requester: Option<RenderRequester>,
         spinner_color_fn: Box<dyn Fn(&str) -> String>,
@@ -88,6 +176,7 @@ impl Loader {
         Self::with_requester_and_sleeper(
             render_requester,
             Arc::new(RealSleeper::default()),
+            AnimationClock::system(),
             spinner_color_fn,
             message_color_fn,
             message,
@@ -97,6 +186,7 @@ impl Loader {
     fn with_requester_and_sleeper(
         render_requester: Option<RenderRequester>,
         sleeper: Arc<dyn Sleeper>,
+        clock: AnimationClock,
         spinner_color_fn: Box<dyn Fn(&str) -> String>,
         message_color_fn: Box<dyn Fn(&str) -> String>,
         message: Option<String>,
@@ -107,7 +197,12 @@ impl Loader {
             message: message.unwrap_or_else(|| "Loading...".to_string()),
             text: Text::with_padding("", 1, 0),
             render_requester,
-            current_frame: Arc::new(AtomicUsize::new(0)),
+            frames: SpinnerStyle::Dots.frames(),
+            clock,
+            started_nanos: Arc::new(AtomicU64::new(0)),
+            interval_ms: Arc::new(AtomicU64::new(
+                SpinnerStyle::Dots.interval().as_millis() as u64
+            )),
             stop_flag: Arc::new(AtomicBool::new(false)),
             sleeper,
             thread: None,
@@ -122,12 +217,14 @@ impl Loader {
         }
 
         self.stop_flag.store(false, Ordering::SeqCst);
-        self.current_frame.store(0, Ordering::SeqCst);
+        self.restart_animation();
         self.update_text();
         self.request_render();
 
         let stop_flag = Arc::clone(&self.stop_flag);
-        let current_frame = Arc::clone(&self.current_frame);
+        let clock = self.clock.clone();
+        let started_nanos = Arc::clone(&self.started_nanos);
+        let interval_ms = Arc::clone(&self.interval_ms);
         let render_requester = self.render_requester.clone();
         let sleeper = Arc::clone(&self.sleeper);
 
@@ -136,13 +233,14 @@ impl Loader {
                 break;
             }
 
-            sleeper.sleep(Duration::from_millis(SPINNER_INTERVAL_MS));
+            let start = Duration::from_nanos(started_nanos.load(Ordering::SeqCst));
+            let interval = Duration::from_millis(interval_ms.load(Ordering::SeqCst));
+            sleeper.sleep(clock.until_next_frame(start, interval));
 
             if stop_flag.load(Ordering::SeqCst) {
                 break;
             }
 
-            current_frame.fetch_add(1, Ordering::SeqCst);
             if let Some(request) = render_requester.as_ref() {
                 request();
             }
@@ -160,21 +258,78 @@ impl Loader {
         }
     }
 
+    /// Samples frames from `clock` instead of the system clock; the spinner
+    /// restarts from its first frame.
+    pub fn set_animation_clock(&mut self, clock: AnimationClock) {
+        let running = self.is_running();
+        self.stop();
+        self.clock = clock;
+        self.restart_animation();
+        if running {
+            self.start();
+        }
+    }
+
+    pub(crate) fn animation_clock(&self) -> &AnimationClock {
+        &self.clock
+    }
+
+    /// Switches the animation; a running spinner restarts from the first
+    /// frame at the new style's interval.
+    pub fn set_style(&mut self, style: SpinnerStyle) {
+        self.apply_options(LoaderOptions {
+            style,
+            interval: None,
+        });
+        self.update_text();
+        self.request_render();
+    }
+
+    pub(crate) fn apply_options(&mut self, options: LoaderOptions) {
+        let interval = options
+            .interval
+            .unwrap_or_else(|| options.style.interval())
+            .max(Duration::from_millis(1));
+        self.frames = options.style.frames();
+        self.restart_animation();
+        self.interval_ms
+            .store(interval.as_millis() as u64, Ordering::SeqCst);
+    }
+
     pub fn set_message(&mut self, message: impl Into<String>) {
         self.message = message.into();
         self.update_text();
         self.request_render();
     }
 
+    /// Replaces the message without requesting a render, for wrappers that
+    /// recompose the message while rendering.
+    pub(crate) fn replace_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+
+    fn restart_animation(&self) {
+        let now = u64::try_from(self.clock.now().as_nanos()).unwrap_or(u64::MAX);
+        self.started_nanos.store(now, Ordering::SeqCst);
+    }
+
     fn update_text(&mut self) {
-        let idx = self.current_frame.load(Ordering::SeqCst) % SPINNER_FRAMES.len();
-        let frame = SPINNER_FRAMES[idx];
+        let start = Duration::from_nanos(self.started_nanos.load(Ordering::SeqCst));
+        let interval = Duration::from_millis(self.interval_ms.load(Ordering::SeqCst));
+        let frame = match self.frames.len() {
+            0 => "",
+            len => &self.frames[self.clock.frame(start, interval, len)],
+        };
         let spinner = (self.spinner_color_fn)(frame);
         let message = (self.message_color_fn)(&self.message);
         self.text.set_text(format!("{spinner} {message}"));
     }
 
-    fn request_render(&self) {
+    pub(crate) fn request_render(&self) {
         if let Some(requester) = self.render_requester.as_ref() {
             requester();
         }
@@ -203,7 +358,8 @@ impl Component for Loader {
 
 #[cfg(test)]
 mod tests {
-    use super::{Loader, Sleeper};
+    use super::{Loader, LoaderOptions, Sleeper, SpinnerStyle};
+    use crate::core::animation::AnimationClock;
     use crate::core::component::Component;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::{Arc, Condvar, Mutex};
@@ -212,6 +368,7 @@ mod tests {
     #[derive(Debug, Default)]
     struct TestSleepState {
         wake_tokens: usize,
+        durations: Vec<Duration>,
     }
 
     #[derive(Debug, Default)]
@@ -221,8 +378,9 @@ mod tests {
     }
 
     impl Sleeper for TestSleeper {
-        fn sleep(&self, _duration: Duration) {
+        fn sleep(&self, duration: Duration) {
             let mut state = self.state.lock().expect("test sleeper state poisoned");
+            state.durations.push(duration);
             while state.wake_tokens == 0 {
                 state = self.cvar.wait(state).expect("test sleeper state poisoned");
             }
@@ -247,9 +405,11 @@ mod tests {
         });
 
         let sleeper: Arc<dyn Sleeper> = Arc::new(TestSleeper::default());
+        let clock = AnimationClock::manual();
         let mut loader = Loader::with_requester_and_sleeper(
             Some(render_requester),
             Arc::clone(&sleeper),
+            clock.clone(),
             Box::new(|text| text.to_string()),
             Box::new(|text| text.to_string()),
             Some("Working".to_string()),
@@ -262,6 +422,7 @@ mod tests {
 
         let before = loader.render(20);
 
+        clock.advance(Duration::from_millis(80));
         sleeper.wake();
         rx.recv_timeout(Duration::from_secs(1))
             .expect("tick render request not observed");
@@ -272,4 +433,90 @@ mod tests {
 
         loader.stop();
     }
+
+    #[test]
+    fn loader_frame_follows_clock_between_ticks() {
+        let clock = AnimationClock::manual();
+        let mut loader = Loader::with_requester_and_sleeper(
+            None,
+            Arc::new(TestSleeper::default()),
+            clock.clone(),
+            Box::new(|text| text.to_string()),
+            Box::new(|text| text.to_string()),
+            Some("Working".to_string()),
+        );
+        loader.apply_options(LoaderOptions {
+            style: SpinnerStyle::Line,
+            interval: None,
+        });
+
+        // No tick ran; a late render still shows the frame due by now.
+        clock.advance(Duration::from_millis(270));
+        assert_eq!(loader.render(20)[1].trim_end(), " | Working");
+
+        loader.stop();
+    }
+
+    /// Waits until the spinner thread is sleeping for `expected`.
+    fn wait_for_sleep(sleeper: &TestSleeper, expected: Duration) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(1);
+        loop {
+            let last = sleeper.state.lock().unwrap().durations.last().copied();
+            if last == Some(expected) {
+                return;
+            }
+            assert!(
+                std::time::Instant::now() < deadline,
+                "last sleep {last:?}, expected {expected:?}"
+            );
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn loader_styles_set_frames_and_tick_interval() {
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let render_requester = Arc::new(move || {
+            let _ = tx.send(());
+        });
+        let sleeper = Arc::new(TestSleeper::default());
+        let clock = AnimationClock::manual();
+        let mut loader = Loader::with_requester_and_sleeper(
+            Some(render_requester),
+            Arc::clone(&sleeper) as Arc<dyn Sleeper>,
+            clock.clone(),
+            Box::new(|text| text.to_string()),
+            Box::new(|text| text.to_string()),
+            Some("Working".to_string()),
+        );
+        loader.apply_options(LoaderOptions {
+            style: SpinnerStyle::Line,
+            interval: None,
+        });
+        assert_eq!(loader.render(20)[1].trim_end(), " - Working");
+
+        for _ in rx.try_iter() {}
+        clock.advance(Duration::from_millis(130));
+        sleeper.wake();
+        rx.recv_timeout(Duration::from_secs(1))
+            .expect("tick render request not observed");
+        assert_eq!(loader.render(20)[1].trim_end(), " \\ Working");
+        wait_for_sleep(&sleeper, Duration::from_millis(130));
+
+        loader.set_style(SpinnerStyle::Custom {
+            frames: vec!["a".to_string(), "b".to_string()],
+            interval: Duration::from_millis(250),
+        });
+        assert_eq!(loader.render(20)[1].trim_end(), " a Working");
+        // set_style requests a render itself; wait for the tick's request.
+        for _ in rx.try_iter() {}
+        clock.advance(Duration::from_millis(250));
+        sleeper.wake();
+        rx.recv_timeout(Duration::from_secs(1))
+            .expect("tick render request not observed");
+        assert_eq!(loader.render(20)[1].trim_end(), " b Working");
+        wait_for_sleep(&sleeper, Duration::from_millis(250));
+
+        loader.stop();
+    }
 }
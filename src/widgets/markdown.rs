@@ -5,6 +5,7 @@ use crate::core::terminal_image::is_image_line;
 use crate::core::text::slice::wrap_text_with_ansi;
 use crate::core::text::utils::apply_background_to_line;
 use crate::core::text::width::visible_width;
+use crate::Frame;
 
 use markdown::{mdast, to_mdast, ParseOptions};
 use once_cell::sync::Lazy;
@@ -721,6 +722,14 @@ impl Markdown {
     }
 }
 
+impl Frame {
+    /// Renders `markdown` at `width` with no padding, for hosts that want a
+    /// one-off frame rather than a retained [`Markdown`] component.
+    pub fn from_markdown_lines(markdown: &str, width: usize, theme: MarkdownTheme) -> Frame {
+        Frame::from(Markdown::new(markdown, 0, 0, theme, None).render(width))
+    }
+}
+
 impl Component for Markdown {
     fn render(&mut self, width: usize) -> Vec<String> {
         if let Some(cached) = self.cached_lines.as_ref() {
@@ -1080,6 +1089,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn frame_from_markdown_lines_matches_widget_render() {
+        let frame = crate::Frame::from_markdown_lines("# Title\nbody", 40, theme());
+        let expected = Markdown::new("# Title\nbody", 0, 0, theme(), None).render(40);
+        assert_eq!(frame.into_strings(), expected);
+    }
+
     #[test]
     fn headings_apply_styles_and_spacing() {
         let mut markdown = Markdown::new("# Title\nParagraph", 0, 0, theme(), None);
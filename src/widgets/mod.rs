@@ -1,8 +1,11 @@
 //! Optional higher-level widgets.
 
+pub mod autocomplete_dropdown;
 pub mod r#box;
 pub mod cancellable_loader;
+pub mod code_cell;
 pub mod container;
+pub mod divider;
 pub mod editor;
 pub mod image;
 pub mod input;
@@ -12,23 +15,31 @@ pub mod select_list;
 pub mod settings_list;
 pub mod spacer;
 pub mod text;
+pub mod tree_view;
 pub mod truncated_text;
 
+pub use autocomplete_dropdown::AutocompleteDropdown;
 pub use cancellable_loader::{AbortSignal, CancellableLoader};
-pub use container::Container;
+pub use code_cell::{CodeCell, CodeCellStatus, CodeCellStyleFn, CodeCellTheme};
+pub use container::{Container, ContentWidth};
+pub use divider::{Divider, DividerAlign, DividerStyleFn, DividerTheme};
 pub use editor::{
-    Editor, EditorHeightMode, EditorOptions, EditorPasteMode, EditorTheme, TextChunk,
+    Editor, EditorCounts, EditorEnterOptions, EditorHeightMode, EditorOptions, EditorPasteMode,
+    EditorTheme, TextChunk,
 };
 pub use image::{Image, ImageOptions, ImageTheme};
-pub use input::Input;
-pub use loader::Loader;
+pub use input::{Input, InputStyleFn, InputTheme, InputValidation, InputValidator};
+pub use loader::{Loader, LoaderOptions, SpinnerStyle};
 pub use markdown::{
     highlight_markdown_code_ansi, prewarm_markdown_highlighting, DefaultTextStyle, Markdown,
     MarkdownTheme,
 };
-pub use r#box::Box;
-pub use select_list::{SelectItem, SelectList, SelectListTheme};
-pub use settings_list::{SettingItem, SettingsList, SettingsListOptions, SettingsListTheme};
+pub use r#box::{Box, BoxBorderSides, BoxBorderStyle, BoxTitleAlign};
+pub use select_list::{SelectItem, SelectItemProvider, SelectList, SelectListTheme};
+pub use settings_list::{
+    SettingChangeFn, SettingItem, SettingKind, SettingsList, SettingsListOptions, SettingsListTheme,
+};
 pub use spacer::Spacer;
 pub use text::Text;
+pub use tree_view::{TreeChildLoader, TreeNode, TreeView, TreeViewTheme};
 pub use truncated_text::TruncatedText;
@@ -1,11 +1,21 @@
 //! SelectList widget.
-
+//!
+//! Items come either from an owned `Vec` (filterable) or, for very large
+//! lists, from a provider callback plus a total count. Provider-backed lists
+//! fetch fixed-size chunks on demand, keep only a few chunks cached, and
+//! prefetch one screen ahead in the scroll direction so rendering stays
+//! proportional to `max_visible` rather than to the total count.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
 use std::sync::Arc;
 
 use crate::core::component::Component;
 use crate::core::input_event::InputEvent;
 use crate::core::keybindings::{EditorAction, EditorKeybindingsHandle};
+use crate::core::text::paths::truncate_path_middle;
 use crate::core::text::utils::truncate_to_width;
+use crate::core::text::width::visible_width;
 
 fn normalize_to_single_line(text: &str) -> String {
     let mut out = String::with_capacity(text.len());
@@ -54,13 +64,107 @@ pub struct SelectListTheme {
     pub no_match: Arc<dyn Fn(&str) -> String>,
 }
 
+/// Returns the items for a half-open index range of a provider-backed list.
+///
+/// The range never extends past the total count. Returning fewer items than
+/// requested is allowed; missing rows are left blank until refetched.
+pub type SelectItemProvider = Box<dyn FnMut(Range<usize>) -> Vec<SelectItem>>;
+
+/// Rows fetched per provider call.
+const LAZY_CHUNK_ROWS: usize = 128;
+/// Chunks kept in memory; the ones farthest from the viewport are evicted first.
+const LAZY_MAX_CACHED_CHUNKS: usize = 8;
+
+struct LazyItems {
+    provider: SelectItemProvider,
+    total: usize,
+    chunks: BTreeMap<usize, Vec<SelectItem>>,
+}
+
+impl LazyItems {
+    fn get(&self, index: usize) -> Option<&SelectItem> {
+        if index >= self.total {
+            return None;
+        }
+        self.chunks
+            .get(&(index / LAZY_CHUNK_ROWS))
+            .and_then(|chunk| chunk.get(index % LAZY_CHUNK_ROWS))
+    }
+
+    /// Loads every chunk overlapping `visible` plus the chunk holding
+    /// `prefetch`, then evicts the chunks farthest from `visible`.
+    fn ensure(&mut self, visible: Range<usize>, prefetch: Option<usize>) {
+        if self.total == 0 || visible.is_empty() {
+            return;
+        }
+        let first = visible.start / LAZY_CHUNK_ROWS;
+        let last = (visible.end - 1) / LAZY_CHUNK_ROWS;
+        for chunk in first..=last {
+            self.load_chunk(chunk);
+        }
+        if let Some(index) = prefetch.filter(|index| *index < self.total) {
+            self.load_chunk(index / LAZY_CHUNK_ROWS);
+        }
+
+        while self.chunks.len() > LAZY_MAX_CACHED_CHUNKS {
+            let distance = |chunk: usize| {
+                if chunk < first {
+                    first - chunk
+                } else {
+                    chunk.saturating_sub(last)
+                }
+            };
+            let Some(farthest) = self.chunks.keys().copied().max_by_key(|c| distance(*c)) else {
+                break;
+            };
+            self.chunks.remove(&farthest);
+        }
+    }
+
+    fn load_chunk(&mut self, chunk: usize) {
+        if self.chunks.contains_key(&chunk) {
+            return;
+        }
+        let start = chunk * LAZY_CHUNK_ROWS;
+        let end = (start + LAZY_CHUNK_ROWS).min(self.total);
+        let mut items = (self.provider)(start..end);
+        items.truncate(end - start);
+        self.chunks.insert(chunk, items);
+    }
+}
+
+enum ItemSource {
+    Owned {
+        items: Vec<SelectItem>,
+        filtered: Vec<SelectItem>,
+    },
+    Lazy(LazyItems),
+}
+
+impl ItemSource {
+    fn len(&self) -> usize {
+        match self {
+            Self::Owned { filtered, .. } => filtered.len(),
+            Self::Lazy(lazy) => lazy.total,
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<&SelectItem> {
+        match self {
+            Self::Owned { filtered, .. } => filtered.get(index),
+            Self::Lazy(lazy) => lazy.get(index),
+        }
+    }
+}
+
 pub struct SelectList {
-    items: Vec<SelectItem>,
-    filtered_items: Vec<SelectItem>,
+    source: ItemSource,
     selected_index: usize,
+    scrolling_up: bool,
     max_visible: usize,
     theme: SelectListTheme,
     keybindings: EditorKeybindingsHandle,
+    path_descriptions: bool,
     on_select: Option<Box<dyn FnMut(SelectItem)>>,
     on_cancel: Option<Box<dyn FnMut()>>,
     on_selection_change: Option<Box<dyn FnMut(SelectItem)>>,
@@ -74,22 +178,72 @@ impl SelectList {
         keybindings: EditorKeybindingsHandle,
     ) -> Self {
         Self {
-            filtered_items: items.clone(),
-            items,
+            source: ItemSource::Owned {
+                filtered: items.clone(),
+                items,
+            },
             selected_index: 0,
+            scrolling_up: false,
             max_visible,
             theme,
             keybindings,
+            path_descriptions: false,
             on_select: None,
             on_cancel: None,
             on_selection_change: None,
         }
     }
 
+    /// Creates a virtualized list over `total` items served by `provider`.
+    ///
+    /// Only the chunks around the visible window are fetched. The first window
+    /// is loaded immediately so [`Self::get_selected_item`] works before the
+    /// first render.
+    pub fn with_provider(
+        total: usize,
+        provider: SelectItemProvider,
+        max_visible: usize,
+        theme: SelectListTheme,
+        keybindings: EditorKeybindingsHandle,
+    ) -> Self {
+        let mut list = Self {
+            source: ItemSource::Lazy(LazyItems {
+                provider,
+                total,
+                chunks: BTreeMap::new(),
+            }),
+            selected_index: 0,
+            scrolling_up: false,
+            max_visible,
+            theme,
+            keybindings,
+            path_descriptions: false,
+            on_select: None,
+            on_cancel: None,
+            on_selection_change: None,
+        };
+        list.load_visible();
+        list
+    }
+
+    /// Number of items after filtering (or the provider's total count).
+    pub fn len(&self) -> usize {
+        self.source.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source.len() == 0
+    }
+
+    /// Prefix-filters owned items by value. Provider-backed lists own their
+    /// data, so filtering there is a no-op; narrow the provider and call
+    /// [`Self::set_total_count`] instead.
     pub fn set_filter(&mut self, filter: &str) {
+        let ItemSource::Owned { items, filtered } = &mut self.source else {
+            return;
+        };
         let filter = filter.to_lowercase();
-        self.filtered_items = self
-            .items
+        *filtered = items
             .iter()
             .filter(|item| item.value.to_lowercase().starts_with(&filter))
             .cloned()
@@ -97,12 +251,34 @@ impl SelectList {
         self.selected_index = 0;
     }
 
+    /// Updates the total count of a provider-backed list (for example when a
+    /// log grows) and drops cached chunks so changed rows are refetched.
+    /// The selection is clamped to the new count. No-op for owned lists.
+    pub fn set_total_count(&mut self, total: usize) {
+        let ItemSource::Lazy(lazy) = &mut self.source else {
+            return;
+        };
+        lazy.total = total;
+        lazy.chunks.clear();
+        self.selected_index = self.selected_index.min(total.saturating_sub(1));
+        self.load_visible();
+    }
+
     pub fn set_selected_index(&mut self, index: usize) {
-        if self.filtered_items.is_empty() {
+        let len = self.source.len();
+        if len == 0 {
             self.selected_index = 0;
         } else {
-            self.selected_index = index.min(self.filtered_items.len() - 1);
+            self.scrolling_up = index < self.selected_index;
+            self.selected_index = index.min(len - 1);
         }
+        self.load_visible();
+    }
+
+    /// Treats descriptions as paths: they are truncated in the middle so
+    /// the file name stays visible, instead of at the end.
+    pub fn set_path_descriptions(&mut self, path_descriptions: bool) {
+        self.path_descriptions = path_descriptions;
     }
 
     pub fn set_on_select(&mut self, handler: Option<Box<dyn FnMut(SelectItem)>>) {
@@ -118,11 +294,45 @@ impl SelectList {
     }
 
     pub fn get_selected_item(&self) -> Option<&SelectItem> {
-        self.filtered_items.get(self.selected_index)
+        self.source.get(self.selected_index)
+    }
+
+    /// Index range rendered for the current selection, keeping the selection
+    /// centred where possible.
+    fn visible_range(&self) -> Range<usize> {
+        let len = self.source.len();
+        if len == 0 {
+            return 0..0;
+        }
+        let max_visible = self.max_visible.max(1).min(len);
+        let half = max_visible / 2;
+        let start = if len <= max_visible {
+            0
+        } else {
+            self.selected_index
+                .saturating_sub(half)
+                .min(len - max_visible)
+        };
+        start..(start + max_visible).min(len)
+    }
+
+    /// Ensures the visible window of a provider-backed list is cached and
+    /// prefetches one screen further in the current scroll direction.
+    fn load_visible(&mut self) {
+        let visible = self.visible_range();
+        let page = self.max_visible.max(1);
+        let prefetch = if self.scrolling_up {
+            visible.start.checked_sub(page)
+        } else {
+            Some(visible.end + page - 1)
+        };
+        if let ItemSource::Lazy(lazy) = &mut self.source {
+            lazy.ensure(visible, prefetch);
+        }
     }
 
     fn notify_selection_change(&mut self) {
-        let Some(item) = self.filtered_items.get(self.selected_index) else {
+        let Some(item) = self.source.get(self.selected_index) else {
             return;
         };
         if let Some(handler) = self.on_selection_change.as_mut() {
@@ -130,6 +340,14 @@ impl SelectList {
         }
     }
 
+    fn truncate_description(&self, description: &str, max_width: usize) -> String {
+        if self.path_descriptions {
+            truncate_path_middle(description, max_width)
+        } else {
+            truncate_to_width(description, max_width, "", false)
+        }
+    }
+
     fn render_selected(
         &self,
         width: usize,
@@ -147,12 +365,13 @@ impl SelectList {
             if width > 40 {
                 let max_value_width = 30.min(width.saturating_sub(prefix_width + 4));
                 let truncated_value = truncate_to_width(display_value, max_value_width, "", false);
-                let spacing = " ".repeat(1.max(32usize.saturating_sub(truncated_value.len())));
+                let value_width = visible_width(&truncated_value);
+                let spacing = " ".repeat(1.max(32usize.saturating_sub(value_width)));
 
-                let description_start = prefix_width + truncated_value.len() + spacing.len();
+                let description_start = prefix_width + value_width + spacing.len();
                 let remaining_width = width.saturating_sub(description_start + 2);
                 if remaining_width > 10 {
-                    let truncated_desc = truncate_to_width(description, remaining_width, "", false);
+                    let truncated_desc = self.truncate_description(description, remaining_width);
                     return (self.theme.selected_text)(&format!(
                         "→ {truncated_value}{spacing}{truncated_desc}"
                     ));
@@ -182,12 +401,13 @@ impl SelectList {
             if width > 40 {
                 let max_value_width = 30.min(width.saturating_sub(prefix.len() + 4));
                 let truncated_value = truncate_to_width(display_value, max_value_width, "", false);
-                let spacing = " ".repeat(1.max(32usize.saturating_sub(truncated_value.len())));
+                let value_width = visible_width(&truncated_value);
+                let spacing = " ".repeat(1.max(32usize.saturating_sub(value_width)));
 
-                let description_start = prefix.len() + truncated_value.len() + spacing.len();
+                let description_start = prefix.len() + value_width + spacing.len();
                 let remaining_width = width.saturating_sub(description_start + 2);
                 if remaining_width > 10 {
-                    let truncated_desc = truncate_to_width(description, remaining_width, "", false);
+                    let truncated_desc = self.truncate_description(description, remaining_width);
                     let desc_text = (self.theme.description)(&format!("{spacing}{truncated_desc}"));
                     return format!("{prefix}{truncated_value}{desc_text}");
                 }
@@ -204,24 +424,20 @@ impl Component for SelectList {
     fn render(&mut self, width: usize) -> Vec<String> {
         let mut lines = Vec::new();
 
-        if self.filtered_items.is_empty() {
+        let len = self.source.len();
+        if len == 0 {
             lines.push((self.theme.no_match)("  No matching commands"));
             return lines;
         }
 
-        let max_visible = self.max_visible.max(1).min(self.filtered_items.len());
-        let half = max_visible / 2;
-        let start_index = if self.filtered_items.len() <= max_visible {
-            0
-        } else {
-            let candidate = self.selected_index.saturating_sub(half);
-            let max_start = self.filtered_items.len() - max_visible;
-            candidate.min(max_start)
-        };
-        let end_index = (start_index + max_visible).min(self.filtered_items.len());
+        self.load_visible();
+        let Range {
+            start: start_index,
+            end: end_index,
+        } = self.visible_range();
 
         for idx in start_index..end_index {
-            let Some(item) = self.filtered_items.get(idx) else {
+            let Some(item) = self.source.get(idx) else {
                 continue;
             };
             let description = item.description.as_deref().and_then(|desc| {
@@ -242,12 +458,8 @@ impl Component for SelectList {
             lines.push(line);
         }
 
-        if start_index > 0 || end_index < self.filtered_items.len() {
-            let scroll_text = format!(
-                "  ({}/{})",
-                self.selected_index + 1,
-                self.filtered_items.len()
-            );
+        if start_index > 0 || end_index < len {
+            let scroll_text = format!("  ({}/{})", self.selected_index + 1, len);
             let truncated = truncate_to_width(&scroll_text, width.saturating_sub(2), "", false);
             lines.push((self.theme.scroll_info)(&truncated));
         }
@@ -288,31 +500,36 @@ impl Component for SelectList {
             }
         };
 
+        let len = self.source.len();
         match action {
             Action::Up => {
-                if self.filtered_items.is_empty() {
+                if len == 0 {
                     return;
                 }
                 if self.selected_index == 0 {
-                    self.selected_index = self.filtered_items.len() - 1;
+                    self.selected_index = len - 1;
                 } else {
                     self.selected_index -= 1;
                 }
+                self.scrolling_up = true;
+                self.load_visible();
                 self.notify_selection_change();
             }
             Action::Down => {
-                if self.filtered_items.is_empty() {
+                if len == 0 {
                     return;
                 }
-                if self.selected_index == self.filtered_items.len() - 1 {
+                if self.selected_index == len - 1 {
                     self.selected_index = 0;
                 } else {
                     self.selected_index += 1;
                 }
+                self.scrolling_up = false;
+                self.load_visible();
                 self.notify_selection_change();
             }
             Action::Confirm => {
-                if let Some(item) = self.filtered_items.get(self.selected_index) {
+                if let Some(item) = self.source.get(self.selected_index) {
                     if let Some(handler) = self.on_select.as_mut() {
                         handler(item.clone());
                     }
@@ -328,13 +545,14 @@ impl Component for SelectList {
     }
 
     fn invalidate(&mut self) {
-        // No cached state to invalidate.
+        // Provider chunks describe data, not rendering; `set_total_count`
+        // is the explicit refetch path.
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{SelectItem, SelectList, SelectListTheme};
+    use super::{SelectItem, SelectList, SelectListTheme, LAZY_CHUNK_ROWS};
     use crate::core::component::Component;
     use crate::core::input_event::parse_input_events;
     use crate::default_editor_keybindings_handle;
@@ -358,6 +576,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn path_descriptions_are_truncated_in_the_middle() {
+        let path = "crates/coding_agent/src/runtime/session.rs".to_string();
+        let items = vec![SelectItem::new("session.rs", "session.rs", Some(path))];
+        let mut list = SelectList::new(items, 5, theme(), default_editor_keybindings_handle());
+        assert_eq!(
+            list.render(60),
+            vec!["→ session.rs                      crates/coding_agent/src/\x1b[0m"]
+        );
+
+        list.set_path_descriptions(true);
+        assert_eq!(
+            list.render(60),
+            vec!["→ session.rs                      crates/codi…e/session.rs"]
+        );
+    }
+
     #[test]
     fn select_list_navigates_and_wraps() {
         let items = vec![
@@ -417,4 +652,61 @@ mod tests {
         send(&mut list, "\x1b");
         assert!(*cancelled.borrow());
     }
+
+    #[test]
+    fn provider_lists_fetch_only_the_window_and_prefetch_ahead() {
+        let requests: Rc<RefCell<Vec<std::ops::Range<usize>>>> = Rc::new(RefCell::new(Vec::new()));
+        let requests_ref = requests.clone();
+        let mut list = SelectList::with_provider(
+            50_000,
+            Box::new(move |range| {
+                requests_ref.borrow_mut().push(range.clone());
+                range
+                    .map(|i| SelectItem::new(format!("line {i}"), "", None))
+                    .collect()
+            }),
+            5,
+            theme(),
+            default_editor_keybindings_handle(),
+        );
+
+        assert_eq!(list.len(), 50_000);
+        assert_eq!(list.get_selected_item().unwrap().value, "line 0");
+        assert_eq!(
+            list.render(40),
+            vec![
+                "→ line 0",
+                "  line 1",
+                "  line 2",
+                "  line 3",
+                "  line 4",
+                "  (1/50000)"
+            ]
+        );
+        assert_eq!(requests.borrow().len(), 1);
+        assert_eq!(requests.borrow()[0], 0..LAZY_CHUNK_ROWS);
+
+        // Scrolling down prefetches the next chunk one screen before it is visible.
+        for _ in 0..LAZY_CHUNK_ROWS - 7 {
+            send(&mut list, "\x1b[B");
+        }
+        assert_eq!(
+            requests.borrow().last(),
+            Some(&(LAZY_CHUNK_ROWS..2 * LAZY_CHUNK_ROWS))
+        );
+        assert_eq!(requests.borrow().len(), 2);
+
+        // Wrapping to the end fetches the tail chunk without touching the middle.
+        list.set_selected_index(0);
+        send(&mut list, "\x1b[A");
+        assert_eq!(list.get_selected_item().unwrap().value, "line 49999");
+        assert_eq!(list.render(40).last().unwrap(), "  (50000/50000)");
+        let tail_start = 49_999 / LAZY_CHUNK_ROWS * LAZY_CHUNK_ROWS;
+        assert!(requests.borrow().contains(&(tail_start..50_000)));
+        assert!(requests.borrow().len() <= 5, "{:?}", requests.borrow());
+
+        list.set_total_count(3);
+        assert_eq!(list.get_selected_item().unwrap().value, "line 2");
+        assert_eq!(requests.borrow().last(), Some(&(0..3)));
+    }
 }
@@ -1,4 +1,9 @@
 //! SettingsList widget.
+//!
+//! Items are edited in place according to their [`SettingKind`]: plain items
+//! cycle through `values` (or open a submenu), toggles flip, numbers step
+//! within bounds, text and numbers can be typed into an inline [`Input`], and
+//! sections open a nested list with a breadcrumb; Escape steps back out.
 
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -15,6 +20,25 @@ use crate::widgets::input::Input;
 pub type SubmenuDone = Box<dyn FnMut(Option<String>)>;
 pub type SubmenuFactory = Box<dyn FnMut(String, SubmenuDone) -> Box<dyn Component>>;
 pub type SettingsListItemFormatFn = Box<dyn Fn(&str, bool) -> String>;
+/// Per-item change callback, called with the new value before the list-wide one.
+pub type SettingChangeFn = Box<dyn FnMut(&str)>;
+
+const TOGGLE_ON: &str = "true";
+const TOGGLE_OFF: &str = "false";
+
+/// How a [`SettingItem`] is edited.
+pub enum SettingKind {
+    /// Enter/Space cycles through `values`, or opens `submenu` when set.
+    Value,
+    /// Boolean stored as `"true"`/`"false"` and shown as `on`/`off`.
+    Toggle,
+    /// Free text typed into an inline input.
+    Text,
+    /// Whole number kept within `min..=max`; arrows step by `step`.
+    Number { min: i64, max: i64, step: i64 },
+    /// Nested list opened with Enter/Space or right.
+    Section(Vec<SettingItem>),
+}
 
 pub struct SettingItem {
     pub id: String,
@@ -23,6 +47,8 @@ pub struct SettingItem {
     pub current_value: String,
     pub values: Option<Vec<String>>,
     pub submenu: Option<SubmenuFactory>,
+    pub kind: SettingKind,
+    pub on_change: Option<SettingChangeFn>,
 }
 
 impl SettingItem {
@@ -38,6 +64,71 @@ impl SettingItem {
             current_value: current_value.into(),
             values: None,
             submenu: None,
+            kind: SettingKind::Value,
+            on_change: None,
+        }
+    }
+
+    pub fn toggle(id: impl Into<String>, label: impl Into<String>, enabled: bool) -> Self {
+        let value = if enabled { TOGGLE_ON } else { TOGGLE_OFF };
+        Self {
+            kind: SettingKind::Toggle,
+            ..Self::new(id, label, value)
+        }
+    }
+
+    pub fn text(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        current_value: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind: SettingKind::Text,
+            ..Self::new(id, label, current_value)
+        }
+    }
+
+    /// Number stepping by 1; set `kind` directly for a different step.
+    pub fn number(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        current_value: i64,
+        min: i64,
+        max: i64,
+    ) -> Self {
+        Self {
+            kind: SettingKind::Number { min, max, step: 1 },
+            ..Self::new(id, label, current_value.clamp(min, max).to_string())
+        }
+    }
+
+    pub fn section(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        children: Vec<SettingItem>,
+    ) -> Self {
+        Self {
+            kind: SettingKind::Section(children),
+            ..Self::new(id, label, "")
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_on_change(mut self, handler: SettingChangeFn) -> Self {
+        self.on_change = Some(handler);
+        self
+    }
+
+    fn display_value(&self) -> String {
+        match &self.kind {
+            SettingKind::Toggle if self.current_value == TOGGLE_ON => "on".to_string(),
+            SettingKind::Toggle => "off".to_string(),
+            SettingKind::Section(_) => "›".to_string(),
+            _ => self.current_value.clone(),
         }
     }
 }
@@ -55,8 +146,18 @@ pub struct SettingsListOptions {
     pub enable_search: bool,
 }
 
+/// Inline editor for a `Text` or `Number` item of the current level.
+struct ValueEdit {
+    input: Input,
+    item_index: usize,
+    result: Rc<RefCell<Option<Option<String>>>>,
+    error: Option<String>,
+}
+
 pub struct SettingsList {
     items: Vec<SettingItem>,
+    /// Opened sections as `(item index, selected index to restore)` pairs.
+    section_path: Vec<(usize, usize)>,
     filtered_indices: Vec<usize>,
     theme: SettingsListTheme,
     selected_index: usize,
@@ -66,6 +167,7 @@ pub struct SettingsList {
     on_cancel: Box<dyn FnMut()>,
     search_input: Option<Input>,
     search_enabled: bool,
+    value_edit: Option<ValueEdit>,
     submenu_component: Option<Box<dyn Component>>,
     submenu_display_index: Option<usize>,
     submenu_item_index: Option<usize>,
@@ -93,6 +195,7 @@ impl SettingsList {
 
         Self {
             items,
+            section_path: Vec::new(),
             filtered_indices,
             theme,
             selected_index: 0,
@@ -102,6 +205,7 @@ impl SettingsList {
             on_cancel,
             search_input,
             search_enabled,
+            value_edit: None,
             submenu_component: None,
             submenu_display_index: None,
             submenu_item_index: None,
@@ -109,30 +213,66 @@ impl SettingsList {
         }
     }
 
+    /// Sets the value of the item with `id` at any nesting depth.
     pub fn update_value(&mut self, id: &str, new_value: &str) {
-        if let Some(item) = self.items.iter_mut().find(|item| item.id == id) {
+        if let Some(item) = find_item_mut(&mut self.items, id) {
             item.current_value = new_value.to_string();
         }
     }
 
+    /// Items of the currently opened section (the top level when none is).
+    fn level(&self) -> &[SettingItem] {
+        let mut items = self.items.as_slice();
+        for (index, _) in &self.section_path {
+            match items.get(*index).map(|item| &item.kind) {
+                Some(SettingKind::Section(children)) => items = children,
+                _ => break,
+            }
+        }
+        items
+    }
+
+    fn level_mut(&mut self) -> &mut Vec<SettingItem> {
+        let mut items = &mut self.items;
+        for (index, _) in &self.section_path {
+            let is_section = matches!(
+                items.get(*index).map(|item| &item.kind),
+                Some(SettingKind::Section(_))
+            );
+            if !is_section {
+                break;
+            }
+            match &mut items[*index].kind {
+                SettingKind::Section(children) => items = children,
+                _ => unreachable!("checked above"),
+            }
+        }
+        items
+    }
+
     fn display_len(&self) -> usize {
         if self.search_enabled {
             self.filtered_indices.len()
         } else {
-            self.items.len()
+            self.level().len()
         }
     }
 
     fn display_item_index(&self, display_index: usize) -> Option<usize> {
         if self.search_enabled {
             self.filtered_indices.get(display_index).copied()
-        } else if display_index < self.items.len() {
+        } else if display_index < self.level().len() {
             Some(display_index)
         } else {
             None
         }
     }
 
+    fn selected_item(&self) -> Option<&SettingItem> {
+        self.display_item_index(self.selected_index)
+            .and_then(|index| self.level().get(index))
+    }
+
     fn clamp_selected_index(&mut self) {
         let len = self.display_len();
         if len == 0 {
@@ -145,6 +285,13 @@ impl SettingsList {
     fn render_main_list(&mut self, width: usize) -> Vec<String> {
         let mut lines = Vec::new();
 
+        if !self.section_path.is_empty() {
+            let breadcrumb = self.breadcrumb();
+            let breadcrumb = (self.theme.hint)(&format!("  {breadcrumb}"));
+            lines.push(truncate_to_width(&breadcrumb, width, "...", false));
+            lines.push(String::new());
+        }
+
         if self.search_enabled {
             if let Some(search_input) = self.search_input.as_mut() {
                 lines.extend(search_input.render(width));
@@ -152,7 +299,7 @@ impl SettingsList {
             }
         }
 
-        if self.items.is_empty() {
+        if self.level().is_empty() {
             lines.push((self.theme.hint)("  No settings available"));
             if self.search_enabled {
                 self.add_hint_line(&mut lines, width);
@@ -182,7 +329,7 @@ impl SettingsList {
         let end_index = (start_index + max_visible).min(display_len);
 
         let max_label_width = self
-            .items
+            .level()
             .iter()
             .map(|item| visible_width(&item.label))
             .max()
@@ -193,7 +340,6 @@ impl SettingsList {
             let Some(item_index) = self.display_item_index(display_index) else {
                 continue;
             };
-            let item = &self.items[item_index];
             let is_selected = display_index == self.selected_index;
             let prefix = if is_selected {
                 self.theme.cursor.as_str()
@@ -202,6 +348,8 @@ impl SettingsList {
             };
             let prefix_width = visible_width(prefix);
 
+            let item = &self.level()[item_index];
+            let display_value = item.display_value();
             let label_padding = max_label_width.saturating_sub(visible_width(&item.label));
             let label_padded = format!("{}{}", item.label, " ".repeat(label_padding));
             let label_text = (self.theme.label)(&label_padded, is_selected);
@@ -209,9 +357,19 @@ impl SettingsList {
             let separator = "  ";
             let used_width = prefix_width + max_label_width + visible_width(separator);
             let value_max_width = width.saturating_sub(used_width + 2);
-            let truncated_value =
-                truncate_to_width(&item.current_value, value_max_width, "", false);
-            let value_text = (self.theme.value)(&truncated_value, is_selected);
+            let value_text = match self.value_edit.as_mut() {
+                Some(edit) if is_selected && edit.item_index == item_index => edit
+                    .input
+                    .render(value_max_width)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default(),
+                _ => {
+                    let truncated_value =
+                        truncate_to_width(&display_value, value_max_width, "", false);
+                    (self.theme.value)(&truncated_value, is_selected)
+                }
+            };
 
             let combined = format!("{prefix}{label_text}{separator}{value_text}");
             lines.push(truncate_to_width(&combined, width, "...", false));
@@ -223,13 +381,21 @@ impl SettingsList {
             lines.push((self.theme.hint)(&truncated));
         }
 
-        if let Some(item_index) = self.display_item_index(self.selected_index) {
-            if let Some(description) = self.items[item_index].description.as_deref() {
-                lines.push(String::new());
-                let wrapped = wrap_text_with_ansi(description, width.saturating_sub(4));
-                for line in wrapped {
-                    lines.push((self.theme.description)(&format!("  {line}")));
-                }
+        if let Some(error) = self
+            .value_edit
+            .as_ref()
+            .and_then(|edit| edit.error.as_ref())
+        {
+            lines.push(String::new());
+            lines.push((self.theme.hint)(&format!("  {error}")));
+        } else if let Some(description) = self
+            .selected_item()
+            .and_then(|item| item.description.as_deref())
+        {
+            lines.push(String::new());
+            let wrapped = wrap_text_with_ansi(description, width.saturating_sub(4));
+            for line in wrapped {
+                lines.push((self.theme.description)(&format!("  {line}")));
             }
         }
 
@@ -237,14 +403,46 @@ impl SettingsList {
         lines
     }
 
+    fn breadcrumb(&self) -> String {
+        let mut labels = Vec::new();
+        let mut items = self.items.as_slice();
+        for (index, _) in &self.section_path {
+            let Some(item) = items.get(*index) else {
+                break;
+            };
+            labels.push(item.label.as_str());
+            match &item.kind {
+                SettingKind::Section(children) => items = children,
+                _ => break,
+            }
+        }
+        labels.join(" › ")
+    }
+
     fn activate_item(&mut self) {
         let display_index = self.selected_index;
         let Some(item_index) = self.display_item_index(display_index) else {
             return;
         };
 
+        match self.level()[item_index].kind {
+            SettingKind::Value => {}
+            SettingKind::Toggle => {
+                self.step_item(item_index, 1);
+                return;
+            }
+            SettingKind::Text | SettingKind::Number { .. } => {
+                self.begin_value_edit(item_index);
+                return;
+            }
+            SettingKind::Section(_) => {
+                self.enter_section(item_index);
+                return;
+            }
+        }
+
         let maybe_submenu = {
-            let item = &mut self.items[item_index];
+            let item = &mut self.level_mut()[item_index];
             if let Some(submenu) = item.submenu.as_mut() {
                 let current_value = item.current_value.clone();
                 let result_slot: Rc<RefCell<Option<Option<String>>>> = Rc::new(RefCell::new(None));
@@ -267,34 +465,159 @@ impl SettingsList {
             return;
         }
 
-        let (id, new_value) = {
-            let item = &self.items[item_index];
-            let Some(values) = item.values.as_ref() else {
+        self.step_item(item_index, 1);
+    }
+
+    /// Applies one left (`-1`) or right (`1`) step to the item. Returns
+    /// `false` when the item has nothing to step through.
+    fn step_item(&mut self, item_index: usize, direction: i64) -> bool {
+        let Some(item) = self.level().get(item_index) else {
+            return false;
+        };
+        let new_value = match &item.kind {
+            SettingKind::Toggle => {
+                if item.current_value == TOGGLE_ON {
+                    TOGGLE_OFF.to_string()
+                } else {
+                    TOGGLE_ON.to_string()
+                }
+            }
+            SettingKind::Number { min, max, step } => {
+                let current = item.current_value.trim().parse::<i64>().unwrap_or(*min);
+                let next = current
+                    .saturating_add(step.saturating_mul(direction))
+                    .clamp(*min, *max);
+                if next == current {
+                    return true;
+                }
+                next.to_string()
+            }
+            SettingKind::Value => {
+                let Some(values) = item.values.as_ref().filter(|values| !values.is_empty()) else {
+                    return false;
+                };
+                let next_index = match values.iter().position(|value| value == &item.current_value)
+                {
+                    None => 0,
+                    Some(index) if direction < 0 => {
+                        index.checked_sub(1).unwrap_or(values.len() - 1)
+                    }
+                    Some(index) => (index + 1) % values.len(),
+                };
+                values[next_index].clone()
+            }
+            SettingKind::Text | SettingKind::Section(_) => return false,
+        };
+
+        self.commit_value(item_index, new_value);
+        true
+    }
+
+    fn commit_value(&mut self, item_index: usize, new_value: String) {
+        let id = {
+            let Some(item) = self.level_mut().get_mut(item_index) else {
                 return;
             };
-            if values.is_empty() {
-                return;
+            item.current_value = new_value.clone();
+            if let Some(handler) = item.on_change.as_mut() {
+                handler(&new_value);
             }
-            let current_index = values
-                .iter()
-                .position(|value| value == &item.current_value)
-                .unwrap_or(usize::MAX);
-            let next_index = if current_index == usize::MAX {
-                0
-            } else {
-                (current_index + 1) % values.len()
-            };
-            (item.id.clone(), values[next_index].clone())
+            item.id.clone()
         };
-
-        self.items[item_index].current_value = new_value.clone();
         (self.on_change)(id, new_value);
     }
 
+    fn begin_value_edit(&mut self, item_index: usize) {
+        let Some(current_value) = self
+            .level()
+            .get(item_index)
+            .map(|item| item.current_value.clone())
+        else {
+            return;
+        };
+
+        let result: Rc<RefCell<Option<Option<String>>>> = Rc::new(RefCell::new(None));
+        let mut input = Input::new(self.keybindings.clone());
+        input.set_prompt("");
+        input.set_value(current_value);
+        input.move_cursor_to_end();
+        let submit_slot = result.clone();
+        input.set_on_submit(Some(Box::new(move |value| {
+            *submit_slot.borrow_mut() = Some(Some(value));
+        })));
+        let escape_slot = result.clone();
+        input.set_on_escape(Some(Box::new(move || {
+            *escape_slot.borrow_mut() = Some(None);
+        })));
+
+        self.value_edit = Some(ValueEdit {
+            input,
+            item_index,
+            result,
+            error: None,
+        });
+    }
+
+    fn handle_value_edit_event(&mut self, event: &InputEvent) {
+        let Some(edit) = self.value_edit.as_mut() else {
+            return;
+        };
+        edit.input.handle_event(event);
+        let Some(result) = edit.result.borrow_mut().take() else {
+            return;
+        };
+        let Some(value) = result else {
+            self.value_edit = None;
+            return;
+        };
+
+        let item_index = edit.item_index;
+        let bounds = match self.level().get(item_index).map(|item| &item.kind) {
+            Some(SettingKind::Number { min, max, .. }) => Some((*min, *max)),
+            _ => None,
+        };
+        let value = match bounds {
+            Some((min, max)) => match value.trim().parse::<i64>() {
+                Ok(number) if (min..=max).contains(&number) => number.to_string(),
+                _ => {
+                    if let Some(edit) = self.value_edit.as_mut() {
+                        edit.error = Some(format!("Enter a whole number from {min} to {max}"));
+                    }
+                    return;
+                }
+            },
+            None => value,
+        };
+
+        self.value_edit = None;
+        self.commit_value(item_index, value);
+    }
+
+    fn enter_section(&mut self, item_index: usize) {
+        self.section_path.push((item_index, self.selected_index));
+        self.selected_index = 0;
+        self.reset_search();
+    }
+
+    fn leave_section(&mut self) {
+        if let Some((_, selected_index)) = self.section_path.pop() {
+            self.reset_search();
+            self.selected_index = selected_index;
+            self.clamp_selected_index();
+        }
+    }
+
+    fn reset_search(&mut self) {
+        if let Some(search_input) = self.search_input.as_mut() {
+            search_input.set_value("");
+        }
+        self.filtered_indices = (0..self.level().len()).collect();
+    }
+
     fn apply_filter(&mut self, query: &str) {
-        let indices: Vec<usize> = (0..self.items.len()).collect();
+        let indices: Vec<usize> = (0..self.level().len()).collect();
         let filtered = {
-            let items = &self.items;
+            let items = self.level();
             fuzzy_filter(&indices, query, |index| items[*index].label.as_str())
         };
         self.filtered_indices = filtered;
@@ -324,10 +647,7 @@ impl SettingsList {
 
         if let Some(selected_value) = result {
             if let Some(item_index) = self.submenu_item_index {
-                if let Some(item) = self.items.get_mut(item_index) {
-                    item.current_value = selected_value.clone();
-                    (self.on_change)(item.id.clone(), selected_value);
-                }
+                self.commit_value(item_index, selected_value);
             }
         }
 
@@ -336,16 +656,44 @@ impl SettingsList {
 
     fn add_hint_line(&self, lines: &mut Vec<String>, width: usize) {
         lines.push(String::new());
-        let hint_text = if self.search_enabled {
-            "  Type to search · Enter/Space to change · Esc to cancel"
+        let action = match self.selected_item().map(|item| &item.kind) {
+            _ if self.value_edit.is_some() => "Enter to save · Esc to discard",
+            Some(SettingKind::Section(_)) => "Enter/Space to open",
+            Some(SettingKind::Number { .. }) => "←/→ to adjust · Enter to type",
+            Some(SettingKind::Text) => "Enter/Space to edit",
+            _ => "Enter/Space to change",
+        };
+        let escape = if self.value_edit.is_some() {
+            ""
+        } else if self.section_path.is_empty() {
+            " · Esc to cancel"
         } else {
-            "  Enter/Space to change · Esc to cancel"
+            " · Esc to go back"
         };
-        let hint_line = (self.theme.hint)(hint_text);
+        let search = if self.search_enabled && self.value_edit.is_none() {
+            "Type to search · "
+        } else {
+            ""
+        };
+        let hint_line = (self.theme.hint)(&format!("  {search}{action}{escape}"));
         lines.push(truncate_to_width(&hint_line, width, "...", false));
     }
 }
 
+fn find_item_mut<'a>(items: &'a mut [SettingItem], id: &str) -> Option<&'a mut SettingItem> {
+    for item in items {
+        if item.id == id {
+            return Some(item);
+        }
+        if let SettingKind::Section(children) = &mut item.kind {
+            if let Some(found) = find_item_mut(children, id) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
 impl Component for SettingsList {
     fn render(&mut self, width: usize) -> Vec<String> {
         if let Some(component) = self.submenu_component.as_mut() {
@@ -361,11 +709,16 @@ impl Component for SettingsList {
             return;
         }
 
+        if self.value_edit.is_some() {
+            self.handle_value_edit_event(event);
+            return;
+        }
+
         let key_id = match event {
             InputEvent::Key { key_id, .. } => Some(key_id.as_str()),
             _ => None,
         };
-        let (select_up, select_down, select_confirm, select_cancel) = {
+        let (select_up, select_down, select_confirm, select_cancel, step_left, step_right) = {
             let kb = self
                 .keybindings
                 .lock()
@@ -375,9 +728,27 @@ impl Component for SettingsList {
                 kb.matches(key_id, EditorAction::SelectDown),
                 kb.matches(key_id, EditorAction::SelectConfirm),
                 kb.matches(key_id, EditorAction::SelectCancel),
+                kb.matches(key_id, EditorAction::CursorLeft),
+                kb.matches(key_id, EditorAction::CursorRight),
             )
         };
 
+        if step_left || step_right {
+            if let Some(item_index) = self.display_item_index(self.selected_index) {
+                let handled = if step_right
+                    && matches!(self.level()[item_index].kind, SettingKind::Section(_))
+                {
+                    self.enter_section(item_index);
+                    true
+                } else {
+                    self.step_item(item_index, if step_left { -1 } else { 1 })
+                };
+                if handled {
+                    return;
+                }
+            }
+        }
+
         let display_len = self.display_len();
         if select_up {
             if display_len == 0 {
@@ -403,7 +774,11 @@ impl Component for SettingsList {
         {
             self.activate_item();
         } else if select_cancel {
-            (self.on_cancel)();
+            if self.section_path.is_empty() {
+                (self.on_cancel)();
+            } else {
+                self.leave_section();
+            }
         } else if self.search_enabled {
             let query = if let Some(search_input) = self.search_input.as_mut() {
                 match event {
@@ -421,7 +796,7 @@ impl Component for SettingsList {
                         };
                         search_input.handle_event(&sanitized_event);
                     }
-                    InputEvent::Paste { text, .. } => {
+                    InputEvent::Paste { text, .. } | InputEvent::FilesDropped { text, .. } => {
                         let sanitized: String = text.chars().filter(|ch| *ch != ' ').collect();
                         if sanitized.is_empty() {
                             return;
@@ -576,4 +951,97 @@ mod tests {
             ("submenu".to_string(), "updated".to_string())
         );
     }
+
+    #[test]
+    fn settings_list_sections_and_typed_editors() {
+        let wrap_changes: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+        let wrap_changes_ref = wrap_changes.clone();
+        let items = vec![
+            SettingItem::toggle("wrap", "Wrap", false).with_on_change(Box::new(move |value| {
+                wrap_changes_ref.borrow_mut().push(value.to_string());
+            })),
+            SettingItem::section(
+                "editor",
+                "Editor",
+                vec![
+                    SettingItem::number("tab", "Tab width", 4, 1, 8),
+                    SettingItem::text("font", "Font", "mono"),
+                ],
+            ),
+        ];
+
+        let changes: Rc<RefCell<Vec<(String, String)>>> = Rc::new(RefCell::new(Vec::new()));
+        let changes_ref = changes.clone();
+        let cancelled: Rc<RefCell<bool>> = Rc::new(RefCell::new(false));
+        let cancelled_ref = cancelled.clone();
+        let mut list = SettingsList::new(
+            items,
+            5,
+            theme(),
+            Box::new(move |id: String, value: String| {
+                changes_ref.borrow_mut().push((id, value));
+            }),
+            Box::new(move || *cancelled_ref.borrow_mut() = true),
+            default_editor_keybindings_handle(),
+            None,
+        );
+
+        send(&mut list, " ");
+        assert_eq!(wrap_changes.borrow().as_slice(), &["true"]);
+        assert_eq!(list.render(60)[0], "→ Wrap    on");
+
+        send(&mut list, "\x1b[B");
+        send(&mut list, "\r");
+        assert_eq!(
+            list.render(60),
+            vec![
+                "  Editor",
+                "",
+                "→ Tab width  4",
+                "  Font       mono",
+                "",
+                "  ←/→ to adjust · Enter to type · Esc to go back",
+            ]
+        );
+
+        // Arrows step within bounds; typed numbers are validated before commit.
+        send(&mut list, "\x1b[C");
+        send(&mut list, "\x1b[D");
+        send(&mut list, "\x1b[D");
+        send(&mut list, "\r");
+        for data in ["\x15", "99", "\r"] {
+            send(&mut list, data);
+        }
+        assert!(list
+            .render(60)
+            .contains(&"  Enter a whole number from 1 to 8".to_string()));
+        for data in ["\x15", "7", "\r"] {
+            send(&mut list, data);
+        }
+
+        // Escape discards a text edit; Enter commits it.
+        send(&mut list, "\x1b[B");
+        for data in ["\r", "\x15", "serif", "\x1b", "\r", "\x15", "sans", "\r"] {
+            send(&mut list, data);
+        }
+
+        send(&mut list, "\x1b");
+        assert_eq!(list.render(60)[1], "→ Editor  ›");
+        assert!(!*cancelled.borrow());
+        send(&mut list, "\x1b");
+        assert!(*cancelled.borrow());
+
+        let expected = [
+            ("wrap", "true"),
+            ("tab", "5"),
+            ("tab", "4"),
+            ("tab", "3"),
+            ("tab", "7"),
+            ("font", "sans"),
+        ];
+        assert_eq!(
+            changes.borrow().as_slice(),
+            expected.map(|(id, value)| (id.to_string(), value.to_string()))
+        );
+    }
 }
@@ -0,0 +1,688 @@
+//! TreeView widget.
+//!
+//! Nodes are plain data; the view tracks which node ids are expanded and
+//! flattens the expanded part of the tree into rows on every change. Branch
+//! nodes may arrive without children, in which case the child loader is
+//! called the first time they are expanded.
+
+use std::collections::HashSet;
+
+use crate::core::component::Component;
+use crate::core::input_event::InputEvent;
+use crate::core::keybindings::{EditorAction, EditorKeybindingsHandle};
+use crate::core::text::utils::truncate_to_width;
+
+/// One node of a [`TreeView`]. Ids must be unique within the tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeNode {
+    pub id: String,
+    pub label: String,
+    /// Whether the node can be expanded, even if `children` is not loaded yet.
+    pub is_branch: bool,
+    /// `None` means "not loaded"; the child loader fills it on first expand.
+    pub children: Option<Vec<TreeNode>>,
+}
+
+impl TreeNode {
+    pub fn leaf(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            is_branch: false,
+            children: None,
+        }
+    }
+
+    /// A branch whose children are loaded lazily on first expand.
+    pub fn branch(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            is_branch: true,
+            children: None,
+        }
+    }
+
+    pub fn with_children(mut self, children: Vec<TreeNode>) -> Self {
+        self.is_branch = true;
+        self.children = Some(children);
+        self
+    }
+}
+
+/// Loads the children of the branch with the given id.
+pub type TreeChildLoader = Box<dyn FnMut(&str) -> Vec<TreeNode>>;
+
+pub struct TreeViewTheme {
+    pub selected_text: Box<dyn Fn(&str) -> String>,
+    pub branch_label: Box<dyn Fn(&str) -> String>,
+    pub guide: Box<dyn Fn(&str) -> String>,
+    pub scroll_info: Box<dyn Fn(&str) -> String>,
+    pub empty: Box<dyn Fn(&str) -> String>,
+}
+
+struct TreeRow {
+    /// Child indices from the roots down to the node.
+    path: Vec<usize>,
+    /// Indentation guides plus the branch connector for this row.
+    guides: String,
+}
+
+pub struct TreeView {
+    roots: Vec<TreeNode>,
+    expanded: HashSet<String>,
+    rows: Vec<TreeRow>,
+    selected_index: usize,
+    max_visible: usize,
+    theme: TreeViewTheme,
+    keybindings: EditorKeybindingsHandle,
+    child_loader: Option<TreeChildLoader>,
+    on_select: Option<Box<dyn FnMut(String)>>,
+    on_cancel: Option<Box<dyn FnMut()>>,
+    on_selection_change: Option<Box<dyn FnMut(String)>>,
+    on_toggle: Option<Box<dyn FnMut(String, bool)>>,
+}
+
+impl TreeView {
+    pub fn new(
+        roots: Vec<TreeNode>,
+        max_visible: usize,
+        theme: TreeViewTheme,
+        keybindings: EditorKeybindingsHandle,
+    ) -> Self {
+        let mut view = Self {
+            roots,
+            expanded: HashSet::new(),
+            rows: Vec::new(),
+            selected_index: 0,
+            max_visible,
+            theme,
+            keybindings,
+            child_loader: None,
+            on_select: None,
+            on_cancel: None,
+            on_selection_change: None,
+            on_toggle: None,
+        };
+        view.rebuild_rows(None);
+        view
+    }
+
+    /// Replaces the tree. Expansion state is kept for ids that still exist.
+    pub fn set_roots(&mut self, roots: Vec<TreeNode>) {
+        let selected_id = self.selected_id();
+        self.roots = roots;
+        self.rebuild_rows(selected_id);
+    }
+
+    pub fn set_child_loader(&mut self, loader: Option<TreeChildLoader>) {
+        self.child_loader = loader;
+    }
+
+    /// Called with the node id when Enter is pressed on a leaf.
+    pub fn set_on_select(&mut self, handler: Option<Box<dyn FnMut(String)>>) {
+        self.on_select = handler;
+    }
+
+    pub fn set_on_cancel(&mut self, handler: Option<Box<dyn FnMut()>>) {
+        self.on_cancel = handler;
+    }
+
+    pub fn set_on_selection_change(&mut self, handler: Option<Box<dyn FnMut(String)>>) {
+        self.on_selection_change = handler;
+    }
+
+    /// Called with the node id and its new expanded state.
+    pub fn set_on_toggle(&mut self, handler: Option<Box<dyn FnMut(String, bool)>>) {
+        self.on_toggle = handler;
+    }
+
+    pub fn selected_node(&self) -> Option<&TreeNode> {
+        self.rows
+            .get(self.selected_index)
+            .and_then(|row| self.node(&row.path))
+    }
+
+    pub fn is_expanded(&self, id: &str) -> bool {
+        self.expanded.contains(id)
+    }
+
+    /// Expands the branch with `id`, loading its children if needed.
+    /// Returns `false` when no such branch is currently visible.
+    pub fn expand(&mut self, id: &str) -> bool {
+        match self
+            .rows
+            .iter()
+            .position(|row| self.node_id(row) == Some(id))
+        {
+            Some(index) => self.set_expanded(index, true),
+            None => false,
+        }
+    }
+
+    /// Collapses the node with `id`; returns `false` when it is not visible.
+    pub fn collapse(&mut self, id: &str) -> bool {
+        match self
+            .rows
+            .iter()
+            .position(|row| self.node_id(row) == Some(id))
+        {
+            Some(index) => self.set_expanded(index, false),
+            None => false,
+        }
+    }
+
+    /// Drops the loaded children of `id` so the loader runs again on the next
+    /// expand (for example after the directory changed on disk).
+    pub fn reload_children(&mut self, id: &str) {
+        let selected_id = self.selected_id();
+        let was_expanded = self.expanded.contains(id);
+        if let Some(node) = find_node_mut(&mut self.roots, id) {
+            if node.is_branch {
+                node.children = None;
+            }
+        }
+        if was_expanded {
+            self.load_children(id);
+        }
+        self.rebuild_rows(selected_id);
+    }
+
+    fn node(&self, path: &[usize]) -> Option<&TreeNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.roots.get(*first)?;
+        for index in rest {
+            node = node.children.as_ref()?.get(*index)?;
+        }
+        Some(node)
+    }
+
+    fn node_id(&self, row: &TreeRow) -> Option<&str> {
+        self.node(&row.path).map(|node| node.id.as_str())
+    }
+
+    fn selected_id(&self) -> Option<String> {
+        self.selected_node().map(|node| node.id.clone())
+    }
+
+    /// Re-flattens the tree, keeping `selected_id` selected when it is still
+    /// visible. Callers capture the id before mutating the tree because row
+    /// paths go stale once nodes move.
+    fn rebuild_rows(&mut self, selected_id: Option<String>) {
+        let mut rows = Vec::new();
+        for (index, root) in self.roots.iter().enumerate() {
+            push_rows(
+                root,
+                vec![index],
+                String::new(),
+                true,
+                &self.expanded,
+                &mut rows,
+            );
+        }
+        self.rows = rows;
+
+        if let Some(selected_id) = selected_id {
+            if let Some(index) = self
+                .rows
+                .iter()
+                .position(|row| self.node_id(row) == Some(selected_id.as_str()))
+            {
+                self.selected_index = index;
+            }
+        }
+        self.selected_index = self.selected_index.min(self.rows.len().saturating_sub(1));
+    }
+
+    fn load_children(&mut self, id: &str) {
+        let needs_load = find_node_mut(&mut self.roots, id)
+            .is_some_and(|node| node.is_branch && node.children.is_none());
+        if !needs_load {
+            return;
+        }
+        let children = match self.child_loader.as_mut() {
+            Some(loader) => loader(id),
+            None => Vec::new(),
+        };
+        if let Some(node) = find_node_mut(&mut self.roots, id) {
+            node.children = Some(children);
+        }
+    }
+
+    fn set_expanded(&mut self, row_index: usize, expanded: bool) -> bool {
+        let Some((id, is_branch)) = self
+            .rows
+            .get(row_index)
+            .and_then(|row| self.node(&row.path))
+            .map(|node| (node.id.clone(), node.is_branch))
+        else {
+            return false;
+        };
+        if !is_branch || self.expanded.contains(&id) == expanded {
+            return is_branch;
+        }
+
+        let selected_id = self.selected_id();
+        if expanded {
+            self.load_children(&id);
+            self.expanded.insert(id.clone());
+        } else {
+            self.expanded.remove(&id);
+        }
+        self.rebuild_rows(selected_id);
+        if let Some(handler) = self.on_toggle.as_mut() {
+            handler(id, expanded);
+        }
+        true
+    }
+
+    fn move_selection(&mut self, index: usize) {
+        if self.rows.is_empty() || index == self.selected_index {
+            return;
+        }
+        self.selected_index = index.min(self.rows.len() - 1);
+        let Some(id) = self
+            .rows
+            .get(self.selected_index)
+            .and_then(|row| self.node_id(row))
+        else {
+            return;
+        };
+        let id = id.to_string();
+        if let Some(handler) = self.on_selection_change.as_mut() {
+            handler(id);
+        }
+    }
+
+    fn expand_or_descend(&mut self) {
+        let Some(node) = self.selected_node() else {
+            return;
+        };
+        if !node.is_branch {
+            return;
+        }
+        if self.expanded.contains(&node.id) {
+            let has_children = node.children.as_ref().is_some_and(|c| !c.is_empty());
+            if has_children {
+                self.move_selection(self.selected_index + 1);
+            }
+        } else {
+            self.set_expanded(self.selected_index, true);
+        }
+    }
+
+    fn collapse_or_ascend(&mut self) {
+        let Some(row) = self.rows.get(self.selected_index) else {
+            return;
+        };
+        let collapsible = self
+            .node(&row.path)
+            .is_some_and(|node| self.expanded.contains(&node.id));
+        if collapsible {
+            self.set_expanded(self.selected_index, false);
+            return;
+        }
+        if row.path.len() > 1 {
+            let parent = &row.path[..row.path.len() - 1];
+            if let Some(index) = self.rows.iter().position(|row| row.path == parent) {
+                self.move_selection(index);
+            }
+        }
+    }
+
+    fn confirm(&mut self) {
+        let Some(node) = self.selected_node() else {
+            return;
+        };
+        if node.is_branch {
+            let expanded = !self.expanded.contains(&node.id);
+            self.set_expanded(self.selected_index, expanded);
+        } else {
+            let id = node.id.clone();
+            if let Some(handler) = self.on_select.as_mut() {
+                handler(id);
+            }
+        }
+    }
+
+    fn render_row(&self, width: usize, row: &TreeRow, selected: bool) -> String {
+        let Some(node) = self.node(&row.path) else {
+            return String::new();
+        };
+        let marker = if !node.is_branch {
+            "  "
+        } else if self.expanded.contains(&node.id) {
+            "▾ "
+        } else {
+            "▸ "
+        };
+
+        let prefix = if selected { "→ " } else { "  " };
+        let fixed = format!("{prefix}{}{marker}", row.guides);
+        let label_width = width.saturating_sub(fixed.chars().count() + 1);
+        let label = truncate_to_width(&node.label, label_width, "…", false);
+
+        if selected {
+            return (self.theme.selected_text)(&format!("{fixed}{label}"));
+        }
+        let label = if node.is_branch {
+            (self.theme.branch_label)(&label)
+        } else {
+            label
+        };
+        let guides = if row.guides.is_empty() {
+            String::new()
+        } else {
+            (self.theme.guide)(&row.guides)
+        };
+        format!("{prefix}{guides}{marker}{label}")
+    }
+}
+
+fn push_rows(
+    node: &TreeNode,
+    path: Vec<usize>,
+    ancestors: String,
+    is_last: bool,
+    expanded: &HashSet<String>,
+    rows: &mut Vec<TreeRow>,
+) {
+    let depth = path.len();
+    let guides = if depth == 1 {
+        String::new()
+    } else if is_last {
+        format!("{ancestors}└─")
+    } else {
+        format!("{ancestors}├─")
+    };
+    rows.push(TreeRow {
+        path: path.clone(),
+        guides,
+    });
+
+    if !expanded.contains(&node.id) {
+        return;
+    }
+    let Some(children) = node.children.as_ref() else {
+        return;
+    };
+    let child_ancestors = if depth == 1 {
+        String::new()
+    } else if is_last {
+        format!("{ancestors}  ")
+    } else {
+        format!("{ancestors}│ ")
+    };
+    for (index, child) in children.iter().enumerate() {
+        let mut child_path = path.clone();
+        child_path.push(index);
+        push_rows(
+            child,
+            child_path,
+            child_ancestors.clone(),
+            index + 1 == children.len(),
+            expanded,
+            rows,
+        );
+    }
+}
+
+fn find_node_mut<'a>(nodes: &'a mut [TreeNode], id: &str) -> Option<&'a mut TreeNode> {
+    for node in nodes {
+        if node.id == id {
+            return Some(node);
+        }
+        if let Some(found) = node
+            .children
+            .as_mut()
+            .and_then(|children| find_node_mut(children, id))
+        {
+            return Some(found);
+        }
+    }
+    None
+}
+
+impl Component for TreeView {
+    fn render(&mut self, width: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if self.rows.is_empty() {
+            lines.push((self.theme.empty)("  (empty)"));
+            return lines;
+        }
+
+        let len = self.rows.len();
+        let max_visible = self.max_visible.max(1).min(len);
+        let half = max_visible / 2;
+        let start_index = if len <= max_visible {
+            0
+        } else {
+            self.selected_index
+                .saturating_sub(half)
+                .min(len - max_visible)
+        };
+        let end_index = (start_index + max_visible).min(len);
+
+        for index in start_index..end_index {
+            lines.push(self.render_row(width, &self.rows[index], index == self.selected_index));
+        }
+
+        if start_index > 0 || end_index < len {
+            let scroll_text = format!("  ({}/{})", self.selected_index + 1, len);
+            let truncated = truncate_to_width(&scroll_text, width.saturating_sub(2), "", false);
+            lines.push((self.theme.scroll_info)(&truncated));
+        }
+
+        lines
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        let key_id = match event {
+            InputEvent::Key { key_id, .. } => Some(key_id.as_str()),
+            _ => None,
+        };
+
+        enum Action {
+            Up,
+            Down,
+            PageUp,
+            PageDown,
+            Expand,
+            Collapse,
+            Confirm,
+            Cancel,
+            None,
+        }
+
+        let action = {
+            let kb = self
+                .keybindings
+                .lock()
+                .expect("editor keybindings lock poisoned");
+
+            if kb.matches(key_id, EditorAction::SelectUp) {
+                Action::Up
+            } else if kb.matches(key_id, EditorAction::SelectDown) {
+                Action::Down
+            } else if kb.matches(key_id, EditorAction::SelectPageUp) {
+                Action::PageUp
+            } else if kb.matches(key_id, EditorAction::SelectPageDown) {
+                Action::PageDown
+            } else if kb.matches(key_id, EditorAction::TreeExpand) {
+                Action::Expand
+            } else if kb.matches(key_id, EditorAction::TreeCollapse) {
+                Action::Collapse
+            } else if kb.matches(key_id, EditorAction::SelectConfirm) {
+                Action::Confirm
+            } else if kb.matches(key_id, EditorAction::SelectCancel) {
+                Action::Cancel
+            } else {
+                Action::None
+            }
+        };
+
+        let len = self.rows.len();
+        let page = self.max_visible.max(1);
+        match action {
+            Action::Up if len > 0 => {
+                let index = self.selected_index.checked_sub(1).unwrap_or(len - 1);
+                self.move_selection(index);
+            }
+            Action::Down if len > 0 => {
+                let index = (self.selected_index + 1) % len;
+                self.move_selection(index);
+            }
+            Action::PageUp => self.move_selection(self.selected_index.saturating_sub(page)),
+            Action::PageDown => self.move_selection(self.selected_index + page),
+            Action::Expand => self.expand_or_descend(),
+            Action::Collapse => self.collapse_or_ascend(),
+            Action::Confirm => self.confirm(),
+            Action::Cancel => {
+                if let Some(handler) = self.on_cancel.as_mut() {
+                    handler();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn invalidate(&mut self) {
+        // Rows are rebuilt eagerly on every tree change.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TreeNode, TreeView, TreeViewTheme};
+    use crate::core::component::Component;
+    use crate::core::input_event::parse_input_events;
+    use crate::default_editor_keybindings_handle;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn theme() -> TreeViewTheme {
+        TreeViewTheme {
+            selected_text: Box::new(|text| text.to_string()),
+            branch_label: Box::new(|text| format!("{text}/")),
+            guide: Box::new(|text| text.to_string()),
+            scroll_info: Box::new(|text| text.to_string()),
+            empty: Box::new(|text| text.to_string()),
+        }
+    }
+
+    fn send(view: &mut TreeView, data: &str) {
+        for event in parse_input_events(data, false) {
+            view.handle_event(&event);
+        }
+    }
+
+    fn workspace() -> Vec<TreeNode> {
+        vec![
+            TreeNode::branch("src", "src").with_children(vec![
+                TreeNode::branch("src/widgets", "widgets"),
+                TreeNode::leaf("src/lib.rs", "lib.rs"),
+            ]),
+            TreeNode::leaf("Cargo.toml", "Cargo.toml"),
+        ]
+    }
+
+    #[test]
+    fn tree_view_expands_lazily_and_draws_guides() {
+        let mut view = TreeView::new(
+            workspace(),
+            10,
+            theme(),
+            default_editor_keybindings_handle(),
+        );
+        let loads: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+        let loads_ref = loads.clone();
+        view.set_child_loader(Some(Box::new(move |id| {
+            loads_ref.borrow_mut().push(id.to_string());
+            vec![
+                TreeNode::leaf(format!("{id}/editor.rs"), "editor.rs"),
+                TreeNode::leaf(format!("{id}/tree_view.rs"), "tree_view.rs"),
+            ]
+        })));
+
+        assert_eq!(view.render(40), vec!["→ ▸ src", "    Cargo.toml"]);
+
+        // right expands, right again descends, right on a collapsed branch expands it.
+        send(&mut view, "\x1b[C");
+        send(&mut view, "\x1b[C");
+        send(&mut view, "\x1b[C");
+        assert_eq!(loads.borrow().as_slice(), &["src/widgets"]);
+        assert_eq!(
+            view.render(40),
+            vec![
+                "  ▾ src/",
+                "→ ├─▾ widgets",
+                "  │ ├─  editor.rs",
+                "  │ └─  tree_view.rs",
+                "  └─  lib.rs",
+                "    Cargo.toml",
+            ]
+        );
+
+        // Collapsing and re-expanding reuses the loaded children.
+        view.collapse("src/widgets");
+        view.expand("src/widgets");
+        assert_eq!(loads.borrow().len(), 1);
+
+        // left on a leaf moves to its parent, left again collapses it.
+        send(&mut view, "\x1b[B");
+        send(&mut view, "\x1b[D");
+        assert_eq!(view.selected_node().unwrap().id, "src/widgets");
+        send(&mut view, "\x1b[D");
+        assert!(!view.is_expanded("src/widgets"));
+
+        view.reload_children("src/widgets");
+        view.expand("src/widgets");
+        assert_eq!(loads.borrow().len(), 2);
+    }
+
+    #[test]
+    fn tree_view_fires_selection_toggle_and_cancel_events() {
+        let mut view = TreeView::new(
+            workspace(),
+            10,
+            theme(),
+            default_editor_keybindings_handle(),
+        );
+        let events: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let events_ref = events.clone();
+        view.set_on_selection_change(Some(Box::new(move |id| {
+            events_ref.borrow_mut().push(format!("change {id}"));
+        })));
+        let events_ref = events.clone();
+        view.set_on_select(Some(Box::new(move |id| {
+            events_ref.borrow_mut().push(format!("select {id}"));
+        })));
+        let events_ref = events.clone();
+        view.set_on_toggle(Some(Box::new(move |id, expanded| {
+            events_ref
+                .borrow_mut()
+                .push(format!("toggle {id} {expanded}"));
+        })));
+        let events_ref = events.clone();
+        view.set_on_cancel(Some(Box::new(move || {
+            events_ref.borrow_mut().push("cancel".to_string());
+        })));
+
+        send(&mut view, "\r");
+        send(&mut view, "\x1b[A");
+        send(&mut view, "\r");
+        send(&mut view, "\x1b");
+
+        assert_eq!(
+            events.borrow().as_slice(),
+            &[
+                "toggle src true",
+                "change Cargo.toml",
+                "select Cargo.toml",
+                "cancel",
+            ]
+        );
+    }
+}
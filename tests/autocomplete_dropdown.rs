@@ -0,0 +1,145 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use tape_tui::core::autocomplete::CommandEntry;
+use tape_tui::core::terminal::Terminal;
+use tape_tui::{
+    default_editor_keybindings_handle, CombinedAutocompleteProvider, Editor, EditorOptions,
+    EditorTheme, SelectListTheme, SlashCommand, TUI,
+};
+
+#[derive(Default)]
+struct TerminalState {
+    writes: String,
+    on_input: Option<Box<dyn FnMut(String) + Send>>,
+}
+
+#[derive(Clone, Default)]
+struct HarnessTerminal {
+    state: Arc<Mutex<TerminalState>>,
+}
+
+impl HarnessTerminal {
+    fn send(&self, data: &str) {
+        let mut state = self.state.lock().expect("lock terminal input");
+        let on_input = state.on_input.as_mut().expect("terminal started");
+        on_input(data.to_string());
+    }
+
+    fn take_writes(&self) -> String {
+        let mut state = self.state.lock().expect("lock terminal writes");
+        std::mem::take(&mut state.writes)
+    }
+}
+
+impl Terminal for HarnessTerminal {
+    fn start(
+        &mut self,
+        on_input: Box<dyn FnMut(String) + Send>,
+        _on_resize: Box<dyn FnMut() + Send>,
+    ) -> std::io::Result<()> {
+        self.state.lock().expect("lock terminal start").on_input = Some(on_input);
+        Ok(())
+    }
+
+    fn stop(&mut self) -> std::io::Result<()> {
+        self.state.lock().expect("lock terminal stop").on_input = None;
+        Ok(())
+    }
+
+    fn drain_input(&mut self, _max_ms: u64, _idle_ms: u64) {}
+
+    fn write(&mut self, data: &str) {
+        self.state
+            .lock()
+            .expect("lock terminal write")
+            .writes
+            .push_str(data);
+    }
+
+    fn columns(&self) -> u16 {
+        40
+    }
+
+    fn rows(&self) -> u16 {
+        12
+    }
+}
+
+fn plain_theme() -> EditorTheme {
+    EditorTheme {
+        border_color: Box::new(|text| text.to_string()),
+        select_list: SelectListTheme {
+            selected_prefix: Arc::new(|text| text.to_string()),
+            selected_text: Arc::new(|text| text.to_string()),
+            description: Arc::new(|text| text.to_string()),
+            scroll_info: Arc::new(|text| text.to_string()),
+            no_match: Arc::new(|text| text.to_string()),
+        },
+    }
+}
+
+fn command(name: &str) -> CommandEntry {
+    CommandEntry::Command(SlashCommand {
+        name: name.to_string(),
+        description: None,
+        get_argument_completions: None,
+    })
+}
+
+#[test]
+fn editor_suggestions_open_in_an_anchored_dropdown_and_apply_on_tab() {
+    let terminal = HarnessTerminal::default();
+    let mut runtime = TUI::new(terminal.clone());
+
+    let mut editor = Editor::new(
+        plain_theme(),
+        default_editor_keybindings_handle(),
+        EditorOptions::default(),
+    );
+    editor.set_autocomplete_provider(Box::new(CombinedAutocompleteProvider::new(
+        vec![command("model"), command("quit")],
+        PathBuf::from("."),
+        None,
+    )));
+    let text = Rc::new(RefCell::new(String::new()));
+    let changed = Rc::clone(&text);
+    editor.set_on_change(Some(Box::new(move |value| *changed.borrow_mut() = value)));
+    let dropdown = editor.autocomplete_dropdown();
+
+    let editor_id = runtime.register_component(editor);
+    let dropdown_id = runtime.register_component(dropdown.clone());
+    dropdown.attach(runtime.runtime_handle(), editor_id, dropdown_id);
+    runtime.set_root(vec![editor_id]);
+    runtime.set_focus(editor_id);
+    runtime.start().expect("runtime start");
+    runtime.run_once();
+    terminal.take_writes();
+
+    terminal.send("/");
+    runtime.run_once();
+    runtime.run_once();
+    assert!(dropdown.is_open());
+    let writes = terminal.take_writes();
+    assert!(writes.contains("model") && writes.contains("quit"));
+
+    // The editor keeps the keys: move to the second item and apply it.
+    terminal.send("\x1b[B");
+    runtime.run_once();
+    terminal.send("\t");
+    runtime.run_once();
+    runtime.run_once();
+    assert_eq!(text.borrow().as_str(), "/quit ");
+    assert!(!dropdown.is_open());
+
+    terminal.send("\x7f");
+    runtime.run_once();
+    runtime.run_once();
+    assert!(dropdown.is_open());
+    terminal.send("\x1b");
+    runtime.run_once();
+    runtime.run_once();
+    assert!(!dropdown.is_open());
+}
@@ -1,24 +1,34 @@
 #![allow(unused_imports)]
 
 use tape_tui::{
-    allocate_image_id, calculate_image_rows, default_editor_keybindings_handle,
+    allocate_image_id, calculate_image_rows, command_reply, default_editor_keybindings_handle,
     delete_all_kitty_images, delete_kitty_image, detect_capabilities, encode_iterm2, encode_kitty,
-    fuzzy_filter, fuzzy_match, get_capabilities, get_cell_dimensions, get_gif_dimensions,
-    get_image_dimensions, get_jpeg_dimensions, get_png_dimensions, get_webp_dimensions,
-    highlight_markdown_code_ansi, image_fallback, is_focusable, is_key_release, is_key_repeat,
-    matches_key, parse_key, prewarm_markdown_highlighting, render_image, reset_capabilities_cache,
-    set_cell_dimensions, truncate_to_width, visible_width, wrap_text_with_ansi, AutocompleteItem,
-    AutocompleteProvider, AutocompleteSuggestions, Box as UiBox, CancellableLoader, CellDimensions,
-    CombinedAutocompleteProvider, Component, Container, DefaultTextStyle, Editor, EditorAction,
-    EditorComponent, EditorKeybindingsConfig, EditorKeybindingsHandle, EditorKeybindingsManager,
-    EditorOptions, EditorTheme, Focusable, FuzzyMatch, Image, ImageDimensions, ImageOptions,
-    ImageProtocol, ImageRenderOptions, ImageTheme, Input, InputEvent, Key, KeyEventType, KeyId,
-    Loader, Markdown, MarkdownTheme, ProcessTerminal, SelectItem, SelectList, SelectListTheme,
-    SettingItem, SettingsList, SettingsListTheme, SlashCommand, Spacer, StdinBuffer,
+    fuzzy_filter, fuzzy_filter_top_k, fuzzy_match, get_capabilities, get_cell_dimensions,
+    get_gif_dimensions, get_image_dimensions, get_jpeg_dimensions, get_png_dimensions,
+    get_webp_dimensions, highlight_markdown_code_ansi, image_fallback, is_focusable,
+    is_key_release, is_key_repeat, matches_key, parse_key, prewarm_markdown_highlighting,
+    render_image, reset_capabilities_cache, set_cell_dimensions, truncate_to_width, visible_width,
+    wrap_text_with_ansi, AnimationClock, AutocompleteDropdown, AutocompleteItem,
+    AutocompleteProvider, AutocompleteSuggestions, Box as UiBox, BoxBorderSides, BoxBorderStyle,
+    BoxTitleAlign, CachedCapabilities, CancellableLoader, CapabilityCache, CellDimensions,
+    CombinedAutocompleteProvider, CommandReply, Component, ComponentMessage, CompositionPhase,
+    Container, DefaultTextStyle, Divider, DividerAlign, DividerStyleFn, DividerTheme, Editor,
+    EditorAction, EditorComponent, EditorCounts, EditorKeybindingsConfig, EditorKeybindingsHandle,
+    EditorKeybindingsManager, EditorOptions, EditorTheme, FocusRing, Focusable, FuzzyHit,
+    FuzzyMatch, FuzzyMatcher, HelpEntry, HelpOverlay, HelpOverlayOptions, HelpOverlayTheme,
+    HelpSection, HostCommand, Image, ImageDimensions, ImageOptions, ImageProtocol,
+    ImageRenderOptions, ImageTheme, Input, InputEvent, InputStyleFn, InputTheme, InputValidation,
+    InputValidator, Key, KeyEventType, KeyEventTypes, KeyId, KeybindingConflict,
+    KeybindingConflictKind, KeybindingContext, KeybindingHelpEntry, KeybindingHelpSection,
+    KeybindingOwner, KittyPlacement, Loader, LoaderOptions, Markdown, MarkdownTheme,
+    NotificationProtocol, ProcessTerminal, Rect, RenderCache, ReplySender, SelectItem,
+    SelectItemProvider, SelectList, SelectListTheme, SettingChangeFn, SettingItem, SettingKind,
+    SettingsList, SettingsListTheme, SlashCommand, Spacer, SpinnerStyle, StdinBuffer,
     StdinBufferEventMap, StdinBufferOptions, SurfaceAnchor, SurfaceHandle, SurfaceId,
     SurfaceInputPolicy, SurfaceKind, SurfaceLayoutOptions, SurfaceMargin, SurfaceOptions,
-    SurfaceSizeValue, SurfaceTransactionMutation, SurfaceVisibility, Terminal,
-    TerminalCapabilities, Text, TruncatedText, CURSOR_MARKER, DEFAULT_EDITOR_KEYBINDINGS, TUI,
+    SurfacePlacement, SurfaceSizeValue, SurfaceTransactionMutation, SurfaceVisibility, Terminal,
+    TerminalCapabilities, Text, TreeChildLoader, TreeNode, TreeView, TreeViewTheme, TruncatedText,
+    CURSOR_MARKER, DEFAULT_EDITOR_KEYBINDINGS, TUI,
 };
 
 #[test]
@@ -0,0 +1,107 @@
+//! Byte-output budgets for canonical render scenarios.
+//!
+//! Each scenario renders through `DiffRenderer` and counts the bytes the
+//! output gate would write. The counts are pinned exactly: a change that
+//! makes a common frame write more bytes shows up here in review, and an
+//! intentional change updates the number alongside it. Timing lives in
+//! `benches/render_pipeline.rs`.
+
+use tape_tui::core::output::OutputGate;
+use tape_tui::core::terminal_image::is_image_line;
+use tape_tui::render::renderer::DiffRenderer;
+use tape_tui::render::surface::{composite_surfaces, RenderedSurface};
+use tape_tui::render::Frame;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 24;
+
+fn transcript(lines: usize) -> Vec<String> {
+    (0..lines)
+        .map(|row| format!("\x1b[2m{row:>4}\x1b[0m transcript line {row}"))
+        .collect()
+}
+
+fn output_bytes(renderer: &mut DiffRenderer, lines: Vec<String>, width: usize) -> usize {
+    let mut gate = OutputGate::new();
+    gate.extend(renderer.render(Frame::from(lines), width, HEIGHT, false, false));
+    gate.encoded_len()
+}
+
+fn rendered(lines: Vec<String>) -> DiffRenderer {
+    let mut renderer = DiffRenderer::new();
+    output_bytes(&mut renderer, lines, WIDTH);
+    renderer
+}
+
+#[test]
+fn first_render_of_a_long_transcript() {
+    let mut renderer = DiffRenderer::new();
+    assert_eq!(output_bytes(&mut renderer, transcript(200), WIDTH), 8704);
+}
+
+#[test]
+fn unchanged_frame_writes_nothing() {
+    let mut renderer = rendered(transcript(200));
+    assert_eq!(output_bytes(&mut renderer, transcript(200), WIDTH), 0);
+}
+
+#[test]
+fn single_line_edit_in_the_viewport() {
+    let mut renderer = rendered(transcript(200));
+    let mut lines = transcript(200);
+    lines[195] = "edited".to_string();
+    assert_eq!(output_bytes(&mut renderer, lines, WIDTH), 41);
+}
+
+#[test]
+fn appending_one_line_to_the_transcript() {
+    let mut renderer = rendered(transcript(200));
+    assert_eq!(output_bytes(&mut renderer, transcript(201), WIDTH), 64);
+}
+
+#[test]
+fn streaming_growth_of_the_last_line() {
+    let mut renderer = rendered(transcript(200));
+    let mut lines = transcript(200);
+    lines.push("partial".to_string());
+    output_bytes(&mut renderer, lines.clone(), WIDTH);
+    lines[200] = "partial reply grows".to_string();
+    assert_eq!(output_bytes(&mut renderer, lines, WIDTH), 50);
+}
+
+#[test]
+fn width_change_clears_and_redraws_the_transcript() {
+    let mut renderer = rendered(transcript(200));
+    assert_eq!(
+        output_bytes(&mut renderer, transcript(200), WIDTH - 20),
+        8715
+    );
+}
+
+#[test]
+fn surface_update_over_a_static_transcript() {
+    let base = transcript(200);
+    let surface = |label: &str| RenderedSurface {
+        lines: (0..5).map(|row| format!("{label} {row}")).collect(),
+        row: 4,
+        col: 10,
+        width: 40,
+    };
+    let composite = |label: &str| {
+        composite_surfaces(
+            base.clone(),
+            &[surface(label)],
+            WIDTH,
+            HEIGHT,
+            base.len(),
+            is_image_line,
+        )
+    };
+
+    let mut renderer = DiffRenderer::new();
+    let mut gate = OutputGate::new();
+    gate.extend(renderer.render(Frame::from(composite("first")), WIDTH, HEIGHT, false, true));
+    gate.clear();
+    gate.extend(renderer.render(Frame::from(composite("second")), WIDTH, HEIGHT, false, true));
+    assert_eq!(gate.encoded_len(), 640);
+}
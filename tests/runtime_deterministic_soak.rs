@@ -157,8 +157,10 @@ impl Component for ProbeComponent {
                 key_id, event_type, ..
             } => format!("key:{key_id}:{event_type:?}"),
             InputEvent::Paste { text, .. } => format!("paste:{text}"),
+            InputEvent::FilesDropped { paths, .. } => format!("files:{paths:?}"),
             InputEvent::Resize { columns, rows } => format!("resize:{columns}x{rows}"),
             InputEvent::UnknownRaw { raw } => format!("raw:{raw}"),
+            InputEvent::Composition { phase, text } => format!("ime:{phase:?}:{text}"),
         };
         self.state
             .events
@@ -141,6 +141,7 @@ fn toast_options() -> SurfaceOptions {
             max_height: Some(SurfaceSizeValue::percent(100.0)),
             ..Default::default()
         },
+        min_update_interval: None,
     }
 }
 
@@ -186,6 +187,7 @@ fn small_terminal_two_pass_allocation_clamps_late_lanes_to_zero_budget() {
                 max_height: Some(SurfaceSizeValue::percent(100.0)),
                 ..Default::default()
             },
+            min_update_interval: None,
         }),
     );
 
@@ -266,6 +268,7 @@ fn resize_recomputes_surface_budget_deterministically() {
                 max_height: Some(SurfaceSizeValue::percent(50.0)),
                 ..Default::default()
             },
+            min_update_interval: None,
         }),
     );
     runtime.run_once();
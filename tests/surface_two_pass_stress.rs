@@ -224,6 +224,7 @@ fn toast_options(input_policy: SurfaceInputPolicy) -> SurfaceOptions {
             max_height: Some(SurfaceSizeValue::percent(100.0)),
             ..Default::default()
         },
+        min_update_interval: None,
     }
 }
 
@@ -281,6 +282,7 @@ fn run_allocation_stress_snapshot() -> AllocationStressSnapshot {
                 max_height: Some(SurfaceSizeValue::percent(100.0)),
                 ..Default::default()
             },
+            min_update_interval: None,
         }),
     );
 